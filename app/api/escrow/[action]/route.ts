@@ -195,8 +195,8 @@ export async function GET(
       const onChainEscrow = await client.getEscrow(buyer, requestId)
 
       if (onChainEscrow) {
-        const status = AnchorEscrowClient.getStatusString(onChainEscrow.status)
-        
+        const status = onChainEscrow.status
+
         return NextResponse.json({
           success: true,
           escrow: {
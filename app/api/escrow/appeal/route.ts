@@ -0,0 +1,107 @@
+/**
+ * Build unsigned appeal transaction
+ * The losing party appeals a platform ruling and posts the appeal bond
+ */
+
+import { NextRequest, NextResponse } from 'next/server'
+import { Connection, PublicKey, Transaction } from '@solana/web3.js'
+import { AnchorEscrowClient, ANCHOR_CONFIG } from '@/lib/anchor-escrow-client'
+import { TOKEN_PROGRAM_ID, getAssociatedTokenAddress } from '@solana/spl-token'
+
+// Minimal wallet interface for building transactions
+interface Wallet {
+  publicKey: PublicKey
+  signTransaction<T extends Transaction>(tx: T): Promise<T>
+  signAllTransactions<T extends Transaction>(txs: T[]): Promise<T[]>
+}
+
+class NodeWallet implements Wallet {
+  constructor(readonly payer: { publicKey: PublicKey }) {}
+
+  async signTransaction<T extends Transaction>(tx: T): Promise<T> {
+    return tx
+  }
+
+  async signAllTransactions<T extends Transaction>(txs: T[]): Promise<T[]> {
+    return txs
+  }
+
+  get publicKey(): PublicKey {
+    return this.payer.publicKey
+  }
+}
+
+export async function POST(request: NextRequest) {
+  try {
+    const body = await request.json()
+    const { appellantPublicKey, buyerPublicKey, requestId } = body
+
+    // Validate parameters
+    if (!appellantPublicKey || !buyerPublicKey || !requestId) {
+      return NextResponse.json(
+        { error: 'Missing required fields' },
+        { status: 400 }
+      )
+    }
+
+    // Create connection and client
+    const connection = new Connection(ANCHOR_CONFIG.DEVNET_RPC, 'confirmed')
+    const appellantPubkey = new PublicKey(appellantPublicKey)
+    const buyerPubkey = new PublicKey(buyerPublicKey)
+    const signerPubkey = appellantPubkey
+
+    // Create a temporary wallet for building transaction
+    const tempWallet = new NodeWallet({ publicKey: appellantPubkey }) as any
+    const client = new AnchorEscrowClient(connection, tempWallet)
+
+    // Get escrow info
+    const [escrowPda] = client.getEscrowPDA(buyerPubkey, requestId)
+
+    // Get token accounts
+    const escrowTokenAccount = await getAssociatedTokenAddress(
+      ANCHOR_CONFIG.USDC_MINT,
+      escrowPda,
+      true
+    )
+    const appellantTokenAccount = await client.ensureTokenAccount(appellantPubkey)
+
+    // Build the appeal instruction
+    const tx = new Transaction()
+
+    const appealIx = await (client as any).program.methods
+      .appeal()
+      .accounts({
+        escrow: escrowPda,
+        appellant: appellantPubkey,
+        escrowTokenAccount,
+        appellantTokenAccount,
+        mint: ANCHOR_CONFIG.USDC_MINT,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .instruction()
+
+    tx.add(appealIx)
+
+    // Get recent blockhash
+    const { blockhash } = await connection.getLatestBlockhash()
+    tx.recentBlockhash = blockhash
+    tx.feePayer = signerPubkey
+
+    // Serialize transaction
+    const serializedTx = tx.serialize({
+      requireAllSignatures: false,
+      verifySignatures: false,
+    })
+
+    return NextResponse.json({
+      transaction: serializedTx.toString('base64'),
+      escrowPda: escrowPda.toBase58(),
+    })
+  } catch (error: any) {
+    console.error('Build appeal transaction error:', error)
+    return NextResponse.json(
+      { error: error.message || 'Failed to build transaction' },
+      { status: 500 }
+    )
+  }
+}
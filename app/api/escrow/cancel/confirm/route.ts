@@ -50,24 +50,13 @@ export async function POST(request: NextRequest) {
       )
     }
 
-    // Update escrow status to cancelled
-    await prisma.$transaction([
-      // Update escrow status
-      prisma.escrow.update({
-        where: { escrowPda },
-        data: {
-          status: 'cancelled',
-        },
-      }),
-
-      // Update request status back to pending
-      prisma.dataRequest.update({
-        where: { id: requestId },
-        data: {
-          status: 'pending',
-        },
-      }),
-    ])
+    // Cancellation waits out the grace period; execute-cancel refunds the buyer
+    await prisma.escrow.update({
+      where: { escrowPda },
+      data: {
+        status: 'pending_cancel',
+      },
+    })
 
     return NextResponse.json({
       success: true,
@@ -1,6 +1,6 @@
 /**
  * Build unsigned cancel transaction
- * Buyer cancels order and gets full refund
+ * Buyer requests cancellation; the provider can contest it during the grace period
  */
 
 import { NextRequest, NextResponse } from 'next/server'
@@ -114,7 +114,8 @@ export async function POST(request: NextRequest) {
       .createEscrow(
         amountLamports,
         requestId,
-        proposalId
+        proposalId,
+        null // couponCode
       )
       .accounts({
         buyer,
@@ -123,6 +124,7 @@ export async function POST(request: NextRequest) {
         escrow: escrowPda,
         buyerTokenAccount,
         escrowTokenAccount,
+        mint: ANCHOR_CONFIG.USDC_MINT,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: new PublicKey('11111111111111111111111111111111'),
       })
@@ -8,7 +8,16 @@
 import { useState } from 'react'
 import { PublicKey } from '@solana/web3.js'
 import { useWallet } from '@solana/wallet-adapter-react'
-import { CheckCircle, XCircle, Clock, AlertTriangle, Package, DollarSign } from 'lucide-react'
+import {
+  CheckCircle,
+  XCircle,
+  Clock,
+  AlertTriangle,
+  Package,
+  DollarSign,
+  Gavel,
+  Scale,
+} from 'lucide-react'
 import { toast } from 'sonner'
 
 interface EscrowStatusProps {
@@ -60,6 +69,31 @@ const statusConfig = {
     color: 'bg-red-100 text-red-800',
     icon: XCircle,
   },
+  pending_cancel: {
+    label: 'Cancel Pending',
+    color: 'bg-red-50 text-red-700',
+    icon: Clock,
+  },
+  rejected: {
+    label: 'Rejected',
+    color: 'bg-pink-100 text-pink-800',
+    icon: XCircle,
+  },
+  escalated: {
+    label: 'With Jury',
+    color: 'bg-indigo-100 text-indigo-800',
+    icon: Scale,
+  },
+  ruled: {
+    label: 'Ruled',
+    color: 'bg-amber-100 text-amber-800',
+    icon: Gavel,
+  },
+  appealed: {
+    label: 'Appealed',
+    color: 'bg-indigo-50 text-indigo-700',
+    icon: Scale,
+  },
 }
 
 export default function EscrowStatus({
@@ -279,7 +313,7 @@ export default function EscrowStatus({
 
       let errorMessage = err.message || 'Failed to confirm release'
 
-      if (err.message?.includes('0x1770') || err.message?.includes('InvalidStatus')) {
+      if (err.message?.includes('0x17ca') || err.message?.includes('NotDelivered')) {
         errorMessage = 'Cannot confirm: Escrow must be in "Delivered" status. Please wait for the provider to mark delivery first.'
       }
 
@@ -365,10 +399,10 @@ export default function EscrowStatus({
         onStatusChange()
       }
 
-      toast.success('Order Cancelled Successfully!', {
+      toast.success('Cancellation Requested!', {
         description: (
           <div className="space-y-2">
-            <p className="text-xs">Full refund issued to buyer</p>
+            <p className="text-xs">The provider can contest it during the grace period</p>
             <p className="font-mono text-xs">{signature}</p>
             <a
               href={confirmData.explorerUrl}
@@ -387,7 +421,7 @@ export default function EscrowStatus({
 
       let errorMessage = err.message || 'Failed to cancel order'
 
-      if (err.message?.includes('0x1770') || err.message?.includes('InvalidStatus')) {
+      if (err.message?.includes('0x17c9') || err.message?.includes('NotFunded')) {
         errorMessage = 'Cannot cancel: Escrow must be in "Funded" status. Once delivered, you can only raise a dispute.'
       }
 
@@ -496,7 +530,7 @@ export default function EscrowStatus({
       // Check for specific error codes
       let errorMessage = err.message || 'Failed to raise dispute'
 
-      if (err.message?.includes('0x1770') || err.message?.includes('InvalidStatus')) {
+      if (err.message?.includes('0x17ca') || err.message?.includes('NotDelivered')) {
         errorMessage = 'Cannot raise dispute: Escrow must be in "Delivered" status. Please wait for the provider to mark delivery first.'
       }
 
@@ -614,6 +648,155 @@ export default function EscrowStatus({
     }
   }
 
+  // Build, sign, send and confirm a transaction for the later-stage actions
+  const submitAction = async ({
+    action,
+    body,
+    successTitle,
+    successText,
+    errorTitle,
+  }: {
+    action: string
+    body: Record<string, unknown>
+    successTitle: string
+    successText: string
+    errorTitle: string
+  }) => {
+    if (!wallet.publicKey || !wallet.signTransaction) {
+      setError('Please connect your wallet first')
+      toast.error('Wallet Not Connected', {
+        description: 'Please connect your wallet first',
+      })
+      return
+    }
+
+    setLoading(true)
+    setError(null)
+
+    try {
+      // Step 1: Build transaction on backend
+      const buildResponse = await fetch(`/api/escrow/${action}`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ ...body, requestId }),
+      })
+
+      const buildData = await buildResponse.json()
+
+      if (!buildResponse.ok) {
+        throw new Error(buildData.error || 'Failed to build transaction')
+      }
+
+      // Step 2: Deserialize and sign transaction
+      const { Transaction, Connection } = await import('@solana/web3.js')
+      const tx = Transaction.from(Buffer.from(buildData.transaction, 'base64'))
+
+      const signedTx = await wallet.signTransaction(tx)
+
+      // Step 3: Send transaction
+      const connection = new Connection('https://api.devnet.solana.com', 'confirmed')
+
+      const signature = await connection.sendRawTransaction(signedTx.serialize())
+
+      // Wait for confirmation
+      const latestBlockhash = await connection.getLatestBlockhash()
+      await connection.confirmTransaction({
+        signature,
+        blockhash: latestBlockhash.blockhash,
+        lastValidBlockHeight: latestBlockhash.lastValidBlockHeight,
+      }, 'confirmed')
+
+      // Step 4: Confirm on backend
+      const confirmResponse = await fetch(`/api/escrow/${action}/confirm`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({
+          signature,
+          escrowPda: buildData.escrowPda,
+          requestId,
+        }),
+      })
+
+      const confirmData = await confirmResponse.json()
+
+      if (!confirmResponse.ok) {
+        throw new Error(confirmData.error || 'Failed to confirm transaction')
+      }
+
+      // Success
+      if (onStatusChange) {
+        onStatusChange()
+      }
+
+      toast.success(successTitle, {
+        description: (
+          <div className="space-y-2">
+            <p className="text-xs">{successText}</p>
+            <p className="font-mono text-xs">{signature}</p>
+            <a
+              href={confirmData.explorerUrl}
+              target="_blank"
+              rel="noopener noreferrer"
+              className="text-primary hover:underline text-xs"
+            >
+              View on Solana Explorer →
+            </a>
+          </div>
+        ),
+        duration: 6000,
+      })
+    } catch (err: any) {
+      console.error(`${action} error:`, err)
+      setError(err.message || 'Transaction failed')
+      toast.error(errorTitle, {
+        description: err.message || 'Transaction failed',
+        duration: 8000,
+      })
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  // Contest a pending cancellation (provider)
+  const handleContestCancel = () =>
+    submitAction({
+      action: 'contest-cancel',
+      body: { providerPublicKey: wallet.publicKey?.toBase58(), buyerPublicKey: buyer },
+      successTitle: 'Cancellation Contested',
+      successText: 'The escrow is now disputed and goes to platform arbitration',
+      errorTitle: 'Failed to Contest Cancellation',
+    })
+
+  // Execute a pending cancellation after the grace period (buyer)
+  const handleExecuteCancel = () =>
+    submitAction({
+      action: 'execute-cancel',
+      body: { buyerPublicKey: buyer },
+      successTitle: 'Order Cancelled Successfully!',
+      successText: 'Full refund issued to buyer',
+      errorTitle: 'Failed to Execute Cancellation',
+    })
+
+  // Claim a refund after the redelivery window of a rejected delivery (buyer)
+  const handleExpireRejection = () =>
+    submitAction({
+      action: 'expire-rejection',
+      body: { buyerPublicKey: buyer },
+      successTitle: 'Refund Claimed Successfully!',
+      successText: 'Full refund issued to buyer',
+      errorTitle: 'Failed to Claim Refund',
+    })
+
+  // Appeal the platform ruling (losing party)
+  const handleAppeal = () =>
+    submitAction({
+      action: 'appeal',
+      body: { appellantPublicKey: wallet.publicKey?.toBase58(), buyerPublicKey: buyer },
+      successTitle: 'Appeal Filed Successfully!',
+      successText: 'The dispute will be referred to the jury',
+      errorTitle: 'Failed to Appeal',
+    })
+
   return (
     <div className="bg-white rounded-lg shadow-md p-6">
       {/* Status header */}
@@ -661,7 +844,7 @@ export default function EscrowStatus({
       {status === 'funded' && isBuyer && (
         <div className="mb-4 p-3 bg-blue-50 border border-blue-200 rounded-lg">
           <p className="text-xs text-blue-800">
-            <strong>⏳ Waiting for Provider:</strong> The provider needs to mark delivery first. You can cancel anytime before delivery.
+            <strong>⏳ Waiting for Provider:</strong> The provider needs to mark delivery first. You can request cancellation anytime before delivery.
           </p>
         </div>
       )}
@@ -682,6 +865,22 @@ export default function EscrowStatus({
         </div>
       )}
 
+      {status === 'pending_cancel' && isProvider && (
+        <div className="mb-4 p-3 bg-red-50 border border-red-200 rounded-lg">
+          <p className="text-xs text-red-800">
+            <strong>⚠️ Cancellation Requested:</strong> The buyer asked to cancel. Contest it before the grace period ends if you have already started the work.
+          </p>
+        </div>
+      )}
+
+      {status === 'rejected' && isProvider && (
+        <div className="mb-4 p-3 bg-pink-50 border border-pink-200 rounded-lg">
+          <p className="text-xs text-pink-800">
+            <strong>📦 Delivery Rejected:</strong> Redeliver the data before the redelivery window closes, or the buyer can claim a full refund.
+          </p>
+        </div>
+      )}
+
       {/* Action buttons */}
       <div className="space-y-2">
         {/* Provider: Mark delivered */}
@@ -713,7 +912,7 @@ export default function EscrowStatus({
             disabled={loading}
             className="w-full px-4 py-2 bg-red-600 text-white rounded-lg hover:bg-red-700 disabled:opacity-50 disabled:cursor-not-allowed"
           >
-            {loading ? 'Processing...' : 'Cancel Order (Full Refund)'}
+            {loading ? 'Processing...' : 'Request Cancellation'}
           </button>
         )}
 
@@ -749,6 +948,61 @@ export default function EscrowStatus({
           </div>
         )}
 
+        {/* Provider: Contest cancellation */}
+        {isProvider && status === 'pending_cancel' && (
+          <button
+            onClick={handleContestCancel}
+            disabled={loading}
+            className="w-full px-4 py-2 bg-yellow-600 text-white rounded-lg hover:bg-yellow-700 disabled:opacity-50 disabled:cursor-not-allowed"
+          >
+            {loading ? 'Processing...' : 'Contest Cancellation'}
+          </button>
+        )}
+
+        {/* Buyer: Execute cancellation after the grace period */}
+        {isBuyer && status === 'pending_cancel' && (
+          <button
+            onClick={handleExecuteCancel}
+            disabled={loading}
+            className="w-full px-4 py-2 bg-red-600 text-white rounded-lg hover:bg-red-700 disabled:opacity-50 disabled:cursor-not-allowed"
+          >
+            {loading ? 'Processing...' : 'Execute Cancellation (Full Refund)'}
+          </button>
+        )}
+
+        {/* Provider: Redeliver */}
+        {isProvider && status === 'rejected' && (
+          <button
+            onClick={handleMarkDelivered}
+            disabled={loading}
+            className="w-full px-4 py-2 bg-purple-600 text-white rounded-lg hover:bg-purple-700 disabled:opacity-50 disabled:cursor-not-allowed"
+          >
+            {loading ? 'Processing...' : 'Redeliver'}
+          </button>
+        )}
+
+        {/* Buyer: Claim refund after the redelivery window */}
+        {isBuyer && status === 'rejected' && (
+          <button
+            onClick={handleExpireRejection}
+            disabled={loading}
+            className="w-full px-4 py-2 bg-orange-600 text-white rounded-lg hover:bg-orange-700 disabled:opacity-50 disabled:cursor-not-allowed"
+          >
+            {loading ? 'Processing...' : 'Claim Refund'}
+          </button>
+        )}
+
+        {/* Buyer/Provider: Appeal the ruling */}
+        {(isBuyer || isProvider) && status === 'ruled' && (
+          <button
+            onClick={handleAppeal}
+            disabled={loading}
+            className="w-full px-4 py-2 bg-indigo-600 text-white rounded-lg hover:bg-indigo-700 disabled:opacity-50 disabled:cursor-not-allowed"
+          >
+            {loading ? 'Processing...' : 'Appeal Ruling'}
+          </button>
+        )}
+
         {/* Completed/Refunded/Cancelled - No actions */}
         {(status === 'completed' || status === 'refunded' || status === 'cancelled') && (
           <div className="text-center text-gray-500 text-sm py-2">
@@ -788,6 +1042,33 @@ export default function EscrowStatus({
           {status === 'cancelled' && (
             <p>• Order cancelled, full refund issued to buyer</p>
           )}
+          {status === 'pending_cancel' && (
+            <>
+              <p>• Buyer requested cancellation, waiting out the grace period</p>
+              <p>• Provider can contest the cancellation and open a dispute</p>
+              <p>• Buyer can execute the cancellation after the grace period for a full refund</p>
+            </>
+          )}
+          {status === 'rejected' && (
+            <>
+              <p>• Buyer rejected the delivery</p>
+              <p>• Provider can redeliver before the redelivery window closes</p>
+              <p>• Buyer can claim a full refund after the window closes</p>
+            </>
+          )}
+          {status === 'ruled' && (
+            <>
+              <p>• Platform has ruled on the dispute, the appeal window is open</p>
+              <p>• The losing party can appeal by posting an appeal bond</p>
+              <p>• The ruling is executed once the appeal window closes</p>
+            </>
+          )}
+          {status === 'appealed' && (
+            <p>• Ruling appealed, waiting for the jury to take the case</p>
+          )}
+          {status === 'escalated' && (
+            <p>• The jury is voting on the dispute, the verdict is executed on-chain</p>
+          )}
         </div>
       </div>
     </div>
@@ -0,0 +1,3092 @@
+//! DataNexus CLI
+//!
+//! 运维和早期合作方无需网页端即可操作托管：
+//! `escrow create|recreate|save-template|bridge|deliver|confirm|dispute|refund|resolve|show` 和
+//! `config init|update|settings|init-insurance|claim-insurance|init-fee-ledger|settle-fees|`
+//! `issue-coupon|revoke-coupon|set-admin`，
+//! 按查询计费的 `meter open|top-up|stop|sign-receipt|draw|close|show`
+//! 、订阅的 `subscription create-plan|update-plan|subscribe|lapse|check`
+//! 以及需求竞价的 `auction open|bid|select|award|withdraw-budget|refund|show`；
+//! 平台管理员可用 `--ledger` 以硬件钱包签名
+
+use std::path::{Path, PathBuf};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::associated_token::{get_associated_token_address, spl_associated_token_account};
+use anchor_spl::token;
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use datanexus_client::auctions;
+use datanexus_client::chunks::{self, ChunkTree};
+use datanexus_client::coupons::{self, Discount};
+use datanexus_client::fetch;
+use datanexus_client::instructions::{self, CreateEscrowParams};
+use datanexus_client::metering;
+use datanexus_client::pda;
+use datanexus_client::submit::{Idempotency, Submission};
+use datanexus_client::subscriptions;
+use datanexus_client::transaction::{PriorityFee, DEFAULT_FEE_PERCENTILE};
+use datanexus_client::{lending, shadow_drive, wormhole, ClientError, EscrowClient};
+use datanexus_escrow::{
+    Escrow, EscrowStatus, PenaltyRecipient, ProtocolSettings, StorageRef, TemplateTerms,
+    BADGE_VERIFIED, COMPLIANCE_BUYER, COMPLIANCE_PROVIDER,
+};
+use solana_derivation_path::DerivationPath;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_remote_wallet::locator::Locator;
+use solana_remote_wallet::remote_keypair::generate_remote_keypair;
+use solana_remote_wallet::remote_wallet::maybe_wallet_manager;
+use solana_signature::Signature;
+use solana_signer::Signer;
+
+/// 默认数据块大小：1 MiB
+const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+
+/// 提交零知识证明的计算单元上限
+const PROOF_COMPUTE_UNITS: u32 = 1_400_000;
+
+#[derive(Parser)]
+#[command(
+    name = "datanexus-cli",
+    version,
+    about = "DataNexus escrow command line tool"
+)]
+struct Cli {
+    /// RPC 节点地址
+    #[arg(
+        long,
+        global = true,
+        env = "DATANEXUS_RPC_URL",
+        default_value = "https://api.devnet.solana.com"
+    )]
+    url: String,
+
+    /// 签名钱包（默认 ~/.config/solana/id.json）
+    #[arg(long, global = true, env = "DATANEXUS_KEYPAIR")]
+    keypair: Option<PathBuf>,
+
+    /// 使用 Ledger 签名（优先于 `--keypair`），可指定设备和账户，如 `--ledger=usb://ledger?key=1`
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "usb://ledger"
+    )]
+    ledger: Option<String>,
+
+    /// 优先费：`auto` 按最近区块估算，或固定单价（micro-lamports / 计算单元）
+    #[arg(long, global = true, env = "DATANEXUS_PRIORITY_FEE", value_parser = parse_priority_fee)]
+    priority_fee: Option<PriorityFee>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 托管操作
+    #[command(subcommand)]
+    Escrow(EscrowCommand),
+
+    /// 平台配置
+    #[command(subcommand)]
+    Config(ConfigCommand),
+
+    /// 按查询计费
+    #[command(subcommand)]
+    Meter(MeterCommand),
+
+    /// 订阅计划和访问凭证
+    #[command(subcommand)]
+    Subscription(SubscriptionCommand),
+
+    /// 需求竞价
+    #[command(subcommand)]
+    Auction(AuctionCommand),
+}
+
+#[derive(Subcommand)]
+enum EscrowCommand {
+    /// 买家创建并充值托管（`--no-fund` 只记录条款）
+    Create(CreateArgs),
+
+    /// 保存可复用的托管条款模板（签名钱包为所有者），创建托管时用 `escrow create --template` 引用
+    SaveTemplate {
+        /// 模板编号
+        id: u64,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 限定提供商（默认不限）
+        #[arg(long)]
+        provider: Option<Pubkey>,
+
+        /// 平台费减免比例（基点，仅平台模板）
+        #[arg(long, conflicts_with = "rebate")]
+        fee_bps: Option<u16>,
+
+        /// 从平台费中返还买家的固定金额（6 位小数，仅平台模板）
+        #[arg(long)]
+        rebate: Option<u64>,
+
+        /// 交付期限，从创建托管时起算（秒，0 为不设）
+        #[arg(long, default_value_t = 0)]
+        delivery_window: i64,
+
+        /// 逾期交付的固定罚金（6 位小数），从提供商份额中扣除
+        #[arg(long, default_value_t = 0)]
+        late_penalty: u64,
+
+        /// 罚金转入国库（默认退还买家）
+        #[arg(long)]
+        penalty_to_platform: bool,
+
+        /// 里程碑数，托管金额按里程碑均分，通过 `escrow deliver-units` 逐个交付
+        #[arg(long, default_value_t = 0)]
+        milestones: u64,
+
+        /// 提供商钱包路径，模板带交付期限时须提供商共同签名
+        #[arg(long)]
+        provider_keypair: Option<PathBuf>,
+    },
+
+    /// 删除模板（仅所有者）
+    CloseTemplate {
+        /// 模板编号
+        id: u64,
+    },
+
+    /// 按已完成的托管再次购买（复制提供商、金额和交付期限），默认同时充值
+    Recreate {
+        /// 已完成的原托管地址
+        previous: Pubkey,
+
+        /// 新托管的需求 ID
+        #[arg(long)]
+        request_id: String,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 只创建托管条款，稍后通过 `escrow fund` 充值
+        #[arg(long)]
+        no_fund: bool,
+    },
+
+    /// 买家为 Created 状态的托管充值
+    Fund {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 付款代币账户（默认为买家的关联代币账户）
+        #[arg(long)]
+        from: Option<Pubkey>,
+    },
+
+    /// 兑付 Wormhole 跨链转账，为其他链上的买家创建托管（买家须签名）
+    Bridge {
+        /// 核心桥已验证的 `PostedVAA` 账户
+        #[arg(long)]
+        vaa: Pubkey,
+
+        /// 买家钱包路径（须与 VAA 载荷中的买家一致）
+        #[arg(long)]
+        buyer_keypair: PathBuf,
+    },
+
+    /// 提供商标记已交付
+    Deliver {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 交付内容哈希（64 位十六进制）
+        #[arg(long)]
+        hash: String,
+
+        /// 预言机公钥（买家要求交付证明时必填）
+        #[arg(long, requires = "oracle_signature")]
+        oracle: Option<Pubkey>,
+
+        /// 预言机对 `托管地址 || 交付哈希` 的签名（base58）
+        #[arg(long, requires = "oracle")]
+        oracle_signature: Option<Signature>,
+
+        /// 交付的数据文件，按 `--chunk-size` 分块记录数据块清单
+        #[arg(long)]
+        data: Option<PathBuf>,
+
+        /// 数据块大小（字节）
+        #[arg(long, default_value_t = DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+    },
+
+    /// 买家拒收交付，等待提供商重新交付
+    Reject {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 拒收说明（链上只记录哈希，说明本身须另行发给提供商）
+        #[arg(long)]
+        reason: String,
+    },
+
+    /// 拒收后提供商逾期未重新交付，退款给买家（任何人可调用）
+    ExpireRejection {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+    },
+
+    /// 买家或提供商把协商消息（或条款修订文件）的哈希记入链上协商记录
+    Message {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 消息或条款文件，链上只记录其 SHA-256 哈希
+        file: PathBuf,
+    },
+
+    /// 买家追加提前交付奖励金（交付前）
+    Bonus {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 奖励金（6 位小数）
+        #[arg(long)]
+        amount: u64,
+
+        /// 奖励截止时间（Unix 时间戳），此前交付才付给提供商
+        #[arg(long)]
+        deadline: i64,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+    },
+
+    /// 托管未经确认就结束时退还奖励金（任何人可调用）
+    ReclaimBonus {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+    },
+
+    /// 回收超过存活时间仍未注资的托管，租金退还买家（任何人可调用）
+    ReclaimStale {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种（默认为托管记录的铸币，版本 5 之前创建的托管须指定）
+        #[arg(long)]
+        mint: Option<Pubkey>,
+    },
+
+    /// 买家要求交付须经预言机证明（交付前）
+    RequireAttestation {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 预言机签名公钥
+        #[arg(long)]
+        oracle: Pubkey,
+    },
+
+    /// 买家要求提供商用零知识证明证实数据集属性（交付前）
+    RequireProof {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// Groth16/Plonk 验证程序
+        #[arg(long)]
+        verifier: Pubkey,
+
+        /// 声明的行数
+        #[arg(long)]
+        row_count: u64,
+
+        /// 声明的表结构哈希（64 位十六进制）
+        #[arg(long)]
+        schema_hash: String,
+    },
+
+    /// 提供商设置访问凭证有效期（交付前，托管须配置访问凭证铸币）
+    AccessWindow {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 有效期（秒），从释放时起算
+        #[arg(long)]
+        duration: i64,
+    },
+
+    /// 买卖双方约定交付截止时间和逾期罚金（交付前，须同时持有提供商密钥）
+    Deadline {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 交付截止时间（Unix 时间戳）
+        #[arg(long)]
+        at: i64,
+
+        /// 逾期交付的固定罚金（6 位小数），从提供商份额中扣除
+        #[arg(long, default_value_t = 0)]
+        late_penalty: u64,
+
+        /// 罚金转入国库（默认退还买家）
+        #[arg(long)]
+        penalty_to_platform: bool,
+
+        /// 提供商钱包路径
+        #[arg(long)]
+        provider_keypair: PathBuf,
+    },
+
+    /// 提供商对未充值的托管报价并设定有效期（须同时持有提供商密钥）
+    Quote {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 报价（最小单位），写入托管金额
+        #[arg(long)]
+        price: u64,
+
+        /// 报价有效期（Unix 时间戳），过期后不能充值
+        #[arg(long)]
+        expires_at: i64,
+
+        /// 提供商钱包路径
+        #[arg(long)]
+        provider_keypair: PathBuf,
+    },
+
+    /// 买家把未充值的托管设为按件计价（单价 × 数量须等于托管金额）
+    UnitPricing {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 单价（最小单位）
+        #[arg(long)]
+        unit_price: u64,
+
+        /// 购买件数
+        #[arg(long)]
+        quantity: u64,
+    },
+
+    /// 提供商交付一批数据并按件结算（买家共同签名）
+    DeliverUnits {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 本批交付件数
+        #[arg(long)]
+        units: u64,
+
+        /// 托管代币铸币
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 买家钱包路径
+        #[arg(long)]
+        buyer_keypair: PathBuf,
+    },
+
+    /// 买家把未充值的托管设为按下载计费（提供商共同签名指定网关）
+    DownloadCap {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 下载上限
+        #[arg(long)]
+        downloads: u64,
+
+        /// 记录下载的网关地址
+        #[arg(long)]
+        gateway: Pubkey,
+
+        /// 提供商钱包路径
+        #[arg(long)]
+        provider_keypair: PathBuf,
+    },
+
+    /// 以当前钱包作为网关记录一次下载并结算
+    ConsumeDownload {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 托管代币铸币
+        #[arg(long)]
+        mint: Pubkey,
+    },
+
+    /// 以当前钱包（提供商）开启可验证销售记录
+    InitSalesHistory,
+
+    /// 查看提供商销售记录的销售数和 Merkle 根
+    SalesHistory {
+        /// 提供商地址
+        provider: Pubkey,
+    },
+
+    /// 查看平台签发给提供商的徽章
+    Badge {
+        /// 提供商地址
+        provider: Pubkey,
+
+        /// 徽章类型
+        #[arg(long, default_value_t = BADGE_VERIFIED)]
+        badge_type: u8,
+    },
+
+    /// 访问期满后冻结买家的访问凭证账户（任何人可调用）
+    RevokeAccess {
+        /// 托管地址
+        escrow: Pubkey,
+    },
+
+    /// 提供商提交数据集属性的零知识证明（交付后）
+    Prove {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 证明文件（验证程序要求的二进制格式）
+        #[arg(long)]
+        proof: PathBuf,
+    },
+
+    /// 买家要求提供商交付加密的解密密钥（交付前）
+    RequireKey {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 买家 X25519 公钥（64 位十六进制）
+        #[arg(long)]
+        buyer_key: String,
+    },
+
+    /// 提供商提交用买家 X25519 公钥加密的解密密钥（交付前）
+    DeliverKey {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 加密后的解密密钥（十六进制，最多 128 字节）
+        #[arg(long)]
+        encrypted_key: String,
+    },
+
+    /// 提供商承诺交付内容 `sha256(存储 URI || salt)`（交付前）
+    Commit {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 存储 URI：`ipfs://<CIDv1>`、`ar://<交易 ID>` 或 `shdw://<存储账户>/<对象路径>`
+        #[arg(long)]
+        uri: String,
+
+        /// salt（64 位十六进制），揭示时须相同
+        #[arg(long)]
+        salt: String,
+    },
+
+    /// 提供商揭示承诺的存储 URI 和 salt（交付前）
+    Reveal {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 存储 URI：`ipfs://<CIDv1>`、`ar://<交易 ID>` 或 `shdw://<存储账户>/<对象路径>`
+        #[arg(long)]
+        uri: String,
+
+        /// 承诺时使用的 salt（64 位十六进制）
+        #[arg(long)]
+        salt: String,
+    },
+
+    /// 核对揭示的 Shadow Drive 交付：存储账户、对象存在且内容哈希与交付哈希一致
+    VerifyDelivery {
+        /// 托管地址
+        escrow: Pubkey,
+    },
+
+    /// 买家开启收益模式，托管资金存入 Solend 储备（交付前）
+    Earn {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// Solend 储备，存入代币须为托管代币
+        #[arg(long)]
+        reserve: Pubkey,
+    },
+
+    /// 赎回收益模式存款，收益按配置分给买家和国库（confirm/refund/resolve 会自动执行）
+    WithdrawYield {
+        /// 托管地址
+        escrow: Pubkey,
+    },
+
+    /// 买家确认并释放资金
+    Confirm {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// Pyth 价格更新账户（托管记录过美元估值或按美元计价时必填）
+        #[arg(long)]
+        price_update: Option<Pubkey>,
+
+        /// 交付质量评分（1–5），记入信誉程序的提供商信誉
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=5))]
+        score: Option<u8>,
+
+        /// 额外给提供商的小费（6 位小数），从买家代币账户直接转出，不收平台费
+        #[arg(long)]
+        tip: Option<u64>,
+    },
+
+    /// 买家按 Pyth 价格记录非 USDC 托管的美元价值（交付前）
+    Value {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 托管代币
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// Pyth 价格源 ID（64 位十六进制）
+        #[arg(long)]
+        feed_id: String,
+
+        /// Pyth 价格更新账户
+        #[arg(long)]
+        price_update: Pubkey,
+    },
+
+    /// 把托管改为按美元计价，释放时按 Pyth 价格折算代币数量（交付前，须同时持有提供商密钥）
+    PriceUsd {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 托管代币
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 成交价（美分），托管中的代币数量为支付上限
+        #[arg(long)]
+        usd_cents: u64,
+
+        /// Pyth 价格源 ID（64 位十六进制）
+        #[arg(long)]
+        feed_id: String,
+
+        /// Pyth 价格更新账户
+        #[arg(long)]
+        price_update: Pubkey,
+
+        /// 提供商钱包路径
+        #[arg(long)]
+        provider_keypair: PathBuf,
+    },
+
+    /// 约定释放时以另一种代币（如 USDC）给提供商结算（注资前，须同时持有提供商密钥）
+    SettleIn {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 托管代币
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 提供商的结算代币
+        #[arg(long)]
+        settle_mint: Pubkey,
+
+        /// 整笔托管金额至少应兑换到的结算代币数量（滑点下限）
+        #[arg(long)]
+        min_out: u64,
+
+        /// 提供商钱包路径
+        #[arg(long)]
+        provider_keypair: PathBuf,
+    },
+
+    /// 买家发起争议
+    Dispute {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种（争议保证金从买家该币种账户扣除）
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 收到的数据文件，按块争议时用于生成 Merkle 证明
+        #[arg(long, requires = "chunk")]
+        data: Option<PathBuf>,
+
+        /// 数据块大小（字节），须与交付时相同
+        #[arg(long, default_value_t = DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+
+        /// 有问题的数据块序号（可重复）
+        #[arg(long, requires = "data")]
+        chunk: Vec<u32>,
+    },
+
+    /// 买家抽查交付的数据块，提供商须在 24 小时内响应（确认前）
+    Challenge {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 随机抽取的块数（最多 8）
+        #[arg(long, default_value_t = 4)]
+        sample: usize,
+
+        /// 指定抽查的数据块序号（可重复，代替随机抽取）
+        #[arg(long, conflicts_with = "sample")]
+        chunk: Vec<u32>,
+    },
+
+    /// 提供商用交付的数据文件响应抽查
+    Respond {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 交付的数据文件
+        #[arg(long)]
+        data: PathBuf,
+
+        /// 数据块大小（字节），须与交付时相同
+        #[arg(long, default_value_t = DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+    },
+
+    /// 抽查逾期未响应时全额退款给买家（任何人可调用）
+    ExpireChallenge {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+    },
+
+    /// 平台按争议结果退款给买家
+    Refund {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+    },
+
+    /// 平台因受限的类别或司法辖区组合阻止释放并退款给买家
+    Block {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 阻止原因（记录在链上，最多 64 字节）
+        #[arg(long)]
+        reason: String,
+    },
+
+    /// 平台作出可上诉的裁决（配置了上诉期时使用）
+    Rule {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 裁决退款给买家（默认释放给提供商）
+        #[arg(long)]
+        refund_to_buyer: bool,
+    },
+
+    /// 败诉方在上诉期内上诉
+    Appeal {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种（上诉保证金从上诉方该币种账户扣除）
+        #[arg(long)]
+        mint: Pubkey,
+    },
+
+    /// 平台裁决争议（Ruled 状态下按已有裁决执行）
+    Resolve {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// Pyth 价格更新账户（按美元计价的托管释放给提供商时必填）
+        #[arg(long)]
+        price_update: Option<Pubkey>,
+
+        /// 退款给买家（默认释放给提供商）
+        #[arg(long)]
+        refund_to_buyer: bool,
+    },
+
+    /// 平台按有效数据块比例拆分争议
+    ResolveSplit {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// Pyth 价格更新账户（按美元计价的托管释放给提供商时必填）
+        #[arg(long)]
+        price_update: Option<Pubkey>,
+
+        /// 判定无效的争议块序号（可重复）
+        #[arg(long)]
+        invalid_chunk: Vec<u32>,
+    },
+
+    /// 查看托管账户
+    Show {
+        /// 托管地址
+        escrow: Pubkey,
+    },
+}
+
+#[derive(Args)]
+struct CreateArgs {
+    /// 提供商钱包
+    #[arg(long)]
+    provider: Pubkey,
+
+    /// 支付币种（使用模板时取模板的铸币）
+    #[arg(long, required_unless_present = "template")]
+    mint: Option<Pubkey>,
+
+    /// 托管金额（最小单位，USDC 为 6 位小数）
+    #[arg(long)]
+    amount: u64,
+
+    /// 需求 ID
+    #[arg(long)]
+    request_id: String,
+
+    /// 提案 ID
+    #[arg(long)]
+    proposal_id: String,
+
+    /// 只创建托管条款，稍后通过 `escrow fund` 充值
+    #[arg(long)]
+    no_fund: bool,
+
+    /// 平台签发的优惠码，释放时减免平台费
+    #[arg(long)]
+    coupon: Option<String>,
+
+    /// 按模板创建（模板编号），沿用模板的铸币、平台费减免、交付期限和里程碑
+    #[arg(long, conflicts_with = "mint")]
+    template: Option<u64>,
+
+    /// 模板所有者（默认为买家自己，平台模板传配置管理员）
+    #[arg(long, requires = "template")]
+    template_owner: Option<Pubkey>,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// 初始化平台配置（签名钱包须为程序升级权限，并成为管理员）
+    Init {
+        /// 国库代币账户
+        #[arg(long)]
+        treasury: Pubkey,
+
+        /// 质押奖励池代币账户
+        #[arg(long)]
+        rewards_pool: Pubkey,
+
+        /// 国库分成（基点）
+        #[arg(long, default_value_t = 8000)]
+        treasury_bps: u16,
+
+        /// 奖励池分成（基点）
+        #[arg(long, default_value_t = 2000)]
+        rewards_pool_bps: u16,
+    },
+
+    /// 排队更新国库、奖励池及分成权重（仅管理员），公示期后执行
+    Update {
+        /// 国库代币账户
+        #[arg(long)]
+        treasury: Pubkey,
+
+        /// 质押奖励池代币账户
+        #[arg(long)]
+        rewards_pool: Pubkey,
+
+        /// 国库分成（基点）
+        #[arg(long)]
+        treasury_bps: u16,
+
+        /// 奖励池分成（基点）
+        #[arg(long)]
+        rewards_pool_bps: u16,
+    },
+
+    /// 排队更新协议策略参数（仅管理员），公示期后执行
+    Settings {
+        /// 创建托管时要求提供商已注册
+        #[arg(long)]
+        require_registered_provider: bool,
+
+        /// 平台费划入保险池的比例（基点）
+        #[arg(long, default_value_t = 0)]
+        insurance_bps: u16,
+
+        /// 买家发起争议需缴纳的保证金（6 位小数）
+        #[arg(long, default_value_t = 0)]
+        dispute_bond: u64,
+
+        /// 托管金额达到该值时交付前要求提供商保证金（0 为不要求）
+        #[arg(long, default_value_t = 0)]
+        collateral_threshold: u64,
+
+        /// 要求的保证金占托管金额的比例（基点）
+        #[arg(long, default_value_t = 0)]
+        collateral_bps: u16,
+
+        /// 平台裁决后的上诉期（秒，0 为裁决立即生效）
+        #[arg(long, default_value_t = 0)]
+        appeal_window: i64,
+
+        /// 上诉需缴纳的保证金（6 位小数）
+        #[arg(long, default_value_t = 0)]
+        appeal_bond: u64,
+
+        /// 收益模式的收益中归买家的比例（基点），其余归国库
+        #[arg(long, default_value_t = 0)]
+        yield_buyer_bps: u16,
+
+        /// 逾期交付每天从提供商份额中退还买家的比例（基点）
+        #[arg(long, default_value_t = 0)]
+        late_refund_bps_per_day: u16,
+
+        /// 逾期退还比例上限（基点）
+        #[arg(long, default_value_t = 0)]
+        late_refund_cap_bps: u16,
+
+        /// 未注资托管可被回收前的存活时间（秒，0 为不回收）
+        #[arg(long, default_value_t = 0)]
+        stale_escrow_ttl: i64,
+
+        /// 托管金额达到该值时要求提供商持有徽章（0 为不要求）
+        #[arg(long, default_value_t = 0)]
+        badge_threshold: u64,
+
+        /// 要求的徽章类型
+        #[arg(long, default_value_t = BADGE_VERIFIED)]
+        required_badge: u8,
+
+        /// 每个买家未进入终态的托管数上限（0 为不限）
+        #[arg(long, default_value_t = 0)]
+        max_open_escrows: u64,
+
+        /// 每个买家每天可创建的托管数上限（0 为不限）
+        #[arg(long, default_value_t = 0)]
+        max_daily_escrows: u64,
+
+        /// 美元计价托管释放时价格偏离计价时价格的上限（基点，0 为不限）
+        #[arg(long, default_value_t = 0)]
+        max_price_deviation_bps: u16,
+
+        /// 平台费整笔转入共享费用金库，由 `settle-fees` 定期拆分
+        #[arg(long)]
+        fee_ledger: bool,
+
+        /// 每笔资金转出前附加 SPL Memo 对账备注
+        #[arg(long)]
+        memo_transfers: bool,
+
+        /// 创建和释放时要求买家持有合规证明
+        #[arg(long)]
+        compliance_buyer: bool,
+
+        /// 创建和释放时要求提供商持有合规证明
+        #[arg(long)]
+        compliance_provider: bool,
+
+        /// 合规证明的签发凭证（Solana Attestation Service）
+        #[arg(long)]
+        attestation_credential: Option<Pubkey>,
+
+        /// 合规证明的结构定义
+        #[arg(long)]
+        attestation_schema: Option<Pubkey>,
+    },
+
+    /// 创建保险池代币账户（仅管理员）
+    InitInsurance {
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+    },
+
+    /// 向提供商签发或续期徽章（仅管理员）
+    IssueBadge {
+        /// 提供商地址
+        provider: Pubkey,
+
+        /// 徽章类型
+        #[arg(long, default_value_t = BADGE_VERIFIED)]
+        badge_type: u8,
+
+        /// 过期时间（Unix 时间戳，0 为长期有效）
+        #[arg(long, default_value_t = 0)]
+        expiry: i64,
+    },
+
+    /// 撤销提供商徽章（仅管理员）
+    RevokeBadge {
+        /// 提供商地址
+        provider: Pubkey,
+
+        /// 徽章类型
+        #[arg(long, default_value_t = BADGE_VERIFIED)]
+        badge_type: u8,
+    },
+
+    /// 将地址列入黑名单（仅管理员），不能再创建托管或标记交付
+    Blacklist {
+        /// 钱包地址
+        subject: Pubkey,
+
+        /// 处置记录（链上只记录哈希）
+        #[arg(long)]
+        reason: String,
+    },
+
+    /// 将地址移出黑名单（仅管理员）
+    Unblacklist {
+        /// 钱包地址
+        subject: Pubkey,
+    },
+
+    /// 签发平台优惠券（仅管理员），创建托管时用 `escrow create --coupon` 兑换
+    IssueCoupon {
+        /// 优惠码（链上只保存其哈希）
+        code: String,
+
+        /// 平台费减免比例（基点）
+        #[arg(long, conflicts_with = "rebate", required_unless_present = "rebate")]
+        fee_bps: Option<u16>,
+
+        /// 从平台费中返还买家的固定金额（6 位小数）
+        #[arg(long)]
+        rebate: Option<u64>,
+
+        /// 限定买家（默认不限）
+        #[arg(long)]
+        buyer: Option<Pubkey>,
+
+        /// 兑换截止时间（Unix 时间戳）
+        #[arg(long)]
+        expires_at: i64,
+    },
+
+    /// 撤销尚未使用的优惠券（仅管理员）
+    RevokeCoupon {
+        /// 优惠码
+        code: String,
+    },
+
+    /// 从保险池赔付已结算托管的买家（仅管理员）
+    ClaimInsurance {
+        /// 托管地址
+        escrow: Pubkey,
+
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 赔付金额（6 位小数）
+        #[arg(long)]
+        amount: u64,
+    },
+
+    /// 创建费用账本和共享费用金库（仅管理员）
+    InitFeeLedger {
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+    },
+
+    /// 把费用金库中累计的平台费拆分给保险池、国库和奖励池
+    SettleFees {
+        /// 支付币种
+        #[arg(long)]
+        mint: Pubkey,
+    },
+
+    /// 移交管理员（仅管理员），移交给治理程序后配置只能通过提案修改
+    SetAdmin {
+        /// 新管理员
+        new_admin: Pubkey,
+    },
+
+    /// 执行已过公示期的配置变更（任何人都可以调用）
+    ExecuteChange,
+
+    /// 撤销尚未执行的配置变更（仅管理员）
+    CancelChange,
+}
+
+#[derive(Subcommand)]
+enum MeterCommand {
+    /// 买家为提供商开通计量账户
+    Open {
+        /// 提供商钱包地址
+        provider: Pubkey,
+
+        /// 计费币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 每次查询单价（最小单位）
+        #[arg(long)]
+        price_per_query: u64,
+
+        /// 收据签名公钥（默认为买家钱包）
+        #[arg(long)]
+        receipt_signer: Option<Pubkey>,
+    },
+
+    /// 买家向计量余额充值
+    TopUp {
+        /// 提供商钱包地址
+        provider: Pubkey,
+
+        /// 充值金额（最小单位）
+        #[arg(long)]
+        amount: u64,
+    },
+
+    /// 买家停止计量，结算期满后可关闭并取回余额
+    Stop {
+        /// 提供商钱包地址
+        provider: Pubkey,
+    },
+
+    /// 用收据签名密钥对累计查询数签名，输出 base58 签名交给提供商
+    SignReceipt {
+        /// 计量账户地址
+        meter: Pubkey,
+
+        /// 累计查询数
+        #[arg(long)]
+        cumulative_queries: u64,
+
+        /// 收据签名密钥路径
+        #[arg(long)]
+        receipt_keypair: PathBuf,
+    },
+
+    /// 提供商凭买家签名的用量收据扣款
+    Draw {
+        /// 计量账户地址
+        meter: Pubkey,
+
+        /// 收据中的累计查询数
+        #[arg(long)]
+        cumulative_queries: u64,
+
+        /// 收据签名（base58）
+        #[arg(long)]
+        signature: Signature,
+    },
+
+    /// 结算期满后买家取回余额并关闭计量账户
+    Close {
+        /// 提供商钱包地址
+        provider: Pubkey,
+    },
+
+    /// 查看计量账户
+    Show {
+        /// 计量账户地址
+        meter: Pubkey,
+    },
+}
+
+#[derive(Subcommand)]
+enum SubscriptionCommand {
+    /// 提供商创建订阅计划和不可转让访问凭证铸币
+    CreatePlan {
+        /// 计划编号（提供商自定义）
+        plan_id: u64,
+
+        /// 付费币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 每个周期的费用（最小单位）
+        #[arg(long)]
+        price: u64,
+
+        /// 周期长度（秒）
+        #[arg(long)]
+        period: i64,
+    },
+
+    /// 提供商调整订阅计划
+    UpdatePlan {
+        /// 计划地址
+        plan: Pubkey,
+
+        /// 每个周期的费用（最小单位）
+        #[arg(long)]
+        price: u64,
+
+        /// 周期长度（秒）
+        #[arg(long)]
+        period: i64,
+
+        /// 停止接受新订阅和续费
+        #[arg(long)]
+        inactive: bool,
+    },
+
+    /// 订阅或续费一个周期
+    Subscribe {
+        /// 计划地址
+        plan: Pubkey,
+    },
+
+    /// 冻结到期未续费的访问凭证（任何人可调用）
+    Lapse {
+        /// 计划地址
+        plan: Pubkey,
+
+        /// 订阅者钱包地址
+        subscriber: Pubkey,
+    },
+
+    /// 查看订阅并检查访问凭证是否有效
+    Check {
+        /// 计划地址
+        plan: Pubkey,
+
+        /// 订阅者钱包地址
+        subscriber: Pubkey,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuctionCommand {
+    /// 买家为自己发布的需求开启竞价
+    Open {
+        /// 需求 ID
+        request_id: String,
+
+        /// 保证金币种
+        #[arg(long)]
+        mint: Pubkey,
+
+        /// 每个出价须缴纳的保证金（最小单位）
+        #[arg(long, default_value_t = 0)]
+        bid_bond: u64,
+
+        /// 竞价截止时间（Unix 时间戳，不晚于需求截止时间）
+        #[arg(long)]
+        closes_at: i64,
+
+        /// 截止后任何人都可以按最低报价选定中标者
+        #[arg(long)]
+        auto_select: bool,
+
+        /// 反向竞价：开启时锁定全部预算，最低报价中标，授标时自动充值并退回差额
+        #[arg(long, conflicts_with = "auto_select")]
+        reverse: bool,
+    },
+
+    /// 提供商缴纳保证金出价
+    Bid {
+        /// 需求地址
+        request: Pubkey,
+
+        /// 提案 ID
+        #[arg(long)]
+        proposal_id: String,
+
+        /// 报价（最小单位，不超过需求预算）
+        #[arg(long)]
+        price: u64,
+
+        /// 条款哈希（十六进制）
+        #[arg(long)]
+        terms_hash: String,
+    },
+
+    /// 竞价截止后选定中标提案
+    Select {
+        /// 需求地址
+        request: Pubkey,
+
+        /// 中标提案地址（默认为最低报价）
+        #[arg(long)]
+        proposal: Option<Pubkey>,
+    },
+
+    /// 买家授标，按中标报价创建托管（之后用 `escrow fund` 充值）
+    Award {
+        /// 需求地址
+        request: Pubkey,
+    },
+
+    /// 反向竞价无人出价或期满未授标时取回锁定的预算
+    WithdrawBudget {
+        /// 需求地址
+        request: Pubkey,
+    },
+
+    /// 退还出价保证金（任何人可调用）
+    Refund {
+        /// 提案地址
+        proposal: Pubkey,
+    },
+
+    /// 查看竞价
+    Show {
+        /// 需求地址
+        request: Pubkey,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let signer: Box<dyn Signer> = match cli.ledger {
+        Some(locator) => load_ledger(&locator)?,
+        None => Box::new(load_keypair(cli.keypair)?),
+    };
+    let client = EscrowClient::new_with_signer(cli.url, signer)
+        .with_priority_fee(cli.priority_fee.unwrap_or_default());
+
+    match cli.command {
+        Command::Escrow(command) => run_escrow(&client, command),
+        Command::Config(command) => run_config(&client, command),
+        Command::Meter(command) => run_meter(&client, command),
+        Command::Subscription(command) => run_subscription(&client, command),
+        Command::Auction(command) => run_auction(&client, command),
+    }
+}
+
+fn run_escrow(client: &EscrowClient, command: EscrowCommand) -> Result<()> {
+    match command {
+        EscrowCommand::Create(args) => {
+            let buyer = client.payer();
+            let config = client.fetch_config()?;
+            let escrow = EscrowClient::escrow_address(&buyer, &args.request_id);
+            let provider_sequence = client.provider_sequence(&args.provider)?;
+            let (buyer_attestation, provider_attestation) =
+                instructions::compliance_attestations(&config, &buyer, &args.provider);
+
+            // 按模板创建时托管铸币取模板铸币
+            let template = match args.template {
+                Some(template_id) => {
+                    let owner = args.template_owner.unwrap_or(buyer);
+                    let template = fetch::fetch_template(client.rpc(), &owner, template_id)?;
+                    Some((pda::find_template_address(&owner, template_id).0, template))
+                }
+                None => None,
+            };
+            let mint = match &template {
+                Some((_, template)) => template.mint,
+                None => args.mint.context("--mint is required")?,
+            };
+
+            let params = CreateEscrowParams {
+                buyer,
+                provider: args.provider,
+                platform: config.admin,
+                amount: args.amount,
+                mint,
+                request_id: args.request_id,
+                proposal_id: args.proposal_id,
+                provider_sequence,
+                buyer_attestation,
+                provider_attestation,
+                coupon: args.coupon.as_ref().map(|code| {
+                    coupons::find_coupon_address(&config.admin, &coupons::code_hash(code)).0
+                }),
+                coupon_code: args.coupon,
+                provider_badge: instructions::provider_badge(&config, &args.provider, args.amount),
+            };
+
+            // 模板创建时一并创建托管代币账户
+            let mut ixs = match template {
+                Some((template_address, template)) => vec![
+                    instructions::create_escrow_from_template(params, template_address, &template),
+                ],
+                None => vec![
+                    instructions::create_escrow_token_account(buyer, escrow, mint),
+                    instructions::create_escrow(params),
+                ],
+            };
+            if !args.no_fund {
+                ixs.push(instructions::fund_escrow(escrow, buyer, mint, None));
+            }
+            let builder = client.builder().instructions(ixs);
+
+            // 托管 PDA 已存在说明之前的请求已经上链，重试时不会重复创建
+            match client.submit(&builder, &[], Idempotency::AccountExists(escrow))? {
+                Submission::Confirmed(signature) => {
+                    println!("Escrow created: {}", escrow);
+                    println!("Signature: {}", signature);
+                }
+                Submission::AlreadyApplied => {
+                    println!("Escrow already exists: {}", escrow);
+                }
+            }
+        }
+        EscrowCommand::SaveTemplate {
+            id,
+            mint,
+            provider,
+            fee_bps,
+            rebate,
+            delivery_window,
+            late_penalty,
+            penalty_to_platform,
+            milestones,
+            provider_keypair,
+        } => {
+            let discount = match (fee_bps, rebate) {
+                (Some(bps), _) => Some(Discount::FeeBps(bps)),
+                (None, Some(amount)) => Some(Discount::Rebate(amount)),
+                (None, None) => None,
+            };
+            let penalty_recipient = if penalty_to_platform {
+                PenaltyRecipient::Platform
+            } else {
+                PenaltyRecipient::Buyer
+            };
+            let provider_keypair = provider_keypair
+                .map(|path| load_keypair(Some(path)))
+                .transpose()?;
+            let provider = provider.or(provider_keypair.as_ref().map(|keypair| keypair.pubkey()));
+            let signers: Vec<&Keypair> = provider_keypair.iter().collect();
+            let terms = TemplateTerms {
+                provider,
+                discount,
+                delivery_window,
+                late_penalty,
+                penalty_recipient,
+                milestones,
+            };
+            let signature = client.send(
+                &[instructions::save_template(client.payer(), id, mint, terms)],
+                &signers,
+            )?;
+
+            println!(
+                "Template saved: {}",
+                pda::find_template_address(&client.payer(), id).0
+            );
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::CloseTemplate { id } => {
+            let signature =
+                client.send(&[instructions::close_template(client.payer(), id)], &[])?;
+
+            println!(
+                "Template closed: {}",
+                pda::find_template_address(&client.payer(), id).0
+            );
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Recreate {
+            previous,
+            request_id,
+            mint,
+            no_fund,
+        } => {
+            let buyer = client.payer();
+            let account = client.fetch_escrow(&previous)?;
+            let escrow = EscrowClient::escrow_address(&buyer, &request_id);
+            let with_delivery_terms = match fetch::fetch_delivery_terms(client.rpc(), &previous) {
+                Ok(_) => true,
+                Err(ClientError::AccountNotFound(_)) => false,
+                Err(err) => return Err(err.into()),
+            };
+
+            let mut ixs = vec![
+                instructions::create_escrow_token_account(buyer, escrow, mint),
+                instructions::recreate_escrow(
+                    previous,
+                    &account,
+                    &client.fetch_config()?,
+                    request_id,
+                    client.provider_sequence(&account.provider)?,
+                    with_delivery_terms,
+                ),
+            ];
+            if !no_fund {
+                ixs.push(instructions::fund_escrow(escrow, buyer, mint, None));
+            }
+            let builder = client.builder().instructions(ixs);
+
+            match client.submit(&builder, &[], Idempotency::AccountExists(escrow))? {
+                Submission::Confirmed(signature) => {
+                    println!("Escrow recreated: {}", escrow);
+                    println!("Previous escrow: {}", previous);
+                    println!("Signature: {}", signature);
+                }
+                Submission::AlreadyApplied => {
+                    println!("Escrow already exists: {}", escrow);
+                }
+            }
+        }
+        EscrowCommand::Fund { escrow, mint, from } => {
+            let signature = client.send(
+                &[instructions::fund_escrow(
+                    escrow,
+                    client.payer(),
+                    mint,
+                    from,
+                )],
+                &[],
+            )?;
+
+            println!("Escrow funded: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Bridge { vaa, buyer_keypair } => {
+            let posted_vaa = wormhole::fetch_posted_vaa(client.rpc(), &vaa)?;
+            let payment = posted_vaa
+                .payment()
+                .ok_or_else(|| anyhow!("VAA has no escrow payment payload"))?;
+            let buyer = load_keypair(Some(buyer_keypair))?;
+            if buyer.pubkey() != payment.buyer {
+                bail!(
+                    "buyer keypair does not match the VAA payload buyer {}",
+                    payment.buyer
+                );
+            }
+            let escrow = EscrowClient::escrow_address(&payment.buyer, &payment.request_id);
+            let provider_sequence = client.provider_sequence(&payment.provider)?;
+
+            let builder = client
+                .builder()
+                .instructions(wormhole::create_escrow_from_bridge(
+                    client.payer(),
+                    vaa,
+                    &posted_vaa,
+                    &client.fetch_config()?,
+                    provider_sequence,
+                )?);
+            match client.submit(&builder, &[&buyer], Idempotency::AccountExists(escrow))? {
+                Submission::Confirmed(signature) => {
+                    println!("Escrow created: {}", escrow);
+                    println!("Buyer: {}", payment.buyer);
+                    println!("Source chain: {}", posted_vaa.emitter_chain);
+                    println!("Signature: {}", signature);
+                }
+                Submission::AlreadyApplied => {
+                    println!("Escrow already exists: {}", escrow);
+                }
+            }
+        }
+        EscrowCommand::Deliver {
+            escrow,
+            hash,
+            oracle,
+            oracle_signature,
+            data,
+            chunk_size,
+        } => {
+            let delivery_hash = parse_hash(&hash, "delivery hash")?;
+            let chunks = data
+                .map(|data| chunk_tree(&data, chunk_size))
+                .transpose()?
+                .map(|tree| tree.manifest());
+            let mut ixs = Vec::new();
+            if let (Some(oracle), Some(oracle_signature)) = (oracle, oracle_signature) {
+                ixs.push(instructions::delivery_attestation(
+                    oracle,
+                    oracle_signature.into(),
+                    &escrow,
+                    &delivery_hash,
+                ));
+            }
+            ixs.push(instructions::mark_delivered(
+                escrow,
+                client.payer(),
+                delivery_hash,
+                chunks,
+            ));
+            let signature = client.send(&ixs, &[])?;
+
+            println!("Escrow delivered: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Reject { escrow, reason } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let reason_hash = instructions::rejection_reason_hash(&reason);
+            let signature = client.send(
+                &[instructions::reject_delivery(escrow, &account, reason_hash)],
+                &[],
+            )?;
+
+            let rejection = fetch::fetch_rejection(client.rpc(), &escrow)?;
+            println!("Delivery rejected: {}", escrow);
+            println!("Redelivery deadline: {}", rejection.deadline);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Message { escrow, file } => {
+            let message = std::fs::read(&file)
+                .with_context(|| format!("failed to read {}", file.display()))?;
+            let message_hash = instructions::message_hash(&message);
+            let signature = client.send(
+                &[instructions::log_message(
+                    escrow,
+                    client.payer(),
+                    message_hash,
+                )],
+                &[],
+            )?;
+
+            let message_log = fetch::fetch_message_log(client.rpc(), &escrow)?;
+            println!("Message logged: {}", escrow);
+            println!(
+                "Hash: {}",
+                message_hash
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<String>()
+            );
+            println!("Entries: {}", message_log.entries.len());
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Bonus {
+            escrow,
+            amount,
+            deadline,
+            mint,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::fund_bonus(
+                    escrow, &account, mint, amount, deadline,
+                )],
+                &[],
+            )?;
+
+            println!("Bonus funded: {}", escrow);
+            println!("Amount: {}", amount);
+            println!("Deadline: {}", deadline);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::ReclaimBonus { escrow, mint } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let signature =
+                client.send(&[instructions::reclaim_bonus(escrow, &account, mint)], &[])?;
+
+            println!("Bonus returned to buyer: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::ReclaimStale { escrow, mint } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let mint = account
+                .mint()
+                .or(mint)
+                .context("escrow has no recorded mint, pass --mint")?;
+
+            // 托管代币账户有余额时先退给买家，买家的关联代币账户不存在则一并创建
+            let vault = get_associated_token_address(&escrow, &mint);
+            let sweep = client
+                .rpc()
+                .get_token_account_balance(&vault)
+                .is_ok_and(|balance| balance.amount != "0");
+            let mut ixs = Vec::new();
+            if sweep {
+                ixs.push(
+                    spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                        &client.payer(),
+                        &account.buyer,
+                        &mint,
+                        &token::ID,
+                    ),
+                );
+            }
+            ixs.push(instructions::reclaim_stale(escrow, &account, mint, sweep));
+            let signature = client.send(&ixs, &[])?;
+
+            println!("Stale escrow reclaimed: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::ExpireRejection { escrow, mint } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::expire_rejection(escrow, &account, mint)],
+                &[],
+            )?;
+
+            println!("Redelivery expired, escrow refunded: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::RequireAttestation { escrow, oracle } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::require_delivery_attestation(
+                    escrow, &account, oracle,
+                )],
+                &[],
+            )?;
+
+            println!("Delivery attestation required: {}", escrow);
+            println!("Oracle: {}", oracle);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::RequireProof {
+            escrow,
+            verifier,
+            row_count,
+            schema_hash,
+        } => {
+            let schema_hash = parse_hash(&schema_hash, "schema hash")?;
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::require_dataset_proof(
+                    escrow,
+                    &account,
+                    verifier,
+                    row_count,
+                    schema_hash,
+                )],
+                &[],
+            )?;
+
+            println!("Dataset proof required: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::AccessWindow { escrow, duration } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::set_access_window(escrow, &account, duration)],
+                &[],
+            )?;
+
+            println!("Access window set: {}", escrow);
+            println!("Duration: {} seconds", duration);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Deadline {
+            escrow,
+            at,
+            late_penalty,
+            penalty_to_platform,
+            provider_keypair,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let provider = load_keypair(Some(provider_keypair))?;
+            let penalty_recipient = if penalty_to_platform {
+                PenaltyRecipient::Platform
+            } else {
+                PenaltyRecipient::Buyer
+            };
+            let signature = client.send(
+                &[instructions::set_delivery_deadline(
+                    escrow,
+                    &account,
+                    at,
+                    late_penalty,
+                    penalty_recipient,
+                )],
+                &[&provider],
+            )?;
+
+            println!("Delivery deadline set: {}", escrow);
+            println!("Deadline: {}", at);
+            println!("Late penalty: {} ({:?})", late_penalty, penalty_recipient);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Quote {
+            escrow,
+            price,
+            expires_at,
+            provider_keypair,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let provider = load_keypair(Some(provider_keypair))?;
+            let signature = client.send(
+                &[instructions::quote_price(
+                    escrow, &account, price, expires_at,
+                )],
+                &[&provider],
+            )?;
+
+            println!("Price quoted: {}", escrow);
+            println!("Price: {}, expires at: {}", price, expires_at);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::UnitPricing {
+            escrow,
+            unit_price,
+            quantity,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::set_unit_pricing(
+                    escrow, &account, unit_price, quantity,
+                )],
+                &[],
+            )?;
+
+            println!("Unit pricing set: {}", escrow);
+            println!("Unit price: {}, quantity: {}", unit_price, quantity);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::DeliverUnits {
+            escrow,
+            units,
+            mint,
+            buyer_keypair,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let buyer = load_keypair(Some(buyer_keypair))?;
+            let signature = client.send(
+                &[instructions::deliver_units(
+                    escrow,
+                    &account,
+                    &client.fetch_config()?,
+                    mint,
+                    units,
+                )],
+                &[&buyer],
+            )?;
+            let pricing = fetch::fetch_units(client.rpc(), &escrow)?;
+
+            println!("Units delivered: {}", escrow);
+            println!("Delivered: {}/{}", pricing.delivered, pricing.quantity);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::DownloadCap {
+            escrow,
+            downloads,
+            gateway,
+            provider_keypair,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let provider = load_keypair(Some(provider_keypair))?;
+            let signature = client.send(
+                &[instructions::set_download_cap(
+                    escrow, &account, downloads, gateway,
+                )],
+                &[&provider],
+            )?;
+
+            println!("Download cap set: {}", escrow);
+            println!("Downloads: {}, gateway: {}", downloads, gateway);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::ConsumeDownload { escrow, mint } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::consume_download(
+                    escrow,
+                    &account,
+                    &client.fetch_config()?,
+                    mint,
+                    client.payer(),
+                )],
+                &[],
+            )?;
+            let downloads = fetch::fetch_downloads(client.rpc(), &escrow)?;
+
+            println!("Download consumed: {}", escrow);
+            println!(
+                "Downloads remaining: {}/{}",
+                downloads.downloads_remaining, downloads.total
+            );
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::InitSalesHistory => {
+            let provider = client.payer();
+            let signature = client.send(&[instructions::init_sales_history(provider)], &[])?;
+
+            println!(
+                "Sales history opened: {}",
+                pda::find_sales_history_address(&provider).0
+            );
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::SalesHistory { provider } => {
+            let history = fetch::fetch_sales_history(client.rpc(), &provider)?;
+
+            println!("Provider: {}", history.provider);
+            println!("Sales:    {}", history.count);
+            println!(
+                "Root:     {}",
+                history
+                    .root
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<String>()
+            );
+        }
+        EscrowCommand::Badge {
+            provider,
+            badge_type,
+        } => {
+            let badge = fetch::fetch_badge(client.rpc(), &provider, badge_type)?;
+
+            println!("Provider:   {}", badge.provider);
+            println!("Type:       {}", badge.badge_type);
+            println!("Issued at:  {}", badge.issued_at);
+            if badge.expires_at == 0 {
+                println!("Expires at: never");
+            } else {
+                println!("Expires at: {}", badge.expires_at);
+            }
+        }
+        EscrowCommand::RevokeAccess { escrow } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let access_window = fetch::fetch_access_window(client.rpc(), &escrow)?;
+            let access_mint = account.access_mint().context("escrow has no access mint")?;
+            // 访问凭证铸币可能属于 SPL Token 或 Token-2022
+            let access_token_program = client.rpc().get_account(&access_mint)?.owner;
+            let signature = client.send(
+                &[instructions::revoke_expired_access(
+                    escrow,
+                    &account,
+                    &access_window,
+                    access_token_program,
+                )],
+                &[],
+            )?;
+
+            println!("Access revoked: {}", escrow);
+            println!("Token account: {}", access_window.token_account);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Prove { escrow, proof } => {
+            let proof = std::fs::read(&proof)
+                .with_context(|| format!("failed to read {}", proof.display()))?;
+            let dataset_proof = fetch::fetch_dataset_proof(client.rpc(), &escrow)?;
+            let account = client.fetch_escrow(&escrow)?;
+            // 验证程序的 pairing 校验需要接近单笔交易上限的计算单元
+            let builder = client
+                .builder()
+                .compute_unit_limit(PROOF_COMPUTE_UNITS)
+                .instruction(instructions::submit_dataset_proof(
+                    escrow,
+                    &account,
+                    dataset_proof.verifier,
+                    proof,
+                ));
+            if let Submission::Confirmed(signature) =
+                client.submit(&builder, &[], Idempotency::None)?
+            {
+                println!("Dataset proof verified: {}", escrow);
+                println!("Signature: {}", signature);
+            }
+        }
+        EscrowCommand::RequireKey { escrow, buyer_key } => {
+            let buyer_key = parse_hash(&buyer_key, "buyer key")?;
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::require_key_delivery(
+                    escrow, &account, buyer_key,
+                )],
+                &[],
+            )?;
+
+            println!("Key delivery required: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::DeliverKey {
+            escrow,
+            encrypted_key,
+        } => {
+            let encrypted_key = parse_hex(&encrypted_key, "encrypted key")?;
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::deliver_key(escrow, &account, encrypted_key)],
+                &[],
+            )?;
+
+            println!("Decryption key delivered: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Commit { escrow, uri, salt } => {
+            let salt = parse_hash(&salt, "salt")?;
+            let commitment = instructions::delivery_commitment(&parse_storage(&uri)?, &salt);
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::commit_delivery(escrow, &account, commitment)],
+                &[],
+            )?;
+
+            println!("Delivery committed: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Reveal { escrow, uri, salt } => {
+            let salt = parse_hash(&salt, "salt")?;
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::reveal_delivery(
+                    escrow,
+                    &account,
+                    parse_storage(&uri)?,
+                    salt,
+                )],
+                &[],
+            )?;
+
+            println!("Delivery revealed: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::VerifyDelivery { escrow } => {
+            let delivery = shadow_drive::verify_delivery(client.rpc(), &escrow)?;
+
+            println!("Shadow Drive delivery verified: {}", escrow);
+            println!("Storage account: {}", delivery.storage_account);
+            println!("Object: {}", delivery.url);
+            println!("Size: {} bytes", delivery.size);
+        }
+        EscrowCommand::Confirm {
+            escrow,
+            mint,
+            price_update,
+            score,
+            tip,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let config = client.fetch_config()?;
+            let mut ixs = withdraw_yield_first(client, escrow, &account)?;
+            ixs.push(instructions::confirm_and_release(
+                escrow,
+                &account,
+                &config,
+                mint,
+                price_update,
+                score,
+                tip,
+            ));
+            let signature = client.send(&ixs, &[])?;
+
+            println!("Escrow released: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Earn { escrow, reserve } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let reserve = lending::fetch_reserve(client.rpc(), &reserve)?;
+            let signature = client.send(&lending::enable_yield(escrow, &account, &reserve), &[])?;
+
+            println!("Yield enabled: {}", escrow);
+            println!("Reserve: {}", reserve.address);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::WithdrawYield { escrow } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let ixs = withdraw_yield_first(client, escrow, &account)?;
+            if ixs.is_empty() {
+                bail!("escrow {} has no yield position", escrow);
+            }
+            let signature = client.send(&ixs, &[])?;
+
+            println!("Yield withdrawn: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Value {
+            escrow,
+            mint,
+            feed_id,
+            price_update,
+        } => {
+            let feed_id = parse_hash(&feed_id, "feed id")?;
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::record_valuation(
+                    escrow,
+                    &account,
+                    mint,
+                    price_update,
+                    feed_id,
+                )],
+                &[],
+            )?;
+
+            println!("Escrow valued: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::PriceUsd {
+            escrow,
+            mint,
+            usd_cents,
+            feed_id,
+            price_update,
+            provider_keypair,
+        } => {
+            let feed_id = parse_hash(&feed_id, "feed id")?;
+            let account = client.fetch_escrow(&escrow)?;
+            let provider = load_keypair(Some(provider_keypair))?;
+            let signature = client.send(
+                &[instructions::denominate_in_usd(
+                    escrow,
+                    &account,
+                    mint,
+                    price_update,
+                    feed_id,
+                    usd_cents,
+                )],
+                &[&provider],
+            )?;
+
+            println!("Escrow priced in USD: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::SettleIn {
+            escrow,
+            mint,
+            settle_mint,
+            min_out,
+            provider_keypair,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let provider = load_keypair(Some(provider_keypair))?;
+            let signature = client.send(
+                &[instructions::set_settlement_swap(
+                    escrow,
+                    &account,
+                    mint,
+                    settle_mint,
+                    min_out,
+                )],
+                &[&provider],
+            )?;
+
+            println!("Settlement swap set: {}", escrow);
+            println!("Settle mint: {}", settle_mint);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Dispute {
+            escrow,
+            mint,
+            data,
+            chunk_size,
+            chunk,
+        } => {
+            let proofs = match data {
+                Some(data) => {
+                    let tree = chunk_tree(&data, chunk_size)?;
+                    chunk
+                        .into_iter()
+                        .map(|index| {
+                            tree.proof(index)
+                                .ok_or_else(|| anyhow!("chunk {} is out of range", index))
+                        })
+                        .collect::<Result<Vec<_>>>()?
+                }
+                None => Vec::new(),
+            };
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::raise_dispute(escrow, &account, mint, proofs)],
+                &[],
+            )?;
+
+            println!("Escrow disputed: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Challenge {
+            escrow,
+            sample,
+            chunk,
+        } => {
+            let indices = if chunk.is_empty() {
+                let manifest = fetch::fetch_chunk_manifest(client.rpc(), &escrow)?;
+                // 随机种子取自新生成的密钥对（操作系统随机数）
+                let seed: [u8; 32] = Keypair::new().to_bytes()[..32].try_into().unwrap();
+                chunks::sample_indices(&seed, manifest.chunk_count, sample)
+            } else {
+                chunk
+            };
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::challenge(escrow, &account, indices.clone())],
+                &[],
+            )?;
+
+            println!("Chunks challenged: {:?}", indices);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Respond {
+            escrow,
+            data,
+            chunk_size,
+        } => {
+            let challenge = fetch::fetch_challenge(client.rpc(), &escrow)?;
+            let tree = chunk_tree(&data, chunk_size)?;
+            let proofs = challenge
+                .indices
+                .iter()
+                .map(|&index| {
+                    tree.proof(index)
+                        .ok_or_else(|| anyhow!("chunk {} is out of range", index))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::respond_challenge(escrow, &account, proofs)],
+                &[],
+            )?;
+
+            println!("Challenge answered: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::ExpireChallenge { escrow, mint } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::expire_challenge(escrow, &account, mint)],
+                &[],
+            )?;
+
+            println!("Challenge expired, escrow refunded: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Refund { escrow, mint } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let mut ixs = withdraw_yield_first(client, escrow, &account)?;
+            ixs.push(instructions::refund(escrow, &account, mint));
+            let signature = client.send(&ixs, &[])?;
+
+            println!("Escrow refunded: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Block {
+            escrow,
+            mint,
+            reason,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let mut ixs = withdraw_yield_first(client, escrow, &account)?;
+            ixs.push(instructions::block_release(escrow, &account, mint, reason));
+            let signature = client.send(&ixs, &[])?;
+
+            println!("Release blocked, escrow refunded: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Rule {
+            escrow,
+            refund_to_buyer,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::rule_dispute(
+                    escrow,
+                    &account,
+                    client.payer(),
+                    refund_to_buyer,
+                )],
+                &[],
+            )?;
+
+            println!("Dispute ruled, refund to buyer: {}", refund_to_buyer);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Appeal { escrow, mint } => {
+            let signature =
+                client.send(&[instructions::appeal(escrow, client.payer(), mint)], &[])?;
+
+            println!("Ruling appealed: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Resolve {
+            escrow,
+            mint,
+            price_update,
+            refund_to_buyer,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let config = client.fetch_config()?;
+            let mut ixs = withdraw_yield_first(client, escrow, &account)?;
+            ixs.push(instructions::resolve_dispute(
+                escrow,
+                &account,
+                &config,
+                mint,
+                price_update,
+                refund_to_buyer,
+            ));
+            let signature = client.send(&ixs, &[])?;
+
+            if refund_to_buyer {
+                println!("Dispute resolved, refunded to buyer: {}", escrow);
+            } else {
+                println!("Dispute resolved, released to provider: {}", escrow);
+            }
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::ResolveSplit {
+            escrow,
+            mint,
+            price_update,
+            invalid_chunk,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let config = client.fetch_config()?;
+            let mut ixs = withdraw_yield_first(client, escrow, &account)?;
+            ixs.push(instructions::resolve_dispute_split(
+                escrow,
+                &account,
+                &config,
+                mint,
+                price_update,
+                invalid_chunk,
+            ));
+            let signature = client.send(&ixs, &[])?;
+
+            println!("Dispute split by chunks: {}", escrow);
+            println!("Signature: {}", signature);
+        }
+        EscrowCommand::Show { escrow } => {
+            let account = client.fetch_escrow(&escrow)?;
+            print_escrow(&escrow, &account);
+        }
+    }
+
+    Ok(())
+}
+
+fn run_config(client: &EscrowClient, command: ConfigCommand) -> Result<()> {
+    match command {
+        ConfigCommand::Init {
+            treasury,
+            rewards_pool,
+            treasury_bps,
+            rewards_pool_bps,
+        } => {
+            let signature = client.send(
+                &[instructions::initialize_config(
+                    client.payer(),
+                    treasury,
+                    rewards_pool,
+                    treasury_bps,
+                    rewards_pool_bps,
+                )],
+                &[],
+            )?;
+
+            println!("Config initialized, admin: {}", client.payer());
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::Update {
+            treasury,
+            rewards_pool,
+            treasury_bps,
+            rewards_pool_bps,
+        } => {
+            let signature = client.send(
+                &[instructions::update_config(
+                    client.payer(),
+                    client.payer(),
+                    treasury,
+                    rewards_pool,
+                    treasury_bps,
+                    rewards_pool_bps,
+                )],
+                &[],
+            )?;
+
+            print_pending_change(client)?;
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::Settings {
+            require_registered_provider,
+            insurance_bps,
+            dispute_bond,
+            collateral_threshold,
+            collateral_bps,
+            appeal_window,
+            appeal_bond,
+            yield_buyer_bps,
+            late_refund_bps_per_day,
+            late_refund_cap_bps,
+            stale_escrow_ttl,
+            badge_threshold,
+            required_badge,
+            max_open_escrows,
+            max_daily_escrows,
+            max_price_deviation_bps,
+            fee_ledger,
+            memo_transfers,
+            compliance_buyer,
+            compliance_provider,
+            attestation_credential,
+            attestation_schema,
+        } => {
+            let mut compliance = 0;
+            if compliance_buyer {
+                compliance |= COMPLIANCE_BUYER;
+            }
+            if compliance_provider {
+                compliance |= COMPLIANCE_PROVIDER;
+            }
+
+            let settings = ProtocolSettings {
+                require_registered_provider,
+                insurance_bps,
+                dispute_bond,
+                collateral_threshold,
+                collateral_bps,
+                appeal_window,
+                appeal_bond,
+                yield_buyer_bps,
+                memo_transfers,
+                compliance,
+                attestation_credential: attestation_credential.unwrap_or_default(),
+                attestation_schema: attestation_schema.unwrap_or_default(),
+                late_refund_bps_per_day,
+                late_refund_cap_bps,
+                stale_escrow_ttl,
+                badge_threshold,
+                required_badge,
+                max_open_escrows,
+                max_daily_escrows,
+                max_price_deviation_bps,
+                fee_ledger,
+            };
+            let signature = client.send(
+                &[instructions::update_settings(
+                    client.payer(),
+                    client.payer(),
+                    settings,
+                )],
+                &[],
+            )?;
+
+            print_pending_change(client)?;
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::IssueBadge {
+            provider,
+            badge_type,
+            expiry,
+        } => {
+            let signature = client.send(
+                &[instructions::issue_badge(
+                    client.payer(),
+                    client.payer(),
+                    provider,
+                    badge_type,
+                    expiry,
+                )],
+                &[],
+            )?;
+
+            println!(
+                "Badge issued: {}",
+                pda::find_badge_address(&provider, badge_type).0
+            );
+            println!("Provider: {}, type: {}", provider, badge_type);
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::RevokeBadge {
+            provider,
+            badge_type,
+        } => {
+            let signature = client.send(
+                &[instructions::revoke_badge(
+                    client.payer(),
+                    &provider,
+                    badge_type,
+                )],
+                &[],
+            )?;
+
+            println!("Badge revoked: {}", provider);
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::Blacklist { subject, reason } => {
+            let signature = client.send(
+                &[instructions::add_to_blacklist(
+                    client.payer(),
+                    client.payer(),
+                    subject,
+                    instructions::blacklist_reason_hash(&reason),
+                )],
+                &[],
+            )?;
+
+            println!("Blacklisted: {}", subject);
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::Unblacklist { subject } => {
+            let signature = client.send(
+                &[instructions::remove_from_blacklist(
+                    client.payer(),
+                    &subject,
+                )],
+                &[],
+            )?;
+
+            println!("Removed from blacklist: {}", subject);
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::IssueCoupon {
+            code,
+            fee_bps,
+            rebate,
+            buyer,
+            expires_at,
+        } => {
+            let discount = match (fee_bps, rebate) {
+                (Some(bps), _) => Discount::FeeBps(bps),
+                (None, Some(amount)) => Discount::Rebate(amount),
+                (None, None) => bail!("either --fee-bps or --rebate is required"),
+            };
+            let code_hash = coupons::code_hash(&code);
+            let signature = client.send(
+                &[coupons::issue_coupon(
+                    client.payer(),
+                    code_hash,
+                    discount,
+                    buyer,
+                    expires_at,
+                )],
+                &[],
+            )?;
+
+            println!(
+                "Coupon issued: {}",
+                coupons::find_coupon_address(&client.payer(), &code_hash).0
+            );
+            println!("Discount: {:?}", discount);
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::RevokeCoupon { code } => {
+            let code_hash = coupons::code_hash(&code);
+            let signature =
+                client.send(&[coupons::revoke_coupon(client.payer(), &code_hash)], &[])?;
+
+            println!("Coupon revoked: {}", code);
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::InitInsurance { mint } => {
+            let signature = client.send(
+                &[instructions::init_insurance(
+                    client.payer(),
+                    client.payer(),
+                    mint,
+                )],
+                &[],
+            )?;
+
+            println!("Insurance vault initialized");
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::ClaimInsurance {
+            escrow,
+            mint,
+            amount,
+        } => {
+            let account = client.fetch_escrow(&escrow)?;
+            let signature = client.send(
+                &[instructions::claim_insurance(
+                    client.payer(),
+                    client.payer(),
+                    escrow,
+                    &account,
+                    mint,
+                    amount,
+                )],
+                &[],
+            )?;
+
+            println!("Insurance paid to {}: {}", account.buyer, amount);
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::InitFeeLedger { mint } => {
+            let signature = client.send(
+                &[instructions::init_fee_ledger(
+                    client.payer(),
+                    client.payer(),
+                    mint,
+                )],
+                &[],
+            )?;
+
+            println!("Fee ledger initialized");
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::SettleFees { mint } => {
+            let config = client.fetch_config()?;
+            let signature = client.send(&[instructions::settle_fees(&config, mint)], &[])?;
+
+            println!("Fees settled");
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::SetAdmin { new_admin } => {
+            let signature =
+                client.send(&[instructions::set_admin(client.payer(), new_admin)], &[])?;
+
+            println!("Admin transferred to {}", new_admin);
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::ExecuteChange => {
+            let pending = fetch::fetch_pending_config_change(client.rpc())?;
+            let signature =
+                client.send(&[instructions::execute_config_change(pending.payer)], &[])?;
+
+            println!("Config change executed");
+            println!("Signature: {}", signature);
+        }
+        ConfigCommand::CancelChange => {
+            let pending = fetch::fetch_pending_config_change(client.rpc())?;
+            let signature = client.send(
+                &[instructions::cancel_config_change(
+                    client.payer(),
+                    pending.payer,
+                )],
+                &[],
+            )?;
+
+            println!("Config change cancelled");
+            println!("Signature: {}", signature);
+        }
+    }
+
+    Ok(())
+}
+
+/// 打印刚排队的配置变更及其最早执行时间
+/// 托管开启过收益模式时，先赎回存款的指令，放在释放、退款或裁决之前
+fn run_meter(client: &EscrowClient, command: MeterCommand) -> Result<()> {
+    match command {
+        MeterCommand::Open {
+            provider,
+            mint,
+            price_per_query,
+            receipt_signer,
+        } => {
+            let buyer = client.payer();
+            let signature = client.send(
+                &[metering::open_meter(
+                    buyer,
+                    provider,
+                    mint,
+                    price_per_query,
+                    receipt_signer.unwrap_or(buyer),
+                )],
+                &[],
+            )?;
+
+            println!(
+                "Meter opened: {}",
+                metering::find_meter_address(&buyer, &provider).0
+            );
+            println!("Signature: {}", signature);
+        }
+        MeterCommand::TopUp { provider, amount } => {
+            let meter = metering::find_meter_address(&client.payer(), &provider).0;
+            let account = metering::fetch_meter(client.rpc(), &client.payer(), &provider)?;
+            let signature = client.send(&[metering::top_up(meter, &account, amount)], &[])?;
+
+            println!("Meter topped up: {}", meter);
+            println!("Amount: {}", amount);
+            println!("Signature: {}", signature);
+        }
+        MeterCommand::Stop { provider } => {
+            let meter = metering::find_meter_address(&client.payer(), &provider).0;
+            let account = metering::fetch_meter(client.rpc(), &client.payer(), &provider)?;
+            let signature = client.send(&[metering::stop_meter(meter, &account)], &[])?;
+
+            println!("Meter stopped: {}", meter);
+            println!(
+                "Provider may submit receipts for {}s",
+                metering::SETTLEMENT_PERIOD
+            );
+            println!("Signature: {}", signature);
+        }
+        MeterCommand::SignReceipt {
+            meter,
+            cumulative_queries,
+            receipt_keypair,
+        } => {
+            // 收据绑定计量账户的开通时间，重新开通后旧收据失效
+            let account: metering::Meter = fetch::fetch_account(client.rpc(), &meter)?;
+            let receipt_signer = load_keypair(Some(receipt_keypair))?;
+            let signature = receipt_signer.sign_message(&metering::usage_receipt_message(
+                &meter,
+                account.opened_at,
+                cumulative_queries,
+            ));
+
+            println!("{}", signature);
+        }
+        MeterCommand::Draw {
+            meter,
+            cumulative_queries,
+            signature,
+        } => {
+            let account: metering::Meter = fetch::fetch_account(client.rpc(), &meter)?;
+            let signature = client.send(
+                &metering::draw(meter, &account, cumulative_queries, signature.into()),
+                &[],
+            )?;
+
+            println!("Usage drawn: {}", meter);
+            println!("Cumulative queries: {}", cumulative_queries);
+            println!("Signature: {}", signature);
+        }
+        MeterCommand::Close { provider } => {
+            let meter = metering::find_meter_address(&client.payer(), &provider).0;
+            let account = metering::fetch_meter(client.rpc(), &client.payer(), &provider)?;
+            let signature = client.send(&[metering::close_meter(meter, &account)], &[])?;
+
+            println!("Meter closed: {}", meter);
+            println!("Signature: {}", signature);
+        }
+        MeterCommand::Show { meter } => {
+            let account: metering::Meter = fetch::fetch_account(client.rpc(), &meter)?;
+            println!("Meter:            {}", meter);
+            println!("Buyer:            {}", account.buyer);
+            println!("Provider:         {}", account.provider);
+            println!("Mint:             {}", account.mint);
+            println!("Receipt signer:   {}", account.receipt_signer);
+            println!("Price per query:  {}", account.price_per_query);
+            println!("Consumed queries: {}", account.consumed_queries);
+            println!("Total paid:       {}", account.total_paid);
+            if let Some(stopped_at) = account.stopped_at {
+                println!("Stopped at:       {}", stopped_at);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_subscription(client: &EscrowClient, command: SubscriptionCommand) -> Result<()> {
+    match command {
+        SubscriptionCommand::CreatePlan {
+            plan_id,
+            mint,
+            price,
+            period,
+        } => {
+            let provider = client.payer();
+            let signature = client.send(
+                &[subscriptions::create_plan(
+                    provider, plan_id, mint, price, period,
+                )],
+                &[],
+            )?;
+            let plan = subscriptions::find_plan_address(&provider, plan_id).0;
+
+            println!("Plan created: {}", plan);
+            println!(
+                "Access mint: {}",
+                subscriptions::find_plan_mint_address(&plan).0
+            );
+            println!("Signature: {}", signature);
+        }
+        SubscriptionCommand::UpdatePlan {
+            plan,
+            price,
+            period,
+            inactive,
+        } => {
+            let account = subscriptions::fetch_plan(client.rpc(), &plan)?;
+            let signature = client.send(
+                &[subscriptions::update_plan(
+                    plan, &account, price, period, !inactive,
+                )],
+                &[],
+            )?;
+
+            println!("Plan updated: {}", plan);
+            println!("Signature: {}", signature);
+        }
+        SubscriptionCommand::Subscribe { plan } => {
+            let account = subscriptions::fetch_plan(client.rpc(), &plan)?;
+            let signature = client.send(
+                &[subscriptions::subscribe(plan, &account, client.payer())],
+                &[],
+            )?;
+            let subscription =
+                subscriptions::fetch_subscription(client.rpc(), &plan, &client.payer())?;
+
+            println!("Subscribed: {}", plan);
+            println!("Paid until: {}", subscription.paid_until);
+            println!("Signature: {}", signature);
+        }
+        SubscriptionCommand::Lapse { plan, subscriber } => {
+            let account = subscriptions::fetch_plan(client.rpc(), &plan)?;
+            let subscription = subscriptions::fetch_subscription(client.rpc(), &plan, &subscriber)?;
+            let signature =
+                client.send(&[subscriptions::lapse(plan, &account, &subscription)], &[])?;
+
+            println!("Subscription lapsed: {}", subscriber);
+            println!("Signature: {}", signature);
+        }
+        SubscriptionCommand::Check { plan, subscriber } => {
+            let account = subscriptions::fetch_plan(client.rpc(), &plan)?;
+            let subscription = subscriptions::fetch_subscription(client.rpc(), &plan, &subscriber)?;
+
+            println!("Plan:          {}", plan);
+            println!("Subscriber:    {}", subscriber);
+            println!("Paid until:    {}", subscription.paid_until);
+            println!(
+                "Access:        {}",
+                subscriptions::has_access(client.rpc(), &account, &subscriber)?
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn run_auction(client: &EscrowClient, command: AuctionCommand) -> Result<()> {
+    match command {
+        AuctionCommand::Open {
+            request_id,
+            mint,
+            bid_bond,
+            closes_at,
+            auto_select,
+            reverse,
+        } => {
+            let buyer = client.payer();
+            let request = auctions::find_request_address(&buyer, &request_id).0;
+            let ix = if reverse {
+                auctions::open_reverse_auction(buyer, request, mint, bid_bond, closes_at)
+            } else {
+                auctions::open_auction(buyer, request, mint, bid_bond, closes_at, auto_select)
+            };
+            let signature = client.send(&[ix], &[])?;
+
+            println!(
+                "Auction opened: {}",
+                auctions::find_auction_address(&request).0
+            );
+            println!("Closes at: {}", closes_at);
+            println!("Signature: {}", signature);
+        }
+        AuctionCommand::Bid {
+            request,
+            proposal_id,
+            price,
+            terms_hash,
+        } => {
+            let auction = auctions::fetch_auction(client.rpc(), &request)?;
+            let signature = client.send(
+                &[auctions::submit_bid(
+                    client.payer(),
+                    request,
+                    &auction,
+                    proposal_id,
+                    price,
+                    parse_hash(&terms_hash, "terms hash")?,
+                )],
+                &[],
+            )?;
+
+            println!(
+                "Bid submitted: {}",
+                auctions::find_proposal_address(&request, &client.payer()).0
+            );
+            println!("Price: {}, bond: {}", price, auction.bid_bond);
+            println!("Signature: {}", signature);
+        }
+        AuctionCommand::Select { request, proposal } => {
+            let auction = auctions::fetch_auction(client.rpc(), &request)?;
+            let proposal = proposal
+                .or(auction.best_proposal)
+                .context("auction has no bids")?;
+            let signature = client.send(
+                &[auctions::select_bid(client.payer(), request, proposal)],
+                &[],
+            )?;
+
+            println!("Bid selected: {}", proposal);
+            println!("Signature: {}", signature);
+        }
+        AuctionCommand::Award { request } => {
+            let account = auctions::fetch_request(client.rpc(), &request)?;
+            let auction = auctions::fetch_auction(client.rpc(), &request)?;
+            let winner = auction.winner.context("auction has no winner yet")?;
+            let proposal = auctions::fetch_proposal(client.rpc(), &winner)?;
+            let config = client.fetch_config()?;
+            let provider_sequence = client.provider_sequence(&proposal.provider)?;
+            let escrow = EscrowClient::escrow_address(&account.buyer, &account.request_id);
+
+            // 反向竞价在授标时同时充值，托管代币账户须先创建
+            let ixs = if auction.locked_budget > 0 {
+                vec![
+                    instructions::create_escrow_token_account(client.payer(), escrow, auction.mint),
+                    auctions::award_reverse_auction(
+                        request,
+                        &account,
+                        &auction,
+                        &proposal,
+                        &config,
+                        provider_sequence,
+                    ),
+                ]
+            } else {
+                vec![auctions::award_auction(
+                    request,
+                    &account,
+                    &auction,
+                    &proposal,
+                    &config,
+                    provider_sequence,
+                )]
+            };
+            let signature = client.send(&ixs, &[])?;
+
+            println!("Auction awarded: {}", escrow);
+            println!("Price: {}", proposal.price);
+            if auction.locked_budget > 0 {
+                println!(
+                    "Budget refunded: {}",
+                    auction.locked_budget - proposal.price
+                );
+            }
+            println!("Signature: {}", signature);
+        }
+        AuctionCommand::WithdrawBudget { request } => {
+            let auction = auctions::fetch_auction(client.rpc(), &request)?;
+            let signature = client.send(
+                &[auctions::withdraw_budget(client.payer(), request, &auction)],
+                &[],
+            )?;
+
+            println!("Budget withdrawn: {}", auction.locked_budget);
+            println!("Signature: {}", signature);
+        }
+        AuctionCommand::Refund { proposal } => {
+            let account = auctions::fetch_proposal(client.rpc(), &proposal)?;
+            let auction = auctions::fetch_auction(client.rpc(), &account.request)?;
+            let bid = auctions::fetch_bid(client.rpc(), &proposal)?;
+            let signature = client.send(
+                &[auctions::refund_bid(account.request, &auction, &bid)],
+                &[],
+            )?;
+
+            println!("Bid bond refunded: {}", proposal);
+            println!("Amount: {}", bid.bond);
+            println!("Signature: {}", signature);
+        }
+        AuctionCommand::Show { request } => {
+            let auction = auctions::fetch_auction(client.rpc(), &request)?;
+
+            println!("Request:       {}", request);
+            println!("Mint:          {}", auction.mint);
+            println!("Bid bond:      {}", auction.bid_bond);
+            println!("Closes at:     {}", auction.closes_at);
+            println!("Auto select:   {}", auction.auto_select);
+            if auction.locked_budget > 0 {
+                println!("Locked budget: {}", auction.locked_budget);
+            }
+            if let Some(best) = auction.best_proposal {
+                println!("Lowest bid:    {} ({})", best, auction.best_price);
+            }
+            if let Some(winner) = auction.winner {
+                println!("Winner:        {}", winner);
+            }
+            if let Some(escrow) = auction.escrow {
+                println!("Escrow:        {}", escrow);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn withdraw_yield_first(
+    client: &EscrowClient,
+    escrow: Pubkey,
+    account: &Escrow,
+) -> Result<Vec<Instruction>> {
+    let position = match fetch::fetch_yield_position(client.rpc(), &escrow) {
+        Ok(position) => position,
+        Err(ClientError::AccountNotFound(_)) => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let reserve = lending::fetch_reserve(client.rpc(), &position.reserve)?;
+
+    Ok(lending::withdraw_yield(
+        escrow,
+        account,
+        &client.fetch_config()?,
+        client.payer(),
+        &position,
+        &reserve,
+    ))
+}
+
+fn print_pending_change(client: &EscrowClient) -> Result<()> {
+    let pending = fetch::fetch_pending_config_change(client.rpc())?;
+    println!(
+        "Config change queued, executable after {}",
+        pending.execute_after
+    );
+    Ok(())
+}
+
+fn load_keypair(path: Option<PathBuf>) -> Result<Keypair> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let home = std::env::var_os("HOME").context("HOME is not set, pass --keypair")?;
+            PathBuf::from(home).join(".config/solana/id.json")
+        }
+    };
+
+    read_keypair_file(&path)
+        .map_err(|err| anyhow!("failed to read keypair {}: {}", path.display(), err))
+}
+
+/// 连接 Ledger，`locator` 形如 `usb://ledger[/<设备公钥>][?key=<account>[/<change>]]`
+///
+/// 需以 `--features ledger` 编译才能访问 USB 设备
+fn load_ledger(locator: &str) -> Result<Box<dyn Signer>> {
+    let (path, query) = locator.split_once('?').unwrap_or((locator, ""));
+    let derivation_path = match query.strip_prefix("key=") {
+        Some(key) => DerivationPath::from_key_str(key)
+            .map_err(|err| anyhow!("invalid ledger key `{}`: {}", key, err))?,
+        None if query.is_empty() => DerivationPath::default(),
+        None => bail!(
+            "unsupported ledger query `{}`, expected `key=<account>[/<change>]`",
+            query
+        ),
+    };
+    let locator = Locator::new_from_path(path)
+        .map_err(|err| anyhow!("invalid ledger locator `{}`: {}", path, err))?;
+
+    let wallet_manager = maybe_wallet_manager()
+        .context("failed to access USB devices (was the CLI built with `--features ledger`?)")?
+        .context("no Ledger device found")?;
+    let keypair =
+        generate_remote_keypair(locator, derivation_path, &wallet_manager, false, "ledger")
+            .context("failed to connect to Ledger")?;
+
+    Ok(Box::new(keypair))
+}
+
+fn parse_priority_fee(value: &str) -> Result<PriorityFee> {
+    if value == "auto" {
+        return Ok(PriorityFee::Percentile(DEFAULT_FEE_PERCENTILE));
+    }
+    let price = value
+        .parse()
+        .context("priority fee must be `auto` or micro-lamports per compute unit")?;
+    Ok(PriorityFee::Fixed(price))
+}
+
+fn parse_hash(hex: &str, name: &str) -> Result<[u8; 32]> {
+    parse_hex(hex, name)?
+        .try_into()
+        .map_err(|_| anyhow!("{} must be 32 bytes (64 hex characters)", name))
+}
+
+fn parse_storage(uri: &str) -> Result<StorageRef> {
+    instructions::parse_storage_uri(uri)
+        .ok_or_else(|| anyhow!("storage URI must start with ipfs://, ar:// or shdw://"))
+}
+
+/// 读取数据文件并按 `chunk_size` 分块构建 Merkle 树
+fn chunk_tree(path: &Path, chunk_size: usize) -> Result<ChunkTree> {
+    if chunk_size == 0 {
+        bail!("chunk size must be positive");
+    }
+    let data = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    ChunkTree::from_chunks(&data.chunks(chunk_size).collect::<Vec<_>>())
+        .ok_or_else(|| anyhow!("{} is empty", path.display()))
+}
+
+fn parse_hex(hex: &str, name: &str) -> Result<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        bail!("{} must be an even number of hex characters", name);
+    }
+
+    (0..hex.len() / 2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .with_context(|| format!("invalid hex in {} at byte {}", name, i))
+        })
+        .collect()
+}
+
+fn status_name(status: &EscrowStatus) -> &'static str {
+    match status {
+        EscrowStatus::Created => "Created",
+        EscrowStatus::Funded => "Funded",
+        EscrowStatus::Delivered => "Delivered",
+        EscrowStatus::Disputed => "Disputed",
+        EscrowStatus::Completed => "Completed",
+        EscrowStatus::Refunded => "Refunded",
+        EscrowStatus::Cancelled => "Cancelled",
+        EscrowStatus::Escalated => "Escalated",
+        EscrowStatus::Ruled => "Ruled",
+        EscrowStatus::Appealed => "Appealed",
+        EscrowStatus::Rejected => "Rejected",
+        EscrowStatus::PendingCancel => "PendingCancel",
+    }
+}
+
+fn print_escrow(address: &Pubkey, escrow: &Escrow) {
+    println!("Escrow:        {}", address);
+    println!("Status:        {}", status_name(&escrow.status()));
+    println!("Buyer:         {}", escrow.buyer);
+    println!("Provider:      {}", escrow.provider);
+    println!("Platform:      {}", escrow.platform);
+    println!("Amount:        {}", escrow.amount);
+    println!("Request ID:    {}", escrow.request_id());
+    println!("Proposal ID:   {}", escrow.proposal_id());
+    println!("Created at:    {}", escrow.created_at);
+    if let Some(delivered_at) = escrow.delivered_at() {
+        println!("Delivered at:  {}", delivered_at);
+    }
+    if let Some(completed_at) = escrow.completed_at() {
+        println!("Completed at:  {}", completed_at);
+    }
+    if let Some(disputed_at) = escrow.disputed_at() {
+        println!("Disputed at:   {}", disputed_at);
+    }
+    if let Some(refunded_at) = escrow.refunded_at() {
+        println!("Refunded at:   {}", refunded_at);
+    }
+    println!(
+        "Delivery hash: {}",
+        escrow
+            .delivery_hash
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>()
+    );
+    println!("Version:       {}", escrow.version);
+}
@@ -0,0 +1,335 @@
+//! 需求竞价
+//!
+//! 竞价由需求程序管理：买家为需求开启竞价 `[b"auction", 需求]`，
+//! 提供商缴纳保证金出价（同时提交提案），截止后选定中标者，
+//! 买家授标时需求程序 CPI 托管程序按中标报价创建托管；
+//! 保证金存放在 `[b"auction_vault", 竞价]`，按 `[b"bid", 提案]` 逐个退还。
+//! 反向竞价开启时把需求预算也锁入该金库，授标时按中标报价为托管充值并退回差额
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token;
+use datanexus_escrow::Config;
+use datanexus_requests::{accounts, instruction, ID};
+use solana_rpc_client::rpc_client::RpcClient;
+
+use crate::fetch::fetch_account;
+use crate::instructions::{compliance_attestations, provider_badge};
+use crate::{pda, Result};
+
+pub use datanexus_requests::{Auction, BidBond, Proposal, Request};
+
+/// 需求 `[b"request", buyer, request_id]`（需求程序）
+pub fn find_request_address(buyer: &Pubkey, request_id: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"request", buyer.as_ref(), request_id.as_bytes()], &ID)
+}
+
+/// 提案 `[b"proposal", request, provider]`（需求程序）
+pub fn find_proposal_address(request: &Pubkey, provider: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"proposal", request.as_ref(), provider.as_ref()], &ID)
+}
+
+/// 竞价 `[b"auction", request]`（需求程序）
+pub fn find_auction_address(request: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"auction", request.as_ref()], &ID)
+}
+
+/// 保证金金库 `[b"auction_vault", auction]`（需求程序）
+pub fn find_auction_vault_address(auction: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"auction_vault", auction.as_ref()], &ID)
+}
+
+/// 出价保证金 `[b"bid", proposal]`（需求程序）
+pub fn find_bid_address(proposal: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"bid", proposal.as_ref()], &ID)
+}
+
+/// 读取需求（不存在时返回 `ClientError::AccountNotFound`）
+pub fn fetch_request(rpc: &RpcClient, request: &Pubkey) -> Result<Request> {
+    fetch_account(rpc, request)
+}
+
+/// 读取提案（不存在时返回 `ClientError::AccountNotFound`）
+pub fn fetch_proposal(rpc: &RpcClient, proposal: &Pubkey) -> Result<Proposal> {
+    fetch_account(rpc, proposal)
+}
+
+/// 读取需求的竞价（未开启竞价时返回 `ClientError::AccountNotFound`）
+pub fn fetch_auction(rpc: &RpcClient, request: &Pubkey) -> Result<Auction> {
+    fetch_account(rpc, &find_auction_address(request).0)
+}
+
+/// 读取出价保证金（不是竞价出价时返回 `ClientError::AccountNotFound`）
+pub fn fetch_bid(rpc: &RpcClient, proposal: &Pubkey) -> Result<BidBond> {
+    fetch_account(rpc, &find_bid_address(proposal).0)
+}
+
+/// 买家选定提案（非竞价出价），托管程序只接受已选定的提案创建托管
+pub fn select_proposal(buyer: Pubkey, request: Pubkey, proposal: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::SelectProposal {
+            request,
+            proposal,
+            buyer,
+            bid: find_bid_address(&proposal).0,
+        }
+        .to_account_metas(None),
+        data: instruction::SelectProposal {}.data(),
+    }
+}
+
+/// 买家为需求开启竞价，保证金币种为 `mint`
+pub fn open_auction(
+    buyer: Pubkey,
+    request: Pubkey,
+    mint: Pubkey,
+    bid_bond: u64,
+    closes_at: i64,
+    auto_select: bool,
+) -> Instruction {
+    let auction = find_auction_address(&request).0;
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::OpenAuction {
+            request,
+            auction,
+            vault: find_auction_vault_address(&auction).0,
+            buyer,
+            mint,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::OpenAuction {
+            bid_bond,
+            closes_at,
+            auto_select,
+        }
+        .data(),
+    }
+}
+
+/// 买家开启反向竞价，需求预算从买家的关联代币账户锁入竞价金库
+pub fn open_reverse_auction(
+    buyer: Pubkey,
+    request: Pubkey,
+    mint: Pubkey,
+    bid_bond: u64,
+    closes_at: i64,
+) -> Instruction {
+    let auction = find_auction_address(&request).0;
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::OpenReverseAuction {
+            open: accounts::OpenAuction {
+                request,
+                auction,
+                vault: find_auction_vault_address(&auction).0,
+                buyer,
+                mint,
+                token_program: token::ID,
+                system_program: system_program::ID,
+            },
+            buyer_token_account: get_associated_token_address(&buyer, &mint),
+        }
+        .to_account_metas(None),
+        data: instruction::OpenReverseAuction {
+            bid_bond,
+            closes_at,
+        }
+        .data(),
+    }
+}
+
+/// 提供商出价，保证金从提供商的关联代币账户转入金库
+pub fn submit_bid(
+    provider: Pubkey,
+    request: Pubkey,
+    auction: &Auction,
+    proposal_id: String,
+    price: u64,
+    terms_hash: [u8; 32],
+) -> Instruction {
+    let auction_address = find_auction_address(&request).0;
+    let proposal = find_proposal_address(&request, &provider).0;
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::SubmitBid {
+            request,
+            auction: auction_address,
+            proposal,
+            bid: find_bid_address(&proposal).0,
+            vault: find_auction_vault_address(&auction_address).0,
+            provider,
+            provider_token_account: get_associated_token_address(&provider, &auction.mint),
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SubmitBid {
+            proposal_id,
+            price,
+            terms_hash,
+        }
+        .data(),
+    }
+}
+
+/// 竞价截止后选定中标提案（买家，或开启自动选择时任何人选定最低报价）
+pub fn select_bid(authority: Pubkey, request: Pubkey, proposal: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::SelectBid {
+            request,
+            auction: find_auction_address(&request).0,
+            proposal,
+            authority,
+        }
+        .to_account_metas(None),
+        data: instruction::SelectBid {}.data(),
+    }
+}
+
+/// 买家授标，需求程序 CPI 托管程序按中标报价创建托管
+///
+/// `provider_sequence` 为中标提供商当前的托管总数；需求带限制标志时同时传入托管的限制记录
+pub fn award_auction(
+    request_address: Pubkey,
+    request: &Request,
+    auction: &Auction,
+    winner: &Proposal,
+    config: &Config,
+    provider_sequence: u64,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: award_auction_accounts(
+            request_address,
+            request,
+            auction,
+            winner,
+            config,
+            provider_sequence,
+        )
+        .to_account_metas(None),
+        data: instruction::AwardAuction {}.data(),
+    }
+}
+
+/// 买家为反向竞价授标，锁定的预算按中标报价为托管充值，差额退回买家的关联代币账户
+///
+/// 托管的关联代币账户须先创建（见 `instructions::create_escrow_token_account`）
+pub fn award_reverse_auction(
+    request_address: Pubkey,
+    request: &Request,
+    auction: &Auction,
+    winner: &Proposal,
+    config: &Config,
+    provider_sequence: u64,
+) -> Instruction {
+    let auction_address = find_auction_address(&request_address).0;
+    let escrow = pda::escrow(&request.buyer, &request.request_id);
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::AwardReverseAuction {
+            award: award_auction_accounts(
+                request_address,
+                request,
+                auction,
+                winner,
+                config,
+                provider_sequence,
+            ),
+            vault: find_auction_vault_address(&auction_address).0,
+            buyer_token_account: get_associated_token_address(&request.buyer, &auction.mint),
+            escrow_token_account: get_associated_token_address(&escrow, &auction.mint),
+            quote: pda::quote(&escrow),
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::AwardReverseAuction {}.data(),
+    }
+}
+
+/// 买家取回反向竞价锁定的预算，退到买家的关联代币账户
+pub fn withdraw_budget(buyer: Pubkey, request: Pubkey, auction: &Auction) -> Instruction {
+    let auction_address = find_auction_address(&request).0;
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::WithdrawBudget {
+            request,
+            auction: auction_address,
+            vault: find_auction_vault_address(&auction_address).0,
+            buyer,
+            buyer_token_account: get_associated_token_address(&buyer, &auction.mint),
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::WithdrawBudget {}.data(),
+    }
+}
+
+fn award_auction_accounts(
+    request_address: Pubkey,
+    request: &Request,
+    auction: &Auction,
+    winner: &Proposal,
+    config: &Config,
+    provider_sequence: u64,
+) -> accounts::AwardAuction {
+    let escrow = pda::escrow(&request.buyer, &request.request_id);
+    let (buyer_attestation, provider_attestation) =
+        compliance_attestations(config, &request.buyer, &winner.provider);
+
+    accounts::AwardAuction {
+        request: request_address,
+        auction: find_auction_address(&request_address).0,
+        proposal: find_proposal_address(&request_address, &winner.provider).0,
+        escrow,
+        buyer: request.buyer,
+        escrow_config: pda::config(),
+        buyer_stats: pda::buyer_stats(&request.buyer),
+        provider: winner.provider,
+        provider_stats: pda::provider_stats(&winner.provider),
+        provider_escrow_index: pda::provider_escrow_index(&winner.provider, provider_sequence),
+        protocol_stats: pda::protocol_stats(),
+        provider_profile: None,
+        restriction: (request.restrictions != 0).then(|| pda::restriction(&escrow)),
+        platform: config.admin,
+        buyer_attestation,
+        provider_attestation,
+        provider_badge: provider_badge(config, &winner.provider, winner.price),
+        buyer_blacklist: pda::blacklist(&request.buyer),
+        provider_blacklist: pda::blacklist(&winner.provider),
+        mint: auction.mint,
+        escrow_program: datanexus_escrow::ID,
+        system_program: system_program::ID,
+    }
+}
+
+/// 退还出价保证金（任何人可调用），退到提供商的关联代币账户
+pub fn refund_bid(request: Pubkey, auction: &Auction, bid: &BidBond) -> Instruction {
+    let auction_address = find_auction_address(&request).0;
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::RefundBid {
+            auction: auction_address,
+            bid: find_bid_address(&bid.proposal).0,
+            vault: find_auction_vault_address(&auction_address).0,
+            provider_token_account: get_associated_token_address(&bid.provider, &auction.mint),
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RefundBid {}.data(),
+    }
+}
@@ -0,0 +1,114 @@
+//! 数据块清单
+//!
+//! 与链上一致的数据块 Merkle 树：提供商交付时提交根和块数，
+//! 买家抽查或争议时为指定的块生成证明，平台据此调用 `resolve_dispute_split`
+
+use datanexus_escrow::{ChunkManifestParams, ChunkProof};
+use solana_sha256_hasher::{hash, hashv};
+
+/// 由随机种子在 `0..chunk_count` 中抽取至多 `count` 个不同的块序号，供 `challenge` 使用
+///
+/// 第 i 个候选为 `sha256(seed || i)` 前 8 字节对块数取模，重复的跳过
+pub fn sample_indices(seed: &[u8; 32], chunk_count: u32, count: usize) -> Vec<u32> {
+    let count = count.min(chunk_count as usize);
+    let mut indices = Vec::with_capacity(count);
+    let mut counter: u64 = 0;
+    while indices.len() < count {
+        let digest = hashv(&[seed, &counter.to_le_bytes()]).to_bytes();
+        let index =
+            (u64::from_le_bytes(digest[..8].try_into().unwrap()) % chunk_count as u64) as u32;
+        if !indices.contains(&index) {
+            indices.push(index);
+        }
+        counter += 1;
+    }
+    indices
+}
+
+/// 数据块 Merkle 树
+///
+/// 叶子为 `sha256(0x00 || 块哈希)`，内部节点为 `sha256(0x01 || 左 || 右)`，
+/// 某层节点数为奇数时最后一个节点与自身配对
+#[derive(Clone, Debug)]
+pub struct ChunkTree {
+    chunk_hashes: Vec<[u8; 32]>,
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl ChunkTree {
+    /// 由各数据块内容构建
+    pub fn from_chunks<T: AsRef<[u8]>>(chunks: &[T]) -> Option<Self> {
+        let chunk_hashes: Vec<[u8; 32]> = chunks
+            .iter()
+            .map(|chunk| hash(chunk.as_ref()).to_bytes())
+            .collect();
+        Self::new(chunk_hashes)
+    }
+
+    /// 由各数据块内容的 sha256 构建，至少需要一块
+    pub fn new(chunk_hashes: Vec<[u8; 32]>) -> Option<Self> {
+        if chunk_hashes.is_empty() {
+            return None;
+        }
+
+        let mut levels = vec![chunk_hashes
+            .iter()
+            .map(|chunk_hash| hashv(&[&[0], chunk_hash]).to_bytes())
+            .collect::<Vec<_>>()];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| {
+                    let right = pair.get(1).unwrap_or(&pair[0]);
+                    hashv(&[&[1], &pair[0], right]).to_bytes()
+                })
+                .collect();
+            levels.push(level);
+        }
+
+        Some(Self {
+            chunk_hashes,
+            levels,
+        })
+    }
+
+    /// Merkle 根
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// 数据块数
+    pub fn chunk_count(&self) -> u32 {
+        self.chunk_hashes.len() as u32
+    }
+
+    /// `mark_delivered` 的数据块清单参数
+    pub fn manifest(&self) -> ChunkManifestParams {
+        ChunkManifestParams {
+            root: self.root(),
+            chunk_count: self.chunk_count(),
+        }
+    }
+
+    /// 第 `index` 块的 Merkle 证明，供 `raise_dispute` 使用
+    pub fn proof(&self, index: u32) -> Option<ChunkProof> {
+        let chunk_hash = *self.chunk_hashes.get(index as usize)?;
+        let mut position = index as usize;
+        let proof = self.levels[..self.levels.len() - 1]
+            .iter()
+            .map(|level| {
+                let sibling = level.get(position ^ 1).unwrap_or(&level[position]);
+                position /= 2;
+                *sibling
+            })
+            .collect();
+
+        Some(ChunkProof {
+            index,
+            chunk_hash,
+            proof,
+        })
+    }
+}
@@ -0,0 +1,73 @@
+//! 平台优惠券
+//!
+//! 优惠券由优惠券程序管理，PDA 为 `[b"coupon", 签发人, sha256(优惠码)]`；
+//! 买家创建托管时通过 `CreateEscrowParams.coupon` / `coupon_code` 附带优惠券和优惠码，
+//! 托管程序 CPI 校验优惠码并兑换，释放时减免平台费
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use datanexus_coupons::{accounts, instruction, ID};
+use solana_rpc_client::rpc_client::RpcClient;
+
+use crate::fetch::fetch_account;
+use crate::Result;
+
+pub use datanexus_coupons::{Coupon, Discount};
+
+/// 优惠码的哈希：`sha256(优惠码)`，优惠码本身在链下分发
+pub fn code_hash(code: &str) -> [u8; 32] {
+    solana_sha256_hasher::hash(code.as_bytes()).to_bytes()
+}
+
+/// 优惠券 `[b"coupon", issuer, code_hash]`（优惠券程序）
+pub fn find_coupon_address(issuer: &Pubkey, code_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"coupon", issuer.as_ref(), code_hash], &ID)
+}
+
+/// 读取优惠券（不存在时返回 `ClientError::AccountNotFound`）
+pub fn fetch_coupon(rpc: &RpcClient, issuer: &Pubkey, code_hash: &[u8; 32]) -> Result<Coupon> {
+    fetch_account(rpc, &find_coupon_address(issuer, code_hash).0)
+}
+
+/// 平台签发优惠券，`buyer` 为空时任何买家都可使用
+///
+/// 托管程序只接受签发人为托管配置管理员的优惠券
+pub fn issue_coupon(
+    issuer: Pubkey,
+    code_hash: [u8; 32],
+    discount: Discount,
+    buyer: Option<Pubkey>,
+    expires_at: i64,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::IssueCoupon {
+            coupon: find_coupon_address(&issuer, &code_hash).0,
+            issuer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::IssueCoupon {
+            code_hash,
+            discount,
+            buyer,
+            expires_at,
+        }
+        .data(),
+    }
+}
+
+/// 签发人撤销未使用的优惠券，租金退还签发人
+pub fn revoke_coupon(issuer: Pubkey, code_hash: &[u8; 32]) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::RevokeCoupon {
+            coupon: find_coupon_address(&issuer, code_hash).0,
+            issuer,
+        }
+        .to_account_metas(None),
+        data: instruction::RevokeCoupon {}.data(),
+    }
+}
@@ -0,0 +1,181 @@
+//! 托管程序事件解码
+//!
+//! 程序通过 `emit!` 把事件以 `Program data: <base64>` 写入日志，
+//! 这里按鉴别符解码为强类型结构，调用方无需解析 `msg!` 文本
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+pub use datanexus_escrow::{
+    CancelRequested, DeliveryRejected, DisputeAppealed, DisputeEscalated, DisputeRaised,
+    DisputeResolved, DisputeRuled, DownloadConsumed, EscrowCancelled, EscrowCreated,
+    EscrowDelivered, EscrowFunded, EscrowReclaimed, EscrowRefunded, EscrowReleased, SaleRecorded,
+    UnitsDelivered,
+};
+
+/// `emit!` 写入的日志前缀
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// 托管程序事件
+pub enum EscrowEvent {
+    Created(EscrowCreated),
+    Funded(EscrowFunded),
+    Delivered(EscrowDelivered),
+    Rejected(DeliveryRejected),
+    Released(EscrowReleased),
+    UnitsDelivered(UnitsDelivered),
+    DownloadConsumed(DownloadConsumed),
+    Refunded(EscrowRefunded),
+    CancelRequested(CancelRequested),
+    Cancelled(EscrowCancelled),
+    Reclaimed(EscrowReclaimed),
+    DisputeRaised(DisputeRaised),
+    DisputeEscalated(DisputeEscalated),
+    DisputeResolved(DisputeResolved),
+    DisputeRuled(DisputeRuled),
+    DisputeAppealed(DisputeAppealed),
+    SaleRecorded(SaleRecorded),
+}
+
+impl EscrowEvent {
+    /// 按鉴别符解码一条事件数据（8 字节鉴别符 + borsh 数据），非托管事件返回 None
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+        let (discriminator, mut payload) = data.split_at(8);
+
+        let event = if discriminator == EscrowCreated::DISCRIMINATOR {
+            Self::Created(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == EscrowFunded::DISCRIMINATOR {
+            Self::Funded(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == EscrowDelivered::DISCRIMINATOR {
+            Self::Delivered(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == DeliveryRejected::DISCRIMINATOR {
+            Self::Rejected(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == EscrowReleased::DISCRIMINATOR {
+            Self::Released(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == UnitsDelivered::DISCRIMINATOR {
+            Self::UnitsDelivered(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == DownloadConsumed::DISCRIMINATOR {
+            Self::DownloadConsumed(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == EscrowRefunded::DISCRIMINATOR {
+            Self::Refunded(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == CancelRequested::DISCRIMINATOR {
+            Self::CancelRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == EscrowCancelled::DISCRIMINATOR {
+            Self::Cancelled(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == EscrowReclaimed::DISCRIMINATOR {
+            Self::Reclaimed(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == DisputeRaised::DISCRIMINATOR {
+            Self::DisputeRaised(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == DisputeEscalated::DISCRIMINATOR {
+            Self::DisputeEscalated(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == DisputeResolved::DISCRIMINATOR {
+            Self::DisputeResolved(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == DisputeRuled::DISCRIMINATOR {
+            Self::DisputeRuled(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == DisputeAppealed::DISCRIMINATOR {
+            Self::DisputeAppealed(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else if discriminator == SaleRecorded::DISCRIMINATOR {
+            Self::SaleRecorded(AnchorDeserialize::deserialize(&mut payload).ok()?)
+        } else {
+            return None;
+        };
+
+        Some(event)
+    }
+
+    /// 解码 `emit_cpi!` 产生的自调用内部指令数据（带 `EVENT_IX_TAG` 前缀）
+    pub fn decode_cpi(instruction_data: &[u8]) -> Option<Self> {
+        Self::decode(instruction_data.strip_prefix(EVENT_IX_TAG_LE)?)
+    }
+
+    /// 事件所属托管
+    pub fn escrow(&self) -> Pubkey {
+        match self {
+            Self::Created(event) => event.escrow,
+            Self::Funded(event) => event.escrow,
+            Self::Delivered(event) => event.escrow,
+            Self::Rejected(event) => event.escrow,
+            Self::Released(event) => event.escrow,
+            Self::UnitsDelivered(event) => event.escrow,
+            Self::DownloadConsumed(event) => event.escrow,
+            Self::Refunded(event) => event.escrow,
+            Self::CancelRequested(event) => event.escrow,
+            Self::Cancelled(event) => event.escrow,
+            Self::Reclaimed(event) => event.escrow,
+            Self::DisputeRaised(event) => event.escrow,
+            Self::DisputeEscalated(event) => event.escrow,
+            Self::DisputeResolved(event) => event.escrow,
+            Self::DisputeRuled(event) => event.escrow,
+            Self::DisputeAppealed(event) => event.escrow,
+            Self::SaleRecorded(event) => event.escrow,
+        }
+    }
+
+    /// 事件发生的链上时间
+    pub fn timestamp(&self) -> i64 {
+        match self {
+            Self::Created(event) => event.timestamp,
+            Self::Funded(event) => event.timestamp,
+            Self::Delivered(event) => event.timestamp,
+            Self::Rejected(event) => event.timestamp,
+            Self::Released(event) => event.timestamp,
+            Self::UnitsDelivered(event) => event.timestamp,
+            Self::DownloadConsumed(event) => event.timestamp,
+            Self::Refunded(event) => event.timestamp,
+            Self::CancelRequested(event) => event.timestamp,
+            Self::Cancelled(event) => event.timestamp,
+            Self::Reclaimed(event) => event.timestamp,
+            Self::DisputeRaised(event) => event.timestamp,
+            Self::DisputeEscalated(event) => event.timestamp,
+            Self::DisputeResolved(event) => event.timestamp,
+            Self::DisputeRuled(event) => event.timestamp,
+            Self::DisputeAppealed(event) => event.timestamp,
+            Self::SaleRecorded(event) => event.timestamp,
+        }
+    }
+}
+
+/// 解析一笔交易的全部日志，按出现顺序返回托管事件
+///
+/// 根据 `Program <id> invoke` / `success` / `failed` 跟踪调用栈，
+/// 只接受托管程序自身（含被其他程序 CPI 调用时）写入的事件数据
+pub fn parse_logs(logs: &[String]) -> Vec<EscrowEvent> {
+    let escrow_program = datanexus_escrow::ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+            if stack.last() != Some(&escrow_program.as_str()) {
+                continue;
+            }
+            if let Some(event) = STANDARD
+                .decode(data)
+                .ok()
+                .and_then(|data| EscrowEvent::decode(&data))
+            {
+                events.push(event);
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut parts = rest.split(' ');
+            let (Some(program), Some(action)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            match action {
+                "invoke" => stack.push(program),
+                "success" | "failed" | "failed:" => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    events
+}
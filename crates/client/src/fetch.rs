@@ -0,0 +1,256 @@
+//! 账户读取
+//!
+//! 按 PDA 推导地址并反序列化托管程序账户，账户不存在时返回 `ClientError::AccountNotFound`；
+//! `list_escrows_*` 通过 `getProgramAccounts` 按定长字段的 memcmp 过滤批量查询托管
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use datanexus_escrow::{
+    AccessWindow, Badge, Blacklist, BuyerStats, CancelRequest, ChunkChallenge, ChunkManifest,
+    Config, DatasetProof, DeliveryCommitment, DeliveryRejection, DeliveryTerms, Escrow,
+    EscrowBonus, EscrowDownloads, EscrowQuote, EscrowRestriction, EscrowStatus, EscrowUnits,
+    KeyDelivery, MessageLog, PendingConfigChange, ProtocolStats, ProviderEscrowIndex,
+    ProviderStats, SalesHistory, Template, YieldPosition, ESCROW_VERSION,
+};
+use datanexus_reputation::Rating;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+
+use crate::{pda, ClientError, Result};
+
+/// 读取并反序列化任意 Anchor 账户
+pub fn fetch_account<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<T> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*address))?;
+
+    T::try_deserialize(&mut account.data.as_slice())
+        .map_err(|err| ClientError::Deserialize(*address, err))
+}
+
+/// 读取托管账户
+pub fn fetch_escrow(rpc: &RpcClient, address: &Pubkey) -> Result<Escrow> {
+    fetch_account(rpc, address)
+}
+
+/// 读取平台配置
+pub fn fetch_config(rpc: &RpcClient) -> Result<Config> {
+    fetch_account(rpc, &pda::config())
+}
+
+/// 读取买家统计
+pub fn fetch_buyer_stats(rpc: &RpcClient, buyer: &Pubkey) -> Result<BuyerStats> {
+    fetch_account(rpc, &pda::buyer_stats(buyer))
+}
+
+/// 读取提供商统计
+pub fn fetch_provider_stats(rpc: &RpcClient, provider: &Pubkey) -> Result<ProviderStats> {
+    fetch_account(rpc, &pda::provider_stats(provider))
+}
+
+/// 读取提供商第 `sequence` 个托管的索引
+pub fn fetch_provider_escrow_index(
+    rpc: &RpcClient,
+    provider: &Pubkey,
+    sequence: u64,
+) -> Result<ProviderEscrowIndex> {
+    fetch_account(rpc, &pda::provider_escrow_index(provider, sequence))
+}
+
+/// 读取全局协议统计
+pub fn fetch_protocol_stats(rpc: &RpcClient) -> Result<ProtocolStats> {
+    fetch_account(rpc, &pda::protocol_stats())
+}
+
+/// 读取待生效的配置变更（没有排队的变更时返回 `ClientError::AccountNotFound`）
+pub fn fetch_pending_config_change(rpc: &RpcClient) -> Result<PendingConfigChange> {
+    fetch_account(rpc, &pda::pending_config_change())
+}
+
+/// 读取托管的收益模式存款（未开启或已赎回时返回 `ClientError::AccountNotFound`）
+pub fn fetch_yield_position(rpc: &RpcClient, escrow: &Pubkey) -> Result<YieldPosition> {
+    fetch_account(rpc, &pda::yield_position(escrow))
+}
+
+/// 读取托管的类别和司法辖区限制（需求未设置限制时返回 `ClientError::AccountNotFound`）
+pub fn fetch_restriction(rpc: &RpcClient, escrow: &Pubkey) -> Result<EscrowRestriction> {
+    fetch_account(rpc, &pda::restriction(escrow))
+}
+
+/// 读取托管的加密解密密钥交付（买家未要求时返回 `ClientError::AccountNotFound`）
+pub fn fetch_key_delivery(rpc: &RpcClient, escrow: &Pubkey) -> Result<KeyDelivery> {
+    fetch_account(rpc, &pda::key_delivery(escrow))
+}
+
+/// 读取提供商的交付承诺（未承诺时返回 `ClientError::AccountNotFound`）
+pub fn fetch_delivery_commitment(rpc: &RpcClient, escrow: &Pubkey) -> Result<DeliveryCommitment> {
+    fetch_account(rpc, &pda::delivery_commitment(escrow))
+}
+
+/// 读取交付的数据块清单（交付时未提交清单则返回 `ClientError::AccountNotFound`）
+pub fn fetch_chunk_manifest(rpc: &RpcClient, escrow: &Pubkey) -> Result<ChunkManifest> {
+    fetch_account(rpc, &pda::chunk_manifest(escrow))
+}
+
+/// 读取买家对数据块的抽查（未抽查时返回 `ClientError::AccountNotFound`）
+pub fn fetch_challenge(rpc: &RpcClient, escrow: &Pubkey) -> Result<ChunkChallenge> {
+    fetch_account(rpc, &pda::challenge(escrow))
+}
+
+/// 读取买家要求的数据集属性证明（未要求时返回 `ClientError::AccountNotFound`）
+pub fn fetch_dataset_proof(rpc: &RpcClient, escrow: &Pubkey) -> Result<DatasetProof> {
+    fetch_account(rpc, &pda::dataset_proof(escrow))
+}
+
+/// 读取信誉程序中托管的评分（未评分时返回 `ClientError::AccountNotFound`）
+pub fn fetch_rating(rpc: &RpcClient, escrow: &Pubkey) -> Result<Rating> {
+    fetch_account(rpc, &pda::rating(escrow))
+}
+
+/// 读取买家的取消申请（未申请时返回 `ClientError::AccountNotFound`）
+pub fn fetch_cancel_request(rpc: &RpcClient, escrow: &Pubkey) -> Result<CancelRequest> {
+    fetch_account(rpc, &pda::cancel_request(escrow))
+}
+
+/// 读取提供商的报价（未报价时返回 `ClientError::AccountNotFound`）
+pub fn fetch_quote(rpc: &RpcClient, escrow: &Pubkey) -> Result<EscrowQuote> {
+    fetch_account(rpc, &pda::quote(escrow))
+}
+
+/// 读取按件计价托管的单价和交付进度（非按件计价时返回 `ClientError::AccountNotFound`）
+pub fn fetch_units(rpc: &RpcClient, escrow: &Pubkey) -> Result<EscrowUnits> {
+    fetch_account(rpc, &pda::units(escrow))
+}
+
+/// 读取按下载计费托管的网关和剩余下载次数（非按下载计费时返回 `ClientError::AccountNotFound`）
+pub fn fetch_downloads(rpc: &RpcClient, escrow: &Pubkey) -> Result<EscrowDownloads> {
+    fetch_account(rpc, &pda::downloads(escrow))
+}
+
+/// 读取提供商销售记录（未开启时返回 `ClientError::AccountNotFound`）
+pub fn fetch_sales_history(rpc: &RpcClient, provider: &Pubkey) -> Result<SalesHistory> {
+    fetch_account(rpc, &pda::sales_history(provider))
+}
+
+/// 读取平台签发的提供商徽章（未签发或已撤销时返回 `ClientError::AccountNotFound`）
+pub fn fetch_badge(rpc: &RpcClient, provider: &Pubkey, badge_type: u8) -> Result<Badge> {
+    fetch_account(rpc, &pda::badge(provider, badge_type))
+}
+
+/// 读取地址的黑名单条目（未列入时返回 `ClientError::AccountNotFound`）
+pub fn fetch_blacklist(rpc: &RpcClient, subject: &Pubkey) -> Result<Blacklist> {
+    fetch_account(rpc, &pda::blacklist(subject))
+}
+
+/// 读取托管条款模板（不存在时返回 `ClientError::AccountNotFound`）
+pub fn fetch_template(rpc: &RpcClient, owner: &Pubkey, template_id: u64) -> Result<Template> {
+    fetch_account(rpc, &pda::template(owner, template_id))
+}
+
+/// 读取买卖双方的协商记录（未记录过时返回 `ClientError::AccountNotFound`）
+pub fn fetch_message_log(rpc: &RpcClient, escrow: &Pubkey) -> Result<MessageLog> {
+    fetch_account(rpc, &pda::message_log(escrow))
+}
+
+/// 读取提前交付奖励金（未追加时返回 `ClientError::AccountNotFound`）
+pub fn fetch_bonus(rpc: &RpcClient, escrow: &Pubkey) -> Result<EscrowBonus> {
+    fetch_account(rpc, &pda::bonus(escrow))
+}
+
+/// 读取约定的交付条款（未约定时返回 `ClientError::AccountNotFound`）
+pub fn fetch_delivery_terms(rpc: &RpcClient, escrow: &Pubkey) -> Result<DeliveryTerms> {
+    fetch_account(rpc, &pda::delivery_terms(escrow))
+}
+
+/// 读取买家拒收交付的记录（从未拒收时返回 `ClientError::AccountNotFound`）
+pub fn fetch_rejection(rpc: &RpcClient, escrow: &Pubkey) -> Result<DeliveryRejection> {
+    fetch_account(rpc, &pda::rejection(escrow))
+}
+
+/// 读取访问凭证有效期（未设置时返回 `ClientError::AccountNotFound`）
+pub fn fetch_access_window(rpc: &RpcClient, escrow: &Pubkey) -> Result<AccessWindow> {
+    fetch_account(rpc, &pda::access_window(escrow))
+}
+
+/// 托管账户的基础过滤条件：鉴别符和当前布局版本
+///
+/// 未迁移的旧布局账户中状态等字段不在固定偏移上，按版本排除，避免误匹配
+pub fn escrow_filters() -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, Escrow::DISCRIMINATOR.to_vec())),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            Escrow::VERSION_OFFSET,
+            vec![ESCROW_VERSION],
+        )),
+    ]
+}
+
+/// 按任意附加过滤条件查询托管账户
+pub fn list_escrows(
+    rpc: &RpcClient,
+    filters: impl IntoIterator<Item = RpcFilterType>,
+) -> Result<Vec<(Pubkey, Escrow)>> {
+    let mut all_filters = escrow_filters();
+    all_filters.extend(filters);
+
+    let config = RpcProgramAccountsConfig {
+        filters: Some(all_filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(rpc.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    rpc.get_program_accounts_with_config(&datanexus_escrow::ID, config)?
+        .into_iter()
+        .map(|(address, account)| {
+            Escrow::try_deserialize(&mut account.data.as_slice())
+                .map(|escrow| (address, escrow))
+                .map_err(|err| ClientError::Deserialize(address, err))
+        })
+        .collect()
+}
+
+/// 查询买家的全部托管
+pub fn list_escrows_by_buyer(rpc: &RpcClient, buyer: &Pubkey) -> Result<Vec<(Pubkey, Escrow)>> {
+    list_escrows(
+        rpc,
+        [RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            Escrow::BUYER_OFFSET,
+            buyer.to_bytes().to_vec(),
+        ))],
+    )
+}
+
+/// 查询提供商的全部托管
+pub fn list_escrows_by_provider(
+    rpc: &RpcClient,
+    provider: &Pubkey,
+) -> Result<Vec<(Pubkey, Escrow)>> {
+    list_escrows(
+        rpc,
+        [RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            Escrow::PROVIDER_OFFSET,
+            provider.to_bytes().to_vec(),
+        ))],
+    )
+}
+
+/// 查询处于某一状态的全部托管
+pub fn list_escrows_by_status(
+    rpc: &RpcClient,
+    status: EscrowStatus,
+) -> Result<Vec<(Pubkey, Escrow)>> {
+    list_escrows(
+        rpc,
+        [RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            Escrow::STATUS_OFFSET,
+            vec![status as u8],
+        ))],
+    )
+}
@@ -0,0 +1,1908 @@
+//! 托管指令构造
+//!
+//! 每个函数返回一条可直接放入交易的 `Instruction`，
+//! 托管相关 PDA 由函数内部推导，调用者只需提供钱包、铸币等外部地址
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+    spl_associated_token_account,
+};
+use anchor_spl::metadata::mpl_token_metadata;
+use anchor_spl::{token, token_2022};
+use datanexus_escrow::{
+    accounts, instruction, AccessWindow, ChunkManifestParams, ChunkProof, Config, Escrow,
+    EscrowStatus, PenaltyRecipient, ProtocolSettings, StorageProtocol, StorageRef, Template,
+    TemplateTerms, COMPLIANCE_BUYER, COMPLIANCE_PROVIDER, ED25519_PROGRAM_ID, JUPITER_PROGRAM_ID,
+    MEMO_PROGRAM_ID, REPUTATION_PROGRAM_ID,
+};
+
+use crate::pda;
+
+/// 创建托管的参数
+pub struct CreateEscrowParams {
+    pub buyer: Pubkey,                        // 买家（签名者，付款方）
+    pub provider: Pubkey,                     // 提供商
+    pub platform: Pubkey,                     // 平台（须为配置管理员）
+    pub amount: u64,                          // 托管金额（6 位小数）
+    pub mint: Pubkey,                         // 托管代币铸币（按模板创建时须为模板铸币）
+    pub request_id: String,                   // 需求 ID
+    pub proposal_id: String,                  // 提案 ID
+    pub provider_sequence: u64,               // 提供商当前托管总数（`ProviderStats.total_escrows`）
+    pub buyer_attestation: Option<Pubkey>,    // 买家合规证明（见 `compliance_attestations`）
+    pub provider_attestation: Option<Pubkey>, // 提供商合规证明
+    pub coupon: Option<Pubkey>,               // 平台优惠券（见 `coupons::find_coupon_address`）
+    pub coupon_code: Option<String>,          // 优惠码，与 `coupon` 同时传入
+    pub provider_badge: Option<Pubkey>,       // 提供商徽章（见 `provider_badge`）
+}
+
+/// 初始化平台配置，`admin` 须为托管程序的升级权限
+pub fn initialize_config(
+    admin: Pubkey,
+    treasury: Pubkey,
+    rewards_pool: Pubkey,
+    treasury_bps: u16,
+    rewards_pool_bps: u16,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::InitializeConfig {
+            config: pda::config(),
+            admin,
+            treasury,
+            rewards_pool,
+            program: datanexus_escrow::ID,
+            program_data: pda::program_data(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitializeConfig {
+            treasury_bps,
+            rewards_pool_bps,
+        }
+        .data(),
+    }
+}
+
+/// 排队更新国库、奖励池及分成权重（仅管理员），公示期后由 `execute_config_change` 生效
+pub fn update_config(
+    admin: Pubkey,
+    payer: Pubkey,
+    treasury: Pubkey,
+    rewards_pool: Pubkey,
+    treasury_bps: u16,
+    rewards_pool_bps: u16,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::UpdateConfig {
+            config: pda::config(),
+            admin,
+            treasury,
+            rewards_pool,
+            pending_change: pda::pending_config_change(),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::UpdateConfig {
+            treasury_bps,
+            rewards_pool_bps,
+        }
+        .data(),
+    }
+}
+
+/// 排队更新协议策略参数（仅管理员），公示期后由 `execute_config_change` 生效
+pub fn update_settings(admin: Pubkey, payer: Pubkey, settings: ProtocolSettings) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::UpdateSettings {
+            config: pda::config(),
+            admin,
+            pending_change: pda::pending_config_change(),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::UpdateSettings { settings }.data(),
+    }
+}
+
+/// 执行已过公示期的配置变更（任何人都可以调用），`payer` 为排队时的租金付款人
+pub fn execute_config_change(payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ExecuteConfigChange {
+            config: pda::config(),
+            pending_change: pda::pending_config_change(),
+            payer,
+        }
+        .to_account_metas(None),
+        data: instruction::ExecuteConfigChange {}.data(),
+    }
+}
+
+/// 撤销尚未执行的配置变更（仅管理员），`payer` 为排队时的租金付款人
+pub fn cancel_config_change(admin: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::CancelConfigChange {
+            config: pda::config(),
+            admin,
+            pending_change: pda::pending_config_change(),
+            payer,
+        }
+        .to_account_metas(None),
+        data: instruction::CancelConfigChange {}.data(),
+    }
+}
+
+/// 移交管理员（仅管理员），如移交给治理程序的权限 PDA
+pub fn set_admin(admin: Pubkey, new_admin: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::SetAdmin {
+            config: pda::config(),
+            admin,
+        }
+        .to_account_metas(None),
+        data: instruction::SetAdmin { new_admin }.data(),
+    }
+}
+
+/// 向提供商签发或续期徽章（仅管理员），`expiry` 为 0 表示长期有效
+pub fn issue_badge(
+    admin: Pubkey,
+    payer: Pubkey,
+    provider: Pubkey,
+    badge_type: u8,
+    expiry: i64,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::IssueBadge {
+            config: pda::config(),
+            admin,
+            badge: pda::badge(&provider, badge_type),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::IssueBadge {
+            provider,
+            badge_type,
+            expiry,
+        }
+        .data(),
+    }
+}
+
+/// 撤销提供商徽章（仅管理员），租金退还管理员
+pub fn revoke_badge(admin: Pubkey, provider: &Pubkey, badge_type: u8) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RevokeBadge {
+            config: pda::config(),
+            admin,
+            badge: pda::badge(provider, badge_type),
+        }
+        .to_account_metas(None),
+        data: instruction::RevokeBadge {}.data(),
+    }
+}
+
+/// 将地址列入黑名单（仅管理员），`reason_hash` 为链下处置记录的哈希
+pub fn add_to_blacklist(
+    admin: Pubkey,
+    payer: Pubkey,
+    subject: Pubkey,
+    reason_hash: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::AddToBlacklist {
+            config: pda::config(),
+            admin,
+            blacklist: pda::blacklist(&subject),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::AddToBlacklist {
+            subject,
+            reason_hash,
+        }
+        .data(),
+    }
+}
+
+/// 将地址移出黑名单（仅管理员），租金退还管理员
+pub fn remove_from_blacklist(admin: Pubkey, subject: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RemoveFromBlacklist {
+            config: pda::config(),
+            admin,
+            blacklist: pda::blacklist(subject),
+        }
+        .to_account_metas(None),
+        data: instruction::RemoveFromBlacklist {}.data(),
+    }
+}
+
+/// 创建保险池代币账户（仅管理员）
+pub fn init_insurance(admin: Pubkey, payer: Pubkey, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::InitInsurance {
+            config: pda::config(),
+            insurance_vault: pda::insurance_vault(),
+            mint,
+            admin,
+            payer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitInsurance {}.data(),
+    }
+}
+
+/// 从保险池赔付买家（仅管理员）
+pub fn claim_insurance(
+    admin: Pubkey,
+    payer: Pubkey,
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    mint: Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ClaimInsurance {
+            config: pda::config(),
+            admin,
+            escrow: escrow_address,
+            claim: pda::insurance_claim(&escrow_address),
+            insurance_vault: pda::insurance_vault(),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            payer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimInsurance { amount }.data(),
+    }
+}
+
+/// 创建费用账本和共享费用金库（仅管理员）
+pub fn init_fee_ledger(admin: Pubkey, payer: Pubkey, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::InitFeeLedger {
+            config: pda::config(),
+            fee_ledger: pda::fee_ledger(),
+            fee_vault: pda::fee_vault(),
+            mint,
+            admin,
+            payer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitFeeLedger {}.data(),
+    }
+}
+
+/// 拆分费用金库中累计的平台费（任何人可调用）
+pub fn settle_fees(config: &Config, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::SettleFees {
+            config: pda::config(),
+            fee_ledger: pda::fee_ledger(),
+            fee_vault: pda::fee_vault(),
+            treasury_token_account: config.treasury,
+            rewards_pool_token_account: config.rewards_pool,
+            insurance_vault: insurance_vault(config),
+            mint,
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SettleFees {}.data(),
+    }
+}
+
+/// 为托管 PDA 创建（幂等）关联代币账户，需在 `fund_escrow` 之前执行
+pub fn create_escrow_token_account(payer: Pubkey, escrow: Pubkey, mint: Pubkey) -> Instruction {
+    spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        &payer,
+        &escrow,
+        &mint,
+        &token::ID,
+    )
+}
+
+/// 创建托管（只记录条款，托管处于 Created 状态）
+pub fn create_escrow(params: CreateEscrowParams) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: create_escrow_accounts(&params).to_account_metas(None),
+        data: instruction::CreateEscrow {
+            amount: params.amount,
+            request_id: params.request_id,
+            proposal_id: params.proposal_id,
+            coupon_code: params.coupon_code,
+        }
+        .data(),
+    }
+}
+
+/// 按已完成的托管再次购买，新托管使用 `request_id` 并复制原托管的提供商、金额等条款
+///
+/// `with_delivery_terms` 为原托管设置过交付期限时传 `true`，新托管按相同时长起算截止时间
+pub fn recreate_escrow(
+    previous_address: Pubkey,
+    previous: &Escrow,
+    config: &Config,
+    request_id: String,
+    provider_sequence: u64,
+    with_delivery_terms: bool,
+) -> Instruction {
+    let escrow = pda::escrow(&previous.buyer, &request_id);
+    let (buyer_attestation, provider_attestation) =
+        compliance_attestations(config, &previous.buyer, &previous.provider);
+
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RecreateEscrow {
+            escrow,
+            previous_escrow: previous_address,
+            buyer: previous.buyer,
+            config: pda::config(),
+            buyer_stats: pda::buyer_stats(&previous.buyer),
+            provider_stats: pda::provider_stats(&previous.provider),
+            provider_escrow_index: pda::provider_escrow_index(
+                &previous.provider,
+                provider_sequence,
+            ),
+            protocol_stats: pda::protocol_stats(),
+            provider_profile: None,
+            previous_delivery_terms: with_delivery_terms
+                .then(|| pda::delivery_terms(&previous_address)),
+            delivery_terms: with_delivery_terms.then(|| pda::delivery_terms(&escrow)),
+            system_program: system_program::ID,
+            buyer_attestation,
+            provider_attestation,
+            provider_badge: provider_badge(config, &previous.provider, previous.amount),
+            buyer_blacklist: pda::blacklist(&previous.buyer),
+            provider_blacklist: pda::blacklist(&previous.provider),
+        }
+        .to_account_metas(None),
+        data: instruction::RecreateEscrow { request_id }.data(),
+    }
+}
+
+/// 保存托管条款模板（模板带交付期限时 `terms.provider` 须共同签名）
+pub fn save_template(
+    owner: Pubkey,
+    template_id: u64,
+    mint: Pubkey,
+    terms: TemplateTerms,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::SaveTemplate {
+            template: pda::template(&owner, template_id),
+            owner,
+            config: pda::config(),
+            mint,
+            provider: terms.provider.filter(|_| terms.delivery_window > 0),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SaveTemplate { template_id, terms }.data(),
+    }
+}
+
+/// 删除模板（仅所有者）
+pub fn close_template(owner: Pubkey, template_id: u64) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::CloseTemplate {
+            template: pda::template(&owner, template_id),
+            owner,
+        }
+        .to_account_metas(None),
+        data: instruction::CloseTemplate {}.data(),
+    }
+}
+
+/// 按模板创建托管，同时按模板铸币创建托管代币账户（无需再调用 `create_escrow_token_account`）
+///
+/// 模板带平台费减免时 `params.coupon` 须为空
+pub fn create_escrow_from_template(
+    params: CreateEscrowParams,
+    template_address: Pubkey,
+    template: &Template,
+) -> Instruction {
+    let escrow = pda::escrow(&params.buyer, &params.request_id);
+
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::CreateEscrowFromTemplate {
+            create: create_escrow_accounts(&params),
+            template: template_address,
+            escrow_token_account: get_associated_token_address(&escrow, &template.mint),
+            template_discount: template.terms.discount.map(|_| pda::discount(&escrow)),
+            delivery_terms: (template.terms.delivery_window > 0)
+                .then(|| pda::delivery_terms(&escrow)),
+            units: (template.terms.milestones > 1).then(|| pda::units(&escrow)),
+            token_program: token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::CreateEscrowFromTemplate {
+            amount: params.amount,
+            request_id: params.request_id,
+            proposal_id: params.proposal_id,
+            coupon_code: params.coupon_code,
+        }
+        .data(),
+    }
+}
+
+fn create_escrow_accounts(params: &CreateEscrowParams) -> accounts::CreateEscrow {
+    let escrow = pda::escrow(&params.buyer, &params.request_id);
+
+    accounts::CreateEscrow {
+        escrow,
+        buyer: params.buyer,
+        config: pda::config(),
+        buyer_stats: pda::buyer_stats(&params.buyer),
+        provider: params.provider,
+        provider_stats: pda::provider_stats(&params.provider),
+        provider_escrow_index: pda::provider_escrow_index(
+            &params.provider,
+            params.provider_sequence,
+        ),
+        protocol_stats: pda::protocol_stats(),
+        provider_profile: None,
+        request: None,
+        proposal: None,
+        restriction: None,
+        platform: params.platform,
+        system_program: system_program::ID,
+        coupon: params.coupon,
+        discount: params.coupon.map(|_| pda::discount(&escrow)),
+        coupon_redeemer: params.coupon.map(|_| pda::coupon_redeemer()),
+        coupons_program: params.coupon.map(|_| datanexus_coupons::ID),
+        access_mint: None,
+        buyer_attestation: params.buyer_attestation,
+        provider_attestation: params.provider_attestation,
+        provider_badge: params.provider_badge,
+        buyer_blacklist: pda::blacklist(&params.buyer),
+        provider_blacklist: pda::blacklist(&params.provider),
+        mint: params.mint,
+    }
+}
+
+/// 买家为托管充值
+///
+/// `buyer_token_account` 为付款账户，为 `None` 时使用买家 `mint` 的关联代币账户
+pub fn fund_escrow(
+    escrow: Pubkey,
+    buyer: Pubkey,
+    mint: Pubkey,
+    buyer_token_account: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: fund_escrow_accounts(escrow, buyer, mint, buyer_token_account)
+            .to_account_metas(None),
+        data: instruction::FundEscrow {}.data(),
+    }
+}
+
+/// 先用 Jupiter 把买家选择的代币兑换为 `mint`，再为托管充值
+///
+/// `swap` 为 Jupiter `/swap-instructions` 返回的路由指令，输出账户须为买家 `mint` 的关联代币账户；
+/// 路由账户较多，通常需配合 Jupiter 的地址查找表以 v0 交易发送
+pub fn fund_escrow_with_swap(
+    escrow: Pubkey,
+    buyer: Pubkey,
+    mint: Pubkey,
+    swap: Instruction,
+) -> Instruction {
+    let mut accounts = accounts::FundEscrowWithSwap {
+        fund: fund_escrow_accounts(escrow, buyer, mint, None),
+        jupiter_program: JUPITER_PROGRAM_ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(swap.accounts);
+
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts,
+        data: instruction::FundEscrowWithSwap {
+            swap_data: swap.data,
+        }
+        .data(),
+    }
+}
+
+fn fund_escrow_accounts(
+    escrow: Pubkey,
+    buyer: Pubkey,
+    mint: Pubkey,
+    buyer_token_account: Option<Pubkey>,
+) -> accounts::FundEscrow {
+    accounts::FundEscrow {
+        escrow,
+        buyer,
+        buyer_stats: pda::buyer_stats(&buyer),
+        protocol_stats: pda::protocol_stats(),
+        quote: pda::quote(&escrow),
+        buyer_token_account: buyer_token_account
+            .unwrap_or_else(|| get_associated_token_address(&buyer, &mint)),
+        escrow_token_account: get_associated_token_address(&escrow, &mint),
+        mint,
+        token_program: token::ID,
+    }
+}
+
+/// 提供商标记已交付
+///
+/// 传入 `chunks`（见 `chunks::ChunkTree::manifest`）时同时记录数据块清单
+pub fn mark_delivered(
+    escrow: Pubkey,
+    provider: Pubkey,
+    delivery_hash: [u8; 32],
+    chunks: Option<ChunkManifestParams>,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::MarkDelivered {
+            escrow,
+            provider,
+            provider_stats: pda::provider_stats(&provider),
+            config: pda::config(),
+            provider_bond: Some(pda::provider_bond(&provider)),
+            delivery_oracle: pda::delivery_oracle(&escrow),
+            instructions_sysvar: Some(sysvar::instructions::ID),
+            key_delivery: pda::key_delivery(&escrow),
+            delivery_commitment: pda::delivery_commitment(&escrow),
+            chunk_manifest: chunks.is_some().then(|| pda::chunk_manifest(&escrow)),
+            provider_blacklist: pda::blacklist(&provider),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::MarkDelivered {
+            delivery_hash,
+            chunks,
+        }
+        .data(),
+    }
+}
+
+/// 拒收说明的哈希：`sha256(说明)`，说明本身在链下传递给提供商
+pub fn rejection_reason_hash(reason: &str) -> [u8; 32] {
+    solana_sha256_hasher::hash(reason.as_bytes()).to_bytes()
+}
+
+/// 黑名单处置记录的哈希：`sha256(记录)`，记录本身由平台在链下保存
+pub fn blacklist_reason_hash(reason: &str) -> [u8; 32] {
+    solana_sha256_hasher::hash(reason.as_bytes()).to_bytes()
+}
+
+/// 协商消息的哈希：`sha256(消息)`，消息本身在链下保存
+pub fn message_hash(message: &[u8]) -> [u8; 32] {
+    solana_sha256_hasher::hash(message).to_bytes()
+}
+
+/// 买家或提供商（`author`）把一条协商消息的哈希追加到协商记录
+pub fn log_message(escrow_address: Pubkey, author: Pubkey, message_hash: [u8; 32]) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::LogMessage {
+            escrow: escrow_address,
+            author,
+            message_log: pda::message_log(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::LogMessage { message_hash }.data(),
+    }
+}
+
+/// 买家拒收交付，托管进入 Rejected 等待提供商重新交付
+pub fn reject_delivery(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    reason_hash: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RejectDelivery {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            rejection: pda::rejection(&escrow_address),
+            challenge: pda::challenge(&escrow_address),
+            dataset_proof: pda::dataset_proof(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RejectDelivery { reason_hash }.data(),
+    }
+}
+
+/// 拒收后提供商逾期未重新交付，全额退款给买家（任何人可调用）
+pub fn expire_rejection(escrow_address: Pubkey, escrow: &Escrow, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ExpireRejection {
+            escrow: escrow_address,
+            rejection: pda::rejection(&escrow_address),
+            provider_stats: pda::provider_stats(&escrow.provider),
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            config: pda::config(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            mint,
+            token_program: token::ID,
+            memo_program: Some(MEMO_PROGRAM_ID),
+        }
+        .to_account_metas(None),
+        data: instruction::ExpireRejection {}.data(),
+    }
+}
+
+/// 买家追加提前交付奖励金（交付前），`deadline` 前交付时随释放付给提供商
+pub fn fund_bonus(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    mint: Pubkey,
+    amount: u64,
+    deadline: i64,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::FundBonus {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            bonus: pda::bonus(&escrow_address),
+            bonus_vault: pda::bonus_vault(&escrow_address),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            mint,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::FundBonus { amount, deadline }.data(),
+    }
+}
+
+/// 托管未经确认就结束时，把未结算的奖励金退还买家（任何人可调用）
+pub fn reclaim_bonus(escrow_address: Pubkey, escrow: &Escrow, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ReclaimBonus {
+            escrow: escrow_address,
+            bonus: pda::bonus(&escrow_address),
+            bonus_vault: pda::bonus_vault(&escrow_address),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            mint,
+            config: pda::config(),
+            token_program: token::ID,
+            memo_program: Some(MEMO_PROGRAM_ID),
+        }
+        .to_account_metas(None),
+        data: instruction::ReclaimBonus {}.data(),
+    }
+}
+
+/// 买家要求交付须经预言机证明（交付前）
+pub fn require_delivery_attestation(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    oracle: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RequireDeliveryAttestation {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            delivery_oracle: pda::delivery_oracle(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RequireDeliveryAttestation { oracle }.data(),
+    }
+}
+
+/// 买家要求提供商交付用 `buyer_key`（X25519 公钥）加密的解密密钥（交付前）
+pub fn require_key_delivery(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    buyer_key: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RequireKeyDelivery {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            key_delivery: pda::key_delivery(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RequireKeyDelivery { buyer_key }.data(),
+    }
+}
+
+/// 提供商提交加密的解密密钥，需在 `mark_delivered` 之前
+pub fn deliver_key(escrow_address: Pubkey, escrow: &Escrow, encrypted_key: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::DeliverKey {
+            escrow: escrow_address,
+            provider: escrow.provider,
+            key_delivery: pda::key_delivery(&escrow_address),
+        }
+        .to_account_metas(None),
+        data: instruction::DeliverKey { encrypted_key }.data(),
+    }
+}
+
+/// 解析 `ipfs://<CID>`、`ar://<交易 ID>` 或 `shdw://<存储账户>/<对象路径>` 形式的存储 URI（格式由程序校验）
+pub fn parse_storage_uri(uri: &str) -> Option<StorageRef> {
+    let (protocol, id) = if let Some(id) = uri.strip_prefix("ipfs://") {
+        (StorageProtocol::Ipfs, id)
+    } else if let Some(id) = uri.strip_prefix("ar://") {
+        (StorageProtocol::Arweave, id)
+    } else if let Some(object) = uri.strip_prefix("shdw://") {
+        let (storage_account, path) = object.split_once('/')?;
+        let storage_account = storage_account.parse().ok()?;
+        (StorageProtocol::ShadowDrive { storage_account }, path)
+    } else {
+        return None;
+    };
+
+    Some(StorageRef {
+        protocol,
+        id: id.to_string(),
+    })
+}
+
+/// 交付承诺：`sha256(存储 URI || salt)`
+pub fn delivery_commitment(storage: &StorageRef, salt: &[u8; 32]) -> [u8; 32] {
+    solana_sha256_hasher::hashv(&[storage.uri().as_bytes(), salt]).to_bytes()
+}
+
+/// 提供商承诺交付内容（交付前），`commitment` 见 `delivery_commitment`
+pub fn commit_delivery(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    commitment: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::CommitDelivery {
+            escrow: escrow_address,
+            provider: escrow.provider,
+            delivery_commitment: pda::delivery_commitment(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::CommitDelivery { commitment }.data(),
+    }
+}
+
+/// 提供商揭示承诺的存储位置和 salt，需在 `mark_delivered` 之前
+pub fn reveal_delivery(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    storage: StorageRef,
+    salt: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RevealDelivery {
+            escrow: escrow_address,
+            provider: escrow.provider,
+            delivery_commitment: pda::delivery_commitment(&escrow_address),
+            storage_account: match storage.protocol {
+                StorageProtocol::ShadowDrive { storage_account } => Some(storage_account),
+                _ => None,
+            },
+        }
+        .to_account_metas(None),
+        data: instruction::RevealDelivery { storage, salt }.data(),
+    }
+}
+
+/// 预言机需要签名的交付证明消息：`托管地址 || 交付哈希`
+pub fn delivery_attestation_message(escrow: &Pubkey, delivery_hash: &[u8; 32]) -> [u8; 64] {
+    let mut message = [0u8; 64];
+    message[..32].copy_from_slice(escrow.as_ref());
+    message[32..].copy_from_slice(delivery_hash);
+    message
+}
+
+/// 预言机交付证明的 Ed25519 签名校验指令，须紧接在 `mark_delivered` 之前
+///
+/// 公钥、签名和消息依次放在指令数据中，偏移量的指令索引均指向本条指令
+pub fn delivery_attestation(
+    oracle: Pubkey,
+    signature: [u8; 64],
+    escrow: &Pubkey,
+    delivery_hash: &[u8; 32],
+) -> Instruction {
+    ed25519_verify(
+        oracle,
+        signature,
+        &delivery_attestation_message(escrow, delivery_hash),
+    )
+}
+
+/// 单签名的 Ed25519 签名校验指令，公钥、签名和消息依次放在指令数据中
+pub(crate) fn ed25519_verify(
+    public_key: Pubkey,
+    signature: [u8; 64],
+    message: &[u8],
+) -> Instruction {
+    const HEADER_LEN: u16 = 2 + 7 * 2;
+    const PUBLIC_KEY_OFFSET: u16 = HEADER_LEN;
+    const SIGNATURE_OFFSET: u16 = PUBLIC_KEY_OFFSET + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+
+    let mut data = vec![1, 0];
+    for value in [
+        SIGNATURE_OFFSET,
+        u16::MAX,
+        PUBLIC_KEY_OFFSET,
+        u16::MAX,
+        MESSAGE_OFFSET,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(public_key.as_ref());
+    data.extend_from_slice(&signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ED25519_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// 买家确认并释放资金（不铸造收据和访问凭证）
+///
+/// 托管记录过美元估值或按美元计价时须传入同一价格源的 Pyth `PriceUpdateV2` 账户；
+/// `quality_score`（1–5）为买家对本次交付的评分，经 CPI 记入信誉程序（每个托管一次），
+/// `tip_amount` 为从买家关联代币账户额外给提供商的小费；逾期交付的退款转入买家的关联代币账户
+pub fn confirm_and_release(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    mint: Pubkey,
+    price_update: Option<Pubkey>,
+    quality_score: Option<u8>,
+    tip_amount: Option<u64>,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: confirm_and_release_accounts(
+            escrow_address,
+            escrow,
+            config,
+            mint,
+            price_update,
+            quality_score,
+        )
+        .to_account_metas(None),
+        data: instruction::ConfirmAndRelease {
+            quality_score,
+            tip_amount,
+            swap_data: None,
+        }
+        .data(),
+    }
+}
+
+/// 买家确认并释放资金，同时为买家铸造收据 NFT 并写入收据记录
+///
+/// 收据元数据 URI 指向 `[b"receipt_record", escrow]`，记录实际释放金额、交付哈希和各阶段时间
+pub fn confirm_and_release_with_receipt(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    mint: Pubkey,
+    price_update: Option<Pubkey>,
+) -> Instruction {
+    let receipt_mint = pda::receipt_mint(&escrow_address);
+    let mut accounts =
+        confirm_and_release_accounts(escrow_address, escrow, config, mint, price_update, None);
+    accounts.receipt_record = Some(pda::receipt_record(&escrow_address));
+    accounts.receipt_mint = Some(receipt_mint);
+    accounts.buyer_receipt_account =
+        Some(get_associated_token_address(&escrow.buyer, &receipt_mint));
+    accounts.receipt_metadata =
+        Some(mpl_token_metadata::accounts::Metadata::find_pda(&receipt_mint).0);
+    accounts.receipt_master_edition =
+        Some(mpl_token_metadata::accounts::MasterEdition::find_pda(&receipt_mint).0);
+    accounts.token_metadata_program = Some(mpl_token_metadata::ID);
+    accounts.associated_token_program = Some(spl_associated_token_account::ID);
+    accounts.system_program = Some(system_program::ID);
+    accounts.rent = Some(sysvar::rent::ID);
+
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::ConfirmAndRelease {
+            quality_score: None,
+            tip_amount: None,
+            swap_data: None,
+        }
+        .data(),
+    }
+}
+
+/// 买家确认并释放资金，提供商份额经 Jupiter 兑换为约定的 `settle_mint` 后支付
+///
+/// 托管须已调用 `set_settlement_swap` 约定兑换结算；`swap` 为 Jupiter `/swap-instructions`
+/// 返回的 ExactIn 路由指令，转账权限为托管 PDA，输入为托管代币账户中的提供商份额，
+/// 输出为提供商 `settle_mint` 的关联代币账户。路由账户较多，通常需配合地址查找表以 v0 交易发送
+pub fn confirm_and_release_with_swap(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    mint: Pubkey,
+    price_update: Option<Pubkey>,
+    settle_mint: Pubkey,
+    swap: Instruction,
+) -> Instruction {
+    let mut accounts =
+        confirm_and_release_accounts(escrow_address, escrow, config, mint, price_update, None);
+    accounts.provider_settle_token_account =
+        Some(get_associated_token_address(&escrow.provider, &settle_mint));
+    accounts.jupiter_program = Some(JUPITER_PROGRAM_ID);
+    let mut accounts = accounts.to_account_metas(None);
+    accounts.extend(swap.accounts);
+
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts,
+        data: instruction::ConfirmAndRelease {
+            quality_score: None,
+            tip_amount: None,
+            swap_data: Some(swap.data),
+        }
+        .data(),
+    }
+}
+
+fn confirm_and_release_accounts(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    mint: Pubkey,
+    price_update: Option<Pubkey>,
+    quality_score: Option<u8>,
+) -> accounts::ConfirmAndRelease {
+    accounts::ConfirmAndRelease {
+        escrow: escrow_address,
+        buyer: escrow.buyer,
+        buyer_stats: pda::buyer_stats(&escrow.buyer),
+        provider_stats: pda::provider_stats(&escrow.provider),
+        config: pda::config(),
+        protocol_stats: pda::protocol_stats(),
+        escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+        provider_token_account: get_associated_token_address(&escrow.provider, &mint),
+        buyer_token_account: Some(get_associated_token_address(&escrow.buyer, &mint)),
+        treasury_token_account: Some(config.treasury),
+        rewards_pool_token_account: Some(config.rewards_pool),
+        insurance_vault: insurance_vault(config),
+        fee_ledger: fee_ledger(config),
+        fee_vault: fee_vault(config),
+        delivery_terms: pda::delivery_terms(&escrow_address),
+        discount: pda::discount(&escrow_address),
+        bonus: pda::bonus(&escrow_address),
+        bonus_vault: pda::bonus_vault(&escrow_address),
+        valuation: pda::valuation(&escrow_address),
+        price_update,
+        usd_pricing: pda::usd_pricing(&escrow_address),
+        settlement_swap: pda::settlement_swap(&escrow_address),
+        provider_settle_token_account: None,
+        jupiter_program: None,
+        delivery_commitment: pda::delivery_commitment(&escrow_address),
+        mint,
+        token_program: token::ID,
+        memo_program: Some(MEMO_PROGRAM_ID),
+        buyer_attestation: compliance_attestation(config, COMPLIANCE_BUYER, &escrow.buyer),
+        provider_attestation: compliance_attestation(config, COMPLIANCE_PROVIDER, &escrow.provider),
+        receipt_record: None,
+        receipt_mint: None,
+        buyer_receipt_account: None,
+        receipt_metadata: None,
+        receipt_master_edition: None,
+        token_metadata_program: None,
+        associated_token_program: None,
+        system_program: quality_score.is_some().then_some(system_program::ID),
+        rent: None,
+        receipt_tree_config: None,
+        receipt_merkle_tree: None,
+        bubblegum_program: None,
+        log_wrapper: None,
+        compression_program: None,
+        access_mint: None,
+        buyer_access_account: None,
+        access_authority: None,
+        access_token_program: None,
+        access_window: pda::access_window(&escrow_address),
+        rating: quality_score.map(|_| pda::rating(&escrow_address)),
+        provider_reputation: quality_score.map(|_| pda::provider_reputation(&escrow.provider)),
+        reputation_program: quality_score.map(|_| REPUTATION_PROGRAM_ID),
+        sales_history: pda::sales_history(&escrow.provider),
+        restriction: pda::restriction(&escrow_address),
+    }
+}
+
+/// 买家按 Pyth 价格记录托管的美元价值（交付前）
+pub fn record_valuation(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    mint: Pubkey,
+    price_update: Pubkey,
+    feed_id: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RecordValuation {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            valuation: pda::valuation(&escrow_address),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            mint,
+            price_update,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RecordValuation { feed_id }.data(),
+    }
+}
+
+/// 把托管改为按美元计价（交付前，买家和提供商都须签名），`usd_cents` 为以美分计的成交价
+///
+/// 托管中的代币数量是支付上限，释放时按 Pyth 价格折算，多余部分退还买家
+pub fn denominate_in_usd(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    mint: Pubkey,
+    price_update: Pubkey,
+    feed_id: [u8; 32],
+    usd_cents: u64,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::DenominateInUsd {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            usd_pricing: pda::usd_pricing(&escrow_address),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            mint,
+            price_update,
+            quote: pda::quote(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::DenominateInUsd { feed_id, usd_cents }.data(),
+    }
+}
+
+/// 约定释放时以 `settle_mint` 给提供商结算（创建后、注资前，买家和提供商都须签名）
+///
+/// `min_out` 为整笔托管金额至少应兑换到的结算代币数量，释放见 `confirm_and_release_with_swap`
+pub fn set_settlement_swap(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    mint: Pubkey,
+    settle_mint: Pubkey,
+    min_out: u64,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::SetSettlementSwap {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            settlement_swap: pda::settlement_swap(&escrow_address),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            settle_mint,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetSettlementSwap { min_out }.data(),
+    }
+}
+
+/// 买家发起争议（按配置缴纳争议保证金）
+///
+/// `chunks` 为有问题的数据块及其证明（见 `chunks::ChunkTree::proof`），整体争议时传空
+pub fn raise_dispute(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    mint: Pubkey,
+    chunks: Vec<ChunkProof>,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RaiseDispute {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            provider_stats: pda::provider_stats(&escrow.provider),
+            protocol_stats: pda::protocol_stats(),
+            config: pda::config(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            mint,
+            token_program: token::ID,
+            chunk_manifest: (!chunks.is_empty()).then(|| pda::chunk_manifest(&escrow_address)),
+            dispute_record: pda::dispute_record(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RaiseDispute { chunks }.data(),
+    }
+}
+
+/// 买家申请取消已充值但尚未交付的托管，进入异议期
+pub fn cancel(escrow_address: Pubkey, escrow: &Escrow) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::Cancel {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            cancel_request: pda::cancel_request(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Cancel {}.data(),
+    }
+}
+
+/// 提供商在异议期内反对取消，托管转入争议
+pub fn contest_cancel(escrow_address: Pubkey, escrow: &Escrow) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ContestCancel {
+            escrow: escrow_address,
+            provider: escrow.provider,
+            buyer: escrow.buyer,
+            cancel_request: pda::cancel_request(&escrow_address),
+            protocol_stats: pda::protocol_stats(),
+            dispute_record: pda::dispute_record(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ContestCancel {}.data(),
+    }
+}
+
+/// 异议期满后执行取消，全额退款给买家（任何人可调用）
+pub fn execute_cancel(escrow_address: Pubkey, escrow: &Escrow, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ExecuteCancel {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            cancel_request: pda::cancel_request(&escrow_address),
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            config: pda::config(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            mint,
+            token_program: token::ID,
+            memo_program: Some(MEMO_PROGRAM_ID),
+        }
+        .to_account_metas(None),
+        data: instruction::ExecuteCancel {}.data(),
+    }
+}
+
+/// 回收超过存活时间仍未注资的托管，连同已创建的托管代币账户关闭，租金退还买家（任何人可调用）
+///
+/// `mint` 须为托管记录的铸币（`Escrow::mint`）；托管代币账户有余额时 `sweep` 传 `true`，
+/// 余额退到买家的关联代币账户（须已创建）
+pub fn reclaim_stale(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    mint: Pubkey,
+    sweep: bool,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ReclaimStale {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            config: pda::config(),
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            mint,
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            buyer_token_account: sweep.then(|| get_associated_token_address(&escrow.buyer, &mint)),
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ReclaimStale {}.data(),
+    }
+}
+
+/// 平台将争议中的托管全额退款给买家
+pub fn refund(escrow_address: Pubkey, escrow: &Escrow, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::Refund {
+            escrow: escrow_address,
+            authority: escrow.platform,
+            provider_stats: pda::provider_stats(&escrow.provider),
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            protocol_stats: pda::protocol_stats(),
+            config: pda::config(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            mint,
+            token_program: token::ID,
+            dispute_record: pda::dispute_record(&escrow_address),
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            memo_program: Some(MEMO_PROGRAM_ID),
+        }
+        .to_account_metas(None),
+        data: instruction::Refund {}.data(),
+    }
+}
+
+/// 平台因受限的类别或司法辖区组合阻止释放，全额退款给买家并记录原因
+pub fn block_release(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    mint: Pubkey,
+    reason: String,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::BlockRelease {
+            escrow: escrow_address,
+            platform: escrow.platform,
+            restriction: pda::restriction(&escrow_address),
+            provider_stats: pda::provider_stats(&escrow.provider),
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            protocol_stats: pda::protocol_stats(),
+            config: pda::config(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            mint,
+            token_program: token::ID,
+            dispute_record: pda::dispute_record(&escrow_address),
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            memo_program: Some(MEMO_PROGRAM_ID),
+        }
+        .to_account_metas(None),
+        data: instruction::BlockRelease { reason }.data(),
+    }
+}
+
+/// 平台作出可上诉的裁决（配置了上诉期时使用）
+pub fn rule_dispute(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    payer: Pubkey,
+    refund_to_buyer: bool,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RuleDispute {
+            escrow: escrow_address,
+            ruling: pda::ruling(&escrow_address),
+            config: pda::config(),
+            platform: escrow.platform,
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RuleDispute { refund_to_buyer }.data(),
+    }
+}
+
+/// 败诉方在上诉期内上诉（按配置缴纳上诉保证金）
+pub fn appeal(escrow_address: Pubkey, appellant: Pubkey, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::Appeal {
+            escrow: escrow_address,
+            ruling: pda::ruling(&escrow_address),
+            dispute_record: pda::dispute_record(&escrow_address),
+            appellant,
+            config: pda::config(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            appellant_token_account: get_associated_token_address(&appellant, &mint),
+            mint,
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Appeal {}.data(),
+    }
+}
+
+/// 平台裁决争议（不铸造访问凭证）
+///
+/// 托管处于 Ruled 状态时按已有裁决执行，需在上诉期满后调用；
+/// 按美元计价的托管释放给提供商时须传入同一价格源的 Pyth `PriceUpdateV2` 账户
+pub fn resolve_dispute(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    mint: Pubkey,
+    price_update: Option<Pubkey>,
+    refund_to_buyer: bool,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ResolveDispute {
+            escrow: escrow_address,
+            platform: escrow.platform,
+            provider_stats: pda::provider_stats(&escrow.provider),
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            config: pda::config(),
+            protocol_stats: pda::protocol_stats(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            provider_token_account: get_associated_token_address(&escrow.provider, &mint),
+            treasury_token_account: Some(config.treasury),
+            rewards_pool_token_account: Some(config.rewards_pool),
+            insurance_vault: insurance_vault(config),
+            fee_ledger: fee_ledger(config),
+            fee_vault: fee_vault(config),
+            ruling: (escrow.status() == EscrowStatus::Ruled).then(|| pda::ruling(&escrow_address)),
+            discount: pda::discount(&escrow_address),
+            price_update,
+            usd_pricing: pda::usd_pricing(&escrow_address),
+            mint,
+            token_program: token::ID,
+            memo_program: Some(MEMO_PROGRAM_ID),
+            access_mint: None,
+            buyer_access_account: None,
+            access_authority: None,
+            access_token_program: None,
+            access_window: pda::access_window(&escrow_address),
+            sales_history: pda::sales_history(&escrow.provider),
+            dispute_record: pda::dispute_record(&escrow_address),
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            restriction: pda::restriction(&escrow_address),
+        }
+        .to_account_metas(None),
+        data: instruction::ResolveDispute { refund_to_buyer }.data(),
+    }
+}
+
+/// 提供商设置访问凭证有效期（交付前，`duration` 为秒数）
+pub fn set_access_window(escrow_address: Pubkey, escrow: &Escrow, duration: i64) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::SetAccessWindow {
+            escrow: escrow_address,
+            provider: escrow.provider,
+            access_window: pda::access_window(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetAccessWindow { duration }.data(),
+    }
+}
+
+/// 买卖双方约定交付截止时间和逾期罚金（交付前，买家和提供商都须签名）
+pub fn set_delivery_deadline(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    deadline: i64,
+    late_penalty: u64,
+    penalty_recipient: PenaltyRecipient,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::SetDeliveryDeadline {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            delivery_terms: pda::delivery_terms(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetDeliveryDeadline {
+            deadline,
+            late_penalty,
+            penalty_recipient,
+        }
+        .data(),
+    }
+}
+
+/// 提供商对未充值的托管报价（买家和提供商都须签名）
+///
+/// 充值时按报价金额转入，`expires_at` 后不能再充值
+pub fn quote_price(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    price: u64,
+    expires_at: i64,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::QuotePrice {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            quote: pda::quote(&escrow_address),
+            units: pda::units(&escrow_address),
+            downloads: pda::downloads(&escrow_address),
+            usd_pricing: pda::usd_pricing(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::QuotePrice { price, expires_at }.data(),
+    }
+}
+
+/// 买家把未充值的托管设为按件计价，`unit_price × quantity` 须等于托管金额
+pub fn set_unit_pricing(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    unit_price: u64,
+    quantity: u64,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::SetUnitPricing {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            units: pda::units(&escrow_address),
+            quote: pda::quote(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetUnitPricing {
+            unit_price,
+            quantity,
+        }
+        .data(),
+    }
+}
+
+/// 提供商交付 `units` 件并按件结算（买家和提供商都须签名），货款转入提供商的关联代币账户
+pub fn deliver_units(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    mint: Pubkey,
+    units: u64,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::DeliverUnits {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            units: pda::units(&escrow_address),
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            provider_stats: pda::provider_stats(&escrow.provider),
+            config: pda::config(),
+            protocol_stats: pda::protocol_stats(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            provider_token_account: get_associated_token_address(&escrow.provider, &mint),
+            treasury_token_account: Some(config.treasury),
+            rewards_pool_token_account: Some(config.rewards_pool),
+            insurance_vault: insurance_vault(config),
+            fee_ledger: fee_ledger(config),
+            fee_vault: fee_vault(config),
+            mint,
+            token_program: token::ID,
+            memo_program: Some(MEMO_PROGRAM_ID),
+            sales_history: pda::sales_history(&escrow.provider),
+            restriction: pda::restriction(&escrow_address),
+            provider_blacklist: pda::blacklist(&escrow.provider),
+        }
+        .to_account_metas(None),
+        data: instruction::DeliverUnits { units }.data(),
+    }
+}
+
+/// 提供商开启可验证销售记录，此后完成的销售逐笔追加
+pub fn init_sales_history(provider: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::InitSalesHistory {
+            sales_history: pda::sales_history(&provider),
+            provider,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitSalesHistory {}.data(),
+    }
+}
+
+/// 买家把未充值的托管设为最多下载 `downloads` 次，由 `gateway` 记录下载（买家和提供商都须签名）
+pub fn set_download_cap(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    downloads: u64,
+    gateway: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::SetDownloadCap {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            downloads: pda::downloads(&escrow_address),
+            quote: pda::quote(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetDownloadCap { downloads, gateway }.data(),
+    }
+}
+
+/// 网关记录一次下载并按剩余次数均摊结算，货款转入提供商的关联代币账户
+///
+/// 网关为程序 PDA 时由网关程序以同样的账户 CPI 调用
+pub fn consume_download(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    mint: Pubkey,
+    gateway: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ConsumeDownload {
+            escrow: escrow_address,
+            gateway,
+            downloads: pda::downloads(&escrow_address),
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            provider_stats: pda::provider_stats(&escrow.provider),
+            config: pda::config(),
+            protocol_stats: pda::protocol_stats(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            provider_token_account: get_associated_token_address(&escrow.provider, &mint),
+            treasury_token_account: Some(config.treasury),
+            rewards_pool_token_account: Some(config.rewards_pool),
+            insurance_vault: insurance_vault(config),
+            fee_ledger: fee_ledger(config),
+            fee_vault: fee_vault(config),
+            mint,
+            token_program: token::ID,
+            memo_program: Some(MEMO_PROGRAM_ID),
+            sales_history: pda::sales_history(&escrow.provider),
+            restriction: pda::restriction(&escrow_address),
+            provider_blacklist: pda::blacklist(&escrow.provider),
+        }
+        .to_account_metas(None),
+        data: instruction::ConsumeDownload {}.data(),
+    }
+}
+
+/// 访问期满后冻结买家的访问凭证账户（任何人可调用）
+///
+/// `access_token_program` 为访问凭证铸币所属的代币程序（SPL Token 或 Token-2022）
+pub fn revoke_expired_access(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    access_window: &AccessWindow,
+    access_token_program: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RevokeExpiredAccess {
+            escrow: escrow_address,
+            access_window: pda::access_window(&escrow_address),
+            access_mint: escrow.access_mint().unwrap_or_default(),
+            buyer_access_account: access_window.token_account,
+            access_authority: pda::access_authority(&escrow.provider),
+            access_token_program,
+        }
+        .to_account_metas(None),
+        data: instruction::RevokeExpiredAccess {}.data(),
+    }
+}
+
+/// 买家要求提供商用零知识证明证实数据集的行数和表结构哈希（交付前）
+pub fn require_dataset_proof(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    verifier: Pubkey,
+    row_count: u64,
+    schema_hash: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RequireDatasetProof {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            dataset_proof: pda::dataset_proof(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RequireDatasetProof {
+            verifier,
+            row_count,
+            schema_hash,
+        }
+        .data(),
+    }
+}
+
+/// 提供商提交数据集属性的零知识证明（交付后），`verifier` 为买家指定的验证程序
+///
+/// 链上 pairing 校验耗费较多计算单元，发送时应提高计算预算
+pub fn submit_dataset_proof(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    verifier: Pubkey,
+    proof: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::SubmitDatasetProof {
+            escrow: escrow_address,
+            provider: escrow.provider,
+            dataset_proof: pda::dataset_proof(&escrow_address),
+            verifier,
+        }
+        .to_account_metas(None),
+        data: instruction::SubmitDatasetProof { proof }.data(),
+    }
+}
+
+/// 买家抽查交付的数据块（见 `chunks::sample_indices`）
+pub fn challenge(escrow_address: Pubkey, escrow: &Escrow, indices: Vec<u32>) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ChallengeChunks {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            chunk_manifest: pda::chunk_manifest(&escrow_address),
+            challenge: pda::challenge(&escrow_address),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Challenge { indices }.data(),
+    }
+}
+
+/// 提供商响应抽查，`proofs` 按抽查顺序排列（见 `chunks::ChunkTree::proof`）
+pub fn respond_challenge(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    proofs: Vec<ChunkProof>,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::RespondChallenge {
+            escrow: escrow_address,
+            provider: escrow.provider,
+            chunk_manifest: pda::chunk_manifest(&escrow_address),
+            challenge: pda::challenge(&escrow_address),
+        }
+        .to_account_metas(None),
+        data: instruction::RespondChallenge { proofs }.data(),
+    }
+}
+
+/// 抽查逾期未响应，全额退款给买家（任何人可调用）
+pub fn expire_challenge(escrow_address: Pubkey, escrow: &Escrow, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ExpireChallenge {
+            escrow: escrow_address,
+            challenge: pda::challenge(&escrow_address),
+            provider_stats: pda::provider_stats(&escrow.provider),
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            config: pda::config(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            mint,
+            token_program: token::ID,
+            memo_program: Some(MEMO_PROGRAM_ID),
+        }
+        .to_account_metas(None),
+        data: instruction::ExpireChallenge {}.data(),
+    }
+}
+
+/// 平台（或陪审团）按有效数据块比例拆分争议，`invalid_chunks` 为判定无效的争议块序号
+///
+/// 按美元计价的托管有有效块时须传入同一价格源的 Pyth `PriceUpdateV2` 账户
+pub fn resolve_dispute_split(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    mint: Pubkey,
+    price_update: Option<Pubkey>,
+    invalid_chunks: Vec<u32>,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::ResolveDisputeSplit {
+            escrow: escrow_address,
+            platform: escrow.platform,
+            chunk_manifest: pda::chunk_manifest(&escrow_address),
+            provider_stats: pda::provider_stats(&escrow.provider),
+            buyer_stats: pda::buyer_stats(&escrow.buyer),
+            config: pda::config(),
+            protocol_stats: pda::protocol_stats(),
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            provider_token_account: get_associated_token_address(&escrow.provider, &mint),
+            treasury_token_account: Some(config.treasury),
+            rewards_pool_token_account: Some(config.rewards_pool),
+            insurance_vault: insurance_vault(config),
+            fee_ledger: fee_ledger(config),
+            fee_vault: fee_vault(config),
+            discount: pda::discount(&escrow_address),
+            price_update,
+            usd_pricing: pda::usd_pricing(&escrow_address),
+            mint,
+            token_program: token::ID,
+            memo_program: Some(MEMO_PROGRAM_ID),
+            sales_history: pda::sales_history(&escrow.provider),
+            dispute_record: pda::dispute_record(&escrow_address),
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            restriction: pda::restriction(&escrow_address),
+        }
+        .to_account_metas(None),
+        data: instruction::ResolveDisputeSplit { invalid_chunks }.data(),
+    }
+}
+
+/// 把任意旧布局的托管账户迁移到当前版本，`payer` 补足扩容所需的租金
+pub fn migrate_escrow(escrow_address: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::MigrateEscrow {
+            escrow: escrow_address,
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::MigrateEscrow {}.data(),
+    }
+}
+
+/// 把旧布局的全局配置迁移到当前布局，`payer` 补足扩容所需的租金
+pub fn migrate_config(payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::MigrateConfig {
+            config: pda::config(),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::MigrateConfig {}.data(),
+    }
+}
+
+/// 管理员迁移买家统计并回填未结托管数
+///
+/// `open_escrows` 须为该买家全部未进入终态的托管地址，可由 `fetch::list_escrows_by_buyer` 查询后筛选
+pub fn migrate_buyer_stats(
+    buyer: Pubkey,
+    admin: Pubkey,
+    payer: Pubkey,
+    open_escrows: &[Pubkey],
+) -> Instruction {
+    let mut accounts = accounts::MigrateBuyerStats {
+        buyer_stats: pda::buyer_stats(&buyer),
+        buyer,
+        config: pda::config(),
+        admin,
+        payer,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        open_escrows
+            .iter()
+            .map(|escrow| AccountMeta::new_readonly(*escrow, false)),
+    );
+
+    Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts,
+        data: instruction::MigrateBuyerStats {}.data(),
+    }
+}
+
+/// 把按 SPL Token 构造的托管指令改为 Token-2022 铸币 `mint` 使用
+///
+/// 代币程序替换为 Token-2022，指令中各地址的 `mint` 关联代币账户替换为 Token-2022 下的关联代币账户，
+/// 并把铸币转账钩子需要的额外账户（钩子程序、额外账户元数据 PDA 及其声明的账户）追加为 remaining accounts。
+/// 适用于 `create_escrow_token_account`、`create_escrow_from_template`、`fund_escrow`、
+/// `confirm_and_release`、`deliver_units`、`consume_download`、`raise_dispute`、
+/// `cancel`、`refund`、`appeal`、`resolve_dispute`、`resolve_dispute_split` 和 `expire_challenge`；
+/// 收据、访问凭证、保险池仍只支持 SPL Token
+pub fn with_token_2022(
+    mut instruction: Instruction,
+    mint: Pubkey,
+    hook_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let owners: Vec<Pubkey> = instruction
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect();
+    for meta in &mut instruction.accounts {
+        if meta.pubkey == token::ID {
+            meta.pubkey = token_2022::ID;
+        } else if let Some(owner) = owners
+            .iter()
+            .find(|owner| get_associated_token_address(owner, &mint) == meta.pubkey)
+        {
+            meta.pubkey =
+                get_associated_token_address_with_program_id(owner, &mint, &token_2022::ID);
+        }
+    }
+    instruction.accounts.extend(hook_accounts);
+    instruction
+}
+
+/// 按协议策略参数推导创建托管须传入的买家和提供商合规证明，未要求的一方为 `None`
+pub fn compliance_attestations(
+    config: &Config,
+    buyer: &Pubkey,
+    provider: &Pubkey,
+) -> (Option<Pubkey>, Option<Pubkey>) {
+    (
+        compliance_attestation(config, COMPLIANCE_BUYER, buyer),
+        compliance_attestation(config, COMPLIANCE_PROVIDER, provider),
+    )
+}
+
+/// 按协议策略参数推导创建 `amount` 托管须传入的提供商徽章，未达到门槛时为 `None`
+pub fn provider_badge(config: &Config, provider: &Pubkey, amount: u64) -> Option<Pubkey> {
+    let settings = &config.settings;
+    (settings.badge_threshold > 0 && amount >= settings.badge_threshold)
+        .then(|| pda::badge(provider, settings.required_badge))
+}
+
+fn compliance_attestation(config: &Config, flag: u8, subject: &Pubkey) -> Option<Pubkey> {
+    (config.settings.compliance & flag != 0)
+        .then(|| pda::find_attestation_address(&config.settings, subject).0)
+}
+
+/// 配置了保险比例时传入保险池，否则省略
+fn insurance_vault(config: &Config) -> Option<Pubkey> {
+    (config.settings.insurance_bps > 0).then(pda::insurance_vault)
+}
+
+/// 开启费用账本时传入账本和共享费用金库，否则省略
+fn fee_ledger(config: &Config) -> Option<Pubkey> {
+    config.settings.fee_ledger.then(pda::fee_ledger)
+}
+
+fn fee_vault(config: &Config) -> Option<Pubkey> {
+    config.settings.fee_ledger.then(pda::fee_vault)
+}
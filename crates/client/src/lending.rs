@@ -0,0 +1,163 @@
+//! 收益模式的 Solend 储备
+//!
+//! 托管开启收益模式后资金存放在 Solend 储备中，存入和赎回前须在同一交易中刷新储备。
+//! 这里按 SPL token-lending 的储备布局手工解析，不依赖其 SDK
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token;
+use datanexus_escrow::{accounts, instruction, Config, Escrow, YieldPosition, SOLEND_PROGRAM_ID};
+use solana_rpc_client::rpc_client::RpcClient;
+
+use crate::{pda, ClientError, Result};
+
+/// 借贷程序 `RefreshReserve` 指令序号
+const REFRESH_RESERVE: u8 = 3;
+
+/// 储备账户长度下限（到抵押代币供应账户为止）
+const RESERVE_MIN_LEN: usize = 299;
+
+/// Solend 储备中收益模式用到的地址
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolendReserve {
+    pub address: Pubkey,            // 储备
+    pub lending_market: Pubkey,     // 借贷市场
+    pub liquidity_mint: Pubkey,     // 存入代币（应为托管代币）
+    pub liquidity_supply: Pubkey,   // 储备的存入代币供应账户
+    pub pyth_oracle: Pubkey,        // Pyth 价格账户
+    pub switchboard_oracle: Pubkey, // Switchboard 价格账户
+    pub collateral_mint: Pubkey,    // 抵押代币铸币
+}
+
+impl SolendReserve {
+    /// 从储备账户数据解析
+    ///
+    /// 布局：版本、最后更新（slot、stale）、借贷市场，随后是流动性
+    /// （铸币、精度、供应账户、Pyth、Switchboard、余额、借出、累计利率、市价）和抵押代币铸币
+    pub fn parse(address: Pubkey, data: &[u8]) -> Option<Self> {
+        if data.len() < RESERVE_MIN_LEN {
+            return None;
+        }
+        let key = |offset: usize| Pubkey::try_from(&data[offset..offset + 32]).unwrap();
+
+        Some(Self {
+            address,
+            lending_market: key(10),
+            liquidity_mint: key(42),
+            liquidity_supply: key(75),
+            pyth_oracle: key(107),
+            switchboard_oracle: key(139),
+            collateral_mint: key(227),
+        })
+    }
+
+    /// 借贷市场权限 `[lending_market]`
+    pub fn lending_market_authority(&self) -> Pubkey {
+        Pubkey::find_program_address(&[self.lending_market.as_ref()], &SOLEND_PROGRAM_ID).0
+    }
+}
+
+/// 读取 Solend 储备
+pub fn fetch_reserve(rpc: &RpcClient, address: &Pubkey) -> Result<SolendReserve> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    if account.owner != SOLEND_PROGRAM_ID {
+        return Err(ClientError::InvalidReserve(*address));
+    }
+
+    SolendReserve::parse(*address, &account.data).ok_or(ClientError::InvalidReserve(*address))
+}
+
+/// 刷新储备的利率和价格，存入和赎回前必须在同一交易中执行
+pub fn refresh_reserve(reserve: &SolendReserve) -> Instruction {
+    Instruction {
+        program_id: SOLEND_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(reserve.address, false),
+            AccountMeta::new_readonly(reserve.pyth_oracle, false),
+            AccountMeta::new_readonly(reserve.switchboard_oracle, false),
+        ],
+        data: vec![REFRESH_RESERVE],
+    }
+}
+
+/// 买家开启收益模式（刷新储备后存入）
+pub fn enable_yield(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    reserve: &SolendReserve,
+) -> Vec<Instruction> {
+    let enable = Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::EnableYield {
+            escrow: escrow_address,
+            buyer: escrow.buyer,
+            yield_position: pda::yield_position(&escrow_address),
+            escrow_token_account: get_associated_token_address(
+                &escrow_address,
+                &reserve.liquidity_mint,
+            ),
+            collateral_account: get_associated_token_address(
+                &escrow_address,
+                &reserve.collateral_mint,
+            ),
+            reserve: reserve.address,
+            reserve_liquidity_supply: reserve.liquidity_supply,
+            reserve_collateral_mint: reserve.collateral_mint,
+            lending_market: reserve.lending_market,
+            lending_market_authority: reserve.lending_market_authority(),
+            lending_program: SOLEND_PROGRAM_ID,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::EnableYield {}.data(),
+    };
+
+    vec![refresh_reserve(reserve), enable]
+}
+
+/// 赎回收益模式存款（刷新储备后赎回），`caller` 为买家、提供商或平台
+///
+/// 应放在释放、退款或裁决指令之前的同一交易中
+pub fn withdraw_yield(
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    caller: Pubkey,
+    position: &YieldPosition,
+    reserve: &SolendReserve,
+) -> Vec<Instruction> {
+    let mint = reserve.liquidity_mint;
+    let withdraw = Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::WithdrawYield {
+            escrow: escrow_address,
+            caller,
+            config: pda::config(),
+            yield_position: pda::yield_position(&escrow_address),
+            buyer: escrow.buyer,
+            buyer_token_account: get_associated_token_address(&escrow.buyer, &mint),
+            treasury: config.treasury,
+            escrow_token_account: get_associated_token_address(&escrow_address, &mint),
+            collateral_account: position.collateral,
+            reserve: reserve.address,
+            reserve_collateral_mint: reserve.collateral_mint,
+            reserve_liquidity_supply: reserve.liquidity_supply,
+            lending_market: reserve.lending_market,
+            lending_market_authority: reserve.lending_market_authority(),
+            lending_program: SOLEND_PROGRAM_ID,
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::WithdrawYield {}.data(),
+    };
+
+    vec![refresh_reserve(reserve), withdraw]
+}
@@ -0,0 +1,526 @@
+//! DataNexus Client SDK
+//!
+//! 托管程序的 Rust 客户端：
+//! 1. `instructions` 构造各托管指令（PDA 自动推导）
+//! 2. `EscrowClient` 封装 RPC 连接和付款钱包，负责发送交易和读取账户
+//! 3. `transaction::TransactionBuilder` 组装计算预算、优先费、持久 nonce 和托管指令，支持 legacy 和 v0 交易
+//! 4. `lookup_table` 管理包含平台级账户的地址查找表
+//! 5. `events` 从交易日志解码强类型的托管事件
+//! 6. `state` 在发送交易前按链上规则校验状态流转和调用权限
+//! 7. `pda` / `fetch` 推导托管程序的全部 PDA 并读取对应账户，按买家、提供商或状态批量查询托管
+//! 8. `simulate` 预检模拟交易，把错误码还原为 `EscrowError` 并指出未通过约束的账户
+//! 9. `submit` 按 `RetryPolicy` 退避重试发送，重发前检查链上效果避免重复执行
+//! 10. `offline` 导出未签名交易、导入其他机器产生的签名并组装，支持多签和冷钱包
+//! 11. `squads` 平台或管理员为 Squads 多签金库时，构造包含托管指令的多签提案
+//! 12. `wormhole` 解析跨链转账的 VAA，兑付后为其他链上的买家创建托管
+//! 13. `lending` 解析 Solend 储备，开启和赎回托管的收益模式
+//! 14. `chunks` 构建数据块 Merkle 树，生成交付清单和按块争议的证明
+//! 15. `shadow_drive` 核对 Shadow Drive 交付的存储账户、对象和内容哈希
+//! 16. `coupons` 签发和撤销平台优惠券，推导优惠券地址
+//! 17. `metering` 按查询计费：开通计量账户、充值、签发用量收据和凭收据扣款
+//! 18. `subscriptions` 订阅计划和不可转让访问凭证，供网关检查订阅是否有效
+//! 19. `auctions` 需求竞价：开启竞价、缴纳保证金出价、选定中标者、授标创建托管和退还保证金
+//! 20. `sales` 由销售记录事件重建提供商的销售 Merkle 树，生成和校验单笔销售的包含证明
+
+pub mod auctions;
+pub mod chunks;
+pub mod coupons;
+pub mod events;
+pub mod fetch;
+pub mod instructions;
+pub mod lending;
+pub mod lookup_table;
+pub mod metering;
+pub mod offline;
+pub mod pda;
+pub mod sales;
+pub mod shadow_drive;
+pub mod simulate;
+pub mod squads;
+pub mod state;
+pub mod submit;
+pub mod subscriptions;
+pub mod transaction;
+pub mod wormhole;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_instruction;
+use datanexus_escrow::{Config, Escrow, EscrowStatus, ProviderStats};
+use simulate::{ProgramFailure, Simulation};
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_commitment_config::CommitmentConfig;
+use solana_hash::Hash;
+use solana_keypair::Keypair;
+use solana_message::{AddressLookupTableAccount, VersionedMessage};
+use solana_nonce::state::State as NonceState;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::client_error::ErrorKind;
+use solana_rpc_client_api::config::RpcSimulateTransactionConfig;
+use solana_rpc_client_api::request::{RpcError, RpcResponseErrorData};
+use solana_rpc_client_api::response::RpcSimulateTransactionResult;
+use solana_signature::Signature;
+use solana_signer::Signer;
+use solana_transaction::versioned::VersionedTransaction;
+use solana_transaction::Transaction;
+use submit::{Idempotency, RetryPolicy, Submission};
+use transaction::{PriorityFee, TransactionBuilder};
+
+pub use datanexus_escrow::ID as ESCROW_PROGRAM_ID;
+
+/// 客户端错误
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("RPC error: {0}")]
+    Rpc(Box<solana_rpc_client_api::client_error::Error>),
+
+    #[error("Account not found: {0}")]
+    AccountNotFound(Pubkey),
+
+    #[error("Failed to deserialize account {0}: {1}")]
+    Deserialize(Pubkey, anchor_lang::error::Error),
+
+    #[error("Invalid address lookup table: {0}")]
+    InvalidLookupTable(Pubkey),
+
+    #[error("Not a Squads multisig account: {0}")]
+    InvalidMultisig(Pubkey),
+
+    #[error("Not a Wormhole token bridge payment VAA: {0}")]
+    InvalidPostedVaa(Pubkey),
+
+    #[error("Not a Solend reserve account: {0}")]
+    InvalidReserve(Pubkey),
+
+    #[error("Failed to compile v0 message: {0}")]
+    Compile(#[from] solana_message::CompileError),
+
+    #[error("Failed to sign transaction: {0}")]
+    Signer(#[from] solana_signer::SignerError),
+
+    #[error("Durable nonce error: {0}")]
+    Nonce(#[from] solana_rpc_client_nonce_utils::Error),
+
+    #[error("Transaction failed: {0}")]
+    Program(Box<ProgramFailure>),
+
+    #[error("Invalid encoded transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("{0} is not a required signer of this transaction")]
+    NotASigner(Pubkey),
+
+    #[error("Signature does not verify for {0}")]
+    InvalidSignature(Pubkey),
+
+    #[error("Transaction is missing signatures from: {0:?}")]
+    MissingSignatures(Vec<Pubkey>),
+
+    #[error("Not a Shadow Drive storage account: {0}")]
+    InvalidStorageAccount(Pubkey),
+
+    #[error("Escrow {0} has no revealed Shadow Drive delivery")]
+    NotShadowDrive(Pubkey),
+
+    #[error("Storage object not found: {0}")]
+    ObjectNotFound(String),
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Delivered content hash does not match the on-chain delivery hash")]
+    DeliveryHashMismatch {
+        expected: [u8; 32],
+        actual: [u8; 32],
+    },
+}
+
+impl From<solana_rpc_client_api::client_error::Error> for ClientError {
+    /// 发送前的预检失败带有程序日志，解码为 `ClientError::Program`
+    fn from(err: solana_rpc_client_api::client_error::Error) -> Self {
+        if let ErrorKind::RpcError(RpcError::RpcResponseError {
+            data:
+                RpcResponseErrorData::SendTransactionPreflightFailure(RpcSimulateTransactionResult {
+                    err: Some(error),
+                    logs,
+                    ..
+                }),
+            ..
+        }) = err.kind()
+        {
+            let logs = logs.as_deref().unwrap_or_default();
+            return ClientError::Program(Box::new(ProgramFailure::decode(error, logs)));
+        }
+        ClientError::Rpc(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// 托管程序客户端
+pub struct EscrowClient {
+    rpc: RpcClient,
+    payer: Box<dyn Signer>,
+    priority_fee: PriorityFee,
+    retry_policy: RetryPolicy,
+}
+
+impl EscrowClient {
+    /// 以 confirmed 提交级别连接 RPC 节点
+    pub fn new(url: impl ToString, payer: Keypair) -> Self {
+        Self::new_with_signer(url, Box::new(payer))
+    }
+
+    /// 使用任意签名者（如硬件钱包）作为付款钱包
+    pub fn new_with_signer(url: impl ToString, payer: Box<dyn Signer>) -> Self {
+        Self {
+            rpc: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+            payer,
+            priority_fee: PriorityFee::None,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// 设置 `send` 使用的优先费
+    pub fn with_priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    /// 设置 `submit` 使用的重试策略
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// 以付款钱包和客户端优先费设置初始化的交易构造器
+    pub fn builder(&self) -> TransactionBuilder {
+        TransactionBuilder::new(self.payer.pubkey()).priority_fee(self.priority_fee)
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// 付款钱包地址（同时作为默认签名者）
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    /// 签名并发送交易，付款钱包始终签名
+    pub fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let all_signers = self.signers(signers);
+
+        let builder = self.builder().instructions(instructions.iter().cloned());
+        let mut tx = self.prepare(&builder)?;
+        let blockhash = tx.message.recent_blockhash;
+        tx.try_sign(&all_signers, blockhash)?;
+
+        Ok(self.rpc.send_and_confirm_transaction(&tx)?)
+    }
+
+    /// 带重试地签名发送，付款钱包始终签名
+    ///
+    /// 每次重试先确认之前发出的签名和 `idempotency` 描述的链上效果，
+    /// 均未生效时才以新的区块哈希（或 nonce）重新签名；只重试暂时性错误
+    pub fn submit(
+        &self,
+        builder: &TransactionBuilder,
+        signers: &[&Keypair],
+        idempotency: Idempotency,
+    ) -> Result<Submission> {
+        let all_signers = self.signers(signers);
+
+        let mut sent: Vec<Signature> = Vec::new();
+        let mut attempt = 0;
+        loop {
+            if attempt > 0 {
+                std::thread::sleep(self.retry_policy.backoff(attempt));
+            }
+
+            for signature in &sent {
+                match self.rpc.get_signature_status(signature)? {
+                    Some(Ok(())) => return Ok(Submission::Confirmed(*signature)),
+                    Some(Err(error)) => {
+                        let failure = ProgramFailure::decode(&error, &[]);
+                        return Err(ClientError::Program(Box::new(failure)));
+                    }
+                    None => {}
+                }
+            }
+            if self.is_applied(&idempotency)? {
+                return Ok(Submission::AlreadyApplied);
+            }
+
+            let mut tx = self.prepare(builder)?;
+            let blockhash = tx.message.recent_blockhash;
+            tx.try_sign(&all_signers, blockhash)?;
+            sent.push(tx.signatures[0]);
+            attempt += 1;
+
+            match self.rpc.send_and_confirm_transaction(&tx) {
+                Ok(signature) => return Ok(Submission::Confirmed(signature)),
+                Err(err) => {
+                    let err = ClientError::from(err);
+                    if attempt >= self.retry_policy.max_attempts || !submit::is_retryable(&err) {
+                        return Err(err);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 交易的链上效果是否已经存在
+    fn is_applied(&self, idempotency: &Idempotency) -> Result<bool> {
+        match idempotency {
+            Idempotency::None => Ok(false),
+            Idempotency::AccountExists(address) => Ok(self
+                .rpc
+                .get_account_with_commitment(address, self.rpc.commitment())?
+                .value
+                .is_some()),
+            Idempotency::EscrowStatus { escrow, status } => match self.fetch_escrow(escrow) {
+                Ok(account) => Ok(account.status() == *status),
+                Err(ClientError::AccountNotFound(_)) => Ok(false),
+                Err(err) => Err(err),
+            },
+        }
+    }
+
+    /// 按最近区块中 `accounts` 相关交易的优先费估算单价（micro-lamports / 计算单元）
+    pub fn estimate_priority_fee(&self, accounts: &[Pubkey], percentile: u8) -> Result<u64> {
+        let fees: Vec<u64> = self
+            .rpc
+            .get_recent_prioritization_fees(accounts)?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        Ok(transaction::fee_percentile(&fees, percentile))
+    }
+
+    /// 预检模拟交易（不校验签名），失败时返回解码后的 `ClientError::Program`
+    pub fn simulate(&self, builder: &TransactionBuilder) -> Result<Simulation> {
+        let tx = self.prepare(builder)?;
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            commitment: Some(self.rpc.commitment()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result = self
+            .rpc
+            .simulate_transaction_with_config(&tx, config)?
+            .value;
+        simulate::check(result).map_err(|failure| ClientError::Program(Box::new(failure)))
+    }
+
+    /// 估算优先费并填入最新区块哈希，返回待签名的交易
+    pub fn prepare(&self, builder: &TransactionBuilder) -> Result<Transaction> {
+        let compute_unit_price = self.compute_unit_price(builder)?;
+        let blockhash = self.blockhash(builder)?;
+        Ok(builder.build(compute_unit_price, blockhash))
+    }
+
+    /// 同 `prepare`，但构造引用 `lookup_tables` 的 v0 消息
+    pub fn prepare_v0(
+        &self,
+        builder: &TransactionBuilder,
+        lookup_tables: &[Pubkey],
+    ) -> Result<VersionedMessage> {
+        let tables = lookup_tables
+            .iter()
+            .map(|address| self.fetch_lookup_table(address))
+            .collect::<Result<Vec<_>>>()?;
+        let compute_unit_price = self.compute_unit_price(builder)?;
+        let blockhash = self.blockhash(builder)?;
+        Ok(builder.build_v0(compute_unit_price, blockhash, &tables)?)
+    }
+
+    /// 以 v0 交易签名并发送，付款钱包始终签名
+    pub fn send_v0(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+        lookup_tables: &[Pubkey],
+    ) -> Result<Signature> {
+        let all_signers = self.signers(signers);
+
+        let builder = self.builder().instructions(instructions.iter().cloned());
+        let message = self.prepare_v0(&builder, lookup_tables)?;
+        let tx = VersionedTransaction::try_new(message, &all_signers)?;
+
+        Ok(self.rpc.send_and_confirm_transaction(&tx)?)
+    }
+
+    /// 读取地址查找表
+    pub fn fetch_lookup_table(&self, address: &Pubkey) -> Result<AddressLookupTableAccount> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())?
+            .value
+            .ok_or(ClientError::AccountNotFound(*address))?;
+        let table = AddressLookupTable::deserialize(&account.data)
+            .map_err(|_| ClientError::InvalidLookupTable(*address))?;
+
+        Ok(AddressLookupTableAccount {
+            key: *address,
+            addresses: table.addresses.to_vec(),
+        })
+    }
+
+    /// 创建包含平台级账户的查找表，付款钱包为权限方，返回查找表地址
+    ///
+    /// 新写入的地址要到下一个 slot 才能被交易引用
+    pub fn create_platform_lookup_table(&self, mint: &Pubkey) -> Result<Pubkey> {
+        let config = self.fetch_config()?;
+        let recent_slot = self.rpc.get_slot()?;
+        let (create, address) = lookup_table::create(self.payer(), self.payer(), recent_slot);
+
+        let mut extends = lookup_table::extend(
+            address,
+            self.payer(),
+            self.payer(),
+            &lookup_table::platform_addresses(&config, mint),
+        )
+        .into_iter();
+        let mut first = vec![create];
+        first.extend(extends.next());
+        self.send(&first, &[])?;
+        for extend in extends {
+            self.send(&[extend], &[])?;
+        }
+
+        Ok(address)
+    }
+
+    /// 向付款钱包管理的查找表追加地址（已存在的地址会被跳过）
+    pub fn extend_lookup_table(&self, address: &Pubkey, addresses: &[Pubkey]) -> Result<()> {
+        let existing = self.fetch_lookup_table(address)?.addresses;
+        let missing: Vec<Pubkey> = addresses
+            .iter()
+            .filter(|candidate| !existing.contains(candidate))
+            .copied()
+            .collect();
+
+        for extend in lookup_table::extend(*address, self.payer(), self.payer(), &missing) {
+            self.send(&[extend], &[])?;
+        }
+        Ok(())
+    }
+
+    /// 读取持久 nonce 账户，返回当前 nonce 值（作为交易的区块哈希）
+    ///
+    /// `authority` 与账户记录的权限方不一致时报错
+    pub fn fetch_nonce(&self, account: &Pubkey, authority: &Pubkey) -> Result<Hash> {
+        let nonce_account = solana_rpc_client_nonce_utils::get_account_with_commitment(
+            &self.rpc,
+            account,
+            self.rpc.commitment(),
+        )?;
+        let data = solana_rpc_client_nonce_utils::data_from_account(&nonce_account)?;
+        if data.authority != *authority {
+            return Err(solana_rpc_client_nonce_utils::Error::InvalidAuthority {
+                provided: *authority,
+                expected: data.authority,
+            }
+            .into());
+        }
+
+        Ok(data.blockhash())
+    }
+
+    /// 创建持久 nonce 账户，付款钱包支付租金
+    pub fn create_nonce_account(&self, nonce: &Keypair, authority: &Pubkey) -> Result<Signature> {
+        let lamports = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(NonceState::size())?;
+        let instructions = system_instruction::create_nonce_account(
+            &self.payer(),
+            &nonce.pubkey(),
+            authority,
+            lamports,
+        );
+
+        self.send(&instructions, &[nonce])
+    }
+
+    /// 以付款钱包对交易部分签名，其余签名可由 `offline::add_signature` 导入
+    pub fn sign_as_payer(&self, tx: &mut Transaction) -> Result<()> {
+        let blockhash = tx.message.recent_blockhash;
+        tx.try_partial_sign(&[self.payer.as_ref()], blockhash)?;
+        Ok(())
+    }
+
+    /// 广播已签名的交易（如冷钱包离线签名的持久 nonce 交易），签名不全时拒绝发送
+    pub fn send_signed(&self, tx: &Transaction) -> Result<Signature> {
+        offline::ensure_signed(tx)?;
+        Ok(self.rpc.send_and_confirm_transaction(tx)?)
+    }
+
+    /// 付款钱包在前，其余签名者在后
+    fn signers<'a>(&'a self, signers: &[&'a Keypair]) -> Vec<&'a dyn Signer> {
+        let mut all_signers: Vec<&dyn Signer> = vec![self.payer.as_ref()];
+        all_signers.extend(signers.iter().map(|signer| *signer as &dyn Signer));
+        all_signers
+    }
+
+    fn blockhash(&self, builder: &TransactionBuilder) -> Result<Hash> {
+        match builder.get_durable_nonce() {
+            Some(nonce) => self.fetch_nonce(&nonce.account, &nonce.authority),
+            None => Ok(self.rpc.get_latest_blockhash()?),
+        }
+    }
+
+    fn compute_unit_price(&self, builder: &TransactionBuilder) -> Result<Option<u64>> {
+        Ok(match builder.get_priority_fee() {
+            PriorityFee::None => None,
+            PriorityFee::Fixed(price) => Some(price),
+            PriorityFee::Percentile(percentile) => {
+                Some(self.estimate_priority_fee(&builder.writable_accounts(), percentile)?)
+            }
+        })
+    }
+
+    /// 读取托管账户
+    pub fn fetch_escrow(&self, address: &Pubkey) -> Result<Escrow> {
+        fetch::fetch_escrow(&self.rpc, address)
+    }
+
+    /// 查询买家的全部托管
+    pub fn list_escrows_by_buyer(&self, buyer: &Pubkey) -> Result<Vec<(Pubkey, Escrow)>> {
+        fetch::list_escrows_by_buyer(&self.rpc, buyer)
+    }
+
+    /// 查询提供商的全部托管
+    pub fn list_escrows_by_provider(&self, provider: &Pubkey) -> Result<Vec<(Pubkey, Escrow)>> {
+        fetch::list_escrows_by_provider(&self.rpc, provider)
+    }
+
+    /// 查询处于某一状态的全部托管
+    pub fn list_escrows_by_status(&self, status: EscrowStatus) -> Result<Vec<(Pubkey, Escrow)>> {
+        fetch::list_escrows_by_status(&self.rpc, status)
+    }
+
+    /// 读取平台配置
+    pub fn fetch_config(&self) -> Result<Config> {
+        fetch::fetch_config(&self.rpc)
+    }
+
+    /// 读取提供商统计
+    pub fn fetch_provider_stats(&self, provider: &Pubkey) -> Result<ProviderStats> {
+        fetch::fetch_provider_stats(&self.rpc, provider)
+    }
+
+    /// 提供商下一个托管的索引序号（统计账户不存在时为 0）
+    pub fn provider_sequence(&self, provider: &Pubkey) -> Result<u64> {
+        match self.fetch_provider_stats(provider) {
+            Ok(stats) => Ok(stats.total_escrows),
+            Err(ClientError::AccountNotFound(_)) => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// 托管 PDA 地址
+    pub fn escrow_address(buyer: &Pubkey, request_id: &str) -> Pubkey {
+        pda::escrow(buyer, request_id)
+    }
+}
@@ -0,0 +1,60 @@
+//! 地址查找表（ALT）
+//!
+//! 平台级账户（配置、协议统计、支付币种、国库、奖励池、平台钱包和常用程序）几乎出现在每笔托管交易中，
+//! 放入查找表后 v0 交易只需 1 字节索引即可引用
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_spl::associated_token;
+use anchor_spl::token;
+use datanexus_escrow::Config;
+use solana_address_lookup_table_interface::instruction as alt_instruction;
+
+use crate::pda;
+
+/// 单条 `extend_lookup_table` 指令最多写入的地址数（受交易大小限制）
+pub const MAX_ADDRESSES_PER_EXTEND: usize = 20;
+
+/// 平台查找表应包含的地址
+pub fn platform_addresses(config: &Config, mint: &Pubkey) -> Vec<Pubkey> {
+    vec![
+        datanexus_escrow::ID,
+        pda::config(),
+        pda::protocol_stats(),
+        config.admin,
+        config.treasury,
+        config.rewards_pool,
+        *mint,
+        token::ID,
+        associated_token::ID,
+        system_program::ID,
+    ]
+}
+
+/// 创建查找表，返回指令和查找表地址
+///
+/// `recent_slot` 须为最近已确认的 slot，用于派生查找表地址
+pub fn create(authority: Pubkey, payer: Pubkey, recent_slot: u64) -> (Instruction, Pubkey) {
+    alt_instruction::create_lookup_table(authority, payer, recent_slot)
+}
+
+/// 向查找表追加地址，超过 `MAX_ADDRESSES_PER_EXTEND` 时拆成多条指令（需分别放入不同交易）
+pub fn extend(
+    lookup_table: Pubkey,
+    authority: Pubkey,
+    payer: Pubkey,
+    addresses: &[Pubkey],
+) -> Vec<Instruction> {
+    addresses
+        .chunks(MAX_ADDRESSES_PER_EXTEND)
+        .map(|chunk| {
+            alt_instruction::extend_lookup_table(
+                lookup_table,
+                authority,
+                Some(payer),
+                chunk.to_vec(),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,167 @@
+//! 按查询计费
+//!
+//! 计量账户由计量程序管理，PDA 为 `[b"meter", 买家, 提供商]`，余额存放在 `[b"meter_vault", 计量账户]`；
+//! 买家的 API 客户端用收据签名密钥对 `usage_receipt_message` 签名，提供商凭收据 `draw` 扣款
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token;
+use datanexus_metering::{accounts, instruction, ID};
+use solana_rpc_client::rpc_client::RpcClient;
+
+use crate::fetch::fetch_account;
+use crate::instructions::ed25519_verify;
+use crate::Result;
+
+pub use datanexus_metering::{Meter, SETTLEMENT_PERIOD};
+
+/// 计量账户 `[b"meter", buyer, provider]`（计量程序）
+pub fn find_meter_address(buyer: &Pubkey, provider: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"meter", buyer.as_ref(), provider.as_ref()], &ID)
+}
+
+/// 计量余额金库 `[b"meter_vault", meter]`（计量程序）
+pub fn find_meter_vault_address(meter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"meter_vault", meter.as_ref()], &ID)
+}
+
+/// 读取计量账户（未开通或已关闭时返回 `ClientError::AccountNotFound`）
+pub fn fetch_meter(rpc: &RpcClient, buyer: &Pubkey, provider: &Pubkey) -> Result<Meter> {
+    fetch_account(rpc, &find_meter_address(buyer, provider).0)
+}
+
+/// 收据签名密钥需要签名的用量收据：`计量账户地址 || 开通时间（i64 小端）|| 累计查询数（u64 小端）`
+///
+/// `opened_at` 取自计量账户，关闭后重新开通的计量账户不接受旧收据
+pub fn usage_receipt_message(meter: &Pubkey, opened_at: i64, cumulative_queries: u64) -> [u8; 48] {
+    let mut message = [0u8; 48];
+    message[..32].copy_from_slice(meter.as_ref());
+    message[32..40].copy_from_slice(&opened_at.to_le_bytes());
+    message[40..].copy_from_slice(&cumulative_queries.to_le_bytes());
+    message
+}
+
+/// 用量收据的 Ed25519 签名校验指令，须紧接在 `draw` 之前
+pub fn usage_receipt(
+    receipt_signer: Pubkey,
+    signature: [u8; 64],
+    meter: &Pubkey,
+    opened_at: i64,
+    cumulative_queries: u64,
+) -> Instruction {
+    ed25519_verify(
+        receipt_signer,
+        signature,
+        &usage_receipt_message(meter, opened_at, cumulative_queries),
+    )
+}
+
+/// 买家为提供商开通计量账户，`receipt_signer` 为签发用量收据的密钥
+pub fn open_meter(
+    buyer: Pubkey,
+    provider: Pubkey,
+    mint: Pubkey,
+    price_per_query: u64,
+    receipt_signer: Pubkey,
+) -> Instruction {
+    let meter = find_meter_address(&buyer, &provider).0;
+    Instruction {
+        program_id: ID,
+        accounts: accounts::OpenMeter {
+            meter,
+            vault: find_meter_vault_address(&meter).0,
+            buyer,
+            provider,
+            mint,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::OpenMeter {
+            price_per_query,
+            receipt_signer,
+        }
+        .data(),
+    }
+}
+
+/// 买家从关联代币账户向计量余额充值
+pub fn top_up(meter_address: Pubkey, meter: &Meter, amount: u64) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::TopUp {
+            meter: meter_address,
+            vault: find_meter_vault_address(&meter_address).0,
+            buyer: meter.buyer,
+            buyer_token_account: get_associated_token_address(&meter.buyer, &meter.mint),
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::TopUp { amount }.data(),
+    }
+}
+
+/// 买家停止计量，结算期满后可 `close_meter` 取回余额
+pub fn stop_meter(meter_address: Pubkey, meter: &Meter) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::StopMeter {
+            meter: meter_address,
+            buyer: meter.buyer,
+        }
+        .to_account_metas(None),
+        data: instruction::StopMeter {}.data(),
+    }
+}
+
+/// 提供商凭用量收据扣款，货款转入提供商的关联代币账户
+///
+/// 返回收据签名校验和扣款两条指令，须按顺序放在同一笔交易中
+pub fn draw(
+    meter_address: Pubkey,
+    meter: &Meter,
+    cumulative_queries: u64,
+    signature: [u8; 64],
+) -> Vec<Instruction> {
+    vec![
+        usage_receipt(
+            meter.receipt_signer,
+            signature,
+            &meter_address,
+            meter.opened_at,
+            cumulative_queries,
+        ),
+        Instruction {
+            program_id: ID,
+            accounts: accounts::Draw {
+                meter: meter_address,
+                vault: find_meter_vault_address(&meter_address).0,
+                provider: meter.provider,
+                provider_token_account: get_associated_token_address(&meter.provider, &meter.mint),
+                instructions_sysvar: sysvar::instructions::ID,
+                token_program: token::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Draw { cumulative_queries }.data(),
+        },
+    ]
+}
+
+/// 结算期满后买家取回余额并关闭计量账户
+pub fn close_meter(meter_address: Pubkey, meter: &Meter) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::CloseMeter {
+            meter: meter_address,
+            vault: find_meter_vault_address(&meter_address).0,
+            buyer: meter.buyer,
+            buyer_token_account: get_associated_token_address(&meter.buyer, &meter.mint),
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::CloseMeter {}.data(),
+    }
+}
@@ -0,0 +1,99 @@
+//! 离线签名
+//!
+//! 多签和冷钱包场景下交易的构造、签名与广播分别在不同机器上完成：
+//! 1. 在线机器用 `TransactionBuilder` 构造交易（建议配合持久 nonce），`encode_transaction` 导出 base64
+//! 2. 各签名方 `decode_transaction` 后用 `sign_offline` 签名，导出 `<公钥>=<签名>`
+//! 3. 在线机器 `add_signature` 逐个导入签名，`missing_signers` 为空后广播
+
+use anchor_lang::prelude::Pubkey;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_signature::Signature;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+use crate::{ClientError, Result};
+
+/// 交易序列化为 base64（未签名或部分签名均可）
+pub fn encode_transaction(tx: &Transaction) -> Result<String> {
+    let data =
+        bincode::serialize(tx).map_err(|err| ClientError::InvalidTransaction(err.to_string()))?;
+    Ok(STANDARD.encode(data))
+}
+
+/// 从 base64 还原交易
+pub fn decode_transaction(encoded: &str) -> Result<Transaction> {
+    let data = STANDARD
+        .decode(encoded.trim())
+        .map_err(|err| ClientError::InvalidTransaction(err.to_string()))?;
+    bincode::deserialize(&data).map_err(|err| ClientError::InvalidTransaction(err.to_string()))
+}
+
+/// 待签名的消息字节（base64），供只接受原始消息的硬件或托管签名服务使用
+pub fn encode_message(tx: &Transaction) -> String {
+    STANDARD.encode(tx.message_data())
+}
+
+/// 交易需要的全部签名者
+pub fn required_signers(tx: &Transaction) -> &[Pubkey] {
+    let count = usize::from(tx.message.header.num_required_signatures);
+    &tx.message.account_keys[..count.min(tx.message.account_keys.len())]
+}
+
+/// 尚未签名的签名者
+pub fn missing_signers(tx: &Transaction) -> Vec<Pubkey> {
+    required_signers(tx)
+        .iter()
+        .zip(&tx.signatures)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(pubkey, _)| *pubkey)
+        .collect()
+}
+
+/// 离线签名：对交易消息签名，不修改交易本身
+pub fn sign_offline(tx: &Transaction, signer: &dyn Signer) -> Result<(Pubkey, Signature)> {
+    let pubkey = signer.try_pubkey()?;
+    if !required_signers(tx).contains(&pubkey) {
+        return Err(ClientError::NotASigner(pubkey));
+    }
+    let signature = signer.try_sign_message(&tx.message_data())?;
+    Ok((pubkey, signature))
+}
+
+/// 导入其他机器产生的签名，签名须能通过该签名者公钥的验证
+pub fn add_signature(tx: &mut Transaction, pubkey: &Pubkey, signature: Signature) -> Result<()> {
+    let index = required_signers(tx)
+        .iter()
+        .position(|signer| signer == pubkey)
+        .ok_or(ClientError::NotASigner(*pubkey))?;
+    if !signature.verify(pubkey.as_ref(), &tx.message_data()) {
+        return Err(ClientError::InvalidSignature(*pubkey));
+    }
+    tx.signatures[index] = signature;
+    Ok(())
+}
+
+/// 签名导出格式 `<公钥>=<签名>`（与 Solana CLI 的 `--signer` 参数一致）
+pub fn format_signature(pubkey: &Pubkey, signature: &Signature) -> String {
+    format!("{}={}", pubkey, signature)
+}
+
+/// 解析 `<公钥>=<签名>`
+pub fn parse_signature(value: &str) -> Result<(Pubkey, Signature)> {
+    let invalid = || ClientError::InvalidTransaction(format!("invalid signer: {}", value));
+    let (pubkey, signature) = value.trim().split_once('=').ok_or_else(invalid)?;
+    Ok((
+        pubkey.parse().map_err(|_| invalid())?,
+        signature.parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// 确认全部签名齐全，可以广播
+pub fn ensure_signed(tx: &Transaction) -> Result<()> {
+    let missing = missing_signers(tx);
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(ClientError::MissingSignatures(missing))
+    }
+}
@@ -0,0 +1,467 @@
+//! 托管程序 PDA 推导
+//!
+//! `find_*_address` 返回地址和 bump，覆盖托管程序创建或签名使用的全部 PDA
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use datanexus_coupons::COUPON_REDEEMER_SEED;
+use datanexus_escrow::{
+    ProtocolSettings, ATTESTATION_PROGRAM_ID, ID, REPUTATION_PROGRAM_ID, STAKING_PROGRAM_ID,
+};
+
+/// 平台配置 `[b"config"]`
+pub fn find_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &ID)
+}
+
+/// 托管程序的 ProgramData（升级加载器 PDA `[program_id]`），记录升级权限
+pub fn find_program_data_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+/// 托管 `[b"escrow", buyer, request_id]`
+pub fn find_escrow_address(buyer: &Pubkey, request_id: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"escrow", buyer.as_ref(), request_id.as_bytes()], &ID)
+}
+
+/// 买家统计 `[b"buyer_stats", buyer]`
+pub fn find_buyer_stats_address(buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"buyer_stats", buyer.as_ref()], &ID)
+}
+
+/// 提供商统计 `[b"provider_stats", provider]`
+pub fn find_provider_stats_address(provider: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"provider_stats", provider.as_ref()], &ID)
+}
+
+/// 提供商托管索引 `[b"provider_escrow", provider, sequence]`
+pub fn find_provider_escrow_index_address(provider: &Pubkey, sequence: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"provider_escrow",
+            provider.as_ref(),
+            &sequence.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+/// 全局协议统计 `[b"protocol_stats"]`
+pub fn find_protocol_stats_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"protocol_stats"], &ID)
+}
+
+/// 收据 NFT 铸币 `[b"receipt", escrow]`
+pub fn find_receipt_mint_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"receipt", escrow.as_ref()], &ID)
+}
+
+/// 收据记录 `[b"receipt_record", escrow]`，收据元数据 URI 指向该账户
+pub fn find_receipt_record_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"receipt_record", escrow.as_ref()], &ID)
+}
+
+/// 提供商数据访问凭证的铸币权限 `[b"access", provider]`
+pub fn find_access_authority_address(provider: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"access", provider.as_ref()], &ID)
+}
+
+/// 保险池代币账户 `[b"insurance_vault"]`
+pub fn find_insurance_vault_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"insurance_vault"], &ID)
+}
+
+/// 费用账本 `[b"fee_ledger"]`
+pub fn find_fee_ledger_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"fee_ledger"], &ID)
+}
+
+/// 共享费用金库代币账户 `[b"fee_vault"]`
+pub fn find_fee_vault_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"fee_vault"], &ID)
+}
+
+/// 保险赔付记录 `[b"insurance_claim", escrow]`
+pub fn find_insurance_claim_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"insurance_claim", escrow.as_ref()], &ID)
+}
+
+/// 质押程序中的提供商保证金 `[b"bond", provider]`（属于质押程序）
+pub fn find_provider_bond_address(provider: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"bond", provider.as_ref()], &STAKING_PROGRAM_ID)
+}
+
+/// 平台可上诉裁决 `[b"ruling", escrow]`
+pub fn find_ruling_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"ruling", escrow.as_ref()], &ID)
+}
+
+/// 待生效的配置变更 `[b"pending_config"]`
+pub fn find_pending_config_change_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pending_config"], &ID)
+}
+
+/// 托管美元估值 `[b"valuation", escrow]`
+pub fn find_valuation_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"valuation", escrow.as_ref()], &ID)
+}
+
+/// 美元计价托管的成交价 `[b"usd_pricing", escrow]`
+pub fn find_usd_pricing_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"usd_pricing", escrow.as_ref()], &ID)
+}
+
+/// 兑换结算约定 `[b"settlement_swap", escrow]`
+pub fn find_settlement_swap_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"settlement_swap", escrow.as_ref()], &ID)
+}
+
+/// 交付证明预言机 `[b"delivery_oracle", escrow]`
+pub fn find_delivery_oracle_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"delivery_oracle", escrow.as_ref()], &ID)
+}
+
+/// 收益模式存款 `[b"yield", escrow]`
+pub fn find_yield_position_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"yield", escrow.as_ref()], &ID)
+}
+
+/// Wormhole 代币桥兑付的赎回者 `[b"redeemer"]`
+pub fn find_redeemer_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"redeemer"], &ID)
+}
+
+/// 合规证明 `[b"attestation", credential, schema, subject]`（属于 Solana Attestation Service）
+///
+/// 凭证和结构取自协议策略参数
+pub fn find_attestation_address(settings: &ProtocolSettings, subject: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"attestation",
+            settings.attestation_credential.as_ref(),
+            settings.attestation_schema.as_ref(),
+            subject.as_ref(),
+        ],
+        &ATTESTATION_PROGRAM_ID,
+    )
+}
+
+/// 托管类别和司法辖区限制 `[b"restriction", escrow]`
+pub fn find_restriction_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"restriction", escrow.as_ref()], &ID)
+}
+
+/// 加密解密密钥交付 `[b"key_delivery", escrow]`
+pub fn find_key_delivery_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"key_delivery", escrow.as_ref()], &ID)
+}
+
+/// 提供商交付承诺 `[b"commitment", escrow]`
+pub fn find_delivery_commitment_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"commitment", escrow.as_ref()], &ID)
+}
+
+/// 交付的数据块清单 `[b"manifest", escrow]`
+pub fn find_chunk_manifest_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"manifest", escrow.as_ref()], &ID)
+}
+
+/// 买家发起的争议记录 `[b"dispute", escrow]`
+pub fn find_dispute_record_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"dispute", escrow.as_ref()], &ID)
+}
+
+/// 买家对数据块的抽查 `[b"challenge", escrow]`
+pub fn find_challenge_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"challenge", escrow.as_ref()], &ID)
+}
+
+/// 买家要求的数据集属性证明 `[b"dataset_proof", escrow]`
+pub fn find_dataset_proof_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"dataset_proof", escrow.as_ref()], &ID)
+}
+
+/// 提供商设置的访问凭证有效期 `[b"access_window", escrow]`
+pub fn find_access_window_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"access_window", escrow.as_ref()], &ID)
+}
+
+/// 买家拒收交付的记录 `[b"rejection", escrow]`
+pub fn find_rejection_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"rejection", escrow.as_ref()], &ID)
+}
+
+/// 信誉程序中托管的评分 `[b"rating", escrow]`
+pub fn find_rating_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"rating", escrow.as_ref()], &REPUTATION_PROGRAM_ID)
+}
+
+/// 信誉程序中提供商的累计信誉 `[b"reputation", provider]`
+pub fn find_provider_reputation_address(provider: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"reputation", provider.as_ref()], &REPUTATION_PROGRAM_ID)
+}
+
+/// 约定的交付条款 `[b"delivery_terms", escrow]`
+pub fn find_delivery_terms_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"delivery_terms", escrow.as_ref()], &ID)
+}
+
+/// 提前交付奖励金 `[b"bonus", escrow]`
+pub fn find_bonus_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"bonus", escrow.as_ref()], &ID)
+}
+
+/// 奖励金代币账户 `[b"bonus_vault", escrow]`
+pub fn find_bonus_vault_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"bonus_vault", escrow.as_ref()], &ID)
+}
+
+/// 协商记录 `[b"message_log", escrow]`
+pub fn find_message_log_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"message_log", escrow.as_ref()], &ID)
+}
+
+/// 买家的取消申请 `[b"cancel_request", escrow]`
+pub fn find_cancel_request_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"cancel_request", escrow.as_ref()], &ID)
+}
+
+/// 创建托管时兑换的优惠券 `[b"discount", escrow]`
+pub fn find_discount_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"discount", escrow.as_ref()], &ID)
+}
+
+/// 签名兑换优惠券的托管程序权限 `[COUPON_REDEEMER_SEED]`
+pub fn find_coupon_redeemer_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COUPON_REDEEMER_SEED], &ID)
+}
+
+/// 提供商报价 `[b"quote", escrow]`
+pub fn find_quote_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"quote", escrow.as_ref()], &ID)
+}
+
+/// 按件计价 `[b"units", escrow]`
+pub fn find_units_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"units", escrow.as_ref()], &ID)
+}
+
+/// 按下载计费 `[b"downloads", escrow]`
+pub fn find_downloads_address(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"downloads", escrow.as_ref()], &ID)
+}
+
+/// 提供商销售记录 `[b"sales_history", provider]`
+pub fn find_sales_history_address(provider: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sales_history", provider.as_ref()], &ID)
+}
+
+/// 平台签发的提供商徽章 `[b"badge", provider, badge_type]`
+pub fn find_badge_address(provider: &Pubkey, badge_type: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"badge", provider.as_ref(), &[badge_type]], &ID)
+}
+
+/// 平台黑名单条目 `[b"blacklist", subject]`
+pub fn find_blacklist_address(subject: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"blacklist", subject.as_ref()], &ID)
+}
+
+/// 托管条款模板 `[b"template", owner, template_id]`
+pub fn find_template_address(owner: &Pubkey, template_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"template", owner.as_ref(), &template_id.to_le_bytes()],
+        &ID,
+    )
+}
+
+pub(crate) fn config() -> Pubkey {
+    find_config_address().0
+}
+
+pub(crate) fn program_data() -> Pubkey {
+    find_program_data_address().0
+}
+
+pub(crate) fn escrow(buyer: &Pubkey, request_id: &str) -> Pubkey {
+    find_escrow_address(buyer, request_id).0
+}
+
+pub(crate) fn buyer_stats(buyer: &Pubkey) -> Pubkey {
+    find_buyer_stats_address(buyer).0
+}
+
+pub(crate) fn provider_stats(provider: &Pubkey) -> Pubkey {
+    find_provider_stats_address(provider).0
+}
+
+pub(crate) fn provider_escrow_index(provider: &Pubkey, sequence: u64) -> Pubkey {
+    find_provider_escrow_index_address(provider, sequence).0
+}
+
+pub(crate) fn protocol_stats() -> Pubkey {
+    find_protocol_stats_address().0
+}
+
+pub(crate) fn receipt_record(escrow: &Pubkey) -> Pubkey {
+    find_receipt_record_address(escrow).0
+}
+
+pub(crate) fn receipt_mint(escrow: &Pubkey) -> Pubkey {
+    find_receipt_mint_address(escrow).0
+}
+
+pub(crate) fn insurance_vault() -> Pubkey {
+    find_insurance_vault_address().0
+}
+
+pub(crate) fn fee_ledger() -> Pubkey {
+    find_fee_ledger_address().0
+}
+
+pub(crate) fn fee_vault() -> Pubkey {
+    find_fee_vault_address().0
+}
+
+pub(crate) fn insurance_claim(escrow: &Pubkey) -> Pubkey {
+    find_insurance_claim_address(escrow).0
+}
+
+pub(crate) fn provider_bond(provider: &Pubkey) -> Pubkey {
+    find_provider_bond_address(provider).0
+}
+
+pub(crate) fn ruling(escrow: &Pubkey) -> Pubkey {
+    find_ruling_address(escrow).0
+}
+
+pub(crate) fn pending_config_change() -> Pubkey {
+    find_pending_config_change_address().0
+}
+
+pub(crate) fn valuation(escrow: &Pubkey) -> Pubkey {
+    find_valuation_address(escrow).0
+}
+
+pub(crate) fn usd_pricing(escrow: &Pubkey) -> Pubkey {
+    find_usd_pricing_address(escrow).0
+}
+
+pub(crate) fn settlement_swap(escrow: &Pubkey) -> Pubkey {
+    find_settlement_swap_address(escrow).0
+}
+
+pub(crate) fn delivery_oracle(escrow: &Pubkey) -> Pubkey {
+    find_delivery_oracle_address(escrow).0
+}
+
+pub(crate) fn yield_position(escrow: &Pubkey) -> Pubkey {
+    find_yield_position_address(escrow).0
+}
+
+pub(crate) fn redeemer() -> Pubkey {
+    find_redeemer_address().0
+}
+
+pub(crate) fn restriction(escrow: &Pubkey) -> Pubkey {
+    find_restriction_address(escrow).0
+}
+
+pub(crate) fn key_delivery(escrow: &Pubkey) -> Pubkey {
+    find_key_delivery_address(escrow).0
+}
+
+pub(crate) fn delivery_commitment(escrow: &Pubkey) -> Pubkey {
+    find_delivery_commitment_address(escrow).0
+}
+
+pub(crate) fn chunk_manifest(escrow: &Pubkey) -> Pubkey {
+    find_chunk_manifest_address(escrow).0
+}
+
+pub(crate) fn challenge(escrow: &Pubkey) -> Pubkey {
+    find_challenge_address(escrow).0
+}
+
+pub(crate) fn dataset_proof(escrow: &Pubkey) -> Pubkey {
+    find_dataset_proof_address(escrow).0
+}
+
+pub(crate) fn access_authority(provider: &Pubkey) -> Pubkey {
+    find_access_authority_address(provider).0
+}
+
+pub(crate) fn access_window(escrow: &Pubkey) -> Pubkey {
+    find_access_window_address(escrow).0
+}
+
+pub(crate) fn rejection(escrow: &Pubkey) -> Pubkey {
+    find_rejection_address(escrow).0
+}
+
+pub(crate) fn rating(escrow: &Pubkey) -> Pubkey {
+    find_rating_address(escrow).0
+}
+
+pub(crate) fn provider_reputation(provider: &Pubkey) -> Pubkey {
+    find_provider_reputation_address(provider).0
+}
+
+pub(crate) fn delivery_terms(escrow: &Pubkey) -> Pubkey {
+    find_delivery_terms_address(escrow).0
+}
+
+pub(crate) fn bonus(escrow: &Pubkey) -> Pubkey {
+    find_bonus_address(escrow).0
+}
+
+pub(crate) fn bonus_vault(escrow: &Pubkey) -> Pubkey {
+    find_bonus_vault_address(escrow).0
+}
+
+pub(crate) fn message_log(escrow: &Pubkey) -> Pubkey {
+    find_message_log_address(escrow).0
+}
+
+pub(crate) fn cancel_request(escrow: &Pubkey) -> Pubkey {
+    find_cancel_request_address(escrow).0
+}
+
+pub(crate) fn quote(escrow: &Pubkey) -> Pubkey {
+    find_quote_address(escrow).0
+}
+
+pub(crate) fn discount(escrow: &Pubkey) -> Pubkey {
+    find_discount_address(escrow).0
+}
+
+pub(crate) fn coupon_redeemer() -> Pubkey {
+    find_coupon_redeemer_address().0
+}
+
+pub(crate) fn units(escrow: &Pubkey) -> Pubkey {
+    find_units_address(escrow).0
+}
+
+pub(crate) fn downloads(escrow: &Pubkey) -> Pubkey {
+    find_downloads_address(escrow).0
+}
+
+pub(crate) fn template(owner: &Pubkey, template_id: u64) -> Pubkey {
+    find_template_address(owner, template_id).0
+}
+
+pub(crate) fn sales_history(provider: &Pubkey) -> Pubkey {
+    find_sales_history_address(provider).0
+}
+
+pub(crate) fn badge(provider: &Pubkey, badge_type: u8) -> Pubkey {
+    find_badge_address(provider, badge_type).0
+}
+
+pub(crate) fn blacklist(subject: &Pubkey) -> Pubkey {
+    find_blacklist_address(subject).0
+}
+
+pub(crate) fn dispute_record(escrow: &Pubkey) -> Pubkey {
+    find_dispute_record_address(escrow).0
+}
@@ -0,0 +1,120 @@
+//! 提供商销售记录
+//!
+//! 与链上 `SalesHistory` 一致的增量 Merkle 树：提供商按序号用 `SaleRecorded` 事件重建全部叶子，
+//! 为某笔销售生成包含证明；新买家读取链上的根后校验证明，无需信任平台或提供商
+
+use datanexus_escrow::{SaleRecorded, SALES_TREE_DEPTH};
+use solana_sha256_hasher::hashv;
+
+pub use datanexus_escrow::SalesHistory;
+
+/// 一笔销售的叶子 `sha256(0x00 || 托管 || 金额档位 || 类别 || 完成时间)`
+pub fn sale_leaf(sale: &SaleRecorded) -> [u8; 32] {
+    hashv(&[
+        &[0],
+        sale.escrow.as_ref(),
+        &[sale.amount_bucket],
+        &sale.category.to_le_bytes(),
+        &sale.timestamp.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// 销售的包含证明，`siblings` 为自底向上的兄弟节点（共 `SALES_TREE_DEPTH` 个）
+#[derive(Clone, Debug)]
+pub struct SaleProof {
+    pub index: u64,
+    pub leaf: [u8; 32],
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl SaleProof {
+    /// 由叶子和兄弟节点计算根
+    pub fn root(&self) -> [u8; 32] {
+        let mut node = self.leaf;
+        let mut position = self.index;
+        for sibling in &self.siblings {
+            node = if position.is_multiple_of(2) {
+                hashv(&[&[1], &node, sibling])
+            } else {
+                hashv(&[&[1], sibling, &node])
+            }
+            .to_bytes();
+            position /= 2;
+        }
+        node
+    }
+
+    /// 证明是否对应链上记录的根（`SalesHistory.root` 或之后某笔 `SaleRecorded.root`）
+    pub fn verify(&self, root: &[u8; 32]) -> bool {
+        self.siblings.len() == SALES_TREE_DEPTH && self.root() == *root
+    }
+}
+
+/// 销售记录 Merkle 树
+///
+/// 内部节点为 `sha256(0x01 || 左 || 右)`，尚未记录的位置为零叶子构成的子树
+#[derive(Clone, Debug)]
+pub struct SalesTree {
+    levels: Vec<Vec<[u8; 32]>>,
+    zeros: Vec<[u8; 32]>,
+}
+
+impl SalesTree {
+    /// 由按序号排列的 `SaleRecorded` 事件构建
+    pub fn from_sales(sales: &[SaleRecorded]) -> Self {
+        Self::new(sales.iter().map(sale_leaf).collect())
+    }
+
+    /// 由按序号排列的叶子构建
+    pub fn new(leaves: Vec<[u8; 32]>) -> Self {
+        let mut zeros = vec![[0; 32]];
+        let mut levels = vec![leaves];
+        for level in 0..SALES_TREE_DEPTH {
+            let zero = zeros[level];
+            let next = levels[level]
+                .chunks(2)
+                .map(|pair| hashv(&[&[1], &pair[0], pair.get(1).unwrap_or(&zero)]).to_bytes())
+                .collect();
+            levels.push(next);
+            zeros.push(hashv(&[&[1], &zero, &zero]).to_bytes());
+        }
+
+        Self { levels, zeros }
+    }
+
+    /// Merkle 根，与链上 `SalesHistory.root` 一致
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[SALES_TREE_DEPTH]
+            .first()
+            .copied()
+            .unwrap_or(self.zeros[SALES_TREE_DEPTH])
+    }
+
+    /// 已记录的销售数
+    pub fn count(&self) -> u64 {
+        self.levels[0].len() as u64
+    }
+
+    /// 第 `index` 笔销售的包含证明
+    pub fn proof(&self, index: u64) -> Option<SaleProof> {
+        let leaf = *self.levels[0].get(index as usize)?;
+        let mut position = index as usize;
+        let siblings = (0..SALES_TREE_DEPTH)
+            .map(|level| {
+                let sibling = self.levels[level]
+                    .get(position ^ 1)
+                    .copied()
+                    .unwrap_or(self.zeros[level]);
+                position /= 2;
+                sibling
+            })
+            .collect();
+
+        Some(SaleProof {
+            index,
+            leaf,
+            siblings,
+        })
+    }
+}
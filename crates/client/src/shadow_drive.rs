@@ -0,0 +1,81 @@
+//! Shadow Drive 交付
+//!
+//! 提供商把数据上传到 Shadow Drive 存储账户后，以 `StorageProtocol::ShadowDrive` 揭示存储位置，
+//! 程序在 `reveal_delivery` 时核对存储账户归属。买家确认前用 `verify_delivery`
+//! 核对对象确实存在、内容的 sha256 与链上交付哈希一致
+
+use anchor_lang::prelude::Pubkey;
+use datanexus_escrow::{StorageProtocol, StorageRef, SHADOW_DRIVE_PROGRAM_ID};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sha256_hasher::hash;
+
+use crate::{fetch, ClientError, Result};
+
+/// 核对通过的 Shadow Drive 交付
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShadowDriveDelivery {
+    pub storage_account: Pubkey, // 存储账户
+    pub path: String,            // 对象路径
+    pub url: String,             // 网关地址
+    pub size: usize,             // 对象大小（字节）
+}
+
+/// 存储账户内对象的存储引用，供 `commit_delivery` / `reveal_delivery` 使用
+pub fn storage_ref(storage_account: Pubkey, path: &str) -> StorageRef {
+    StorageRef {
+        protocol: StorageProtocol::ShadowDrive { storage_account },
+        id: path.to_string(),
+    }
+}
+
+/// 核对存储账户存在且归 Shadow Drive 程序所有
+pub fn check_storage_account(rpc: &RpcClient, storage_account: &Pubkey) -> Result<()> {
+    let account = rpc
+        .get_account_with_commitment(storage_account, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*storage_account))?;
+    if account.owner != SHADOW_DRIVE_PROGRAM_ID {
+        return Err(ClientError::InvalidStorageAccount(*storage_account));
+    }
+
+    Ok(())
+}
+
+/// 从 Shadow Drive 网关下载对象，对象不存在时返回 `ClientError::ObjectNotFound`
+pub fn fetch_object(storage: &StorageRef) -> Result<Vec<u8>> {
+    let url = storage.uri();
+    let response = reqwest::blocking::get(&url)?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(ClientError::ObjectNotFound(url));
+    }
+
+    Ok(response.error_for_status()?.bytes()?.to_vec())
+}
+
+/// 核对托管揭示的 Shadow Drive 交付：存储账户归属、对象存在且内容哈希与交付哈希一致
+pub fn verify_delivery(rpc: &RpcClient, escrow_address: &Pubkey) -> Result<ShadowDriveDelivery> {
+    let escrow = fetch::fetch_escrow(rpc, escrow_address)?;
+    let storage = fetch::fetch_delivery_commitment(rpc, escrow_address)?
+        .storage
+        .ok_or(ClientError::NotShadowDrive(*escrow_address))?;
+    let StorageProtocol::ShadowDrive { storage_account } = storage.protocol else {
+        return Err(ClientError::NotShadowDrive(*escrow_address));
+    };
+
+    check_storage_account(rpc, &storage_account)?;
+    let object = fetch_object(&storage)?;
+    let content_hash = hash(&object).to_bytes();
+    if content_hash != escrow.delivery_hash {
+        return Err(ClientError::DeliveryHashMismatch {
+            expected: escrow.delivery_hash,
+            actual: content_hash,
+        });
+    }
+
+    Ok(ShadowDriveDelivery {
+        storage_account,
+        url: storage.uri(),
+        path: storage.id,
+        size: object.len(),
+    })
+}
@@ -0,0 +1,170 @@
+//! 预检模拟与错误解码
+//!
+//! 把 `custom program error: 0x1771` 这类错误码还原为 `EscrowError`，
+//! 并从 Anchor 错误日志中取出失败的约束和账户名，调用方可直接展示失败原因
+
+use std::fmt;
+
+use datanexus_escrow_types::EscrowError;
+use solana_instruction::error::InstructionError;
+use solana_rpc_client_api::response::RpcSimulateTransactionResult;
+use solana_transaction_error::TransactionError;
+
+/// Anchor 错误日志前缀（`occurred` / `thrown in` / `caused by account` 三种形式）
+const ANCHOR_ERROR: &str = "Program log: AnchorError ";
+
+/// 成功的模拟结果
+#[derive(Clone, Debug, Default)]
+pub struct Simulation {
+    pub units_consumed: Option<u64>, // 消耗的计算单元
+    pub logs: Vec<String>,           // 程序日志
+}
+
+/// 交易失败原因
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FailureReason {
+    /// 托管程序自定义错误
+    Escrow(EscrowError),
+    /// Anchor 框架错误（账户约束、反序列化等）
+    Anchor {
+        name: String,
+        code: u32,
+        message: String,
+    },
+    /// 其他程序的自定义错误码
+    Custom(u32),
+    /// 运行时错误
+    Transaction(TransactionError),
+}
+
+impl fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Escrow(error) => write!(f, "{:?} ({}): {}", error, error.code(), error),
+            Self::Anchor {
+                name,
+                code,
+                message,
+            } => write!(f, "{} ({}): {}", name, code, message),
+            Self::Custom(code) => write!(f, "custom program error {:#x}", code),
+            Self::Transaction(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// 解码后的交易失败
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramFailure {
+    pub instruction: Option<u8>, // 失败的指令下标
+    pub reason: FailureReason,   // 失败原因
+    pub account: Option<String>, // 未通过约束的账户（Anchor 日志给出时）
+    pub logs: Vec<String>,       // 程序日志
+}
+
+impl ProgramFailure {
+    /// 由交易错误和程序日志解码
+    pub fn decode(error: &TransactionError, logs: &[String]) -> Self {
+        let anchor = logs.iter().find_map(|line| AnchorLog::parse(line));
+
+        let (instruction, reason) = match error {
+            TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+                let reason = match (EscrowError::from_code(*code), &anchor) {
+                    (Some(error), _) if failed_in_escrow(logs) => FailureReason::Escrow(error),
+                    (_, Some(log)) if log.code == *code => FailureReason::Anchor {
+                        name: log.name.clone(),
+                        code: log.code,
+                        message: log.message.clone(),
+                    },
+                    _ => FailureReason::Custom(*code),
+                };
+                (Some(*index), reason)
+            }
+            TransactionError::InstructionError(index, _) => {
+                (Some(*index), FailureReason::Transaction(error.clone()))
+            }
+            _ => (None, FailureReason::Transaction(error.clone())),
+        };
+
+        Self {
+            instruction,
+            reason,
+            account: anchor.and_then(|log| log.account),
+            logs: logs.to_vec(),
+        }
+    }
+
+    /// 托管程序错误（失败原因为其他时返回 None）
+    pub fn escrow_error(&self) -> Option<EscrowError> {
+        match self.reason {
+            FailureReason::Escrow(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProgramFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(index) = self.instruction {
+            write!(f, "instruction {} failed: ", index)?;
+        }
+        write!(f, "{}", self.reason)?;
+        if let Some(account) = &self.account {
+            write!(f, " (account: {})", account)?;
+        }
+        Ok(())
+    }
+}
+
+/// 检查模拟结果，失败时解码错误
+pub fn check(result: RpcSimulateTransactionResult) -> Result<Simulation, ProgramFailure> {
+    let logs = result.logs.unwrap_or_default();
+    match result.err {
+        Some(error) => Err(ProgramFailure::decode(&error, &logs)),
+        None => Ok(Simulation {
+            units_consumed: result.units_consumed,
+            logs,
+        }),
+    }
+}
+
+/// 最先失败的程序是否为托管程序（CPI 中被调用方先记录失败）
+fn failed_in_escrow(logs: &[String]) -> bool {
+    let escrow_program = datanexus_escrow::ID.to_string();
+    logs.iter()
+        .find_map(|line| {
+            let rest = line.strip_prefix("Program ")?;
+            let (program, action) = rest.split_once(' ')?;
+            action.starts_with("failed").then_some(program)
+        })
+        .is_some_and(|program| program == escrow_program)
+}
+
+/// 一条 Anchor 错误日志
+struct AnchorLog {
+    account: Option<String>,
+    name: String,
+    code: u32,
+    message: String,
+}
+
+impl AnchorLog {
+    /// 解析 `AnchorError ... Error Code: <name>. Error Number: <code>. Error Message: <msg>.`
+    fn parse(line: &str) -> Option<Self> {
+        let rest = line.strip_prefix(ANCHOR_ERROR)?;
+        let account = rest
+            .strip_prefix("caused by account: ")
+            .and_then(|rest| rest.split_once(". Error Code: "))
+            .map(|(account, _)| account.to_string());
+
+        let (_, rest) = rest.split_once("Error Code: ")?;
+        let (name, rest) = rest.split_once(". Error Number: ")?;
+        let (code, message) = rest.split_once(". Error Message: ")?;
+
+        Some(Self {
+            account,
+            name: name.to_string(),
+            code: code.parse().ok()?,
+            message: message.trim_end_matches('.').to_string(),
+        })
+    }
+}
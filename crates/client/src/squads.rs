@@ -0,0 +1,253 @@
+//! Squads v4 多签提案
+//!
+//! 平台或管理员可以是 Squads 多签的金库 PDA：多签成员先创建包含托管指令的金库交易和提案，
+//! 达到门限后由 Squads 程序以金库 PDA 签名 CPI 调用托管程序。
+//! 这里按 Squads v4 的指令布局手工编码，不依赖其 SDK
+
+use anchor_lang::prelude::{pubkey, Pubkey};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use datanexus_escrow::{Config, Escrow};
+use solana_message::legacy::Message;
+use solana_rpc_client::rpc_client::RpcClient;
+
+use crate::{instructions, ClientError, Result};
+
+/// Squads v4 程序
+pub const SQUADS_PROGRAM_ID: Pubkey = pubkey!("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
+
+/// `vault_transaction_create` 指令鉴别符
+const VAULT_TRANSACTION_CREATE: [u8; 8] = [48, 250, 78, 168, 208, 226, 218, 211];
+
+/// `proposal_create` 指令鉴别符
+const PROPOSAL_CREATE: [u8; 8] = [220, 60, 73, 224, 30, 108, 79, 159];
+
+/// `Multisig` 账户鉴别符
+const MULTISIG_DISCRIMINATOR: [u8; 8] = [224, 116, 121, 186, 68, 161, 79, 236];
+
+/// `Multisig.transaction_index` 的偏移：鉴别符、create_key、config_authority、threshold、time_lock 之后
+const TRANSACTION_INDEX_OFFSET: usize = 8 + 32 + 32 + 2 + 4;
+
+/// 多签金库 `[b"multisig", multisig, b"vault", vault_index]`，作为托管的平台或管理员地址
+pub fn find_vault_address(multisig: &Pubkey, vault_index: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"multisig", multisig.as_ref(), b"vault", &[vault_index]],
+        &SQUADS_PROGRAM_ID,
+    )
+}
+
+/// 金库交易 `[b"multisig", multisig, b"transaction", index]`
+pub fn find_transaction_address(multisig: &Pubkey, transaction_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"multisig",
+            multisig.as_ref(),
+            b"transaction",
+            &transaction_index.to_le_bytes(),
+        ],
+        &SQUADS_PROGRAM_ID,
+    )
+}
+
+/// 提案 `[b"multisig", multisig, b"transaction", index, b"proposal"]`
+pub fn find_proposal_address(multisig: &Pubkey, transaction_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"multisig",
+            multisig.as_ref(),
+            b"transaction",
+            &transaction_index.to_le_bytes(),
+            b"proposal",
+        ],
+        &SQUADS_PROGRAM_ID,
+    )
+}
+
+/// 读取多签账户，返回下一笔交易应使用的序号
+pub fn next_transaction_index(rpc: &RpcClient, multisig: &Pubkey) -> Result<u64> {
+    let account = rpc
+        .get_account_with_commitment(multisig, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*multisig))?;
+    if account.owner != SQUADS_PROGRAM_ID || !account.data.starts_with(&MULTISIG_DISCRIMINATOR) {
+        return Err(ClientError::InvalidMultisig(*multisig));
+    }
+
+    let index = account
+        .data
+        .get(TRANSACTION_INDEX_OFFSET..TRANSACTION_INDEX_OFFSET + 8)
+        .ok_or(ClientError::InvalidMultisig(*multisig))?;
+    let index = u64::from_le_bytes(index.try_into().unwrap());
+
+    Ok(index + 1)
+}
+
+/// 把以金库为签名者的指令编码为 Squads `TransactionMessage`
+///
+/// 账户顺序与 legacy 消息相同（可写签名者、只读签名者、可写非签名者、只读非签名者），
+/// 长度前缀为 Squads 的 `SmallVec`：指令数据为 u16，其余为 u8
+pub fn transaction_message(vault: &Pubkey, instructions: &[Instruction]) -> Vec<u8> {
+    let message = Message::new(instructions, Some(vault));
+    let header = message.header;
+    let num_signers = header.num_required_signatures;
+    let num_keys = message.account_keys.len() as u8;
+
+    let mut data = vec![
+        num_signers,
+        num_signers - header.num_readonly_signed_accounts,
+        num_keys - num_signers - header.num_readonly_unsigned_accounts,
+        num_keys,
+    ];
+    for key in &message.account_keys {
+        data.extend_from_slice(key.as_ref());
+    }
+
+    data.push(message.instructions.len() as u8);
+    for instruction in &message.instructions {
+        data.push(instruction.program_id_index);
+        data.push(instruction.accounts.len() as u8);
+        data.extend_from_slice(&instruction.accounts);
+        data.extend_from_slice(&(instruction.data.len() as u16).to_le_bytes());
+        data.extend_from_slice(&instruction.data);
+    }
+
+    // 不使用地址查找表
+    data.push(0);
+    data
+}
+
+/// 创建金库交易，`instructions` 中的平台或管理员账户应为该金库
+pub fn vault_transaction_create(
+    multisig: Pubkey,
+    creator: Pubkey,
+    rent_payer: Pubkey,
+    transaction_index: u64,
+    vault_index: u8,
+    instructions: &[Instruction],
+) -> Instruction {
+    let (vault, _) = find_vault_address(&multisig, vault_index);
+    let message = transaction_message(&vault, instructions);
+
+    let mut data = VAULT_TRANSACTION_CREATE.to_vec();
+    data.push(vault_index);
+    // 临时签名者数量
+    data.push(0);
+    data.extend_from_slice(&(message.len() as u32).to_le_bytes());
+    data.extend_from_slice(&message);
+    // 无备注
+    data.push(0);
+
+    Instruction {
+        program_id: SQUADS_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(multisig, false),
+            AccountMeta::new(
+                find_transaction_address(&multisig, transaction_index).0,
+                false,
+            ),
+            AccountMeta::new_readonly(creator, true),
+            AccountMeta::new(rent_payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+/// 为金库交易创建投票中的提案
+pub fn proposal_create(
+    multisig: Pubkey,
+    creator: Pubkey,
+    rent_payer: Pubkey,
+    transaction_index: u64,
+) -> Instruction {
+    let mut data = PROPOSAL_CREATE.to_vec();
+    data.extend_from_slice(&transaction_index.to_le_bytes());
+    // 非草稿，创建后即可投票
+    data.push(0);
+
+    Instruction {
+        program_id: SQUADS_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new(find_proposal_address(&multisig, transaction_index).0, false),
+            AccountMeta::new_readonly(creator, true),
+            AccountMeta::new(rent_payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+/// 创建包含 `instructions` 的金库交易及其提案，`creator` 须为多签成员
+pub fn propose(
+    multisig: Pubkey,
+    vault_index: u8,
+    creator: Pubkey,
+    transaction_index: u64,
+    instructions: &[Instruction],
+) -> Vec<Instruction> {
+    vec![
+        vault_transaction_create(
+            multisig,
+            creator,
+            creator,
+            transaction_index,
+            vault_index,
+            instructions,
+        ),
+        proposal_create(multisig, creator, creator, transaction_index),
+    ]
+}
+
+/// 提案：以多签金库作为平台将争议中的托管全额退款给买家
+///
+/// 托管的 `platform` 须为 `vault_index` 对应的金库
+pub fn propose_refund(
+    multisig: Pubkey,
+    vault_index: u8,
+    creator: Pubkey,
+    transaction_index: u64,
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    mint: Pubkey,
+) -> Vec<Instruction> {
+    propose(
+        multisig,
+        vault_index,
+        creator,
+        transaction_index,
+        &[instructions::refund(escrow_address, escrow, mint)],
+    )
+}
+
+/// 提案：以多签金库作为平台裁决争议
+///
+/// 托管的 `platform` 须为 `vault_index` 对应的金库
+#[allow(clippy::too_many_arguments)]
+pub fn propose_resolve_dispute(
+    multisig: Pubkey,
+    vault_index: u8,
+    creator: Pubkey,
+    transaction_index: u64,
+    escrow_address: Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    mint: Pubkey,
+    price_update: Option<Pubkey>,
+    refund_to_buyer: bool,
+) -> Vec<Instruction> {
+    propose(
+        multisig,
+        vault_index,
+        creator,
+        transaction_index,
+        &[instructions::resolve_dispute(
+            escrow_address,
+            escrow,
+            config,
+            mint,
+            price_update,
+            refund_to_buyer,
+        )],
+    )
+}
@@ -0,0 +1,165 @@
+//! 托管状态机
+//!
+//! 与链上 `require!` 规则一致的状态和权限检查，前端和机器人可以在发送交易前预先校验，
+//! 避免注定失败的交易浪费手续费
+
+use anchor_lang::prelude::Pubkey;
+use datanexus_escrow::{Escrow, EscrowStatus, JURY_AUTHORITY_SEED, JURY_PROGRAM_ID};
+
+/// 托管参与方
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Actor {
+    Buyer,
+    Provider,
+    Platform,
+    /// 陪审团程序的权限 PDA
+    Jury,
+}
+
+/// 会改变托管状态的指令
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// 买家为 Created 状态的托管充值
+    FundEscrow,
+    MarkDelivered,
+    /// 按件计价托管交付一批数据（买家共同签名），最后一批后托管完成
+    DeliverUnits,
+    /// 买家拒收后重新交付（`mark_delivered`）
+    Redeliver,
+    Cancel,
+    /// 提供商在异议期内反对取消
+    ContestCancel,
+    ConfirmAndRelease,
+    RejectDelivery,
+    RaiseDispute,
+    ResolveDispute,
+    ResolveDisputeSplit,
+    Refund,
+    EscalateDispute,
+    JuryVerdict,
+    RuleDispute,
+}
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::FundEscrow,
+        Action::MarkDelivered,
+        Action::DeliverUnits,
+        Action::Redeliver,
+        Action::Cancel,
+        Action::ContestCancel,
+        Action::ConfirmAndRelease,
+        Action::RejectDelivery,
+        Action::RaiseDispute,
+        Action::ResolveDispute,
+        Action::ResolveDisputeSplit,
+        Action::Refund,
+        Action::EscalateDispute,
+        Action::JuryVerdict,
+        Action::RuleDispute,
+    ];
+
+    /// 有权调用该指令的参与方
+    pub fn actor(self) -> Actor {
+        match self {
+            Action::MarkDelivered
+            | Action::DeliverUnits
+            | Action::Redeliver
+            | Action::ContestCancel => Actor::Provider,
+            Action::FundEscrow
+            | Action::Cancel
+            | Action::ConfirmAndRelease
+            | Action::RejectDelivery
+            | Action::RaiseDispute => Actor::Buyer,
+            Action::ResolveDispute
+            | Action::ResolveDisputeSplit
+            | Action::Refund
+            | Action::RuleDispute => Actor::Platform,
+            Action::EscalateDispute | Action::JuryVerdict => Actor::Jury,
+        }
+    }
+
+    /// 指令要求的当前状态
+    pub fn required_status(self) -> EscrowStatus {
+        match self {
+            Action::FundEscrow => EscrowStatus::Created,
+            Action::MarkDelivered | Action::DeliverUnits | Action::Cancel => EscrowStatus::Funded,
+            Action::Redeliver => EscrowStatus::Rejected,
+            Action::ContestCancel => EscrowStatus::PendingCancel,
+            Action::ConfirmAndRelease | Action::RejectDelivery | Action::RaiseDispute => {
+                EscrowStatus::Delivered
+            }
+            Action::ResolveDispute
+            | Action::ResolveDisputeSplit
+            | Action::Refund
+            | Action::EscalateDispute
+            | Action::RuleDispute => EscrowStatus::Disputed,
+            Action::JuryVerdict => EscrowStatus::Escalated,
+        }
+    }
+
+    /// 指令执行后可能的状态（`ResolveDispute`、`ResolveDisputeSplit` 视裁决结果而定）
+    pub fn outcomes(self) -> &'static [EscrowStatus] {
+        match self {
+            Action::FundEscrow => &[EscrowStatus::Funded],
+            Action::MarkDelivered | Action::Redeliver => &[EscrowStatus::Delivered],
+            Action::DeliverUnits => &[EscrowStatus::Funded, EscrowStatus::Completed],
+            Action::Cancel => &[EscrowStatus::PendingCancel],
+            Action::ContestCancel => &[EscrowStatus::Disputed],
+            Action::ConfirmAndRelease => &[EscrowStatus::Completed],
+            Action::RejectDelivery => &[EscrowStatus::Rejected],
+            Action::RaiseDispute => &[EscrowStatus::Disputed],
+            Action::ResolveDispute | Action::ResolveDisputeSplit => {
+                &[EscrowStatus::Refunded, EscrowStatus::Completed]
+            }
+            Action::Refund => &[EscrowStatus::Refunded],
+            Action::EscalateDispute => &[EscrowStatus::Escalated],
+            Action::JuryVerdict => &[EscrowStatus::Refunded, EscrowStatus::Completed],
+            Action::RuleDispute => &[EscrowStatus::Ruled],
+        }
+    }
+
+    /// `actor` 在 `status` 下能否执行该指令
+    pub fn is_allowed(self, status: &EscrowStatus, actor: Actor) -> bool {
+        self.actor() == actor && self.required_status() == *status
+    }
+}
+
+/// `actor` 在 `status` 下可执行的全部指令
+pub fn next_actions(status: &EscrowStatus, actor: Actor) -> Vec<Action> {
+    Action::ALL
+        .into_iter()
+        .filter(|action| action.is_allowed(status, actor))
+        .collect()
+}
+
+/// 钱包在该托管中的身份（同一钱包可能同时是多个参与方）
+pub fn actors(escrow: &Escrow, wallet: &Pubkey) -> Vec<Actor> {
+    let mut actors = Vec::new();
+    if escrow.buyer == *wallet {
+        actors.push(Actor::Buyer);
+    }
+    if escrow.provider == *wallet {
+        actors.push(Actor::Provider);
+    }
+    if escrow.platform == *wallet {
+        actors.push(Actor::Platform);
+    }
+    if jury_authority() == *wallet {
+        actors.push(Actor::Jury);
+    }
+    actors
+}
+
+/// 陪审团程序签署托管 CPI 的权限 PDA
+pub fn jury_authority() -> Pubkey {
+    Pubkey::find_program_address(&[JURY_AUTHORITY_SEED], &JURY_PROGRAM_ID).0
+}
+
+/// 钱包当前可对该托管执行的全部指令
+pub fn actions_for(escrow: &Escrow, wallet: &Pubkey) -> Vec<Action> {
+    actors(escrow, wallet)
+        .into_iter()
+        .flat_map(|actor| next_actions(&escrow.status(), actor))
+        .collect()
+}
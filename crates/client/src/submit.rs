@@ -0,0 +1,99 @@
+//! 交易提交：重试、退避与幂等
+//!
+//! RPC 不稳定时，超时的交易可能已经上链，直接重发会重复执行。
+//! 每次重试前依次检查：之前发出的签名是否已确认、`Idempotency` 描述的链上效果是否已经生效，
+//! 都没有时才以新的区块哈希重新签名发送
+
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use datanexus_escrow::EscrowStatus;
+use solana_rpc_client_api::client_error::ErrorKind;
+use solana_rpc_client_api::request::{RpcError, RpcResponseErrorData};
+use solana_signature::Signature;
+use solana_transaction_error::TransactionError;
+
+use crate::simulate::FailureReason;
+use crate::ClientError;
+
+/// 重试策略（指数退避）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,         // 最多发送次数（含首次）
+    pub initial_backoff: Duration, // 首次重试前的等待
+    pub max_backoff: Duration,     // 单次等待上限
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 不重试
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// 第 `attempt` 次重试（从 1 开始）前的等待时间
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// 交易的链上效果，用于判断重试前是否已经生效
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Idempotency {
+    /// 不检查，仅依据已发出签名的状态
+    None,
+    /// 账户已存在即视为生效（如 `create_escrow` 的托管 PDA）
+    AccountExists(Pubkey),
+    /// 托管已处于目标状态即视为生效（如 `mark_delivered` 后的 `Delivered`）
+    EscrowStatus {
+        escrow: Pubkey,
+        status: EscrowStatus,
+    },
+}
+
+/// 提交结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Submission {
+    /// 交易已确认
+    Confirmed(Signature),
+    /// 链上效果已经存在（由之前的请求完成），未再发送
+    AlreadyApplied,
+}
+
+/// 错误是否为暂时性的（网络、节点落后、区块哈希过期），可以重试
+pub fn is_retryable(err: &ClientError) -> bool {
+    match err {
+        ClientError::Rpc(err) => matches!(
+            err.kind(),
+            ErrorKind::Io(_)
+                | ErrorKind::Reqwest(_)
+                | ErrorKind::RpcError(RpcError::RpcRequestError(_) | RpcError::ForUser(_))
+                | ErrorKind::RpcError(RpcError::RpcResponseError {
+                    data: RpcResponseErrorData::NodeUnhealthy { .. },
+                    ..
+                })
+                | ErrorKind::TransactionError(TransactionError::BlockhashNotFound)
+        ),
+        ClientError::Program(failure) => {
+            failure.reason == FailureReason::Transaction(TransactionError::BlockhashNotFound)
+        }
+        _ => false,
+    }
+}
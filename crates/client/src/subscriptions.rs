@@ -0,0 +1,166 @@
+//! 订阅访问凭证
+//!
+//! 订阅计划由订阅程序管理，PDA 为 `[b"plan", 提供商, 计划编号]`，访问凭证铸币为 `[b"plan_mint", 计划]`；
+//! 访问凭证是 Token-2022 不可转让代币，订阅者的凭证账户为 Token-2022 关联代币账户，
+//! 网关按 `has_access` 检查凭证账户持有 1 枚且未冻结即可授权
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{
+    self, get_associated_token_address, get_associated_token_address_with_program_id,
+};
+use anchor_spl::token_2022::spl_token_2022::extension::StateWithExtensions;
+use anchor_spl::token_2022::spl_token_2022::state::Account as TokenAccountState;
+use anchor_spl::{token, token_2022};
+use datanexus_subscriptions::{accounts, instruction, ID};
+use solana_rpc_client::rpc_client::RpcClient;
+
+use crate::fetch::fetch_account;
+use crate::Result;
+
+pub use datanexus_subscriptions::{Plan, Subscription};
+
+/// 订阅计划 `[b"plan", provider, plan_id]`（订阅程序）
+pub fn find_plan_address(provider: &Pubkey, plan_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"plan", provider.as_ref(), &plan_id.to_le_bytes()], &ID)
+}
+
+/// 计划的访问凭证铸币 `[b"plan_mint", plan]`（订阅程序）
+pub fn find_plan_mint_address(plan: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"plan_mint", plan.as_ref()], &ID)
+}
+
+/// 订阅 `[b"subscription", plan, subscriber]`（订阅程序）
+pub fn find_subscription_address(plan: &Pubkey, subscriber: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"subscription", plan.as_ref(), subscriber.as_ref()], &ID)
+}
+
+/// 订阅者的访问凭证账户（Token-2022 关联代币账户）
+pub fn access_account(plan: &Plan, subscriber: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(subscriber, &plan.access_mint, &token_2022::ID)
+}
+
+/// 读取订阅计划（不存在时返回 `ClientError::AccountNotFound`）
+pub fn fetch_plan(rpc: &RpcClient, plan: &Pubkey) -> Result<Plan> {
+    fetch_account(rpc, plan)
+}
+
+/// 读取订阅（未订阅过时返回 `ClientError::AccountNotFound`）
+pub fn fetch_subscription(
+    rpc: &RpcClient,
+    plan: &Pubkey,
+    subscriber: &Pubkey,
+) -> Result<Subscription> {
+    fetch_account(rpc, &find_subscription_address(plan, subscriber).0)
+}
+
+/// 网关授权检查：订阅者的访问凭证账户持有凭证且未被冻结
+pub fn has_access(rpc: &RpcClient, plan: &Plan, subscriber: &Pubkey) -> Result<bool> {
+    let Some(account) = rpc
+        .get_account_with_commitment(&access_account(plan, subscriber), rpc.commitment())?
+        .value
+    else {
+        return Ok(false);
+    };
+    Ok(
+        StateWithExtensions::<TokenAccountState>::unpack(&account.data)
+            .map(|state| state.base.amount > 0 && !state.base.is_frozen())
+            .unwrap_or(false),
+    )
+}
+
+/// 提供商创建订阅计划，`price` 为每个周期（`period` 秒）的费用
+pub fn create_plan(
+    provider: Pubkey,
+    plan_id: u64,
+    payment_mint: Pubkey,
+    price: u64,
+    period: i64,
+) -> Instruction {
+    let plan = find_plan_address(&provider, plan_id).0;
+    Instruction {
+        program_id: ID,
+        accounts: accounts::CreatePlan {
+            plan,
+            access_mint: find_plan_mint_address(&plan).0,
+            provider,
+            payment_mint,
+            access_token_program: token_2022::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::CreatePlan {
+            plan_id,
+            price,
+            period,
+        }
+        .data(),
+    }
+}
+
+/// 提供商调整价格、周期或停止接受订阅
+pub fn update_plan(
+    plan_address: Pubkey,
+    plan: &Plan,
+    price: u64,
+    period: i64,
+    active: bool,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::UpdatePlan {
+            plan: plan_address,
+            provider: plan.provider,
+        }
+        .to_account_metas(None),
+        data: instruction::UpdatePlan {
+            price,
+            period,
+            active,
+        }
+        .data(),
+    }
+}
+
+/// 订阅者从关联代币账户支付一个周期的费用，获得或解冻访问凭证
+pub fn subscribe(plan_address: Pubkey, plan: &Plan, subscriber: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::Subscribe {
+            plan: plan_address,
+            subscription: find_subscription_address(&plan_address, &subscriber).0,
+            subscriber,
+            subscriber_token_account: get_associated_token_address(&subscriber, &plan.payment_mint),
+            provider_token_account: get_associated_token_address(
+                &plan.provider,
+                &plan.payment_mint,
+            ),
+            access_mint: plan.access_mint,
+            subscriber_access_account: access_account(plan, &subscriber),
+            token_program: token::ID,
+            access_token_program: token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Subscribe {}.data(),
+    }
+}
+
+/// 冻结到期未续费的订阅者的访问凭证（任何人可调用）
+pub fn lapse(plan_address: Pubkey, plan: &Plan, subscription: &Subscription) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::Lapse {
+            plan: plan_address,
+            subscription: find_subscription_address(&plan_address, &subscription.subscriber).0,
+            access_mint: plan.access_mint,
+            subscriber_access_account: subscription.access_account,
+            access_token_program: token_2022::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Lapse {}.data(),
+    }
+}
@@ -0,0 +1,170 @@
+//! 交易构造
+//!
+//! `TransactionBuilder` 把托管指令与 `ComputeBudget` 指令组装成交易，
+//! 网络拥堵时通过计算单元上限和优先费提高上链成功率；
+//! 设置持久 nonce 后交易不随区块哈希过期，冷钱包可离线签名、稍后再广播
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_instruction;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_hash::Hash;
+use solana_message::v0;
+use solana_message::{AddressLookupTableAccount, CompileError, VersionedMessage};
+use solana_transaction::Transaction;
+
+/// 自动估算优先费时默认取的分位数
+pub const DEFAULT_FEE_PERCENTILE: u8 = 75;
+
+/// 优先费（单位：micro-lamports / 计算单元）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PriorityFee {
+    /// 不设置优先费
+    #[default]
+    None,
+    /// 固定单价
+    Fixed(u64),
+    /// 按最近区块中相关账户优先费的分位数估算（0-100）
+    Percentile(u8),
+}
+
+/// 持久 nonce
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DurableNonce {
+    pub account: Pubkey,   // nonce 账户
+    pub authority: Pubkey, // nonce 权限方（须签名）
+}
+
+/// 交易构造器
+#[derive(Clone, Debug)]
+pub struct TransactionBuilder {
+    payer: Pubkey,
+    instructions: Vec<Instruction>,
+    compute_unit_limit: Option<u32>,
+    priority_fee: PriorityFee,
+    nonce: Option<DurableNonce>,
+}
+
+impl TransactionBuilder {
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            instructions: Vec::new(),
+            compute_unit_limit: None,
+            priority_fee: PriorityFee::None,
+            nonce: None,
+        }
+    }
+
+    /// 追加一条指令
+    pub fn instruction(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    /// 追加多条指令
+    pub fn instructions(mut self, instructions: impl IntoIterator<Item = Instruction>) -> Self {
+        self.instructions.extend(instructions);
+        self
+    }
+
+    /// 设置计算单元上限，不设置时使用运行时默认值
+    pub fn compute_unit_limit(mut self, units: u32) -> Self {
+        self.compute_unit_limit = Some(units);
+        self
+    }
+
+    /// 设置优先费
+    pub fn priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    /// 使用持久 nonce：首条指令推进 nonce，区块哈希须取 nonce 账户中保存的值
+    pub fn durable_nonce(mut self, account: Pubkey, authority: Pubkey) -> Self {
+        self.nonce = Some(DurableNonce { account, authority });
+        self
+    }
+
+    pub fn get_durable_nonce(&self) -> Option<DurableNonce> {
+        self.nonce
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer
+    }
+
+    pub fn get_priority_fee(&self) -> PriorityFee {
+        self.priority_fee
+    }
+
+    /// 交易中的可写账户（含付款钱包），优先费按这些账户的竞争程度估算
+    pub fn writable_accounts(&self) -> Vec<Pubkey> {
+        let mut accounts = vec![self.payer];
+        for meta in self.instructions.iter().flat_map(|ix| &ix.accounts) {
+            if meta.is_writable && !accounts.contains(&meta.pubkey) {
+                accounts.push(meta.pubkey);
+            }
+        }
+        accounts
+    }
+
+    /// 组装最终指令：`AdvanceNonceAccount`（运行时要求位于首位）、`ComputeBudget`、托管指令
+    ///
+    /// `compute_unit_price` 为已确定的优先费单价（`PriorityFee::Percentile` 需先估算）
+    pub fn build_instructions(&self, compute_unit_price: Option<u64>) -> Vec<Instruction> {
+        let mut instructions = Vec::with_capacity(self.instructions.len() + 3);
+        if let Some(nonce) = self.nonce {
+            instructions.push(system_instruction::advance_nonce_account(
+                &nonce.account,
+                &nonce.authority,
+            ));
+        }
+        if let Some(units) = self.compute_unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if let Some(price) = compute_unit_price.filter(|price| *price > 0) {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        instructions.extend(self.instructions.iter().cloned());
+        instructions
+    }
+
+    /// 构造未签名的交易，使用持久 nonce 时 `recent_blockhash` 传 nonce 值
+    pub fn build(&self, compute_unit_price: Option<u64>, recent_blockhash: Hash) -> Transaction {
+        let mut tx = Transaction::new_with_payer(
+            &self.build_instructions(compute_unit_price),
+            Some(&self.payer),
+        );
+        tx.message.recent_blockhash = recent_blockhash;
+        tx
+    }
+
+    /// 构造 v0 消息，`lookup_tables` 中出现的非签名账户以索引引用，
+    /// 账户较多的释放、批量流程可借此控制在交易大小上限内
+    pub fn build_v0(
+        &self,
+        compute_unit_price: Option<u64>,
+        recent_blockhash: Hash,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<VersionedMessage, CompileError> {
+        let message = v0::Message::try_compile(
+            &self.payer,
+            &self.build_instructions(compute_unit_price),
+            lookup_tables,
+            recent_blockhash,
+        )?;
+        Ok(VersionedMessage::V0(message))
+    }
+}
+
+/// 取优先费样本的分位数（样本为空时为 0）
+pub fn fee_percentile(fees: &[u64], percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    let mut fees = fees.to_vec();
+    fees.sort_unstable();
+    let index = (fees.len() - 1) * usize::from(percentile.min(100)) / 100;
+    fees[index]
+}
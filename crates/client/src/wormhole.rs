@@ -0,0 +1,218 @@
+//! Wormhole 跨链付款
+//!
+//! 其他链上的买家通过代币桥把代币（如以太坊 USDC）转给托管程序，附带 Borsh 编码的 `BridgePayment`；
+//! 核心桥验证 VAA 并写入 `PostedVAA` 账户后，中继者调用 `create_escrow_from_bridge` 兑付并创建托管。
+//! 这里按核心桥和代币桥的账户布局手工推导和解析，不依赖其 SDK
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{AnchorDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{get_associated_token_address, spl_associated_token_account};
+use anchor_spl::token;
+use datanexus_escrow::{
+    accounts, instruction, BridgePayment, Config, TOKEN_BRIDGE_PROGRAM_ID, WORMHOLE_PROGRAM_ID,
+};
+use solana_rpc_client::rpc_client::RpcClient;
+
+use crate::{instructions, pda, ClientError, Result};
+
+/// `PostedVAA` 账户中载荷长度的偏移：`b"vaa"`、版本、一致性级别、时间、签名集、提交时间、nonce、
+/// 序号、来源链、发送方之后
+const PAYLOAD_OFFSET: usize = 3 + 1 + 1 + 4 + 32 + 4 + 4 + 8 + 2 + 32;
+
+/// 代币桥附带载荷转账中应用载荷的偏移：类型、数量、代币地址、代币链、接收方、接收链、发送方之后
+const TRANSFER_PAYLOAD_OFFSET: usize = 1 + 32 + 32 + 2 + 32 + 2 + 32;
+
+/// 核心桥已验证的 VAA
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PostedVaa {
+    pub emitter_chain: u16,        // 来源链 ID
+    pub emitter_address: [u8; 32], // 来源链代币桥地址
+    pub sequence: u64,             // 消息序号
+    pub payload: Vec<u8>,          // 代币桥消息
+}
+
+impl PostedVaa {
+    /// 从 `PostedVAA` 账户数据解析
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if !data.starts_with(b"vaa") || data.len() < PAYLOAD_OFFSET + 4 {
+            return None;
+        }
+        let payload_len =
+            u32::from_le_bytes(data[PAYLOAD_OFFSET..PAYLOAD_OFFSET + 4].try_into().unwrap());
+        let payload = data.get(PAYLOAD_OFFSET + 4..PAYLOAD_OFFSET + 4 + payload_len as usize)?;
+
+        Some(Self {
+            emitter_chain: u16::from_le_bytes(data[57..59].try_into().unwrap()),
+            emitter_address: data[59..91].try_into().unwrap(),
+            sequence: u64::from_le_bytes(data[49..57].try_into().unwrap()),
+            payload: payload.to_vec(),
+        })
+    }
+
+    /// 转账数量（代币桥按 8 位小数归一化，与包装代币铸币的精度一致）
+    pub fn amount(&self) -> Option<u64> {
+        let bytes = self.payload.get(1..33)?;
+        if bytes[..24].iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        Some(u64::from_be_bytes(bytes[24..].try_into().unwrap()))
+    }
+
+    /// 被转账代币的原生链 ID
+    pub fn token_chain(&self) -> Option<u16> {
+        let bytes = self.payload.get(65..67)?;
+        Some(u16::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// 被转账代币在原生链上的地址
+    pub fn token_address(&self) -> Option<[u8; 32]> {
+        self.payload.get(33..65)?.try_into().ok()
+    }
+
+    /// 解码应用载荷中的托管参数
+    pub fn payment(&self) -> Option<BridgePayment> {
+        let payload = self.payload.get(TRANSFER_PAYLOAD_OFFSET..)?;
+        BridgePayment::deserialize(&mut &payload[..]).ok()
+    }
+}
+
+/// 核心桥 `PostedVAA` 账户 `[b"PostedVAA", vaa_hash]`
+pub fn find_posted_vaa_address(vaa_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"PostedVAA", vaa_hash], &WORMHOLE_PROGRAM_ID)
+}
+
+/// 代币桥配置 `[b"config"]`
+pub fn find_token_bridge_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &TOKEN_BRIDGE_PROGRAM_ID)
+}
+
+/// 代币桥兑付记录 `[emitter_address, emitter_chain, sequence]`（大端），防止重复兑付
+pub fn find_claim_address(vaa: &PostedVaa) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &vaa.emitter_address,
+            &vaa.emitter_chain.to_be_bytes(),
+            &vaa.sequence.to_be_bytes(),
+        ],
+        &TOKEN_BRIDGE_PROGRAM_ID,
+    )
+}
+
+/// 来源链代币桥的注册记录 `[emitter_chain, emitter_address]`
+pub fn find_endpoint_address(emitter_chain: u16, emitter_address: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[&emitter_chain.to_be_bytes(), emitter_address],
+        &TOKEN_BRIDGE_PROGRAM_ID,
+    )
+}
+
+/// 包装代币铸币 `[b"wrapped", token_chain, token_address]`
+pub fn find_wrapped_mint_address(token_chain: u16, token_address: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"wrapped", &token_chain.to_be_bytes(), token_address],
+        &TOKEN_BRIDGE_PROGRAM_ID,
+    )
+}
+
+/// 包装代币元数据 `[b"meta", wrapped_mint]`
+pub fn find_wrapped_meta_address(wrapped_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"meta", wrapped_mint.as_ref()], &TOKEN_BRIDGE_PROGRAM_ID)
+}
+
+/// 代币桥铸币权限 `[b"mint_signer"]`
+pub fn find_mint_authority_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"mint_signer"], &TOKEN_BRIDGE_PROGRAM_ID)
+}
+
+/// 读取核心桥已验证的 VAA
+pub fn fetch_posted_vaa(rpc: &RpcClient, address: &Pubkey) -> Result<PostedVaa> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    if account.owner != WORMHOLE_PROGRAM_ID {
+        return Err(ClientError::InvalidPostedVaa(*address));
+    }
+
+    PostedVaa::parse(&account.data).ok_or(ClientError::InvalidPostedVaa(*address))
+}
+
+/// 兑付跨链转账并创建托管
+///
+/// 先幂等创建托管和赎回者的包装代币关联账户；载荷中的买家须签名，
+/// `provider_sequence` 为提供商当前托管总数（`ProviderStats.total_escrows`），
+/// 平台配置用于推导要求的合规证明
+pub fn create_escrow_from_bridge(
+    payer: Pubkey,
+    posted_vaa_address: Pubkey,
+    vaa: &PostedVaa,
+    config: &Config,
+    provider_sequence: u64,
+) -> Result<Vec<Instruction>> {
+    let invalid = || ClientError::InvalidPostedVaa(posted_vaa_address);
+    let payment = vaa.payment().ok_or_else(invalid)?;
+    let token_chain = vaa.token_chain().ok_or_else(invalid)?;
+    let token_address = vaa.token_address().ok_or_else(invalid)?;
+    let amount = vaa.amount().ok_or_else(invalid)?;
+
+    let escrow = pda::escrow(&payment.buyer, &payment.request_id);
+    let redeemer = pda::redeemer();
+    let (wrapped_mint, _) = find_wrapped_mint_address(token_chain, &token_address);
+    let (buyer_attestation, provider_attestation) =
+        instructions::compliance_attestations(config, &payment.buyer, &payment.provider);
+
+    let create = Instruction {
+        program_id: datanexus_escrow::ID,
+        accounts: accounts::CreateEscrowFromBridge {
+            escrow,
+            payer,
+            buyer: payment.buyer,
+            config: pda::config(),
+            buyer_stats: pda::buyer_stats(&payment.buyer),
+            provider: payment.provider,
+            provider_stats: pda::provider_stats(&payment.provider),
+            provider_escrow_index: pda::provider_escrow_index(&payment.provider, provider_sequence),
+            protocol_stats: pda::protocol_stats(),
+            provider_profile: None,
+            platform: payment.platform,
+            escrow_token_account: get_associated_token_address(&escrow, &wrapped_mint),
+            redeemer,
+            redeemer_token_account: get_associated_token_address(&redeemer, &wrapped_mint),
+            token_bridge_config: find_token_bridge_config_address().0,
+            posted_vaa: posted_vaa_address,
+            claim: find_claim_address(vaa).0,
+            foreign_endpoint: find_endpoint_address(vaa.emitter_chain, &vaa.emitter_address).0,
+            wrapped_mint,
+            wrapped_meta: find_wrapped_meta_address(&wrapped_mint).0,
+            mint_authority: find_mint_authority_address().0,
+            rent: sysvar::rent::ID,
+            wormhole_program: WORMHOLE_PROGRAM_ID,
+            token_bridge_program: TOKEN_BRIDGE_PROGRAM_ID,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            buyer_attestation,
+            provider_attestation,
+            provider_badge: instructions::provider_badge(config, &payment.provider, amount),
+            buyer_blacklist: pda::blacklist(&payment.buyer),
+            provider_blacklist: pda::blacklist(&payment.provider),
+        }
+        .to_account_metas(None),
+        data: instruction::CreateEscrowFromBridge {
+            request_id: payment.request_id,
+        }
+        .data(),
+    };
+
+    Ok(vec![
+        instructions::create_escrow_token_account(payer, escrow, wrapped_mint),
+        spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            &payer,
+            &redeemer,
+            &wrapped_mint,
+            &token::ID,
+        ),
+        create,
+    ])
+}
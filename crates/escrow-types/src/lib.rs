@@ -0,0 +1,665 @@
+//! DataNexus Escrow Types
+//!
+//! 托管程序账户布局和错误码的独立副本，只依赖 borsh 和 `solana-pubkey`，
+//! 链下服务（索引器、Geyser 插件等）无需引入 Anchor 和完整程序即可解析账户。
+//!
+//! 布局必须与 `programs/escrow` 保持一致，`tests/parity.rs` 逐字段校验。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_pubkey::Pubkey;
+
+solana_pubkey::declare_id!("gxDTeSCzk9mqiokrmTb1uNbWCjQ1rj2hsj5N65K9698");
+
+/// 当前托管账户布局版本
+pub const ESCROW_VERSION: u8 = 5;
+
+/// 需求 ID 和提案 ID 的最大字节数
+pub const MAX_ESCROW_ID_LEN: usize = 64;
+
+/// Anchor 自定义错误码起始值
+pub const ERROR_CODE_OFFSET: u32 = 6000;
+
+/// 托管状态
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowStatus {
+    Created,    // 已创建
+    Funded,     // 已充值
+    Delivered,  // 已交付
+    Disputed,   // 争议中
+    Completed,  // 已完成
+    Refunded,   // 已退款
+    Cancelled,  // 已取消
+    Escalated,  // 陪审团审理中
+    Ruled,      // 平台已裁决，上诉期中
+    Appealed,   // 已上诉，等待陪审团受理
+    Rejected,   // 买家拒收，等待提供商重新交付
+    PendingCancel, // 买家申请取消，异议期中
+}
+
+/// 托管账户
+///
+/// 链上为零拷贝定长布局，解析时把定长 ID 还原为字符串、把 0 时间戳还原为 `None`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Escrow {
+    pub buyer: Pubkey,           // 买家
+    pub provider: Pubkey,        // 提供商
+    pub platform: Pubkey,        // 平台
+    pub amount: u64,             // 托管金额（USDC，6 位小数）
+    pub status: EscrowStatus,    // 状态
+    pub version: u8,             // 布局版本
+    pub request_id: String,      // 需求 ID
+    pub proposal_id: String,     // 提案 ID
+    pub created_at: i64,         // 创建时间
+    pub funded_at: Option<i64>,  // 充值时间
+    pub delivered_at: Option<i64>, // 交付时间
+    pub completed_at: Option<i64>, // 完成时间
+    pub refunded_at: Option<i64>,  // 退款时间
+    pub disputed_at: Option<i64>,  // 争议时间
+    pub delivery_hash: [u8; 32], // 交付内容哈希
+    pub access_mint: Option<Pubkey>, // 数据访问凭证铸币（可选）
+    pub bump: u8,                // PDA bump
+    pub mint: Option<Pubkey>,    // 托管代币铸币（版本 5 之前创建且未充值时为空）
+    pub metadata: Vec<u8>,       // 扩展元数据（平台签名追加，按需扩容）
+}
+
+impl Escrow {
+    /// Anchor 账户鉴别符 `sha256("account:Escrow")[..8]`
+    pub const DISCRIMINATOR: [u8; 8] = [31, 213, 123, 187, 186, 22, 218, 155];
+
+    /// 定长字段在账户数据中的偏移（含鉴别符），供 memcmp 过滤
+    pub const BUYER_OFFSET: usize = 8;
+    pub const PROVIDER_OFFSET: usize = 8 + 32;
+    pub const PLATFORM_OFFSET: usize = 8 + 32 + 32;
+    pub const STATUS_OFFSET: usize = 8 + 32 + 32 + 32 + 8;
+    pub const VERSION_OFFSET: usize = Self::STATUS_OFFSET + 1;
+
+    /// 元数据为空时的账户大小（含鉴别符）
+    pub const LEN: usize = 8 + 392;
+
+    /// 扩展元数据在账户数据中的起始偏移
+    pub const METADATA_OFFSET: usize = Self::LEN;
+
+    /// 从链上账户数据解析（含 8 字节鉴别符，元数据按 `metadata_len` 截取）
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, DecodeError> {
+        let body = data
+            .strip_prefix(&Self::DISCRIMINATOR)
+            .ok_or(DecodeError::DiscriminatorMismatch)?
+            .get(..Self::LEN - 8)
+            .ok_or(DecodeError::Truncated)?;
+
+        let bytes = |at: usize, len: usize| &body[at..at + len];
+        let pubkey = |at: usize| Pubkey::try_from(bytes(at, 32)).unwrap();
+        let i64_at = |at: usize| i64::from_le_bytes(bytes(at, 8).try_into().unwrap());
+        let timestamp = |at: usize| Some(i64_at(at)).filter(|value| *value != 0);
+        let id = |at: usize, len: u8| {
+            let len = len as usize;
+            if len > MAX_ESCROW_ID_LEN {
+                return Err(DecodeError::InvalidId);
+            }
+            String::from_utf8(bytes(at, len).to_vec()).map_err(|_| DecodeError::InvalidId)
+        };
+
+        let metadata_len = u32::from_le_bytes(bytes(352, 4).try_into().unwrap()) as usize;
+        let metadata = data
+            .get(Self::METADATA_OFFSET..)
+            .and_then(|metadata| metadata.get(..metadata_len))
+            .ok_or(DecodeError::Truncated)?;
+        let access_mint = pubkey(192);
+        let mint = pubkey(360);
+
+        Ok(Self {
+            buyer: pubkey(0),
+            provider: pubkey(32),
+            platform: pubkey(64),
+            amount: u64::from_le_bytes(bytes(96, 8).try_into().unwrap()),
+            status: EscrowStatus::try_from_slice(bytes(104, 1))?,
+            version: body[105],
+            request_id: id(224, body[107])?,
+            proposal_id: id(288, body[108])?,
+            created_at: i64_at(112),
+            funded_at: timestamp(120),
+            delivered_at: timestamp(128),
+            completed_at: timestamp(136),
+            refunded_at: timestamp(144),
+            disputed_at: timestamp(152),
+            delivery_hash: bytes(160, 32).try_into().unwrap(),
+            access_mint: (access_mint != Pubkey::default()).then_some(access_mint),
+            bump: body[106],
+            mint: (mint != Pubkey::default()).then_some(mint),
+            metadata: metadata.to_vec(),
+        })
+    }
+}
+
+/// 账户解析错误
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error("Account discriminator does not match")]
+    DiscriminatorMismatch,
+
+    #[error("Account data is shorter than the escrow layout")]
+    Truncated,
+
+    #[error("Request or proposal ID is not valid UTF-8 of at most 64 bytes")]
+    InvalidId,
+
+    #[error("Failed to deserialize account: {0}")]
+    Borsh(#[from] std::io::Error),
+}
+
+/// 托管程序错误，顺序与程序中的 `EscrowError` 一致
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum EscrowError {
+    #[error("Invalid escrow status for this operation")]
+    InvalidStatus,
+
+    #[error("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[error("Invalid amount")]
+    InvalidAmount,
+
+    #[error("Fee split weights must sum to 10000 bps")]
+    InvalidFeeSplit,
+
+    #[error("Receipt mint provided without the remaining receipt accounts")]
+    MissingReceiptAccounts,
+
+    #[error("Access mint does not match the escrow or its provider's access authority")]
+    InvalidAccessMint,
+
+    #[error("Escrow has an access mint but the access token accounts are missing")]
+    MissingAccessAccounts,
+
+    #[error("Provider is not registered or its profile is inactive")]
+    ProviderNotRegistered,
+
+    #[error("Proposal does not belong to this request and provider")]
+    ProposalMismatch,
+
+    #[error("Escrow account is already at the current version")]
+    AlreadyMigrated,
+
+    #[error("Escrow account must be migrated to the current version first")]
+    MigrationRequired,
+
+    #[error("Metadata must not be empty")]
+    InvalidMetadata,
+
+    #[error("Metadata exceeds the maximum length")]
+    MetadataTooLong,
+
+    #[error("Insurance bps must not exceed 10000")]
+    InvalidInsuranceBps,
+
+    #[error("Insurance vault is required while insurance bps is set")]
+    MissingInsuranceVault,
+
+    #[error("Escrow must be settled before an insurance claim")]
+    InsuranceNotClaimable,
+
+    #[error("Provider collateral in the staking program is below the requirement")]
+    InsufficientCollateral,
+
+    #[error("Provider bond account is not the staking program's bond PDA")]
+    InvalidCollateralAccount,
+
+    #[error("An appeal window is configured: rule the dispute with rule_dispute first")]
+    RulingRequired,
+
+    #[error("Ruling is missing, does not match, or appeals are disabled")]
+    InvalidRuling,
+
+    #[error("Appeal window has closed")]
+    AppealWindowClosed,
+
+    #[error("Appeal window is still open")]
+    AppealWindowOpen,
+
+    #[error("Config change is still in its timelock")]
+    TimelockActive,
+
+    #[error("Price account is not a verified Pyth price update for this feed")]
+    InvalidPriceFeed,
+
+    #[error("Pyth price is too old")]
+    StalePrice,
+
+    #[error("Escrow has a USD valuation: a Pyth price update is required")]
+    MissingPriceFeed,
+
+    #[error("Delivery requires an oracle Ed25519 attestation right before this instruction")]
+    MissingAttestation,
+
+    #[error("Oracle attestation does not match the oracle, escrow or delivery hash")]
+    InvalidAttestation,
+
+    #[error("Wormhole VAA is not a bridged payment to this program for this escrow")]
+    InvalidBridgePayment,
+
+    #[error("Swap must pay out the escrow token into the buyer's token account")]
+    InvalidSwap,
+
+    #[error("Yield buyer bps must not exceed 10000")]
+    InvalidYieldBps,
+
+    #[error("Memo program is required when transfer memos are enabled")]
+    MissingMemoProgram,
+
+    #[error("A compliance attestation is required")]
+    ComplianceAttestationRequired,
+
+    #[error("Invalid or expired compliance attestation")]
+    InvalidComplianceAttestation,
+
+    #[error("Restricted requests must record an escrow restriction")]
+    RestrictionRequired,
+
+    #[error("Escrow has no recorded restrictions")]
+    NotRestricted,
+
+    #[error("Block reason too long")]
+    BlockReasonTooLong,
+
+    #[error("The encrypted decryption key has not been delivered")]
+    KeyNotDelivered,
+
+    #[error("Encrypted key is empty or too long")]
+    InvalidEncryptedKey,
+
+    #[error("The delivery commitment has not been revealed")]
+    CommitmentNotRevealed,
+
+    #[error("Revealed content does not match the delivery commitment")]
+    CommitmentMismatch,
+
+    #[error("Storage reference ID does not match the protocol's format")]
+    InvalidStorageRef,
+
+    #[error("Chunk disputes require a chunk manifest recorded at delivery")]
+    MissingChunkManifest,
+
+    #[error("Chunk manifest must be passed together with a chunk root and a valid chunk count")]
+    InvalidChunkManifest,
+
+    #[error("Chunk index or Merkle proof does not match the chunk manifest")]
+    InvalidChunkProof,
+
+    #[error("Too many disputed chunks")]
+    TooManyDisputedChunks,
+
+    #[error("Invalid chunks must be distinct chunks disputed by the buyer")]
+    InvalidChunkRuling,
+
+    #[error("Challenge must name 1-8 distinct chunks within the chunk manifest")]
+    InvalidChallenge,
+
+    #[error("Challenge was already answered or its response window has closed")]
+    ChallengeClosed,
+
+    #[error("Challenge response window is still open")]
+    ChallengeOpen,
+
+    #[error("Verifier program does not match the one required by the buyer")]
+    InvalidVerifier,
+
+    #[error("Dataset proof has already been verified")]
+    DatasetProofVerified,
+
+    #[error("Shadow Drive storage account is missing or not owned by the Shadow Drive program")]
+    InvalidStorageAccount,
+
+    #[error("Access window requires an access mint and a positive duration")]
+    InvalidAccessWindow,
+
+    #[error("Access window has not expired yet")]
+    AccessNotExpired,
+
+    #[error("Access token has already been revoked")]
+    AccessRevoked,
+
+    #[error("Delivery has been rejected the maximum number of times")]
+    TooManyRejections,
+
+    #[error("Redelivery window is still open")]
+    RedeliveryOpen,
+
+    #[error("Quality score must be between 1 and 5 and needs the rating account")]
+    InvalidQualityScore,
+
+    #[error("Late refund cap must not exceed 10000 bps")]
+    InvalidLatenessSchedule,
+
+    #[error("Delivery deadline must be in the future")]
+    InvalidDeliveryDeadline,
+
+    #[error("Buyer token account is required to refund a late delivery")]
+    MissingBuyerTokenAccount,
+
+    #[error("Bonus amount must be positive and its deadline in the future")]
+    InvalidBonus,
+
+    #[error("Bonus can only be reclaimed after the escrow closes without release")]
+    BonusLocked,
+
+    #[error("Message log is full")]
+    MessageLogFull,
+
+    #[error("Cancellation can only be executed after the grace period")]
+    CancelGracePeriodOpen,
+
+    #[error("Cancellation grace period has ended")]
+    CancelGracePeriodEnded,
+
+    #[error("Unfunded escrow has not reached the stale TTL")]
+    EscrowNotStale,
+
+    #[error("Price quote has expired; the escrow must be re-quoted")]
+    QuoteExpired,
+
+    #[error("Invalid coupon")]
+    InvalidCoupon,
+
+    #[error("Invalid unit pricing or unit count")]
+    InvalidUnits,
+
+    #[error("Invalid download cap or no downloads remaining")]
+    InvalidDownloads,
+
+    #[error("Invalid escrow template")]
+    InvalidTemplate,
+
+    #[error("Proposal has not been selected by the request owner")]
+    ProposalNotSelected,
+
+    #[error("Provider sales history is full")]
+    SalesHistoryFull,
+
+    #[error("Provider badge required for this escrow amount")]
+    BadgeRequired,
+
+    #[error("Provider badge is invalid or expired")]
+    InvalidBadge,
+
+    #[error("Account is blacklisted by the platform")]
+    Blacklisted,
+
+    #[error("Buyer has too many open escrows")]
+    TooManyOpenEscrows,
+
+    #[error("Buyer exceeded the daily escrow creation limit")]
+    CreationRateLimited,
+
+    #[error("Fee split does not add up to the escrow amount")]
+    FeeSplitMismatch,
+
+    #[error("Pyth price deviates too far from the price at USD denomination")]
+    PriceDeviation,
+
+    #[error("Escrowed tokens do not cover the USD price")]
+    UsdPriceNotCovered,
+
+    #[error("Settlement swap paid out less than the agreed minimum")]
+    SwapSlippageExceeded,
+
+    #[error("Request or proposal ID is too long")]
+    IdTooLong,
+
+    #[error("Fee ledger and fee vault are required while the fee ledger is enabled")]
+    MissingFeeLedger,
+
+    #[error("No platform fees have accrued in the fee ledger")]
+    NoFeesAccrued,
+
+    #[error("Treasury or rewards pool token account is required while its fee share is nonzero")]
+    MissingPlatformTokenAccount,
+
+    #[error("Escrow must be in Created status")]
+    NotCreated,
+
+    #[error("Escrow must be in Funded status")]
+    NotFunded,
+
+    #[error("Escrow must be in Delivered status")]
+    NotDelivered,
+
+    #[error("Escrow must be in Rejected status")]
+    NotRejected,
+
+    #[error("Escrow must be in Disputed status")]
+    NotDisputed,
+
+    #[error("Escrow must be in Ruled status")]
+    NotRuled,
+
+    #[error("Escrow must be in Completed status")]
+    NotCompleted,
+
+    #[error("Escrow must be in PendingCancel status")]
+    NotPendingCancel,
+
+    #[error("Escrow is not awaiting delivery")]
+    NotAwaitingDelivery,
+
+    #[error("Escrow terms can no longer be changed")]
+    TermsLocked,
+
+    #[error("No release is pending for this escrow")]
+    ReleaseNotPending,
+
+    #[error("Escrow is already settled")]
+    EscrowSettled,
+
+    #[error("Only the buyer can perform this action")]
+    OnlyBuyer,
+
+    #[error("Only the provider can perform this action")]
+    OnlyProvider,
+
+    #[error("Requires both buyer and provider signatures")]
+    OnlyBuyerAndProvider,
+
+    #[error("Only the buyer can confirm and release funds")]
+    OnlyBuyerCanConfirm,
+
+    #[error("Only the arbitrator can refund a disputed escrow")]
+    OnlyArbitratorCanRefund,
+
+    #[error("Only the arbitrator can perform this action")]
+    OnlyArbitrator,
+
+    #[error("Only the platform authority can perform this action")]
+    OnlyPlatform,
+
+    #[error("Only the config admin can perform this action")]
+    OnlyAdmin,
+
+    #[error("Only an escrow participant can perform this action")]
+    OnlyParticipant,
+
+    #[error("Only the losing party can appeal")]
+    OnlyLosingParty,
+
+    #[error("Only the registered gateway can perform this action")]
+    OnlyGateway,
+
+    #[error("Only the template owner can perform this action")]
+    OnlyTemplateOwner,
+
+    #[error("Buyer account does not match the escrow")]
+    BuyerMismatch,
+
+    #[error("Mint does not match the escrow token account")]
+    MintMismatch,
+
+    #[error("The response deadline has passed")]
+    DeadlinePassed,
+
+    #[error("Only the program upgrade authority can initialize the config")]
+    OnlyUpgradeAuthority,
+
+    #[error("Escrow account size does not match any known layout")]
+    UnknownEscrowLayout,
+
+    #[error("Yield position must be withdrawn before paying out the escrow")]
+    YieldPositionOpen,
+
+    #[error("Token account is not the escrow's associated token account")]
+    EscrowTokenAccountMismatch,
+
+    #[error("Price quotes cannot be combined with unit, download or USD pricing")]
+    QuoteConflictsWithPricing,
+
+    #[error("Provider account does not match the escrow")]
+    ProviderMismatch,
+
+    #[error("Platform must be the config admin")]
+    PlatformMismatch,
+
+    #[error("Quoted price cannot exceed the escrow amount")]
+    QuoteAboveAmount,
+
+    #[error("Open escrows must be the buyer's distinct, unsettled escrows")]
+    OpenEscrowMismatch,
+}
+
+impl EscrowError {
+    /// 全部错误，按错误码排列
+    pub const ALL: &'static [EscrowError] = &[
+        EscrowError::InvalidStatus,
+        EscrowError::Unauthorized,
+        EscrowError::InvalidAmount,
+        EscrowError::InvalidFeeSplit,
+        EscrowError::MissingReceiptAccounts,
+        EscrowError::InvalidAccessMint,
+        EscrowError::MissingAccessAccounts,
+        EscrowError::ProviderNotRegistered,
+        EscrowError::ProposalMismatch,
+        EscrowError::AlreadyMigrated,
+        EscrowError::MigrationRequired,
+        EscrowError::InvalidMetadata,
+        EscrowError::MetadataTooLong,
+        EscrowError::InvalidInsuranceBps,
+        EscrowError::MissingInsuranceVault,
+        EscrowError::InsuranceNotClaimable,
+        EscrowError::InsufficientCollateral,
+        EscrowError::InvalidCollateralAccount,
+        EscrowError::RulingRequired,
+        EscrowError::InvalidRuling,
+        EscrowError::AppealWindowClosed,
+        EscrowError::AppealWindowOpen,
+        EscrowError::TimelockActive,
+        EscrowError::InvalidPriceFeed,
+        EscrowError::StalePrice,
+        EscrowError::MissingPriceFeed,
+        EscrowError::MissingAttestation,
+        EscrowError::InvalidAttestation,
+        EscrowError::InvalidBridgePayment,
+        EscrowError::InvalidSwap,
+        EscrowError::InvalidYieldBps,
+        EscrowError::MissingMemoProgram,
+        EscrowError::ComplianceAttestationRequired,
+        EscrowError::InvalidComplianceAttestation,
+        EscrowError::RestrictionRequired,
+        EscrowError::NotRestricted,
+        EscrowError::BlockReasonTooLong,
+        EscrowError::KeyNotDelivered,
+        EscrowError::InvalidEncryptedKey,
+        EscrowError::CommitmentNotRevealed,
+        EscrowError::CommitmentMismatch,
+        EscrowError::InvalidStorageRef,
+        EscrowError::MissingChunkManifest,
+        EscrowError::InvalidChunkManifest,
+        EscrowError::InvalidChunkProof,
+        EscrowError::TooManyDisputedChunks,
+        EscrowError::InvalidChunkRuling,
+        EscrowError::InvalidChallenge,
+        EscrowError::ChallengeClosed,
+        EscrowError::ChallengeOpen,
+        EscrowError::InvalidVerifier,
+        EscrowError::DatasetProofVerified,
+        EscrowError::InvalidStorageAccount,
+        EscrowError::InvalidAccessWindow,
+        EscrowError::AccessNotExpired,
+        EscrowError::AccessRevoked,
+        EscrowError::TooManyRejections,
+        EscrowError::RedeliveryOpen,
+        EscrowError::InvalidQualityScore,
+        EscrowError::InvalidLatenessSchedule,
+        EscrowError::InvalidDeliveryDeadline,
+        EscrowError::MissingBuyerTokenAccount,
+        EscrowError::InvalidBonus,
+        EscrowError::BonusLocked,
+        EscrowError::MessageLogFull,
+        EscrowError::CancelGracePeriodOpen,
+        EscrowError::CancelGracePeriodEnded,
+        EscrowError::EscrowNotStale,
+        EscrowError::QuoteExpired,
+        EscrowError::InvalidCoupon,
+        EscrowError::InvalidUnits,
+        EscrowError::InvalidDownloads,
+        EscrowError::InvalidTemplate,
+        EscrowError::ProposalNotSelected,
+        EscrowError::SalesHistoryFull,
+        EscrowError::BadgeRequired,
+        EscrowError::InvalidBadge,
+        EscrowError::Blacklisted,
+        EscrowError::TooManyOpenEscrows,
+        EscrowError::CreationRateLimited,
+        EscrowError::FeeSplitMismatch,
+        EscrowError::PriceDeviation,
+        EscrowError::UsdPriceNotCovered,
+        EscrowError::SwapSlippageExceeded,
+        EscrowError::IdTooLong,
+        EscrowError::MissingFeeLedger,
+        EscrowError::NoFeesAccrued,
+        EscrowError::MissingPlatformTokenAccount,
+        EscrowError::NotCreated,
+        EscrowError::NotFunded,
+        EscrowError::NotDelivered,
+        EscrowError::NotRejected,
+        EscrowError::NotDisputed,
+        EscrowError::NotRuled,
+        EscrowError::NotCompleted,
+        EscrowError::NotPendingCancel,
+        EscrowError::NotAwaitingDelivery,
+        EscrowError::TermsLocked,
+        EscrowError::ReleaseNotPending,
+        EscrowError::EscrowSettled,
+        EscrowError::OnlyBuyer,
+        EscrowError::OnlyProvider,
+        EscrowError::OnlyBuyerAndProvider,
+        EscrowError::OnlyBuyerCanConfirm,
+        EscrowError::OnlyArbitratorCanRefund,
+        EscrowError::OnlyArbitrator,
+        EscrowError::OnlyPlatform,
+        EscrowError::OnlyAdmin,
+        EscrowError::OnlyParticipant,
+        EscrowError::OnlyLosingParty,
+        EscrowError::OnlyGateway,
+        EscrowError::OnlyTemplateOwner,
+        EscrowError::BuyerMismatch,
+        EscrowError::MintMismatch,
+        EscrowError::DeadlinePassed,
+        EscrowError::OnlyUpgradeAuthority,
+        EscrowError::UnknownEscrowLayout,
+        EscrowError::YieldPositionOpen,
+        EscrowError::EscrowTokenAccountMismatch,
+        EscrowError::QuoteConflictsWithPricing,
+        EscrowError::ProviderMismatch,
+        EscrowError::PlatformMismatch,
+        EscrowError::QuoteAboveAmount,
+        EscrowError::OpenEscrowMismatch,
+    ];
+
+    /// 程序返回的自定义错误码
+    pub fn code(self) -> u32 {
+        ERROR_CODE_OFFSET + self as u32
+    }
+
+    /// 由自定义错误码（`Custom(code)`）还原错误
+    pub fn from_code(code: u32) -> Option<Self> {
+        let index = code.checked_sub(ERROR_CODE_OFFSET)?;
+        Self::ALL.get(index as usize).copied()
+    }
+}
@@ -0,0 +1,240 @@
+use anchor_lang::Discriminator;
+use bytemuck::Zeroable;
+use datanexus_escrow_types::{Escrow, EscrowError, EscrowStatus};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn escrow_layout_matches_program() {
+    let mut program_escrow = datanexus_escrow::Escrow::zeroed();
+    program_escrow.buyer = Pubkey::new_unique();
+    program_escrow.provider = Pubkey::new_unique();
+    program_escrow.platform = Pubkey::new_unique();
+    program_escrow.amount = 1_000_000;
+    program_escrow.set_status(datanexus_escrow::EscrowStatus::Disputed);
+    program_escrow.version = datanexus_escrow::ESCROW_VERSION;
+    program_escrow.bump = 254;
+    program_escrow.set_ids("req-1", "prop-1").unwrap();
+    program_escrow.created_at = 1;
+    program_escrow.funded_at = 2;
+    program_escrow.delivered_at = 3;
+    program_escrow.disputed_at = 4;
+    program_escrow.delivery_hash = [7; 32];
+    program_escrow.access_mint = Pubkey::new_unique();
+    program_escrow.mint = Pubkey::new_unique();
+    program_escrow.metadata_len = 3;
+
+    let mut data = datanexus_escrow::Escrow::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&program_escrow));
+    data.extend_from_slice(&[1, 2, 3]);
+
+    assert_eq!(
+        Escrow::DISCRIMINATOR,
+        datanexus_escrow::Escrow::DISCRIMINATOR
+    );
+    assert_eq!(datanexus_escrow_types::ID, datanexus_escrow::ID);
+    assert_eq!(
+        datanexus_escrow_types::ESCROW_VERSION,
+        datanexus_escrow::ESCROW_VERSION
+    );
+    assert_eq!(
+        datanexus_escrow_types::MAX_ESCROW_ID_LEN,
+        datanexus_escrow::MAX_ESCROW_ID_LEN
+    );
+
+    assert_eq!(Escrow::LEN, datanexus_escrow::Escrow::LEN);
+    assert_eq!(
+        Escrow::METADATA_OFFSET,
+        datanexus_escrow::Escrow::METADATA_OFFSET
+    );
+    assert_eq!(Escrow::BUYER_OFFSET, datanexus_escrow::Escrow::BUYER_OFFSET);
+    assert_eq!(
+        Escrow::STATUS_OFFSET,
+        datanexus_escrow::Escrow::STATUS_OFFSET
+    );
+    assert_eq!(
+        Escrow::VERSION_OFFSET,
+        datanexus_escrow::Escrow::VERSION_OFFSET
+    );
+    assert_eq!(
+        &data[Escrow::BUYER_OFFSET..][..32],
+        program_escrow.buyer.as_ref()
+    );
+    assert_eq!(
+        &data[Escrow::PROVIDER_OFFSET..][..32],
+        program_escrow.provider.as_ref()
+    );
+    assert_eq!(
+        &data[Escrow::PLATFORM_OFFSET..][..32],
+        program_escrow.platform.as_ref()
+    );
+    assert_eq!(data[Escrow::STATUS_OFFSET], EscrowStatus::Disputed as u8);
+    assert_eq!(
+        data[Escrow::VERSION_OFFSET],
+        datanexus_escrow::ESCROW_VERSION
+    );
+
+    let escrow = Escrow::try_from_account_data(&data).unwrap();
+    assert_eq!(
+        escrow,
+        Escrow {
+            buyer: program_escrow.buyer,
+            provider: program_escrow.provider,
+            platform: program_escrow.platform,
+            amount: 1_000_000,
+            status: EscrowStatus::Disputed,
+            version: datanexus_escrow::ESCROW_VERSION,
+            request_id: "req-1".to_string(),
+            proposal_id: "prop-1".to_string(),
+            created_at: 1,
+            funded_at: Some(2),
+            delivered_at: Some(3),
+            completed_at: None,
+            refunded_at: None,
+            disputed_at: Some(4),
+            delivery_hash: [7; 32],
+            access_mint: Some(program_escrow.access_mint),
+            bump: 254,
+            mint: Some(program_escrow.mint),
+            metadata: vec![1, 2, 3],
+        }
+    );
+}
+
+#[test]
+fn error_codes_match_program() {
+    use datanexus_escrow::EscrowError as ProgramError;
+
+    let program_errors = [
+        ProgramError::InvalidStatus,
+        ProgramError::Unauthorized,
+        ProgramError::InvalidAmount,
+        ProgramError::InvalidFeeSplit,
+        ProgramError::MissingReceiptAccounts,
+        ProgramError::InvalidAccessMint,
+        ProgramError::MissingAccessAccounts,
+        ProgramError::ProviderNotRegistered,
+        ProgramError::ProposalMismatch,
+        ProgramError::AlreadyMigrated,
+        ProgramError::MigrationRequired,
+        ProgramError::InvalidMetadata,
+        ProgramError::MetadataTooLong,
+        ProgramError::InvalidInsuranceBps,
+        ProgramError::MissingInsuranceVault,
+        ProgramError::InsuranceNotClaimable,
+        ProgramError::InsufficientCollateral,
+        ProgramError::InvalidCollateralAccount,
+        ProgramError::RulingRequired,
+        ProgramError::InvalidRuling,
+        ProgramError::AppealWindowClosed,
+        ProgramError::AppealWindowOpen,
+        ProgramError::TimelockActive,
+        ProgramError::InvalidPriceFeed,
+        ProgramError::StalePrice,
+        ProgramError::MissingPriceFeed,
+        ProgramError::MissingAttestation,
+        ProgramError::InvalidAttestation,
+        ProgramError::InvalidBridgePayment,
+        ProgramError::InvalidSwap,
+        ProgramError::InvalidYieldBps,
+        ProgramError::MissingMemoProgram,
+        ProgramError::ComplianceAttestationRequired,
+        ProgramError::InvalidComplianceAttestation,
+        ProgramError::RestrictionRequired,
+        ProgramError::NotRestricted,
+        ProgramError::BlockReasonTooLong,
+        ProgramError::KeyNotDelivered,
+        ProgramError::InvalidEncryptedKey,
+        ProgramError::CommitmentNotRevealed,
+        ProgramError::CommitmentMismatch,
+        ProgramError::InvalidStorageRef,
+        ProgramError::MissingChunkManifest,
+        ProgramError::InvalidChunkManifest,
+        ProgramError::InvalidChunkProof,
+        ProgramError::TooManyDisputedChunks,
+        ProgramError::InvalidChunkRuling,
+        ProgramError::InvalidChallenge,
+        ProgramError::ChallengeClosed,
+        ProgramError::ChallengeOpen,
+        ProgramError::InvalidVerifier,
+        ProgramError::DatasetProofVerified,
+        ProgramError::InvalidStorageAccount,
+        ProgramError::InvalidAccessWindow,
+        ProgramError::AccessNotExpired,
+        ProgramError::AccessRevoked,
+        ProgramError::TooManyRejections,
+        ProgramError::RedeliveryOpen,
+        ProgramError::InvalidQualityScore,
+        ProgramError::InvalidLatenessSchedule,
+        ProgramError::InvalidDeliveryDeadline,
+        ProgramError::MissingBuyerTokenAccount,
+        ProgramError::InvalidBonus,
+        ProgramError::BonusLocked,
+        ProgramError::MessageLogFull,
+        ProgramError::CancelGracePeriodOpen,
+        ProgramError::CancelGracePeriodEnded,
+        ProgramError::EscrowNotStale,
+        ProgramError::QuoteExpired,
+        ProgramError::InvalidCoupon,
+        ProgramError::InvalidUnits,
+        ProgramError::InvalidDownloads,
+        ProgramError::InvalidTemplate,
+        ProgramError::ProposalNotSelected,
+        ProgramError::SalesHistoryFull,
+        ProgramError::BadgeRequired,
+        ProgramError::InvalidBadge,
+        ProgramError::Blacklisted,
+        ProgramError::TooManyOpenEscrows,
+        ProgramError::CreationRateLimited,
+        ProgramError::FeeSplitMismatch,
+        ProgramError::PriceDeviation,
+        ProgramError::UsdPriceNotCovered,
+        ProgramError::SwapSlippageExceeded,
+        ProgramError::IdTooLong,
+        ProgramError::MissingFeeLedger,
+        ProgramError::NoFeesAccrued,
+        ProgramError::MissingPlatformTokenAccount,
+        ProgramError::NotCreated,
+        ProgramError::NotFunded,
+        ProgramError::NotDelivered,
+        ProgramError::NotRejected,
+        ProgramError::NotDisputed,
+        ProgramError::NotRuled,
+        ProgramError::NotCompleted,
+        ProgramError::NotPendingCancel,
+        ProgramError::NotAwaitingDelivery,
+        ProgramError::TermsLocked,
+        ProgramError::ReleaseNotPending,
+        ProgramError::EscrowSettled,
+        ProgramError::OnlyBuyer,
+        ProgramError::OnlyProvider,
+        ProgramError::OnlyBuyerAndProvider,
+        ProgramError::OnlyBuyerCanConfirm,
+        ProgramError::OnlyArbitratorCanRefund,
+        ProgramError::OnlyArbitrator,
+        ProgramError::OnlyPlatform,
+        ProgramError::OnlyAdmin,
+        ProgramError::OnlyParticipant,
+        ProgramError::OnlyLosingParty,
+        ProgramError::OnlyGateway,
+        ProgramError::OnlyTemplateOwner,
+        ProgramError::BuyerMismatch,
+        ProgramError::MintMismatch,
+        ProgramError::DeadlinePassed,
+        ProgramError::OnlyUpgradeAuthority,
+        ProgramError::UnknownEscrowLayout,
+        ProgramError::YieldPositionOpen,
+        ProgramError::EscrowTokenAccountMismatch,
+        ProgramError::QuoteConflictsWithPricing,
+        ProgramError::ProviderMismatch,
+        ProgramError::PlatformMismatch,
+        ProgramError::QuoteAboveAmount,
+        ProgramError::OpenEscrowMismatch,
+    ];
+    assert_eq!(program_errors.len(), EscrowError::ALL.len());
+
+    for (program_error, error) in program_errors.into_iter().zip(EscrowError::ALL) {
+        assert_eq!(u32::from(program_error), error.code());
+        assert_eq!(program_error.to_string(), error.to_string());
+        assert_eq!(EscrowError::from_code(error.code()), Some(*error));
+    }
+}
@@ -0,0 +1,22 @@
+//! 托管指令模糊测试入口
+//!
+//! ```bash
+//! anchor build
+//! cargo hfuzz run fuzz_instructions
+//! ```
+
+use datanexus_fuzz::FuzzData;
+use honggfuzz::fuzz;
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime");
+
+    loop {
+        fuzz!(|data: FuzzData| {
+            runtime.block_on(datanexus_fuzz::run(data));
+        });
+    }
+}
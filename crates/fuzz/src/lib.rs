@@ -0,0 +1,398 @@
+//! DataNexus Fuzz
+//!
+//! 把任意金额、任意长度的 ID 和任意签名者组合成指令序列，在 `solana-program-test` 中逐条执行，
+//! 检查两类问题：
+//! 1. 程序 panic（`unwrap` 溢出等），表现为 `ProgramFailedToComplete`
+//! 2. 约束被绕过：非授权签名者执行成功、状态非法流转、托管代币余额与状态不符
+//!
+//! 发现问题时直接 panic，由 honggfuzz 记录为崩溃样本。
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::associated_token::get_associated_token_address;
+use arbitrary::Arbitrary;
+use datanexus_client::instructions::{self, CreateEscrowParams};
+use datanexus_escrow::EscrowStatus;
+use datanexus_test_fixtures::solana_program_test::BanksClientError;
+use datanexus_test_fixtures::Fixture;
+use solana_instruction::error::InstructionError;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+/// 单个输入最多执行的指令数
+const MAX_INSTRUCTIONS: usize = 16;
+
+/// 买家初始 USDC，足够覆盖多次大额托管以触发统计溢出
+const BUYER_USDC: u64 = u64::MAX / 2;
+
+/// 参与方
+#[derive(Arbitrary, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Actor {
+    Buyer,
+    OtherBuyer,
+    Provider,
+    Admin,
+    Stranger,
+}
+
+/// 模糊输入：一串指令
+#[derive(Arbitrary, Debug)]
+pub struct FuzzData {
+    pub instructions: Vec<FuzzInstruction>,
+}
+
+/// 指令及其参数，`escrow` 为已创建托管列表的下标（取模）
+#[derive(Arbitrary, Debug)]
+pub enum FuzzInstruction {
+    CreateEscrow {
+        buyer: Actor,
+        provider: Actor,
+        amount: u64,
+        request_id: String,
+        proposal_id: String,
+    },
+    MarkDelivered {
+        escrow: u8,
+        signer: Actor,
+        delivery_hash: [u8; 32],
+    },
+    ConfirmAndRelease {
+        escrow: u8,
+        signer: Actor,
+    },
+    RaiseDispute {
+        escrow: u8,
+        signer: Actor,
+    },
+    ResolveDispute {
+        escrow: u8,
+        signer: Actor,
+        refund_to_buyer: bool,
+    },
+    Refund {
+        escrow: u8,
+        signer: Actor,
+    },
+    Cancel {
+        escrow: u8,
+        signer: Actor,
+    },
+    ContestCancel {
+        escrow: u8,
+        signer: Actor,
+    },
+}
+
+/// 一次模糊运行的环境
+struct Harness {
+    fixture: Fixture,
+    buyer: Keypair,
+    other_buyer: Keypair,
+    provider: Keypair,
+    stranger: Keypair,
+    escrows: Vec<Pubkey>,
+}
+
+impl Harness {
+    async fn new() -> Self {
+        let mut fixture = Fixture::start().await;
+        let buyer = fixture.funded_wallet(BUYER_USDC).await;
+        let other_buyer = fixture.funded_wallet(BUYER_USDC).await;
+        let provider = fixture.funded_wallet(0).await;
+        let stranger = fixture.funded_wallet(0).await;
+
+        Self {
+            fixture,
+            buyer,
+            other_buyer,
+            provider,
+            stranger,
+            escrows: Vec::new(),
+        }
+    }
+
+    fn keypair(&self, actor: Actor) -> Keypair {
+        match actor {
+            Actor::Buyer => self.buyer.insecure_clone(),
+            Actor::OtherBuyer => self.other_buyer.insecure_clone(),
+            Actor::Provider => self.provider.insecure_clone(),
+            Actor::Admin => self.fixture.admin.insecure_clone(),
+            Actor::Stranger => self.stranger.insecure_clone(),
+        }
+    }
+
+    fn escrow(&self, index: u8) -> Option<Pubkey> {
+        if self.escrows.is_empty() {
+            return None;
+        }
+        Some(self.escrows[index as usize % self.escrows.len()])
+    }
+
+    async fn escrow_balance(&mut self, escrow: &Pubkey) -> u64 {
+        let mint = self.fixture.mint;
+        self.fixture
+            .token_balance(&get_associated_token_address(escrow, &mint))
+            .await
+    }
+
+    async fn execute(&mut self, instruction: FuzzInstruction) {
+        match instruction {
+            FuzzInstruction::CreateEscrow {
+                buyer,
+                provider,
+                amount,
+                request_id,
+                proposal_id,
+            } => {
+                let buyer = self.keypair(buyer);
+                let provider = self.keypair(provider).pubkey();
+
+                // 超过 32 字节的种子在客户端推导 PDA 时就会失败，程序无从接收
+                let Some((escrow, _)) = Pubkey::try_find_program_address(
+                    &[b"escrow", buyer.pubkey().as_ref(), request_id.as_bytes()],
+                    &datanexus_escrow::ID,
+                ) else {
+                    return;
+                };
+                let provider_sequence = self.fixture.provider_sequence(&provider).await;
+
+                let result = self
+                    .fixture
+                    .process(
+                        &[
+                            instructions::create_escrow_token_account(
+                                buyer.pubkey(),
+                                escrow,
+                                self.fixture.mint,
+                            ),
+                            instructions::create_escrow(CreateEscrowParams {
+                                buyer: buyer.pubkey(),
+                                provider,
+                                platform: self.fixture.admin.pubkey(),
+                                amount,
+                                mint: self.fixture.mint,
+                                request_id,
+                                proposal_id,
+                                provider_sequence,
+                                buyer_attestation: None,
+                                provider_attestation: None,
+                                provider_badge: None,
+                                coupon: None,
+                                coupon_code: None,
+                            }),
+                            instructions::fund_escrow(
+                                escrow,
+                                buyer.pubkey(),
+                                self.fixture.mint,
+                                None,
+                            ),
+                        ],
+                        &[&buyer],
+                    )
+                    .await;
+
+                if check(result) {
+                    let account = self.fixture.fetch_escrow(&escrow).await;
+                    assert_eq!(account.status(), EscrowStatus::Funded);
+                    assert_eq!(account.amount, amount);
+                    assert_eq!(self.escrow_balance(&escrow).await, amount);
+                    self.escrows.push(escrow);
+                }
+            }
+            FuzzInstruction::MarkDelivered {
+                escrow,
+                signer,
+                delivery_hash,
+            } => {
+                let Some(escrow) = self.escrow(escrow) else {
+                    return;
+                };
+                let signer = self.keypair(signer);
+                let ix = instructions::mark_delivered(escrow, signer.pubkey(), delivery_hash, None);
+                self.transition(escrow, ix, &signer, Party::Provider, EscrowStatus::Funded)
+                    .await;
+            }
+            FuzzInstruction::ConfirmAndRelease { escrow, signer } => {
+                let Some(escrow) = self.escrow(escrow) else {
+                    return;
+                };
+                let signer = self.keypair(signer);
+                let mut account = self.fixture.fetch_escrow(&escrow).await;
+                account.buyer = signer.pubkey();
+                let config = self.fixture.fetch_config().await;
+                let ix = instructions::confirm_and_release(
+                    escrow,
+                    &account,
+                    &config,
+                    self.fixture.mint,
+                    None,
+                    None,
+                    None,
+                );
+                self.transition(escrow, ix, &signer, Party::Buyer, EscrowStatus::Delivered)
+                    .await;
+            }
+            FuzzInstruction::RaiseDispute { escrow, signer } => {
+                let Some(escrow) = self.escrow(escrow) else {
+                    return;
+                };
+                let signer = self.keypair(signer);
+                let mut account = self.fixture.fetch_escrow(&escrow).await;
+                account.buyer = signer.pubkey();
+                let ix =
+                    instructions::raise_dispute(escrow, &account, self.fixture.mint, Vec::new());
+                self.transition(escrow, ix, &signer, Party::Buyer, EscrowStatus::Delivered)
+                    .await;
+            }
+            FuzzInstruction::ResolveDispute {
+                escrow,
+                signer,
+                refund_to_buyer,
+            } => {
+                let Some(escrow) = self.escrow(escrow) else {
+                    return;
+                };
+                let signer = self.keypair(signer);
+                let mut account = self.fixture.fetch_escrow(&escrow).await;
+                account.platform = signer.pubkey();
+                let config = self.fixture.fetch_config().await;
+                let ix = instructions::resolve_dispute(
+                    escrow,
+                    &account,
+                    &config,
+                    self.fixture.mint,
+                    None,
+                    refund_to_buyer,
+                );
+                self.transition(escrow, ix, &signer, Party::Platform, EscrowStatus::Disputed)
+                    .await;
+            }
+            FuzzInstruction::Refund { escrow, signer } => {
+                let Some(escrow) = self.escrow(escrow) else {
+                    return;
+                };
+                let signer = self.keypair(signer);
+                let mut account = self.fixture.fetch_escrow(&escrow).await;
+                account.platform = signer.pubkey();
+                let ix = instructions::refund(escrow, &account, self.fixture.mint);
+                self.transition(escrow, ix, &signer, Party::Platform, EscrowStatus::Disputed)
+                    .await;
+            }
+            FuzzInstruction::Cancel { escrow, signer } => {
+                let Some(escrow) = self.escrow(escrow) else {
+                    return;
+                };
+                let signer = self.keypair(signer);
+                let mut account = self.fixture.fetch_escrow(&escrow).await;
+                account.buyer = signer.pubkey();
+                let ix = instructions::cancel(escrow, &account);
+                self.transition(escrow, ix, &signer, Party::Buyer, EscrowStatus::Funded)
+                    .await;
+            }
+            FuzzInstruction::ContestCancel { escrow, signer } => {
+                let Some(escrow) = self.escrow(escrow) else {
+                    return;
+                };
+                let signer = self.keypair(signer);
+                let mut account = self.fixture.fetch_escrow(&escrow).await;
+                account.provider = signer.pubkey();
+                let ix = instructions::contest_cancel(escrow, &account);
+                self.transition(
+                    escrow,
+                    ix,
+                    &signer,
+                    Party::Provider,
+                    EscrowStatus::PendingCancel,
+                )
+                .await;
+            }
+        }
+    }
+
+    /// 执行一条状态流转指令，成功时校验签名者、原状态和托管余额
+    async fn transition(
+        &mut self,
+        escrow: Pubkey,
+        ix: Instruction,
+        signer: &Keypair,
+        party: Party,
+        from: EscrowStatus,
+    ) {
+        let before = self.fixture.fetch_escrow(&escrow).await;
+        let result = self.fixture.process(&[ix], &[signer]).await;
+        if !check(result) {
+            return;
+        }
+
+        let authorized = match party {
+            Party::Buyer => before.buyer,
+            Party::Provider => before.provider,
+            Party::Platform => before.platform,
+        };
+        assert_eq!(
+            signer.pubkey(),
+            authorized,
+            "constraint bypass: unauthorized signer succeeded on {:?}",
+            escrow
+        );
+        assert_eq!(
+            before.status(),
+            from,
+            "constraint bypass: illegal transition from {:?}",
+            before.status()
+        );
+
+        let after = self.fixture.fetch_escrow(&escrow).await;
+        let balance = self.escrow_balance(&escrow).await;
+        match after.status() {
+            EscrowStatus::Funded
+            | EscrowStatus::Delivered
+            | EscrowStatus::Disputed
+            | EscrowStatus::Escalated
+            | EscrowStatus::Ruled
+            | EscrowStatus::Appealed
+            | EscrowStatus::Rejected
+            | EscrowStatus::PendingCancel => {
+                assert_eq!(balance, after.amount, "escrow balance mismatch");
+            }
+            EscrowStatus::Completed | EscrowStatus::Refunded | EscrowStatus::Cancelled => {
+                assert_eq!(balance, 0, "funds left in settled escrow");
+            }
+            EscrowStatus::Created => panic!("escrow reverted to Created"),
+        }
+    }
+}
+
+/// 指令的授权方
+#[derive(Clone, Copy)]
+enum Party {
+    Buyer,
+    Provider,
+    Platform,
+}
+
+/// 交易是否成功；程序 panic 时直接 panic
+fn check(result: Result<(), BanksClientError>) -> bool {
+    match result {
+        Ok(()) => true,
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            index,
+            InstructionError::ProgramFailedToComplete,
+        )))
+        | Err(BanksClientError::SimulationError {
+            err:
+                TransactionError::InstructionError(index, InstructionError::ProgramFailedToComplete),
+            ..
+        }) => panic!("program panicked in instruction {}", index),
+        Err(_) => false,
+    }
+}
+
+/// 在全新的测试验证器上执行一组指令
+pub async fn run(data: FuzzData) {
+    let mut harness = Harness::new().await;
+    for instruction in data.instructions.into_iter().take(MAX_INSTRUCTIONS) {
+        harness.execute(instruction).await;
+    }
+}
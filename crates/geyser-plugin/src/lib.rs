@@ -0,0 +1,220 @@
+//! DataNexus Geyser Plugin
+//!
+//! 在验证节点内过滤托管程序的账户更新，解码 `Escrow` 账户并以 JSON 发布到 NATS，
+//! 平台无需轮询 RPC 即可实现亚秒级的界面更新。
+//!
+//! 配置文件（验证节点 `--geyser-plugin-config` 指定）：
+//!
+//! ```json
+//! {
+//!     "libpath": "/path/to/libdatanexus_geyser_plugin.so",
+//!     "nats_url": "nats://127.0.0.1:4222",
+//!     "subject_prefix": "datanexus.escrow",
+//!     "publish_startup": false
+//! }
+//! ```
+//!
+//! 每次更新发布到主题 `<subject_prefix>.<托管地址>`
+
+use std::fs;
+
+use agave_geyser_plugin_interface::geyser_plugin_interface::{
+    GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions, Result,
+};
+use datanexus_escrow_types::Escrow;
+use serde::{Deserialize, Serialize};
+use solana_pubkey::Pubkey;
+use tokio::runtime::Runtime;
+
+/// 插件配置
+#[derive(Deserialize)]
+struct PluginConfig {
+    nats_url: String, // NATS 服务地址
+    #[serde(default = "default_subject_prefix")]
+    subject_prefix: String, // 发布主题前缀
+    #[serde(default)]
+    publish_startup: bool, // 是否发布启动时从快照加载的账户
+}
+
+fn default_subject_prefix() -> String {
+    "datanexus.escrow".to_string()
+}
+
+/// 发布的托管更新
+#[derive(Serialize)]
+struct EscrowUpdate {
+    pubkey: String,
+    slot: u64,
+    write_version: u64,
+    status: String,
+    buyer: String,
+    provider: String,
+    amount: u64,
+    request_id: String,
+    proposal_id: String,
+    created_at: i64,
+    delivered_at: Option<i64>,
+    completed_at: Option<i64>,
+    refunded_at: Option<i64>,
+    disputed_at: Option<i64>,
+}
+
+/// 插件自带 tokio 运行时，发布在后台完成，不阻塞验证节点线程
+#[derive(Default)]
+pub struct EscrowGeyserPlugin {
+    runtime: Option<Runtime>,
+    client: Option<async_nats::Client>,
+    subject_prefix: String,
+    publish_startup: bool,
+}
+
+impl std::fmt::Debug for EscrowGeyserPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EscrowGeyserPlugin")
+            .field("connected", &self.client.is_some())
+            .field("subject_prefix", &self.subject_prefix)
+            .finish()
+    }
+}
+
+impl GeyserPlugin for EscrowGeyserPlugin {
+    fn name(&self) -> &'static str {
+        "datanexus-geyser-plugin"
+    }
+
+    fn on_load(&mut self, config_file: &str, _is_reload: bool) -> Result<()> {
+        let contents = fs::read_to_string(config_file)?;
+        let config: PluginConfig = serde_json::from_str(&contents).map_err(|err| {
+            GeyserPluginError::ConfigFileReadError {
+                msg: err.to_string(),
+            }
+        })?;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("datanexus-geyser")
+            .enable_all()
+            .build()?;
+        let client = runtime
+            .block_on(async_nats::connect(&config.nats_url))
+            .map_err(|err| GeyserPluginError::Custom(err.into()))?;
+
+        self.runtime = Some(runtime);
+        self.client = Some(client);
+        self.subject_prefix = config.subject_prefix;
+        self.publish_startup = config.publish_startup;
+
+        log::info!(
+            "{} loaded, publishing escrow {} updates to {}",
+            self.name(),
+            datanexus_escrow_types::ID,
+            config.nats_url
+        );
+
+        Ok(())
+    }
+
+    fn on_unload(&mut self) {
+        if let (Some(runtime), Some(client)) = (self.runtime.take(), self.client.take()) {
+            if let Err(err) = runtime.block_on(client.flush()) {
+                log::warn!("Failed to flush pending escrow updates: {}", err);
+            }
+        }
+    }
+
+    fn update_account(
+        &self,
+        account: ReplicaAccountInfoVersions,
+        slot: u64,
+        is_startup: bool,
+    ) -> Result<()> {
+        if is_startup && !self.publish_startup {
+            return Ok(());
+        }
+
+        let (pubkey, owner, data, write_version) = match account {
+            ReplicaAccountInfoVersions::V0_0_1(info) => {
+                (info.pubkey, info.owner, info.data, info.write_version)
+            }
+            ReplicaAccountInfoVersions::V0_0_2(info) => {
+                (info.pubkey, info.owner, info.data, info.write_version)
+            }
+            ReplicaAccountInfoVersions::V0_0_3(info) => {
+                (info.pubkey, info.owner, info.data, info.write_version)
+            }
+        };
+
+        // 只关心托管程序拥有的 Escrow 账户
+        if owner != datanexus_escrow_types::ID.as_ref() || !data.starts_with(&Escrow::DISCRIMINATOR)
+        {
+            return Ok(());
+        }
+
+        let pubkey =
+            Pubkey::try_from(pubkey).map_err(|err| GeyserPluginError::AccountsUpdateError {
+                msg: err.to_string(),
+            })?;
+
+        // 关闭中的账户数据可能无法解码，直接跳过
+        let Ok(escrow) = Escrow::try_from_account_data(data) else {
+            return Ok(());
+        };
+
+        let update = EscrowUpdate {
+            pubkey: pubkey.to_string(),
+            slot,
+            write_version,
+            status: format!("{:?}", escrow.status),
+            buyer: escrow.buyer.to_string(),
+            provider: escrow.provider.to_string(),
+            amount: escrow.amount,
+            request_id: escrow.request_id,
+            proposal_id: escrow.proposal_id,
+            created_at: escrow.created_at,
+            delivered_at: escrow.delivered_at,
+            completed_at: escrow.completed_at,
+            refunded_at: escrow.refunded_at,
+            disputed_at: escrow.disputed_at,
+        };
+        let payload =
+            serde_json::to_vec(&update).map_err(|err| GeyserPluginError::AccountsUpdateError {
+                msg: err.to_string(),
+            })?;
+
+        if let (Some(runtime), Some(client)) = (&self.runtime, &self.client) {
+            let subject = format!("{}.{}", self.subject_prefix, update.pubkey);
+            let client = client.clone();
+            runtime.spawn(async move {
+                if let Err(err) = client.publish(subject, payload.into()).await {
+                    log::warn!("Failed to publish escrow update: {}", err);
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    fn account_data_notifications_enabled(&self) -> bool {
+        true
+    }
+
+    fn account_data_snapshot_notifications_enabled(&self) -> bool {
+        self.publish_startup
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        false
+    }
+}
+
+/// 验证节点加载插件的入口
+///
+/// # Safety
+///
+/// 由验证节点通过 `libloading` 调用，返回的指针所有权转移给调用方
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+pub unsafe extern "C" fn _create_plugin() -> *mut dyn GeyserPlugin {
+    let plugin: Box<dyn GeyserPlugin> = Box::new(EscrowGeyserPlugin::default());
+    Box::into_raw(plugin)
+}
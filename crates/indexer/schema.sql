@@ -0,0 +1,59 @@
+-- DataNexus 托管索引表结构（启动时自动执行，可重复执行）
+
+-- 托管当前状态（由事件累积得到）
+CREATE TABLE IF NOT EXISTS escrows (
+    address          TEXT PRIMARY KEY,
+    buyer            TEXT NOT NULL,
+    provider         TEXT NOT NULL,
+    amount           BIGINT NOT NULL,
+    request_id       TEXT NOT NULL,
+    status           TEXT NOT NULL,
+    delivery_hash    TEXT,
+    provider_amount  BIGINT,
+    platform_fee     BIGINT,
+    tip              BIGINT,
+    created_at       BIGINT NOT NULL,
+    updated_at       BIGINT NOT NULL
+);
+
+ALTER TABLE escrows ADD COLUMN IF NOT EXISTS tip BIGINT;
+
+CREATE INDEX IF NOT EXISTS escrows_buyer_idx ON escrows (buyer);
+CREATE INDEX IF NOT EXISTS escrows_provider_idx ON escrows (provider);
+CREATE INDEX IF NOT EXISTS escrows_status_idx ON escrows (status);
+
+-- 状态流转历史
+CREATE TABLE IF NOT EXISTS escrow_transitions (
+    id          BIGSERIAL PRIMARY KEY,
+    escrow      TEXT NOT NULL,
+    status      TEXT NOT NULL,
+    signature   TEXT NOT NULL,
+    slot        BIGINT NOT NULL,
+    timestamp   BIGINT NOT NULL,
+    UNIQUE (signature, escrow, status)
+);
+
+CREATE INDEX IF NOT EXISTS escrow_transitions_escrow_idx ON escrow_transitions (escrow);
+
+-- 争议记录
+CREATE TABLE IF NOT EXISTS disputes (
+    escrow              TEXT PRIMARY KEY,
+    buyer               TEXT NOT NULL,
+    raised_signature    TEXT NOT NULL,
+    raised_at           BIGINT NOT NULL,
+    resolved_signature  TEXT,
+    resolved_at         BIGINT,
+    refund_to_buyer     BOOLEAN
+);
+
+-- 提供商销售记录叶子（与链上 SalesHistory 的 Merkle 树一致）
+CREATE TABLE IF NOT EXISTS sales (
+    provider       TEXT NOT NULL,
+    leaf_index     BIGINT NOT NULL,
+    escrow         TEXT NOT NULL,
+    amount_bucket  SMALLINT NOT NULL,
+    category       INTEGER NOT NULL,
+    root           TEXT NOT NULL,
+    timestamp      BIGINT NOT NULL,
+    PRIMARY KEY (provider, leaf_index)
+);
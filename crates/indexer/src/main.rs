@@ -0,0 +1,82 @@
+//! DataNexus Indexer
+//!
+//! 订阅托管程序日志，解码 Anchor 事件并写入 Postgres：
+//! - `escrows`：每个托管的当前状态
+//! - `escrow_transitions`：状态流转历史
+//! - `disputes`：争议的发起与裁决
+//!
+//! 环境变量：
+//! - `DATABASE_URL`：Postgres 连接串（必填）
+//! - `DATANEXUS_WS_URL`：RPC WebSocket 地址（默认 devnet）
+
+mod store;
+
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use datanexus_client::{events, ESCROW_PROGRAM_ID};
+use solana_commitment_config::CommitmentConfig;
+use solana_pubsub_client::pubsub_client::PubsubClient;
+use solana_rpc_client_api::config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+
+use crate::store::Store;
+
+/// 断线重连间隔
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+fn main() -> Result<()> {
+    let database_url = std::env::var("DATABASE_URL").context("DATABASE_URL is not set")?;
+    let ws_url = std::env::var("DATANEXUS_WS_URL")
+        .unwrap_or_else(|_| "wss://api.devnet.solana.com".to_string());
+
+    let mut store = Store::connect(&database_url)?;
+    println!("Indexing escrow program {}", ESCROW_PROGRAM_ID);
+
+    loop {
+        if let Err(err) = run(&ws_url, &mut store) {
+            eprintln!("Subscription failed: {:#}", err);
+        }
+        eprintln!("Reconnecting in {}s", RECONNECT_DELAY.as_secs());
+        thread::sleep(RECONNECT_DELAY);
+    }
+}
+
+/// 订阅日志直到连接断开
+fn run(ws_url: &str, store: &mut Store) -> Result<()> {
+    let (_subscription, receiver) = PubsubClient::logs_subscribe(
+        ws_url,
+        RpcTransactionLogsFilter::Mentions(vec![ESCROW_PROGRAM_ID.to_string()]),
+        RpcTransactionLogsConfig {
+            commitment: Some(CommitmentConfig::confirmed()),
+        },
+    )?;
+    println!("Subscribed to {}", ws_url);
+
+    for response in receiver.iter() {
+        let slot = response.context.slot;
+        let logs = response.value;
+
+        // 失败的交易不改变链上状态
+        if logs.err.is_some() {
+            continue;
+        }
+
+        let events = events::parse_logs(&logs.logs);
+        if events.is_empty() {
+            continue;
+        }
+
+        store
+            .record(&logs.signature, slot, &events)
+            .with_context(|| format!("failed to record transaction {}", logs.signature))?;
+        println!(
+            "Indexed {} event(s) from {} at slot {}",
+            events.len(),
+            logs.signature,
+            slot
+        );
+    }
+
+    Ok(())
+}
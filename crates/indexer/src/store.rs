@@ -0,0 +1,254 @@
+//! Postgres 持久化
+
+use anyhow::Result;
+use datanexus_client::events::EscrowEvent;
+use postgres::{Client, NoTls, Transaction};
+
+/// 表结构，启动时执行
+const SCHEMA: &str = include_str!("../schema.sql");
+
+pub struct Store {
+    client: Client,
+}
+
+impl Store {
+    /// 连接数据库并确保表结构存在
+    pub fn connect(database_url: &str) -> Result<Self> {
+        let mut client = Client::connect(database_url, NoTls)?;
+        client.batch_execute(SCHEMA)?;
+        Ok(Self { client })
+    }
+
+    /// 在一个数据库事务中写入一笔链上交易的全部事件
+    pub fn record(&mut self, signature: &str, slot: u64, events: &[EscrowEvent]) -> Result<()> {
+        let mut tx = self.client.transaction()?;
+        for event in events {
+            apply(&mut tx, signature, slot, event)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn apply(tx: &mut Transaction, signature: &str, slot: u64, event: &EscrowEvent) -> Result<()> {
+    let escrow = event.escrow().to_string();
+
+    match event {
+        EscrowEvent::Created(event) => {
+            tx.execute(
+                "INSERT INTO escrows
+                     (address, buyer, provider, amount, request_id, status, created_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, 'Created', $6, $6)
+                 ON CONFLICT (address) DO NOTHING",
+                &[
+                    &escrow,
+                    &event.buyer.to_string(),
+                    &event.provider.to_string(),
+                    &to_bigint(event.amount),
+                    &event.request_id,
+                    &event.timestamp,
+                ],
+            )?;
+            transition(tx, &escrow, "Created", signature, slot, event.timestamp)?;
+        }
+        EscrowEvent::Funded(event) => {
+            set_status(tx, &escrow, "Funded", event.timestamp)?;
+            transition(tx, &escrow, "Funded", signature, slot, event.timestamp)?;
+        }
+        EscrowEvent::Delivered(event) => {
+            let delivery_hash: String = event
+                .delivery_hash
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            tx.execute(
+                "UPDATE escrows SET status = 'Delivered', delivery_hash = $2, updated_at = $3
+                 WHERE address = $1",
+                &[&escrow, &delivery_hash, &event.timestamp],
+            )?;
+            transition(tx, &escrow, "Delivered", signature, slot, event.timestamp)?;
+        }
+        EscrowEvent::Rejected(event) => {
+            tx.execute(
+                "UPDATE escrows SET status = 'Rejected', delivery_hash = NULL, updated_at = $2
+                 WHERE address = $1",
+                &[&escrow, &event.timestamp],
+            )?;
+            transition(tx, &escrow, "Rejected", signature, slot, event.timestamp)?;
+        }
+        EscrowEvent::Released(event) => {
+            tx.execute(
+                "UPDATE escrows
+                 SET status = 'Completed', provider_amount = $2, platform_fee = $3, tip = $4,
+                     updated_at = $5
+                 WHERE address = $1",
+                &[
+                    &escrow,
+                    &to_bigint(event.provider_amount),
+                    &to_bigint(event.platform_fee),
+                    &to_bigint(event.tip),
+                    &event.timestamp,
+                ],
+            )?;
+            transition(tx, &escrow, "Completed", signature, slot, event.timestamp)?;
+        }
+        // 按件交付：累计结算金额，最后一批交付后托管完成
+        EscrowEvent::UnitsDelivered(event) => {
+            let completed = event.delivered == event.quantity;
+            tx.execute(
+                "UPDATE escrows
+                 SET status = $2, provider_amount = COALESCE(provider_amount, 0) + $3,
+                     platform_fee = COALESCE(platform_fee, 0) + $4, updated_at = $5
+                 WHERE address = $1",
+                &[
+                    &escrow,
+                    &if completed { "Completed" } else { "Funded" },
+                    &to_bigint(event.provider_amount),
+                    &to_bigint(event.platform_fee),
+                    &event.timestamp,
+                ],
+            )?;
+            if completed {
+                transition(tx, &escrow, "Completed", signature, slot, event.timestamp)?;
+            }
+        }
+        // 按下载结算：累计结算金额，最后一次下载后托管完成
+        EscrowEvent::DownloadConsumed(event) => {
+            let completed = event.downloads_remaining == 0;
+            tx.execute(
+                "UPDATE escrows
+                 SET status = $2, provider_amount = COALESCE(provider_amount, 0) + $3,
+                     platform_fee = COALESCE(platform_fee, 0) + $4, updated_at = $5
+                 WHERE address = $1",
+                &[
+                    &escrow,
+                    &if completed { "Completed" } else { "Funded" },
+                    &to_bigint(event.provider_amount),
+                    &to_bigint(event.platform_fee),
+                    &event.timestamp,
+                ],
+            )?;
+            if completed {
+                transition(tx, &escrow, "Completed", signature, slot, event.timestamp)?;
+            }
+        }
+        EscrowEvent::Refunded(event) => {
+            set_status(tx, &escrow, "Refunded", event.timestamp)?;
+            transition(tx, &escrow, "Refunded", signature, slot, event.timestamp)?;
+        }
+        EscrowEvent::CancelRequested(event) => {
+            set_status(tx, &escrow, "PendingCancel", event.timestamp)?;
+            transition(
+                tx,
+                &escrow,
+                "PendingCancel",
+                signature,
+                slot,
+                event.timestamp,
+            )?;
+        }
+        EscrowEvent::Cancelled(event) => {
+            set_status(tx, &escrow, "Cancelled", event.timestamp)?;
+            transition(tx, &escrow, "Cancelled", signature, slot, event.timestamp)?;
+        }
+        EscrowEvent::Reclaimed(event) => {
+            set_status(tx, &escrow, "Reclaimed", event.timestamp)?;
+            transition(tx, &escrow, "Reclaimed", signature, slot, event.timestamp)?;
+        }
+        EscrowEvent::DisputeRaised(event) => {
+            set_status(tx, &escrow, "Disputed", event.timestamp)?;
+            transition(tx, &escrow, "Disputed", signature, slot, event.timestamp)?;
+            tx.execute(
+                "INSERT INTO disputes (escrow, buyer, raised_signature, raised_at)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (escrow) DO NOTHING",
+                &[
+                    &escrow,
+                    &event.buyer.to_string(),
+                    &signature,
+                    &event.timestamp,
+                ],
+            )?;
+        }
+        EscrowEvent::DisputeEscalated(event) => {
+            set_status(tx, &escrow, "Escalated", event.timestamp)?;
+            transition(tx, &escrow, "Escalated", signature, slot, event.timestamp)?;
+        }
+        EscrowEvent::DisputeRuled(event) => {
+            set_status(tx, &escrow, "Ruled", event.timestamp)?;
+            transition(tx, &escrow, "Ruled", signature, slot, event.timestamp)?;
+        }
+        EscrowEvent::DisputeAppealed(event) => {
+            set_status(tx, &escrow, "Appealed", event.timestamp)?;
+            transition(tx, &escrow, "Appealed", signature, slot, event.timestamp)?;
+        }
+        EscrowEvent::DisputeResolved(event) => {
+            tx.execute(
+                "UPDATE disputes
+                 SET resolved_signature = $2, resolved_at = $3, refund_to_buyer = $4
+                 WHERE escrow = $1",
+                &[
+                    &escrow,
+                    &signature,
+                    &event.timestamp,
+                    &event.refund_to_buyer,
+                ],
+            )?;
+        }
+        // 销售记录叶子，按序号保存供提供商重建 Merkle 树
+        EscrowEvent::SaleRecorded(event) => {
+            let root: String = event
+                .root
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            tx.execute(
+                "INSERT INTO sales
+                     (provider, leaf_index, escrow, amount_bucket, category, root, timestamp)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (provider, leaf_index) DO NOTHING",
+                &[
+                    &event.provider.to_string(),
+                    &to_bigint(event.index),
+                    &escrow,
+                    &(event.amount_bucket as i16),
+                    &(event.category as i32),
+                    &root,
+                    &event.timestamp,
+                ],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn set_status(tx: &mut Transaction, escrow: &str, status: &str, timestamp: i64) -> Result<()> {
+    tx.execute(
+        "UPDATE escrows SET status = $2, updated_at = $3 WHERE address = $1",
+        &[&escrow, &status, &timestamp],
+    )?;
+    Ok(())
+}
+
+fn transition(
+    tx: &mut Transaction,
+    escrow: &str,
+    status: &str,
+    signature: &str,
+    slot: u64,
+    timestamp: i64,
+) -> Result<()> {
+    tx.execute(
+        "INSERT INTO escrow_transitions (escrow, status, signature, slot, timestamp)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (signature, escrow, status) DO NOTHING",
+        &[&escrow, &status, &signature, &to_bigint(slot), &timestamp],
+    )?;
+    Ok(())
+}
+
+/// Postgres 没有无符号整数，u64 按 BIGINT 存储
+fn to_bigint(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
@@ -0,0 +1,52 @@
+//! DataNexus Notifier
+//!
+//! 订阅托管程序事件，通过 WebSocket 向按钱包地址订阅的客户端实时推送 JSON 通知
+//! （已交付、已释放、已发起争议等）。
+//!
+//! 环境变量：
+//! - `DATANEXUS_WS_URL`：RPC WebSocket 地址（默认 devnet）
+//! - `DATANEXUS_RPC_URL`：RPC HTTP 地址（默认 devnet）
+//! - `LISTEN_ADDR`：WebSocket 服务监听地址（默认 0.0.0.0:8900）
+
+mod server;
+mod tail;
+
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+
+/// 断线重连间隔
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// 广播缓冲区大小，慢客户端超出后丢弃旧通知
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let ws_url = std::env::var("DATANEXUS_WS_URL")
+        .unwrap_or_else(|_| "wss://api.devnet.solana.com".to_string());
+    let rpc_url = std::env::var("DATANEXUS_RPC_URL")
+        .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
+    let listen_addr = std::env::var("LISTEN_ADDR").unwrap_or_else(|_| "0.0.0.0:8900".to_string());
+
+    let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+
+    let listener = TcpListener::bind(&listen_addr).await?;
+    println!("Listening on ws://{}", listen_addr);
+    let server_sender = sender.clone();
+    tokio::spawn(async move {
+        if let Err(err) = server::serve(listener, server_sender).await {
+            eprintln!("WebSocket server stopped: {:#}", err);
+        }
+    });
+
+    loop {
+        if let Err(err) = tail::run(&ws_url, &rpc_url, &sender).await {
+            eprintln!("Subscription failed: {:#}", err);
+        }
+        eprintln!("Reconnecting in {}s", RECONNECT_DELAY.as_secs());
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
@@ -0,0 +1,103 @@
+//! WebSocket 服务
+//!
+//! 客户端发送 `{"subscribe": "<钱包地址>"}` / `{"unsubscribe": "<钱包地址>"}`，
+//! 之后收到以该地址为买家或提供商的托管通知
+
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::tail::Notification;
+
+/// 客户端请求
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Request {
+    Subscribe(String),
+    Unsubscribe(String),
+}
+
+/// 接受连接，每个连接一个任务
+pub async fn serve(
+    listener: TcpListener,
+    sender: broadcast::Sender<Arc<Notification>>,
+) -> Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let receiver = sender.subscribe();
+        tokio::spawn(async move {
+            if let Err(err) = handle(stream, peer, receiver).await {
+                eprintln!("Connection {} closed with error: {:#}", peer, err);
+            }
+        });
+    }
+}
+
+async fn handle(
+    stream: TcpStream,
+    peer: SocketAddr,
+    mut receiver: broadcast::Receiver<Arc<Notification>>,
+) -> Result<()> {
+    let mut socket = tokio_tungstenite::accept_async(stream).await?;
+    let mut wallets: HashSet<Pubkey> = HashSet::new();
+    println!("Client connected: {}", peer);
+
+    loop {
+        tokio::select! {
+            message = socket.next() => {
+                let Some(message) = message else { break };
+                match message? {
+                    Message::Text(text) => {
+                        let reply = match apply_request(&mut wallets, &text) {
+                            Ok(()) => serde_json::json!({ "ok": true }),
+                            Err(err) => serde_json::json!({ "ok": false, "error": err }),
+                        };
+                        socket.send(Message::Text(reply.to_string())).await?;
+                    }
+                    Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+            notification = receiver.recv() => {
+                let notification = match notification {
+                    Ok(notification) => notification,
+                    Err(RecvError::Lagged(skipped)) => {
+                        eprintln!("Client {} lagged, {} notification(s) dropped", peer, skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if wallets.contains(&notification.buyer) || wallets.contains(&notification.provider) {
+                    socket
+                        .send(Message::Text(serde_json::to_string(notification.as_ref())?))
+                        .await?;
+                }
+            }
+        }
+    }
+
+    println!("Client disconnected: {}", peer);
+    Ok(())
+}
+
+fn apply_request(wallets: &mut HashSet<Pubkey>, text: &str) -> std::result::Result<(), String> {
+    let request: Request = serde_json::from_str(text).map_err(|err| err.to_string())?;
+    match request {
+        Request::Subscribe(wallet) => {
+            wallets.insert(Pubkey::from_str(&wallet).map_err(|err| err.to_string())?);
+        }
+        Request::Unsubscribe(wallet) => {
+            wallets.remove(&Pubkey::from_str(&wallet).map_err(|err| err.to_string())?);
+        }
+    }
+    Ok(())
+}
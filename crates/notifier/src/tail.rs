@@ -0,0 +1,158 @@
+//! 订阅托管程序日志并广播通知
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anyhow::Result;
+use datanexus_client::events::{self, EscrowEvent};
+use datanexus_escrow::Escrow;
+use futures_util::StreamExt;
+use serde::Serialize;
+use solana_commitment_config::CommitmentConfig;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use tokio::sync::broadcast;
+
+/// 推送给 WebSocket 客户端的通知
+#[derive(Serialize)]
+pub struct Notification {
+    /// created / delivered / rejected / released / refunded / cancelled / disputed / escalated / resolved / ruled / appealed
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// 托管地址
+    pub escrow: String,
+    /// 买家（仅用于路由）
+    #[serde(skip)]
+    pub buyer: Pubkey,
+    /// 提供商（仅用于路由）
+    #[serde(skip)]
+    pub provider: Pubkey,
+    /// 交易签名
+    pub signature: String,
+    /// 链上时间
+    pub timestamp: i64,
+    /// 相关金额（释放时为提供商所得）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<u64>,
+}
+
+/// 托管双方，事件里缺少的一方通过 RPC 读取托管账户补齐
+struct Parties {
+    rpc: RpcClient,
+    cache: HashMap<Pubkey, (Pubkey, Pubkey)>,
+}
+
+impl Parties {
+    async fn resolve(&mut self, escrow: &Pubkey) -> Result<(Pubkey, Pubkey)> {
+        if let Some(parties) = self.cache.get(escrow) {
+            return Ok(*parties);
+        }
+
+        let data = self.rpc.get_account_data(escrow).await?;
+        let account = Escrow::try_deserialize(&mut data.as_slice())?;
+        let parties = (account.buyer, account.provider);
+        self.cache.insert(*escrow, parties);
+
+        Ok(parties)
+    }
+}
+
+/// 订阅日志直到连接断开
+pub async fn run(
+    ws_url: &str,
+    rpc_url: &str,
+    sender: &broadcast::Sender<Arc<Notification>>,
+) -> Result<()> {
+    let pubsub = PubsubClient::new(ws_url).await?;
+    let (mut stream, _unsubscribe) = pubsub
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![datanexus_escrow::ID.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )
+        .await?;
+    println!("Subscribed to {}", ws_url);
+
+    let mut parties = Parties {
+        rpc: RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()),
+        cache: HashMap::new(),
+    };
+
+    while let Some(response) = stream.next().await {
+        let logs = response.value;
+        if logs.err.is_some() {
+            continue;
+        }
+
+        for event in events::parse_logs(&logs.logs) {
+            let escrow = event.escrow();
+            let (buyer, provider) = match &event {
+                EscrowEvent::Created(event) => {
+                    parties.cache.insert(escrow, (event.buyer, event.provider));
+                    (event.buyer, event.provider)
+                }
+                // 回收时托管账户已关闭，直接使用事件中的双方
+                EscrowEvent::Reclaimed(event) => {
+                    parties.cache.remove(&escrow);
+                    (event.buyer, event.provider)
+                }
+                _ => match parties.resolve(&escrow).await {
+                    Ok(resolved) => resolved,
+                    Err(err) => {
+                        eprintln!("Failed to resolve parties of {}: {:#}", escrow, err);
+                        continue;
+                    }
+                },
+            };
+
+            let (kind, timestamp, amount) = match &event {
+                EscrowEvent::Created(event) => ("created", event.timestamp, Some(event.amount)),
+                EscrowEvent::Funded(event) => ("funded", event.timestamp, Some(event.amount)),
+                EscrowEvent::Delivered(event) => ("delivered", event.timestamp, None),
+                EscrowEvent::Rejected(event) => ("rejected", event.timestamp, None),
+                EscrowEvent::Released(event) => {
+                    ("released", event.timestamp, Some(event.provider_amount))
+                }
+                EscrowEvent::UnitsDelivered(event) => (
+                    "units_delivered",
+                    event.timestamp,
+                    Some(event.provider_amount),
+                ),
+                EscrowEvent::DownloadConsumed(event) => (
+                    "download_consumed",
+                    event.timestamp,
+                    Some(event.provider_amount),
+                ),
+                EscrowEvent::Refunded(event) => ("refunded", event.timestamp, Some(event.amount)),
+                EscrowEvent::CancelRequested(event) => ("cancel_requested", event.timestamp, None),
+                EscrowEvent::Cancelled(event) => ("cancelled", event.timestamp, Some(event.amount)),
+                EscrowEvent::Reclaimed(event) => ("reclaimed", event.timestamp, None),
+                EscrowEvent::DisputeRaised(event) => ("disputed", event.timestamp, None),
+                EscrowEvent::DisputeEscalated(event) => ("escalated", event.timestamp, None),
+                EscrowEvent::DisputeResolved(event) => ("resolved", event.timestamp, None),
+                EscrowEvent::DisputeRuled(event) => ("ruled", event.timestamp, None),
+                EscrowEvent::DisputeAppealed(event) => {
+                    ("appealed", event.timestamp, Some(event.bond))
+                }
+                EscrowEvent::SaleRecorded(event) => ("sale_recorded", event.timestamp, None),
+            };
+
+            // 没有订阅者时发送失败，忽略即可
+            let _ = sender.send(Arc::new(Notification {
+                kind,
+                escrow: escrow.to_string(),
+                buyer,
+                provider,
+                signature: logs.signature.clone(),
+                timestamp,
+                amount,
+            }));
+        }
+    }
+
+    Ok(())
+}
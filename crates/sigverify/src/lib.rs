@@ -0,0 +1,77 @@
+//! DataNexus Sigverify
+//!
+//! 程序校验链下签名（交付预言机证明、用量收据等）的共用逻辑：
+//! 签名由交易中紧接的 Ed25519 原生程序指令验证，程序读取该指令，
+//! 确认签名公钥和消息就是自己期望的内容。
+
+use solana_program::account_info::AccountInfo;
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::instructions;
+
+/// Ed25519 签名校验原生程序
+pub const ED25519_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("Ed25519SigVerify111111111111111111111111111");
+
+/// Ed25519 签名校验指令中，偏移量指向本条指令数据时的指令索引
+pub const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// 签名校验指令头部长度：签名数、填充，随后 7 个 u16 偏移
+pub const ED25519_HEADER_LEN: usize = 2 + 7 * 2;
+
+/// 签名校验失败的原因，由各程序映射为自己的错误码
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureError {
+    /// 前一条指令不是 Ed25519 签名校验
+    Missing,
+    /// 签名校验指令的公钥或消息与期望不符，或不是单签名的自包含指令
+    Invalid,
+}
+
+/// 读取当前指令的前一条指令，当前指令是交易中第一条时返回 `None`
+pub fn preceding_instruction(
+    instructions_sysvar: &AccountInfo,
+) -> Result<Option<Instruction>, ProgramError> {
+    let current = instructions::load_current_index_checked(instructions_sysvar)?;
+    if current == 0 {
+        return Ok(None);
+    }
+    instructions::load_instruction_at_checked(current as usize - 1, instructions_sysvar).map(Some)
+}
+
+/// 校验 `ix` 是 `signer` 对 `message` 的 Ed25519 签名校验指令
+///
+/// 只接受签名、公钥和消息都位于该校验指令自身数据中的单签名指令
+pub fn verify_signature(
+    ix: &Instruction,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<(), SignatureError> {
+    if ix.program_id != ED25519_PROGRAM_ID {
+        return Err(SignatureError::Missing);
+    }
+
+    let data = &ix.data;
+    if data.len() < ED25519_HEADER_LEN || data[0] != 1 {
+        return Err(SignatureError::Invalid);
+    }
+    let offset = |i: usize| u16::from_le_bytes([data[2 + i * 2], data[3 + i * 2]]);
+    let (public_key_offset, message_offset, message_size) =
+        (offset(2) as usize, offset(4) as usize, offset(5) as usize);
+    if offset(1) != ED25519_CURRENT_INSTRUCTION
+        || offset(3) != ED25519_CURRENT_INSTRUCTION
+        || offset(6) != ED25519_CURRENT_INSTRUCTION
+    {
+        return Err(SignatureError::Invalid);
+    }
+
+    if data.get(public_key_offset..public_key_offset + 32) != Some(signer.as_ref())
+        || message_size != message.len()
+        || data.get(message_offset..message_offset + message_size) != Some(message)
+    {
+        return Err(SignatureError::Invalid);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::Pubkey;
+use datanexus_client::instructions::{delivery_attestation, delivery_attestation_message};
+use datanexus_client::metering::{usage_receipt, usage_receipt_message};
+use datanexus_sigverify::{verify_signature, SignatureError, ED25519_HEADER_LEN};
+
+const SIGNATURE: [u8; 64] = [5; 64];
+
+#[test]
+fn accepts_the_client_attestation_layout() {
+    let oracle = Pubkey::new_unique();
+    let escrow = Pubkey::new_unique();
+    let ix = delivery_attestation(oracle, SIGNATURE, &escrow, &[1; 32]);
+
+    let message = delivery_attestation_message(&escrow, &[1; 32]);
+    assert_eq!(verify_signature(&ix, &oracle, &message), Ok(()));
+}
+
+#[test]
+fn rejects_other_signers_and_messages() {
+    let oracle = Pubkey::new_unique();
+    let escrow = Pubkey::new_unique();
+    let ix = delivery_attestation(oracle, SIGNATURE, &escrow, &[1; 32]);
+    let message = delivery_attestation_message(&escrow, &[1; 32]);
+
+    assert_eq!(
+        verify_signature(&ix, &Pubkey::new_unique(), &message),
+        Err(SignatureError::Invalid)
+    );
+    let other = delivery_attestation_message(&escrow, &[2; 32]);
+    assert_eq!(
+        verify_signature(&ix, &oracle, &other),
+        Err(SignatureError::Invalid)
+    );
+    assert_eq!(
+        verify_signature(&ix, &oracle, &message[..32]),
+        Err(SignatureError::Invalid)
+    );
+}
+
+#[test]
+fn rejects_other_programs_and_malformed_headers() {
+    let oracle = Pubkey::new_unique();
+    let escrow = Pubkey::new_unique();
+    let message = delivery_attestation_message(&escrow, &[1; 32]);
+
+    let mut ix = delivery_attestation(oracle, SIGNATURE, &escrow, &[1; 32]);
+    ix.program_id = Pubkey::new_unique();
+    assert_eq!(
+        verify_signature(&ix, &oracle, &message),
+        Err(SignatureError::Missing)
+    );
+
+    // 多签名指令
+    let mut ix = delivery_attestation(oracle, SIGNATURE, &escrow, &[1; 32]);
+    ix.data[0] = 2;
+    assert_eq!(
+        verify_signature(&ix, &oracle, &message),
+        Err(SignatureError::Invalid)
+    );
+
+    // 公钥指向其他指令的数据
+    let mut ix = delivery_attestation(oracle, SIGNATURE, &escrow, &[1; 32]);
+    ix.data[6..8].copy_from_slice(&0u16.to_le_bytes());
+    assert_eq!(
+        verify_signature(&ix, &oracle, &message),
+        Err(SignatureError::Invalid)
+    );
+
+    let mut ix = delivery_attestation(oracle, SIGNATURE, &escrow, &[1; 32]);
+    ix.data.truncate(ED25519_HEADER_LEN - 1);
+    assert_eq!(
+        verify_signature(&ix, &oracle, &message),
+        Err(SignatureError::Invalid)
+    );
+}
+
+#[test]
+fn usage_receipts_do_not_carry_over_to_a_reopened_meter() {
+    let receipt_signer = Pubkey::new_unique();
+    let meter = Pubkey::new_unique();
+    let ix = usage_receipt(receipt_signer, SIGNATURE, &meter, 1_700_000_000, 10);
+
+    let message = usage_receipt_message(&meter, 1_700_000_000, 10);
+    assert_eq!(verify_signature(&ix, &receipt_signer, &message), Ok(()));
+
+    // 同一地址重新开通后开通时间不同，旧收据不再匹配
+    let reopened = usage_receipt_message(&meter, 1_700_086_401, 10);
+    assert_eq!(
+        verify_signature(&ix, &receipt_signer, &reopened),
+        Err(SignatureError::Invalid)
+    );
+}
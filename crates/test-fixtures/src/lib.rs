@@ -0,0 +1,553 @@
+//! DataNexus Test Fixtures
+//!
+//! 基于 `solana-program-test` 的托管程序测试夹具，供各 crate 的集成测试复用：
+//! 1. `Fixture::start` 加载托管程序、创建测试 USDC（6 位小数）并初始化平台配置
+//! 2. `funded_wallet` 创建持有 SOL 和 USDC 的买家 / 提供商钱包
+//! 3. `create_escrow` / `deliver` / `confirm` / `dispute` / `resolve` 等将托管推进到各个状态
+//!
+//! 指令均通过 `datanexus-client` 构造，与链下客户端保持同一套账户布局。
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::clock::Clock;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::associated_token::{get_associated_token_address, spl_associated_token_account};
+use anchor_spl::token::{self, spl_token};
+use datanexus_client::instructions::{self, CreateEscrowParams};
+use datanexus_client::pda;
+use datanexus_escrow::{Config, Escrow, ProviderStats};
+use solana_account::AccountSharedData;
+use solana_keypair::Keypair;
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+pub use solana_program_test;
+
+/// 测试 USDC 小数位
+pub const USDC_DECIMALS: u8 = 6;
+
+/// 默认平台费分配：国库 80%，奖励池 20%
+pub const TREASURY_BPS: u16 = 8000;
+pub const REWARDS_POOL_BPS: u16 = 2000;
+
+/// 新钱包默认 SOL 余额，足够支付托管相关账户的租金
+const WALLET_LAMPORTS: u64 = 10_000_000_000;
+
+/// 加载 `anchor build` 产出的托管程序的 `ProgramTest`，需要额外程序或账户时在此基础上追加
+///
+/// Anchor 0.32 的 CPI 只能在 SBF 目标上执行，因此不能以原生处理器运行，
+/// 未设置 `SBF_OUT_DIR` 时从工作区的 `target/deploy` 读取 `datanexus_escrow.so`。
+/// 程序经升级加载器部署，`initialize_config` 需要校验其 ProgramData 中的升级权限
+pub fn program_test() -> ProgramTest {
+    if std::env::var_os("SBF_OUT_DIR").is_none() && std::env::var_os("BPF_OUT_DIR").is_none() {
+        std::env::set_var(
+            "SBF_OUT_DIR",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy"),
+        );
+    }
+
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_upgradeable_program_to_genesis("datanexus_escrow", &datanexus_escrow::ID);
+    program_test
+}
+
+/// 托管测试夹具
+pub struct Fixture {
+    pub context: ProgramTestContext,
+    pub admin: Keypair,          // 平台管理员，同时作为托管的平台方
+    pub mint_authority: Keypair, // 测试 USDC 铸币权限
+    pub mint: Pubkey,            // 测试 USDC
+    pub treasury: Pubkey,        // 国库 USDC 账户
+    pub rewards_pool: Pubkey,    // 奖励池 USDC 账户
+}
+
+impl Fixture {
+    /// 启动测试验证器并完成平台初始化
+    pub async fn start() -> Self {
+        Self::start_with(program_test()).await
+    }
+
+    /// 使用自定义的 `ProgramTest` 启动
+    pub async fn start_with(program_test: ProgramTest) -> Self {
+        let context = program_test.start_with_context().await;
+        let mut fixture = Self {
+            context,
+            admin: Keypair::new(),
+            mint_authority: Keypair::new(),
+            mint: Pubkey::default(),
+            treasury: Pubkey::default(),
+            rewards_pool: Pubkey::default(),
+        };
+
+        let admin = fixture.admin.insecure_clone();
+        fixture.set_upgrade_authority(&admin.pubkey()).await;
+        fixture.airdrop(&admin.pubkey(), WALLET_LAMPORTS).await;
+        fixture.mint = fixture.create_mint().await;
+        fixture.treasury = fixture.create_token_account(&Keypair::new().pubkey()).await;
+        fixture.rewards_pool = fixture.create_token_account(&Keypair::new().pubkey()).await;
+
+        fixture
+            .process(
+                &[instructions::initialize_config(
+                    admin.pubkey(),
+                    fixture.treasury,
+                    fixture.rewards_pool,
+                    TREASURY_BPS,
+                    REWARDS_POOL_BPS,
+                )],
+                &[&admin],
+            )
+            .await
+            .expect("initialize_config");
+
+        fixture
+    }
+
+    /// 改写托管程序 ProgramData 中的升级权限
+    ///
+    /// `add_upgradeable_program_to_genesis` 使用默认公钥作为升级权限，
+    /// 这里直接改写 `UpgradeableLoaderState::ProgramData` 头部（4 字节变体 + 8 字节槽位之后的
+    /// `Option<Pubkey>`），让夹具的管理员能够通过 `initialize_config` 的校验
+    pub async fn set_upgrade_authority(&mut self, authority: &Pubkey) {
+        const AUTHORITY_OFFSET: usize = 4 + 8;
+
+        let program_data = pda::find_program_data_address().0;
+        let mut account = self
+            .context
+            .banks_client
+            .get_account(program_data)
+            .await
+            .expect("get_account")
+            .expect("program data not found");
+        account.data[AUTHORITY_OFFSET] = 1;
+        account.data[AUTHORITY_OFFSET + 1..AUTHORITY_OFFSET + 33]
+            .copy_from_slice(authority.as_ref());
+        self.context
+            .set_account(&program_data, &AccountSharedData::from(account));
+    }
+
+    /// 签名并处理交易，测试上下文的付款钱包始终签名
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let blockhash = self.context.get_new_latest_blockhash().await?;
+
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+
+        self.context.banks_client.process_transaction(tx).await
+    }
+
+    /// 从付款钱包转入 SOL
+    pub async fn airdrop(&mut self, to: &Pubkey, lamports: u64) {
+        let payer = self.context.payer.pubkey();
+        self.process(&[system_instruction::transfer(&payer, to, lamports)], &[])
+            .await
+            .expect("airdrop");
+    }
+
+    /// 创建持有 SOL 和 `usdc` 数量测试 USDC 的钱包
+    pub async fn funded_wallet(&mut self, usdc: u64) -> Keypair {
+        let wallet = Keypair::new();
+        self.airdrop(&wallet.pubkey(), WALLET_LAMPORTS).await;
+        let token_account = self.create_token_account(&wallet.pubkey()).await;
+        if usdc > 0 {
+            self.mint_to(&token_account, usdc).await;
+        }
+        wallet
+    }
+
+    /// 为 `owner` 创建测试 USDC 的关联代币账户
+    pub async fn create_token_account(&mut self, owner: &Pubkey) -> Pubkey {
+        let payer = self.context.payer.pubkey();
+        self.process(
+            &[
+                spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                    &payer,
+                    owner,
+                    &self.mint,
+                    &token::ID,
+                ),
+            ],
+            &[],
+        )
+        .await
+        .expect("create_associated_token_account");
+
+        get_associated_token_address(owner, &self.mint)
+    }
+
+    /// 向代币账户铸造测试 USDC
+    pub async fn mint_to(&mut self, token_account: &Pubkey, amount: u64) {
+        let mint_authority = self.mint_authority.insecure_clone();
+        self.process(
+            &[spl_token::instruction::mint_to(
+                &token::ID,
+                &self.mint,
+                token_account,
+                &mint_authority.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap()],
+            &[&mint_authority],
+        )
+        .await
+        .expect("mint_to");
+    }
+
+    /// 创建并充值托管，平台方为 `admin`，返回托管地址
+    pub async fn create_escrow(
+        &mut self,
+        buyer: &Keypair,
+        provider: &Pubkey,
+        amount: u64,
+        request_id: &str,
+    ) -> Result<Pubkey, BanksClientError> {
+        let escrow = self
+            .create_unfunded_escrow(buyer, provider, amount, request_id)
+            .await?;
+        self.fund(&escrow, buyer).await?;
+
+        Ok(escrow)
+    }
+
+    /// 创建托管和托管代币账户但不充值（Created），返回托管地址
+    pub async fn create_unfunded_escrow(
+        &mut self,
+        buyer: &Keypair,
+        provider: &Pubkey,
+        amount: u64,
+        request_id: &str,
+    ) -> Result<Pubkey, BanksClientError> {
+        let escrow = datanexus_client::EscrowClient::escrow_address(&buyer.pubkey(), request_id);
+        let provider_sequence = self.provider_sequence(provider).await;
+
+        self.process(
+            &[
+                instructions::create_escrow_token_account(buyer.pubkey(), escrow, self.mint),
+                instructions::create_escrow(CreateEscrowParams {
+                    buyer: buyer.pubkey(),
+                    provider: *provider,
+                    platform: self.admin.pubkey(),
+                    amount,
+                    mint: self.mint,
+                    request_id: request_id.to_string(),
+                    proposal_id: format!("proposal-{}", request_id),
+                    provider_sequence,
+                    buyer_attestation: None,
+                    provider_attestation: None,
+                    provider_badge: None,
+                    coupon: None,
+                    coupon_code: None,
+                }),
+            ],
+            &[buyer],
+        )
+        .await?;
+
+        Ok(escrow)
+    }
+
+    /// 买家为托管充值 Created -> Funded
+    pub async fn fund(&mut self, escrow: &Pubkey, buyer: &Keypair) -> Result<(), BanksClientError> {
+        self.process(
+            &[instructions::fund_escrow(
+                *escrow,
+                buyer.pubkey(),
+                self.mint,
+                None,
+            )],
+            &[buyer],
+        )
+        .await
+    }
+
+    /// 提供商标记交付 Funded -> Delivered
+    pub async fn deliver(
+        &mut self,
+        escrow: &Pubkey,
+        provider: &Keypair,
+        delivery_hash: [u8; 32],
+    ) -> Result<(), BanksClientError> {
+        self.process(
+            &[instructions::mark_delivered(
+                *escrow,
+                provider.pubkey(),
+                delivery_hash,
+                None,
+            )],
+            &[provider],
+        )
+        .await
+    }
+
+    /// 买家确认并释放 Delivered -> Completed
+    pub async fn confirm(
+        &mut self,
+        escrow: &Pubkey,
+        buyer: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let account = self.fetch_escrow(escrow).await;
+        let config = self.fetch_config().await;
+        self.process(
+            &[instructions::confirm_and_release(
+                *escrow, &account, &config, self.mint, None, None, None,
+            )],
+            &[buyer],
+        )
+        .await
+    }
+
+    /// 买家发起争议 Delivered -> Disputed
+    pub async fn dispute(
+        &mut self,
+        escrow: &Pubkey,
+        buyer: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let account = self.fetch_escrow(escrow).await;
+        let ix = instructions::raise_dispute(*escrow, &account, self.mint, Vec::new());
+        self.process(&[ix], &[buyer]).await
+    }
+
+    /// 平台裁决争议 Disputed -> Refunded / Completed
+    pub async fn resolve(
+        &mut self,
+        escrow: &Pubkey,
+        refund_to_buyer: bool,
+    ) -> Result<(), BanksClientError> {
+        let account = self.fetch_escrow(escrow).await;
+        let config = self.fetch_config().await;
+        let admin = self.admin.insecure_clone();
+        self.process(
+            &[instructions::resolve_dispute(
+                *escrow,
+                &account,
+                &config,
+                self.mint,
+                None,
+                refund_to_buyer,
+            )],
+            &[&admin],
+        )
+        .await
+    }
+
+    /// 平台全额退款 Disputed -> Refunded
+    pub async fn refund(&mut self, escrow: &Pubkey) -> Result<(), BanksClientError> {
+        let account = self.fetch_escrow(escrow).await;
+        let admin = self.admin.insecure_clone();
+        self.process(
+            &[instructions::refund(*escrow, &account, self.mint)],
+            &[&admin],
+        )
+        .await
+    }
+
+    /// 买家申请取消 Funded -> PendingCancel
+    pub async fn cancel(
+        &mut self,
+        escrow: &Pubkey,
+        buyer: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let account = self.fetch_escrow(escrow).await;
+        self.process(&[instructions::cancel(*escrow, &account)], &[buyer])
+            .await
+    }
+
+    /// 提供商反对取消 PendingCancel -> Disputed
+    pub async fn contest_cancel(
+        &mut self,
+        escrow: &Pubkey,
+        provider: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let account = self.fetch_escrow(escrow).await;
+        self.process(
+            &[instructions::contest_cancel(*escrow, &account)],
+            &[provider],
+        )
+        .await
+    }
+
+    /// 异议期满后执行取消 PendingCancel -> Cancelled（先用 `advance_clock` 越过异议期）
+    pub async fn execute_cancel(&mut self, escrow: &Pubkey) -> Result<(), BanksClientError> {
+        let account = self.fetch_escrow(escrow).await;
+        self.process(
+            &[instructions::execute_cancel(*escrow, &account, self.mint)],
+            &[],
+        )
+        .await
+    }
+
+    /// 迁移旧布局的托管账户，测试上下文的付款钱包补足租金
+    pub async fn migrate(&mut self, escrow: &Pubkey) -> Result<(), BanksClientError> {
+        let payer = self.context.payer.pubkey();
+        self.process(&[instructions::migrate_escrow(*escrow, payer)], &[])
+            .await
+    }
+
+    /// 迁移旧布局的全局配置，测试上下文的付款钱包补足租金
+    pub async fn migrate_config(&mut self) -> Result<(), BanksClientError> {
+        let payer = self.context.payer.pubkey();
+        self.process(&[instructions::migrate_config(payer)], &[])
+            .await
+    }
+
+    /// 管理员迁移买家统计，`open_escrows` 为回填的未结托管
+    pub async fn migrate_buyer_stats(
+        &mut self,
+        buyer: &Pubkey,
+        open_escrows: &[Pubkey],
+    ) -> Result<(), BanksClientError> {
+        let payer = self.context.payer.pubkey();
+        let admin = self.admin.insecure_clone();
+        self.process(
+            &[instructions::migrate_buyer_stats(
+                *buyer,
+                admin.pubkey(),
+                payer,
+                open_escrows,
+            )],
+            &[&admin],
+        )
+        .await
+    }
+
+    /// 替换账户数据（保留所有者和余额），用于构造旧布局的账户
+    pub async fn replace_account_data(&mut self, address: &Pubkey, data: Vec<u8>) {
+        let mut account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await
+            .expect("get_account")
+            .expect("account not found");
+        account.data = data;
+        self.context
+            .set_account(address, &AccountSharedData::from(account));
+    }
+
+    /// 把链上时钟向前拨 `seconds` 秒
+    pub async fn advance_clock(&mut self, seconds: i64) {
+        let mut clock: Clock = self
+            .context
+            .banks_client
+            .get_sysvar()
+            .await
+            .expect("clock sysvar");
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    /// 读取并反序列化 Anchor 账户，账户不存在时返回 `None`
+    pub async fn fetch<T: AccountDeserialize>(&mut self, address: &Pubkey) -> Option<T> {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await
+            .expect("get_account")?;
+
+        Some(T::try_deserialize(&mut account.data.as_slice()).expect("deserialize account"))
+    }
+
+    pub async fn fetch_escrow(&mut self, address: &Pubkey) -> Escrow {
+        self.fetch(address).await.expect("escrow account")
+    }
+
+    pub async fn fetch_config(&mut self) -> Config {
+        self.fetch(&pda::find_config_address().0)
+            .await
+            .expect("config account")
+    }
+
+    /// 账户的 SOL 余额（账户不存在时为 0）
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(*address)
+            .await
+            .expect("get_balance")
+    }
+
+    /// `owner` 的测试 USDC 余额（关联代币账户不存在时为 0）
+    pub async fn usdc_balance(&mut self, owner: &Pubkey) -> u64 {
+        self.token_balance(&get_associated_token_address(owner, &self.mint))
+            .await
+    }
+
+    /// 代币账户余额（账户不存在时为 0）
+    pub async fn token_balance(&mut self, token_account: &Pubkey) -> u64 {
+        match self
+            .context
+            .banks_client
+            .get_account(*token_account)
+            .await
+            .expect("get_account")
+        {
+            Some(account) => {
+                spl_token::state::Account::unpack(&account.data)
+                    .expect("token account")
+                    .amount
+            }
+            None => 0,
+        }
+    }
+
+    /// 提供商下一个托管的索引序号（统计账户不存在时为 0）
+    pub async fn provider_sequence(&mut self, provider: &Pubkey) -> u64 {
+        self.fetch::<ProviderStats>(&pda::find_provider_stats_address(provider).0)
+            .await
+            .map_or(0, |stats| stats.total_escrows)
+    }
+
+    /// 创建 6 位小数的测试铸币，铸币权限为 `mint_authority`
+    pub async fn create_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.context.payer.pubkey();
+        let rent = self
+            .context
+            .banks_client
+            .get_rent()
+            .await
+            .expect("get_rent")
+            .minimum_balance(spl_token::state::Mint::LEN);
+
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    rent,
+                    spl_token::state::Mint::LEN as u64,
+                    &token::ID,
+                ),
+                spl_token::instruction::initialize_mint(
+                    &token::ID,
+                    &mint.pubkey(),
+                    &self.mint_authority.pubkey(),
+                    None,
+                    USDC_DECIMALS,
+                )
+                .unwrap(),
+            ],
+            &[&mint],
+        )
+        .await
+        .expect("create_mint");
+
+        mint.pubkey()
+    }
+}
@@ -0,0 +1,134 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountSerialize;
+use bytemuck::Zeroable;
+use datanexus_client::coupons::Discount;
+use datanexus_escrow as escrow;
+use datanexus_escrow::{
+    ChunkChallenge, ChunkManifest, ConfigChange, DeliveryCommitment, Escrow, EscrowRestriction,
+    KeyDelivery, MessageEntry, MessageLog, PenaltyRecipient, PendingConfigChange, ProtocolSettings,
+    StorageProtocol, StorageRef, Template, TemplateTerms,
+};
+
+/// 账户序列化后（含 8 字节判别符）的长度
+fn serialized_len<T: AccountSerialize>(account: &T) -> usize {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    data.len()
+}
+
+#[test]
+fn maximal_escrow_fits_allocation() {
+    let request_id = "r".repeat(escrow::MAX_ESCROW_ID_LEN);
+    let proposal_id = "p".repeat(escrow::MAX_ESCROW_ID_LEN);
+
+    let mut account = Escrow::zeroed();
+    account.set_ids(&request_id, &proposal_id).unwrap();
+
+    assert_eq!(8 + bytemuck::bytes_of(&account).len(), Escrow::LEN);
+    assert_eq!(account.request_id(), request_id);
+    assert_eq!(account.proposal_id(), proposal_id);
+
+    let too_long = "r".repeat(escrow::MAX_ESCROW_ID_LEN + 1);
+    assert!(account.set_ids(&too_long, &proposal_id).is_err());
+}
+
+#[test]
+fn maximal_variable_length_accounts_fill_allocation() {
+    let key = Pubkey::new_unique();
+
+    let key_delivery = KeyDelivery {
+        escrow: key,
+        buyer_key: [1; 32],
+        encrypted_key: vec![2; escrow::MAX_ENCRYPTED_KEY_LEN],
+        delivered_at: Some(i64::MAX),
+        bump: 255,
+    };
+    assert_eq!(serialized_len(&key_delivery), KeyDelivery::LEN);
+
+    let commitment = DeliveryCommitment {
+        escrow: key,
+        commitment: [1; 32],
+        storage: Some(StorageRef {
+            protocol: StorageProtocol::ShadowDrive {
+                storage_account: key,
+            },
+            id: "a".repeat(escrow::MAX_STORAGE_ID_LEN),
+        }),
+        salt: [2; 32],
+        revealed_at: Some(i64::MAX),
+        bump: 255,
+    };
+    assert_eq!(serialized_len(&commitment), DeliveryCommitment::LEN);
+
+    let manifest = ChunkManifest {
+        escrow: key,
+        root: [1; 32],
+        chunk_count: u32::MAX,
+        disputed_chunks: vec![u32::MAX; escrow::MAX_DISPUTED_CHUNKS],
+        bump: 255,
+    };
+    assert_eq!(serialized_len(&manifest), ChunkManifest::LEN);
+
+    let challenge = ChunkChallenge {
+        escrow: key,
+        indices: vec![u32::MAX; escrow::MAX_CHALLENGE_CHUNKS],
+        deadline: i64::MAX,
+        responded_at: Some(i64::MAX),
+        bump: 255,
+    };
+    assert_eq!(serialized_len(&challenge), ChunkChallenge::LEN);
+
+    let log = MessageLog {
+        escrow: key,
+        entries: vec![
+            MessageEntry {
+                author: key,
+                message_hash: [1; 32],
+                timestamp: i64::MAX,
+            };
+            escrow::MAX_MESSAGES
+        ],
+        bump: 255,
+    };
+    assert_eq!(serialized_len(&log), MessageLog::LEN);
+
+    let restriction = EscrowRestriction {
+        escrow: key,
+        flags: u32::MAX,
+        blocked: true,
+        reason: "x".repeat(escrow::MAX_BLOCK_REASON_LEN),
+        blocked_at: Some(i64::MAX),
+        bump: 255,
+    };
+    assert_eq!(serialized_len(&restriction), EscrowRestriction::LEN);
+}
+
+#[test]
+fn largest_enum_variants_fill_allocation() {
+    let key = Pubkey::new_unique();
+
+    let template = Template {
+        owner: key,
+        template_id: u64::MAX,
+        mint: key,
+        terms: TemplateTerms {
+            provider: Some(key),
+            discount: Some(Discount::Rebate(u64::MAX)),
+            delivery_window: i64::MAX,
+            late_penalty: u64::MAX,
+            penalty_recipient: PenaltyRecipient::Platform,
+            milestones: u64::MAX,
+        },
+        bump: 255,
+    };
+    assert_eq!(serialized_len(&template), Template::LEN);
+
+    let pending = PendingConfigChange {
+        change: ConfigChange::Settings(ProtocolSettings::default()),
+        payer: key,
+        queued_at: i64::MAX,
+        execute_after: i64::MAX,
+        bump: 255,
+    };
+    assert_eq!(serialized_len(&pending), PendingConfigChange::LEN);
+}
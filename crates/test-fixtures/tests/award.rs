@@ -0,0 +1,123 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{Discriminator, InstructionData, ToAccountMetas};
+use datanexus_escrow::{accounts, instruction};
+use datanexus_requests::{
+    create_escrow_data, CreateEscrowAccounts, FundEscrowAccounts, CREATE_ESCROW_DISCRIMINATOR,
+    ESCROW_PROGRAM_ID, FUND_ESCROW_DISCRIMINATOR,
+};
+
+/// 需求程序不能依赖托管程序，授标时手工构造的 CPI 必须与托管程序的指令保持一致
+#[test]
+fn award_create_escrow_matches_the_escrow_program() {
+    assert_eq!(ESCROW_PROGRAM_ID, datanexus_escrow::ID);
+    assert_eq!(
+        CREATE_ESCROW_DISCRIMINATOR,
+        instruction::CreateEscrow::DISCRIMINATOR
+    );
+
+    let request_id = "req-award".to_string();
+    let proposal_id = "prop-award".to_string();
+    assert_eq!(
+        create_escrow_data(1_000_000, &request_id, &proposal_id).unwrap(),
+        instruction::CreateEscrow {
+            amount: 1_000_000,
+            request_id: request_id.clone(),
+            proposal_id: proposal_id.clone(),
+            coupon_code: None,
+        }
+        .data()
+    );
+
+    // 可选账户分别按传入和未传入比对，确保占位和可写标志一致
+    for optional in [true, false] {
+        let key = |present: bool| present.then(Pubkey::new_unique);
+        let ours = CreateEscrowAccounts {
+            escrow: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            buyer_stats: Pubkey::new_unique(),
+            provider: Pubkey::new_unique(),
+            provider_stats: Pubkey::new_unique(),
+            provider_escrow_index: Pubkey::new_unique(),
+            protocol_stats: Pubkey::new_unique(),
+            provider_profile: key(optional),
+            request: Pubkey::new_unique(),
+            proposal: Pubkey::new_unique(),
+            restriction: key(optional),
+            platform: Pubkey::new_unique(),
+            system_program: Pubkey::new_unique(),
+            buyer_attestation: key(optional),
+            provider_attestation: key(optional),
+            provider_badge: key(optional),
+            buyer_blacklist: Pubkey::new_unique(),
+            provider_blacklist: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+        };
+        let theirs = accounts::CreateEscrow {
+            escrow: ours.escrow,
+            buyer: ours.buyer,
+            config: ours.config,
+            buyer_stats: ours.buyer_stats,
+            provider: ours.provider,
+            provider_stats: ours.provider_stats,
+            provider_escrow_index: ours.provider_escrow_index,
+            protocol_stats: ours.protocol_stats,
+            provider_profile: ours.provider_profile,
+            request: Some(ours.request),
+            proposal: Some(ours.proposal),
+            restriction: ours.restriction,
+            platform: ours.platform,
+            system_program: ours.system_program,
+            coupon: None,
+            discount: None,
+            coupon_redeemer: None,
+            coupons_program: None,
+            access_mint: None,
+            buyer_attestation: ours.buyer_attestation,
+            provider_attestation: ours.provider_attestation,
+            provider_badge: ours.provider_badge,
+            buyer_blacklist: ours.buyer_blacklist,
+            provider_blacklist: ours.provider_blacklist,
+            mint: ours.mint,
+        };
+
+        assert_eq!(ours.to_account_metas(), theirs.to_account_metas(None));
+    }
+}
+
+#[test]
+fn award_fund_escrow_matches_the_escrow_program() {
+    assert_eq!(
+        FUND_ESCROW_DISCRIMINATOR,
+        instruction::FundEscrow::DISCRIMINATOR
+    );
+    assert_eq!(
+        FUND_ESCROW_DISCRIMINATOR.to_vec(),
+        instruction::FundEscrow {}.data()
+    );
+
+    let ours = FundEscrowAccounts {
+        escrow: Pubkey::new_unique(),
+        buyer: Pubkey::new_unique(),
+        buyer_stats: Pubkey::new_unique(),
+        protocol_stats: Pubkey::new_unique(),
+        quote: Pubkey::new_unique(),
+        buyer_token_account: Pubkey::new_unique(),
+        escrow_token_account: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        token_program: Pubkey::new_unique(),
+    };
+    let theirs = accounts::FundEscrow {
+        escrow: ours.escrow,
+        buyer: ours.buyer,
+        buyer_stats: ours.buyer_stats,
+        protocol_stats: ours.protocol_stats,
+        quote: ours.quote,
+        buyer_token_account: ours.buyer_token_account,
+        escrow_token_account: ours.escrow_token_account,
+        mint: ours.mint,
+        token_program: ours.token_program,
+    };
+
+    assert_eq!(ours.to_account_metas(), theirs.to_account_metas(None));
+}
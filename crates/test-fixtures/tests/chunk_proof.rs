@@ -0,0 +1,117 @@
+use anchor_lang::prelude::Pubkey;
+use datanexus_client::chunks::ChunkTree;
+use datanexus_escrow::{ChunkManifest, ChunkProof, EscrowError};
+use solana_sha256_hasher::hashv;
+
+fn build_tree(chunk_count: u8) -> ChunkTree {
+    let chunks: Vec<Vec<u8>> = (0..chunk_count).map(|i| vec![i; 64]).collect();
+    ChunkTree::from_chunks(&chunks).unwrap()
+}
+
+fn manifest(tree: &ChunkTree) -> ChunkManifest {
+    ChunkManifest {
+        escrow: Pubkey::new_unique(),
+        root: tree.root(),
+        chunk_count: tree.chunk_count(),
+        disputed_chunks: Vec::new(),
+        bump: 255,
+    }
+}
+
+#[test]
+fn every_chunk_proof_verifies() {
+    for chunk_count in 1..=9 {
+        let tree = build_tree(chunk_count);
+        let manifest = manifest(&tree);
+        for index in 0..tree.chunk_count() {
+            let proof = tree.proof(index).unwrap();
+            assert!(manifest.verify(&proof), "{} of {}", index, chunk_count);
+        }
+    }
+}
+
+#[test]
+fn odd_level_pairs_last_node_with_itself() {
+    let tree = build_tree(3);
+    let leaves: Vec<[u8; 32]> = (0..3)
+        .map(|i| hashv(&[&[0u8], &tree.proof(i).unwrap().chunk_hash]).to_bytes())
+        .collect();
+    let left = hashv(&[&[1u8], &leaves[0], &leaves[1]]).to_bytes();
+    let right = hashv(&[&[1u8], &leaves[2], &leaves[2]]).to_bytes();
+    assert_eq!(tree.root(), hashv(&[&[1u8], &left, &right]).to_bytes());
+
+    // 最后一块的兄弟节点是它自己
+    let proof = tree.proof(2).unwrap();
+    assert_eq!(proof.proof, vec![leaves[2], left]);
+    assert!(manifest(&tree).verify(&proof));
+}
+
+#[test]
+fn tampered_proofs_are_rejected() {
+    let tree = build_tree(5);
+    let manifest = manifest(&tree);
+    let valid = tree.proof(3).unwrap();
+
+    let mut chunk_hash = valid.clone();
+    chunk_hash.chunk_hash[0] ^= 1;
+    assert!(!manifest.verify(&chunk_hash));
+
+    let mut sibling = valid.clone();
+    sibling.proof[1][0] ^= 1;
+    assert!(!manifest.verify(&sibling));
+
+    let mut index = valid.clone();
+    index.index = 2;
+    assert!(!manifest.verify(&index));
+
+    let mut short = valid.clone();
+    short.proof.pop();
+    assert!(!manifest.verify(&short));
+
+    let mut long = valid.clone();
+    long.proof.push([0; 32]);
+    assert!(!manifest.verify(&long));
+
+    // 序号超出块数：即使证明能推出根也拒绝
+    let mut out_of_range = tree.proof(4).unwrap();
+    out_of_range.index = 5;
+    assert!(!manifest.verify(&out_of_range));
+}
+
+#[test]
+fn dispute_records_verified_chunks() {
+    let tree = build_tree(7);
+    let mut manifest = manifest(&tree);
+
+    manifest
+        .dispute(&[tree.proof(6).unwrap(), tree.proof(1).unwrap()])
+        .unwrap();
+    assert_eq!(manifest.disputed_chunks, vec![6, 1]);
+
+    // 同一块不能重复争议
+    assert_eq!(
+        manifest.dispute(&[tree.proof(6).unwrap()]).unwrap_err(),
+        EscrowError::InvalidChunkProof.into()
+    );
+
+    let mut tampered = tree.proof(3).unwrap();
+    tampered.chunk_hash = [0; 32];
+    assert_eq!(
+        manifest.dispute(&[tampered]).unwrap_err(),
+        EscrowError::InvalidChunkProof.into()
+    );
+    assert_eq!(manifest.disputed_chunks, vec![6, 1]);
+
+    // 证明来自另一棵树
+    let other = build_tree(6).proof(0).unwrap();
+    assert_eq!(
+        manifest
+            .dispute(&[ChunkProof {
+                index: 0,
+                chunk_hash: other.chunk_hash,
+                proof: other.proof,
+            }])
+            .unwrap_err(),
+        EscrowError::InvalidChunkProof.into()
+    );
+}
@@ -0,0 +1,124 @@
+use std::mem::size_of;
+use std::process::Command;
+
+use datanexus_escrow as escrow;
+
+/// SBF 单个栈帧上限
+const STACK_FRAME: usize = 4096;
+
+/// 指令上下文结构体的大小上限，为 `try_accounts` 的局部变量和后续新增账户留出余量
+const MAX_CONTEXT_SIZE: usize = STACK_FRAME / 4;
+
+/// 各指令上下文结构体的 `size_of`（宿主与 SBF 均为 64 位，指针和 `AccountInfo` 大小一致）
+///
+/// 只能发现未装箱的账户，不测量实际栈占用；实际栈帧由 `sbf_stack_frames_fit` 检查
+macro_rules! context_sizes {
+    ($($context:ident),* $(,)?) => {
+        [$((stringify!($context), size_of::<escrow::$context<'static>>())),*]
+    };
+}
+
+#[test]
+fn context_structs_stay_small() {
+    let sizes = context_sizes!(
+        InitializeConfig,
+        UpdateConfig,
+        UpdateSettings,
+        ExecuteConfigChange,
+        CancelConfigChange,
+        InitInsurance,
+        ClaimInsurance,
+        InitFeeLedger,
+        SettleFees,
+        SetAdmin,
+        InitReceiptTree,
+        IssueBadge,
+        RevokeBadge,
+        AddToBlacklist,
+        RemoveFromBlacklist,
+        CreateEscrow,
+        FundEscrow,
+        FundEscrowWithSwap,
+        SaveTemplate,
+        CloseTemplate,
+        CreateEscrowFromTemplate,
+        RecreateEscrow,
+        CreateEscrowFromBridge,
+        RecordValuation,
+        DenominateInUsd,
+        SetSettlementSwap,
+        RequireDeliveryAttestation,
+        RequireKeyDelivery,
+        DeliverKey,
+        CommitDelivery,
+        RevealDelivery,
+        EnableYield,
+        WithdrawYield,
+        MarkDelivered,
+        SetDeliveryDeadline,
+        QuotePrice,
+        SetUnitPricing,
+        DeliverUnits,
+        InitSalesHistory,
+        SetDownloadCap,
+        ConsumeDownload,
+        FundBonus,
+        ReclaimBonus,
+        RejectDelivery,
+        ExpireRejection,
+        SetAccessWindow,
+        RevokeExpiredAccess,
+        TransferAccess,
+        RequireDatasetProof,
+        SubmitDatasetProof,
+        ChallengeChunks,
+        RespondChallenge,
+        ExpireChallenge,
+        ConfirmAndRelease,
+        Refund,
+        BlockRelease,
+        Cancel,
+        ContestCancel,
+        ExecuteCancel,
+        ReclaimStale,
+        RaiseDispute,
+        EscalateDispute,
+        RuleDispute,
+        Appeal,
+        ResolveDispute,
+        ResolveDisputeSplit,
+        ExtendMetadata,
+        LogMessage,
+        MigrateEscrow,
+        MigrateConfig,
+        MigrateBuyerStats,
+    );
+    for (context, size) in sizes {
+        assert!(
+            size <= MAX_CONTEXT_SIZE,
+            "{} is {} bytes (max {}); box its accounts",
+            context,
+            size,
+            MAX_CONTEXT_SIZE
+        );
+    }
+}
+
+/// 运行 `scripts/check-sbf-stack.sh`：逐个 `cargo build-sbf` 构建程序，
+/// 编译器报告 `Stack offset of N exceeded max offset of 4096` 的函数即实际栈帧超限
+#[test]
+#[ignore = "requires `cargo build-sbf` (Solana platform tools)"]
+fn sbf_stack_frames_fit() {
+    let script = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../scripts/check-sbf-stack.sh"
+    );
+    let status = Command::new("bash")
+        .arg(script)
+        .status()
+        .expect("run check-sbf-stack.sh");
+    assert!(
+        status.success(),
+        "SBF stack check failed, see the output above"
+    );
+}
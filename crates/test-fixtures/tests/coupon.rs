@@ -0,0 +1,78 @@
+use anchor_lang::solana_program::instruction::Instruction;
+use datanexus_client::coupons::{self, Coupon, Discount};
+use datanexus_client::instructions::{self, CreateEscrowParams};
+use datanexus_client::EscrowClient;
+use datanexus_test_fixtures::{program_test, Fixture};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const AMOUNT: u64 = 1_000_000;
+const CODE: &str = "WELCOME-10";
+
+fn create_with_coupon(
+    fixture: &Fixture,
+    buyer: &Keypair,
+    provider: &Keypair,
+    request_id: &str,
+    code: &str,
+) -> Vec<Instruction> {
+    let escrow = EscrowClient::escrow_address(&buyer.pubkey(), request_id);
+    vec![
+        instructions::create_escrow_token_account(buyer.pubkey(), escrow, fixture.mint),
+        instructions::create_escrow(CreateEscrowParams {
+            buyer: buyer.pubkey(),
+            provider: provider.pubkey(),
+            platform: fixture.admin.pubkey(),
+            amount: AMOUNT,
+            mint: fixture.mint,
+            request_id: request_id.to_string(),
+            proposal_id: format!("proposal-{}", request_id),
+            provider_sequence: 0,
+            buyer_attestation: None,
+            provider_attestation: None,
+            provider_badge: None,
+            coupon: Some(
+                coupons::find_coupon_address(&fixture.admin.pubkey(), &coupons::code_hash(CODE)).0,
+            ),
+            coupon_code: Some(code.to_string()),
+        }),
+    ]
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_coupons.so)"]
+async fn redeeming_requires_the_coupon_code() {
+    let mut program_test = program_test();
+    program_test.add_program("datanexus_coupons", datanexus_coupons::ID, None);
+    let mut fixture = Fixture::start_with(program_test).await;
+    let buyer = fixture.funded_wallet(AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    // 不限买家的优惠券，地址可以从链上扫描得到
+    let admin = fixture.admin.insecure_clone();
+    fixture
+        .process(
+            &[coupons::issue_coupon(
+                admin.pubkey(),
+                coupons::code_hash(CODE),
+                Discount::FeeBps(5000),
+                None,
+                i64::MAX,
+            )],
+            &[&admin],
+        )
+        .await
+        .unwrap();
+    let coupon_address = coupons::find_coupon_address(&admin.pubkey(), &coupons::code_hash(CODE)).0;
+
+    let ixs = create_with_coupon(&fixture, &buyer, &provider, "req-coupon", "GUESS");
+    assert!(fixture.process(&ixs, &[&buyer]).await.is_err());
+    let coupon: Coupon = fixture.fetch(&coupon_address).await.unwrap();
+    assert_eq!(coupon.redeemed_escrow, None);
+
+    let ixs = create_with_coupon(&fixture, &buyer, &provider, "req-coupon", CODE);
+    fixture.process(&ixs, &[&buyer]).await.unwrap();
+    let escrow = EscrowClient::escrow_address(&buyer.pubkey(), "req-coupon");
+    let coupon: Coupon = fixture.fetch(&coupon_address).await.unwrap();
+    assert_eq!(coupon.redeemed_escrow, Some(escrow));
+}
@@ -0,0 +1,135 @@
+use datanexus_escrow::{FeeSplit, PlatformFeeShares, BPS_DENOMINATOR, PLATFORM_FEE_BPS};
+
+/// 整除边界、零头和接近 `u64::MAX` 的金额
+const EDGE_AMOUNTS: &[u64] = &[
+    0,
+    1,
+    19,
+    20,
+    21,
+    99,
+    100,
+    101,
+    9_999,
+    10_000,
+    10_001,
+    999_999,
+    1_000_000,
+    1_000_001,
+    u64::MAX / 20,
+    u64::MAX / 20 + 1,
+    u64::MAX - 1,
+    u64::MAX,
+];
+
+#[test]
+fn split_adds_up_to_amount() {
+    for &amount in EDGE_AMOUNTS {
+        let split = FeeSplit::new(amount).unwrap();
+        assert_eq!(
+            split.platform_fee as u128 + split.provider_amount as u128,
+            amount as u128,
+            "amount {}",
+            amount
+        );
+    }
+}
+
+#[test]
+fn fee_rounds_down_and_dust_goes_to_provider() {
+    for &amount in EDGE_AMOUNTS {
+        let split = FeeSplit::new(amount).unwrap();
+        let exact = amount as u128 * PLATFORM_FEE_BPS as u128;
+        let denominator = BPS_DENOMINATOR as u128;
+        assert_eq!(
+            split.platform_fee as u128,
+            exact / denominator,
+            "amount {}",
+            amount
+        );
+        assert!(
+            split.platform_fee as u128 * denominator <= exact,
+            "amount {}",
+            amount
+        );
+    }
+}
+
+#[test]
+fn known_splits() {
+    let cases = [
+        (0, 0, 0),
+        (1, 0, 1),
+        (19, 0, 19),
+        (20, 1, 19),
+        (21, 1, 20),
+        (1_000_000, 50_000, 950_000),
+        (1_000_019, 50_000, 950_019),
+        (u64::MAX, u64::MAX / 20, u64::MAX - u64::MAX / 20),
+    ];
+    for (amount, platform_fee, provider_amount) in cases {
+        assert_eq!(
+            FeeSplit::new(amount).unwrap(),
+            FeeSplit {
+                platform_fee,
+                provider_amount,
+            },
+            "amount {}",
+            amount
+        );
+    }
+}
+
+#[test]
+fn platform_fee_shares_add_up_for_any_fee() {
+    // 保险池和国库比例取边界值，平台费取到 `u64::MAX` 也不溢出
+    for &platform_fee in EDGE_AMOUNTS {
+        for insurance_bps in [0, 1, 2_500, 9_999, 10_000] {
+            for treasury_bps in [0, 8_000, 10_000] {
+                let shares =
+                    PlatformFeeShares::new(platform_fee, insurance_bps, treasury_bps).unwrap();
+                assert_eq!(
+                    shares.insurance as u128
+                        + shares.treasury as u128
+                        + shares.rewards_pool as u128,
+                    platform_fee as u128,
+                    "fee {} insurance {} treasury {}",
+                    platform_fee,
+                    insurance_bps,
+                    treasury_bps
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn known_platform_fee_shares() {
+    assert_eq!(
+        PlatformFeeShares::new(50_000, 1_000, 8_000).unwrap(),
+        PlatformFeeShares {
+            insurance: 5_000,
+            treasury: 36_000,
+            rewards_pool: 9_000,
+        }
+    );
+    // 零头归奖励池
+    assert_eq!(
+        PlatformFeeShares::new(7, 1_000, 8_000).unwrap(),
+        PlatformFeeShares {
+            insurance: 0,
+            treasury: 5,
+            rewards_pool: 2,
+        }
+    );
+    // 9 位小数下约 1.8e15 以上的平台费
+    let large = 2_000_000_000_000_000;
+    assert_eq!(
+        PlatformFeeShares::new(large, 1_000, 8_000).unwrap(),
+        PlatformFeeShares {
+            insurance: 200_000_000_000_000,
+            treasury: 1_440_000_000_000_000,
+            rewards_pool: 360_000_000_000_000,
+        }
+    );
+}
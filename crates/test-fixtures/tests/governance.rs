@@ -0,0 +1,102 @@
+use anchor_lang::prelude::Pubkey;
+use datanexus_governance::{GovernanceParams, Proposal, ProposalAction, ProposalStatus};
+
+const QUORUM: u64 = 1_000;
+const VOTING_ENDS_AT: i64 = 1_000;
+const TIMELOCK_DELAY: i64 = 500;
+
+fn params() -> GovernanceParams {
+    GovernanceParams {
+        quorum: QUORUM,
+        proposal_threshold: 100,
+        voting_period: VOTING_ENDS_AT,
+        timelock_delay: TIMELOCK_DELAY,
+    }
+}
+
+fn proposal() -> Proposal {
+    Proposal {
+        id: 0,
+        proposer: Pubkey::new_unique(),
+        action: ProposalAction::SetEscrowAdmin(Pubkey::new_unique()),
+        for_votes: 0,
+        against_votes: 0,
+        created_at: 0,
+        voting_ends_at: VOTING_ENDS_AT,
+        eta: 0,
+        status: ProposalStatus::Voting,
+        bump: 0,
+    }
+}
+
+/// 按给定的赞成票和反对票计票后的提案
+fn tallied(for_votes: u64, against_votes: u64) -> Proposal {
+    let mut proposal = proposal();
+    proposal.record_vote(true, for_votes);
+    proposal.record_vote(false, against_votes);
+    proposal.tally(&params(), VOTING_ENDS_AT);
+    proposal
+}
+
+#[test]
+fn proposals_need_quorum_and_a_majority() {
+    assert_eq!(tallied(QUORUM, 0).status, ProposalStatus::Queued);
+    assert_eq!(tallied(QUORUM, QUORUM - 1).status, ProposalStatus::Queued);
+
+    // 未达法定票数、平票或反对票占优均否决
+    assert_eq!(tallied(QUORUM - 1, 0).status, ProposalStatus::Defeated);
+    assert_eq!(tallied(QUORUM, QUORUM).status, ProposalStatus::Defeated);
+    assert_eq!(tallied(QUORUM, QUORUM + 1).status, ProposalStatus::Defeated);
+    assert_eq!(tallied(0, 0).status, ProposalStatus::Defeated);
+}
+
+#[test]
+fn queued_proposals_wait_for_the_timelock() {
+    let proposal = tallied(QUORUM, 0);
+    let eta = VOTING_ENDS_AT + TIMELOCK_DELAY;
+    assert_eq!(proposal.eta, eta);
+
+    assert!(proposal.require_executable(eta - 1).is_err());
+    assert!(proposal.require_executable(eta).is_ok());
+
+    // 被否决或已执行的提案不能执行
+    assert!(tallied(0, QUORUM).require_executable(eta).is_err());
+    let mut executed = tallied(QUORUM, 0);
+    executed.status = ProposalStatus::Executed;
+    assert!(executed.require_executable(eta).is_err());
+}
+
+#[test]
+fn governance_updates_are_validated_when_proposed() {
+    assert!(ProposalAction::UpdateGovernance(params())
+        .validate()
+        .is_ok());
+
+    // 法定票数为零、投票期为零或时间锁为负的参数会让治理失效，不能发起
+    let invalid = [
+        GovernanceParams {
+            quorum: 0,
+            ..params()
+        },
+        GovernanceParams {
+            voting_period: 0,
+            ..params()
+        },
+        GovernanceParams {
+            timelock_delay: -1,
+            ..params()
+        },
+    ];
+    for params in invalid {
+        assert!(ProposalAction::UpdateGovernance(params).validate().is_err());
+    }
+
+    // 立即执行（时间锁为 0）是允许的
+    let immediate = GovernanceParams {
+        timelock_delay: 0,
+        ..params()
+    };
+    assert!(ProposalAction::UpdateGovernance(immediate)
+        .validate()
+        .is_ok());
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::Pubkey;
+use datanexus_escrow::VoteLock;
+use datanexus_jury::{Case, CaseStatus, Juror};
+
+const VOTING_ENDS_AT: i64 = 1_000;
+const MIN_STAKE: u64 = 100;
+
+fn open_case() -> Case {
+    Case {
+        escrow: Pubkey::new_unique(),
+        buyer: Pubkey::new_unique(),
+        provider: Pubkey::new_unique(),
+        opened_by: Pubkey::new_unique(),
+        opened_at: 0,
+        voting_ends_at: VOTING_ENDS_AT,
+        buyer_votes: 0,
+        provider_votes: 0,
+        voters: 0,
+        status: CaseStatus::Voting,
+        refund_to_buyer: false,
+        bump: 0,
+    }
+}
+
+fn juror(staked: u64) -> Juror {
+    Juror {
+        owner: Pubkey::new_unique(),
+        staked,
+        lock: VoteLock::default(),
+        bump: 0,
+    }
+}
+
+#[test]
+fn votes_are_weighted_by_stake() {
+    let mut case = open_case();
+    case.record_vote(true, 300);
+    case.record_vote(false, 200);
+    case.record_vote(false, 200);
+
+    assert_eq!(case.buyer_votes, 300);
+    assert_eq!(case.provider_votes, 400);
+    assert_eq!(case.voters, 3);
+    // 人数占优不影响结果，按权重裁决
+    assert!(!case.verdict());
+
+    case.record_vote(true, 101);
+    assert!(case.verdict());
+}
+
+#[test]
+fn tie_and_no_votes_refund_the_buyer() {
+    let case = open_case();
+    assert!(case.verdict());
+
+    let mut case = open_case();
+    case.record_vote(true, 500);
+    case.record_vote(false, 500);
+    assert!(case.verdict());
+}
+
+#[test]
+fn parties_and_under_staked_jurors_cannot_vote() {
+    let case = open_case();
+    assert!(case
+        .require_eligible(&juror(MIN_STAKE), MIN_STAKE, VOTING_ENDS_AT - 1)
+        .is_ok());
+
+    // 买卖双方不能为自己的案件投票
+    let mut buyer = juror(MIN_STAKE);
+    buyer.owner = case.buyer;
+    assert!(case.require_eligible(&buyer, MIN_STAKE, 0).is_err());
+    let mut provider = juror(MIN_STAKE);
+    provider.owner = case.provider;
+    assert!(case.require_eligible(&provider, MIN_STAKE, 0).is_err());
+
+    // 质押低于门槛或为零（门槛为 0 时）不能投票
+    assert!(case
+        .require_eligible(&juror(MIN_STAKE - 1), MIN_STAKE, 0)
+        .is_err());
+    assert!(case.require_eligible(&juror(0), 0, 0).is_err());
+}
+
+#[test]
+fn votes_close_at_the_deadline_and_after_the_verdict() {
+    let mut case = open_case();
+    assert!(case
+        .require_eligible(&juror(MIN_STAKE), MIN_STAKE, VOTING_ENDS_AT)
+        .is_err());
+
+    case.status = CaseStatus::Executed;
+    assert!(case
+        .require_eligible(&juror(MIN_STAKE), MIN_STAKE, 0)
+        .is_err());
+}
+
+#[test]
+fn voting_locks_stake_until_the_latest_case_closes() {
+    // 锁定期取所投案件中最晚的投票截止时间
+    let mut juror = juror(MIN_STAKE);
+    assert!(juror.lock.is_unlocked(0));
+
+    juror.lock.extend(VOTING_ENDS_AT * 2);
+    juror.lock.extend(VOTING_ENDS_AT);
+    assert_eq!(juror.lock.until, VOTING_ENDS_AT * 2);
+    assert!(!juror.lock.is_unlocked(VOTING_ENDS_AT * 2 - 1));
+    assert!(juror.lock.is_unlocked(VOTING_ENDS_AT * 2));
+}
@@ -0,0 +1,219 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use datanexus_client::instructions::{self, CreateEscrowParams};
+use datanexus_client::pda;
+use datanexus_escrow::{
+    CancelRequest, DisputeRecord, EscrowStatus, ProviderStats, CANCEL_GRACE_PERIOD,
+};
+use datanexus_test_fixtures::Fixture;
+use solana_signer::Signer;
+
+const AMOUNT: u64 = 1_000_000;
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn escrow_lifecycle() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(3 * AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    // Funded -> Delivered -> Completed
+    let escrow = fixture
+        .create_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-release")
+        .await
+        .unwrap();
+    fixture.deliver(&escrow, &provider, [1; 32]).await.unwrap();
+    fixture.confirm(&escrow, &buyer).await.unwrap();
+    assert_eq!(
+        fixture.fetch_escrow(&escrow).await.status(),
+        EscrowStatus::Completed
+    );
+    assert_eq!(fixture.usdc_balance(&provider.pubkey()).await, 950_000);
+    let treasury = fixture.treasury;
+    let rewards_pool = fixture.rewards_pool;
+    assert_eq!(fixture.token_balance(&treasury).await, 40_000);
+    assert_eq!(fixture.token_balance(&rewards_pool).await, 10_000);
+
+    // Funded -> Delivered -> Disputed -> Refunded
+    let escrow = fixture
+        .create_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-dispute")
+        .await
+        .unwrap();
+    fixture.deliver(&escrow, &provider, [2; 32]).await.unwrap();
+    fixture.dispute(&escrow, &buyer).await.unwrap();
+    fixture.resolve(&escrow, true).await.unwrap();
+    assert_eq!(
+        fixture.fetch_escrow(&escrow).await.status(),
+        EscrowStatus::Refunded
+    );
+
+    // Funded -> PendingCancel -> Cancelled
+    let escrow = fixture
+        .create_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-cancel")
+        .await
+        .unwrap();
+    fixture.cancel(&escrow, &buyer).await.unwrap();
+    assert_eq!(
+        fixture.fetch_escrow(&escrow).await.status(),
+        EscrowStatus::PendingCancel
+    );
+    assert!(fixture.execute_cancel(&escrow).await.is_err());
+    fixture.advance_clock(CANCEL_GRACE_PERIOD + 1).await;
+    fixture.execute_cancel(&escrow).await.unwrap();
+    assert_eq!(
+        fixture.fetch_escrow(&escrow).await.status(),
+        EscrowStatus::Cancelled
+    );
+    assert_eq!(fixture.usdc_balance(&buyer.pubkey()).await, 2 * AMOUNT);
+    let cancel_request = pda::find_cancel_request_address(&escrow).0;
+    assert!(fixture
+        .fetch::<CancelRequest>(&cancel_request)
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn dispute_refund_pays_only_recorded_bond() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    let escrow = fixture
+        .create_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-surplus")
+        .await
+        .unwrap();
+    fixture.deliver(&escrow, &provider, [3; 32]).await.unwrap();
+    fixture.dispute(&escrow, &buyer).await.unwrap();
+
+    let record = fixture
+        .fetch::<DisputeRecord>(&pda::find_dispute_record_address(&escrow).0)
+        .await
+        .expect("dispute record");
+    let bond = fixture.fetch_config().await.settings.dispute_bond;
+    assert_eq!(record.bond, bond);
+    assert_eq!(record.appeal_bond, 0);
+
+    // 直接转入托管代币账户的余额不是保证金，不随裁决支付
+    let vault = get_associated_token_address(&escrow, &fixture.mint);
+    fixture.mint_to(&vault, 5_000).await;
+
+    fixture.resolve(&escrow, true).await.unwrap();
+    assert_eq!(fixture.usdc_balance(&buyer.pubkey()).await, AMOUNT);
+    assert_eq!(fixture.token_balance(&vault).await, 5_000);
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn refund_only_pays_the_buyer_token_account() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    let escrow = fixture
+        .create_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-refund-to")
+        .await
+        .unwrap();
+    fixture.deliver(&escrow, &provider, [4; 32]).await.unwrap();
+    fixture.dispute(&escrow, &buyer).await.unwrap();
+
+    // 平台把退款指向其他人的代币账户会被拒绝
+    let account = fixture.fetch_escrow(&escrow).await;
+    let buyer_token_account = get_associated_token_address(&buyer.pubkey(), &fixture.mint);
+    let mut ix = instructions::refund(escrow, &account, fixture.mint);
+    for meta in &mut ix.accounts {
+        if meta.pubkey == buyer_token_account {
+            meta.pubkey = get_associated_token_address(&provider.pubkey(), &fixture.mint);
+        }
+    }
+    let admin = fixture.admin.insecure_clone();
+    assert!(fixture.process(&[ix], &[&admin]).await.is_err());
+
+    fixture.refund(&escrow).await.unwrap();
+    assert_eq!(fixture.usdc_balance(&buyer.pubkey()).await, AMOUNT);
+    assert_eq!(fixture.usdc_balance(&provider.pubkey()).await, 0);
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn contested_cancel_records_dispute_and_closes_request() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    let escrow = fixture
+        .create_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-contest")
+        .await
+        .unwrap();
+    fixture.cancel(&escrow, &buyer).await.unwrap();
+    let provider_lamports = fixture.lamports(&provider.pubkey()).await;
+    fixture.contest_cancel(&escrow, &provider).await.unwrap();
+    assert_eq!(
+        fixture.fetch_escrow(&escrow).await.status(),
+        EscrowStatus::Disputed
+    );
+
+    // 取消申请关闭，争议记录建立，平台可按常规流程裁决
+    let cancel_request = pda::find_cancel_request_address(&escrow).0;
+    assert!(fixture
+        .fetch::<CancelRequest>(&cancel_request)
+        .await
+        .is_none());
+    let record = fixture
+        .fetch::<DisputeRecord>(&pda::find_dispute_record_address(&escrow).0)
+        .await
+        .expect("dispute record");
+    assert_eq!(record.buyer, buyer.pubkey());
+    assert_eq!(record.payer, provider.pubkey());
+    assert_eq!(record.bond, 0);
+
+    // 买家发起的取消不计入提供商的争议次数
+    let stats = fixture
+        .fetch::<ProviderStats>(&pda::find_provider_stats_address(&provider.pubkey()).0)
+        .await
+        .expect("provider stats");
+    assert_eq!(stats.disputes, 0);
+
+    // 裁决后争议记录关闭，租金退还给支付它的提供商
+    fixture.resolve(&escrow, true).await.unwrap();
+    assert_eq!(
+        fixture.fetch_escrow(&escrow).await.status(),
+        EscrowStatus::Refunded
+    );
+    assert_eq!(fixture.usdc_balance(&buyer.pubkey()).await, AMOUNT);
+    assert_eq!(
+        fixture.lamports(&provider.pubkey()).await,
+        provider_lamports
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn create_requires_the_config_admin_as_platform() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    // 买家自选的平台地址会拿走平台费并获得裁决权，必须拒绝
+    let ix = instructions::create_escrow(CreateEscrowParams {
+        buyer: buyer.pubkey(),
+        provider: provider.pubkey(),
+        platform: Pubkey::new_unique(),
+        amount: AMOUNT,
+        mint: fixture.mint,
+        request_id: "req-platform".to_string(),
+        proposal_id: "proposal-req-platform".to_string(),
+        provider_sequence: 0,
+        buyer_attestation: None,
+        provider_attestation: None,
+        provider_badge: None,
+        coupon: None,
+        coupon_code: None,
+    });
+    assert!(fixture.process(&[ix], &[&buyer]).await.is_err());
+
+    fixture
+        .create_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-platform")
+        .await
+        .unwrap();
+}
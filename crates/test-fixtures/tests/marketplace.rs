@@ -0,0 +1,166 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token;
+use datanexus_client::{instructions, pda};
+use datanexus_escrow::EscrowStatus;
+use datanexus_marketplace::{accounts, instruction, purchase_request_id, Listing};
+use datanexus_test_fixtures::{program_test, Fixture};
+use solana_signer::Signer;
+
+/// 单个 PDA 种子的最大长度
+const MAX_SEED_LEN: usize = 32;
+
+const PRICE: u64 = 1_000_000;
+
+#[test]
+fn purchase_request_ids_fit_in_a_seed() {
+    let listing = Pubkey::new_unique();
+    for sold in [0, 1, u32::MAX] {
+        assert!(purchase_request_id(&listing, sold).len() <= MAX_SEED_LEN);
+    }
+    assert_ne!(
+        purchase_request_id(&listing, 0),
+        purchase_request_id(&listing, 1)
+    );
+    assert_ne!(
+        purchase_request_id(&listing, 0),
+        purchase_request_id(&Pubkey::new_unique(), 0)
+    );
+}
+
+fn create_listing(provider: Pubkey, mint: Pubkey, stock: u32) -> (Pubkey, Instruction) {
+    let listing_id = 1u64;
+    let listing = Pubkey::find_program_address(
+        &[b"listing", provider.as_ref(), &listing_id.to_le_bytes()],
+        &datanexus_marketplace::ID,
+    )
+    .0;
+
+    let ix = Instruction {
+        program_id: datanexus_marketplace::ID,
+        accounts: accounts::CreateListing {
+            listing,
+            provider,
+            mint,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::CreateListing {
+            listing_id,
+            price: PRICE,
+            metadata_hash: [7; 32],
+            stock,
+        }
+        .data(),
+    };
+    (listing, ix)
+}
+
+fn purchase(
+    fixture: &Fixture,
+    listing: Pubkey,
+    escrow: Pubkey,
+    buyer: Pubkey,
+    provider: Pubkey,
+    provider_sequence: u64,
+) -> Instruction {
+    Instruction {
+        program_id: datanexus_marketplace::ID,
+        accounts: accounts::Purchase {
+            listing,
+            escrow,
+            buyer,
+            escrow_config: pda::find_config_address().0,
+            buyer_stats: pda::find_buyer_stats_address(&buyer).0,
+            provider,
+            provider_stats: pda::find_provider_stats_address(&provider).0,
+            provider_escrow_index: pda::find_provider_escrow_index_address(
+                &provider,
+                provider_sequence,
+            )
+            .0,
+            protocol_stats: pda::find_protocol_stats_address().0,
+            escrow_quote: pda::find_quote_address(&escrow).0,
+            provider_profile: None,
+            platform: fixture.admin.pubkey(),
+            buyer_token_account: get_associated_token_address(&buyer, &fixture.mint),
+            escrow_token_account: get_associated_token_address(&escrow, &fixture.mint),
+            mint: fixture.mint,
+            buyer_attestation: None,
+            provider_attestation: None,
+            provider_badge: None,
+            buyer_blacklist: pda::find_blacklist_address(&buyer).0,
+            provider_blacklist: pda::find_blacklist_address(&provider).0,
+            escrow_program: datanexus_escrow::ID,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Purchase {}.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_marketplace.so)"]
+async fn purchase_creates_and_funds_an_escrow() {
+    let mut program_test = program_test();
+    program_test.add_program("datanexus_marketplace", datanexus_marketplace::ID, None);
+    let mut fixture = Fixture::start_with(program_test).await;
+    let buyer = fixture.funded_wallet(2 * PRICE).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    let (listing, ix) = create_listing(provider.pubkey(), fixture.mint, 2);
+    fixture.process(&[ix], &[&provider]).await.unwrap();
+
+    // 连续购买两次，每次按销售序号生成新的托管
+    for sold in 0..2 {
+        let request_id = purchase_request_id(&listing, sold);
+        let escrow = pda::find_escrow_address(&buyer.pubkey(), &request_id).0;
+        let provider_sequence = fixture.provider_sequence(&provider.pubkey()).await;
+        let ix = purchase(
+            &fixture,
+            listing,
+            escrow,
+            buyer.pubkey(),
+            provider.pubkey(),
+            provider_sequence,
+        );
+        fixture
+            .process(
+                &[
+                    instructions::create_escrow_token_account(buyer.pubkey(), escrow, fixture.mint),
+                    ix,
+                ],
+                &[&buyer],
+            )
+            .await
+            .unwrap();
+
+        let account = fixture.fetch_escrow(&escrow).await;
+        assert_eq!(account.status(), EscrowStatus::Funded);
+        assert_eq!(account.request_id(), request_id);
+        assert_eq!(account.amount, PRICE);
+        assert_eq!(fixture.usdc_balance(&escrow).await, PRICE);
+    }
+
+    let state: Listing = fixture.fetch(&listing).await.unwrap();
+    assert_eq!(state.sold, 2);
+    assert_eq!(state.stock, 0);
+    assert_eq!(fixture.usdc_balance(&buyer.pubkey()).await, 0);
+
+    // 库存售罄后拒绝购买
+    let escrow = pda::find_escrow_address(&buyer.pubkey(), &purchase_request_id(&listing, 2)).0;
+    let provider_sequence = fixture.provider_sequence(&provider.pubkey()).await;
+    let ix = purchase(
+        &fixture,
+        listing,
+        escrow,
+        buyer.pubkey(),
+        provider.pubkey(),
+        provider_sequence,
+    );
+    assert!(fixture.process(&[ix], &[&buyer]).await.is_err());
+}
@@ -0,0 +1,402 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorSerialize, Discriminator};
+use bytemuck::Zeroable;
+use datanexus_client::pda;
+use datanexus_escrow::{
+    BuyerStats, Config, Escrow, EscrowStatus, LegacyEscrow, LegacyLayout, ProtocolSettings,
+    ESCROW_VERSION,
+};
+use datanexus_test_fixtures::Fixture;
+use solana_signer::Signer;
+
+const AMOUNT: u64 = 1_000_000;
+
+const LAYOUTS: [LegacyLayout; 5] = [
+    LegacyLayout::V0,
+    LegacyLayout::V0DeliveryHash,
+    LegacyLayout::V0AccessMint,
+    LegacyLayout::V1,
+    LegacyLayout::V2,
+];
+
+/// 旧布局中的托管字段
+struct Legacy {
+    buyer: Pubkey,
+    provider: Pubkey,
+    platform: Pubkey,
+    request_id: String,
+    proposal_id: String,
+    status: EscrowStatus,
+    bump: u8,
+    metadata: Vec<u8>,
+}
+
+impl Legacy {
+    fn new(buyer: Pubkey, provider: Pubkey, request_id: &str, status: EscrowStatus) -> Self {
+        Self {
+            buyer,
+            provider,
+            platform: Pubkey::new_unique(),
+            request_id: request_id.to_string(),
+            proposal_id: format!("proposal-{}", request_id),
+            status,
+            bump: pda::find_escrow_address(&buyer, request_id).1,
+            metadata: vec![9; 12],
+        }
+    }
+
+    /// 按 `layout` 逐字段 Borsh 编码，并补零到该布局分配的账户大小
+    fn serialize(&self, layout: LegacyLayout) -> Vec<u8> {
+        let mut data = Escrow::DISCRIMINATOR.to_vec();
+        self.buyer.serialize(&mut data).unwrap();
+        self.provider.serialize(&mut data).unwrap();
+        self.platform.serialize(&mut data).unwrap();
+        AMOUNT.serialize(&mut data).unwrap();
+        self.request_id.serialize(&mut data).unwrap();
+        self.proposal_id.serialize(&mut data).unwrap();
+        self.status.serialize(&mut data).unwrap();
+        1_700_000_000i64.serialize(&mut data).unwrap();
+        Some(1_700_000_100i64).serialize(&mut data).unwrap();
+        Some(1_700_000_200i64).serialize(&mut data).unwrap();
+        None::<i64>.serialize(&mut data).unwrap();
+        None::<i64>.serialize(&mut data).unwrap();
+        None::<i64>.serialize(&mut data).unwrap();
+        if layout >= LegacyLayout::V0DeliveryHash {
+            [7u8; 32].serialize(&mut data).unwrap();
+        }
+        if layout >= LegacyLayout::V0AccessMint {
+            Some(self.provider).serialize(&mut data).unwrap();
+        }
+        self.bump.serialize(&mut data).unwrap();
+        if layout >= LegacyLayout::V1 {
+            let version: u8 = if layout == LegacyLayout::V2 { 2 } else { 1 };
+            version.serialize(&mut data).unwrap();
+        }
+
+        let len = match layout {
+            LegacyLayout::V0 => LegacyLayout::V0_LEN,
+            LegacyLayout::V0DeliveryHash => LegacyLayout::V0_DELIVERY_HASH_LEN,
+            LegacyLayout::V0AccessMint => LegacyLayout::V0_ACCESS_MINT_LEN,
+            LegacyLayout::V1 => LegacyLayout::V1_LEN,
+            LegacyLayout::V2 => {
+                self.metadata.serialize(&mut data).unwrap();
+                LegacyLayout::V2_LEN + self.metadata.len()
+            }
+        };
+        assert!(
+            data.len() <= len,
+            "{:?} encodes past its allocation",
+            layout
+        );
+        data.resize(len, 0);
+        data
+    }
+}
+
+#[test]
+fn legacy_layouts_are_detected_by_account_size() {
+    let legacy = Legacy::new(
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        "req-layout",
+        EscrowStatus::Funded,
+    );
+    for layout in LAYOUTS {
+        let data = legacy.serialize(layout);
+        assert_eq!(LegacyLayout::from_len(data.len()), Some(layout));
+    }
+    assert_eq!(LegacyLayout::from_len(LegacyLayout::V0_LEN - 1), None);
+    assert_eq!(LegacyLayout::from_len(LegacyLayout::V1_LEN + 1), None);
+}
+
+#[test]
+fn every_legacy_layout_migrates_field_by_field() {
+    let buyer = Pubkey::new_unique();
+    let provider = Pubkey::new_unique();
+    let legacy = Legacy::new(buyer, provider, "req-migrate", EscrowStatus::Delivered);
+    let (address, bump) = pda::find_escrow_address(&buyer, "req-migrate");
+
+    for layout in LAYOUTS {
+        let data = legacy.serialize(layout);
+        let escrow = LegacyEscrow::read(layout, &mut &data[8..])
+            .unwrap()
+            .migrate()
+            .migrate()
+            .unwrap();
+
+        assert_eq!(escrow.buyer, buyer, "{:?}", layout);
+        assert_eq!(escrow.provider, provider, "{:?}", layout);
+        assert_eq!(escrow.platform, legacy.platform, "{:?}", layout);
+        assert_eq!(escrow.amount, AMOUNT, "{:?}", layout);
+        assert_eq!(escrow.status(), EscrowStatus::Delivered, "{:?}", layout);
+        assert_eq!(escrow.version, ESCROW_VERSION, "{:?}", layout);
+        assert_eq!(escrow.request_id(), "req-migrate", "{:?}", layout);
+        assert_eq!(escrow.proposal_id(), "proposal-req-migrate", "{:?}", layout);
+        assert_eq!(escrow.created_at, 1_700_000_000, "{:?}", layout);
+        assert_eq!(escrow.funded_at, 1_700_000_100, "{:?}", layout);
+        assert_eq!(escrow.delivered_at, 1_700_000_200, "{:?}", layout);
+        assert_eq!(escrow.completed_at, 0, "{:?}", layout);
+
+        // bump 必须仍能推导出托管地址，否则迁移后的账户无法通过种子校验
+        assert_eq!(escrow.bump, bump, "{:?}", layout);
+        assert_eq!(
+            Pubkey::create_program_address(
+                &[b"escrow", buyer.as_ref(), b"req-migrate", &[escrow.bump]],
+                &datanexus_escrow::ID,
+            ),
+            Ok(address),
+            "{:?}",
+            layout
+        );
+
+        let delivery_hash = if layout >= LegacyLayout::V0DeliveryHash {
+            [7; 32]
+        } else {
+            [0; 32]
+        };
+        assert_eq!(escrow.delivery_hash, delivery_hash, "{:?}", layout);
+        let access_mint = (layout >= LegacyLayout::V0AccessMint).then_some(provider);
+        assert_eq!(escrow.access_mint(), access_mint, "{:?}", layout);
+        let metadata_len = if layout == LegacyLayout::V2 { 12 } else { 0 };
+        assert_eq!(escrow.metadata_len, metadata_len, "{:?}", layout);
+    }
+}
+
+#[test]
+fn version_4_keeps_its_fields_and_moves_metadata_after_the_mint() {
+    let buyer = Pubkey::new_unique();
+    let mut v4 = Escrow::zeroed();
+    v4.buyer = buyer;
+    v4.amount = AMOUNT;
+    v4.set_status(EscrowStatus::Created);
+    v4.version = 4;
+    v4.bump = pda::find_escrow_address(&buyer, "req-v4").1;
+    v4.set_ids("req-v4", "proposal-req-v4").unwrap();
+    v4.metadata_len = 3;
+
+    // 版本 4 的定长部分没有末尾的 `mint`，扩展元数据紧跟其后
+    let mut data = Escrow::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&bytemuck::bytes_of(&v4)[..Escrow::V4_LEN - 8]);
+    data.extend_from_slice(&[1, 2, 3]);
+
+    let (escrow, metadata) = Escrow::read_v4(&data).unwrap();
+    assert_eq!(escrow.buyer, buyer);
+    assert_eq!(escrow.amount, AMOUNT);
+    assert_eq!(escrow.status(), EscrowStatus::Created);
+    assert_eq!(escrow.version, ESCROW_VERSION);
+    assert_eq!(escrow.bump, v4.bump);
+    assert_eq!(escrow.request_id(), "req-v4");
+    assert_eq!(escrow.metadata_len, 3);
+    assert_eq!(escrow.mint(), None);
+    assert_eq!(metadata, [1, 2, 3]);
+
+    // 元数据长度超出账户数据时拒绝
+    assert!(Escrow::read_v4(&data[..data.len() - 1]).is_err());
+}
+
+/// 买家限流上线前 `ProtocolSettings` 末尾没有的字段：
+/// `max_open_escrows`、`max_daily_escrows`、`max_price_deviation_bps`、`fee_ledger`
+const SETTINGS_APPENDED_LEN: usize = 8 + 8 + 2 + 1;
+
+fn serialize<T: AccountSerialize>(account: &T) -> Vec<u8> {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn config_migration_zero_fills_appended_settings() {
+    let config = Config {
+        admin: Pubkey::new_unique(),
+        treasury: Pubkey::new_unique(),
+        rewards_pool: Pubkey::new_unique(),
+        treasury_bps: 7_000,
+        rewards_pool_bps: 3_000,
+        receipt_tree: Pubkey::new_unique(),
+        settings: ProtocolSettings {
+            dispute_bond: 5_000,
+            appeal_window: 86_400,
+            required_badge: 2,
+            max_open_escrows: 3,
+            max_daily_escrows: 10,
+            max_price_deviation_bps: 50,
+            fee_ledger: true,
+            ..ProtocolSettings::default()
+        },
+        bump: 254,
+    };
+    let current = serialize(&config);
+    assert_eq!(current.len(), Config::LEN);
+
+    // 旧布局：末尾追加的字段不存在，`bump` 紧跟在 `required_badge` 之后
+    let settings_end = Config::LEN - 1 - SETTINGS_APPENDED_LEN;
+    let mut legacy = current[..settings_end].to_vec();
+    legacy.push(config.bump);
+
+    let data = Config::migrate(&legacy).unwrap();
+    assert_eq!(data.len(), Config::LEN);
+    let migrated = Config::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(migrated.admin, config.admin);
+    assert_eq!(migrated.treasury_bps, 7_000);
+    assert_eq!(migrated.settings.dispute_bond, 5_000);
+    assert_eq!(migrated.settings.appeal_window, 86_400);
+    assert_eq!(migrated.settings.required_badge, 2);
+    assert_eq!(migrated.bump, 254);
+    // 新增参数取默认值：不限制、不启用
+    assert_eq!(migrated.settings.max_open_escrows, 0);
+    assert_eq!(migrated.settings.max_daily_escrows, 0);
+    assert_eq!(migrated.settings.max_price_deviation_bps, 0);
+    assert!(!migrated.settings.fee_ledger);
+
+    // 当前布局不能再迁移，其他账户的数据不能当作配置迁移
+    assert!(Config::migrate(&current).is_err());
+    let mut foreign = legacy.clone();
+    foreign[..8].copy_from_slice(BuyerStats::DISCRIMINATOR);
+    assert!(Config::migrate(&foreign).is_err());
+}
+
+#[test]
+fn buyer_stats_migration_backfills_open_escrows() {
+    let stats = BuyerStats {
+        buyer: Pubkey::new_unique(),
+        total_escrows: 9,
+        total_volume: 9 * AMOUNT,
+        completed: 5,
+        disputes_raised: 1,
+        cancels: 1,
+        bump: 253,
+        open_escrows: 0,
+        window_start: 0,
+        window_creations: 0,
+    };
+    let current = serialize(&stats);
+    assert_eq!(current.len(), BuyerStats::LEN);
+    let legacy = current[..BuyerStats::V0_LEN].to_vec();
+
+    let data = BuyerStats::migrate(&legacy, 2).unwrap();
+    assert_eq!(data.len(), BuyerStats::LEN);
+    let migrated = BuyerStats::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(migrated.buyer, stats.buyer);
+    assert_eq!(migrated.total_escrows, 9);
+    assert_eq!(migrated.completed, 5);
+    assert_eq!(migrated.cancels, 1);
+    assert_eq!(migrated.bump, 253);
+    assert_eq!(migrated.open_escrows, 2);
+    assert_eq!((migrated.window_start, migrated.window_creations), (0, 0));
+
+    // 已迁移的账户按传入的托管重新统计
+    let recounted = BuyerStats::migrate(&data, 1).unwrap();
+    assert_eq!(
+        BuyerStats::try_deserialize(&mut &recounted[..])
+            .unwrap()
+            .open_escrows,
+        1
+    );
+
+    // 其他大小的账户不是买家统计
+    assert!(BuyerStats::migrate(&current[..BuyerStats::V0_LEN - 1], 0).is_err());
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn baseline_escrow_migrates_and_releases() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    // 先走正常流程建好统计账户和资金，再把托管改写为初始布局
+    let escrow = fixture
+        .create_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-baseline")
+        .await
+        .unwrap();
+    fixture.deliver(&escrow, &provider, [1; 32]).await.unwrap();
+
+    let mut legacy = Legacy::new(
+        buyer.pubkey(),
+        provider.pubkey(),
+        "req-baseline",
+        EscrowStatus::Delivered,
+    );
+    legacy.platform = fixture.admin.pubkey();
+    legacy.proposal_id = "proposal-req-baseline".to_string();
+    fixture
+        .replace_account_data(&escrow, legacy.serialize(LegacyLayout::V0))
+        .await;
+
+    fixture.migrate(&escrow).await.unwrap();
+    let migrated = fixture.fetch_escrow(&escrow).await;
+    assert_eq!(migrated.version, ESCROW_VERSION);
+    assert_eq!(migrated.bump, legacy.bump);
+    assert_eq!(migrated.status(), EscrowStatus::Delivered);
+
+    fixture.confirm(&escrow, &buyer).await.unwrap();
+    assert_eq!(
+        fixture.fetch_escrow(&escrow).await.status(),
+        EscrowStatus::Completed
+    );
+    assert_eq!(fixture.usdc_balance(&provider.pubkey()).await, 950_000);
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn legacy_config_and_buyer_stats_migrate_before_settlement() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(2 * AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    let settled = fixture
+        .create_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-settled")
+        .await
+        .unwrap();
+    let open = fixture
+        .create_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-open")
+        .await
+        .unwrap();
+    fixture.deliver(&settled, &provider, [1; 32]).await.unwrap();
+    fixture.confirm(&settled, &buyer).await.unwrap();
+
+    // 把配置和买家统计改写为限流上线前的布局
+    let config_address = pda::find_config_address().0;
+    let config = serialize(&fixture.fetch_config().await);
+    let settings_end = Config::LEN - 1 - SETTINGS_APPENDED_LEN;
+    let mut legacy_config = config[..settings_end].to_vec();
+    legacy_config.push(config[Config::LEN - 1]);
+    fixture
+        .replace_account_data(&config_address, legacy_config)
+        .await;
+    let stats_address = pda::find_buyer_stats_address(&buyer.pubkey()).0;
+    let stats: BuyerStats = fixture.fetch(&stats_address).await.unwrap();
+    fixture
+        .replace_account_data(
+            &stats_address,
+            serialize(&stats)[..BuyerStats::V0_LEN].to_vec(),
+        )
+        .await;
+
+    fixture.migrate_config().await.unwrap();
+    let migrated = serialize(&fixture.fetch_config().await);
+    assert_eq!(migrated[..settings_end], config[..settings_end]);
+    assert_eq!(migrated[Config::LEN - 1], config[Config::LEN - 1]);
+    assert!(fixture.migrate_config().await.is_err());
+
+    // 已结束的托管和重复传入的托管都不计入
+    assert!(fixture
+        .migrate_buyer_stats(&buyer.pubkey(), &[open, settled])
+        .await
+        .is_err());
+    assert!(fixture
+        .migrate_buyer_stats(&buyer.pubkey(), &[open, open])
+        .await
+        .is_err());
+    fixture
+        .migrate_buyer_stats(&buyer.pubkey(), &[open])
+        .await
+        .unwrap();
+    let stats: BuyerStats = fixture.fetch(&stats_address).await.unwrap();
+    assert_eq!(stats.open_escrows, 1);
+
+    fixture.deliver(&open, &provider, [2; 32]).await.unwrap();
+    fixture.confirm(&open, &buyer).await.unwrap();
+    let stats: BuyerStats = fixture.fetch(&stats_address).await.unwrap();
+    assert_eq!(stats.open_escrows, 0);
+}
@@ -0,0 +1,134 @@
+use datanexus_client::instructions;
+use datanexus_escrow::EscrowStatus;
+use datanexus_test_fixtures::Fixture;
+use solana_signer::Signer;
+
+const AMOUNT: u64 = 1_000_000;
+const PRICE: u64 = 800_000;
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn funding_transfers_the_quoted_price() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    let escrow = fixture
+        .create_unfunded_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-quote")
+        .await
+        .unwrap();
+    let account = fixture.fetch_escrow(&escrow).await;
+    fixture
+        .process(
+            &[instructions::quote_price(escrow, &account, PRICE, i64::MAX)],
+            &[&buyer, &provider],
+        )
+        .await
+        .unwrap();
+
+    // 报价不改写托管金额，充值时按报价转入
+    assert_eq!(fixture.fetch_escrow(&escrow).await.amount, AMOUNT);
+    fixture.fund(&escrow, &buyer).await.unwrap();
+    let funded = fixture.fetch_escrow(&escrow).await;
+    assert_eq!(funded.status(), EscrowStatus::Funded);
+    assert_eq!(funded.amount, PRICE);
+    assert_eq!(fixture.usdc_balance(&buyer.pubkey()).await, AMOUNT - PRICE);
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn quotes_exclude_unit_pricing() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(2 * AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    // 先按件计价，再报价
+    let escrow = fixture
+        .create_unfunded_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-units")
+        .await
+        .unwrap();
+    let account = fixture.fetch_escrow(&escrow).await;
+    fixture
+        .process(
+            &[instructions::set_unit_pricing(
+                escrow, &account, 1_000, 1_000,
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    assert!(fixture
+        .process(
+            &[instructions::quote_price(escrow, &account, PRICE, i64::MAX)],
+            &[&buyer, &provider],
+        )
+        .await
+        .is_err());
+
+    // 先报价，再按件计价
+    let escrow = fixture
+        .create_unfunded_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-quoted")
+        .await
+        .unwrap();
+    let account = fixture.fetch_escrow(&escrow).await;
+    fixture
+        .process(
+            &[instructions::quote_price(escrow, &account, PRICE, i64::MAX)],
+            &[&buyer, &provider],
+        )
+        .await
+        .unwrap();
+    assert!(fixture
+        .process(
+            &[instructions::set_unit_pricing(
+                escrow, &account, 1_000, 1_000
+            )],
+            &[&buyer],
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn quotes_cannot_exceed_the_escrow_amount() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(2 * AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    let escrow = fixture
+        .create_unfunded_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-above")
+        .await
+        .unwrap();
+    let account = fixture.fetch_escrow(&escrow).await;
+
+    // 徽章门槛和提案价格只在创建时按托管金额校验，更高的报价会绕过它们
+    assert!(fixture
+        .process(
+            &[instructions::quote_price(
+                escrow,
+                &account,
+                AMOUNT + 1,
+                i64::MAX
+            )],
+            &[&buyer, &provider],
+        )
+        .await
+        .is_err());
+
+    fixture
+        .process(
+            &[instructions::quote_price(
+                escrow,
+                &account,
+                AMOUNT,
+                i64::MAX,
+            )],
+            &[&buyer, &provider],
+        )
+        .await
+        .unwrap();
+    fixture.fund(&escrow, &buyer).await.unwrap();
+    assert_eq!(fixture.fetch_escrow(&escrow).await.amount, AMOUNT);
+    assert_eq!(fixture.usdc_balance(&buyer.pubkey()).await, AMOUNT);
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use bytemuck::Zeroable;
+use datanexus_client::{instructions, pda};
+use datanexus_escrow::{Config, Escrow, RATE_PROVIDER_DISCRIMINATOR, REPUTATION_PROGRAM_ID};
+use datanexus_reputation::instruction::RateProvider;
+
+fn delivered_escrow() -> Escrow {
+    let mut escrow = Escrow::zeroed();
+    escrow.buyer = Pubkey::new_unique();
+    escrow.provider = Pubkey::new_unique();
+    escrow.amount = 1_000_000;
+    escrow
+}
+
+fn config() -> Config {
+    Config {
+        admin: Pubkey::new_unique(),
+        treasury: Pubkey::new_unique(),
+        rewards_pool: Pubkey::new_unique(),
+        treasury_bps: 8000,
+        rewards_pool_bps: 2000,
+        receipt_tree: Pubkey::default(),
+        settings: Default::default(),
+        bump: 255,
+    }
+}
+
+#[test]
+fn confirm_rates_through_the_reputation_program() {
+    assert_eq!(REPUTATION_PROGRAM_ID, datanexus_reputation::ID);
+    assert_eq!(RATE_PROVIDER_DISCRIMINATOR, RateProvider::DISCRIMINATOR);
+
+    let escrow = delivered_escrow();
+    let escrow_address = Pubkey::new_unique();
+    let ix = instructions::confirm_and_release(
+        escrow_address,
+        &escrow,
+        &config(),
+        Pubkey::new_unique(),
+        None,
+        Some(4),
+        None,
+    );
+
+    // 评分账户与 `rate_provider` 使用同一 PDA，每个托管只能评分一次
+    let rating = Pubkey::find_program_address(
+        &[b"rating", escrow_address.as_ref()],
+        &datanexus_reputation::ID,
+    )
+    .0;
+    assert_eq!(pda::find_rating_address(&escrow_address).0, rating);
+    let reputation = pda::find_provider_reputation_address(&escrow.provider).0;
+    for account in [rating, reputation, REPUTATION_PROGRAM_ID] {
+        assert!(ix.accounts.iter().any(|meta| meta.pubkey == account));
+    }
+}
+
+#[test]
+fn confirm_without_score_skips_reputation_accounts() {
+    let escrow = delivered_escrow();
+    let escrow_address = Pubkey::new_unique();
+    let ix = instructions::confirm_and_release(
+        escrow_address,
+        &escrow,
+        &config(),
+        Pubkey::new_unique(),
+        None,
+        None,
+        None,
+    );
+
+    let rating = pda::find_rating_address(&escrow_address).0;
+    assert!(ix.accounts.iter().all(|meta| meta.pubkey != rating));
+}
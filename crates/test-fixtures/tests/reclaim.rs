@@ -0,0 +1,109 @@
+use anchor_lang::AccountSerialize;
+use anchor_spl::associated_token::get_associated_token_address;
+use datanexus_client::{instructions, pda};
+use datanexus_test_fixtures::Fixture;
+use solana_signer::Signer;
+
+const AMOUNT: u64 = 1_000_000;
+const TTL: i64 = 60;
+const DUST: u64 = 5;
+
+/// 直接改写配置中的未注资存活时间，跳过配置变更的公示期
+async fn set_stale_escrow_ttl(fixture: &mut Fixture, ttl: i64) {
+    let config_address = pda::find_config_address().0;
+    let mut config = fixture.fetch_config().await;
+    config.settings.stale_escrow_ttl = ttl;
+
+    let len = fixture
+        .context
+        .banks_client
+        .get_account(config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+        .len();
+    let mut data = Vec::with_capacity(len);
+    config.try_serialize(&mut data).unwrap();
+    data.resize(len, 0);
+    fixture.replace_account_data(&config_address, data).await;
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn reclaim_sweeps_dust_back_to_the_buyer() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(0).await;
+    let provider = fixture.funded_wallet(0).await;
+    set_stale_escrow_ttl(&mut fixture, TTL).await;
+
+    let escrow = fixture
+        .create_unfunded_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-stale")
+        .await
+        .unwrap();
+    let account = fixture.fetch_escrow(&escrow).await;
+    assert_eq!(account.mint(), Some(fixture.mint));
+
+    // 误转入托管代币账户的余额会让关闭失败，须先退给买家
+    let vault = get_associated_token_address(&escrow, &fixture.mint);
+    fixture.mint_to(&vault, DUST).await;
+    fixture.advance_clock(TTL + 1).await;
+
+    let mint = fixture.mint;
+    assert!(fixture
+        .process(
+            &[instructions::reclaim_stale(escrow, &account, mint, false)],
+            &[],
+        )
+        .await
+        .is_err());
+    fixture
+        .process(
+            &[instructions::reclaim_stale(escrow, &account, mint, true)],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(fixture.usdc_balance(&buyer.pubkey()).await, DUST);
+    assert_eq!(fixture.lamports(&escrow).await, 0);
+    assert_eq!(fixture.lamports(&vault).await, 0);
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn reclaim_rejects_a_mint_other_than_the_escrow_mint() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(0).await;
+    let provider = fixture.funded_wallet(0).await;
+    set_stale_escrow_ttl(&mut fixture, TTL).await;
+
+    let escrow = fixture
+        .create_unfunded_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-stale-mint")
+        .await
+        .unwrap();
+    let account = fixture.fetch_escrow(&escrow).await;
+    fixture.advance_clock(TTL + 1).await;
+
+    // 另一种铸币的空托管代币账户不能替代真正的托管代币账户被关闭
+    let other_mint = fixture.create_mint().await;
+    assert!(fixture
+        .process(
+            &[instructions::reclaim_stale(
+                escrow, &account, other_mint, false
+            )],
+            &[],
+        )
+        .await
+        .is_err());
+
+    let mint = fixture.mint;
+    fixture
+        .process(
+            &[instructions::reclaim_stale(escrow, &account, mint, false)],
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(fixture.lamports(&escrow).await, 0);
+}
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::Pubkey;
+use bytemuck::Zeroable;
+use datanexus_escrow::{Escrow, EscrowStatus};
+use datanexus_staking::{require_fraud_ruling, Bond};
+
+const SLASH_BPS: u16 = 2_500;
+
+fn bond(amount: u64, unbonding_amount: u64) -> Bond {
+    Bond {
+        provider: Pubkey::new_unique(),
+        amount,
+        unbonding_amount,
+        unbonding_at: 0,
+        slashed_total: 0,
+        bump: 0,
+    }
+}
+
+#[test]
+fn slash_takes_from_bonded_before_unbonding() {
+    // 罚没额按保证金总量（含解绑中部分）计算，已质押部分足够时解绑中部分不动
+    let mut bonded = bond(800, 200);
+    assert_eq!(bonded.slash(SLASH_BPS).unwrap(), 250);
+    assert_eq!(bonded.amount, 550);
+    assert_eq!(bonded.unbonding_amount, 200);
+    assert_eq!(bonded.slashed_total, 250);
+
+    // 已质押部分不足时再扣解绑中部分，申请解绑不能逃避罚没
+    let mut unbonding = bond(100, 900);
+    assert_eq!(unbonding.slash(SLASH_BPS).unwrap(), 250);
+    assert_eq!(unbonding.amount, 0);
+    assert_eq!(unbonding.unbonding_amount, 750);
+    assert_eq!(unbonding.slashed_total, 250);
+
+    // 全额罚没后再罚没无可扣除
+    let mut full = bond(300, 700);
+    assert_eq!(full.slash(10_000).unwrap(), 1_000);
+    assert_eq!((full.amount, full.unbonding_amount), (0, 0));
+    assert!(full.slash(10_000).is_err());
+}
+
+#[test]
+fn slash_amounts_that_round_to_zero_are_rejected() {
+    let mut dust = bond(3, 0);
+    assert!(dust.slash(SLASH_BPS).is_err());
+    assert_eq!(dust.amount, 3);
+    assert_eq!(dust.slashed_total, 0);
+}
+
+#[test]
+fn only_refunds_after_a_dispute_count_as_fraud_rulings() {
+    let mut escrow = Escrow::zeroed();
+    escrow.set_status(EscrowStatus::Refunded);
+    escrow.refunded_at = 2;
+    // 未经争议的退款（取消、超时）不能罚没
+    assert!(require_fraud_ruling(&escrow).is_err());
+
+    escrow.disputed_at = 1;
+    assert!(require_fraud_ruling(&escrow).is_ok());
+
+    // 争议以释放给提供商结案，或仍在审理中，都不能罚没
+    for status in [
+        EscrowStatus::Completed,
+        EscrowStatus::Disputed,
+        EscrowStatus::Ruled,
+    ] {
+        escrow.set_status(status);
+        assert!(require_fraud_ruling(&escrow).is_err());
+    }
+}
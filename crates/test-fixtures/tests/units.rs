@@ -0,0 +1,120 @@
+use datanexus_client::{instructions, pda};
+use datanexus_escrow::{EscrowDownloads, EscrowStatus, EscrowUnits, ProviderStats};
+use datanexus_test_fixtures::Fixture;
+use solana_signer::Signer;
+
+const AMOUNT: u64 = 1_000_000;
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn final_unit_delivery_records_stats_and_released_amount() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+
+    let escrow = fixture
+        .create_unfunded_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-units")
+        .await
+        .unwrap();
+    let account = fixture.fetch_escrow(&escrow).await;
+    fixture
+        .process(
+            &[instructions::set_unit_pricing(escrow, &account, 250_000, 4)],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    fixture.fund(&escrow, &buyer).await.unwrap();
+
+    let config = fixture.fetch_config().await;
+    let mint = fixture.mint;
+    for units in [1, 3] {
+        let account = fixture.fetch_escrow(&escrow).await;
+        fixture
+            .process(
+                &[instructions::deliver_units(
+                    escrow, &account, &config, mint, units,
+                )],
+                &[&buyer, &provider],
+            )
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        fixture.fetch_escrow(&escrow).await.status(),
+        EscrowStatus::Completed
+    );
+
+    // 最后一批交付计入提供商的交付统计，成交额为各批实际释放的货款之和
+    let stats = fixture
+        .fetch::<ProviderStats>(&pda::find_provider_stats_address(&provider.pubkey()).0)
+        .await
+        .unwrap();
+    assert_eq!(stats.delivered, 1);
+    assert_eq!(stats.completed_sales, 1);
+    let units = fixture
+        .fetch::<EscrowUnits>(&pda::find_units_address(&escrow).0)
+        .await
+        .unwrap();
+    assert_eq!(units.released, AMOUNT);
+    assert_eq!(fixture.usdc_balance(&provider.pubkey()).await, 950_000);
+}
+
+#[tokio::test]
+#[ignore = "requires `anchor build` (target/deploy/datanexus_escrow.so)"]
+async fn final_download_records_the_released_amount() {
+    let mut fixture = Fixture::start().await;
+    let buyer = fixture.funded_wallet(AMOUNT).await;
+    let provider = fixture.funded_wallet(0).await;
+    let gateway = fixture.funded_wallet(0).await;
+
+    let escrow = fixture
+        .create_unfunded_escrow(&buyer, &provider.pubkey(), AMOUNT, "req-downloads")
+        .await
+        .unwrap();
+    let account = fixture.fetch_escrow(&escrow).await;
+    fixture
+        .process(
+            &[instructions::set_download_cap(
+                escrow,
+                &account,
+                3,
+                gateway.pubkey(),
+            )],
+            &[&buyer, &provider],
+        )
+        .await
+        .unwrap();
+    fixture.fund(&escrow, &buyer).await.unwrap();
+
+    let config = fixture.fetch_config().await;
+    let mint = fixture.mint;
+    for _ in 0..3 {
+        let account = fixture.fetch_escrow(&escrow).await;
+        fixture
+            .process(
+                &[instructions::consume_download(
+                    escrow,
+                    &account,
+                    &config,
+                    mint,
+                    gateway.pubkey(),
+                )],
+                &[&gateway],
+            )
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        fixture.fetch_escrow(&escrow).await.status(),
+        EscrowStatus::Completed
+    );
+
+    // 成交额为各次实际释放的货款之和，而不是设置上限时的托管金额
+    let downloads = fixture
+        .fetch::<EscrowDownloads>(&pda::find_downloads_address(&escrow).0)
+        .await
+        .unwrap();
+    assert_eq!(downloads.released, AMOUNT);
+    assert_eq!(downloads.downloads_remaining, 0);
+}
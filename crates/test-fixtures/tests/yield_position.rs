@@ -0,0 +1,18 @@
+use bytemuck::Zeroable;
+use datanexus_escrow::{Escrow, EscrowError};
+
+#[test]
+fn open_yield_position_blocks_payouts() {
+    let mut escrow = Escrow::zeroed();
+    assert!(escrow.require_no_yield_position().is_ok());
+
+    // 本金仍在借贷储备中，须先 `withdraw_yield`
+    escrow.yield_open = 1;
+    assert_eq!(
+        escrow.require_no_yield_position().unwrap_err(),
+        EscrowError::YieldPositionOpen.into()
+    );
+
+    escrow.yield_open = 0;
+    assert!(escrow.require_no_yield_position().is_ok());
+}
@@ -11,7 +11,7 @@ import {
   TOKEN_PROGRAM_ID,
   createAssociatedTokenAccountInstruction 
 } from '@solana/spl-token'
-import idl from '../target/idl/datanexus_escrow.json'
+import idl from './idl/datanexus_escrow.json'
 
 // 配置常量
 export const ANCHOR_CONFIG = {
@@ -20,7 +20,7 @@ export const ANCHOR_CONFIG = {
   DEVNET_RPC: 'https://api.devnet.solana.com',
 }
 
-// Escrow 状态枚举
+// Escrow 状态枚举（链上以 u8 存储，取值为 ESCROW_STATUSES 中的下标）
 export enum EscrowStatus {
   Created = 'created',
   Funded = 'funded',
@@ -29,9 +29,56 @@ export enum EscrowStatus {
   Completed = 'completed',
   Refunded = 'refunded',
   Cancelled = 'cancelled',
+  Escalated = 'escalated',
+  Ruled = 'ruled',
+  Appealed = 'appealed',
+  Rejected = 'rejected',
+  PendingCancel = 'pending_cancel',
 }
 
-// Escrow 数据结构
+// 与程序 EscrowStatus 的变体顺序一致
+export const ESCROW_STATUSES: EscrowStatus[] = [
+  EscrowStatus.Created,
+  EscrowStatus.Funded,
+  EscrowStatus.Delivered,
+  EscrowStatus.Disputed,
+  EscrowStatus.Completed,
+  EscrowStatus.Refunded,
+  EscrowStatus.Cancelled,
+  EscrowStatus.Escalated,
+  EscrowStatus.Ruled,
+  EscrowStatus.Appealed,
+  EscrowStatus.Rejected,
+  EscrowStatus.PendingCancel,
+]
+
+// 链上零拷贝布局（program.account.escrow.fetch 的原始结果）
+export interface RawEscrowAccount {
+  buyer: PublicKey
+  provider: PublicKey
+  platform: PublicKey
+  amount: BN
+  status: number
+  version: number
+  bump: number
+  requestIdLen: number
+  proposalIdLen: number
+  createdAt: BN
+  fundedAt: BN
+  deliveredAt: BN
+  completedAt: BN
+  refundedAt: BN
+  disputedAt: BN
+  deliveryHash: number[]
+  accessMint: PublicKey
+  requestIdBytes: number[]
+  proposalIdBytes: number[]
+  metadataLen: number
+  yieldOpen: number
+  mint: PublicKey
+}
+
+// Escrow 数据结构（0 时间戳和默认公钥还原为 null，定长 ID 还原为字符串）
 export interface EscrowAccount {
   buyer: PublicKey
   provider: PublicKey
@@ -46,7 +93,12 @@ export interface EscrowAccount {
   completedAt: BN | null
   refundedAt: BN | null
   disputedAt: BN | null
+  deliveryHash: number[]
+  accessMint: PublicKey | null
+  version: number
   bump: number
+  yieldOpen: boolean
+  mint: PublicKey | null
 }
 
 /**
@@ -93,7 +145,7 @@ export class AnchorEscrowClient {
     try {
       const [escrowPda] = this.getEscrowPDA(buyer, requestId)
       const escrowAccount = await (this.program.account as any).escrow.fetch(escrowPda)
-      return escrowAccount as any
+      return AnchorEscrowClient.decodeEscrow(escrowAccount as RawEscrowAccount)
     } catch (error) {
       console.error('获取 Escrow 失败:', error)
       return null
@@ -155,7 +207,7 @@ export class AnchorEscrowClient {
 
     // 调用智能合约
     const tx = await this.program.methods
-      .createEscrow(amountLamports, requestId, proposalId)
+      .createEscrow(amountLamports, requestId, proposalId, null)
       .accounts({
         buyer,
         provider,
@@ -164,6 +216,7 @@ export class AnchorEscrowClient {
         buyerTokenAccount,
         escrowTokenAccount,
         usdcMint: ANCHOR_CONFIG.USDC_MINT,
+        mint: ANCHOR_CONFIG.USDC_MINT,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc()
@@ -328,17 +381,45 @@ export class AnchorEscrowClient {
   }
 
   /**
-   * 获取 Escrow 状态的可读字符串
+   * 获取 Escrow 状态的可读字符串（链上状态为 u8）
    */
-  static getStatusString(status: any): EscrowStatus {
-    if (status.created) return EscrowStatus.Created
-    if (status.funded) return EscrowStatus.Funded
-    if (status.delivered) return EscrowStatus.Delivered
-    if (status.disputed) return EscrowStatus.Disputed
-    if (status.completed) return EscrowStatus.Completed
-    if (status.refunded) return EscrowStatus.Refunded
-    if (status.cancelled) return EscrowStatus.Cancelled
-    return EscrowStatus.Created
+  static getStatusString(status: number): EscrowStatus {
+    const value = ESCROW_STATUSES[status]
+    if (value === undefined) {
+      throw new Error(`未知的 Escrow 状态: ${status}`)
+    }
+    return value
+  }
+
+  /**
+   * 解析零拷贝布局的 Escrow 账户
+   */
+  static decodeEscrow(raw: RawEscrowAccount): EscrowAccount {
+    const timestamp = (value: BN): BN | null => (value.isZero() ? null : value)
+    const id = (bytes: number[], len: number): string =>
+      Buffer.from(bytes.slice(0, len)).toString('utf8')
+
+    return {
+      buyer: raw.buyer,
+      provider: raw.provider,
+      platform: raw.platform,
+      amount: raw.amount,
+      requestId: id(raw.requestIdBytes, raw.requestIdLen),
+      proposalId: id(raw.proposalIdBytes, raw.proposalIdLen),
+      status: AnchorEscrowClient.getStatusString(raw.status),
+      createdAt: raw.createdAt,
+      fundedAt: timestamp(raw.fundedAt),
+      deliveredAt: timestamp(raw.deliveredAt),
+      completedAt: timestamp(raw.completedAt),
+      refundedAt: timestamp(raw.refundedAt),
+      disputedAt: timestamp(raw.disputedAt),
+      deliveryHash: raw.deliveryHash,
+      accessMint: raw.accessMint.equals(PublicKey.default) ? null : raw.accessMint,
+      version: raw.version,
+      bump: raw.bump,
+      yieldOpen: raw.yieldOpen !== 0,
+      mint: raw.mint.equals(PublicKey.default) ? null : raw.mint,
+    }
   }
 
   /**
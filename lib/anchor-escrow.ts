@@ -19,6 +19,7 @@ import {
 } from '@solana/spl-token'
 import { Program, AnchorProvider, web3, BN, Idl } from '@coral-xyz/anchor'
 import idl from './idl/datanexus_escrow.json'
+import type { RawEscrowAccount } from './anchor-escrow-client'
 
 // 程序 ID (已部署到 Devnet)
 const PROGRAM_ID = new PublicKey('gxDTeSCzk9mqiokrmTb1uNbWCjQ1rj2hsj5N65K9698')
@@ -38,17 +39,41 @@ const PLATFORM_WALLET = new PublicKey(
 )
 
 /**
- * 托管状态
+ * 托管状态（链上以 u8 存储，取值为 ESCROW_STATUSES 中的下标）
  */
 export enum EscrowStatus {
   Created = 'Created',
   Funded = 'Funded',
   Delivered = 'Delivered',
+  Disputed = 'Disputed',
   Completed = 'Completed',
   Refunded = 'Refunded',
   Cancelled = 'Cancelled',
+  Escalated = 'Escalated',
+  Ruled = 'Ruled',
+  Appealed = 'Appealed',
+  Rejected = 'Rejected',
+  PendingCancel = 'PendingCancel',
 }
 
+/**
+ * 与程序 EscrowStatus 的变体顺序一致
+ */
+export const ESCROW_STATUSES: EscrowStatus[] = [
+  EscrowStatus.Created,
+  EscrowStatus.Funded,
+  EscrowStatus.Delivered,
+  EscrowStatus.Disputed,
+  EscrowStatus.Completed,
+  EscrowStatus.Refunded,
+  EscrowStatus.Cancelled,
+  EscrowStatus.Escalated,
+  EscrowStatus.Ruled,
+  EscrowStatus.Appealed,
+  EscrowStatus.Rejected,
+  EscrowStatus.PendingCancel,
+]
+
 /**
  * 托管账户数据
  */
@@ -65,9 +90,38 @@ export interface EscrowAccount {
   deliveredAt?: BN
   completedAt?: BN
   refundedAt?: BN
+  disputedAt?: BN
+  accessMint?: PublicKey
   bump: number
 }
 
+/**
+ * 解析零拷贝布局的托管账户（0 时间戳和默认公钥视为未设置）
+ */
+function decodeEscrow(raw: RawEscrowAccount): EscrowAccount {
+  const timestamp = (value: BN): BN | undefined => (value.isZero() ? undefined : value)
+  const id = (bytes: number[], len: number): string =>
+    Buffer.from(bytes.slice(0, len)).toString('utf8')
+
+  return {
+    buyer: raw.buyer,
+    provider: raw.provider,
+    platform: raw.platform,
+    amount: raw.amount,
+    requestId: id(raw.requestIdBytes, raw.requestIdLen),
+    proposalId: id(raw.proposalIdBytes, raw.proposalIdLen),
+    status: ESCROW_STATUSES[raw.status],
+    createdAt: raw.createdAt,
+    fundedAt: timestamp(raw.fundedAt),
+    deliveredAt: timestamp(raw.deliveredAt),
+    completedAt: timestamp(raw.completedAt),
+    refundedAt: timestamp(raw.refundedAt),
+    disputedAt: timestamp(raw.disputedAt),
+    accessMint: raw.accessMint.equals(PublicKey.default) ? undefined : raw.accessMint,
+    bump: raw.bump,
+  }
+}
+
 /**
  * Anchor Escrow 客户端
  */
@@ -152,7 +206,7 @@ export class AnchorEscrowClient {
 
     // 调用 create_escrow 指令
     const ix = await this.program.methods
-      .createEscrow(amountBN, requestId, proposalId)
+      .createEscrow(amountBN, requestId, proposalId, null)
       .accounts({
         escrow: escrowPDA,
         buyer,
@@ -160,6 +214,7 @@ export class AnchorEscrowClient {
         platform: PLATFORM_WALLET,
         buyerTokenAccount,
         escrowTokenAccount,
+        mint: USDC_MINT,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -312,7 +367,7 @@ export class AnchorEscrowClient {
   async getEscrow(escrowPDA: PublicKey): Promise<EscrowAccount | null> {
     try {
       const escrowAccount = await this.program.account.escrow.fetch(escrowPDA)
-      return escrowAccount as EscrowAccount
+      return decodeEscrow(escrowAccount as unknown as RawEscrowAccount)
     } catch (error) {
       console.error('Failed to fetch escrow account:', error)
       return null
@@ -346,7 +401,7 @@ export class AnchorEscrowClient {
           },
         },
       ])
-      return escrows.map((e) => e.account as EscrowAccount)
+      return escrows.map((e) => decodeEscrow(e.account as unknown as RawEscrowAccount))
     } catch (error) {
       console.error('Failed to fetch escrows by buyer:', error)
       return []
@@ -366,7 +421,7 @@ export class AnchorEscrowClient {
           },
         },
       ])
-      return escrows.map((e) => e.account as EscrowAccount)
+      return escrows.map((e) => decodeEscrow(e.account as unknown as RawEscrowAccount))
     } catch (error) {
       console.error('Failed to fetch escrows by provider:', error)
       return []
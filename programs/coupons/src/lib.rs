@@ -0,0 +1,221 @@
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hash;
+
+declare_id!("Cy1w2vkjdVWaj6SGhvZg3rRCxHFVyqmRrUbdiT8NCwGW");
+
+/// 托管程序 ID，兑换须由托管程序的兑换权限 PDA 签名
+pub const ESCROW_PROGRAM_ID: Pubkey = pubkey!("gxDTeSCzk9mqiokrmTb1uNbWCjQ1rj2hsj5N65K9698");
+
+/// 托管程序兑换权限 PDA 的种子
+pub const COUPON_REDEEMER_SEED: &[u8] = b"coupon_redeemer";
+
+/// 基点分母
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// DataNexus Coupons Program
+///
+/// 平台优惠券：
+/// 1. 平台签发优惠券 PDA（平台费减免基点或固定返还金额），可限定买家和有效期
+/// 2. 买家创建托管时附带优惠券和优惠码，托管程序以兑换权限 PDA 签名 CPI `redeem_coupon` 标记已使用
+/// 3. 释放资金时托管程序按券面减免平台费，减免部分退还买家
+#[program]
+pub mod datanexus_coupons {
+    use super::*;
+
+    /// 签发优惠券
+    ///
+    /// `code_hash` 为优惠码的哈希（优惠码本身在链下分发），`buyer` 为空时任何买家都可使用；
+    /// 托管程序只接受签发人为托管配置管理员的优惠券
+    pub fn issue_coupon(
+        ctx: Context<IssueCoupon>,
+        code_hash: [u8; 32],
+        discount: Discount,
+        buyer: Option<Pubkey>,
+        expires_at: i64,
+    ) -> Result<()> {
+        match discount {
+            Discount::FeeBps(bps) => require!(
+                bps > 0 && bps as u64 <= BPS_DENOMINATOR,
+                CouponError::InvalidDiscount
+            ),
+            Discount::Rebate(amount) => require!(amount > 0, CouponError::InvalidDiscount),
+        }
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, CouponError::CouponExpired);
+
+        let coupon = &mut ctx.accounts.coupon;
+        coupon.issuer = ctx.accounts.issuer.key();
+        coupon.code_hash = code_hash;
+        coupon.discount = discount;
+        coupon.buyer = buyer;
+        coupon.expires_at = expires_at;
+        coupon.issued_at = now;
+        coupon.redeemed_escrow = None;
+        coupon.redeemed_at = None;
+        coupon.bump = ctx.bumps.coupon;
+
+        msg!("Coupon issued: {}", coupon.key());
+        msg!("Discount: {:?}", discount);
+        msg!("Expires at: {}", expires_at);
+
+        Ok(())
+    }
+
+    /// 撤销未使用的优惠券（仅签发人），租金退还签发人
+    pub fn revoke_coupon(ctx: Context<RevokeCoupon>) -> Result<()> {
+        let coupon = &ctx.accounts.coupon;
+        require!(
+            coupon.redeemed_escrow.is_none(),
+            CouponError::CouponAlreadyRedeemed
+        );
+
+        msg!("Coupon revoked: {}", coupon.key());
+
+        Ok(())
+    }
+
+    /// 兑换优惠券（只能由托管程序在创建托管时 CPI 调用）
+    ///
+    /// `code` 为买家提供的优惠码，哈希须与签发时的 `code_hash` 一致，
+    /// 不限买家的优惠券也不能仅凭链上扫描到的地址兑换
+    pub fn redeem_coupon(ctx: Context<RedeemCoupon>, escrow: Pubkey, code: String) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let coupon = &mut ctx.accounts.coupon;
+
+        require!(
+            hash(code.as_bytes()).to_bytes() == coupon.code_hash,
+            CouponError::InvalidCode
+        );
+        require!(
+            coupon.redeemed_escrow.is_none(),
+            CouponError::CouponAlreadyRedeemed
+        );
+        require!(now <= coupon.expires_at, CouponError::CouponExpired);
+        if let Some(buyer) = coupon.buyer {
+            require_keys_eq!(buyer, ctx.accounts.buyer.key(), CouponError::Unauthorized);
+        }
+
+        coupon.redeemed_escrow = Some(escrow);
+        coupon.redeemed_at = Some(now);
+
+        emit!(CouponRedeemed {
+            coupon: coupon.key(),
+            escrow,
+            buyer: ctx.accounts.buyer.key(),
+            discount: coupon.discount,
+            timestamp: now,
+        });
+
+        msg!("Coupon redeemed: {}", coupon.key());
+        msg!("Escrow: {}", escrow);
+
+        Ok(())
+    }
+}
+
+/// 优惠券面额
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum Discount {
+    FeeBps(u16), // 平台费减免比例（基点）
+    Rebate(u64), // 从平台费中返还买家的固定金额（6 位小数），不超过平台费
+}
+
+/// 平台签发的优惠券
+#[account]
+#[derive(InitSpace)]
+pub struct Coupon {
+    pub issuer: Pubkey,                  // 签发人（托管配置管理员）
+    pub code_hash: [u8; 32],             // 优惠码哈希
+    pub discount: Discount,              // 面额
+    pub buyer: Option<Pubkey>,           // 限定买家（为空时不限）
+    pub expires_at: i64,                 // 兑换截止时间
+    pub issued_at: i64,                  // 签发时间
+    pub redeemed_escrow: Option<Pubkey>, // 兑换的托管（未兑换为空）
+    pub redeemed_at: Option<i64>,        // 兑换时间
+    pub bump: u8,                        // PDA bump
+}
+
+impl Coupon {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 优惠券已兑换
+#[event]
+pub struct CouponRedeemed {
+    pub coupon: Pubkey,
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub discount: Discount,
+    pub timestamp: i64,
+}
+
+/// 签发优惠券的上下文
+#[derive(Accounts)]
+#[instruction(code_hash: [u8; 32])]
+pub struct IssueCoupon<'info> {
+    #[account(
+        init,
+        payer = issuer,
+        space = Coupon::LEN,
+        seeds = [b"coupon", issuer.key().as_ref(), code_hash.as_ref()],
+        bump
+    )]
+    pub coupon: Account<'info, Coupon>,
+
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 撤销优惠券的上下文
+#[derive(Accounts)]
+pub struct RevokeCoupon<'info> {
+    #[account(
+        mut,
+        seeds = [b"coupon", issuer.key().as_ref(), coupon.code_hash.as_ref()],
+        bump = coupon.bump,
+        has_one = issuer @ CouponError::Unauthorized,
+        close = issuer
+    )]
+    pub coupon: Account<'info, Coupon>,
+
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+}
+
+/// 兑换优惠券的上下文
+#[derive(Accounts)]
+pub struct RedeemCoupon<'info> {
+    #[account(
+        mut,
+        seeds = [b"coupon", coupon.issuer.as_ref(), coupon.code_hash.as_ref()],
+        bump = coupon.bump
+    )]
+    pub coupon: Account<'info, Coupon>,
+
+    /// 托管程序的兑换权限 PDA
+    #[account(seeds = [COUPON_REDEEMER_SEED], bump, seeds::program = ESCROW_PROGRAM_ID)]
+    pub redeemer: Signer<'info>,
+
+    pub buyer: Signer<'info>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum CouponError {
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[msg("Invalid discount")]
+    InvalidDiscount,
+
+    #[msg("Coupon has expired")]
+    CouponExpired,
+
+    #[msg("Coupon has already been redeemed")]
+    CouponAlreadyRedeemed,
+
+    #[msg("Coupon code does not match")]
+    InvalidCode,
+}
@@ -1,551 +1,11614 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::metadata::{
+    self, mpl_token_metadata::types::DataV2, CreateMasterEditionV3, CreateMetadataAccountsV3,
+    Metadata,
+};
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_interface::{self, TokenInterface};
+use bytemuck::Zeroable;
+use datanexus_coupons::program::DatanexusCoupons;
+use datanexus_coupons::{Coupon, Discount, COUPON_REDEEMER_SEED};
+use datanexus_registry::ProviderProfile;
+use datanexus_requests::{Proposal, ProposalStatus, Request, RequestStatus};
+use datanexus_sigverify::SignatureError;
+use mpl_bubblegum::instructions::{CreateTreeConfigCpiBuilder, MintV1CpiBuilder};
+use mpl_bubblegum::types::{MetadataArgs, TokenProgramVersion, TokenStandard};
+use solana_sha256_hasher::hashv;
 
 declare_id!("gxDTeSCzk9mqiokrmTb1uNbWCjQ1rj2hsj5N65K9698");
 
+/// 基点分母（10000 bps = 100%）
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// 平台费比例（基点）
+pub const PLATFORM_FEE_BPS: u64 = 500;
+
+/// 配置变更的公示期（秒）：排队的变更至少经过该时长才能执行
+pub const CONFIG_TIMELOCK: i64 = 2 * 24 * 60 * 60;
+
+/// 托管账户当前布局版本（0 表示尚未迁移的旧布局）
+pub const ESCROW_VERSION: u8 = 5;
+
+/// 托管扩展元数据上限（字节）
+pub const MAX_METADATA_LEN: usize = 256;
+
+/// 托管的需求 ID 和提案 ID 上限（字节）
+pub const MAX_ESCROW_ID_LEN: usize = 64;
+
+/// 平台阻止释放时记录的原因上限（字节）
+pub const MAX_BLOCK_REASON_LEN: usize = 64;
+
+/// 加密后的数据集解密密钥上限（字节），足够容纳 sealed box 封装的 32 字节密钥
+pub const MAX_ENCRYPTED_KEY_LEN: usize = 128;
+
+/// 存储引用 ID（CID、交易 ID 或对象路径）的上限（字节）
+pub const MAX_STORAGE_ID_LEN: usize = 128;
+
+/// IPFS CIDv1 的长度：`b` 前缀 + base32 编码的 36 字节（单字节编解码器 + sha2-256 多重哈希）
+pub const IPFS_CID_V1_LEN: usize = 59;
+
+/// Arweave 交易 ID 的长度：base64url 编码的 32 字节（无填充）
+pub const ARWEAVE_TX_ID_LEN: usize = 43;
+
+/// 数据块清单最多的块数
+pub const MAX_CHUNKS: u32 = 1 << 20;
+
+/// 一次争议最多指出的数据块数
+pub const MAX_DISPUTED_CHUNKS: usize = 16;
+
+/// 一次抽查最多的数据块数
+pub const MAX_CHALLENGE_CHUNKS: usize = 8;
+
+/// 提供商响应抽查的期限：24 小时
+pub const CHALLENGE_WINDOW: i64 = 24 * 60 * 60;
+
+/// 买家评分的最高分（评分为 1–5）
+pub const MAX_QUALITY_SCORE: u8 = 5;
+
+/// 每个托管最多拒收交付的次数，用完后只能确认或发起争议
+pub const MAX_REJECTIONS: u8 = 3;
+
+/// 每个托管的协商记录最多条数
+pub const MAX_MESSAGES: usize = 32;
+
+/// 买家取消后提供商可以异议的期限：24 小时
+pub const CANCEL_GRACE_PERIOD: i64 = 24 * 60 * 60;
+
+/// 拒收后提供商重新交付的期限：3 天
+pub const REDELIVERY_WINDOW: i64 = 3 * 24 * 60 * 60;
+
+/// 提供商销售记录 Merkle 树深度，最多记录 2^16 笔销售
+pub const SALES_TREE_DEPTH: usize = 16;
+
+/// 买家创建托管的限流窗口（`ProtocolSettings.max_daily_escrows`）
+pub const CREATION_WINDOW: i64 = 24 * 60 * 60;
+
+/// 徽章类型：平台已核验身份和数据来源的提供商
+pub const BADGE_VERIFIED: u8 = 0;
+
+/// 收据 NFT 名称
+pub const RECEIPT_NAME: &str = "DataNexus Receipt";
+
+/// 收据 NFT 符号
+pub const RECEIPT_SYMBOL: &str = "DNXR";
+
+/// 收据 NFT 元数据 URI 前缀（后接收据记录账户地址）
+pub const RECEIPT_URI_PREFIX: &str = "https://xdatanexus.vercel.app/api/receipts/";
+
+/// SPL Noop 程序（压缩 NFT 日志包装器）
+pub const SPL_NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// SPL Account Compression 程序
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+/// 陪审团程序：争议金额达到门槛时，由质押代币持有人投票裁决
+pub const JURY_PROGRAM_ID: Pubkey = pubkey!("HLtvvKCiYsp83uBD5VBSXyjggSkynqzkGSMCN39ZbRFj");
+
+/// 陪审团程序签署 CPI 的权限 PDA 种子
+pub const JURY_AUTHORITY_SEED: &[u8] = b"jury_authority";
+
+/// 转售程序：访问凭证换手时通过 CPI 更新访问期记录的凭证账户
+pub const RESALE_PROGRAM_ID: Pubkey = pubkey!("DMoHhiEEFp81ubEYvDmx5H4vmsEpTVYDHrrwcswbzPo");
+
+/// 转售程序签署 CPI 的权限 PDA 种子
+pub const RESALE_AUTHORITY_SEED: &[u8] = b"resale_authority";
+
+/// 信誉程序：买家确认时给出的评分通过 CPI 记入提供商信誉，每个托管一条
+pub const REPUTATION_PROGRAM_ID: Pubkey = pubkey!("GoFyy3FG4xZbSfKwVvbx29Pw6D9MFdXUmBofBo37x7NL");
+
+/// 信誉程序 `rate_provider` 指令鉴别符（`sha256("global:rate_provider")[..8]`）
+pub const RATE_PROVIDER_DISCRIMINATOR: [u8; 8] = [70, 12, 76, 182, 225, 123, 186, 182];
+
+/// 质押程序：提供商在此质押保证金，大额托管交付前校验
+pub const STAKING_PROGRAM_ID: Pubkey = pubkey!("3cknkeLdBfENtjS4MtNEmJs6JQp6tWRCpyFokan4PZiA");
+
+/// Pyth 拉取式预言机的接收程序，`PriceUpdateV2` 账户归其所有
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
+    pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Pyth `PriceUpdateV2` 账户鉴别符
+const PRICE_UPDATE_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+/// 估值价格的最大允许延迟（秒），超过即视为过期
+pub const MAX_PRICE_AGE: i64 = 60;
+
+/// Ed25519 签名校验原生程序
+pub use datanexus_sigverify::ED25519_PROGRAM_ID;
+
+/// Solend 借贷程序（SPL token-lending 布局），开启收益模式的托管资金存入其储备
+pub const SOLEND_PROGRAM_ID: Pubkey = pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
+
+/// 借贷程序 `DepositReserveLiquidity` 指令序号
+const DEPOSIT_RESERVE_LIQUIDITY: u8 = 4;
+
+/// 借贷程序 `RedeemReserveCollateral` 指令序号
+const REDEEM_RESERVE_COLLATERAL: u8 = 5;
+
+/// Jupiter 聚合器 v6 程序
+pub const JUPITER_PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+/// Wormhole 核心桥程序，已验证的 `PostedVAA` 账户归其所有
+pub const WORMHOLE_PROGRAM_ID: Pubkey = pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+
+/// Wormhole 代币桥程序
+pub const TOKEN_BRIDGE_PROGRAM_ID: Pubkey =
+    pubkey!("wormDTUJ6AWPNvk59vGQbDvGJmqbDTdgWgAqcLBCgUb");
+
+/// 代币桥 `CompleteWrappedWithPayload` 指令序号
+const COMPLETE_WRAPPED_WITH_PAYLOAD: u8 = 10;
+
+/// 代币桥消息类型：附带载荷的转账
+const TRANSFER_WITH_PAYLOAD: u8 = 3;
+
+/// Wormhole 中 Solana 的链 ID
+const WORMHOLE_SOLANA_CHAIN: u16 = 1;
+
+/// SPL Memo 程序（v2），开启转账备注时在每笔资金转出前记录对账备注
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Solana Attestation Service 程序，KYC 等合规证明账户归其所有
+pub const ATTESTATION_PROGRAM_ID: Pubkey =
+    pubkey!("22zoJMtdu4tQc2PzL74ZUT7FrwgB1Udec8DdW4yw4BdG");
+
+/// `ProtocolSettings.compliance` 标志：买家须持有合规证明
+pub const COMPLIANCE_BUYER: u8 = 1 << 0;
+
+/// `ProtocolSettings.compliance` 标志：提供商须持有合规证明
+pub const COMPLIANCE_PROVIDER: u8 = 1 << 1;
+
+/// Solana Attestation Service 账户类型：证明
+const ATTESTATION_ACCOUNT_TYPE: u8 = 2;
+
+/// Shadow Drive 存储程序，存储账户归其所有
+pub const SHADOW_DRIVE_PROGRAM_ID: Pubkey =
+    pubkey!("2e1wdyNhUvE76y6yUCvah2KaviavMJYKoRun8acMRBZZ");
+
+/// Shadow Drive 对象网关
+pub const SHADOW_DRIVE_GATEWAY: &str = "https://shdw-drive.genesysgo.net";
+
+/// 零知识证明验证程序接口 `verify(proof: Vec<u8>, public_inputs: Vec<[u8; 32]>)` 的鉴别符
+/// （`sha256("global:verify")[..8]`），Groth16 和 Plonk 验证程序都按此接口接入
+pub const ZK_VERIFY_DISCRIMINATOR: [u8; 8] = [133, 161, 141, 48, 120, 198, 88, 150];
+
 /// DataNexus Escrow Program
 /// 
 /// 实现去中心化的数据交易托管：
 /// 1. 买家创建托管并转入 USDC
 /// 2. 提供商交付数据
-/// 3. 买家确认后自动释放资金（95% 给提供商，5% 给平台）
-/// 4. 支持争议和退款
+/// 3. 买家确认后自动释放资金（95% 给提供商，5% 平台费分给国库和质押奖励池）
+/// 4. 支持争议和退款，大额争议可移交陪审团投票裁决
 #[program]
 pub mod datanexus_escrow {
     use super::*;
 
+    /// 初始化平台配置
+    ///
+    /// 设置平台费的分配目标：国库账户与质押奖励池，按基点权重分配。
+    /// 仅程序升级权限可调用，防止部署后被抢先初始化并夺取管理员
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        treasury_bps: u16,
+        rewards_pool_bps: u16,
+    ) -> Result<()> {
+        require!(
+            treasury_bps as u64 + rewards_pool_bps as u64 == BPS_DENOMINATOR,
+            EscrowError::InvalidFeeSplit
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.treasury = ctx.accounts.treasury.key();
+        config.rewards_pool = ctx.accounts.rewards_pool.key();
+        config.treasury_bps = treasury_bps;
+        config.rewards_pool_bps = rewards_pool_bps;
+        config.receipt_tree = Pubkey::default();
+        config.settings = ProtocolSettings::default();
+        config.bump = ctx.bumps.config;
+
+        msg!("Config initialized: {}", config.key());
+        msg!("Treasury: {} ({} bps)", config.treasury, treasury_bps);
+        msg!("Rewards pool: {} ({} bps)", config.rewards_pool, rewards_pool_bps);
+
+        Ok(())
+    }
+
+    /// 排队更新平台费分配（仅管理员）
+    ///
+    /// 国库、奖励池地址和分成比例变更先写入 `[b"pending_config"]`，
+    /// 经过 `CONFIG_TIMELOCK` 公示期后才能由 `execute_config_change` 生效
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        treasury_bps: u16,
+        rewards_pool_bps: u16,
+    ) -> Result<()> {
+        require!(
+            treasury_bps as u64 + rewards_pool_bps as u64 == BPS_DENOMINATOR,
+            EscrowError::InvalidFeeSplit
+        );
+
+        let change = ConfigChange::FeeSplit {
+            treasury: ctx.accounts.treasury.key(),
+            rewards_pool: ctx.accounts.rewards_pool.key(),
+            treasury_bps,
+            rewards_pool_bps,
+        };
+        let pending = &mut ctx.accounts.pending_change;
+        queue_config_change(pending, change, ctx.accounts.payer.key(), ctx.bumps.pending_change)?;
+
+        msg!("Config change queued: {}", pending.key());
+        msg!("Treasury: {} ({} bps)", ctx.accounts.treasury.key(), treasury_bps);
+        msg!(
+            "Rewards pool: {} ({} bps)",
+            ctx.accounts.rewards_pool.key(),
+            rewards_pool_bps
+        );
+        msg!("Execute after: {}", pending.execute_after);
+
+        Ok(())
+    }
+
+    /// 排队更新协议策略参数（仅管理员）
+    ///
+    /// 与 `update_config` 共用同一个待生效变更，经过公示期后才能执行
+    pub fn update_settings(ctx: Context<UpdateSettings>, settings: ProtocolSettings) -> Result<()> {
+        require!(
+            settings.insurance_bps as u64 <= BPS_DENOMINATOR,
+            EscrowError::InvalidInsuranceBps
+        );
+        require!(
+            settings.yield_buyer_bps as u64 <= BPS_DENOMINATOR,
+            EscrowError::InvalidYieldBps
+        );
+        require!(
+            settings.late_refund_cap_bps as u64 <= BPS_DENOMINATOR,
+            EscrowError::InvalidLatenessSchedule
+        );
+
+        msg!("Settings change queued");
+        msg!(
+            "Require registered provider: {}",
+            settings.require_registered_provider
+        );
+        msg!("Insurance: {} bps", settings.insurance_bps);
+        msg!("Dispute bond: {} USDC", settings.dispute_bond);
+        msg!(
+            "Collateral: {} bps above {} USDC",
+            settings.collateral_bps,
+            settings.collateral_threshold
+        );
+        msg!(
+            "Appeal window: {}s, bond: {} USDC",
+            settings.appeal_window,
+            settings.appeal_bond
+        );
+        msg!("Yield to buyer: {} bps", settings.yield_buyer_bps);
+        msg!("Transfer memos: {}", settings.memo_transfers);
+        msg!(
+            "Compliance: {:#04b}, credential: {}, schema: {}",
+            settings.compliance,
+            settings.attestation_credential,
+            settings.attestation_schema
+        );
+        msg!(
+            "Late refund: {} bps per day, capped at {} bps",
+            settings.late_refund_bps_per_day,
+            settings.late_refund_cap_bps
+        );
+        msg!("Stale escrow TTL: {}s", settings.stale_escrow_ttl);
+        msg!(
+            "Badge type {} required above {} USDC",
+            settings.required_badge,
+            settings.badge_threshold
+        );
+        msg!(
+            "Buyer limits: {} open, {} per day",
+            settings.max_open_escrows,
+            settings.max_daily_escrows
+        );
+        msg!("Max price deviation: {} bps", settings.max_price_deviation_bps);
+        msg!("Fee ledger: {}", settings.fee_ledger);
+
+        let pending = &mut ctx.accounts.pending_change;
+        queue_config_change(
+            pending,
+            ConfigChange::Settings(settings),
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_change,
+        )?;
+
+        msg!("Execute after: {}", pending.execute_after);
+
+        Ok(())
+    }
+
+    /// 执行已过公示期的配置变更（任何人都可以调用）
+    ///
+    /// 变更写入配置后关闭待生效账户，租金退还排队时的付款人
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+        let pending = &ctx.accounts.pending_change;
+        require!(
+            Clock::get()?.unix_timestamp >= pending.execute_after,
+            EscrowError::TimelockActive
+        );
+
+        let config = &mut ctx.accounts.config;
+        match pending.change.clone() {
+            ConfigChange::FeeSplit {
+                treasury,
+                rewards_pool,
+                treasury_bps,
+                rewards_pool_bps,
+            } => {
+                config.treasury = treasury;
+                config.rewards_pool = rewards_pool;
+                config.treasury_bps = treasury_bps;
+                config.rewards_pool_bps = rewards_pool_bps;
+
+                msg!("Config updated: {}", config.key());
+                msg!("Treasury: {} ({} bps)", treasury, treasury_bps);
+                msg!("Rewards pool: {} ({} bps)", rewards_pool, rewards_pool_bps);
+            }
+            ConfigChange::Settings(settings) => {
+                config.settings = settings;
+
+                msg!("Settings updated: {}", config.key());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 撤销尚未执行的配置变更（仅管理员）
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        msg!("Config change cancelled: {}", ctx.accounts.pending_change.key());
+
+        Ok(())
+    }
+
+    /// 创建保险池代币账户（仅管理员）
+    ///
+    /// 保险池为 `[b"insurance_vault"]` PDA，权限为 Config PDA；
+    /// `ProtocolSettings.insurance_bps` 非零时，每笔平台费先按该比例划入保险池
+    pub fn init_insurance(ctx: Context<InitInsurance>) -> Result<()> {
+        msg!("Insurance vault initialized: {}", ctx.accounts.insurance_vault.key());
+        msg!("Mint: {}", ctx.accounts.mint.key());
+
+        Ok(())
+    }
+
+    /// 从保险池赔付买家（仅管理员，移交治理后需提案通过）
+    ///
+    /// 用于提供商失联等托管流程无法弥补买家损失的情况：托管须已结算，
+    /// 赔付额不超过托管金额，每个托管只能赔付一次
+    pub fn claim_insurance(ctx: Context<ClaimInsurance>, amount: u64) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(escrow.status().is_terminal(), EscrowError::InsuranceNotClaimable);
+        require!(
+            amount > 0 && amount <= escrow.amount,
+            EscrowError::InvalidAmount
+        );
+
+        let config = &ctx.accounts.config;
+        let seeds = &[b"config".as_ref(), &[config.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.insurance_vault.to_account_info(),
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            amount,
+        )?;
+
+        let claim = &mut ctx.accounts.claim;
+        claim.escrow = ctx.accounts.escrow.key();
+        claim.buyer = escrow.buyer;
+        claim.amount = amount;
+        claim.claimed_at = Clock::get()?.unix_timestamp;
+        claim.bump = ctx.bumps.claim;
+
+        msg!("Insurance claim paid: {}", claim.key());
+        msg!("Escrow: {}, buyer: {}", claim.escrow, claim.buyer);
+        msg!("Amount: {} USDC", amount);
+
+        Ok(())
+    }
+
+    /// 创建费用账本和共享费用金库（仅管理员）
+    ///
+    /// 费用账本为 `[b"fee_ledger"]` PDA，金库为 `[b"fee_vault"]` PDA，权限为费用账本；
+    /// 开启 `ProtocolSettings.fee_ledger` 后，释放时平台费整笔转入金库并记入账本
+    pub fn init_fee_ledger(ctx: Context<InitFeeLedger>) -> Result<()> {
+        let ledger = &mut ctx.accounts.fee_ledger;
+        ledger.vault = ctx.accounts.fee_vault.key();
+        ledger.accrued = 0;
+        ledger.total_settled = 0;
+        ledger.settled_at = 0;
+        ledger.bump = ctx.bumps.fee_ledger;
+
+        msg!("Fee ledger initialized: {}", ledger.key());
+        msg!("Fee vault: {}", ledger.vault);
+        msg!("Mint: {}", ctx.accounts.mint.key());
+
+        Ok(())
+    }
+
+    /// 拆分费用金库中累计的平台费（任何人可调用）
+    ///
+    /// 按释放时相同的权重分给保险池、国库和奖励池，由费用账本 PDA 签名；
+    /// 多笔释放的平台费合并为一次拆分，释放路径只需一笔平台费转账
+    pub fn settle_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleFees<'info>>,
+    ) -> Result<()> {
+        let accrued = ctx.accounts.fee_ledger.accrued;
+        require!(accrued > 0, EscrowError::NoFeesAccrued);
+
+        let bump = ctx.accounts.fee_ledger.bump;
+        let seeds = &[b"fee_ledger".as_ref(), &[bump]];
+        let signer = &[&seeds[..]];
+
+        msg!("Settling fees: {} USDC", accrued);
+
+        distribute_platform_fee(
+            &ctx.accounts.config,
+            ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.fee_vault.to_account_info(),
+            Some(ctx.accounts.treasury_token_account.to_account_info()),
+            Some(ctx.accounts.rewards_pool_token_account.to_account_info()),
+            ctx.accounts
+                .insurance_vault
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts.fee_ledger.to_account_info(),
+            ctx.remaining_accounts,
+            None,
+            None,
+            signer,
+            accrued,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let ledger = &mut ctx.accounts.fee_ledger;
+        ledger.accrued = 0;
+        ledger.total_settled = ledger.total_settled.checked_add(accrued).unwrap();
+        ledger.settled_at = now;
+
+        emit!(FeesSettled {
+            amount: accrued,
+            total_settled: ledger.total_settled,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// 移交管理员（仅管理员）
+    ///
+    /// 移交给 `datanexus_governance` 的权限 PDA 后，配置只能通过通过投票并经过时间锁的提案修改
+    pub fn set_admin(ctx: Context<SetAdmin>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = new_admin;
+
+        msg!("Admin transferred: {}", config.key());
+        msg!("New admin: {}", new_admin);
+
+        Ok(())
+    }
+
+    /// 创建平台管理的收据 Merkle 树（仅管理员）
+    ///
+    /// Merkle 树账户需在同一交易中预先分配，树配置租金由 `payer` 支付；
+    /// 树的创建者为 Config PDA，因此只有本程序可以向树中铸造压缩收据
+    pub fn init_receipt_tree(
+        ctx: Context<InitReceiptTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let seeds = &[b"config".as_ref(), &[config.bump]];
+        let signer = &[&seeds[..]];
+
+        CreateTreeConfigCpiBuilder::new(&ctx.accounts.bubblegum_program)
+            .tree_config(&ctx.accounts.tree_config)
+            .merkle_tree(&ctx.accounts.merkle_tree)
+            .payer(&ctx.accounts.payer)
+            .tree_creator(&config.to_account_info())
+            .log_wrapper(&ctx.accounts.log_wrapper)
+            .compression_program(&ctx.accounts.compression_program)
+            .system_program(&ctx.accounts.system_program)
+            .max_depth(max_depth)
+            .max_buffer_size(max_buffer_size)
+            .public(false)
+            .invoke_signed(signer)?;
+
+        let config = &mut ctx.accounts.config;
+        config.receipt_tree = ctx.accounts.merkle_tree.key();
+
+        msg!("Receipt tree initialized: {}", config.receipt_tree);
+        msg!("Max depth: {}, max buffer size: {}", max_depth, max_buffer_size);
+
+        Ok(())
+    }
+
+    /// 向提供商签发徽章（仅管理员）
+    ///
+    /// 徽章为 `[b"badge", 提供商, 类型]` PDA，前端直接读取即可展示；再次签发同类型徽章视为续期。
+    /// `expiry` 为 0 表示长期有效；`ProtocolSettings.badge_threshold` 非零时，
+    /// 达到该金额的托管要求提供商持有 `required_badge` 类型的有效徽章
+    pub fn issue_badge(
+        ctx: Context<IssueBadge>,
+        provider: Pubkey,
+        badge_type: u8,
+        expiry: i64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(expiry == 0 || expiry > now, EscrowError::InvalidBadge);
+
+        let badge = &mut ctx.accounts.badge;
+        badge.provider = provider;
+        badge.badge_type = badge_type;
+        badge.issued_at = now;
+        badge.expires_at = expiry;
+        badge.bump = ctx.bumps.badge;
+
+        emit!(BadgeIssued {
+            provider,
+            badge_type,
+            issued_at: now,
+            expires_at: expiry,
+        });
+
+        msg!("Badge issued: {}", badge.key());
+        msg!("Provider: {}, type: {}", provider, badge_type);
+        msg!("Expires at: {}", expiry);
+
+        Ok(())
+    }
+
+    /// 撤销提供商徽章（仅管理员），关闭徽章账户
+    pub fn revoke_badge(ctx: Context<RevokeBadge>) -> Result<()> {
+        let badge = &ctx.accounts.badge;
+
+        emit!(BadgeRevoked {
+            provider: badge.provider,
+            badge_type: badge.badge_type,
+        });
+
+        msg!("Badge revoked: {}", badge.key());
+        msg!("Provider: {}, type: {}", badge.provider, badge.badge_type);
+
+        Ok(())
+    }
+
+    /// 将地址列入黑名单（仅管理员）
+    ///
+    /// 黑名单为 `[b"blacklist", 地址]` PDA，`reason_hash` 为链下处置记录的哈希；
+    /// 列入后该地址不能作为买家或提供商创建托管，作为提供商也不能再标记交付
+    pub fn add_to_blacklist(
+        ctx: Context<AddToBlacklist>,
+        subject: Pubkey,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        let entry = &mut ctx.accounts.blacklist;
+        entry.subject = subject;
+        entry.reason_hash = reason_hash;
+        entry.added_at = now;
+        entry.bump = ctx.bumps.blacklist;
+
+        emit!(Blacklisted {
+            subject,
+            reason_hash,
+            timestamp: now,
+        });
+
+        msg!("Blacklisted: {}", subject);
+
+        Ok(())
+    }
+
+    /// 将地址移出黑名单（仅管理员），关闭黑名单账户
+    pub fn remove_from_blacklist(ctx: Context<RemoveFromBlacklist>) -> Result<()> {
+        let subject = ctx.accounts.blacklist.subject;
+
+        emit!(Unblacklisted {
+            subject,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Removed from blacklist: {}", subject);
+
+        Ok(())
+    }
+
     /// 创建托管账户
     /// 
-    /// 买家调用此指令创建托管，并转入 USDC
+    /// 买家调用此指令记录托管条款，托管处于 Created 状态，随后调用 `fund_escrow` 转入代币；
+    /// 两步可以放在同一交易中，也可以在内部审批后再充值
+    /// 如果传入链上需求和提案 PDA，校验提案属于该需求和该提供商，且金额等于报价
+    /// 附带优惠券时须同时传入优惠码 `coupon_code`，由优惠券程序校验其哈希
     pub fn create_escrow(
         ctx: Context<CreateEscrow>,
         amount: u64,
         request_id: String,
         proposal_id: String,
+        coupon_code: Option<String>,
     ) -> Result<()> {
-        let escrow = &mut ctx.accounts.escrow;
-        
-        // 初始化托管账户
-        escrow.buyer = ctx.accounts.buyer.key();
-        escrow.provider = ctx.accounts.provider.key();
-        escrow.platform = ctx.accounts.platform.key();
+        ctx.accounts
+            .create(&ctx.bumps, amount, request_id, proposal_id, coupon_code)
+    }
+
+    /// 保存可复用的托管条款模板 `[b"template", owner, template_id]`
+    ///
+    /// 买家或平台管理员都可保存模板；平台费减免只能由平台模板携带。
+    /// 带交付期限的模板须指定提供商，并由提供商共同签名认可期限和逾期罚金
+    pub fn save_template(
+        ctx: Context<SaveTemplate>,
+        template_id: u64,
+        terms: TemplateTerms,
+    ) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+
+        match terms.discount {
+            Some(Discount::FeeBps(bps)) => require!(
+                bps > 0 && bps as u64 <= BPS_DENOMINATOR,
+                EscrowError::InvalidTemplate
+            ),
+            Some(Discount::Rebate(rebate)) => require!(rebate > 0, EscrowError::InvalidTemplate),
+            None => {}
+        }
+        require!(
+            terms.discount.is_none() || owner == ctx.accounts.config.admin,
+            EscrowError::OnlyAdmin
+        );
+        require!(terms.delivery_window >= 0, EscrowError::InvalidTemplate);
+        if terms.delivery_window > 0 {
+            let signer = ctx
+                .accounts
+                .provider
+                .as_ref()
+                .ok_or(EscrowError::InvalidTemplate)?;
+            require!(
+                terms.provider == Some(signer.key()),
+                EscrowError::InvalidTemplate
+            );
+        }
+
+        let template = &mut ctx.accounts.template;
+        template.owner = owner;
+        template.template_id = template_id;
+        template.mint = ctx.accounts.mint.key();
+        template.terms = terms;
+        template.bump = ctx.bumps.template;
+
+        msg!("Template saved: {}", template.key());
+        msg!("Mint: {}", template.mint);
+
+        Ok(())
+    }
+
+    /// 删除模板（仅所有者），租金退还所有者；已按模板创建的托管不受影响
+    pub fn close_template(ctx: Context<CloseTemplate>) -> Result<()> {
+        msg!("Template closed: {}", ctx.accounts.template.key());
+
+        Ok(())
+    }
+
+    /// 按模板创建托管，指令数据只需金额、需求 ID、提案 ID 和可选的优惠码
+    ///
+    /// 模板须属于买家或平台；创建时按模板的铸币创建托管代币账户，
+    /// 并写入平台费减免、交付期限（从创建时起算）和里程碑（按件计价，每个里程碑一件）
+    pub fn create_escrow_from_template(
+        ctx: Context<CreateEscrowFromTemplate>,
+        amount: u64,
+        request_id: String,
+        proposal_id: String,
+        coupon_code: Option<String>,
+    ) -> Result<()> {
+        let template = &ctx.accounts.template;
+        require!(
+            template.owner == ctx.accounts.create.buyer.key()
+                || template.owner == ctx.accounts.create.config.admin,
+            EscrowError::InvalidTemplate
+        );
+        if let Some(provider) = template.terms.provider {
+            require_keys_eq!(
+                provider,
+                ctx.accounts.create.provider.key(),
+                EscrowError::InvalidTemplate
+            );
+        }
+
+        ctx.accounts.create.create(
+            &ctx.bumps.create,
+            amount,
+            request_id,
+            proposal_id,
+            coupon_code,
+        )?;
+        ctx.accounts.apply(&ctx.bumps, amount)
+    }
+
+    /// 买家为 Created 状态的托管转入托管金额，托管转为 Funded
+    ///
+    /// 付款账户可以是买家持有或授权给买家的任意同币种代币账户
+    pub fn fund_escrow<'info>(ctx: Context<'_, '_, 'info, 'info, FundEscrow<'info>>) -> Result<()> {
+        ctx.accounts.fund(ctx.remaining_accounts)
+    }
+
+    /// 按已完成的托管再次购买，复制提供商、平台、金额、提案和访问凭证铸币
+    ///
+    /// `request_id` 为新托管的需求 ID（新托管 PDA 的种子），新托管处于 Created 状态等待充值；
+    /// 传入原托管的交付条款时按相同时长从现在起算新的截止时间，逾期罚金照原条款
+    pub fn recreate_escrow(ctx: Context<RecreateEscrow>, request_id: String) -> Result<()> {
+        let previous = *ctx.accounts.previous_escrow.load()?;
+        require!(
+            previous.status() == EscrowStatus::Completed,
+            EscrowError::NotCompleted
+        );
+        require!(previous.amount > 0, EscrowError::InvalidAmount);
+
+        check_escrow_parties(
+            &ctx.accounts.config.settings,
+            &previous.buyer,
+            &previous.provider,
+            previous.amount,
+            ctx.accounts.provider_profile.as_deref(),
+            [&ctx.accounts.buyer_blacklist, &ctx.accounts.provider_blacklist],
+            ctx.accounts.provider_badge.as_deref(),
+            [
+                ctx.accounts.buyer_attestation.as_ref(),
+                ctx.accounts.provider_attestation.as_ref(),
+            ],
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = ctx.accounts.escrow.load_init()?;
+        escrow.buyer = previous.buyer;
+        escrow.provider = previous.provider;
+        escrow.platform = previous.platform;
+        escrow.amount = previous.amount;
+        escrow.set_ids(&request_id, previous.proposal_id())?;
+        escrow.set_status(EscrowStatus::Created);
+        escrow.created_at = now;
+        escrow.access_mint = previous.access_mint;
+        escrow.bump = ctx.bumps.escrow;
+        escrow.version = ESCROW_VERSION;
+        escrow.mint = previous.mint;
+
+        // 交付期限按原托管的时长顺延
+        match (
+            ctx.accounts.previous_delivery_terms.as_ref(),
+            ctx.accounts.delivery_terms.as_mut(),
+        ) {
+            (Some(previous_terms), Some(delivery_terms)) => {
+                let window = previous_terms.deadline.saturating_sub(previous.created_at);
+                delivery_terms.escrow = escrow_key;
+                delivery_terms.deadline = now.checked_add(window.max(0)).unwrap();
+                delivery_terms.bump = ctx.bumps.delivery_terms.unwrap();
+                delivery_terms.late_penalty = previous_terms.late_penalty;
+                delivery_terms.penalty_recipient = previous_terms.penalty_recipient;
+            }
+            (None, None) => {}
+            _ => return err!(EscrowError::InvalidDeliveryDeadline),
+        }
+
+        // 再次购买时统计账户都已存在
+        let bumps = EscrowStatsBumps {
+            buyer_stats: ctx.accounts.buyer_stats.bump,
+            provider_stats: ctx.accounts.provider_stats.bump,
+            provider_escrow_index: ctx.bumps.provider_escrow_index,
+            protocol_stats: ctx.accounts.protocol_stats.bump,
+        };
+        record_escrow_created(
+            &ctx.accounts.config.settings,
+            escrow_key,
+            &escrow,
+            &mut ctx.accounts.buyer_stats,
+            &mut ctx.accounts.provider_stats,
+            &mut ctx.accounts.provider_escrow_index,
+            &mut ctx.accounts.protocol_stats,
+            bumps,
+        )?;
+
+        emit!(EscrowCreated {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            amount: escrow.amount,
+            request_id: escrow.request_id().to_string(),
+            timestamp: now,
+        });
+
+        msg!("Escrow recreated: {}", escrow_key);
+        msg!("Previous escrow: {}", ctx.accounts.previous_escrow.key());
+        msg!("Amount: {} USDC", escrow.amount);
+
+        Ok(())
+    }
+
+    /// 先用 Jupiter 把买家选择的代币兑换为托管代币，再为托管充值
+    ///
+    /// `swap_data` 为 Jupiter 路由指令的数据，其账户按顺序放在 remaining accounts 中，
+    /// 输出账户应为买家的付款代币账户（建议 ExactOut 兑换出托管金额）。
+    /// 兑换与充值在同一指令中完成，兑换后余额不足时整笔交易失败，多兑换的部分留在买家账户
+    pub fn fund_escrow_with_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FundEscrowWithSwap<'info>>,
+        swap_data: Vec<u8>,
+    ) -> Result<()> {
+        let buyer_token_account = &ctx.accounts.fund.buyer_token_account;
+        require_keys_eq!(
+            buyer_token_account.mint,
+            ctx.accounts.fund.escrow_token_account.mint,
+            EscrowError::InvalidSwap
+        );
+        let before = buyer_token_account.amount;
+
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect();
+        invoke(
+            &Instruction {
+                program_id: JUPITER_PROGRAM_ID,
+                accounts,
+                data: swap_data,
+            },
+            ctx.remaining_accounts,
+        )?;
+
+        ctx.accounts.fund.buyer_token_account.reload()?;
+        let received = ctx
+            .accounts
+            .fund
+            .buyer_token_account
+            .amount
+            .saturating_sub(before);
+        require!(received > 0, EscrowError::InvalidSwap);
+        msg!("Swapped into {} escrow tokens", received);
+
+        // remaining accounts 已用于 Jupiter 路由，兑换模式不支持带转账钩子的铸币
+        ctx.accounts.fund.fund(&[])
+    }
+
+    /// 以 Wormhole 跨链转账创建并充值托管
+    ///
+    /// 其他链上的买家通过代币桥向本程序发送附带载荷的转账，载荷为 Borsh 编码的 `BridgePayment`；
+    /// 核心桥验证 VAA 后任何人（通常是中继者）都可调用：CPI 代币桥兑付到赎回者 PDA 的代币账户，
+    /// 再全额转入托管。托管金额为实际到账数量，铸币为代币桥的包装代币（如以太坊 USDC）
+    pub fn create_escrow_from_bridge(
+        ctx: Context<CreateEscrowFromBridge>,
+        request_id: String,
+    ) -> Result<()> {
+        let (source_chain, payment) = read_bridge_payment(&ctx.accounts.posted_vaa)?;
+        require!(
+            payment.buyer == ctx.accounts.buyer.key()
+                && payment.provider == ctx.accounts.provider.key()
+                && payment.platform == ctx.accounts.platform.key()
+                && payment.request_id == request_id,
+            EscrowError::InvalidBridgePayment
+        );
+
+        // 到账数量由代币桥兑付决定，徽章门槛按实际到账数量检查
+        let amount = ctx.accounts.redeem(ctx.bumps.redeemer)?;
+        require!(amount > 0, EscrowError::InvalidAmount);
+
+        check_escrow_parties(
+            &ctx.accounts.config.settings,
+            &payment.buyer,
+            &payment.provider,
+            amount,
+            ctx.accounts.provider_profile.as_deref(),
+            [&ctx.accounts.buyer_blacklist, &ctx.accounts.provider_blacklist],
+            ctx.accounts.provider_badge.as_deref(),
+            [
+                ctx.accounts.buyer_attestation.as_ref(),
+                ctx.accounts.provider_attestation.as_ref(),
+            ],
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = ctx.accounts.escrow.load_init()?;
+        escrow.buyer = payment.buyer;
+        escrow.provider = payment.provider;
+        escrow.platform = payment.platform;
         escrow.amount = amount;
-        escrow.request_id = request_id;
-        escrow.proposal_id = proposal_id;
-        escrow.status = EscrowStatus::Created;
-        escrow.created_at = Clock::get()?.unix_timestamp;
+        escrow.set_ids(&payment.request_id, &payment.proposal_id)?;
+        escrow.set_status(EscrowStatus::Funded);
+        escrow.created_at = now;
+        escrow.funded_at = now;
         escrow.bump = ctx.bumps.escrow;
+        escrow.version = ESCROW_VERSION;
+        escrow.mint = ctx.accounts.wrapped_mint.key();
+
+        record_escrow_created(
+            &ctx.accounts.config.settings,
+            escrow_key,
+            &escrow,
+            &mut ctx.accounts.buyer_stats,
+            &mut ctx.accounts.provider_stats,
+            &mut ctx.accounts.provider_escrow_index,
+            &mut ctx.accounts.protocol_stats,
+            EscrowStatsBumps {
+                buyer_stats: ctx.bumps.buyer_stats,
+                provider_stats: ctx.bumps.provider_stats,
+                provider_escrow_index: ctx.bumps.provider_escrow_index,
+                protocol_stats: ctx.bumps.protocol_stats,
+            },
+        )?;
+
+        // 跨链托管创建即充值，同时计入成交量
+        let buyer_stats = &mut ctx.accounts.buyer_stats;
+        buyer_stats.total_volume = buyer_stats.total_volume.checked_add(amount).unwrap();
+        let protocol_stats = &mut ctx.accounts.protocol_stats;
+        protocol_stats.total_volume = protocol_stats.total_volume.checked_add(amount).unwrap();
+
+        emit!(EscrowCreated {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            amount,
+            request_id: escrow.request_id().to_string(),
+            timestamp: now,
+        });
+        emit!(EscrowFunded {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            amount,
+            timestamp: now,
+        });
+
+        msg!("Escrow created from Wormhole transfer: {}", escrow_key);
+        msg!("Source chain: {}", source_chain);
+        msg!("Amount: {}", amount);
+
+        Ok(())
+    }
+
+    /// 买家按 Pyth 价格记录非 USDC 托管的美元价值
+    ///
+    /// 须在交付前（Created 或 Funded）调用，通常与 `create_escrow` 放在同一交易；
+    /// 记录后 `confirm_and_release` 必须提供同一价格源的新鲜价格，过期则拒绝释放
+    pub fn record_valuation(ctx: Context<RecordValuation>, feed_id: [u8; 32]) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created || escrow.status() == EscrowStatus::Funded,
+            EscrowError::TermsLocked
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let price = read_pyth_price(&ctx.accounts.price_update, &feed_id, now)?;
+        let decimals = ctx.accounts.mint.decimals;
+        let usd_value = usd_value(escrow.amount, decimals, &price)?;
+
+        let valuation = &mut ctx.accounts.valuation;
+        valuation.escrow = ctx.accounts.escrow.key();
+        valuation.mint = ctx.accounts.mint.key();
+        valuation.feed_id = feed_id;
+        valuation.decimals = decimals;
+        valuation.price = price.price;
+        valuation.exponent = price.exponent;
+        valuation.usd_value = usd_value;
+        valuation.recorded_at = now;
+        valuation.release_usd_value = None;
+        valuation.released_at = None;
+        valuation.bump = ctx.bumps.valuation;
+
+        msg!("Escrow valued: {}", ctx.accounts.escrow.key());
+        msg!("Price: {} x 10^{}", price.price, price.exponent);
+        msg!("USD value: {}", usd_value);
+
+        Ok(())
+    }
+
+    /// 把托管改为按美元计价（交付前，须双方签名）
+    ///
+    /// `usd_cents` 为以美分计的成交价，托管中的代币数量作为上限；
+    /// `confirm_and_release` 按新鲜的 Pyth 价格把美元价折算为代币数量支付，多余部分退还买家
+    pub fn denominate_in_usd(
+        ctx: Context<DenominateInUsd>,
+        feed_id: [u8; 32],
+        usd_cents: u64,
+    ) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created || escrow.status() == EscrowStatus::Funded,
+            EscrowError::TermsLocked
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer
+                && ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyBuyerAndProvider
+        );
+        require!(usd_cents > 0, EscrowError::InvalidAmount);
+        require!(
+            ctx.accounts.quote.owner != &crate::ID,
+            EscrowError::QuoteConflictsWithPricing
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let price = read_pyth_price(&ctx.accounts.price_update, &feed_id, now)?;
+        let decimals = ctx.accounts.mint.decimals;
+        let token_amount = usd_token_amount(usd_cents, decimals, &price)?;
+        require!(
+            token_amount <= escrow.amount,
+            EscrowError::UsdPriceNotCovered
+        );
+
+        let usd_pricing = &mut ctx.accounts.usd_pricing;
+        usd_pricing.escrow = ctx.accounts.escrow.key();
+        usd_pricing.feed_id = feed_id;
+        usd_pricing.decimals = decimals;
+        usd_pricing.usd_cents = usd_cents;
+        usd_pricing.price = price.price;
+        usd_pricing.exponent = price.exponent;
+        usd_pricing.recorded_at = now;
+        usd_pricing.released_amount = None;
+        usd_pricing.bump = ctx.bumps.usd_pricing;
+
+        msg!("Escrow priced in USD: {}", ctx.accounts.escrow.key());
+        msg!("Price: {} USD cents", usd_cents);
+        msg!("Token amount at current price: {}", token_amount);
+
+        Ok(())
+    }
+
+    /// 约定以另一种代币（如 USDC）给提供商结算（创建后、注资前，须双方签名）
+    ///
+    /// 结算代币和滑点下限决定提供商的实际所得，须由提供商认可；
+    /// 托管仍持有原代币，`confirm_and_release` 时提供商份额经 Jupiter 兑换为 `settle_mint` 支付；
+    /// `min_out` 为整笔托管金额至少应兑换到的结算代币数量，释放时按提供商份额比例折算为滑点下限
+    pub fn set_settlement_swap(ctx: Context<SetSettlementSwap>, min_out: u64) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created,
+            EscrowError::NotCreated
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer
+                && ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyBuyerAndProvider
+        );
+        require!(min_out > 0, EscrowError::InvalidAmount);
+        require_keys_neq!(
+            ctx.accounts.settle_mint.key(),
+            ctx.accounts.escrow_token_account.mint,
+            EscrowError::InvalidSwap
+        );
+
+        let settlement = &mut ctx.accounts.settlement_swap;
+        settlement.escrow = ctx.accounts.escrow.key();
+        settlement.settle_mint = ctx.accounts.settle_mint.key();
+        settlement.min_out = min_out;
+        settlement.bump = ctx.bumps.settlement_swap;
+
+        msg!("Settlement swap set: {}", ctx.accounts.escrow.key());
+        msg!("Settle mint: {}", settlement.settle_mint);
+        msg!("Minimum out: {}", min_out);
+
+        Ok(())
+    }
+
+    /// 买家要求交付须经预言机证明（交付前）
+    ///
+    /// 之后 `mark_delivered` 前一条指令必须是 Ed25519 签名校验，
+    /// 由 `oracle`（如 Switchboard 函数的签名密钥）对 `托管地址 || 交付哈希` 签名，
+    /// 证明数据端点返回的内容与提交的哈希一致
+    pub fn require_delivery_attestation(
+        ctx: Context<RequireDeliveryAttestation>,
+        oracle: Pubkey,
+    ) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created || escrow.status() == EscrowStatus::Funded,
+            EscrowError::TermsLocked
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+
+        let delivery_oracle = &mut ctx.accounts.delivery_oracle;
+        delivery_oracle.escrow = ctx.accounts.escrow.key();
+        delivery_oracle.oracle = oracle;
+        delivery_oracle.bump = ctx.bumps.delivery_oracle;
+
+        msg!("Delivery attestation required: {}", ctx.accounts.escrow.key());
+        msg!("Oracle: {}", oracle);
+
+        Ok(())
+    }
+
+    /// 买家要求提供商交付加密的数据集解密密钥（交付前，通常与创建托管放在同一交易）
+    ///
+    /// `buyer_key` 为买家的 X25519 公钥，记录在 `[b"key_delivery", escrow]`；
+    /// 之后 `mark_delivered` 要求提供商已通过 `deliver_key` 提交用该公钥加密的密钥
+    pub fn require_key_delivery(
+        ctx: Context<RequireKeyDelivery>,
+        buyer_key: [u8; 32],
+    ) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created || escrow.status() == EscrowStatus::Funded,
+            EscrowError::TermsLocked
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+
+        let key_delivery = &mut ctx.accounts.key_delivery;
+        key_delivery.escrow = ctx.accounts.escrow.key();
+        key_delivery.buyer_key = buyer_key;
+        key_delivery.encrypted_key = Vec::new();
+        key_delivery.delivered_at = None;
+        key_delivery.bump = ctx.bumps.key_delivery;
+
+        msg!("Key delivery required: {}", ctx.accounts.escrow.key());
+
+        Ok(())
+    }
+
+    /// 提供商提交用买家 X25519 公钥加密的数据集解密密钥（交付前，可重复提交覆盖）
+    ///
+    /// 建议使用 sealed box（临时 X25519 密钥 + XSalsa20-Poly1305），只有买家能解密
+    pub fn deliver_key(ctx: Context<DeliverKey>, encrypted_key: Vec<u8>) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Funded || escrow.status() == EscrowStatus::Rejected,
+            EscrowError::NotAwaitingDelivery
+        );
+        require!(
+            ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyProvider
+        );
+        require!(
+            !encrypted_key.is_empty() && encrypted_key.len() <= MAX_ENCRYPTED_KEY_LEN,
+            EscrowError::InvalidEncryptedKey
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let key_delivery = &mut ctx.accounts.key_delivery;
+        key_delivery.encrypted_key = encrypted_key;
+        key_delivery.delivered_at = Some(now);
+
+        emit!(KeyDelivered {
+            escrow: ctx.accounts.escrow.key(),
+            provider: escrow.provider,
+            timestamp: now,
+        });
+
+        msg!("Decryption key delivered: {}", ctx.accounts.escrow.key());
+        msg!("Encrypted key length: {} bytes", key_delivery.encrypted_key.len());
+
+        Ok(())
+    }
+
+    /// 提供商承诺交付内容（交付前）
+    ///
+    /// `commitment` 为 `sha256(存储 URI || salt)`（URI 见 `StorageRef::uri`），记录在 `[b"commitment", escrow]`；
+    /// 之后须在 `mark_delivered` 前用 `reveal_delivery` 揭示存储位置，买家确认时程序核对承诺
+    pub fn commit_delivery(ctx: Context<CommitDelivery>, commitment: [u8; 32]) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Funded,
+            EscrowError::NotFunded
+        );
+        require!(
+            ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyProvider
+        );
+
+        let delivery_commitment = &mut ctx.accounts.delivery_commitment;
+        delivery_commitment.escrow = ctx.accounts.escrow.key();
+        delivery_commitment.commitment = commitment;
+        delivery_commitment.storage = None;
+        delivery_commitment.salt = [0; 32];
+        delivery_commitment.revealed_at = None;
+        delivery_commitment.bump = ctx.bumps.delivery_commitment;
+
+        msg!("Delivery committed: {}", ctx.accounts.escrow.key());
+
+        Ok(())
+    }
+
+    /// 提供商揭示承诺的存储位置和 salt（交付前）
+    ///
+    /// 存储引用按协议校验格式后记录，是否与承诺一致在 `confirm_and_release` 时核对，不一致时买家可据此发起争议
+    pub fn reveal_delivery(
+        ctx: Context<RevealDelivery>,
+        storage: StorageRef,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Funded,
+            EscrowError::NotFunded
+        );
+        require!(
+            ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyProvider
+        );
+        storage.validate()?;
+
+        // Shadow Drive 存储账户须随指令传入，核对确为存储程序所有
+        if let StorageProtocol::ShadowDrive { storage_account } = storage.protocol {
+            let account = ctx
+                .accounts
+                .storage_account
+                .as_ref()
+                .ok_or(EscrowError::InvalidStorageAccount)?;
+            require!(
+                account.key() == storage_account && account.owner == &SHADOW_DRIVE_PROGRAM_ID,
+                EscrowError::InvalidStorageAccount
+            );
+        }
+
+        msg!("Delivery revealed: {}", ctx.accounts.escrow.key());
+        msg!("URI: {}", storage.uri());
+
+        let delivery_commitment = &mut ctx.accounts.delivery_commitment;
+        delivery_commitment.storage = Some(storage);
+        delivery_commitment.salt = salt;
+        delivery_commitment.revealed_at = Some(Clock::get()?.unix_timestamp);
+
+        Ok(())
+    }
+
+    /// 提供商为数据访问凭证设置有效期（交付前，托管须配置访问凭证铸币）
+    ///
+    /// `duration` 为秒数，记录在 `[b"access_window", escrow]`；
+    /// 释放时铸造访问凭证并从释放时刻起算到期时间，到期后任何人可调用 `revoke_expired_access` 冻结买家的凭证账户
+    pub fn set_access_window(ctx: Context<SetAccessWindow>, duration: i64) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created || escrow.status() == EscrowStatus::Funded,
+            EscrowError::TermsLocked
+        );
+        require!(
+            ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyProvider
+        );
+        require!(
+            escrow.access_mint().is_some() && duration > 0,
+            EscrowError::InvalidAccessWindow
+        );
+
+        let access_window = &mut ctx.accounts.access_window;
+        access_window.escrow = ctx.accounts.escrow.key();
+        access_window.duration = duration;
+        access_window.expires_at = None;
+        access_window.token_account = Pubkey::default();
+        access_window.revoked_at = None;
+        access_window.bump = ctx.bumps.access_window;
+
+        msg!("Access window set: {}", ctx.accounts.escrow.key());
+        msg!("Duration: {} seconds", duration);
+
+        Ok(())
+    }
+
+    /// 买家开启收益模式：托管资金存入 Solend 储备生息
+    ///
+    /// 仅在 Funded 状态可开启，托管 PDA 持有储备的抵押代币；
+    /// 储备须在同一交易中先刷新（`RefreshReserve`）
+    pub fn enable_yield(ctx: Context<EnableYield>) -> Result<()> {
+        let escrow = *ctx.accounts.escrow.load()?;
+        require!(
+            escrow.status() == EscrowStatus::Funded,
+            EscrowError::NotFunded
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+
+        let principal = escrow.amount;
+        ctx.accounts.deposit(principal)?;
+        ctx.accounts.collateral_account.reload()?;
+
+        let position = &mut ctx.accounts.yield_position;
+        position.escrow = ctx.accounts.escrow.key();
+        position.reserve = ctx.accounts.reserve.key();
+        position.collateral = ctx.accounts.collateral_account.key();
+        position.principal = principal;
+        position.deposited_at = Clock::get()?.unix_timestamp;
+        position.bump = ctx.bumps.yield_position;
+        ctx.accounts.escrow.load_mut()?.yield_open = 1;
+
+        msg!("Yield enabled: {}", ctx.accounts.escrow.key());
+        msg!("Deposited: {}", principal);
+        msg!("Collateral: {}", ctx.accounts.collateral_account.amount);
+
+        Ok(())
+    }
+
+    /// 赎回收益模式的存款，本金回到托管代币账户，收益按配置分给买家和国库
+    ///
+    /// 买家、提供商或平台均可调用，须在释放、退款或裁决前（可在同一交易中）执行；
+    /// 赎回不足本金时托管金额按亏损扣减，之后的支付以扣减后的金额为准；
+    /// 储备须在同一交易中先刷新
+    pub fn withdraw_yield(ctx: Context<WithdrawYield>) -> Result<()> {
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        let caller = ctx.accounts.caller.key();
+        require!(
+            caller == escrow.buyer || caller == escrow.provider || caller == escrow.platform,
+            EscrowError::OnlyParticipant
+        );
+
+        let before = ctx.accounts.escrow_token_account.amount;
+        let collateral = ctx.accounts.collateral_account.amount;
+        ctx.accounts.redeem(collateral)?;
+        ctx.accounts.escrow_token_account.reload()?;
+
+        let redeemed = ctx.accounts.escrow_token_account.amount.saturating_sub(before);
+        let principal = ctx.accounts.yield_position.principal;
+        let earned = redeemed.saturating_sub(principal);
+        let loss = principal.saturating_sub(redeemed);
+        escrow.amount = escrow.amount.saturating_sub(loss);
+        escrow.yield_open = 0;
+        let buyer_share = (earned as u128)
+            .checked_mul(ctx.accounts.config.settings.yield_buyer_bps as u128)
+            .unwrap()
+            .checked_div(BPS_DENOMINATOR as u128)
+            .unwrap() as u64;
+        let platform_share = earned - buyer_share;
+
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        for (to, amount) in [
+            (ctx.accounts.buyer_token_account.to_account_info(), buyer_share),
+            (ctx.accounts.treasury.to_account_info(), platform_share),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to,
+                authority: ctx.accounts.escrow.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(
+                CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                amount,
+            )?;
+        }
+
+        // 关闭已清空的抵押代币账户，租金退还买家
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.collateral_account.to_account_info(),
+                destination: ctx.accounts.buyer.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        msg!("Yield withdrawn: {}", ctx.accounts.escrow.key());
+        msg!("Principal: {}, earned: {}, loss: {}", principal, earned, loss);
+        msg!("Buyer share: {}, platform share: {}", buyer_share, platform_share);
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        Ok(())
+    }
+
+    /// 提供商标记数据已交付
+    ///
+    /// 记录交付内容的哈希，供收据和争议使用；
+    /// 买家要求预言机证明时，同一交易中须有预言机对该哈希的签名。
+    /// 传入 `chunks` 时同时记录数据块的 Merkle 根，买家可按块发起争议
+    pub fn mark_delivered(
+        ctx: Context<MarkDelivered>,
+        delivery_hash: [u8; 32],
+        chunks: Option<ChunkManifestParams>,
+    ) -> Result<()> {
+        let escrow = *ctx.accounts.escrow.load()?;
+
+        // 买家拒收后在 Rejected 状态重新交付
+        require!(
+            escrow.status() == EscrowStatus::Funded || escrow.status() == EscrowStatus::Rejected,
+            EscrowError::NotAwaitingDelivery
+        );
+
+        require!(
+            ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyProvider
+        );
+        check_not_blacklisted(&ctx.accounts.provider_blacklist)?;
+
+        // 大额托管要求提供商在质押程序中有足额保证金
+        let settings = &ctx.accounts.config.settings;
+        if settings.collateral_threshold > 0 && escrow.amount >= settings.collateral_threshold {
+            let required = (escrow.amount as u128 * settings.collateral_bps as u128
+                / BPS_DENOMINATOR as u128) as u64;
+            let bond = ctx
+                .accounts
+                .provider_bond
+                .as_ref()
+                .ok_or(EscrowError::InsufficientCollateral)?;
+            let collateral = provider_collateral(bond, &escrow.provider)?;
+            require!(collateral >= required, EscrowError::InsufficientCollateral);
+
+            msg!("Collateral: {} USDC (required {})", collateral, required);
+        }
+
+        ctx.accounts.verify_attestation(&delivery_hash)?;
+        ctx.accounts.verify_key_delivery()?;
+        ctx.accounts.verify_revealed()?;
+        ctx.accounts.record_chunks(chunks, ctx.bumps.chunk_manifest)?;
+        let first_delivery = escrow.status() == EscrowStatus::Funded;
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = ctx.accounts.escrow.load_mut()?;
+
+        let delivered_at = Clock::get()?.unix_timestamp;
+        escrow.set_status(EscrowStatus::Delivered);
+        escrow.delivered_at = delivered_at;
+        escrow.delivery_hash = delivery_hash;
+
+        // 累计交付耗时（从充值到交付），拒收后的重新交付不再计入
+        if first_delivery {
+            let funded_at = escrow.funded_at().unwrap_or(escrow.created_at);
+            let provider_stats = &mut ctx.accounts.provider_stats;
+            provider_stats.delivered = provider_stats.delivered.checked_add(1).unwrap();
+            provider_stats.total_delivery_time = provider_stats
+                .total_delivery_time
+                .checked_add(delivered_at.saturating_sub(funded_at).max(0) as u64)
+                .unwrap();
+        }
+
+        emit!(EscrowDelivered {
+            escrow: escrow_key,
+            provider: escrow.provider,
+            delivery_hash,
+            timestamp: delivered_at,
+        });
+
+        msg!("Data delivered for escrow: {}", escrow_key);
+
+        Ok(())
+    }
+
+    /// 买卖双方约定交付截止时间和逾期罚金（交付前，须双方签名）
+    ///
+    /// 记录在 `[b"delivery_terms", escrow]`；逾期交付时 `confirm_and_release` 按
+    /// `ProtocolSettings` 的逾期退还比例自动扣减提供商份额并退还买家，
+    /// 另从提供商份额中扣除固定的 `late_penalty`，按 `penalty_recipient` 付给买家或平台
+    pub fn set_delivery_deadline(
+        ctx: Context<SetDeliveryDeadline>,
+        deadline: i64,
+        late_penalty: u64,
+        penalty_recipient: PenaltyRecipient,
+    ) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created || escrow.status() == EscrowStatus::Funded,
+            EscrowError::TermsLocked
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer
+                && ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyBuyerAndProvider
+        );
+        require!(
+            deadline > Clock::get()?.unix_timestamp,
+            EscrowError::InvalidDeliveryDeadline
+        );
+
+        let delivery_terms = &mut ctx.accounts.delivery_terms;
+        delivery_terms.escrow = ctx.accounts.escrow.key();
+        delivery_terms.deadline = deadline;
+        delivery_terms.bump = ctx.bumps.delivery_terms;
+        delivery_terms.late_penalty = late_penalty;
+        delivery_terms.penalty_recipient = penalty_recipient;
+
+        msg!("Delivery deadline set: {}", ctx.accounts.escrow.key());
+        msg!("Deadline: {}", deadline);
+        msg!("Late penalty: {} to {:?}", late_penalty, penalty_recipient);
+
+        Ok(())
+    }
+
+    /// 提供商对未充值的托管报价并设定有效期（买卖双方共同签名）
+    ///
+    /// 报价只记录在 `[b"quote", escrow]`，`fund_escrow` 按报价金额充值；
+    /// 过期后拒绝充值，双方须重新报价。报价不能高于创建时的托管金额，
+    /// 高额徽章和提案价格都只在创建时按该金额校验过。
+    /// 按件计价、下载计费和美元计价都以托管金额为准，不能与报价同时使用
+    pub fn quote_price(ctx: Context<QuotePrice>, price: u64, expires_at: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let escrow_key = ctx.accounts.escrow.key();
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created,
+            EscrowError::NotCreated
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer
+                && ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyBuyerAndProvider
+        );
+        require!(price > 0, EscrowError::InvalidAmount);
+        require!(price <= escrow.amount, EscrowError::QuoteAboveAmount);
+        require!(expires_at > now, EscrowError::QuoteExpired);
+        for pricing in [
+            &ctx.accounts.units,
+            &ctx.accounts.downloads,
+            &ctx.accounts.usd_pricing,
+        ] {
+            require!(
+                pricing.owner != &crate::ID,
+                EscrowError::QuoteConflictsWithPricing
+            );
+        }
+
+        let quote = &mut ctx.accounts.quote;
+        quote.escrow = escrow_key;
+        quote.price = price;
+        quote.expires_at = expires_at;
+        quote.quoted_at = now;
+        quote.bump = ctx.bumps.quote;
+
+        msg!("Price quoted: {}", escrow_key);
+        msg!("Price: {} USDC, expires at: {}", price, expires_at);
+
+        Ok(())
+    }
+
+    /// 买家把未充值的托管设为按件计价（单价 × 数量）
+    ///
+    /// 单价与数量的乘积须等于托管金额，记录在 `[b"units", escrow]`；
+    /// 充值后提供商通过 `deliver_units` 分批交付，按交付件数释放货款
+    pub fn set_unit_pricing(
+        ctx: Context<SetUnitPricing>,
+        unit_price: u64,
+        quantity: u64,
+    ) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created,
+            EscrowError::NotCreated
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+        require!(
+            unit_price > 0
+                && quantity > 0
+                && unit_price.checked_mul(quantity) == Some(escrow.amount),
+            EscrowError::InvalidUnits
+        );
+        require!(
+            ctx.accounts.quote.owner != &crate::ID,
+            EscrowError::QuoteConflictsWithPricing
+        );
+
+        let units = &mut ctx.accounts.units;
+        units.escrow = ctx.accounts.escrow.key();
+        units.unit_price = unit_price;
+        units.quantity = quantity;
+        units.delivered = 0;
+        units.released = 0;
+        units.bump = ctx.bumps.units;
+
+        msg!("Unit pricing set: {}", ctx.accounts.escrow.key());
+        msg!("Unit price: {} USDC, quantity: {}", unit_price, quantity);
+
+        Ok(())
+    }
+
+    /// 提供商交付 `units` 件数据，买家共同签名确认收货后按件释放货款
+    ///
+    /// 每批货款扣除 5% 平台费后付给提供商，托管金额同步减少，剩余资金仍可按原流程退款或争议；
+    /// 最后一批付清托管余额，托管进入 Completed
+    pub fn deliver_units<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DeliverUnits<'info>>,
+        units: u64,
+    ) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Funded,
+            EscrowError::NotFunded
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer
+                && ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyBuyerAndProvider
+        );
+        check_not_blacklisted(&ctx.accounts.provider_blacklist)?;
+
+        let pricing = &ctx.accounts.units;
+        let delivered = pricing.delivered.checked_add(units).unwrap();
+        require!(
+            units > 0 && delivered <= pricing.quantity,
+            EscrowError::InvalidUnits
+        );
+        let completed = delivered == pricing.quantity;
+
+        // 最后一批付清余额，其余按件数均摊剩余余额：未扣转账手续费时即 `单价 × 件数`，
+        // 带转账手续费的铸币到账较少时各批同比例减少，不会提前耗尽余额
+        let amount = if completed {
+            escrow.amount
+        } else {
+            let undelivered = pricing.quantity - pricing.delivered;
+            (escrow.amount as u128 * units as u128 / undelivered as u128) as u64
+        };
+        let remaining = escrow
+            .amount
+            .checked_sub(amount)
+            .ok_or(EscrowError::InvalidUnits)?;
+        let FeeSplit {
+            platform_fee,
+            provider_amount,
+        } = FeeSplit::new(amount)?;
+
+        msg!("Releasing {} units:", units);
+        msg!("  Provider: {} USDC", provider_amount);
+        msg!("  Platform fee: {} USDC", platform_fee);
+
+        // 生成 PDA 签名种子
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            escrow_key,
+            &escrow,
+        )?;
+
+        if let Some(memo) = &memo {
+            memo.log("provider")?;
+        }
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        transfer_tokens(
+            &cpi_program,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.provider_token_account.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            provider_amount,
+        )?;
+
+        let fee_accrual = FeeAccrual::new(
+            &ctx.accounts.config,
+            ctx.accounts.fee_ledger.as_mut(),
+            ctx.accounts.fee_vault.as_deref(),
+        )?;
+        distribute_platform_fee(
+            &ctx.accounts.config,
+            cpi_program,
+            &ctx.accounts.mint,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts
+                .treasury_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts
+                .rewards_pool_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts
+                .insurance_vault
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            memo.as_ref(),
+            fee_accrual,
+            signer,
+            platform_fee,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        escrow.amount = remaining;
+        let pricing = &mut ctx.accounts.units;
+        pricing.delivered = delivered;
+        pricing.released = pricing.released.checked_add(amount).unwrap();
+
+        let provider_stats = &mut ctx.accounts.provider_stats;
+        provider_stats.sales_volume = provider_stats
+            .sales_volume
+            .checked_add(provider_amount)
+            .unwrap();
+
+        let protocol_stats = &mut ctx.accounts.protocol_stats;
+        protocol_stats.total_fees = protocol_stats.total_fees.checked_add(platform_fee).unwrap();
+
+        emit!(UnitsDelivered {
+            escrow: escrow_key,
+            provider: escrow.provider,
+            units,
+            delivered,
+            quantity: ctx.accounts.units.quantity,
+            provider_amount,
+            platform_fee,
+            timestamp: now,
+        });
+
+        if completed {
+            escrow.set_status(EscrowStatus::Completed);
+            escrow.delivered_at = now;
+            escrow.completed_at = now;
+
+            let buyer_stats = &mut ctx.accounts.buyer_stats;
+            buyer_stats.completed = buyer_stats.completed.checked_add(1).unwrap();
+            buyer_stats.record_settlement()?;
+            provider_stats.completed_sales = provider_stats.completed_sales.checked_add(1).unwrap();
+
+            // 最后一批交付完成整笔交付，与 `mark_delivered` 一样累计交付耗时（从充值到交付）
+            let funded_at = escrow.funded_at().unwrap_or(escrow.created_at);
+            provider_stats.delivered = provider_stats.delivered.checked_add(1).unwrap();
+            provider_stats.total_delivery_time = provider_stats
+                .total_delivery_time
+                .checked_add(now.saturating_sub(funded_at).max(0) as u64)
+                .unwrap();
+
+            // 成交额按各批实际释放的货款累计
+            record_sale(
+                &ctx.accounts.sales_history,
+                &ctx.accounts.restriction,
+                escrow_key,
+                ctx.accounts.units.released,
+                now,
+            )?;
+
+            msg!("Escrow completed: {}", escrow_key);
+        }
+
+        msg!("Delivered {}/{} units", delivered, ctx.accounts.units.quantity);
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        Ok(())
+    }
+
+    /// 买家把未充值的托管设为按下载次数计费，提供商共同签名指定网关
+    ///
+    /// 下载上限与网关记录在 `[b"downloads", escrow]`；充值后网关每次提供下载时
+    /// CPI 调用 `consume_download`（网关可以是其程序的 PDA），按剩余次数均摊释放货款
+    pub fn set_download_cap(
+        ctx: Context<SetDownloadCap>,
+        downloads: u64,
+        gateway: Pubkey,
+    ) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created,
+            EscrowError::NotCreated
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer
+                && ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyBuyerAndProvider
+        );
+        require!(downloads > 0, EscrowError::InvalidDownloads);
+        require!(
+            ctx.accounts.quote.owner != &crate::ID,
+            EscrowError::QuoteConflictsWithPricing
+        );
+
+        let record = &mut ctx.accounts.downloads;
+        record.escrow = ctx.accounts.escrow.key();
+        record.gateway = gateway;
+        record.total = downloads;
+        record.downloads_remaining = downloads;
+        record.bump = ctx.bumps.downloads;
+        record.released = 0;
+
+        msg!("Download cap set: {}", ctx.accounts.escrow.key());
+        msg!("Downloads: {}, gateway: {}", downloads, gateway);
+
+        Ok(())
+    }
+
+    /// 网关记录一次下载，释放托管余额中按剩余次数均摊的一份
+    ///
+    /// 每份扣除 5% 平台费后付给提供商，剩余次数减一；最后一次下载付清托管余额，托管进入 Completed
+    pub fn consume_download<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConsumeDownload<'info>>,
+    ) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Funded,
+            EscrowError::NotFunded
+        );
+        require!(
+            ctx.accounts.gateway.key() == ctx.accounts.downloads.gateway,
+            EscrowError::OnlyGateway
+        );
+        check_not_blacklisted(&ctx.accounts.provider_blacklist)?;
+
+        let downloads_remaining = ctx.accounts.downloads.downloads_remaining;
+        require!(downloads_remaining > 0, EscrowError::InvalidDownloads);
+        let completed = downloads_remaining == 1;
+
+        let amount = escrow.amount.checked_div(downloads_remaining).unwrap();
+        let amount = if completed { escrow.amount } else { amount };
+        let FeeSplit {
+            platform_fee,
+            provider_amount,
+        } = FeeSplit::new(amount)?;
+
+        let total = ctx.accounts.downloads.total;
+        msg!("Releasing download {}/{}:", total - downloads_remaining + 1, total);
+        msg!("  Provider: {} USDC", provider_amount);
+        msg!("  Platform fee: {} USDC", platform_fee);
+
+        // 生成 PDA 签名种子
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            escrow_key,
+            &escrow,
+        )?;
+
+        if let Some(memo) = &memo {
+            memo.log("provider")?;
+        }
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        transfer_tokens(
+            &cpi_program,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.provider_token_account.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            provider_amount,
+        )?;
+
+        let fee_accrual = FeeAccrual::new(
+            &ctx.accounts.config,
+            ctx.accounts.fee_ledger.as_mut(),
+            ctx.accounts.fee_vault.as_deref(),
+        )?;
+        distribute_platform_fee(
+            &ctx.accounts.config,
+            cpi_program,
+            &ctx.accounts.mint,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts
+                .treasury_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts
+                .rewards_pool_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts
+                .insurance_vault
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            memo.as_ref(),
+            fee_accrual,
+            signer,
+            platform_fee,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        escrow.amount = escrow.amount.checked_sub(amount).unwrap();
+        let downloads_remaining = downloads_remaining - 1;
+        let downloads = &mut ctx.accounts.downloads;
+        downloads.downloads_remaining = downloads_remaining;
+        downloads.released = downloads.released.checked_add(amount).unwrap();
+
+        let provider_stats = &mut ctx.accounts.provider_stats;
+        provider_stats.sales_volume = provider_stats
+            .sales_volume
+            .checked_add(provider_amount)
+            .unwrap();
+
+        let protocol_stats = &mut ctx.accounts.protocol_stats;
+        protocol_stats.total_fees = protocol_stats.total_fees.checked_add(platform_fee).unwrap();
+
+        emit!(DownloadConsumed {
+            escrow: escrow_key,
+            provider: escrow.provider,
+            downloads_remaining,
+            provider_amount,
+            platform_fee,
+            timestamp: now,
+        });
+
+        if completed {
+            escrow.set_status(EscrowStatus::Completed);
+            escrow.delivered_at = now;
+            escrow.completed_at = now;
+
+            let buyer_stats = &mut ctx.accounts.buyer_stats;
+            buyer_stats.completed = buyer_stats.completed.checked_add(1).unwrap();
+            buyer_stats.record_settlement()?;
+            provider_stats.completed_sales = provider_stats.completed_sales.checked_add(1).unwrap();
+
+            // 成交额按各次实际释放的货款累计
+            record_sale(
+                &ctx.accounts.sales_history,
+                &ctx.accounts.restriction,
+                escrow_key,
+                ctx.accounts.downloads.released,
+                now,
+            )?;
+
+            msg!("Escrow completed: {}", escrow_key);
+        }
+
+        msg!("Downloads remaining: {}", downloads_remaining);
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        Ok(())
+    }
+
+    /// 提供商开启可验证销售记录，此后每笔完成的销售追加到 `[b"sales_history", provider]`
+    ///
+    /// 开启前完成的销售不会补记
+    pub fn init_sales_history(ctx: Context<InitSalesHistory>) -> Result<()> {
+        let history = &mut ctx.accounts.sales_history;
+        history.provider = ctx.accounts.provider.key();
+        history.count = 0;
+        history.root = SalesHistory::empty_root();
+        history.frontier = [[0; 32]; SALES_TREE_DEPTH];
+        history.bump = ctx.bumps.sales_history;
+
+        msg!("Sales history opened: {}", history.key());
+
+        Ok(())
+    }
+
+    /// 买家追加提前交付奖励金（交付前）
+    ///
+    /// 奖励金存入 `[b"bonus_vault", escrow]` 代币账户，与托管金额分开保管；
+    /// 在 `deadline` 前交付时随 `confirm_and_release` 一起付给提供商，否则退还买家
+    pub fn fund_bonus<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FundBonus<'info>>,
+        amount: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created || escrow.status() == EscrowStatus::Funded,
+            EscrowError::TermsLocked
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+        require!(
+            amount > 0 && deadline > Clock::get()?.unix_timestamp,
+            EscrowError::InvalidBonus
+        );
+
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.buyer_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.bonus_vault.to_account_info(),
+            ctx.accounts.buyer.to_account_info(),
+            ctx.remaining_accounts,
+            &[],
+            amount,
+        )?;
+
+        let bonus = &mut ctx.accounts.bonus;
+        bonus.escrow = ctx.accounts.escrow.key();
+        bonus.amount = amount;
+        bonus.deadline = deadline;
+        bonus.settled_at = None;
+        bonus.bump = ctx.bumps.bonus;
+
+        msg!("Bonus funded: {}", ctx.accounts.escrow.key());
+        msg!("Amount: {}", amount);
+        msg!("Deadline: {}", deadline);
+
+        Ok(())
+    }
+
+    /// 托管未经买家确认就结束时（退款、取消或裁决），把未结算的奖励金退还买家（任何人可调用）
+    pub fn reclaim_bonus<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReclaimBonus<'info>>,
+    ) -> Result<()> {
+        let escrow = *ctx.accounts.escrow.load()?;
+        let amount = ctx.accounts.bonus.amount;
+
+        require!(escrow.status().is_terminal(), EscrowError::BonusLocked);
+        require!(amount > 0, EscrowError::BonusLocked);
+
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            ctx.accounts.escrow.key(),
+            &escrow,
+        )?;
+
+        if let Some(memo) = &memo {
+            memo.log("refund")?;
+        }
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.bonus_vault.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.buyer_token_account.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            amount,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let bonus = &mut ctx.accounts.bonus;
+        bonus.amount = 0;
+        bonus.settled_at = Some(now);
+
+        emit!(BonusSettled {
+            escrow: ctx.accounts.escrow.key(),
+            recipient: escrow.buyer,
+            amount,
+            earned: false,
+            timestamp: now,
+        });
+
+        msg!("Bonus returned to buyer: {}", ctx.accounts.escrow.key());
+        msg!("Amount: {}", amount);
+
+        Ok(())
+    }
+
+    /// 买家拒收交付，托管进入 Rejected 状态
+    ///
+    /// 提供商须在 `REDELIVERY_WINDOW` 内修正后再次 `mark_delivered`，
+    /// 逾期任何人可调用 `expire_rejection` 退款给买家；
+    /// 与争议不同，拒收只在买卖双方之间处理，不需要平台介入；
+    /// `reason_hash` 为拒收说明的哈希（说明本身在链下传递），
+    /// 拒收次数记录在 `[b"rejection", escrow]`，不超过 `MAX_REJECTIONS`；
+    /// 抽查未响应时不能拒收，数据集属性证明须针对新交付重新提交
+    pub fn reject_delivery(ctx: Context<RejectDelivery>, reason_hash: [u8; 32]) -> Result<()> {
+        let escrow = *ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Delivered,
+            EscrowError::NotDelivered
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+        require!(
+            ctx.accounts.rejection.count < MAX_REJECTIONS,
+            EscrowError::TooManyRejections
+        );
+
+        ctx.accounts.reset_delivery_checks()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = ctx.accounts.escrow.load_mut()?;
+
+        escrow.set_status(EscrowStatus::Rejected);
+        escrow.delivered_at = 0;
+        escrow.delivery_hash = [0; 32];
+
+        let deadline = now.checked_add(REDELIVERY_WINDOW).unwrap();
+        let rejection = &mut ctx.accounts.rejection;
+        rejection.escrow = escrow_key;
+        rejection.count = rejection.count.checked_add(1).unwrap();
+        rejection.reason_hash = reason_hash;
+        rejection.rejected_at = now;
+        rejection.deadline = deadline;
+        rejection.bump = ctx.bumps.rejection;
+
+        emit!(DeliveryRejected {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            reason_hash,
+            rejections: rejection.count,
+            deadline,
+            timestamp: now,
+        });
+
+        msg!("Delivery rejected: {}", escrow_key);
+        msg!("Rejections: {}/{}", rejection.count, MAX_REJECTIONS);
+        msg!("Redelivery deadline: {}", deadline);
+
+        Ok(())
+    }
+
+    /// 拒收后提供商逾期未重新交付，全额退款给买家（任何人可调用）
+    pub fn expire_rejection<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExpireRejection<'info>>,
+    ) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Rejected,
+            EscrowError::NotRejected
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= ctx.accounts.rejection.deadline,
+            EscrowError::RedeliveryOpen
+        );
+
+        let amount = escrow.amount;
+
+        // 生成 PDA 签名种子
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            escrow_key,
+            &escrow,
+        )?;
+
+        // 退款给买家
+        if let Some(memo) = &memo {
+            memo.log("refund")?;
+        }
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.buyer_token_account.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            amount,
+        )?;
+
+        escrow.set_status(EscrowStatus::Refunded);
+        escrow.refunded_at = now;
+        ctx.accounts.buyer_stats.record_settlement()?;
+
+        let provider_stats = &mut ctx.accounts.provider_stats;
+        provider_stats.refunds = provider_stats.refunds.checked_add(1).unwrap();
+        provider_stats.refunded_volume = provider_stats.refunded_volume.checked_add(amount).unwrap();
+
+        emit!(EscrowRefunded {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            amount,
+            timestamp: now,
+        });
+
+        msg!("Redelivery expired, escrow refunded: {}", escrow_key);
+        msg!("Amount: {} USDC", amount);
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        Ok(())
+    }
+
+    /// 买家要求提供商用零知识证明证实数据集属性（交付前）
+    ///
+    /// `verifier` 为买家信任的 Groth16/Plonk 验证程序，`row_count` 和 `schema_hash` 为挂单声明的行数和表结构哈希，
+    /// 记录在 `[b"dataset_proof", escrow]`；交付后提供商通过 `submit_dataset_proof` 提交证明
+    pub fn require_dataset_proof(
+        ctx: Context<RequireDatasetProof>,
+        verifier: Pubkey,
+        row_count: u64,
+        schema_hash: [u8; 32],
+    ) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Created || escrow.status() == EscrowStatus::Funded,
+            EscrowError::TermsLocked
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+
+        let dataset_proof = &mut ctx.accounts.dataset_proof;
+        dataset_proof.escrow = ctx.accounts.escrow.key();
+        dataset_proof.verifier = verifier;
+        dataset_proof.row_count = row_count;
+        dataset_proof.schema_hash = schema_hash;
+        dataset_proof.verified_at = None;
+        dataset_proof.bump = ctx.bumps.dataset_proof;
+
+        msg!("Dataset proof required: {}", ctx.accounts.escrow.key());
+        msg!("Verifier: {}", verifier);
+
+        Ok(())
+    }
+
+    /// 提供商提交数据集属性的零知识证明（交付后、买家确认前）
+    ///
+    /// 通过 CPI 调用买家指定的验证程序，公开输入依次为交付哈希、表结构哈希和行数（各 32 字节大端），
+    /// 证明因此绑定到本次交付的内容；验证程序拒绝时整笔交易失败
+    pub fn submit_dataset_proof(ctx: Context<SubmitDatasetProof>, proof: Vec<u8>) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Delivered,
+            EscrowError::NotDelivered
+        );
+        require!(
+            ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyProvider
+        );
+
+        let dataset_proof = &mut ctx.accounts.dataset_proof;
+        require!(
+            dataset_proof.verified_at.is_none(),
+            EscrowError::DatasetProofVerified
+        );
+
+        let mut data = ZK_VERIFY_DISCRIMINATOR.to_vec();
+        (proof, dataset_proof.public_inputs(&escrow.delivery_hash)).serialize(&mut data)?;
+        invoke(
+            &Instruction {
+                program_id: dataset_proof.verifier,
+                accounts: vec![],
+                data,
+            },
+            &[ctx.accounts.verifier.to_account_info()],
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        dataset_proof.verified_at = Some(now);
+
+        emit!(DatasetProofVerified {
+            escrow: ctx.accounts.escrow.key(),
+            verifier: dataset_proof.verifier,
+            row_count: dataset_proof.row_count,
+            schema_hash: dataset_proof.schema_hash,
+            timestamp: now,
+        });
+
+        msg!("Dataset proof verified: {}", ctx.accounts.escrow.key());
+        msg!("Row count: {}", dataset_proof.row_count);
+
+        Ok(())
+    }
+
+    /// 买家抽查交付的数据块（确认前）
+    ///
+    /// 买家随机选取至多 `MAX_CHALLENGE_CHUNKS` 个块序号，记录在 `[b"challenge", escrow]`；
+    /// 提供商须在 `CHALLENGE_WINDOW` 内用 `respond_challenge` 提交这些块的哈希和 Merkle 证明，
+    /// 逾期未响应时任何人都可以调用 `expire_challenge` 全额退款给买家。每个托管只能抽查一次
+    pub fn challenge(ctx: Context<ChallengeChunks>, indices: Vec<u32>) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Delivered,
+            EscrowError::NotDelivered
+        );
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+
+        let chunk_count = ctx.accounts.chunk_manifest.chunk_count;
+        require!(
+            !indices.is_empty() && indices.len() <= MAX_CHALLENGE_CHUNKS,
+            EscrowError::InvalidChallenge
+        );
+        for (i, index) in indices.iter().enumerate() {
+            require!(
+                *index < chunk_count && !indices[..i].contains(index),
+                EscrowError::InvalidChallenge
+            );
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let challenge = &mut ctx.accounts.challenge;
+        challenge.escrow = ctx.accounts.escrow.key();
+        challenge.indices = indices;
+        challenge.deadline = now.checked_add(CHALLENGE_WINDOW).unwrap();
+        challenge.responded_at = None;
+        challenge.bump = ctx.bumps.challenge;
+
+        emit!(ChallengeIssued {
+            escrow: ctx.accounts.escrow.key(),
+            indices: challenge.indices.clone(),
+            deadline: challenge.deadline,
+            timestamp: now,
+        });
+
+        msg!("Chunks challenged: {:?}", challenge.indices);
+        msg!("Response deadline: {}", challenge.deadline);
+
+        Ok(())
+    }
+
+    /// 提供商在期限内响应抽查
+    ///
+    /// `proofs` 须按抽查顺序逐一对应，每块的 Merkle 证明须能推出交付清单的根；
+    /// 块哈希写入事件，买家据此核对下载的数据
+    pub fn respond_challenge(ctx: Context<RespondChallenge>, proofs: Vec<ChunkProof>) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Delivered,
+            EscrowError::NotDelivered
+        );
+        require!(
+            ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyProvider
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let challenge = &mut ctx.accounts.challenge;
+        require!(challenge.responded_at.is_none(), EscrowError::ChallengeClosed);
+        require!(now < challenge.deadline, EscrowError::DeadlinePassed);
+
+        let manifest = &ctx.accounts.chunk_manifest;
+        require!(
+            proofs.len() == challenge.indices.len(),
+            EscrowError::InvalidChunkProof
+        );
+        for (proof, index) in proofs.iter().zip(&challenge.indices) {
+            require!(
+                proof.index == *index && manifest.verify(proof),
+                EscrowError::InvalidChunkProof
+            );
+        }
+
+        challenge.responded_at = Some(now);
+
+        emit!(ChallengeAnswered {
+            escrow: ctx.accounts.escrow.key(),
+            chunk_hashes: proofs.iter().map(|proof| proof.chunk_hash).collect(),
+            timestamp: now,
+        });
+
+        msg!("Challenge answered: {}", ctx.accounts.escrow.key());
+
+        Ok(())
+    }
+
+    /// 抽查逾期未响应，全额退款给买家（任何人可调用）
+    pub fn expire_challenge<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExpireChallenge<'info>>,
+    ) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+        let challenge = &ctx.accounts.challenge;
+
+        // 买家已发起争议的托管按争议流程处理
+        require!(
+            escrow.status() == EscrowStatus::Delivered,
+            EscrowError::NotDelivered
+        );
+        require!(
+            challenge.responded_at.is_none(),
+            EscrowError::ChallengeClosed
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= challenge.deadline, EscrowError::ChallengeOpen);
+
+        let amount = escrow.amount;
+
+        // 生成 PDA 签名种子
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            escrow_key,
+            &escrow,
+        )?;
+
+        // 退款给买家
+        if let Some(memo) = &memo {
+            memo.log("refund")?;
+        }
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.buyer_token_account.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            amount,
+        )?;
+
+        escrow.set_status(EscrowStatus::Refunded);
+        escrow.refunded_at = now;
+        ctx.accounts.buyer_stats.record_settlement()?;
+
+        let provider_stats = &mut ctx.accounts.provider_stats;
+        provider_stats.refunds = provider_stats.refunds.checked_add(1).unwrap();
+        provider_stats.refunded_volume = provider_stats.refunded_volume.checked_add(amount).unwrap();
+
+        emit!(EscrowRefunded {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            amount,
+            timestamp: now,
+        });
+
+        msg!("Challenge expired, escrow refunded: {}", escrow_key);
+        msg!("Amount: {} USDC", amount);
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        Ok(())
+    }
+
+    /// 买家确认交付并释放资金
+    /// 
+    /// 自动分配：95% 给提供商，5% 平台费按配置分给国库和奖励池
+    /// 如果传入了收据账户，同时给买家铸造一枚收据 NFT；
+    /// 如果传入了 Merkle 树账户，则铸造压缩收据（cNFT）；
+    /// 传入 `quality_score`（1–5）时通过信誉程序记录买家评分，每个托管只能评分一次；
+    /// 传入 `tip_amount` 时从买家代币账户直接给提供商小费，不收平台费；
+    /// 约定了兑换结算的托管须传入 `swap_data`（Jupiter 路由指令数据，路由账户在 remaining accounts），
+    /// 提供商份额经 Jupiter 兑换为结算代币后支付，此时不支持带转账钩子的铸币
+    pub fn confirm_and_release<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConfirmAndRelease<'info>>,
+        quality_score: Option<u8>,
+        tip_amount: Option<u64>,
+        swap_data: Option<Vec<u8>>,
+    ) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Delivered,
+            EscrowError::NotDelivered
+        );
+
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyerCanConfirm
+        );
+
+        // 兑换结算时 remaining accounts 属于 Jupiter 路由
+        let settlement_swap = ctx.accounts.settlement_swap()?;
+        require!(
+            settlement_swap.is_some() == swap_data.is_some(),
+            EscrowError::InvalidSwap
+        );
+        let hook_accounts = if swap_data.is_some() {
+            &[]
+        } else {
+            ctx.remaining_accounts
+        };
+
+        ctx.accounts.revalue()?;
+        let usd_amount = usd_release_amount(
+            &ctx.accounts.usd_pricing,
+            ctx.accounts.price_update.as_ref(),
+            &ctx.accounts.config.settings,
+            escrow.amount,
+        )?;
+        ctx.accounts.verify_commitment()?;
+        let tip = tip_amount.unwrap_or_default();
+        ctx.accounts.send_tip(tip, hook_accounts)?;
+        let delivery_terms = ctx.accounts.delivery_terms()?;
+        let discount = escrow_discount(&ctx.accounts.discount)?;
+        let delivered_at = escrow.delivered_at().unwrap_or_default();
+
+        // 可选：释放时买家和/或提供商的合规证明仍须有效
+        let settings = &ctx.accounts.config.settings;
+        check_compliance(
+            settings,
+            COMPLIANCE_BUYER,
+            ctx.accounts.buyer_attestation.as_ref(),
+            &escrow.buyer,
+        )?;
+        check_compliance(
+            settings,
+            COMPLIANCE_PROVIDER,
+            ctx.accounts.provider_attestation.as_ref(),
+            &escrow.provider,
+        )?;
+
+        // 计算分配金额；美元计价时只支付折算的代币数量，其余退还买家
+        let escrowed_amount = escrow.amount;
+        let total_amount = usd_amount.unwrap_or(escrowed_amount);
+        let usd_refund = escrowed_amount.checked_sub(total_amount).unwrap();
+        let FeeSplit {
+            platform_fee,
+            provider_amount: provider_share,
+        } = FeeSplit::new(total_amount)?;
+        // 优惠券：从平台费中减免，减免部分退还买家
+        let coupon_rebate = discount.map_or(0, |discount| discount.amount(platform_fee));
+        let platform_fee = platform_fee.checked_sub(coupon_rebate).unwrap();
+        // 逾期交付：按逾期天数从提供商份额中扣减退还买家，再扣除约定的固定罚金
+        let (days_late, late_refund, late_penalty) = match &delivery_terms {
+            Some(terms) => {
+                let bps = terms.late_refund_bps(delivered_at, &ctx.accounts.config.settings);
+                let late_refund =
+                    (provider_share as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64;
+                let remaining = provider_share.checked_sub(late_refund).unwrap();
+                let late_penalty = terms.late_penalty(delivered_at, remaining);
+                (terms.days_late(delivered_at), late_refund, late_penalty)
+            }
+            None => (0, 0, 0),
+        };
+        let penalty_recipient =
+            delivery_terms.map_or(PenaltyRecipient::Buyer, |terms| terms.penalty_recipient);
+        let (buyer_penalty, platform_penalty) = match penalty_recipient {
+            PenaltyRecipient::Buyer => (late_penalty, 0),
+            PenaltyRecipient::Platform => (0, late_penalty),
+        };
+        let provider_amount = provider_share
+            .checked_sub(late_refund)
+            .unwrap()
+            .checked_sub(late_penalty)
+            .unwrap();
+        // 各笔去向之和必须等于托管金额，不留零头
+        require!(
+            [usd_refund, coupon_rebate, late_refund, late_penalty, platform_fee]
+                .iter()
+                .try_fold(provider_amount, |sum, part| sum.checked_add(*part))
+                == Some(escrowed_amount),
+            EscrowError::FeeSplitMismatch
+        );
+
+        msg!("Releasing funds:");
+        msg!("  Total: {} USDC", total_amount);
+        msg!("  Provider: {} USDC", provider_amount);
+        msg!("  Platform fee: {} USDC", platform_fee);
+        if late_refund > 0 {
+            msg!("  Late refund ({} days): {} USDC", days_late, late_refund);
+        }
+        if late_penalty > 0 {
+            msg!("  Late penalty ({:?}): {} USDC", penalty_recipient, late_penalty);
+        }
+        if coupon_rebate > 0 {
+            msg!("  Coupon rebate: {} USDC", coupon_rebate);
+        }
+        if usd_refund > 0 {
+            msg!("  USD pricing refund: {}", usd_refund);
+        }
+
+        // 生成 PDA 签名种子
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            escrow_key,
+            &escrow,
+        )?;
+
+        // 转账提供商份额，约定了兑换结算时兑换为结算代币后支付
+        if let Some(memo) = &memo {
+            memo.log("provider")?;
+        }
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        match (settlement_swap, swap_data) {
+            (Some(settlement), Some(swap_data)) => {
+                let provider_settle_token_account = ctx
+                    .accounts
+                    .provider_settle_token_account
+                    .as_mut()
+                    .ok_or(EscrowError::InvalidSwap)?;
+                require!(
+                    ctx.accounts.jupiter_program.is_some()
+                        && provider_settle_token_account.mint == settlement.settle_mint,
+                    EscrowError::InvalidSwap
+                );
+                // 最少兑换所得按托管金额约定，提供商份额只是其中一部分时按比例折算
+                let min_out = (settlement.min_out as u128 * provider_amount as u128
+                    / escrowed_amount as u128) as u64;
+                let received = swap_to_provider(
+                    swap_data,
+                    provider_amount,
+                    min_out,
+                    escrow_key,
+                    &mut ctx.accounts.escrow_token_account,
+                    provider_settle_token_account,
+                    ctx.remaining_accounts,
+                    signer,
+                )?;
+
+                emit!(SettlementSwapped {
+                    escrow: escrow_key,
+                    provider: escrow.provider,
+                    settle_mint: settlement.settle_mint,
+                    amount_in: provider_amount,
+                    amount_out: received,
+                    timestamp: Clock::get()?.unix_timestamp,
+                });
+            }
+            _ => transfer_tokens(
+                &cpi_program,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                ctx.accounts.provider_token_account.to_account_info(),
+                ctx.accounts.escrow.to_account_info(),
+                hook_accounts,
+                signer,
+                provider_amount,
+            )?,
+        }
+
+        let buyer_amount = late_refund
+            .checked_add(buyer_penalty)
+            .unwrap()
+            .checked_add(coupon_rebate)
+            .unwrap()
+            .checked_add(usd_refund)
+            .unwrap();
+        if buyer_amount > 0 {
+            let buyer_token_account = ctx
+                .accounts
+                .buyer_token_account
+                .as_ref()
+                .ok_or(EscrowError::MissingBuyerTokenAccount)?;
+            if let Some(memo) = &memo {
+                memo.log("refund")?;
+            }
+            transfer_tokens(
+                &cpi_program,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                buyer_token_account.to_account_info(),
+                ctx.accounts.escrow.to_account_info(),
+                hook_accounts,
+                signer,
+                buyer_amount,
+            )?;
+        }
+        if platform_penalty > 0 {
+            let treasury_token_account = ctx
+                .accounts
+                .treasury_token_account
+                .as_ref()
+                .ok_or(EscrowError::MissingPlatformTokenAccount)?;
+
+            if let Some(memo) = &memo {
+                memo.log("platform")?;
+            }
+            transfer_tokens(
+                &cpi_program,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                treasury_token_account.to_account_info(),
+                ctx.accounts.escrow.to_account_info(),
+                hook_accounts,
+                signer,
+                platform_penalty,
+            )?;
+        }
+
+        if late_refund > 0 {
+            emit!(LateDeliveryRefunded {
+                escrow: escrow_key,
+                buyer: escrow.buyer,
+                days_late,
+                amount: late_refund,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+        if late_penalty > 0 {
+            emit!(LateDeliveryPenalized {
+                escrow: escrow_key,
+                provider: escrow.provider,
+                recipient: penalty_recipient,
+                amount: late_penalty,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        // 平台费（5%）分给国库和奖励池
+        let fee_accrual = FeeAccrual::new(
+            &ctx.accounts.config,
+            ctx.accounts.fee_ledger.as_mut(),
+            ctx.accounts.fee_vault.as_deref(),
+        )?;
+        distribute_platform_fee(
+            &ctx.accounts.config,
+            cpi_program,
+            &ctx.accounts.mint,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts
+                .treasury_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts
+                .rewards_pool_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts
+                .insurance_vault
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts.escrow.to_account_info(),
+            hook_accounts,
+            memo.as_ref(),
+            fee_accrual,
+            signer,
+            platform_fee,
+        )?;
+
+        escrow.set_status(EscrowStatus::Completed);
+        escrow.completed_at = Clock::get()?.unix_timestamp;
+        record_sale(
+            &ctx.accounts.sales_history,
+            &ctx.accounts.restriction,
+            escrow_key,
+            total_amount,
+            escrow.completed_at().unwrap_or_default(),
+        )?;
+
+        let buyer_stats = &mut ctx.accounts.buyer_stats;
+        buyer_stats.completed = buyer_stats.completed.checked_add(1).unwrap();
+        buyer_stats.record_settlement()?;
+
+        let provider_stats = &mut ctx.accounts.provider_stats;
+        provider_stats.completed_sales = provider_stats.completed_sales.checked_add(1).unwrap();
+        provider_stats.sales_volume = provider_stats
+            .sales_volume
+            .checked_add(provider_amount)
+            .unwrap();
+
+        let protocol_stats = &mut ctx.accounts.protocol_stats;
+        protocol_stats.total_fees = protocol_stats.total_fees.checked_add(platform_fee).unwrap();
+
+        emit!(EscrowReleased {
+            escrow: escrow_key,
+            provider: escrow.provider,
+            provider_amount,
+            platform_fee,
+            tip,
+            timestamp: escrow.completed_at().unwrap_or_default(),
+        });
+
+        msg!("Escrow completed: {}", escrow_key);
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        ctx.accounts.rate_provider(quality_score)?;
+        ctx.accounts.settle_bonus(memo.as_ref(), hook_accounts)?;
+        ctx.accounts.record_receipt(total_amount, ctx.bumps.receipt_record)?;
+        ctx.accounts.mint_receipt()?;
+        ctx.accounts.mint_compressed_receipt()?;
+
+        mint_access_token(
+            &escrow,
+            &ctx.accounts.access_mint,
+            &ctx.accounts.buyer_access_account,
+            &ctx.accounts.access_authority,
+            ctx.bumps.access_authority,
+            &ctx.accounts.access_token_program,
+            &ctx.accounts.access_window,
+        )?;
+
+        Ok(())
+    }
+
+    /// 退款给买家（仅平台可调用，用于争议解决）
+    pub fn refund<'info>(ctx: Context<'_, '_, 'info, 'info, Refund<'info>>) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+
+        // 只允许在 Disputed 状态退款
+        require!(
+            escrow.status() == EscrowStatus::Disputed,
+            EscrowError::NotDisputed
+        );
+
+        // 只有平台可以发起退款（仲裁后）
+        require!(
+            ctx.accounts.authority.key() == escrow.platform,
+            EscrowError::OnlyArbitratorCanRefund
+        );
+
+        // 设置了上诉期时平台只能先作出可上诉的裁决
+        require!(
+            ctx.accounts.config.settings.appeal_window == 0,
+            EscrowError::RulingRequired
+        );
+
+        let amount = escrow.amount;
+        // 争议记录登记的保证金退款时一并退还买家
+        let bond = dispute_bond(&ctx.accounts.dispute_record)?;
+
+        // 生成 PDA 签名种子
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            escrow_key,
+            &escrow,
+        )?;
+
+        // 退款给买家
+        if let Some(memo) = &memo {
+            memo.log("refund")?;
+        }
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.buyer_token_account.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            amount.checked_add(bond).unwrap(),
+        )?;
+
+        escrow.set_status(EscrowStatus::Refunded);
+        escrow.refunded_at = Clock::get()?.unix_timestamp;
+        ctx.accounts.buyer_stats.record_settlement()?;
+        close_dispute_record(
+            &ctx.accounts.dispute_record,
+            &ctx.accounts.buyer,
+            &ctx.accounts.provider,
+        )?;
+
+        let provider_stats = &mut ctx.accounts.provider_stats;
+        provider_stats.refunds = provider_stats.refunds.checked_add(1).unwrap();
+        provider_stats.refunded_volume = provider_stats.refunded_volume.checked_add(amount).unwrap();
+
+        // 退款结束争议
+        let protocol_stats = &mut ctx.accounts.protocol_stats;
+        protocol_stats.active_disputes = protocol_stats.active_disputes.saturating_sub(1);
+
+        emit!(EscrowRefunded {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            amount,
+            timestamp: escrow.refunded_at().unwrap_or_default(),
+        });
+
+        msg!("Escrow refunded: {}", escrow_key);
+        msg!("Amount: {} USDC", amount);
+        msg!("Dispute bond returned: {} USDC", bond);
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        Ok(())
+    }
+
+    /// 平台阻止释放并退款给买家
+    ///
+    /// 托管记录了类别或司法辖区限制，平台检测到受限组合（如买家所在辖区不允许购买该类数据）时调用，
+    /// 原因记录在托管限制账户；资金和已缴的争议保证金全部退还买家
+    pub fn block_release<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BlockRelease<'info>>,
+        reason: String,
+    ) -> Result<()> {
+        require!(
+            reason.len() <= MAX_BLOCK_REASON_LEN,
+            EscrowError::BlockReasonTooLong
+        );
+
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+        let was_disputed = match escrow.status() {
+            EscrowStatus::Funded | EscrowStatus::Delivered => false,
+            EscrowStatus::Disputed => true,
+            _ => return err!(EscrowError::ReleaseNotPending),
+        };
+
+        let restriction = &mut ctx.accounts.restriction;
+        require!(restriction.flags != 0, EscrowError::NotRestricted);
+
+        let now = Clock::get()?.unix_timestamp;
+        restriction.blocked = true;
+        restriction.reason = reason;
+        restriction.blocked_at = Some(now);
+
+        let amount = escrow.amount;
+        let bond = dispute_bond(&ctx.accounts.dispute_record)?;
+
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            escrow_key,
+            &escrow,
+        )?;
+
+        // 退款给买家
+        if let Some(memo) = &memo {
+            memo.log("refund")?;
+        }
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.buyer_token_account.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            amount.checked_add(bond).unwrap(),
+        )?;
+
+        escrow.set_status(EscrowStatus::Refunded);
+        escrow.refunded_at = now;
+        ctx.accounts.buyer_stats.record_settlement()?;
+        close_dispute_record(
+            &ctx.accounts.dispute_record,
+            &ctx.accounts.buyer,
+            &ctx.accounts.provider,
+        )?;
+
+        let provider_stats = &mut ctx.accounts.provider_stats;
+        provider_stats.refunds = provider_stats.refunds.checked_add(1).unwrap();
+        provider_stats.refunded_volume = provider_stats.refunded_volume.checked_add(amount).unwrap();
+
+        if was_disputed {
+            let protocol_stats = &mut ctx.accounts.protocol_stats;
+            protocol_stats.active_disputes = protocol_stats.active_disputes.saturating_sub(1);
+        }
+
+        emit!(ReleaseBlocked {
+            escrow: escrow_key,
+            flags: restriction.flags,
+            reason: restriction.reason.clone(),
+            timestamp: now,
+        });
+        emit!(EscrowRefunded {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            amount,
+            timestamp: now,
+        });
+
+        msg!("Release blocked: {}", escrow_key);
+        msg!("Restrictions: {:#010x}", restriction.flags);
+        msg!("Reason: {}", restriction.reason);
+        msg!("Refunded amount: {} USDC", amount);
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        Ok(())
+    }
+
+    /// 买家申请取消托管（仅在交付前），托管进入 PendingCancel
+    ///
+    /// 提供商可能已在处理中，`CANCEL_GRACE_PERIOD` 内可以 `contest_cancel` 转入争议；
+    /// 期满无异议后任何人都可以调用 `execute_cancel` 退款给买家
+    pub fn cancel(ctx: Context<Cancel>) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = ctx.accounts.escrow.load_mut()?;
+
+        // 只允许在 Funded 状态取消（交付前）
+        require!(
+            escrow.status() == EscrowStatus::Funded,
+            EscrowError::NotFunded
+        );
+
+        // 只有买家可以取消
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let deadline = now.checked_add(CANCEL_GRACE_PERIOD).unwrap();
+
+        let cancel_request = &mut ctx.accounts.cancel_request;
+        cancel_request.escrow = escrow_key;
+        cancel_request.requested_at = now;
+        cancel_request.deadline = deadline;
+        cancel_request.bump = ctx.bumps.cancel_request;
+
+        escrow.set_status(EscrowStatus::PendingCancel);
+
+        emit!(CancelRequested {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            deadline,
+            timestamp: now,
+        });
+
+        msg!("Cancellation requested: {}", escrow_key);
+        msg!("Contest deadline: {}", deadline);
+
+        Ok(())
+    }
+
+    /// 提供商在异议期内反对取消，托管转入争议由平台裁决，取消申请随之关闭
+    pub fn contest_cancel(ctx: Context<ContestCancel>) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = ctx.accounts.escrow.load_mut()?;
+
+        require!(
+            escrow.status() == EscrowStatus::PendingCancel,
+            EscrowError::NotPendingCancel
+        );
+        require!(
+            ctx.accounts.provider.key() == escrow.provider,
+            EscrowError::OnlyProvider
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now <= ctx.accounts.cancel_request.deadline,
+            EscrowError::CancelGracePeriodEnded
+        );
+
+        escrow.set_status(EscrowStatus::Disputed);
+        escrow.disputed_at = now;
+
+        // 争议记录的租金由提供商支付，争议结束时退还提供商；反对取消不缴保证金，
+        // 取消由买家发起，不计入提供商的争议次数
+        let record = &mut ctx.accounts.dispute_record;
+        record.escrow = escrow_key;
+        record.buyer = escrow.buyer;
+        record.raised_at = now;
+        record.disputed_chunks = 0;
+        record.bond = 0;
+        record.appeal_bond = 0;
+        record.payer = ctx.accounts.provider.key();
+        record.bump = ctx.bumps.dispute_record;
+
+        let protocol_stats = &mut ctx.accounts.protocol_stats;
+        protocol_stats.active_disputes = protocol_stats.active_disputes.checked_add(1).unwrap();
+
+        // 索引器按争议事件更新状态
+        emit!(DisputeRaised {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            timestamp: now,
+        });
+
+        msg!("Cancellation contested: {}", escrow_key);
+        msg!("Provider: {}", escrow.provider);
+
+        Ok(())
+    }
+
+    /// 异议期满后执行取消，全额退款给买家（任何人可调用）
+    pub fn execute_cancel<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteCancel<'info>>,
+    ) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+
+        require!(
+            escrow.status() == EscrowStatus::PendingCancel,
+            EscrowError::NotPendingCancel
+        );
+        require!(
+            Clock::get()?.unix_timestamp > ctx.accounts.cancel_request.deadline,
+            EscrowError::CancelGracePeriodOpen
+        );
+
+        let amount = escrow.amount;
+
+        // 生成 PDA 签名种子
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            escrow_key,
+            &escrow,
+        )?;
+
+        // 退款给买家
+        if let Some(memo) = &memo {
+            memo.log("refund")?;
+        }
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.buyer_token_account.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            amount,
+        )?;
+
+        escrow.set_status(EscrowStatus::Cancelled);
+
+        let buyer_stats = &mut ctx.accounts.buyer_stats;
+        buyer_stats.cancels = buyer_stats.cancels.checked_add(1).unwrap();
+        buyer_stats.record_settlement()?;
+
+        emit!(EscrowCancelled {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Escrow cancelled: {}", escrow_key);
+        msg!("Refunded amount: {} USDC", amount);
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        Ok(())
+    }
+
+    /// 回收创建后长期未注资的托管（任何人都可以调用）
+    ///
+    /// 托管停留在 Created 状态超过 `ProtocolSettings.stale_escrow_ttl` 后关闭，租金退还买家。
+    /// 托管代币账户按托管记录的铸币推导，已创建时随托管一并关闭；
+    /// 其中的余额（例如他人转入的零头）先退给买家，不能借此阻止回收
+    pub fn reclaim_stale<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReclaimStale<'info>>,
+    ) -> Result<()> {
+        let escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+        let ttl = ctx.accounts.config.settings.stale_escrow_ttl;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            escrow.status() == EscrowStatus::Created,
+            EscrowError::NotCreated
+        );
+        require!(
+            ttl > 0 && now > escrow.created_at.saturating_add(ttl),
+            EscrowError::EscrowNotStale
+        );
+
+        let escrow_token_account = &ctx.accounts.escrow_token_account;
+        if escrow_token_account.owner == &ctx.accounts.token_program.key() {
+            let seeds = &[
+                b"escrow",
+                escrow.buyer.as_ref(),
+                escrow.request_id().as_bytes(),
+                &[escrow.bump],
+            ];
+            let balance = token_interface::TokenAccount::try_deserialize(
+                &mut &escrow_token_account.try_borrow_data()?[..],
+            )?
+            .amount;
+            if balance > 0 {
+                let buyer_token_account = ctx
+                    .accounts
+                    .buyer_token_account
+                    .as_ref()
+                    .ok_or(EscrowError::MissingBuyerTokenAccount)?;
+                transfer_tokens(
+                    &ctx.accounts.token_program.to_account_info(),
+                    escrow_token_account.to_account_info(),
+                    &ctx.accounts.mint,
+                    buyer_token_account.to_account_info(),
+                    ctx.accounts.escrow.to_account_info(),
+                    ctx.remaining_accounts,
+                    &[&seeds[..]],
+                    balance,
+                )?;
+                msg!("Swept to buyer: {}", balance);
+            }
+            token_interface::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: escrow_token_account.to_account_info(),
+                    destination: ctx.accounts.buyer.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                &[&seeds[..]],
+            ))?;
+        }
+
+        ctx.accounts.buyer_stats.record_settlement()?;
+
+        emit!(EscrowReclaimed {
+            escrow: ctx.accounts.escrow.key(),
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            timestamp: now,
+        });
+
+        msg!("Stale escrow reclaimed: {}", ctx.accounts.escrow.key());
+        msg!("Created at: {}", escrow.created_at);
+
+        Ok(())
+    }
+
+    /// 买家发起争议
+    ///
+    /// 买家须缴纳 `ProtocolSettings.dispute_bond` 争议保证金，存入托管代币账户；
+    /// 裁决支持买家时退还，否则没收给提供商。
+    /// 交付记录了数据块清单时，买家可以在 `chunks` 中指出有问题的块并附 Merkle 证明，
+    /// 供 `resolve_dispute_split` 按块裁决
+    pub fn raise_dispute<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RaiseDispute<'info>>,
+        chunks: Vec<ChunkProof>,
+    ) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = ctx.accounts.escrow.load_mut()?;
+
+        // 只允许在 Delivered 状态发起争议
+        require!(
+            escrow.status() == EscrowStatus::Delivered,
+            EscrowError::NotDelivered
+        );
+
+        // 只有买家可以发起争议
+        require!(
+            ctx.accounts.buyer.key() == escrow.buyer,
+            EscrowError::OnlyBuyer
+        );
+
+        if !chunks.is_empty() {
+            let manifest = ctx
+                .accounts
+                .chunk_manifest
+                .as_mut()
+                .ok_or(EscrowError::MissingChunkManifest)?;
+            manifest.dispute(&chunks)?;
+
+            msg!("Disputed chunks: {:?}", manifest.disputed_chunks);
+        }
+
+        let bond = ctx.accounts.config.settings.dispute_bond;
+
+        // 争议记录的租金由买家支付，争议结束时退还；保证金按实缴金额登记，结算时原数支付
+        let record = &mut ctx.accounts.dispute_record;
+        record.escrow = escrow_key;
+        record.buyer = escrow.buyer;
+        record.raised_at = Clock::get()?.unix_timestamp;
+        record.disputed_chunks = chunks.len() as u32;
+        record.bond = bond;
+        record.appeal_bond = 0;
+        record.payer = ctx.accounts.buyer.key();
+        record.bump = ctx.bumps.dispute_record;
+
+        if bond > 0 {
+            transfer_tokens(
+                &ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.buyer_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                ctx.accounts.buyer.to_account_info(),
+                ctx.remaining_accounts,
+                &[],
+                bond,
+            )?;
+        }
+
+        escrow.set_status(EscrowStatus::Disputed);
+        escrow.disputed_at = record.raised_at;
+
+        let buyer_stats = &mut ctx.accounts.buyer_stats;
+        buyer_stats.disputes_raised = buyer_stats.disputes_raised.checked_add(1).unwrap();
+
+        let provider_stats = &mut ctx.accounts.provider_stats;
+        provider_stats.disputes = provider_stats.disputes.checked_add(1).unwrap();
+
+        let protocol_stats = &mut ctx.accounts.protocol_stats;
+        protocol_stats.active_disputes = protocol_stats.active_disputes.checked_add(1).unwrap();
+
+        emit!(DisputeRaised {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            timestamp: escrow.disputed_at().unwrap_or_default(),
+        });
+
+        msg!("Dispute raised for escrow: {}", escrow_key);
+        msg!("Buyer: {}", escrow.buyer);
+        msg!("Dispute bond: {} USDC", bond);
+
+        Ok(())
+    }
+
+    /// 将争议移交陪审团（仅陪审团程序通过 CPI 调用）
+    ///
+    /// 争议中或平台裁决被上诉后均可移交；
+    /// 移交后平台不能再退款或裁决，只有陪审团权限 PDA 可以调用 `resolve_dispute`
+    pub fn escalate_dispute(ctx: Context<EscalateDispute>) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = ctx.accounts.escrow.load_mut()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Disputed || escrow.status() == EscrowStatus::Appealed,
+            EscrowError::NotDisputed
+        );
+
+        escrow.set_status(EscrowStatus::Escalated);
+
+        emit!(DisputeEscalated {
+            escrow: escrow_key,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Dispute escalated to jury: {}", escrow_key);
+
+        Ok(())
+    }
+
+    /// 平台作出可上诉的裁决（设置了上诉期时使用）
+    ///
+    /// 资金保持锁定，裁决记录在 `[b"ruling", escrow]`；
+    /// 上诉期内败诉方可以 `appeal`，期满无人上诉后任何人都可以调用 `resolve_dispute` 执行
+    pub fn rule_dispute(ctx: Context<RuleDispute>, refund_to_buyer: bool) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = ctx.accounts.escrow.load_mut()?;
+
+        require!(
+            escrow.status() == EscrowStatus::Disputed,
+            EscrowError::NotDisputed
+        );
+        require!(
+            ctx.accounts.platform.key() == escrow.platform,
+            EscrowError::OnlyArbitrator
+        );
+
+        let appeal_window = ctx.accounts.config.settings.appeal_window;
+        require!(appeal_window > 0, EscrowError::InvalidRuling);
+
+        let now = Clock::get()?.unix_timestamp;
+        let ruling = &mut ctx.accounts.ruling;
+        ruling.escrow = escrow_key;
+        ruling.refund_to_buyer = refund_to_buyer;
+        ruling.ruled_at = now;
+        ruling.appeal_deadline = now.checked_add(appeal_window).unwrap();
+        ruling.appellant = None;
+        ruling.bump = ctx.bumps.ruling;
+
+        escrow.set_status(EscrowStatus::Ruled);
+
+        emit!(DisputeRuled {
+            escrow: escrow_key,
+            refund_to_buyer,
+            appeal_deadline: ruling.appeal_deadline,
+            timestamp: now,
+        });
+
+        msg!("Dispute ruled: {}", escrow_key);
+        msg!("Refund to buyer: {}", refund_to_buyer);
+        msg!("Appeal deadline: {}", ruling.appeal_deadline);
+
+        Ok(())
+    }
+
+    /// 败诉方在上诉期内上诉
+    ///
+    /// 上诉方缴纳 `ProtocolSettings.appeal_bond` 存入托管代币账户，托管转为 Appealed，
+    /// 由陪审团程序 `open_case` 接手；最终胜诉方取回全部保证金
+    pub fn appeal<'info>(ctx: Context<'_, '_, 'info, 'info, Appeal<'info>>) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = ctx.accounts.escrow.load_mut()?;
+        let ruling = &mut ctx.accounts.ruling;
+        let appellant = ctx.accounts.appellant.key();
+
+        require!(
+            escrow.status() == EscrowStatus::Ruled,
+            EscrowError::NotRuled
+        );
+
+        // 裁决退款时提供商败诉，否则买家败诉
+        let losing_party = if ruling.refund_to_buyer {
+            escrow.provider
+        } else {
+            escrow.buyer
+        };
+        require!(appellant == losing_party, EscrowError::OnlyLosingParty);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < ruling.appeal_deadline, EscrowError::AppealWindowClosed);
+
+        let bond = ctx.accounts.config.settings.appeal_bond;
+        if bond > 0 {
+            transfer_tokens(
+                &ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.appellant_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                ctx.accounts.appellant.to_account_info(),
+                ctx.remaining_accounts,
+                &[],
+                bond,
+            )?;
+        }
+
+        ruling.appellant = Some(appellant);
+        ctx.accounts.dispute_record.appeal_bond = bond;
+        escrow.set_status(EscrowStatus::Appealed);
+
+        emit!(DisputeAppealed {
+            escrow: escrow_key,
+            appellant,
+            bond,
+            timestamp: now,
+        });
+
+        msg!("Ruling appealed: {}", escrow_key);
+        msg!("Appellant: {}", appellant);
+        msg!("Appeal bond: {} USDC", bond);
+
+        Ok(())
+    }
+
+    /// 平台解决争议（退款或释放）
+    ///
+    /// 已移交陪审团的争议（Escalated）只能由陪审团权限 PDA 通过 CPI 裁决；
+    /// 平台可上诉裁决（Ruled）在上诉期满后任何人都可以按裁决结果执行
+    pub fn resolve_dispute<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveDispute<'info>>,
+        refund_to_buyer: bool,
+    ) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+
+        // 争议中由平台裁决，移交陪审团后由陪审团裁决，可上诉裁决期满后按裁决执行
+        let judge = match escrow.status() {
+            EscrowStatus::Disputed => {
+                require!(
+                    ctx.accounts.config.settings.appeal_window == 0,
+                    EscrowError::RulingRequired
+                );
+                Some(escrow.platform)
+            }
+            EscrowStatus::Escalated => Some(
+                Pubkey::find_program_address(&[JURY_AUTHORITY_SEED], &JURY_PROGRAM_ID).0,
+            ),
+            EscrowStatus::Ruled => {
+                let ruling = ctx
+                    .accounts
+                    .ruling
+                    .as_ref()
+                    .ok_or(EscrowError::InvalidRuling)?;
+                require!(
+                    ruling.refund_to_buyer == refund_to_buyer,
+                    EscrowError::InvalidRuling
+                );
+                require!(
+                    Clock::get()?.unix_timestamp >= ruling.appeal_deadline,
+                    EscrowError::AppealWindowOpen
+                );
+                None
+            }
+            _ => return err!(EscrowError::NotDisputed),
+        };
+        if let Some(judge) = judge {
+            require!(
+                ctx.accounts.platform.key() == judge,
+                EscrowError::OnlyArbitrator
+            );
+        }
+
+        let amount = escrow.amount;
+        // 争议记录登记的争议和上诉保证金：全部归胜诉方（支持买家时退给买家，否则给提供商）
+        let bond = dispute_bond(&ctx.accounts.dispute_record)?;
+
+        // 争议结束，无论结果如何
+        let protocol_stats = &mut ctx.accounts.protocol_stats;
+        protocol_stats.active_disputes = protocol_stats.active_disputes.saturating_sub(1);
+
+        // 生成 PDA 签名种子
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            escrow_key,
+            &escrow,
+        )?;
+
+        if refund_to_buyer {
+            // 退款给买家
+            if let Some(memo) = &memo {
+                memo.log("refund")?;
+            }
+            transfer_tokens(
+                &ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.escrow_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                ctx.accounts.buyer_token_account.to_account_info(),
+                ctx.accounts.escrow.to_account_info(),
+                ctx.remaining_accounts,
+                signer,
+                amount.checked_add(bond).unwrap(),
+            )?;
+
+            escrow.set_status(EscrowStatus::Refunded);
+            escrow.refunded_at = Clock::get()?.unix_timestamp;
+            ctx.accounts.buyer_stats.record_settlement()?;
+            close_dispute_record(
+                &ctx.accounts.dispute_record,
+                &ctx.accounts.buyer,
+                &ctx.accounts.provider,
+            )?;
+
+            let provider_stats = &mut ctx.accounts.provider_stats;
+            provider_stats.refunds = provider_stats.refunds.checked_add(1).unwrap();
+            provider_stats.refunded_volume =
+                provider_stats.refunded_volume.checked_add(amount).unwrap();
+
+            emit!(EscrowRefunded {
+                escrow: escrow_key,
+                buyer: escrow.buyer,
+                amount,
+                timestamp: escrow.refunded_at().unwrap_or_default(),
+            });
+
+            msg!("Dispute resolved: Refunded to buyer");
+            msg!("Amount: {} USDC", amount);
+            msg!("Dispute bond returned: {} USDC", bond);
+        } else {
+            // 释放给提供商（95/5）；与 `confirm_and_release` 相同，美元计价时只支付折算的代币数量
+            let total_amount = usd_release_amount(
+                &ctx.accounts.usd_pricing,
+                ctx.accounts.price_update.as_ref(),
+                &ctx.accounts.config.settings,
+                amount,
+            )?
+            .unwrap_or(amount);
+            let usd_refund = amount.checked_sub(total_amount).unwrap();
+            let FeeSplit {
+                platform_fee,
+                provider_amount,
+            } = FeeSplit::new(total_amount)?;
+            // 优惠券：从平台费中减免，减免部分和美元计价的差额一起退还买家
+            let coupon_rebate = escrow_discount(&ctx.accounts.discount)?
+                .map_or(0, |discount| discount.amount(platform_fee));
+            let platform_fee = platform_fee.checked_sub(coupon_rebate).unwrap();
+            require!(
+                [platform_fee, coupon_rebate, usd_refund]
+                    .iter()
+                    .try_fold(provider_amount, |sum, part| sum.checked_add(*part))
+                    == Some(amount),
+                EscrowError::FeeSplitMismatch
+            );
+            let buyer_amount = coupon_rebate.checked_add(usd_refund).unwrap();
+
+            // 转账给提供商（95%）
+            // 没收的争议保证金随同转给提供商，不计平台费
+            if let Some(memo) = &memo {
+                memo.log("provider")?;
+            }
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            transfer_tokens(
+                &cpi_program,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                ctx.accounts.provider_token_account.to_account_info(),
+                ctx.accounts.escrow.to_account_info(),
+                ctx.remaining_accounts,
+                signer,
+                provider_amount.checked_add(bond).unwrap(),
+            )?;
+
+            if buyer_amount > 0 {
+                if let Some(memo) = &memo {
+                    memo.log("refund")?;
+                }
+                transfer_tokens(
+                    &cpi_program,
+                    ctx.accounts.escrow_token_account.to_account_info(),
+                    &ctx.accounts.mint,
+                    ctx.accounts.buyer_token_account.to_account_info(),
+                    ctx.accounts.escrow.to_account_info(),
+                    ctx.remaining_accounts,
+                    signer,
+                    buyer_amount,
+                )?;
+            }
+
+            // 平台费（5%）分给国库和奖励池
+            let fee_accrual = FeeAccrual::new(
+                &ctx.accounts.config,
+                ctx.accounts.fee_ledger.as_mut(),
+                ctx.accounts.fee_vault.as_deref(),
+            )?;
+            distribute_platform_fee(
+                &ctx.accounts.config,
+                cpi_program,
+                &ctx.accounts.mint,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                ctx.accounts
+                    .treasury_token_account
+                    .as_ref()
+                    .map(|account| account.to_account_info()),
+                ctx.accounts
+                    .rewards_pool_token_account
+                    .as_ref()
+                    .map(|account| account.to_account_info()),
+                ctx.accounts
+                    .insurance_vault
+                    .as_ref()
+                    .map(|account| account.to_account_info()),
+                ctx.accounts.escrow.to_account_info(),
+                ctx.remaining_accounts,
+                memo.as_ref(),
+                fee_accrual,
+                signer,
+                platform_fee,
+            )?;
+
+            escrow.set_status(EscrowStatus::Completed);
+            escrow.completed_at = Clock::get()?.unix_timestamp;
+            ctx.accounts.buyer_stats.record_settlement()?;
+            close_dispute_record(
+                &ctx.accounts.dispute_record,
+                &ctx.accounts.buyer,
+                &ctx.accounts.provider,
+            )?;
+            record_sale(
+                &ctx.accounts.sales_history,
+                &ctx.accounts.restriction,
+                escrow_key,
+                total_amount,
+                escrow.completed_at().unwrap_or_default(),
+            )?;
+
+            let provider_stats = &mut ctx.accounts.provider_stats;
+            provider_stats.completed_sales =
+                provider_stats.completed_sales.checked_add(1).unwrap();
+            provider_stats.sales_volume = provider_stats
+                .sales_volume
+                .checked_add(provider_amount)
+                .unwrap();
+
+            let protocol_stats = &mut ctx.accounts.protocol_stats;
+            protocol_stats.total_fees = protocol_stats.total_fees.checked_add(platform_fee).unwrap();
+
+            emit!(EscrowReleased {
+                escrow: escrow_key,
+                provider: escrow.provider,
+                provider_amount,
+                platform_fee,
+                tip: 0,
+                timestamp: escrow.completed_at().unwrap_or_default(),
+            });
+
+            msg!("Dispute resolved: Released to provider");
+            msg!("Provider amount: {} USDC (95%)", provider_amount);
+            msg!("Platform fee: {} USDC (5%)", platform_fee);
+            if coupon_rebate > 0 {
+                msg!("Coupon rebate: {} USDC", coupon_rebate);
+            }
+            if usd_refund > 0 {
+                msg!("USD pricing refund: {}", usd_refund);
+            }
+            msg!("Dispute bond forfeited: {} USDC", bond);
+
+            mint_access_token(
+                &escrow,
+                &ctx.accounts.access_mint,
+                &ctx.accounts.buyer_access_account,
+                &ctx.accounts.access_authority,
+                ctx.bumps.access_authority,
+                &ctx.accounts.access_token_program,
+                &ctx.accounts.access_window,
+            )?;
+        }
+
+        emit!(DisputeResolved {
+            escrow: escrow_key,
+            refund_to_buyer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        Ok(())
+    }
+
+    /// 按有效数据块比例拆分争议托管
+    ///
+    /// 裁决方与 `resolve_dispute` 相同（平台或陪审团权限 PDA），从买家争议的数据块中指出确属无效的块；
+    /// 提供商按有效块占比收款（扣 5% 平台费），其余退还买家。
+    /// 至少一块被判无效时争议保证金退还买家，否则没收给提供商
+    pub fn resolve_dispute_split<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveDisputeSplit<'info>>,
+        invalid_chunks: Vec<u32>,
+    ) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let mut escrow = *ctx.accounts.escrow.load()?;
+        escrow.require_no_yield_position()?;
+
+        // 可上诉裁决只有退款或释放两种结果，不能按块拆分
+        let judge = match escrow.status() {
+            EscrowStatus::Disputed => {
+                require!(
+                    ctx.accounts.config.settings.appeal_window == 0,
+                    EscrowError::RulingRequired
+                );
+                escrow.platform
+            }
+            EscrowStatus::Escalated => {
+                Pubkey::find_program_address(&[JURY_AUTHORITY_SEED], &JURY_PROGRAM_ID).0
+            }
+            _ => return err!(EscrowError::NotDisputed),
+        };
+        require!(
+            ctx.accounts.platform.key() == judge,
+            EscrowError::OnlyArbitrator
+        );
+
+        let manifest = &ctx.accounts.chunk_manifest;
+        for (i, index) in invalid_chunks.iter().enumerate() {
+            require!(
+                manifest.disputed_chunks.contains(index) && !invalid_chunks[..i].contains(index),
+                EscrowError::InvalidChunkRuling
+            );
+        }
+        let chunk_count = manifest.chunk_count as u64;
+        let valid_chunks = chunk_count.checked_sub(invalid_chunks.len() as u64).unwrap();
+
+        let amount = escrow.amount;
+        let bond = dispute_bond(&ctx.accounts.dispute_record)?;
+        // 美元计价时提供商份额按折算的代币数量计算，差额随退款退还买家
+        let release_amount = if valid_chunks > 0 {
+            usd_release_amount(
+                &ctx.accounts.usd_pricing,
+                ctx.accounts.price_update.as_ref(),
+                &ctx.accounts.config.settings,
+                amount,
+            )?
+            .unwrap_or(amount)
+        } else {
+            amount
+        };
+        let provider_share =
+            (release_amount as u128 * valid_chunks as u128 / chunk_count as u128) as u64;
+        let FeeSplit {
+            platform_fee,
+            provider_amount,
+        } = FeeSplit::new(provider_share)?;
+        // 优惠券：按提供商份额的平台费减免，减免部分随退款退还买家
+        let coupon_rebate = escrow_discount(&ctx.accounts.discount)?
+            .map_or(0, |discount| discount.amount(platform_fee));
+        let platform_fee = platform_fee.checked_sub(coupon_rebate).unwrap();
+        let buyer_refund = amount.checked_sub(provider_share).unwrap();
+        require!(
+            [platform_fee, coupon_rebate, buyer_refund]
+                .iter()
+                .try_fold(provider_amount, |sum, part| sum.checked_add(*part))
+                == Some(amount),
+            EscrowError::FeeSplitMismatch
+        );
+        let (buyer_bond, provider_bond) = if invalid_chunks.is_empty() {
+            (0, bond)
+        } else {
+            (bond, 0)
+        };
+
+        let protocol_stats = &mut ctx.accounts.protocol_stats;
+        protocol_stats.active_disputes = protocol_stats.active_disputes.saturating_sub(1);
+        protocol_stats.total_fees = protocol_stats.total_fees.checked_add(platform_fee).unwrap();
+
+        // 生成 PDA 签名种子
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let memo = TransferMemo::new(
+            &ctx.accounts.config,
+            ctx.accounts.memo_program.as_ref(),
+            escrow_key,
+            &escrow,
+        )?;
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        // 无效块对应的金额退还买家
+        let buyer_total = buyer_refund
+            .checked_add(coupon_rebate)
+            .and_then(|total| total.checked_add(buyer_bond))
+            .unwrap();
+        if buyer_total > 0 {
+            if let Some(memo) = &memo {
+                memo.log("refund")?;
+            }
+            transfer_tokens(
+                &cpi_program,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                ctx.accounts.buyer_token_account.to_account_info(),
+                ctx.accounts.escrow.to_account_info(),
+                ctx.remaining_accounts,
+                signer,
+                buyer_total,
+            )?;
+        }
+
+        // 有效块对应的金额（扣平台费）转给提供商
+        let provider_total = provider_amount.checked_add(provider_bond).unwrap();
+        if provider_total > 0 {
+            if let Some(memo) = &memo {
+                memo.log("provider")?;
+            }
+            transfer_tokens(
+                &cpi_program,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                ctx.accounts.provider_token_account.to_account_info(),
+                ctx.accounts.escrow.to_account_info(),
+                ctx.remaining_accounts,
+                signer,
+                provider_total,
+            )?;
+        }
+
+        let fee_accrual = FeeAccrual::new(
+            &ctx.accounts.config,
+            ctx.accounts.fee_ledger.as_mut(),
+            ctx.accounts.fee_vault.as_deref(),
+        )?;
+        distribute_platform_fee(
+            &ctx.accounts.config,
+            cpi_program,
+            &ctx.accounts.mint,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts
+                .treasury_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts
+                .rewards_pool_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts
+                .insurance_vault
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.remaining_accounts,
+            memo.as_ref(),
+            fee_accrual,
+            signer,
+            platform_fee,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let provider_stats = &mut ctx.accounts.provider_stats;
+        if provider_share > 0 {
+            escrow.set_status(EscrowStatus::Completed);
+            escrow.completed_at = now;
+            provider_stats.completed_sales =
+                provider_stats.completed_sales.checked_add(1).unwrap();
+            record_sale(
+                &ctx.accounts.sales_history,
+                &ctx.accounts.restriction,
+                escrow_key,
+                provider_share,
+                now,
+            )?;
+        } else {
+            escrow.set_status(EscrowStatus::Refunded);
+            escrow.refunded_at = now;
+            provider_stats.refunds = provider_stats.refunds.checked_add(1).unwrap();
+        }
+        ctx.accounts.buyer_stats.record_settlement()?;
+        close_dispute_record(
+            &ctx.accounts.dispute_record,
+            &ctx.accounts.buyer,
+            &ctx.accounts.provider,
+        )?;
+        provider_stats.sales_volume = provider_stats
+            .sales_volume
+            .checked_add(provider_amount)
+            .unwrap();
+        provider_stats.refunded_volume = provider_stats
+            .refunded_volume
+            .checked_add(buyer_refund)
+            .unwrap();
+
+        // 索引器按托管终态事件更新状态
+        if provider_share > 0 {
+            emit!(EscrowReleased {
+                escrow: escrow_key,
+                provider: escrow.provider,
+                provider_amount,
+                platform_fee,
+                tip: 0,
+                timestamp: now,
+            });
+        } else {
+            emit!(EscrowRefunded {
+                escrow: escrow_key,
+                buyer: escrow.buyer,
+                amount: buyer_refund,
+                timestamp: now,
+            });
+        }
+        emit!(DisputeSplit {
+            escrow: escrow_key,
+            valid_chunks: valid_chunks as u32,
+            chunk_count: chunk_count as u32,
+            provider_amount,
+            platform_fee,
+            buyer_refund,
+            timestamp: now,
+        });
+
+        msg!("Dispute split: {}/{} chunks valid", valid_chunks, chunk_count);
+        msg!("Provider amount: {} USDC", provider_amount);
+        msg!("Platform fee: {} USDC", platform_fee);
+        msg!("Buyer refund: {} USDC", buyer_refund);
+        msg!("Dispute bond returned: {} USDC", buyer_bond);
+        msg!("Dispute bond forfeited: {} USDC", provider_bond);
+
+        *ctx.accounts.escrow.load_mut()? = escrow;
+
+        Ok(())
+    }
+
+    /// 访问期满后冻结买家的访问凭证账户（任何人可调用）
+    ///
+    /// 访问凭证铸币的冻结权限须为提供商的访问凭证 PDA；冻结作用于整个凭证账户，
+    /// 提供商应为每个数据集使用独立的铸币（建议 Token-2022 不可转让铸币，防止买家转出凭证）
+    pub fn revoke_expired_access(ctx: Context<RevokeExpiredAccess>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let access_window = &ctx.accounts.access_window;
+
+        let expires_at = access_window
+            .expires_at
+            .ok_or(EscrowError::AccessNotExpired)?;
+        require!(
+            access_window.revoked_at.is_none(),
+            EscrowError::AccessRevoked
+        );
+        require!(now >= expires_at, EscrowError::AccessNotExpired);
+        require!(
+            ctx.accounts.access_mint.freeze_authority
+                == Some(ctx.accounts.access_authority.key()).into(),
+            EscrowError::InvalidAccessMint
+        );
+
+        let provider = ctx.accounts.escrow.load()?.provider;
+        let seeds = &[
+            b"access".as_ref(),
+            provider.as_ref(),
+            &[ctx.bumps.access_authority],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = token_interface::FreezeAccount {
+            account: ctx.accounts.buyer_access_account.to_account_info(),
+            mint: ctx.accounts.access_mint.to_account_info(),
+            authority: ctx.accounts.access_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.access_token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token_interface::freeze_account(cpi_ctx)?;
+
+        let access_window = &mut ctx.accounts.access_window;
+        access_window.revoked_at = Some(now);
+
+        emit!(AccessRevoked {
+            escrow: ctx.accounts.escrow.key(),
+            buyer: ctx.accounts.escrow.load()?.buyer,
+            token_account: access_window.token_account,
+            timestamp: now,
+        });
+
+        msg!("Access revoked: {}", ctx.accounts.escrow.key());
+        msg!("Expired at: {}", expires_at);
+
+        Ok(())
+    }
+
+    /// 访问凭证换手时更新访问期记录的凭证账户（仅转售程序通过 CPI 调用）
+    ///
+    /// 挂单、取消挂单和成交时分别指向挂单金库、卖家和新买家的凭证账户，
+    /// 到期撤销始终冻结当前持有凭证的账户
+    pub fn transfer_access(ctx: Context<TransferAccess>) -> Result<()> {
+        let access_window = &mut ctx.accounts.access_window;
+        require!(
+            access_window.revoked_at.is_none(),
+            EscrowError::AccessRevoked
+        );
+
+        access_window.token_account = ctx.accounts.token_account.key();
+
+        msg!("Access transferred: {}", ctx.accounts.escrow.key());
+        msg!("Token account: {}", access_window.token_account);
+
+        Ok(())
+    }
+
+    /// 平台为托管追加扩展元数据（如合同条款哈希、司法辖区标签）
+    ///
+    /// 账户按追加长度扩容，总长度不超过 MAX_METADATA_LEN
+    pub fn extend_metadata(ctx: Context<ExtendMetadata>, data: Vec<u8>) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let start = ctx.accounts.escrow.load()?.metadata_len as usize;
+
+        require!(!data.is_empty(), EscrowError::InvalidMetadata);
+        require!(
+            start + data.len() <= MAX_METADATA_LEN,
+            EscrowError::MetadataTooLong
+        );
+
+        // 元数据位于定长部分之后，直接写入扩容出的尾部字节
+        let metadata_len = start + data.len();
+        {
+            let info = ctx.accounts.escrow.to_account_info();
+            let mut bytes = info.try_borrow_mut_data()?;
+            let offset = Escrow::METADATA_OFFSET + start;
+            bytes[offset..offset + data.len()].copy_from_slice(&data);
+        }
+        ctx.accounts.escrow.load_mut()?.metadata_len = metadata_len as u32;
+
+        msg!("Escrow metadata extended: {}", escrow_key);
+        msg!("Metadata length: {} bytes", metadata_len);
+
+        Ok(())
+    }
+
+    /// 买家或提供商记录一条链下消息（协商内容、条款修订）的哈希
+    ///
+    /// 记录按时间顺序追加到 `[b"message_log", escrow]`，只能追加不能修改，
+    /// 为仲裁提供不可篡改的协商时间线；每个托管最多 `MAX_MESSAGES` 条
+    pub fn log_message(ctx: Context<LogMessage>, message_hash: [u8; 32]) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+        let author = ctx.accounts.author.key();
+
+        require!(!escrow.status().is_terminal(), EscrowError::EscrowSettled);
+        require!(
+            author == escrow.buyer || author == escrow.provider,
+            EscrowError::OnlyParticipant
+        );
+
+        let message_log = &mut ctx.accounts.message_log;
+        require!(
+            message_log.entries.len() < MAX_MESSAGES,
+            EscrowError::MessageLogFull
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        message_log.escrow = ctx.accounts.escrow.key();
+        message_log.bump = ctx.bumps.message_log;
+        message_log.entries.push(MessageEntry {
+            author,
+            message_hash,
+            timestamp: now,
+        });
+
+        emit!(MessageLogged {
+            escrow: ctx.accounts.escrow.key(),
+            author,
+            message_hash,
+            index: (message_log.entries.len() - 1) as u32,
+            timestamp: now,
+        });
+
+        msg!("Message logged: {}", ctx.accounts.escrow.key());
+        msg!("Entries: {}", message_log.entries.len());
+
+        Ok(())
+    }
+
+    /// 将旧布局的托管账户迁移到当前版本
+    ///
+    /// 账户按当前布局扩容（新增字段以零填充，即默认值），由调用者补足租金；
+    /// 版本 3 起状态和版本移到变长字段之前，迁移时按旧布局读取后重排。
+    /// 版本 3 之前的布局按账户大小区分（见 `LegacyLayout`），逐字段读取；
+    /// 版本 4 的定长部分原样保留，扩展元数据后移到新增的 `mint` 之后
+    pub fn migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+
+        // 版本 3 之前的布局中该位置是 request_id 长度（u32 小端）的第二个字节，
+        // request_id 作为 PDA 种子不超过 32 字节，因此恒为 0
+        let (from_version, escrow, metadata) = {
+            let data = escrow_info.data.borrow();
+            require!(
+                data[..8] == *Escrow::DISCRIMINATOR,
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+            let version = data[Escrow::VERSION_OFFSET];
+            require!(version < ESCROW_VERSION, EscrowError::AlreadyMigrated);
+            if version == 4 {
+                let (escrow, metadata) = Escrow::read_v4(&data)?;
+                (version, escrow, metadata)
+            } else {
+                let previous = if version == 3 {
+                    EscrowV3::deserialize(&mut &data[8..])?
+                } else {
+                    let layout = LegacyLayout::from_len(data.len())
+                        .ok_or(EscrowError::UnknownEscrowLayout)?;
+                    LegacyEscrow::read(layout, &mut &data[8..])?.migrate()
+                };
+                (previous.version, previous.migrate()?, previous.metadata)
+            }
+        };
+
+        // 定长部分之后紧跟扩展元数据，账户大小按实际长度调整
+        let len = Escrow::LEN + metadata.len();
+        resize_account(
+            &escrow_info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            len,
+        )?;
+
+        {
+            let mut data = escrow_info.data.borrow_mut();
+            data[8..Escrow::LEN].copy_from_slice(bytemuck::bytes_of(&escrow));
+            data[Escrow::METADATA_OFFSET..].copy_from_slice(&metadata);
+        }
+
+        msg!("Escrow migrated: {}", escrow_info.key());
+        msg!("Version: {} -> {}", from_version, ESCROW_VERSION);
+
+        Ok(())
+    }
+
+    /// 将旧布局的全局配置迁移到当前布局（任何人可调用）
+    ///
+    /// `ProtocolSettings` 的新字段都追加在末尾，补零即为默认值（见 `Config::migrate`），
+    /// 账户扩容租金由调用者补足
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+        let from_len = config_info.data_len();
+        let data = Config::migrate(&config_info.try_borrow_data()?)?;
+
+        resize_account(
+            &config_info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            Config::LEN,
+        )?;
+        config_info.try_borrow_mut_data()?.copy_from_slice(&data);
+
+        msg!("Config migrated: {}", config_info.key());
+        msg!("Size: {} -> {}", from_len, Config::LEN);
+
+        Ok(())
+    }
+
+    /// 将旧布局的买家统计迁移到当前布局，并回填未进入终态的托管数（仅管理员）
+    ///
+    /// 限流字段上线前创建的托管没有计入 `open_escrows`，迁移时该买家全部未进入终态的托管
+    /// 须作为 remaining accounts 传入（旧布局的托管先 `migrate_escrow`），逐个校验后计数；
+    /// 已迁移的账户可再次调用，按传入的托管重新统计。账户扩容租金由 `payer` 补足
+    pub fn migrate_buyer_stats<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateBuyerStats<'info>>,
+    ) -> Result<()> {
+        let buyer = ctx.accounts.buyer.key();
+        let mut open: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        for info in ctx.remaining_accounts {
+            let loader = AccountLoader::<Escrow>::try_from(info)?;
+            {
+                let data = info.try_borrow_data()?;
+                require!(
+                    data.len() >= Escrow::LEN && data[Escrow::VERSION_OFFSET] == ESCROW_VERSION,
+                    EscrowError::MigrationRequired
+                );
+            }
+            let escrow = loader.load()?;
+            require!(
+                escrow.buyer == buyer
+                    && !escrow.status().is_terminal()
+                    && !open.contains(info.key),
+                EscrowError::OpenEscrowMismatch
+            );
+            open.push(*info.key);
+        }
+        let open_escrows = open.len() as u64;
+
+        let stats_info = ctx.accounts.buyer_stats.to_account_info();
+        let data = BuyerStats::migrate(&stats_info.try_borrow_data()?, open_escrows)?;
+        resize_account(
+            &stats_info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            BuyerStats::LEN,
+        )?;
+        stats_info.try_borrow_mut_data()?.copy_from_slice(&data);
+
+        msg!("Buyer stats migrated: {}", stats_info.key());
+        msg!("Open escrows: {}", open_escrows);
+
+        Ok(())
+    }
+}
+
+/// 托管金额在平台费和提供商份额之间的拆分
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub platform_fee: u64,    // 平台费
+    pub provider_amount: u64, // 提供商份额
+}
+
+impl FeeSplit {
+    /// 先按 `PLATFORM_FEE_BPS` 向下取整计算平台费，余数（包括除不尽的零头）全部归提供商
+    pub fn new(amount: u64) -> Result<Self> {
+        let platform_fee =
+            (amount as u128 * PLATFORM_FEE_BPS as u128 / BPS_DENOMINATOR as u128) as u64;
+        let split = Self {
+            platform_fee,
+            provider_amount: amount.checked_sub(platform_fee).unwrap(),
+        };
+
+        // 不变量：平台费与提供商份额之和等于拆分金额
+        require!(
+            split.platform_fee.checked_add(split.provider_amount) == Some(amount),
+            EscrowError::FeeSplitMismatch
+        );
+        Ok(split)
+    }
+}
+
+/// 平台费在保险池、国库和奖励池之间的拆分
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlatformFeeShares {
+    pub insurance: u64,       // 保险池份额
+    pub treasury: u64,        // 国库份额
+    pub rewards_pool: u64,    // 奖励池份额
+}
+
+impl PlatformFeeShares {
+    /// 先按 `insurance_bps` 划出保险池份额，剩余部分中国库份额按 `treasury_bps` 向下取整，
+    /// 余数全部归奖励池；按 u128 计算，任何金额都不会溢出
+    pub fn new(platform_fee: u64, insurance_bps: u16, treasury_bps: u16) -> Result<Self> {
+        let insurance =
+            (platform_fee as u128 * insurance_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        let remaining = platform_fee
+            .checked_sub(insurance)
+            .ok_or(EscrowError::FeeSplitMismatch)?;
+        let treasury = (remaining as u128 * treasury_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        let shares = Self {
+            insurance,
+            treasury,
+            rewards_pool: remaining
+                .checked_sub(treasury)
+                .ok_or(EscrowError::FeeSplitMismatch)?,
+        };
+
+        // 不变量：各份额之和等于平台费
+        require!(
+            shares
+                .insurance
+                .checked_add(shares.treasury)
+                .and_then(|sum| sum.checked_add(shares.rewards_pool))
+                == Some(platform_fee),
+            EscrowError::FeeSplitMismatch
+        );
+        Ok(shares)
+    }
+}
+
+/// 代币投票的锁定期，陪审团质押和治理存款共用
+///
+/// 投票后锁定投票代币至所投事项的最晚投票截止，期间不能取回，
+/// 避免同一份代币在多个账户间转移重复投票
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct VoteLock {
+    pub until: i64,              // 锁定至（0 为未锁定）
+}
+
+impl VoteLock {
+    /// 延长至 `voting_ends_at`，截止更早的事项不会缩短锁定期
+    pub fn extend(&mut self, voting_ends_at: i64) {
+        self.until = self.until.max(voting_ends_at);
+    }
+
+    /// 所投事项的投票是否已全部截止
+    pub fn is_unlocked(&self, now: i64) -> bool {
+        now >= self.until
+    }
+}
+
+/// 将平台费按配置权重分给保险池、国库和奖励池
+///
+/// 先按 `insurance_bps` 划出保险池份额，剩余部分中国库份额向下取整，
+/// 余数全部进入奖励池，保证各笔转账之和等于平台费；
+/// 传入 `fee_accrual` 时改为整笔转入共享费用金库，留待 `settle_fees` 拆分。
+/// 金额为零的一笔不发起转账，对应的代币账户也可以不传
+#[allow(clippy::too_many_arguments)]
+fn distribute_platform_fee<'info>(
+    config: &Config,
+    token_program: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, token_interface::Mint>,
+    escrow_token_account: AccountInfo<'info>,
+    treasury_token_account: Option<AccountInfo<'info>>,
+    rewards_pool_token_account: Option<AccountInfo<'info>>,
+    insurance_vault: Option<AccountInfo<'info>>,
+    escrow: AccountInfo<'info>,
+    hook_accounts: &[AccountInfo<'info>],
+    memo: Option<&TransferMemo<'info>>,
+    fee_accrual: Option<FeeAccrual<'_, 'info>>,
+    signer: &[&[&[u8]]],
+    platform_fee: u64,
+) -> Result<()> {
+    if platform_fee == 0 {
+        msg!("  Platform fee: 0 USDC, skipped");
+        return Ok(());
+    }
+
+    if let Some(accrual) = fee_accrual {
+        msg!("  Fee vault: {} USDC", platform_fee);
+
+        if let Some(memo) = memo {
+            memo.log("platform")?;
+        }
+        transfer_tokens(
+            &token_program,
+            escrow_token_account,
+            mint,
+            accrual.vault,
+            escrow,
+            hook_accounts,
+            signer,
+            platform_fee,
+        )?;
+
+        accrual.ledger.accrued = accrual.ledger.accrued.checked_add(platform_fee).unwrap();
+        return Ok(());
+    }
+
+    let PlatformFeeShares {
+        insurance: insurance_amount,
+        treasury: treasury_amount,
+        rewards_pool: rewards_pool_amount,
+    } = PlatformFeeShares::new(
+        platform_fee,
+        config.settings.insurance_bps,
+        config.treasury_bps,
+    )?;
+
+    if insurance_amount > 0 {
+        let insurance_vault = insurance_vault.ok_or(EscrowError::MissingInsuranceVault)?;
+
+        msg!(
+            "  Insurance ({} bps): {} USDC",
+            config.settings.insurance_bps,
+            insurance_amount
+        );
+
+        if let Some(memo) = memo {
+            memo.log("platform")?;
+        }
+        transfer_tokens(
+            &token_program,
+            escrow_token_account.clone(),
+            mint,
+            insurance_vault,
+            escrow.clone(),
+            hook_accounts,
+            signer,
+            insurance_amount,
+        )?;
+    }
+
+    msg!("  Treasury ({} bps): {} USDC", config.treasury_bps, treasury_amount);
+    msg!("  Rewards pool ({} bps): {} USDC", config.rewards_pool_bps, rewards_pool_amount);
+
+    // 转账给国库
+    if treasury_amount > 0 {
+        let treasury_token_account =
+            treasury_token_account.ok_or(EscrowError::MissingPlatformTokenAccount)?;
+
+        if let Some(memo) = memo {
+            memo.log("platform")?;
+        }
+        transfer_tokens(
+            &token_program,
+            escrow_token_account.clone(),
+            mint,
+            treasury_token_account,
+            escrow.clone(),
+            hook_accounts,
+            signer,
+            treasury_amount,
+        )?;
+    }
+
+    // 转账给奖励池
+    if rewards_pool_amount > 0 {
+        let rewards_pool_token_account =
+            rewards_pool_token_account.ok_or(EscrowError::MissingPlatformTokenAccount)?;
+
+        if let Some(memo) = memo {
+            memo.log("platform")?;
+        }
+        transfer_tokens(
+            &token_program,
+            escrow_token_account,
+            mint,
+            rewards_pool_token_account,
+            escrow,
+            hook_accounts,
+            signer,
+            rewards_pool_amount,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// 用 `transfer_checked` 转账，兼容 SPL Token 和 Token-2022
+///
+/// 铸币启用了转账钩子时，从 `hook_accounts`（指令的 remaining accounts）中
+/// 找出钩子程序、额外账户元数据 PDA 及其声明的额外账户一并传入
+#[allow(clippy::too_many_arguments)]
+fn transfer_tokens<'info>(
+    token_program: &AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, token_interface::Mint>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    hook_accounts: &[AccountInfo<'info>],
+    signer: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    spl_token_2022::onchain::invoke_transfer_checked(
+        token_program.key,
+        from,
+        mint.to_account_info(),
+        to,
+        authority,
+        hook_accounts,
+        amount,
+        mint.decimals,
+        signer,
+    )?;
+
+    Ok(())
+}
+
+/// 以托管 PDA 签名调用 Jupiter，把提供商份额从托管代币账户兑换到提供商的结算代币账户
+///
+/// 路由须恰好从托管代币账户转出 `amount`（ExactIn），提供商实际到账不少于 `min_out`，
+/// 否则整笔释放失败；返回兑换所得
+#[allow(clippy::too_many_arguments)]
+fn swap_to_provider<'info>(
+    swap_data: Vec<u8>,
+    amount: u64,
+    min_out: u64,
+    escrow: Pubkey,
+    escrow_token_account: &mut InterfaceAccount<'info, token_interface::TokenAccount>,
+    provider_settle_token_account: &mut InterfaceAccount<'info, token_interface::TokenAccount>,
+    route_accounts: &[AccountInfo<'info>],
+    signer: &[&[&[u8]]],
+) -> Result<u64> {
+    let vault_before = escrow_token_account.amount;
+    let provider_before = provider_settle_token_account.amount;
+
+    // 托管 PDA 是路由的转账权限，由本程序代为签名
+    let accounts = route_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer || account.key() == escrow,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    invoke_signed(
+        &Instruction {
+            program_id: JUPITER_PROGRAM_ID,
+            accounts,
+            data: swap_data,
+        },
+        route_accounts,
+        signer,
+    )?;
+
+    escrow_token_account.reload()?;
+    provider_settle_token_account.reload()?;
+    require!(
+        vault_before.checked_sub(escrow_token_account.amount) == Some(amount),
+        EscrowError::InvalidSwap
+    );
+    let received = provider_settle_token_account
+        .amount
+        .saturating_sub(provider_before);
+    require!(received >= min_out, EscrowError::SwapSlippageExceeded);
+
+    msg!("Swapped {} escrow tokens into {} settlement tokens", amount, received);
+
+    Ok(received)
+}
+
+/// 平台费记账：开启 `fee_ledger` 时平台费整笔转入共享费用金库并累加到账本
+struct FeeAccrual<'a, 'info> {
+    ledger: &'a mut FeeLedger,   // 费用账本
+    vault: AccountInfo<'info>,   // 共享费用金库
+}
+
+impl<'a, 'info> FeeAccrual<'a, 'info> {
+    /// 未开启 `fee_ledger` 时返回 `None`；开启后必须传入费用账本和金库
+    fn new(
+        config: &Config,
+        ledger: Option<&'a mut Box<Account<'info, FeeLedger>>>,
+        vault: Option<&InterfaceAccount<'info, token_interface::TokenAccount>>,
+    ) -> Result<Option<Self>> {
+        if !config.settings.fee_ledger {
+            return Ok(None);
+        }
+        let ledger = ledger.ok_or(EscrowError::MissingFeeLedger)?;
+        let vault = vault.ok_or(EscrowError::MissingFeeLedger)?;
+
+        Ok(Some(Self {
+            vault: vault.to_account_info(),
+            ledger,
+        }))
+    }
+}
+
+/// 资金转出的对账备注
+///
+/// 格式为 `datanexus:escrow=<托管地址>;request_id=<需求 ID>;leg=<provider|platform|refund>`，
+/// 紧邻每笔转账之前 CPI 备注程序，也满足 Token-2022 接收账户的转入备注要求
+struct TransferMemo<'info> {
+    program: AccountInfo<'info>, // 备注程序
+    escrow: Pubkey,              // 托管
+    request_id: String,          // 需求 ID
+}
+
+impl<'info> TransferMemo<'info> {
+    /// 未开启 `memo_transfers` 时返回 `None`；开启后必须传入备注程序
+    fn new(
+        config: &Config,
+        memo_program: Option<&UncheckedAccount<'info>>,
+        escrow_key: Pubkey,
+        escrow: &Escrow,
+    ) -> Result<Option<Self>> {
+        if !config.settings.memo_transfers {
+            return Ok(None);
+        }
+        let program = memo_program.ok_or(EscrowError::MissingMemoProgram)?;
+
+        Ok(Some(Self {
+            program: program.to_account_info(),
+            escrow: escrow_key,
+            request_id: escrow.request_id().to_string(),
+        }))
+    }
+
+    fn log(&self, leg: &str) -> Result<()> {
+        let memo = format!(
+            "datanexus:escrow={};request_id={};leg={}",
+            self.escrow, self.request_id, leg
+        );
+        invoke(
+            &Instruction {
+                program_id: MEMO_PROGRAM_ID,
+                accounts: vec![],
+                data: memo.into_bytes(),
+            },
+            std::slice::from_ref(&self.program),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// 质押程序 `Bond` 账户的前缀字段
+///
+/// 质押程序依赖本程序，这里不能反向引用其类型，按相同布局只读解析
+#[derive(AnchorDeserialize)]
+struct ProviderBond {
+    provider: Pubkey,
+    amount: u64,
+}
+
+/// 质押程序 `Bond` 账户鉴别符（`sha256("account:Bond")[..8]`）
+const BOND_DISCRIMINATOR: [u8; 8] = [224, 128, 48, 251, 182, 246, 111, 196];
+
+/// 读取提供商在质押程序中的已质押数量
+///
+/// 校验账户属于质押程序、地址为 `[b"bond", provider]` PDA 且鉴别符为 `Bond`
+fn provider_collateral(bond: &AccountInfo, provider: &Pubkey) -> Result<u64> {
+    let (expected, _) =
+        Pubkey::find_program_address(&[b"bond", provider.as_ref()], &STAKING_PROGRAM_ID);
+    require!(
+        bond.owner == &STAKING_PROGRAM_ID && bond.key() == expected,
+        EscrowError::InvalidCollateralAccount
+    );
+
+    let data = bond.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == BOND_DISCRIMINATOR,
+        EscrowError::InvalidCollateralAccount
+    );
+
+    let bond = ProviderBond::deserialize(&mut &data[8..])
+        .map_err(|_| error!(EscrowError::InvalidCollateralAccount))?;
+    require!(bond.provider == *provider, EscrowError::InvalidCollateralAccount);
+
+    Ok(bond.amount)
+}
+
+/// 争议结束时关闭争议记录，租金退还给记录的 `payer`（发起争议的买家或反对取消的提供商）
+///
+/// 本功能上线前发起的争议没有记录（PDA 未初始化），直接跳过
+fn close_dispute_record<'info>(
+    record: &UncheckedAccount<'info>,
+    buyer: &UncheckedAccount<'info>,
+    provider: &UncheckedAccount<'info>,
+) -> Result<()> {
+    if record.owner != &crate::ID {
+        return Ok(());
+    }
+    // 加入 `payer` 之前创建的记录较短，租金都由买家支付
+    let paid_by_provider = record.data_len() >= DisputeRecord::LEN
+        && DisputeRecord::try_deserialize(&mut &record.try_borrow_data()?[..])?.payer
+            == provider.key();
+    let recipient = if paid_by_provider {
+        provider
+    } else {
+        buyer
+    };
+    let rent = record.lamports();
+    **recipient.try_borrow_mut_lamports()? = recipient.lamports().checked_add(rent).unwrap();
+    **record.try_borrow_mut_lamports()? = 0;
+    record.assign(&system_program::ID);
+    record.resize(0)?;
+
+    Ok(())
+}
+
+/// 创建托管前检查双方：提供商注册、黑名单、高金额徽章和合规证明
+///
+/// `create_escrow`、`recreate_escrow` 和 `create_escrow_from_bridge` 共用
+#[allow(clippy::too_many_arguments)]
+fn check_escrow_parties(
+    settings: &ProtocolSettings,
+    buyer: &Pubkey,
+    provider: &Pubkey,
+    amount: u64,
+    provider_profile: Option<&Account<ProviderProfile>>,
+    blacklists: [&UncheckedAccount; 2],
+    provider_badge: Option<&Account<Badge>>,
+    attestations: [Option<&UncheckedAccount>; 2],
+) -> Result<()> {
+    // 可选：要求提供商已在注册表中注册并处于启用状态
+    if settings.require_registered_provider {
+        let profile = provider_profile.ok_or(EscrowError::ProviderNotRegistered)?;
+        require!(profile.active, EscrowError::ProviderNotRegistered);
+    }
+
+    let [buyer_blacklist, provider_blacklist] = blacklists;
+    check_not_blacklisted(buyer_blacklist)?;
+    check_not_blacklisted(provider_blacklist)?;
+
+    // 可选：高金额托管要求提供商持有平台徽章
+    check_badge(settings, provider_badge, amount)?;
+
+    // 可选：要求买家和/或提供商持有合规证明
+    let [buyer_attestation, provider_attestation] = attestations;
+    check_compliance(settings, COMPLIANCE_BUYER, buyer_attestation, buyer)?;
+    check_compliance(settings, COMPLIANCE_PROVIDER, provider_attestation, provider)?;
+
+    Ok(())
+}
+
+/// 新建托管时统计账户和提供商侧索引的 PDA bump
+struct EscrowStatsBumps {
+    buyer_stats: u8,
+    provider_stats: u8,
+    provider_escrow_index: u8,
+    protocol_stats: u8,
+}
+
+/// 新建托管后更新买家、提供商和协议统计，并写入提供商侧索引
+///
+/// 买家统计按 `record_creation` 限制创建频率；索引序号即该提供商此前的托管总数。
+/// `create_escrow`、`recreate_escrow` 和 `create_escrow_from_bridge` 共用
+#[allow(clippy::too_many_arguments)]
+fn record_escrow_created(
+    settings: &ProtocolSettings,
+    escrow_key: Pubkey,
+    escrow: &Escrow,
+    buyer_stats: &mut BuyerStats,
+    provider_stats: &mut ProviderStats,
+    provider_escrow_index: &mut ProviderEscrowIndex,
+    protocol_stats: &mut ProtocolStats,
+    bumps: EscrowStatsBumps,
+) -> Result<()> {
+    if buyer_stats.buyer == Pubkey::default() {
+        buyer_stats.buyer = escrow.buyer;
+        buyer_stats.bump = bumps.buyer_stats;
+    }
+    buyer_stats.total_escrows = buyer_stats.total_escrows.checked_add(1).unwrap();
+    buyer_stats.record_creation(settings, escrow.created_at)?;
+
+    if provider_stats.provider == Pubkey::default() {
+        provider_stats.provider = escrow.provider;
+        provider_stats.bump = bumps.provider_stats;
+    }
+    provider_escrow_index.provider = escrow.provider;
+    provider_escrow_index.sequence = provider_stats.total_escrows;
+    provider_escrow_index.escrow = escrow_key;
+    provider_escrow_index.bump = bumps.provider_escrow_index;
+    provider_stats.total_escrows = provider_stats.total_escrows.checked_add(1).unwrap();
+
+    if protocol_stats.bump == 0 {
+        protocol_stats.bump = bumps.protocol_stats;
+    }
+    protocol_stats.total_escrows = protocol_stats.total_escrows.checked_add(1).unwrap();
+
+    Ok(())
+}
+
+/// 校验地址未被列入黑名单，`entry` 为该地址的 `[b"blacklist", 地址]` PDA（可以未初始化）
+fn check_not_blacklisted(entry: &UncheckedAccount) -> Result<()> {
+    require!(entry.owner != &crate::ID, EscrowError::Blacklisted);
+    Ok(())
+}
+
+/// 按 `settings.badge_threshold` 校验提供商持有 `required_badge` 类型的有效徽章
+///
+/// 托管金额低于门槛或门槛为 0 时直接通过
+fn check_badge(
+    settings: &ProtocolSettings,
+    badge: Option<&Account<Badge>>,
+    amount: u64,
+) -> Result<()> {
+    if settings.badge_threshold == 0 || amount < settings.badge_threshold {
+        return Ok(());
+    }
+    let badge = badge.ok_or(EscrowError::BadgeRequired)?;
+    require!(
+        badge.badge_type == settings.required_badge,
+        EscrowError::BadgeRequired
+    );
+    require!(
+        badge.is_valid(Clock::get()?.unix_timestamp),
+        EscrowError::InvalidBadge
+    );
+
+    Ok(())
+}
+
+/// Solana Attestation Service 证明账户的字段（去掉账户类型字节）
+#[derive(AnchorDeserialize)]
+struct Attestation {
+    nonce: Pubkey,      // 证明对象（钱包地址）
+    credential: Pubkey, // 签发凭证
+    schema: Pubkey,     // 结构定义
+    _data: Vec<u8>,     // 证明内容
+    _signer: Pubkey,    // 签发人
+    expiry: i64,        // 过期时间（0 为永不过期）
+}
+
+/// 按 `settings.compliance` 校验 `subject` 持有有效的合规证明
+///
+/// `flag` 为 `COMPLIANCE_BUYER` 或 `COMPLIANCE_PROVIDER`，未要求时直接通过；
+/// 证明须属于 Solana Attestation Service，凭证、结构和对象一致且未过期
+fn check_compliance(
+    settings: &ProtocolSettings,
+    flag: u8,
+    attestation: Option<&UncheckedAccount>,
+    subject: &Pubkey,
+) -> Result<()> {
+    if settings.compliance & flag == 0 {
+        return Ok(());
+    }
+    let attestation = attestation.ok_or(EscrowError::ComplianceAttestationRequired)?;
+    require!(
+        attestation.owner == &ATTESTATION_PROGRAM_ID,
+        EscrowError::InvalidComplianceAttestation
+    );
+
+    let data = attestation.try_borrow_data()?;
+    require!(
+        data.first() == Some(&ATTESTATION_ACCOUNT_TYPE),
+        EscrowError::InvalidComplianceAttestation
+    );
+    let attestation = Attestation::deserialize(&mut &data[1..])
+        .map_err(|_| error!(EscrowError::InvalidComplianceAttestation))?;
+    require!(
+        attestation.nonce == *subject
+            && attestation.credential == settings.attestation_credential
+            && attestation.schema == settings.attestation_schema,
+        EscrowError::InvalidComplianceAttestation
+    );
+    require!(
+        attestation.expiry == 0 || Clock::get()?.unix_timestamp < attestation.expiry,
+        EscrowError::InvalidComplianceAttestation
+    );
+
+    Ok(())
+}
+
+/// 读取交付承诺，提供商未承诺（PDA 未初始化）时返回 `None`
+fn read_delivery_commitment(account: &UncheckedAccount) -> Result<Option<DeliveryCommitment>> {
+    let account = account.to_account_info();
+    if account.owner != &crate::ID {
+        return Ok(None);
+    }
+
+    let commitment = DeliveryCommitment::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    Ok(Some(commitment))
+}
+
+/// 提供商开启了销售记录时追加一笔完成的销售，`amount` 为该笔销售的总额（含平台费）
+///
+/// 金额只记录十进制数量级档位（最小单位），类别为托管限制标志的低 16 位
+fn record_sale(
+    sales_history: &UncheckedAccount,
+    restriction: &UncheckedAccount,
+    escrow: Pubkey,
+    amount: u64,
+    timestamp: i64,
+) -> Result<()> {
+    let history_info = sales_history.to_account_info();
+    if history_info.owner != &crate::ID {
+        return Ok(());
+    }
+    let mut history = SalesHistory::try_deserialize(&mut &history_info.try_borrow_data()?[..])?;
+
+    let restriction_info = restriction.to_account_info();
+    let category = if restriction_info.owner == &crate::ID {
+        let restriction =
+            EscrowRestriction::try_deserialize(&mut &restriction_info.try_borrow_data()?[..])?;
+        restriction.flags as u16
+    } else {
+        0
+    };
+    let amount_bucket = amount.checked_ilog10().unwrap_or(0) as u8;
+
+    let leaf = hashv(&[
+        &[0],
+        escrow.as_ref(),
+        &[amount_bucket],
+        &category.to_le_bytes(),
+        &timestamp.to_le_bytes(),
+    ])
+    .to_bytes();
+    let index = history.count;
+    history.append(leaf)?;
+    history.try_serialize(&mut &mut history_info.try_borrow_mut_data()?[..])?;
+
+    emit!(SaleRecorded {
+        provider: history.provider,
+        escrow,
+        index,
+        amount_bucket,
+        category,
+        timestamp,
+        root: history.root,
+    });
+
+    Ok(())
+}
+
+/// 由数据块哈希和 Merkle 证明计算根
+///
+/// 叶子为 `sha256(0x00 || 块哈希)`，内部节点为 `sha256(0x01 || 左 || 右)`，
+/// 某层节点数为奇数时最后一个节点与自身配对
+fn chunk_merkle_root(index: u32, chunk_hash: &[u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+    let mut node = hashv(&[&[0], chunk_hash]).to_bytes();
+    let mut position = index;
+    for sibling in proof {
+        node = if position.is_multiple_of(2) {
+            hashv(&[&[1], &node, sibling])
+        } else {
+            hashv(&[&[1], sibling, &node])
+        }
+        .to_bytes();
+        position /= 2;
+    }
+    node
+}
+
+/// 写入待生效的配置变更，公示期从现在开始计算
+fn queue_config_change(
+    pending: &mut PendingConfigChange,
+    change: ConfigChange,
+    payer: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    pending.change = change;
+    pending.payer = payer;
+    pending.queued_at = now;
+    pending.execute_after = now.checked_add(CONFIG_TIMELOCK).unwrap();
+    pending.bump = bump;
+
+    Ok(())
+}
+
+/// Pyth 价格（`price × 10^exponent` 美元）
+struct PythPrice {
+    price: i64,
+    exponent: i32,
+}
+
+/// 解析 Pyth `PriceUpdateV2` 账户，校验属主、鉴别符、完全验证、价格源和新鲜度
+///
+/// 依赖的 Pyth SDK 与当前 Solana 版本不兼容，因此按其布局手工读取：
+/// 鉴别符、write_authority、verification_level（Partial 多 1 字节签名数）、
+/// feed_id、price、conf、exponent、publish_time
+fn read_pyth_price(price_update: &AccountInfo, feed_id: &[u8; 32], now: i64) -> Result<PythPrice> {
+    require!(
+        *price_update.owner == PYTH_RECEIVER_PROGRAM_ID,
+        EscrowError::InvalidPriceFeed
+    );
+    let data = price_update.try_borrow_data()?;
+    require!(
+        data.len() >= 8 + 32 + 1 + 32 + 8 + 8 + 4 + 8
+            && data[..8] == PRICE_UPDATE_DISCRIMINATOR,
+        EscrowError::InvalidPriceFeed
+    );
+
+    // 只接受完全验证（Full = 1）的价格
+    require!(data[40] == 1, EscrowError::InvalidPriceFeed);
+    let message = &data[41..];
+    require!(message[..32] == feed_id[..], EscrowError::InvalidPriceFeed);
+
+    let price = i64::from_le_bytes(message[32..40].try_into().unwrap());
+    let exponent = i32::from_le_bytes(message[48..52].try_into().unwrap());
+    let publish_time = i64::from_le_bytes(message[52..60].try_into().unwrap());
+
+    require!(price > 0, EscrowError::InvalidPriceFeed);
+    require!(
+        now.saturating_sub(publish_time) <= MAX_PRICE_AGE,
+        EscrowError::StalePrice
+    );
+
+    Ok(PythPrice { price, exponent })
+}
+
+/// 解析核心桥已验证的 `PostedVAA` 账户，返回来源链 ID 和代币桥转账中的 `BridgePayment`
+///
+/// 按核心桥布局手工读取：`b"vaa"`、版本、一致性级别、时间、签名集、提交时间、nonce、
+/// 序号、来源链、发送方，随后是 u32 长度前缀的载荷。载荷为代币桥附带载荷的转账（大端）：
+/// 类型、数量、代币地址、代币链、接收方、接收链、发送方，其后为应用载荷。
+/// 只接受发往本程序的包装代币转账，来源链注册由代币桥在兑付时校验
+fn read_bridge_payment(posted_vaa: &AccountInfo) -> Result<(u16, BridgePayment)> {
+    require!(
+        *posted_vaa.owner == WORMHOLE_PROGRAM_ID,
+        EscrowError::InvalidBridgePayment
+    );
+    let data = posted_vaa.try_borrow_data()?;
+    require!(
+        data.len() >= 95 && data[..3] == *b"vaa",
+        EscrowError::InvalidBridgePayment
+    );
+    let emitter_chain = u16::from_le_bytes([data[57], data[58]]);
+    let payload_len = u32::from_le_bytes(data[91..95].try_into().unwrap()) as usize;
+    let payload = data
+        .get(95..95 + payload_len)
+        .ok_or(EscrowError::InvalidBridgePayment)?;
+
+    require!(
+        payload.len() > 133
+            && payload[0] == TRANSFER_WITH_PAYLOAD
+            && payload[65..67] != WORMHOLE_SOLANA_CHAIN.to_be_bytes()
+            && payload[67..99] == crate::ID.to_bytes()
+            && payload[99..101] == WORMHOLE_SOLANA_CHAIN.to_be_bytes(),
+        EscrowError::InvalidBridgePayment
+    );
+    let payment = BridgePayment::deserialize(&mut &payload[133..])
+        .map_err(|_| error!(EscrowError::InvalidBridgePayment))?;
+
+    Ok((emitter_chain, payment))
+}
+
+/// 以托管 PDA 签名调用借贷程序的存入或赎回指令
+///
+/// 两条指令的账户依次为：源代币账户、目标代币账户、储备、两个储备代币账户（以上可写）、
+/// 借贷市场、市场权限、转账权限（托管）、代币程序；最后一个为借贷程序本身
+fn lending_cpi<'info>(
+    tag: u8,
+    amount: u64,
+    accounts: [AccountInfo<'info>; 10],
+    escrow: &Escrow,
+) -> Result<()> {
+    let metas = accounts[..9]
+        .iter()
+        .enumerate()
+        .map(|(i, account)| match i {
+            0..=4 => AccountMeta::new(account.key(), false),
+            _ => AccountMeta::new_readonly(account.key(), i == 7),
+        })
+        .collect();
+    let mut data = vec![tag];
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let seeds = &[
+        b"escrow",
+        escrow.buyer.as_ref(),
+        escrow.request_id().as_bytes(),
+        &[escrow.bump],
+    ];
+    invoke_signed(
+        &Instruction {
+            program_id: SOLEND_PROGRAM_ID,
+            accounts: metas,
+            data,
+        },
+        &accounts,
+        &[&seeds[..]],
+    )?;
+
+    Ok(())
+}
+
+/// 代币数量按价格折算的美元价值（6 位小数）
+fn usd_value(amount: u64, decimals: u8, price: &PythPrice) -> Result<u64> {
+    let value = amount as u128 * price.price as u128;
+    let scale = price.exponent + 6 - decimals as i32;
+    let value = if scale >= 0 {
+        value.checked_mul(10u128.pow(scale as u32))
+    } else {
+        value.checked_div(10u128.pow(scale.unsigned_abs()))
+    };
+
+    value
+        .and_then(|value| u64::try_from(value).ok())
+        .ok_or_else(|| error!(EscrowError::InvalidAmount))
+}
+
+/// 美分价格按 Pyth 价格折算的代币数量（向下取整）
+fn usd_token_amount(usd_cents: u64, decimals: u8, price: &PythPrice) -> Result<u64> {
+    // 代币数量 = 美分 × 10^decimals / (100 × price × 10^exponent)
+    let scale = decimals as i32 - price.exponent;
+    let factor = 10u128.checked_pow(scale.unsigned_abs());
+    let (numerator, denominator) = if scale >= 0 {
+        (factor.and_then(|f| (usd_cents as u128).checked_mul(f)), Some(100 * price.price as u128))
+    } else {
+        (Some(usd_cents as u128), factor.and_then(|f| (100 * price.price as u128).checked_mul(f)))
+    };
+
+    numerator
+        .zip(denominator)
+        .and_then(|(numerator, denominator)| u64::try_from(numerator / denominator).ok())
+        .ok_or_else(|| error!(EscrowError::InvalidAmount))
+}
+
+/// 两个 Pyth 价格的相对偏离（基点，以 `base` 为基准）
+fn price_deviation_bps(base: &PythPrice, price: &PythPrice) -> u64 {
+    // 统一到较小的指数后比较
+    let exponent = base.exponent.min(price.exponent);
+    let normalize = |p: &PythPrice| {
+        (p.price as u128).saturating_mul(10u128.saturating_pow((p.exponent - exponent) as u32))
+    };
+    let (base, price) = (normalize(base), normalize(price));
+    let deviation = base.abs_diff(price).saturating_mul(BPS_DENOMINATOR as u128) / base;
+
+    u64::try_from(deviation).unwrap_or(u64::MAX)
+}
+
+/// 把程序所有的账户调整为 `len` 字节，`payer` 补足所需的租金（迁移旧布局时使用）
+fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    len: usize,
+) -> Result<()> {
+    let rent_due = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account.lamports());
+    if rent_due > 0 {
+        let cpi_accounts = system_program::Transfer {
+            from: payer.to_account_info(),
+            to: account.clone(),
+        };
+        let cpi_program = system_program.to_account_info();
+        system_program::transfer(CpiContext::new(cpi_program, cpi_accounts), rent_due)?;
+    }
+    account.resize(len)?;
+    Ok(())
+}
+
+/// 争议记录中登记的争议保证金与上诉保证金之和
+///
+/// 托管代币账户中的其他余额不计入保证金；没有争议记录或记录早于保证金登记时结果为 0
+fn dispute_bond(record: &UncheckedAccount) -> Result<u64> {
+    if record.owner != &crate::ID || record.data_len() < DisputeRecord::LEN {
+        return Ok(0);
+    }
+    let record = DisputeRecord::try_deserialize(&mut &record.try_borrow_data()?[..])?;
+
+    Ok(record.bond.checked_add(record.appeal_bond).unwrap())
+}
+
+/// 美元计价的托管按新鲜的 Pyth 价格折算本次应付的代币数量，并写入计价账户
+///
+/// 价格偏离计价时超过 `max_price_deviation_bps` 或折算结果超过托管金额时拒绝；
+/// 未按美元计价的托管返回 `None`。确认释放和争议裁决释放都只支付折算数量，其余退还买家
+fn usd_release_amount(
+    usd_pricing: &UncheckedAccount,
+    price_update: Option<&UncheckedAccount>,
+    settings: &ProtocolSettings,
+    escrowed_amount: u64,
+) -> Result<Option<u64>> {
+    if usd_pricing.owner != &crate::ID {
+        return Ok(None);
+    }
+
+    let mut pricing = UsdPricing::try_deserialize(&mut &usd_pricing.try_borrow_data()?[..])?;
+    let price_update = price_update.ok_or(EscrowError::MissingPriceFeed)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let price = read_pyth_price(price_update, &pricing.feed_id, now)?;
+    let max_deviation = settings.max_price_deviation_bps as u64;
+    let priced_at = PythPrice {
+        price: pricing.price,
+        exponent: pricing.exponent,
+    };
+    require!(
+        max_deviation == 0 || price_deviation_bps(&priced_at, &price) <= max_deviation,
+        EscrowError::PriceDeviation
+    );
+
+    let amount = usd_token_amount(pricing.usd_cents, pricing.decimals, &price)?;
+    require!(amount <= escrowed_amount, EscrowError::UsdPriceNotCovered);
+
+    pricing.released_amount = Some(amount);
+    pricing.try_serialize(&mut &mut usd_pricing.try_borrow_mut_data()?[..])?;
+
+    msg!("USD price: {} cents = {} tokens", pricing.usd_cents, amount);
+
+    Ok(Some(amount))
+}
+
+/// 创建托管时兑换的优惠券（未兑换时为 None）
+///
+/// 释放路径（确认释放、争议裁决）都从平台费中按券面减免，减免部分退还买家
+fn escrow_discount(discount: &UncheckedAccount) -> Result<Option<EscrowDiscount>> {
+    if discount.owner != &crate::ID {
+        return Ok(None);
+    }
+    let discount = EscrowDiscount::try_deserialize(&mut &discount.try_borrow_data()?[..])?;
+
+    Ok(Some(discount))
+}
+
+/// 资金释放后给买家铸造 1 枚数据访问凭证（托管未配置访问凭证时跳过）
+///
+/// 凭证铸币可以是普通 SPL Token，也可以是 Token-2022 不可转让代币，
+/// 提供商的交付网关通过检查买家是否持有凭证来授权下载
+fn mint_access_token<'info>(
+    escrow: &Escrow,
+    access_mint: &Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    buyer_access_account: &Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    access_authority: &Option<UncheckedAccount<'info>>,
+    access_authority_bump: Option<u8>,
+    access_token_program: &Option<Interface<'info, TokenInterface>>,
+    access_window: &UncheckedAccount<'info>,
+) -> Result<()> {
+    if escrow.access_mint().is_none() {
+        return Ok(());
+    }
+    let (
+        Some(access_mint),
+        Some(buyer_access_account),
+        Some(access_authority),
+        Some(access_authority_bump),
+        Some(access_token_program),
+    ) = (
+        access_mint,
+        buyer_access_account,
+        access_authority,
+        access_authority_bump,
+        access_token_program,
+    )
+    else {
+        return err!(EscrowError::MissingAccessAccounts);
+    };
+
+    let seeds = &[
+        b"access".as_ref(),
+        escrow.provider.as_ref(),
+        &[access_authority_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = token_interface::MintTo {
+        mint: access_mint.to_account_info(),
+        to: buyer_access_account.to_account_info(),
+        authority: access_authority.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        access_token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    token_interface::mint_to(cpi_ctx, 1)?;
+
+    msg!("Access token minted: {}", access_mint.key());
+
+    // 设置过访问有效期时，从释放时刻起算到期时间
+    let window_info = access_window.to_account_info();
+    if window_info.owner == &crate::ID {
+        let mut window = AccessWindow::try_deserialize(&mut &window_info.try_borrow_data()?[..])?;
+        let expires_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(window.duration)
+            .unwrap();
+        window.expires_at = Some(expires_at);
+        window.token_account = buyer_access_account.key();
+        window.try_serialize(&mut &mut window_info.try_borrow_mut_data()?[..])?;
+
+        msg!("Access expires at: {}", expires_at);
+    }
+
+    Ok(())
+}
+
+/// 平台全局配置
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,           // 管理员
+    pub treasury: Pubkey,        // 国库代币账户
+    pub rewards_pool: Pubkey,    // 质押奖励池代币账户
+    pub treasury_bps: u16,       // 国库分成（基点）
+    pub rewards_pool_bps: u16,   // 奖励池分成（基点）
+    pub receipt_tree: Pubkey,    // 压缩收据 Merkle 树（未设置时为默认值）
+    pub settings: ProtocolSettings, // 协议策略参数
+    pub bump: u8,                // PDA bump
+}
+
+impl Config {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// 把旧布局的配置账户数据补齐为当前布局
+    ///
+    /// `ProtocolSettings` 的新字段都追加在末尾且以 0 为默认值（不限制或不启用），
+    /// 因此在末尾的 `bump` 之前补零即可；补齐后须能按当前布局读取
+    pub fn migrate(data: &[u8]) -> Result<Vec<u8>> {
+        require!(data.len() < Self::LEN, EscrowError::AlreadyMigrated);
+        let (bump, fields) = data
+            .split_last()
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        let mut migrated = fields.to_vec();
+        migrated.resize(Self::LEN - 1, 0);
+        migrated.push(*bump);
+        Self::try_deserialize(&mut &migrated[..])?;
+
+        Ok(migrated)
+    }
+}
+
+/// 排队中的配置变更
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum ConfigChange {
+    /// `update_config`：平台费分配目标和基点
+    FeeSplit {
+        treasury: Pubkey,
+        rewards_pool: Pubkey,
+        treasury_bps: u16,
+        rewards_pool_bps: u16,
+    },
+    /// `update_settings`：协议策略参数
+    Settings(ProtocolSettings),
+}
+
+/// 待生效的配置变更，同一时间只有一个 `[b"pending_config"]`
+#[account]
+#[derive(InitSpace)]
+pub struct PendingConfigChange {
+    pub change: ConfigChange,    // 变更内容
+    pub payer: Pubkey,           // 租金付款人，关闭时退还
+    pub queued_at: i64,          // 排队时间
+    pub execute_after: i64,      // 最早执行时间
+    pub bump: u8,                // PDA bump
+}
+
+impl PendingConfigChange {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 协议策略参数
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq, Eq, InitSpace)]
+pub struct ProtocolSettings {
+    pub require_registered_provider: bool, // 创建托管时要求提供商已注册
+    pub insurance_bps: u16,                // 平台费划入保险池的比例（基点）
+    pub dispute_bond: u64,                 // 买家发起争议需缴纳的保证金（6 位小数）
+    pub collateral_threshold: u64,         // 托管金额达到该值时交付前要求提供商保证金（0 为不要求）
+    pub collateral_bps: u16,               // 要求的保证金占托管金额的比例（基点）
+    pub appeal_window: i64,                // 平台裁决后的上诉期（秒，0 为裁决立即生效）
+    pub appeal_bond: u64,                  // 上诉需缴纳的保证金（6 位小数）
+    pub yield_buyer_bps: u16,              // 收益模式的收益中归买家的比例（基点），其余归国库
+    pub memo_transfers: bool,              // 每笔资金转出前附加 SPL Memo 对账备注
+    pub compliance: u8,                    // 创建和释放时须持有合规证明的一方（`COMPLIANCE_*` 标志）
+    pub attestation_credential: Pubkey,    // 合规证明的签发凭证（Solana Attestation Service）
+    pub attestation_schema: Pubkey,        // 合规证明的结构定义
+    pub late_refund_bps_per_day: u16,      // 逾期交付每天退还买家的提供商份额比例（基点）
+    pub late_refund_cap_bps: u16,          // 逾期退还比例上限（基点）
+    pub stale_escrow_ttl: i64,             // 未注资托管可被回收前的存活时间（秒，0 为不回收）
+    pub badge_threshold: u64,              // 托管金额达到该值时要求提供商持有徽章（0 为不要求）
+    pub required_badge: u8,                // 要求的徽章类型（`BADGE_*`）
+    pub max_open_escrows: u64,             // 每个买家未进入终态的托管数上限（0 为不限）
+    pub max_daily_escrows: u64,            // 每个买家每天可创建的托管数上限（0 为不限）
+    pub max_price_deviation_bps: u16,      // 美元计价托管释放时的价格偏离上限（基点，0 为不限）
+    pub fee_ledger: bool,                  // 平台费整笔转入共享费用金库，由 `settle_fees` 定期拆分
+}
+
+/// 买家发起的争议记录，每个托管一条
+///
+/// 租金由买家在 `raise_dispute` 时支付，争议结束（退款、阻止释放或裁决执行）时关闭并退还买家
+#[account]
+#[derive(InitSpace)]
+pub struct DisputeRecord {
+    pub escrow: Pubkey,            // 托管
+    pub buyer: Pubkey,             // 发起争议的买家
+    pub raised_at: i64,            // 发起时间
+    pub disputed_chunks: u32,      // 按块争议的数据块数（0 为整体争议）
+    pub bond: u64,                 // 买家缴纳的争议保证金
+    pub appeal_bond: u64,          // 上诉方缴纳的上诉保证金
+    pub payer: Pubkey,             // 支付记录租金的一方，争议结束时租金退还给它
+    pub bump: u8,                  // PDA bump
+}
+
+impl DisputeRecord {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 平台的可上诉裁决，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct Ruling {
+    pub escrow: Pubkey,            // 托管
+    pub refund_to_buyer: bool,     // 裁决结果
+    pub ruled_at: i64,             // 裁决时间
+    pub appeal_deadline: i64,      // 上诉截止时间
+    pub appellant: Option<Pubkey>, // 上诉方
+    pub bump: u8,                  // PDA bump
+}
+
+impl Ruling {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 托管要求的交付证明预言机，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct DeliveryOracle {
+    pub escrow: Pubkey,          // 托管
+    pub oracle: Pubkey,          // 预言机签名公钥
+    pub bump: u8,                // PDA bump
+}
+
+impl DeliveryOracle {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 加密的数据集解密密钥交付，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct KeyDelivery {
+    pub escrow: Pubkey,             // 托管
+    pub buyer_key: [u8; 32],        // 买家 X25519 公钥
+    #[max_len(MAX_ENCRYPTED_KEY_LEN)]
+    pub encrypted_key: Vec<u8>,     // 用买家公钥加密的解密密钥（提交前为空）
+    pub delivered_at: Option<i64>,  // 提交时间
+    pub bump: u8,                   // PDA bump
+}
+
+impl KeyDelivery {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 提供商的交付承诺，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct DeliveryCommitment {
+    pub escrow: Pubkey,               // 托管
+    pub commitment: [u8; 32],         // sha256(存储 URI || salt)
+    pub storage: Option<StorageRef>,  // 揭示的存储位置
+    pub salt: [u8; 32],               // 揭示的 salt
+    pub revealed_at: Option<i64>,     // 揭示时间
+    pub bump: u8,                     // PDA bump
+}
+
+impl DeliveryCommitment {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 存储协议
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum StorageProtocol {
+    Ipfs,                                    // IPFS，ID 为 CIDv1（base32）
+    Arweave,                                 // Arweave，ID 为交易 ID（base64url）
+    ShadowDrive { storage_account: Pubkey }, // Shadow Drive，ID 为存储账户内的对象路径
+}
+
+/// 交付内容的存储位置，索引器和网关据此解析交付
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub struct StorageRef {
+    pub protocol: StorageProtocol, // 存储协议
+    #[max_len(MAX_STORAGE_ID_LEN)]
+    pub id: String,                // CID 或交易 ID
+}
+
+impl StorageRef {
+    /// 按协议校验 ID 的长度和字符集
+    pub fn validate(&self) -> Result<()> {
+        let id = self.id.as_bytes();
+        let valid = match self.protocol {
+            StorageProtocol::Ipfs => {
+                id.len() == IPFS_CID_V1_LEN
+                    && id[0] == b'b'
+                    && id[1..].iter().all(|c| matches!(c, b'a'..=b'z' | b'2'..=b'7'))
+            }
+            StorageProtocol::Arweave => {
+                id.len() == ARWEAVE_TX_ID_LEN
+                    && id
+                        .iter()
+                        .all(|c| c.is_ascii_alphanumeric() || *c == b'-' || *c == b'_')
+            }
+            StorageProtocol::ShadowDrive { .. } => {
+                !id.is_empty()
+                    && id.len() <= MAX_STORAGE_ID_LEN
+                    && id[0] != b'/'
+                    && id.iter().all(|c| {
+                        c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b'.' | b'/')
+                    })
+            }
+        };
+        require!(valid, EscrowError::InvalidStorageRef);
+
+        Ok(())
+    }
+
+    /// 网关通用的 URI：`ipfs://<CID>`、`ar://<交易 ID>` 或 Shadow Drive 网关上的对象地址
+    pub fn uri(&self) -> String {
+        match self.protocol {
+            StorageProtocol::Ipfs => format!("ipfs://{}", self.id),
+            StorageProtocol::Arweave => format!("ar://{}", self.id),
+            StorageProtocol::ShadowDrive { storage_account } => {
+                format!("{}/{}/{}", SHADOW_DRIVE_GATEWAY, storage_account, self.id)
+            }
+        }
+    }
+}
+
+/// 交付的数据块清单，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct ChunkManifest {
+    pub escrow: Pubkey,             // 托管
+    pub root: [u8; 32],             // 数据块哈希的 Merkle 根
+    pub chunk_count: u32,           // 数据块数
+    #[max_len(MAX_DISPUTED_CHUNKS)]
+    pub disputed_chunks: Vec<u32>,  // 买家争议的数据块序号
+    pub bump: u8,                   // PDA bump
+}
+
+impl ChunkManifest {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// 校验争议数据块的 Merkle 证明并记录其序号
+    ///
+    /// 证明长度须等于树高 `ceil(log2(chunk_count))`，同一块不能重复
+    pub fn dispute(&mut self, chunks: &[ChunkProof]) -> Result<()> {
+        require!(
+            self.disputed_chunks.len() + chunks.len() <= MAX_DISPUTED_CHUNKS,
+            EscrowError::TooManyDisputedChunks
+        );
+
+        for chunk in chunks {
+            require!(
+                self.verify(chunk) && !self.disputed_chunks.contains(&chunk.index),
+                EscrowError::InvalidChunkProof
+            );
+            self.disputed_chunks.push(chunk.index);
+        }
+
+        Ok(())
+    }
+
+    /// 数据块序号在范围内，证明长度等于树高且能推出清单的根
+    pub fn verify(&self, chunk: &ChunkProof) -> bool {
+        let depth = self.chunk_count.next_power_of_two().trailing_zeros() as usize;
+        chunk.index < self.chunk_count
+            && chunk.proof.len() == depth
+            && chunk_merkle_root(chunk.index, &chunk.chunk_hash, &chunk.proof) == self.root
+    }
+}
+
+/// 买家要求的数据集属性证明，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct DatasetProof {
+    pub escrow: Pubkey,             // 托管
+    pub verifier: Pubkey,           // 零知识证明验证程序
+    pub row_count: u64,             // 声明的行数
+    pub schema_hash: [u8; 32],      // 声明的表结构哈希
+    pub verified_at: Option<i64>,   // 证明通过时间
+    pub bump: u8,                   // PDA bump
+}
+
+impl DatasetProof {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// 验证程序的公开输入：交付哈希、表结构哈希、行数（32 字节大端）
+    pub fn public_inputs(&self, delivery_hash: &[u8; 32]) -> Vec<[u8; 32]> {
+        let mut row_count = [0u8; 32];
+        row_count[24..].copy_from_slice(&self.row_count.to_be_bytes());
+        vec![*delivery_hash, self.schema_hash, row_count]
+    }
+}
+
+/// 买卖双方约定的交付条款，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct DeliveryTerms {
+    pub escrow: Pubkey,             // 托管
+    pub deadline: i64,              // 交付截止时间
+    pub bump: u8,                   // PDA bump
+    pub late_penalty: u64,          // 逾期交付的固定罚金，从提供商份额中扣除
+    pub penalty_recipient: PenaltyRecipient, // 罚金归属
+}
+
+/// 逾期罚金的归属
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum PenaltyRecipient {
+    Buyer,    // 退还买家
+    Platform, // 转入国库
+}
+
+impl DeliveryTerms {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// 逾期天数，不足一天按一天计
+    pub fn days_late(&self, delivered_at: i64) -> u64 {
+        if delivered_at <= self.deadline {
+            return 0;
+        }
+        (delivered_at.saturating_sub(self.deadline) as u64).div_ceil(24 * 60 * 60)
+    }
+
+    /// 逾期退还比例（基点）：每逾期一天 `late_refund_bps_per_day`，不超过 `late_refund_cap_bps`
+    pub fn late_refund_bps(&self, delivered_at: i64, settings: &ProtocolSettings) -> u64 {
+        self.days_late(delivered_at)
+            .saturating_mul(settings.late_refund_bps_per_day as u64)
+            .min(settings.late_refund_cap_bps as u64)
+    }
+
+    /// 逾期时应扣除的固定罚金，不超过提供商剩余份额
+    pub fn late_penalty(&self, delivered_at: i64, provider_amount: u64) -> u64 {
+        if self.days_late(delivered_at) == 0 {
+            return 0;
+        }
+        self.late_penalty.min(provider_amount)
+    }
+}
+
+/// 创建托管时兑换的优惠券，释放时据此减免平台费，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowDiscount {
+    pub escrow: Pubkey,             // 托管
+    pub coupon: Pubkey,             // 兑换的优惠券（按平台模板减免时为模板）
+    pub discount: Discount,         // 券面
+    pub bump: u8,                   // PDA bump
+}
+
+impl EscrowDiscount {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// 从平台费中减免并退还买家的金额，不超过平台费
+    pub fn amount(&self, platform_fee: u64) -> u64 {
+        match self.discount {
+            Discount::FeeBps(bps) => {
+                (platform_fee as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+            }
+            Discount::Rebate(rebate) => rebate.min(platform_fee),
+        }
+    }
+}
+
+/// 提供商对托管金额的报价及有效期，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowQuote {
+    pub escrow: Pubkey,             // 托管
+    pub price: u64,                 // 报价（充值时按此金额转入）
+    pub expires_at: i64,            // 报价有效期，过期后不能充值
+    pub quoted_at: i64,             // 报价时间
+    pub bump: u8,                   // PDA bump
+}
+
+impl EscrowQuote {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 按件计价托管的单价、数量和已交付件数，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowUnits {
+    pub escrow: Pubkey,             // 托管
+    pub unit_price: u64,            // 单价
+    pub quantity: u64,              // 购买件数
+    pub delivered: u64,             // 已交付并结算的件数
+    pub released: u64,              // 已释放的货款（含平台费）
+    pub bump: u8,                   // PDA bump
+}
+
+impl EscrowUnits {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 按下载计费托管的网关与剩余下载次数，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowDownloads {
+    pub escrow: Pubkey,             // 托管
+    pub gateway: Pubkey,            // 提供商网关（签名调用 consume_download）
+    pub total: u64,                 // 下载上限
+    pub downloads_remaining: u64,   // 剩余可下载次数
+    pub bump: u8,                   // PDA bump
+    pub released: u64,              // 已释放的货款（含平台费，销售记录用）
+}
+
+impl EscrowDownloads {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 提供商的可验证销售记录，每个提供商一条
+///
+/// 每笔完成的销售追加一个叶子 `sha256(0x00 || 托管 || 金额档位 || 类别 || 完成时间)`
+/// 到固定深度的增量 Merkle 树，只保存各层最右侧的子树哈希和当前根；
+/// 叶子随 `SaleRecorded` 事件发出，提供商据此向新买家出示包含证明
+#[account]
+#[derive(InitSpace)]
+pub struct SalesHistory {
+    pub provider: Pubkey,                          // 提供商
+    pub count: u64,                                // 已记录的销售数
+    pub root: [u8; 32],                            // 当前 Merkle 根
+    pub frontier: [[u8; 32]; SALES_TREE_DEPTH],    // 各层最右侧的左子树哈希
+    pub bump: u8,                                  // PDA bump
+}
+
+impl SalesHistory {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// 空树的根（所有叶子为零哈希）
+    pub fn empty_root() -> [u8; 32] {
+        (0..SALES_TREE_DEPTH).fold([0; 32], |node, _| hashv(&[&[1], &node, &node]).to_bytes())
+    }
+
+    /// 追加一个叶子并更新根，内部节点为 `sha256(0x01 || 左 || 右)`，空位为零子树哈希
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<()> {
+        require!(
+            self.count < 1 << SALES_TREE_DEPTH,
+            EscrowError::SalesHistoryFull
+        );
+
+        let mut node = leaf;
+        let mut zero = [0; 32];
+        let mut position = self.count;
+        for level in 0..SALES_TREE_DEPTH {
+            node = if position.is_multiple_of(2) {
+                self.frontier[level] = node;
+                hashv(&[&[1], &node, &zero])
+            } else {
+                hashv(&[&[1], &self.frontier[level], &node])
+            }
+            .to_bytes();
+            zero = hashv(&[&[1], &zero, &zero]).to_bytes();
+            position /= 2;
+        }
+
+        self.root = node;
+        self.count += 1;
+        Ok(())
+    }
+}
+
+/// 平台签发的提供商徽章，每个提供商每种类型一条
+#[account]
+#[derive(InitSpace)]
+pub struct Badge {
+    pub provider: Pubkey,          // 提供商
+    pub badge_type: u8,            // 徽章类型（`BADGE_*`）
+    pub issued_at: i64,            // 签发时间
+    pub expires_at: i64,           // 过期时间（0 为长期有效）
+    pub bump: u8,                  // PDA bump
+}
+
+impl Badge {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// 徽章在 `now` 时是否有效
+    pub fn is_valid(&self, now: i64) -> bool {
+        self.expires_at == 0 || now < self.expires_at
+    }
+}
+
+/// 平台黑名单条目，每个地址一条，账户存在即视为已列入
+#[account]
+#[derive(InitSpace)]
+pub struct Blacklist {
+    pub subject: Pubkey,           // 被列入的地址
+    pub reason_hash: [u8; 32],     // 链下处置记录的哈希
+    pub added_at: i64,             // 列入时间
+    pub bump: u8,                  // PDA bump
+}
+
+impl Blacklist {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 可复用的托管条款模板，由买家或平台保存
+#[account]
+#[derive(InitSpace)]
+pub struct Template {
+    pub owner: Pubkey,              // 所有者（买家或平台管理员）
+    pub template_id: u64,           // 所有者下的模板编号
+    pub mint: Pubkey,               // 托管代币铸币
+    pub terms: TemplateTerms,       // 创建托管时写入的条款
+    pub bump: u8,                   // PDA bump
+}
+
+impl Template {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 模板条款
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct TemplateTerms {
+    pub provider: Option<Pubkey>,   // 限定提供商（为空时不限）
+    pub discount: Option<Discount>, // 平台费减免（仅平台模板）
+    pub delivery_window: i64,       // 交付期限，从创建托管时起算（秒，0 为不设）
+    pub late_penalty: u64,          // 逾期交付的固定罚金
+    pub penalty_recipient: PenaltyRecipient, // 罚金归属
+    pub milestones: u64,            // 里程碑数（0 或 1 为一次性交付）
+}
+
+/// 买家追加的提前交付奖励金，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowBonus {
+    pub escrow: Pubkey,             // 托管
+    pub amount: u64,                // 未结算的奖励金（结算后清零）
+    pub deadline: i64,              // 奖励截止时间，此前交付才能获得奖励
+    pub settled_at: Option<i64>,    // 结算时间
+    pub bump: u8,                   // PDA bump
+}
+
+impl EscrowBonus {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 收据记录，每个托管一条
+///
+/// 收据 NFT 和压缩收据的元数据 URI 指向该账户，托管关闭后仍可查到成交金额、交付哈希和各阶段时间
+#[account]
+#[derive(InitSpace)]
+pub struct ReceiptRecord {
+    pub escrow: Pubkey,             // 托管
+    pub buyer: Pubkey,              // 买家
+    pub provider: Pubkey,           // 提供商
+    pub amount: u64,                // 实际释放的金额
+    pub delivery_hash: [u8; 32],    // 交付内容哈希
+    pub created_at: i64,            // 托管创建时间
+    pub funded_at: i64,             // 充值时间
+    pub delivered_at: i64,          // 交付时间
+    pub completed_at: i64,          // 完成时间
+    pub bump: u8,                   // PDA bump
+}
+
+impl ReceiptRecord {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// 从已完成的托管抄录成交信息，`amount` 为本次实际释放的金额
+    pub fn record(&mut self, escrow_key: Pubkey, escrow: &Escrow, amount: u64, bump: u8) {
+        self.escrow = escrow_key;
+        self.buyer = escrow.buyer;
+        self.provider = escrow.provider;
+        self.amount = amount;
+        self.delivery_hash = escrow.delivery_hash;
+        self.created_at = escrow.created_at;
+        self.funded_at = escrow.funded_at;
+        self.delivered_at = escrow.delivered_at;
+        self.completed_at = escrow.completed_at;
+        self.bump = bump;
+    }
+
+    /// 收据元数据 URI：`RECEIPT_URI_PREFIX` 后接收据记录地址
+    pub fn uri(receipt_record: &Pubkey) -> String {
+        format!("{}{}", RECEIPT_URI_PREFIX, receipt_record)
+    }
+}
+
+/// 买家拒收交付的记录，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct DeliveryRejection {
+    pub escrow: Pubkey,             // 托管
+    pub count: u8,                  // 拒收次数
+    pub reason_hash: [u8; 32],      // 最近一次拒收说明的哈希
+    pub rejected_at: i64,           // 最近一次拒收时间
+    pub deadline: i64,              // 重新交付截止时间
+    pub bump: u8,                   // PDA bump
+}
+
+impl DeliveryRejection {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 买家的取消申请，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct CancelRequest {
+    pub escrow: Pubkey,             // 托管
+    pub requested_at: i64,          // 申请时间
+    pub deadline: i64,              // 提供商异议截止时间
+    pub bump: u8,                   // PDA bump
+}
+
+impl CancelRequest {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 买卖双方的协商记录（只追加），每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct MessageLog {
+    pub escrow: Pubkey,             // 托管
+    #[max_len(MAX_MESSAGES)]
+    pub entries: Vec<MessageEntry>, // 按时间顺序的消息哈希
+    pub bump: u8,                   // PDA bump
+}
+
+impl MessageLog {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 一条协商消息的记录
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub struct MessageEntry {
+    pub author: Pubkey,             // 记录人（买家或提供商）
+    pub message_hash: [u8; 32],     // 链下消息的哈希
+    pub timestamp: i64,             // 记录时间
+}
+
+/// 数据访问凭证的有效期，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct AccessWindow {
+    pub escrow: Pubkey,             // 托管
+    pub duration: i64,              // 有效期（秒）
+    pub expires_at: Option<i64>,    // 到期时间（释放时设置）
+    pub token_account: Pubkey,      // 买家的访问凭证账户（释放时设置）
+    pub revoked_at: Option<i64>,    // 撤销时间
+    pub bump: u8,                   // PDA bump
+}
+
+impl AccessWindow {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 买家对交付数据块的抽查，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct ChunkChallenge {
+    pub escrow: Pubkey,             // 托管
+    #[max_len(MAX_CHALLENGE_CHUNKS)]
+    pub indices: Vec<u32>,          // 抽查的数据块序号
+    pub deadline: i64,              // 响应截止时间
+    pub responded_at: Option<i64>,  // 提供商响应时间
+    pub bump: u8,                   // PDA bump
+}
+
+impl ChunkChallenge {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 交付时提交的数据块清单参数
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChunkManifestParams {
+    pub root: [u8; 32],          // 数据块哈希的 Merkle 根
+    pub chunk_count: u32,        // 数据块数
+}
+
+/// 争议数据块及其 Merkle 证明
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChunkProof {
+    pub index: u32,              // 数据块序号
+    pub chunk_hash: [u8; 32],    // 数据块内容的 sha256
+    pub proof: Vec<[u8; 32]>,    // 自叶子向上的兄弟节点
+}
+
+/// 跨链买家在 Wormhole 代币桥转账中附带的托管参数（Borsh 编码）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BridgePayment {
+    pub buyer: Pubkey,           // 买家的 Solana 地址（托管 PDA 种子，接收退款）
+    pub provider: Pubkey,        // 提供商
+    pub platform: Pubkey,        // 平台
+    pub request_id: String,      // 需求 ID
+    pub proposal_id: String,     // 提案 ID
+}
+
+/// 非 USDC 托管的美元估值，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowValuation {
+    pub escrow: Pubkey,                 // 托管
+    pub mint: Pubkey,                   // 托管代币
+    pub feed_id: [u8; 32],              // Pyth 价格源 ID
+    pub decimals: u8,                   // 托管代币精度
+    pub price: i64,                     // 创建时价格
+    pub exponent: i32,                  // 价格指数
+    pub usd_value: u64,                 // 创建时美元价值（6 位小数）
+    pub recorded_at: i64,               // 估值时间
+    pub release_usd_value: Option<u64>, // 释放时美元价值
+    pub released_at: Option<i64>,       // 释放时间
+    pub bump: u8,                       // PDA bump
+}
+
+impl EscrowValuation {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 美元计价托管的成交价，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct UsdPricing {
+    pub escrow: Pubkey,                 // 托管
+    pub feed_id: [u8; 32],              // Pyth 价格源 ID
+    pub decimals: u8,                   // 托管代币精度
+    pub usd_cents: u64,                 // 成交价（美分）
+    pub price: i64,                     // 计价时价格
+    pub exponent: i32,                  // 价格指数
+    pub recorded_at: i64,               // 计价时间
+    pub released_amount: Option<u64>,   // 释放时折算的代币数量
+    pub bump: u8,                       // PDA bump
+}
+
+impl UsdPricing {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 兑换结算约定：托管持有一种代币，释放时以另一种代币给提供商结算，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct SettlementSwap {
+    pub escrow: Pubkey,                 // 托管
+    pub settle_mint: Pubkey,            // 提供商的结算代币
+    pub min_out: u64,                   // 整笔托管金额至少兑换到的结算代币数量
+    pub bump: u8,                       // PDA bump
+}
+
+impl SettlementSwap {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 托管的数据类别和受限司法辖区，创建时从需求复制，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowRestriction {
+    pub escrow: Pubkey,            // 托管
+    pub flags: u32,                // 数据类别和受限司法辖区标志（见需求程序）
+    pub blocked: bool,             // 平台是否已阻止释放
+    #[max_len(MAX_BLOCK_REASON_LEN)]
+    pub reason: String,            // 阻止释放的原因
+    pub blocked_at: Option<i64>,   // 阻止时间
+    pub bump: u8,                  // PDA bump
+}
+
+impl EscrowRestriction {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 收益模式的借贷存款，每个托管一条，赎回时关闭
+#[account]
+#[derive(InitSpace)]
+pub struct YieldPosition {
+    pub escrow: Pubkey,          // 托管
+    pub reserve: Pubkey,         // Solend 储备
+    pub collateral: Pubkey,      // 托管持有的抵押代币账户
+    pub principal: u64,          // 存入的本金
+    pub deposited_at: i64,       // 存入时间
+    pub bump: u8,                // PDA bump
+}
+
+impl YieldPosition {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 保险赔付记录，每个托管一条
+#[account]
+#[derive(InitSpace)]
+pub struct InsuranceClaim {
+    pub escrow: Pubkey,          // 托管
+    pub buyer: Pubkey,           // 获赔买家
+    pub amount: u64,             // 赔付金额
+    pub claimed_at: i64,         // 赔付时间
+    pub bump: u8,                // PDA bump
+}
+
+impl InsuranceClaim {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 费用账本，全局一个
+///
+/// 开启 `fee_ledger` 时释放路径把平台费整笔转入共享费用金库并在此累计，
+/// `settle_fees` 按配置权重拆分后清零
+#[account]
+#[derive(InitSpace)]
+pub struct FeeLedger {
+    pub vault: Pubkey,             // 共享费用金库
+    pub accrued: u64,              // 待拆分的平台费
+    pub total_settled: u64,        // 累计已拆分的平台费
+    pub settled_at: i64,           // 上次拆分时间（0 为未拆分过）
+    pub bump: u8,                  // PDA bump
+}
+
+impl FeeLedger {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 托管账户数据结构
+///
+/// 版本 4 起为零拷贝定长布局，指令直接读写账户数据，不再整块 Borsh（反）序列化：
+/// 可选时间以 0 表示未发生，需求 ID 和提案 ID 存为定长字节加长度，
+/// 扩展元数据紧跟在定长部分之后，长度记在 `metadata_len`
+#[account(zero_copy)]
+pub struct Escrow {
+    pub buyer: Pubkey,             // 买家
+    pub provider: Pubkey,          // 提供商
+    pub platform: Pubkey,          // 平台
+    pub amount: u64,               // 托管金额（USDC，6 位小数）
+    pub status: u8,                // 状态（`EscrowStatus` 序号，经 `status()` 读取）
+    pub version: u8,               // 布局版本
+    pub bump: u8,                  // PDA bump
+    pub request_id_len: u8,        // 需求 ID 长度
+    pub proposal_id_len: u8,       // 提案 ID 长度
+    pub padding: [u8; 3],          // 对齐填充
+    pub created_at: i64,           // 创建时间
+    pub funded_at: i64,            // 充值时间（0 为未发生）
+    pub delivered_at: i64,         // 交付时间（0 为未发生）
+    pub completed_at: i64,         // 完成时间（0 为未发生）
+    pub refunded_at: i64,          // 退款时间（0 为未发生）
+    pub disputed_at: i64,          // 争议时间（0 为未发生）
+    pub delivery_hash: [u8; 32],   // 交付内容哈希
+    pub access_mint: Pubkey,       // 数据访问凭证铸币（默认公钥为未配置）
+    pub request_id_bytes: [u8; MAX_ESCROW_ID_LEN], // 需求 ID
+    pub proposal_id_bytes: [u8; MAX_ESCROW_ID_LEN], // 提案 ID
+    pub metadata_len: u32,         // 扩展元数据长度（平台签名追加，按需扩容）
+    pub yield_open: u8,            // 收益模式存款未赎回（非 0 时本金不在托管代币账户）
+    pub reserved: [u8; 3],         // 预留
+    pub mint: Pubkey,              // 托管代币铸币（版本 5 之前创建且未充值的托管为默认公钥）
+}
+
+impl Escrow {
+    /// 元数据为空时的账户大小
+    pub const LEN: usize = 8 + std::mem::size_of::<Escrow>();
+
+    /// 版本 4 的账户大小（元数据为空时），版本 5 在其定长部分末尾追加了 `mint`
+    pub const V4_LEN: usize = Self::LEN - 32;
+
+    /// 定长字段在账户数据中的偏移（含 8 字节鉴别符），供 `getProgramAccounts` memcmp 过滤
+    ///
+    /// 与版本 3 的 Borsh 布局一致，新增字段只能使用预留字节或追加在末尾
+    pub const BUYER_OFFSET: usize = 8;
+    pub const PROVIDER_OFFSET: usize = 8 + 32;
+    pub const PLATFORM_OFFSET: usize = 8 + 32 + 32;
+    pub const STATUS_OFFSET: usize = 8 + 32 + 32 + 32 + 8;
+    pub const VERSION_OFFSET: usize = Self::STATUS_OFFSET + 1;
+
+    /// 扩展元数据在账户数据中的起始偏移
+    pub const METADATA_OFFSET: usize = Self::LEN;
+
+    /// 托管状态
+    pub fn status(&self) -> EscrowStatus {
+        EscrowStatus::try_from_slice(&[self.status]).expect("escrow status is always valid")
+    }
+
+    pub fn set_status(&mut self, status: EscrowStatus) {
+        self.status = status as u8;
+    }
+
+    /// 收益模式存款未赎回时本金在借贷储备中，释放、退款或裁决前须先 `withdraw_yield`
+    pub fn require_no_yield_position(&self) -> Result<()> {
+        require!(self.yield_open == 0, EscrowError::YieldPositionOpen);
+        Ok(())
+    }
+
+    /// 需求 ID（托管 PDA 种子）
+    pub fn request_id(&self) -> &str {
+        id_str(&self.request_id_bytes, self.request_id_len)
+    }
+
+    /// 提案 ID
+    pub fn proposal_id(&self) -> &str {
+        id_str(&self.proposal_id_bytes, self.proposal_id_len)
+    }
+
+    /// 写入需求 ID 和提案 ID，超过 `MAX_ESCROW_ID_LEN` 字节时拒绝
+    pub fn set_ids(&mut self, request_id: &str, proposal_id: &str) -> Result<()> {
+        require!(
+            request_id.len() <= MAX_ESCROW_ID_LEN && proposal_id.len() <= MAX_ESCROW_ID_LEN,
+            EscrowError::IdTooLong
+        );
+        self.request_id_bytes = [0; MAX_ESCROW_ID_LEN];
+        self.request_id_bytes[..request_id.len()].copy_from_slice(request_id.as_bytes());
+        self.request_id_len = request_id.len() as u8;
+        self.proposal_id_bytes = [0; MAX_ESCROW_ID_LEN];
+        self.proposal_id_bytes[..proposal_id.len()].copy_from_slice(proposal_id.as_bytes());
+        self.proposal_id_len = proposal_id.len() as u8;
+
+        Ok(())
+    }
+
+    pub fn funded_at(&self) -> Option<i64> {
+        timestamp(self.funded_at)
+    }
+
+    pub fn delivered_at(&self) -> Option<i64> {
+        timestamp(self.delivered_at)
+    }
+
+    pub fn completed_at(&self) -> Option<i64> {
+        timestamp(self.completed_at)
+    }
+
+    pub fn refunded_at(&self) -> Option<i64> {
+        timestamp(self.refunded_at)
+    }
+
+    pub fn disputed_at(&self) -> Option<i64> {
+        timestamp(self.disputed_at)
+    }
+
+    /// 数据访问凭证铸币（未配置时为 `None`）
+    pub fn access_mint(&self) -> Option<Pubkey> {
+        (self.access_mint != Pubkey::default()).then_some(self.access_mint)
+    }
+
+    /// 托管代币铸币（版本 5 之前创建且尚未充值时为 `None`，充值时补记）
+    pub fn mint(&self) -> Option<Pubkey> {
+        (self.mint != Pubkey::default()).then_some(self.mint)
+    }
+
+    /// 读取版本 4 的账户数据（含鉴别符），返回升级到当前版本的定长部分和扩展元数据
+    ///
+    /// 版本 4 的定长部分是当前布局去掉末尾 `mint` 的前缀，`mint` 取默认公钥
+    pub fn read_v4(data: &[u8]) -> Result<(Escrow, Vec<u8>)> {
+        let fixed = data
+            .get(8..Self::V4_LEN)
+            .ok_or(EscrowError::UnknownEscrowLayout)?;
+        let mut escrow = Escrow::zeroed();
+        bytemuck::bytes_of_mut(&mut escrow)[..fixed.len()].copy_from_slice(fixed);
+        let metadata = data
+            .get(Self::V4_LEN..)
+            .and_then(|metadata| metadata.get(..escrow.metadata_len as usize))
+            .ok_or(EscrowError::UnknownEscrowLayout)?
+            .to_vec();
+        escrow.version = ESCROW_VERSION;
+
+        Ok((escrow, metadata))
+    }
+}
+
+/// 定长 ID 字段按长度截取（写入时已校验为 UTF-8）
+fn id_str(bytes: &[u8; MAX_ESCROW_ID_LEN], len: u8) -> &str {
+    std::str::from_utf8(&bytes[..len as usize]).unwrap_or_default()
+}
+
+/// 0 表示尚未发生
+fn timestamp(value: i64) -> Option<i64> {
+    (value != 0).then_some(value)
+}
+
+/// 版本 3 的托管布局（Borsh 变长），仅供迁移时读取
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct EscrowV3 {
+    pub buyer: Pubkey,
+    pub provider: Pubkey,
+    pub platform: Pubkey,
+    pub amount: u64,
+    pub status: EscrowStatus,
+    pub version: u8,
+    pub request_id: String,
+    pub proposal_id: String,
+    pub created_at: i64,
+    pub funded_at: Option<i64>,
+    pub delivered_at: Option<i64>,
+    pub completed_at: Option<i64>,
+    pub refunded_at: Option<i64>,
+    pub disputed_at: Option<i64>,
+    pub delivery_hash: [u8; 32],
+    pub access_mint: Option<Pubkey>,
+    pub bump: u8,
+    pub metadata: Vec<u8>,
+}
+
+impl EscrowV3 {
+    /// 转换为零拷贝布局，扩展元数据由调用者写到定长部分之后
+    pub fn migrate(&self) -> Result<Escrow> {
+        let mut escrow = Escrow::zeroed();
+        escrow.buyer = self.buyer;
+        escrow.provider = self.provider;
+        escrow.platform = self.platform;
+        escrow.amount = self.amount;
+        escrow.set_status(self.status.clone());
+        escrow.version = ESCROW_VERSION;
+        escrow.bump = self.bump;
+        escrow.set_ids(&self.request_id, &self.proposal_id)?;
+        escrow.created_at = self.created_at;
+        escrow.funded_at = self.funded_at.unwrap_or_default();
+        escrow.delivered_at = self.delivered_at.unwrap_or_default();
+        escrow.completed_at = self.completed_at.unwrap_or_default();
+        escrow.refunded_at = self.refunded_at.unwrap_or_default();
+        escrow.disputed_at = self.disputed_at.unwrap_or_default();
+        escrow.delivery_hash = self.delivery_hash;
+        escrow.access_mint = self.access_mint.unwrap_or_default();
+        escrow.metadata_len = self.metadata.len() as u32;
+
+        Ok(escrow)
+    }
+}
+
+/// 版本 3 之前的托管布局，均为 Borsh 变长编码，状态位于需求 ID 和提案 ID 之后
+///
+/// 增加交付哈希和访问凭证铸币时没有版本号，只能按账户分配的大小区分；
+/// 需求 ID 和提案 ID 在这些布局中各预留 64 字节，账户大小与实际 ID 长度无关
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LegacyLayout {
+    V0,             // 初始布局：`disputed_at` 之后即为 `bump`
+    V0DeliveryHash, // `bump` 之前增加交付哈希
+    V0AccessMint,   // 交付哈希之后再增加可选的访问凭证铸币
+    V1,             // `bump` 之后增加版本号
+    V2,             // 版本号之后增加扩展元数据（账户按元数据长度扩容）
+}
+
+impl LegacyLayout {
+    /// 各布局的账户大小（含 8 字节鉴别符）
+    pub const V0_LEN: usize = 8 + 32 + 32 + 32 + 8 + 64 + 64 + 1 + 8 + 9 + 9 + 9 + 9 + 9 + 1;
+    pub const V0_DELIVERY_HASH_LEN: usize = Self::V0_LEN + 32;
+    pub const V0_ACCESS_MINT_LEN: usize = Self::V0_DELIVERY_HASH_LEN + 33;
+    pub const V1_LEN: usize = Self::V0_ACCESS_MINT_LEN + 1;
+    pub const V2_LEN: usize = Self::V1_LEN + 4;
+
+    /// 按账户数据长度判断布局
+    pub fn from_len(len: usize) -> Option<Self> {
+        match len {
+            Self::V0_LEN => Some(Self::V0),
+            Self::V0_DELIVERY_HASH_LEN => Some(Self::V0DeliveryHash),
+            Self::V0_ACCESS_MINT_LEN => Some(Self::V0AccessMint),
+            Self::V1_LEN => Some(Self::V1),
+            len if len >= Self::V2_LEN => Some(Self::V2),
+            _ => None,
+        }
+    }
+}
+
+/// 版本 3 之前的托管字段，仅供迁移时读取
+pub struct LegacyEscrow {
+    pub buyer: Pubkey,
+    pub provider: Pubkey,
+    pub platform: Pubkey,
+    pub amount: u64,
+    pub request_id: String,
+    pub proposal_id: String,
+    pub status: EscrowStatus,
+    pub created_at: i64,
+    pub funded_at: Option<i64>,
+    pub delivered_at: Option<i64>,
+    pub completed_at: Option<i64>,
+    pub refunded_at: Option<i64>,
+    pub disputed_at: Option<i64>,
+    pub delivery_hash: [u8; 32],
+    pub access_mint: Option<Pubkey>,
+    pub bump: u8,
+    pub version: u8,
+    pub metadata: Vec<u8>,
+}
+
+impl LegacyEscrow {
+    /// 按 `layout` 逐字段读取（不含鉴别符），该布局中不存在的字段取默认值
+    pub fn read(layout: LegacyLayout, buf: &mut &[u8]) -> Result<Self> {
+        let buyer = Pubkey::deserialize(buf)?;
+        let provider = Pubkey::deserialize(buf)?;
+        let platform = Pubkey::deserialize(buf)?;
+        let amount = u64::deserialize(buf)?;
+        let request_id = String::deserialize(buf)?;
+        let proposal_id = String::deserialize(buf)?;
+        let status = EscrowStatus::deserialize(buf)?;
+        let created_at = i64::deserialize(buf)?;
+        let funded_at = Option::<i64>::deserialize(buf)?;
+        let delivered_at = Option::<i64>::deserialize(buf)?;
+        let completed_at = Option::<i64>::deserialize(buf)?;
+        let refunded_at = Option::<i64>::deserialize(buf)?;
+        let disputed_at = Option::<i64>::deserialize(buf)?;
+        let delivery_hash = if layout >= LegacyLayout::V0DeliveryHash {
+            <[u8; 32]>::deserialize(buf)?
+        } else {
+            [0; 32]
+        };
+        let access_mint = if layout >= LegacyLayout::V0AccessMint {
+            Option::<Pubkey>::deserialize(buf)?
+        } else {
+            None
+        };
+        let bump = u8::deserialize(buf)?;
+        let version = if layout >= LegacyLayout::V1 {
+            u8::deserialize(buf)?
+        } else {
+            0
+        };
+        let metadata = if layout >= LegacyLayout::V2 {
+            Vec::<u8>::deserialize(buf)?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            buyer,
+            provider,
+            platform,
+            amount,
+            request_id,
+            proposal_id,
+            status,
+            created_at,
+            funded_at,
+            delivered_at,
+            completed_at,
+            refunded_at,
+            disputed_at,
+            delivery_hash,
+            access_mint,
+            bump,
+            version,
+            metadata,
+        })
+    }
+
+    /// 按版本 3 布局重排字段
+    pub fn migrate(self) -> EscrowV3 {
+        EscrowV3 {
+            buyer: self.buyer,
+            provider: self.provider,
+            platform: self.platform,
+            amount: self.amount,
+            status: self.status,
+            version: 3,
+            request_id: self.request_id,
+            proposal_id: self.proposal_id,
+            created_at: self.created_at,
+            funded_at: self.funded_at,
+            delivered_at: self.delivered_at,
+            completed_at: self.completed_at,
+            refunded_at: self.refunded_at,
+            disputed_at: self.disputed_at,
+            delivery_hash: self.delivery_hash,
+            access_mint: self.access_mint,
+            bump: self.bump,
+            metadata: self.metadata,
+        }
+    }
+}
+
+/// 买家统计，供提供商评估交易对手风险
+#[account]
+#[derive(InitSpace)]
+pub struct BuyerStats {
+    pub buyer: Pubkey,           // 买家
+    pub total_escrows: u64,      // 累计托管数
+    pub total_volume: u64,       // 累计托管金额（USDC，6 位小数）
+    pub completed: u64,          // 已完成托管数
+    pub disputes_raised: u64,    // 发起争议数
+    pub cancels: u64,            // 取消次数
+    pub bump: u8,                // PDA bump
+    pub open_escrows: u64,       // 未进入终态的托管数
+    pub window_start: i64,       // 当前限流窗口的开始时间
+    pub window_creations: u64,   // 当前限流窗口内创建的托管数
+}
+
+impl BuyerStats {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    /// 限流字段上线前的账户大小
+    pub const V0_LEN: usize = 8 + 32 + 8 * 5 + 1;
+
+    /// 按当前布局重写买家统计账户数据，`open_escrows` 为回填的未进入终态的托管数
+    ///
+    /// 旧布局的新增字段追加在 `bump` 之后，补零后读取；其余字段原样保留
+    pub fn migrate(data: &[u8], open_escrows: u64) -> Result<Vec<u8>> {
+        require!(
+            data.len() == Self::V0_LEN || data.len() == Self::LEN,
+            ErrorCode::AccountDidNotDeserialize
+        );
+        let mut data = data.to_vec();
+        data.resize(Self::LEN, 0);
+        let mut stats = Self::try_deserialize(&mut &data[..])?;
+        stats.open_escrows = open_escrows;
+
+        let mut migrated = Vec::with_capacity(Self::LEN);
+        stats.try_serialize(&mut migrated)?;
+        Ok(migrated)
+    }
+}
+
+impl BuyerStats {
+    /// 按 `ProtocolSettings` 的买家限流参数记录一笔新托管
+    ///
+    /// 未进入终态的托管数达到 `max_open_escrows`，或 `CREATION_WINDOW` 内
+    /// 已创建 `max_daily_escrows` 笔时拒绝创建（参数为 0 表示不限制）
+    fn record_creation(&mut self, settings: &ProtocolSettings, now: i64) -> Result<()> {
+        require!(
+            settings.max_open_escrows == 0 || self.open_escrows < settings.max_open_escrows,
+            EscrowError::TooManyOpenEscrows
+        );
+
+        if now >= self.window_start.saturating_add(CREATION_WINDOW) {
+            self.window_start = now;
+            self.window_creations = 0;
+        }
+        require!(
+            settings.max_daily_escrows == 0 || self.window_creations < settings.max_daily_escrows,
+            EscrowError::CreationRateLimited
+        );
+
+        self.window_creations += 1;
+        self.open_escrows += 1;
+        Ok(())
+    }
+
+    /// 托管进入终态或被回收
+    ///
+    /// 限流字段上线前创建的托管由 `migrate_buyer_stats` 回填，计数不足时说明回填遗漏了托管
+    fn record_settlement(&mut self) -> Result<()> {
+        self.open_escrows = self
+            .open_escrows
+            .checked_sub(1)
+            .ok_or(EscrowError::OpenEscrowMismatch)?;
+        Ok(())
+    }
+}
+
+/// 提供商统计，支持无需索引器的链上排序
+#[account]
+#[derive(InitSpace)]
+pub struct ProviderStats {
+    pub provider: Pubkey,          // 提供商
+    pub total_escrows: u64,        // 累计托管数
+    pub delivered: u64,            // 交付次数
+    pub total_delivery_time: u64,  // 累计交付耗时（秒，从充值到交付）
+    pub completed_sales: u64,      // 已完成销售数
+    pub sales_volume: u64,         // 累计销售收入（扣除平台费后）
+    pub disputes: u64,             // 被发起争议数
+    pub refunds: u64,              // 退款次数
+    pub refunded_volume: u64,      // 累计退款金额
+    pub bump: u8,                  // PDA bump
+}
+
+impl ProviderStats {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+impl ProviderStats {
+    /// 平均交付耗时（秒），尚无交付时返回 None
+    pub fn average_delivery_time(&self) -> Option<u64> {
+        self.total_delivery_time.checked_div(self.delivered)
+    }
+
+    /// 争议率（基点）
+    pub fn dispute_rate_bps(&self) -> u64 {
+        self.disputes
+            .saturating_mul(BPS_DENOMINATOR)
+            .checked_div(self.total_escrows)
+            .unwrap_or(0)
+    }
+}
+
+/// 提供商侧托管索引，按 (提供商, 序号) 派生，提供商可无需扫描即枚举自己的托管
+#[account]
+#[derive(InitSpace)]
+pub struct ProviderEscrowIndex {
+    pub provider: Pubkey,        // 提供商
+    pub sequence: u64,           // 序号（从 0 开始）
+    pub escrow: Pubkey,          // 托管账户
+    pub bump: u8,                // PDA bump
+}
+
+impl ProviderEscrowIndex {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 全局协议统计（单例），仪表盘只需读取一个账户
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolStats {
+    pub total_escrows: u64,      // 累计托管数
+    pub total_volume: u64,       // 累计托管金额（USDC，6 位小数）
+    pub total_fees: u64,         // 累计平台费
+    pub active_disputes: u64,    // 进行中的争议数
+    pub bump: u8,                // PDA bump
+}
+
+impl ProtocolStats {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 托管已创建（尚未充值）
+#[event]
+pub struct EscrowCreated {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub request_id: String,
+    pub timestamp: i64,
+}
+
+/// 买家已为托管充值
+#[event]
+pub struct EscrowFunded {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// 提供商已交付
+#[event]
+pub struct EscrowDelivered {
+    pub escrow: Pubkey,
+    pub provider: Pubkey,
+    pub delivery_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// 按件计价托管交付一批数据并结算
+#[event]
+pub struct UnitsDelivered {
+    pub escrow: Pubkey,
+    pub provider: Pubkey,
+    pub units: u64,
+    pub delivered: u64,
+    pub quantity: u64,
+    pub provider_amount: u64,
+    pub platform_fee: u64,
+    pub timestamp: i64,
+}
+
+/// 按下载计费托管消耗一次下载并结算
+#[event]
+pub struct DownloadConsumed {
+    pub escrow: Pubkey,
+    pub provider: Pubkey,
+    pub downloads_remaining: u64,
+    pub provider_amount: u64,
+    pub platform_fee: u64,
+    pub timestamp: i64,
+}
+
+/// 提供商销售记录追加了一笔完成的销售
+#[event]
+pub struct SaleRecorded {
+    pub provider: Pubkey,
+    pub escrow: Pubkey,
+    pub index: u64,
+    pub amount_bucket: u8,
+    pub category: u16,
+    pub timestamp: i64,
+    pub root: [u8; 32],
+}
+
+/// 平台向提供商签发或续期了徽章
+#[event]
+pub struct BadgeIssued {
+    pub provider: Pubkey,
+    pub badge_type: u8,
+    pub issued_at: i64,
+    pub expires_at: i64,
+}
+
+/// 平台撤销了提供商徽章
+#[event]
+pub struct BadgeRevoked {
+    pub provider: Pubkey,
+    pub badge_type: u8,
+}
+
+/// 平台将地址列入黑名单
+#[event]
+pub struct Blacklisted {
+    pub subject: Pubkey,
+    pub reason_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// 平台将地址移出黑名单
+#[event]
+pub struct Unblacklisted {
+    pub subject: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 逾期交付，部分提供商份额已退还买家
+#[event]
+pub struct LateDeliveryRefunded {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub days_late: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// 逾期交付，约定的罚金已从提供商份额中扣除
+#[event]
+pub struct LateDeliveryPenalized {
+    pub escrow: Pubkey,
+    pub provider: Pubkey,
+    pub recipient: PenaltyRecipient,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// 奖励金已结算：提前交付付给提供商，否则退还买家
+#[event]
+pub struct BonusSettled {
+    pub escrow: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub earned: bool,
+    pub timestamp: i64,
+}
+
+/// 买家申请取消，异议期内提供商可以反对
+#[event]
+pub struct CancelRequested {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+/// 买家或提供商记录了一条协商消息的哈希
+#[event]
+pub struct MessageLogged {
+    pub escrow: Pubkey,
+    pub author: Pubkey,
+    pub message_hash: [u8; 32],
+    pub index: u32,
+    pub timestamp: i64,
+}
+
+/// 买家确认时给出评分
+#[event]
+pub struct QualityRated {
+    pub escrow: Pubkey,
+    pub provider: Pubkey,
+    pub buyer: Pubkey,
+    pub score: u8,
+    pub timestamp: i64,
+}
+
+/// 买家拒收交付，等待提供商重新交付
+#[event]
+pub struct DeliveryRejected {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub reason_hash: [u8; 32],
+    pub rejections: u8,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+/// 数据集属性证明已通过验证
+#[event]
+pub struct DatasetProofVerified {
+    pub escrow: Pubkey,
+    pub verifier: Pubkey,
+    pub row_count: u64,
+    pub schema_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// 买家抽查交付的数据块
+#[event]
+pub struct ChallengeIssued {
+    pub escrow: Pubkey,
+    pub indices: Vec<u32>,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+/// 提供商已响应抽查，块哈希与抽查序号一一对应
+#[event]
+pub struct ChallengeAnswered {
+    pub escrow: Pubkey,
+    pub chunk_hashes: Vec<[u8; 32]>,
+    pub timestamp: i64,
+}
+
+/// 访问期满，买家的访问凭证账户已冻结
+#[event]
+pub struct AccessRevoked {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub token_account: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 提供商已提交加密的解密密钥
+#[event]
+pub struct KeyDelivered {
+    pub escrow: Pubkey,
+    pub provider: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 资金已释放给提供商（买家确认或争议裁决），`tip` 为买家确认时额外给的小费
+#[event]
+pub struct EscrowReleased {
+    pub escrow: Pubkey,
+    pub provider: Pubkey,
+    pub provider_amount: u64,
+    pub platform_fee: u64,
+    pub tip: u64,
+    pub timestamp: i64,
+}
+
+/// 提供商份额经 Jupiter 兑换为结算代币后支付
+#[event]
+pub struct SettlementSwapped {
+    pub escrow: Pubkey,
+    pub provider: Pubkey,
+    pub settle_mint: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub timestamp: i64,
+}
+
+/// 共享费用金库中累计的平台费已拆分
+#[event]
+pub struct FeesSettled {
+    pub amount: u64,
+    pub total_settled: u64,
+    pub timestamp: i64,
+}
+
+/// 平台因受限的类别或司法辖区组合阻止释放并退款
+#[event]
+pub struct ReleaseBlocked {
+    pub escrow: Pubkey,
+    pub flags: u32,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+/// 资金已退还买家（超时退款或争议裁决）
+#[event]
+pub struct EscrowRefunded {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// 买家已取消托管
+#[event]
+pub struct EscrowCancelled {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// 未注资的托管已过期回收
+#[event]
+pub struct EscrowReclaimed {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub provider: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 买家发起争议
+#[event]
+pub struct DisputeRaised {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 争议移交陪审团
+#[event]
+pub struct DisputeEscalated {
+    pub escrow: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 平台作出可上诉的裁决
+#[event]
+pub struct DisputeRuled {
+    pub escrow: Pubkey,
+    pub refund_to_buyer: bool,
+    pub appeal_deadline: i64,
+    pub timestamp: i64,
+}
+
+/// 败诉方上诉
+#[event]
+pub struct DisputeAppealed {
+    pub escrow: Pubkey,
+    pub appellant: Pubkey,
+    pub bond: u64,
+    pub timestamp: i64,
+}
+
+/// 平台裁决争议
+#[event]
+pub struct DisputeResolved {
+    pub escrow: Pubkey,
+    pub refund_to_buyer: bool,
+    pub timestamp: i64,
+}
+
+/// 争议按有效数据块比例拆分
+#[event]
+pub struct DisputeSplit {
+    pub escrow: Pubkey,
+    pub valid_chunks: u32,
+    pub chunk_count: u32,
+    pub provider_amount: u64,
+    pub platform_fee: u64,
+    pub buyer_refund: u64,
+    pub timestamp: i64,
+}
+
+/// 托管状态
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum EscrowStatus {
+    Created,    // 已创建
+    Funded,     // 已充值
+    Delivered,  // 已交付
+    Disputed,   // 争议中
+    Completed,  // 已完成
+    Refunded,   // 已退款
+    Cancelled,  // 已取消
+    Escalated,  // 陪审团审理中
+    Ruled,      // 平台已裁决，上诉期中
+    Appealed,   // 已上诉，等待陪审团受理
+    Rejected,   // 买家拒收，等待提供商重新交付
+    PendingCancel, // 买家申请取消，异议期中
+}
+
+impl EscrowStatus {
+    /// 是否为终态（不再允许任何状态流转）
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            EscrowStatus::Completed | EscrowStatus::Refunded | EscrowStatus::Cancelled
+        )
+    }
+
+    /// 与各指令中的状态检查一致的合法流转
+    pub fn can_transition_to(&self, next: &EscrowStatus) -> bool {
+        matches!(
+            (self, next),
+            (EscrowStatus::Created, EscrowStatus::Funded)
+                | (EscrowStatus::Funded, EscrowStatus::Delivered)
+                | (EscrowStatus::Funded, EscrowStatus::PendingCancel)
+                | (EscrowStatus::PendingCancel, EscrowStatus::Cancelled)
+                | (EscrowStatus::PendingCancel, EscrowStatus::Disputed)
+                | (EscrowStatus::Delivered, EscrowStatus::Completed)
+                | (EscrowStatus::Delivered, EscrowStatus::Disputed)
+                | (EscrowStatus::Delivered, EscrowStatus::Refunded)
+                | (EscrowStatus::Delivered, EscrowStatus::Rejected)
+                | (EscrowStatus::Rejected, EscrowStatus::Delivered)
+                | (EscrowStatus::Rejected, EscrowStatus::Refunded)
+                | (EscrowStatus::Disputed, EscrowStatus::Completed)
+                | (EscrowStatus::Disputed, EscrowStatus::Refunded)
+                | (EscrowStatus::Disputed, EscrowStatus::Escalated)
+                | (EscrowStatus::Escalated, EscrowStatus::Completed)
+                | (EscrowStatus::Escalated, EscrowStatus::Refunded)
+                | (EscrowStatus::Disputed, EscrowStatus::Ruled)
+                | (EscrowStatus::Ruled, EscrowStatus::Completed)
+                | (EscrowStatus::Ruled, EscrowStatus::Refunded)
+                | (EscrowStatus::Ruled, EscrowStatus::Appealed)
+                | (EscrowStatus::Appealed, EscrowStatus::Escalated)
+        )
+    }
+}
+
+/// 初始化配置的上下文
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Config::LEN,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub treasury: Box<Account<'info, TokenAccount>>,
+
+    pub rewards_pool: Box<Account<'info, TokenAccount>>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::DatanexusEscrow>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ EscrowError::OnlyUpgradeAuthority
+    )]
+    pub program_data: Box<Account<'info, ProgramData>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 排队更新配置的上下文
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+
+    pub treasury: Box<Account<'info, TokenAccount>>,
+
+    pub rewards_pool: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PendingConfigChange::LEN,
+        seeds = [b"pending_config"],
+        bump
+    )]
+    pub pending_change: Box<Account<'info, PendingConfigChange>>,
+
+    /// 支付待生效变更的租金（管理员为多签或治理 PDA 时由执行人支付）
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 排队更新策略参数的上下文
+#[derive(Accounts)]
+pub struct UpdateSettings<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PendingConfigChange::LEN,
+        seeds = [b"pending_config"],
+        bump
+    )]
+    pub pending_change: Box<Account<'info, PendingConfigChange>>,
+
+    /// 支付待生效变更的租金（管理员为多签或治理 PDA 时由执行人支付）
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 执行配置变更的上下文
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_config"],
+        bump = pending_change.bump,
+        has_one = payer,
+        close = payer
+    )]
+    pub pending_change: Box<Account<'info, PendingConfigChange>>,
+
+    /// CHECK: Rent refund destination, checked against the pending change
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+/// 撤销配置变更的上下文
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_config"],
+        bump = pending_change.bump,
+        has_one = payer,
+        close = payer
+    )]
+    pub pending_change: Box<Account<'info, PendingConfigChange>>,
+
+    /// CHECK: Rent refund destination, checked against the pending change
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+/// 创建保险池的上下文
+#[derive(Accounts)]
+pub struct InitInsurance<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"insurance_vault"],
+        bump,
+        token::mint = mint,
+        token::authority = config,
+    )]
+    pub insurance_vault: Box<Account<'info, TokenAccount>>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    pub admin: Signer<'info>,
+
+    /// 支付租金（管理员为多签或治理 PDA 时由执行人支付）
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 保险赔付的上下文
+#[derive(Accounts)]
+pub struct ClaimInsurance<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = InsuranceClaim::LEN,
+        seeds = [b"insurance_claim", escrow.key().as_ref()],
+        bump
+    )]
+    pub claim: Box<Account<'info, InsuranceClaim>>,
+
+    #[account(mut, seeds = [b"insurance_vault"], bump)]
+    pub insurance_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = insurance_vault.mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 支付赔付记录租金（管理员为治理 PDA 时由执行人支付）
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 创建费用账本的上下文
+#[derive(Accounts)]
+pub struct InitFeeLedger<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = FeeLedger::LEN,
+        seeds = [b"fee_ledger"],
+        bump
+    )]
+    pub fee_ledger: Box<Account<'info, FeeLedger>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"fee_vault"],
+        bump,
+        token::mint = mint,
+        token::authority = fee_ledger,
+        token::token_program = token_program,
+    )]
+    pub fee_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub admin: Signer<'info>,
+
+    /// 支付租金（管理员为多签或治理 PDA 时由执行人支付）
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 拆分累计平台费的上下文
+#[derive(Accounts)]
+pub struct SettleFees<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, seeds = [b"fee_ledger"], bump = fee_ledger.bump)]
+    pub fee_ledger: Box<Account<'info, FeeLedger>>,
+
+    #[account(mut, address = fee_ledger.vault)]
+    pub fee_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(mut, address = config.treasury)]
+    pub treasury_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(mut, address = config.rewards_pool)]
+    pub rewards_pool_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// 保险池，`insurance_bps` 非零时必填
+    #[account(mut, seeds = [b"insurance_vault"], bump)]
+    pub insurance_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(address = fee_vault.mint)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// 移交管理员的上下文
+#[derive(Accounts)]
+pub struct SetAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+}
+
+/// 创建收据 Merkle 树的上下文
+#[derive(Accounts)]
+pub struct InitReceiptTree<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+
+    /// 支付树配置租金（管理员为多签或治理 PDA 时由执行人支付）
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Tree config PDA, validated and created by the Bubblegum program
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Pre-allocated Merkle tree, initialized by the Account Compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum program
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Noop program
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 签发提供商徽章的上下文
+#[derive(Accounts)]
+#[instruction(provider: Pubkey, badge_type: u8)]
+pub struct IssueBadge<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Badge::LEN,
+        seeds = [b"badge", provider.as_ref(), &[badge_type]],
+        bump
+    )]
+    pub badge: Box<Account<'info, Badge>>,
+
+    /// 支付徽章租金（管理员为治理 PDA 时由执行人支付）
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 撤销提供商徽章的上下文
+#[derive(Accounts)]
+pub struct RevokeBadge<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"badge", badge.provider.as_ref(), &[badge.badge_type]],
+        bump = badge.bump,
+        close = admin
+    )]
+    pub badge: Box<Account<'info, Badge>>,
+}
+
+/// 列入黑名单的上下文
+#[derive(Accounts)]
+#[instruction(subject: Pubkey)]
+pub struct AddToBlacklist<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = Blacklist::LEN,
+        seeds = [b"blacklist", subject.as_ref()],
+        bump
+    )]
+    pub blacklist: Box<Account<'info, Blacklist>>,
+
+    /// 支付黑名单条目租金（管理员为治理 PDA 时由执行人支付）
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 移出黑名单的上下文
+#[derive(Accounts)]
+pub struct RemoveFromBlacklist<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"blacklist", blacklist.subject.as_ref()],
+        bump = blacklist.bump,
+        close = admin
+    )]
+    pub blacklist: Box<Account<'info, Blacklist>>,
+}
+
+/// 创建托管的上下文
+#[derive(Accounts)]
+#[instruction(amount: u64, request_id: String, proposal_id: String)]
+pub struct CreateEscrow<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = Escrow::LEN,
+        seeds = [b"escrow", buyer.key().as_ref(), request_id.as_bytes()],
+        bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = BuyerStats::LEN,
+        seeds = [b"buyer_stats", buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    /// CHECK: Provider address, validated against its registry profile when registration is required
+    pub provider: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = ProviderStats::LEN,
+        seeds = [b"provider_stats", provider.key().as_ref()],
+        bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = ProviderEscrowIndex::LEN,
+        seeds = [
+            b"provider_escrow",
+            provider.key().as_ref(),
+            &provider_stats.total_escrows.to_le_bytes()
+        ],
+        bump
+    )]
+    pub provider_escrow_index: Box<Account<'info, ProviderEscrowIndex>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = ProtocolStats::LEN,
+        seeds = [b"protocol_stats"],
+        bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    /// 可选：提供商注册资料，配置要求注册时必须提供
+    #[account(
+        seeds = [b"provider", provider.key().as_ref()],
+        bump = provider_profile.bump,
+        seeds::program = datanexus_registry::ID
+    )]
+    pub provider_profile: Option<Box<Account<'info, ProviderProfile>>>,
+
+    /// 可选：买家在需求程序中发布的需求，须与 `proposal` 一起提供
+    #[account(
+        seeds = [b"request", buyer.key().as_ref(), request_id.as_bytes()],
+        bump = request.bump,
+        seeds::program = datanexus_requests::ID
+    )]
+    pub request: Option<Box<Account<'info, Request>>>,
+
+    /// 可选：提供商针对该需求提交、并已被买家选定的提案
+    #[account(
+        seeds = [b"proposal", proposal.request.as_ref(), provider.key().as_ref()],
+        bump = proposal.bump,
+        seeds::program = datanexus_requests::ID,
+        constraint = proposal.status == ProposalStatus::Selected @ EscrowError::ProposalNotSelected
+    )]
+    pub proposal: Option<Box<Account<'info, Proposal>>>,
+
+    /// 需求带有限制标志时必须传入，记录托管的类别和司法辖区
+    #[account(
+        init,
+        payer = buyer,
+        space = EscrowRestriction::LEN,
+        seeds = [b"restriction", escrow.key().as_ref()],
+        bump
+    )]
+    pub restriction: Option<Box<Account<'info, EscrowRestriction>>>,
+
+    /// CHECK: Platform address, must be the config admin
+    #[account(address = config.admin @ EscrowError::PlatformMismatch)]
+    pub platform: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// 可选：平台签发的优惠券，须与 `discount`、`coupon_redeemer`、`coupons_program` 一起提供
+    #[account(mut, constraint = coupon.issuer == config.admin @ EscrowError::InvalidCoupon)]
+    pub coupon: Option<Box<Account<'info, Coupon>>>,
+
+    /// 记录兑换的优惠券，释放时减免平台费
+    #[account(
+        init,
+        payer = buyer,
+        space = EscrowDiscount::LEN,
+        seeds = [b"discount", escrow.key().as_ref()],
+        bump
+    )]
+    pub discount: Option<Box<Account<'info, EscrowDiscount>>>,
+
+    /// CHECK: Escrow program PDA that signs coupon redemptions
+    #[account(seeds = [COUPON_REDEEMER_SEED], bump)]
+    pub coupon_redeemer: Option<UncheckedAccount<'info>>,
+
+    pub coupons_program: Option<Program<'info, DatanexusCoupons>>,
+
+    /// 可选：数据访问凭证铸币，铸币权限须为 `[b"access", provider]` PDA
+    pub access_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+
+    /// CHECK: Optional buyer compliance attestation, validated in the handler
+    pub buyer_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional provider compliance attestation, validated in the handler
+    pub provider_attestation: Option<UncheckedAccount<'info>>,
+
+    /// 可选：提供商持有的徽章，托管金额达到 `badge_threshold` 时必须提供
+    #[account(
+        seeds = [b"badge", provider.key().as_ref(), &[config.settings.required_badge]],
+        bump = provider_badge.bump
+    )]
+    pub provider_badge: Option<Box<Account<'info, Badge>>>,
+
+    /// CHECK: Buyer blacklist PDA, may be uninitialized; checked in `check_not_blacklisted`
+    #[account(seeds = [b"blacklist", buyer.key().as_ref()], bump)]
+    pub buyer_blacklist: UncheckedAccount<'info>,
+
+    /// CHECK: Provider blacklist PDA, may be uninitialized; checked in `check_not_blacklisted`
+    #[account(seeds = [b"blacklist", provider.key().as_ref()], bump)]
+    pub provider_blacklist: UncheckedAccount<'info>,
+
+    /// 托管代币铸币，记录在托管中，充值和回收只接受该铸币的托管代币账户
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+}
+
+/// 为托管充值的上下文
+#[derive(Accounts)]
+pub struct FundEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump,
+        has_one = buyer @ EscrowError::OnlyBuyer
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    /// CHECK: Price quote PDA, may be uninitialized; checked in `fund`
+    #[account(seeds = [b"quote", escrow.key().as_ref()], bump)]
+    pub quote: UncheckedAccount<'info>,
+
+    #[account(mut, token::mint = mint)]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(mut, token::mint = mint, token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// 托管记录的铸币（版本 5 之前创建的托管未记录，按本次充值的铸币补记）
+    #[account(
+        constraint = escrow.load()?.mint().is_none_or(|escrow_mint| escrow_mint == mint.key())
+            @ EscrowError::MintMismatch
+    )]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// 兑换后为托管充值的上下文，Jupiter 路由账户放在 remaining accounts 中
+#[derive(Accounts)]
+pub struct FundEscrowWithSwap<'info> {
+    pub fund: FundEscrow<'info>,
+
+    /// CHECK: Jupiter aggregator program
+    #[account(address = JUPITER_PROGRAM_ID)]
+    pub jupiter_program: UncheckedAccount<'info>,
+}
+
+/// 保存模板的上下文
+#[derive(Accounts)]
+#[instruction(template_id: u64)]
+pub struct SaveTemplate<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Template::LEN,
+        seeds = [b"template", owner.key().as_ref(), &template_id.to_le_bytes()],
+        bump
+    )]
+    pub template: Box<Account<'info, Template>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    /// 可选：模板限定的提供商，模板带交付期限时必须签名
+    pub provider: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 删除模板的上下文
+#[derive(Accounts)]
+pub struct CloseTemplate<'info> {
+    #[account(
+        mut,
+        seeds = [b"template", owner.key().as_ref(), &template.template_id.to_le_bytes()],
+        bump = template.bump,
+        has_one = owner @ EscrowError::OnlyTemplateOwner,
+        close = owner
+    )]
+    pub template: Box<Account<'info, Template>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// 按模板创建托管的上下文，模板的各项条款写入对应的附加账户
+#[derive(Accounts)]
+pub struct CreateEscrowFromTemplate<'info> {
+    pub create: CreateEscrow<'info>,
+
+    #[account(
+        seeds = [b"template", template.owner.as_ref(), &template.template_id.to_le_bytes()],
+        bump = template.bump,
+        constraint = template.mint == create.mint.key() @ EscrowError::MintMismatch
+    )]
+    pub template: Box<Account<'info, Template>>,
+
+    #[account(
+        init,
+        payer = create.buyer,
+        associated_token::mint = create.mint,
+        associated_token::authority = create.escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// 模板带平台费减免时必须传入
+    #[account(
+        init,
+        payer = create.buyer,
+        space = EscrowDiscount::LEN,
+        seeds = [b"discount", create.escrow.key().as_ref()],
+        bump
+    )]
+    pub template_discount: Option<Box<Account<'info, EscrowDiscount>>>,
+
+    /// 模板带交付期限时必须传入
+    #[account(
+        init,
+        payer = create.buyer,
+        space = DeliveryTerms::LEN,
+        seeds = [b"delivery_terms", create.escrow.key().as_ref()],
+        bump
+    )]
+    pub delivery_terms: Option<Box<Account<'info, DeliveryTerms>>>,
+
+    /// 模板带多个里程碑时必须传入
+    #[account(
+        init,
+        payer = create.buyer,
+        space = EscrowUnits::LEN,
+        seeds = [b"units", create.escrow.key().as_ref()],
+        bump
+    )]
+    pub units: Option<Box<Account<'info, EscrowUnits>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 按已完成托管再次购买的上下文
+#[derive(Accounts)]
+#[instruction(request_id: String)]
+pub struct RecreateEscrow<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = Escrow::LEN,
+        seeds = [b"escrow", buyer.key().as_ref(), request_id.as_bytes()],
+        bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        seeds = [
+            b"escrow",
+            previous_escrow.load()?.buyer.as_ref(),
+            previous_escrow.load()?.request_id().as_bytes()
+        ],
+        bump = previous_escrow.load()?.bump,
+        constraint = previous_escrow.load()?.buyer == buyer.key() @ EscrowError::OnlyBuyer
+    )]
+    pub previous_escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", buyer.key().as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", previous_escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = ProviderEscrowIndex::LEN,
+        seeds = [
+            b"provider_escrow",
+            previous_escrow.load()?.provider.as_ref(),
+            &provider_stats.total_escrows.to_le_bytes()
+        ],
+        bump
+    )]
+    pub provider_escrow_index: Box<Account<'info, ProviderEscrowIndex>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    /// 可选：提供商注册资料，配置要求注册时必须提供
+    #[account(
+        seeds = [b"provider", previous_escrow.load()?.provider.as_ref()],
+        bump = provider_profile.bump,
+        seeds::program = datanexus_registry::ID
+    )]
+    pub provider_profile: Option<Box<Account<'info, ProviderProfile>>>,
+
+    /// 可选：原托管的交付条款，须与 `delivery_terms` 一起提供
+    #[account(
+        seeds = [b"delivery_terms", previous_escrow.key().as_ref()],
+        bump = previous_delivery_terms.bump
+    )]
+    pub previous_delivery_terms: Option<Box<Account<'info, DeliveryTerms>>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = DeliveryTerms::LEN,
+        seeds = [b"delivery_terms", escrow.key().as_ref()],
+        bump
+    )]
+    pub delivery_terms: Option<Box<Account<'info, DeliveryTerms>>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Optional buyer compliance attestation, validated in the handler
+    pub buyer_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional provider compliance attestation, validated in the handler
+    pub provider_attestation: Option<UncheckedAccount<'info>>,
+
+    /// 可选：提供商持有的徽章，托管金额达到 `badge_threshold` 时必须提供
+    #[account(
+        seeds = [
+            b"badge",
+            previous_escrow.load()?.provider.as_ref(),
+            &[config.settings.required_badge]
+        ],
+        bump = provider_badge.bump
+    )]
+    pub provider_badge: Option<Box<Account<'info, Badge>>>,
+
+    /// CHECK: Buyer blacklist PDA, may be uninitialized; checked in `check_not_blacklisted`
+    #[account(seeds = [b"blacklist", previous_escrow.load()?.buyer.as_ref()], bump)]
+    pub buyer_blacklist: UncheckedAccount<'info>,
+
+    /// CHECK: Provider blacklist PDA, may be uninitialized; checked in `check_not_blacklisted`
+    #[account(seeds = [b"blacklist", previous_escrow.load()?.provider.as_ref()], bump)]
+    pub provider_blacklist: UncheckedAccount<'info>,
+}
+
+/// 以 Wormhole 跨链转账创建托管的上下文
+///
+/// 代币桥相关账户原样传给代币桥，由其校验 VAA、来源链注册和包装代币
+#[derive(Accounts)]
+#[instruction(request_id: String)]
+pub struct CreateEscrowFromBridge<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Escrow::LEN,
+        seeds = [b"escrow", buyer.key().as_ref(), request_id.as_bytes()],
+        bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    /// 中继者，支付账户租金和代币桥兑付记录
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// 买家，须与 VAA 载荷一致并签名：载荷由来源链上的发送方编写，
+    /// 不签名时任何人都能以他人名义创建托管
+    pub buyer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = BuyerStats::LEN,
+        seeds = [b"buyer_stats", buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    /// CHECK: Provider address, matched against the VAA payload
+    pub provider: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ProviderStats::LEN,
+        seeds = [b"provider_stats", provider.key().as_ref()],
+        bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ProviderEscrowIndex::LEN,
+        seeds = [
+            b"provider_escrow",
+            provider.key().as_ref(),
+            &provider_stats.total_escrows.to_le_bytes()
+        ],
+        bump
+    )]
+    pub provider_escrow_index: Box<Account<'info, ProviderEscrowIndex>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ProtocolStats::LEN,
+        seeds = [b"protocol_stats"],
+        bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    /// 可选：提供商注册资料，配置要求注册时必须提供
+    #[account(
+        seeds = [b"provider", provider.key().as_ref()],
+        bump = provider_profile.bump,
+        seeds::program = datanexus_registry::ID
+    )]
+    pub provider_profile: Option<Box<Account<'info, ProviderProfile>>>,
+
+    /// CHECK: Platform address, must be the config admin and match the VAA payload
+    #[account(address = config.admin @ EscrowError::PlatformMismatch)]
+    pub platform: UncheckedAccount<'info>,
+
+    #[account(mut, token::mint = wrapped_mint, token::authority = escrow)]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Redeemer PDA, signs the token bridge redemption for this program
+    #[account(seeds = [b"redeemer"], bump)]
+    pub redeemer: UncheckedAccount<'info>,
+
+    /// 赎回者的代币账户，代币桥兑付到此后转入托管
+    #[account(mut, token::mint = wrapped_mint, token::authority = redeemer)]
+    pub redeemer_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Token bridge config, checked by the token bridge
+    pub token_bridge_config: UncheckedAccount<'info>,
+
+    /// CHECK: Posted VAA, owner checked in `read_bridge_payment` and verified by the token bridge
+    pub posted_vaa: UncheckedAccount<'info>,
+
+    /// CHECK: Token bridge claim PDA, created by the token bridge to prevent replays
+    #[account(mut)]
+    pub claim: UncheckedAccount<'info>,
+
+    /// CHECK: Token bridge registration of the emitter chain, checked by the token bridge
+    pub foreign_endpoint: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub wrapped_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Wrapped token metadata, checked by the token bridge
+    pub wrapped_meta: UncheckedAccount<'info>,
+
+    /// CHECK: Token bridge mint authority, checked by the token bridge
+    pub mint_authority: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Wormhole core bridge program
+    #[account(address = WORMHOLE_PROGRAM_ID)]
+    pub wormhole_program: UncheckedAccount<'info>,
+
+    /// CHECK: Wormhole token bridge program
+    #[account(address = TOKEN_BRIDGE_PROGRAM_ID)]
+    pub token_bridge_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Optional buyer compliance attestation, validated in the handler
+    pub buyer_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional provider compliance attestation, validated in the handler
+    pub provider_attestation: Option<UncheckedAccount<'info>>,
+
+    /// 可选：提供商持有的徽章，托管金额达到 `badge_threshold` 时必须提供
+    #[account(
+        seeds = [b"badge", provider.key().as_ref(), &[config.settings.required_badge]],
+        bump = provider_badge.bump
+    )]
+    pub provider_badge: Option<Box<Account<'info, Badge>>>,
+
+    /// CHECK: Buyer blacklist PDA, may be uninitialized; checked in `check_not_blacklisted`
+    #[account(seeds = [b"blacklist", buyer.key().as_ref()], bump)]
+    pub buyer_blacklist: UncheckedAccount<'info>,
+
+    /// CHECK: Provider blacklist PDA, may be uninitialized; checked in `check_not_blacklisted`
+    #[account(seeds = [b"blacklist", provider.key().as_ref()], bump)]
+    pub provider_blacklist: UncheckedAccount<'info>,
+}
+
+impl<'info> CreateEscrowFromBridge<'info> {
+    /// CPI 代币桥兑付转账到赎回者的代币账户，再全额转入托管，返回到账数量
+    ///
+    /// 附带载荷的转账不收中继费，`to_fees` 与接收账户相同
+    fn redeem(&mut self, redeemer_bump: u8) -> Result<u64> {
+        let before = self.redeemer_token_account.amount;
+        let signer: &[&[&[u8]]] = &[&[b"redeemer", &[redeemer_bump]]];
+
+        let ix = Instruction {
+            program_id: TOKEN_BRIDGE_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.payer.key(), true),
+                AccountMeta::new_readonly(self.token_bridge_config.key(), false),
+                AccountMeta::new_readonly(self.posted_vaa.key(), false),
+                AccountMeta::new(self.claim.key(), false),
+                AccountMeta::new_readonly(self.foreign_endpoint.key(), false),
+                AccountMeta::new(self.redeemer_token_account.key(), false),
+                AccountMeta::new_readonly(self.redeemer.key(), true),
+                AccountMeta::new(self.redeemer_token_account.key(), false),
+                AccountMeta::new(self.wrapped_mint.key(), false),
+                AccountMeta::new_readonly(self.wrapped_meta.key(), false),
+                AccountMeta::new_readonly(self.mint_authority.key(), false),
+                AccountMeta::new_readonly(self.rent.key(), false),
+                AccountMeta::new_readonly(self.system_program.key(), false),
+                AccountMeta::new_readonly(self.wormhole_program.key(), false),
+                AccountMeta::new_readonly(self.token_program.key(), false),
+            ],
+            data: vec![COMPLETE_WRAPPED_WITH_PAYLOAD],
+        };
+        invoke_signed(
+            &ix,
+            &[
+                self.payer.to_account_info(),
+                self.token_bridge_config.to_account_info(),
+                self.posted_vaa.to_account_info(),
+                self.claim.to_account_info(),
+                self.foreign_endpoint.to_account_info(),
+                self.redeemer_token_account.to_account_info(),
+                self.redeemer.to_account_info(),
+                self.wrapped_mint.to_account_info(),
+                self.wrapped_meta.to_account_info(),
+                self.mint_authority.to_account_info(),
+                self.rent.to_account_info(),
+                self.system_program.to_account_info(),
+                self.wormhole_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.token_bridge_program.to_account_info(),
+            ],
+            signer,
+        )?;
+
+        self.redeemer_token_account.reload()?;
+        let amount = self
+            .redeemer_token_account
+            .amount
+            .checked_sub(before)
+            .ok_or(EscrowError::InvalidAmount)?;
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.redeemer_token_account.to_account_info(),
+                    to: self.escrow_token_account.to_account_info(),
+                    authority: self.redeemer.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+
+        Ok(amount)
+    }
+}
+
+impl<'info> CreateEscrow<'info> {
+    /// 初始化托管条款并更新统计，托管处于 Created 状态等待充值
+    fn create(
+        &mut self,
+        bumps: &CreateEscrowBumps,
+        amount: u64,
+        request_id: String,
+        proposal_id: String,
+        coupon_code: Option<String>,
+    ) -> Result<()> {
+        check_escrow_parties(
+            &self.config.settings,
+            &self.buyer.key(),
+            &self.provider.key(),
+            amount,
+            self.provider_profile.as_deref(),
+            [&self.buyer_blacklist, &self.provider_blacklist],
+            self.provider_badge.as_deref(),
+            [self.buyer_attestation.as_ref(), self.provider_attestation.as_ref()],
+        )?;
+
+        // 可选：校验链上需求与提案（需同时提供）
+        match (&self.request, &self.proposal) {
+            (Some(request), Some(proposal)) => {
+                require!(
+                    request.status == RequestStatus::Open,
+                    EscrowError::ProposalMismatch
+                );
+                require_keys_eq!(
+                    proposal.request,
+                    request.key(),
+                    EscrowError::ProposalMismatch
+                );
+                require_keys_eq!(
+                    proposal.provider,
+                    self.provider.key(),
+                    EscrowError::ProposalMismatch
+                );
+                require!(
+                    proposal.proposal_id == proposal_id,
+                    EscrowError::ProposalMismatch
+                );
+                require!(amount == proposal.price, EscrowError::InvalidAmount);
+            }
+            (None, None) => {}
+            _ => return err!(EscrowError::ProposalMismatch),
+        }
+
+        // 需求带有类别或司法辖区限制时记录到托管，平台据此阻止释放
+        let restrictions = self
+            .request
+            .as_ref()
+            .map_or(0, |request| request.restrictions);
+        match self.restriction.as_mut() {
+            Some(restriction) => {
+                restriction.escrow = self.escrow.key();
+                restriction.flags = restrictions;
+                restriction.blocked = false;
+                restriction.reason = String::new();
+                restriction.blocked_at = None;
+                restriction.bump = bumps.restriction.unwrap();
+            }
+            None => require!(restrictions == 0, EscrowError::RestrictionRequired),
+        }
+
+        self.redeem_coupon(bumps, coupon_code)?;
+
+        let escrow_key = self.escrow.key();
+        let mut escrow = self.escrow.load_init()?;
+
+        // 初始化托管账户
+        escrow.buyer = self.buyer.key();
+        escrow.provider = self.provider.key();
+        escrow.platform = self.platform.key();
+        escrow.amount = amount;
+        escrow.set_ids(&request_id, &proposal_id)?;
+        escrow.set_status(EscrowStatus::Created);
+        escrow.created_at = Clock::get()?.unix_timestamp;
+        escrow.bump = bumps.escrow;
+        escrow.version = ESCROW_VERSION;
+        escrow.mint = self.mint.key();
+
+        // 可选：记录数据访问凭证铸币，铸币权限必须属于该提供商的访问凭证 PDA
+        if let Some(access_mint) = &self.access_mint {
+            let (access_authority, _) = Pubkey::find_program_address(
+                &[b"access", escrow.provider.as_ref()],
+                &crate::ID,
+            );
+            require!(
+                access_mint.mint_authority == Some(access_authority).into(),
+                EscrowError::InvalidAccessMint
+            );
+            escrow.access_mint = access_mint.key();
+        }
+
+        record_escrow_created(
+            &self.config.settings,
+            escrow_key,
+            &escrow,
+            &mut self.buyer_stats,
+            &mut self.provider_stats,
+            &mut self.provider_escrow_index,
+            &mut self.protocol_stats,
+            EscrowStatsBumps {
+                buyer_stats: bumps.buyer_stats,
+                provider_stats: bumps.provider_stats,
+                provider_escrow_index: bumps.provider_escrow_index,
+                protocol_stats: bumps.protocol_stats,
+            },
+        )?;
+
+        emit!(EscrowCreated {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            provider: escrow.provider,
+            amount,
+            request_id: escrow.request_id().to_string(),
+            timestamp: escrow.created_at,
+        });
+
+        msg!("Escrow created: {}", escrow_key);
+        msg!("Amount: {} USDC", amount);
+        msg!("Buyer: {}", escrow.buyer);
+        msg!("Provider: {}", escrow.provider);
+
+        Ok(())
+    }
+
+    /// 可选：CPI 优惠券程序校验优惠码并标记优惠券已使用，把券面记录到 `[b"discount", escrow]`
+    fn redeem_coupon(
+        &mut self,
+        bumps: &CreateEscrowBumps,
+        coupon_code: Option<String>,
+    ) -> Result<()> {
+        let (coupon, discount, redeemer, coupons_program, code) = match (
+            self.coupon.as_ref(),
+            self.discount.as_mut(),
+            self.coupon_redeemer.as_ref(),
+            self.coupons_program.as_ref(),
+            coupon_code,
+        ) {
+            (Some(coupon), Some(discount), Some(redeemer), Some(program), Some(code)) => {
+                (coupon, discount, redeemer, program, code)
+            }
+            (None, None, _, _, None) => return Ok(()),
+            _ => return err!(EscrowError::InvalidCoupon),
+        };
+
+        let redeemer_bump = bumps.coupon_redeemer.unwrap();
+        let seeds = &[COUPON_REDEEMER_SEED, &[redeemer_bump]];
+        datanexus_coupons::cpi::redeem_coupon(
+            CpiContext::new_with_signer(
+                coupons_program.to_account_info(),
+                datanexus_coupons::cpi::accounts::RedeemCoupon {
+                    coupon: coupon.to_account_info(),
+                    redeemer: redeemer.to_account_info(),
+                    buyer: self.buyer.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            self.escrow.key(),
+            code,
+        )?;
+
+        discount.escrow = self.escrow.key();
+        discount.coupon = coupon.key();
+        discount.discount = coupon.discount;
+        discount.bump = bumps.discount.unwrap();
+
+        msg!("Coupon redeemed: {}", coupon.key());
+        msg!("Discount: {:?}", coupon.discount);
+
+        Ok(())
+    }
+}
+
+impl<'info> CreateEscrowFromTemplate<'info> {
+    /// 把模板的平台费减免、交付期限和里程碑写入新托管
+    fn apply(&mut self, bumps: &CreateEscrowFromTemplateBumps, amount: u64) -> Result<()> {
+        let escrow = self.create.escrow.key();
+        let template = &self.template;
+        let terms = &template.terms;
+
+        match (terms.discount, self.template_discount.as_mut()) {
+            (Some(discount), Some(record)) => {
+                require!(self.create.coupon.is_none(), EscrowError::InvalidCoupon);
+                record.escrow = escrow;
+                record.coupon = template.key();
+                record.discount = discount;
+                record.bump = bumps.template_discount.unwrap();
+            }
+            (None, None) => {}
+            _ => return err!(EscrowError::InvalidTemplate),
+        }
+
+        match (terms.delivery_window > 0, self.delivery_terms.as_mut()) {
+            (true, Some(delivery_terms)) => {
+                delivery_terms.escrow = escrow;
+                delivery_terms.deadline = self
+                    .create
+                    .escrow
+                    .load()?
+                    .created_at
+                    .checked_add(terms.delivery_window)
+                    .unwrap();
+                delivery_terms.bump = bumps.delivery_terms.unwrap();
+                delivery_terms.late_penalty = terms.late_penalty;
+                delivery_terms.penalty_recipient = terms.penalty_recipient;
+            }
+            (false, None) => {}
+            _ => return err!(EscrowError::InvalidTemplate),
+        }
+
+        match (terms.milestones > 1, self.units.as_mut()) {
+            (true, Some(units)) => {
+                require!(
+                    amount.is_multiple_of(terms.milestones),
+                    EscrowError::InvalidUnits
+                );
+                units.escrow = escrow;
+                units.unit_price = amount / terms.milestones;
+                units.quantity = terms.milestones;
+                units.delivered = 0;
+                units.released = 0;
+                units.bump = bumps.units.unwrap();
+            }
+            (false, None) => {}
+            _ => return err!(EscrowError::InvalidTemplate),
+        }
+
+        msg!("Template applied: {}", template.key());
+
+        Ok(())
+    }
+}
+
+impl<'info> FundEscrow<'info> {
+    /// 从买家付款账户转入托管金额并更新成交量，`fund_escrow` 与 `fund_escrow_with_swap` 共用
+    ///
+    /// `hook_accounts` 为铸币转账钩子需要的额外账户
+    fn fund(&mut self, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(
+            self.escrow.load()?.status() == EscrowStatus::Created,
+            EscrowError::NotCreated
+        );
+        let now = Clock::get()?.unix_timestamp;
+
+        // 有报价时按报价金额充值；报价设有有效期，过期后不能按旧价格充值
+        let quote_info = self.quote.to_account_info();
+        let price = if quote_info.owner == &crate::ID {
+            let quote = EscrowQuote::try_deserialize(&mut &quote_info.try_borrow_data()?[..])?;
+            require!(now <= quote.expires_at, EscrowError::QuoteExpired);
+            quote.price
+        } else {
+            self.escrow.load()?.amount
+        };
+        let before = self.escrow_token_account.amount;
+
+        // 转账 USDC 到托管账户
+        transfer_tokens(
+            &self.token_program.to_account_info(),
+            self.buyer_token_account.to_account_info(),
+            &self.mint,
+            self.escrow_token_account.to_account_info(),
+            self.buyer.to_account_info(),
+            hook_accounts,
+            &[],
+            price,
+        )?;
+
+        // 带转账手续费的 Token-2022 铸币到账少于转出数量，托管金额按托管账户实际增加的余额记录，
+        // 之后的释放、退款和争议保证金都以此为准
+        self.escrow_token_account.reload()?;
+        let amount = self
+            .escrow_token_account
+            .amount
+            .checked_sub(before)
+            .ok_or(EscrowError::InvalidAmount)?;
+        require!(amount > 0, EscrowError::InvalidAmount);
+        if amount < price {
+            msg!("Transfer fee withheld: {} USDC", price - amount);
+        }
+
+        let escrow_key = self.escrow.key();
+        let mut escrow = self.escrow.load_mut()?;
+        escrow.amount = amount;
+        escrow.mint = self.mint.key();
+        escrow.set_status(EscrowStatus::Funded);
+        escrow.funded_at = now;
+
+        let buyer_stats = &mut self.buyer_stats;
+        buyer_stats.total_volume = buyer_stats.total_volume.checked_add(amount).unwrap();
+
+        let protocol_stats = &mut self.protocol_stats;
+        protocol_stats.total_volume = protocol_stats.total_volume.checked_add(amount).unwrap();
+
+        emit!(EscrowFunded {
+            escrow: escrow_key,
+            buyer: escrow.buyer,
+            amount,
+            timestamp: now,
+        });
+
+        msg!("Escrow funded: {}", escrow_key);
+        msg!("Amount: {} USDC", amount);
+
+        Ok(())
+    }
+}
+
+/// 记录美元估值的上下文
+#[derive(Accounts)]
+pub struct RecordValuation<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = EscrowValuation::LEN,
+        seeds = [b"valuation", escrow.key().as_ref()],
+        bump
+    )]
+    pub valuation: Box<Account<'info, EscrowValuation>>,
+
+    #[account(token::mint = mint, token::authority = escrow)]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Pyth PriceUpdateV2 account, parsed and validated in `read_pyth_price`
+    pub price_update: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 美元计价的上下文
+#[derive(Accounts)]
+pub struct DenominateInUsd<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = UsdPricing::LEN,
+        seeds = [b"usd_pricing", escrow.key().as_ref()],
+        bump
+    )]
+    pub usd_pricing: Box<Account<'info, UsdPricing>>,
+
+    #[account(token::mint = mint, token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    /// CHECK: Pyth PriceUpdateV2 account, parsed and validated in `read_pyth_price`
+    pub price_update: UncheckedAccount<'info>,
+
+    /// CHECK: Price quote PDA, must be uninitialized
+    #[account(seeds = [b"quote", escrow.key().as_ref()], bump)]
+    pub quote: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 约定兑换结算的上下文
+#[derive(Accounts)]
+pub struct SetSettlementSwap<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = SettlementSwap::LEN,
+        seeds = [b"settlement_swap", escrow.key().as_ref()],
+        bump
+    )]
+    pub settlement_swap: Box<Account<'info, SettlementSwap>>,
+
+    #[account(token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub settle_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 要求交付证明的上下文
+#[derive(Accounts)]
+pub struct RequireDeliveryAttestation<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = DeliveryOracle::LEN,
+        seeds = [b"delivery_oracle", escrow.key().as_ref()],
+        bump
+    )]
+    pub delivery_oracle: Box<Account<'info, DeliveryOracle>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 要求交付解密密钥的上下文
+#[derive(Accounts)]
+pub struct RequireKeyDelivery<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = KeyDelivery::LEN,
+        seeds = [b"key_delivery", escrow.key().as_ref()],
+        bump
+    )]
+    pub key_delivery: Box<Account<'info, KeyDelivery>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 提交解密密钥的上下文
+#[derive(Accounts)]
+pub struct DeliverKey<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_delivery", escrow.key().as_ref()],
+        bump = key_delivery.bump
+    )]
+    pub key_delivery: Box<Account<'info, KeyDelivery>>,
+}
+
+/// 承诺交付内容的上下文
+#[derive(Accounts)]
+pub struct CommitDelivery<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(
+        init,
+        payer = provider,
+        space = DeliveryCommitment::LEN,
+        seeds = [b"commitment", escrow.key().as_ref()],
+        bump
+    )]
+    pub delivery_commitment: Box<Account<'info, DeliveryCommitment>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 揭示交付内容的上下文
+#[derive(Accounts)]
+pub struct RevealDelivery<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"commitment", escrow.key().as_ref()],
+        bump = delivery_commitment.bump
+    )]
+    pub delivery_commitment: Box<Account<'info, DeliveryCommitment>>,
+
+    /// CHECK: Shadow Drive storage account, required for Shadow Drive storage references
+    pub storage_account: Option<UncheckedAccount<'info>>,
+}
+
+/// 开启收益模式的上下文
+///
+/// 储备相关账户由借贷程序校验
+#[derive(Accounts)]
+pub struct EnableYield<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = YieldPosition::LEN,
+        seeds = [b"yield", escrow.key().as_ref()],
+        bump
+    )]
+    pub yield_position: Box<Account<'info, YieldPosition>>,
+
+    #[account(mut, token::authority = escrow)]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 托管持有的抵押代币账户
+    #[account(
+        init,
+        payer = buyer,
+        associated_token::mint = reserve_collateral_mint,
+        associated_token::authority = escrow
+    )]
+    pub collateral_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Solend reserve, checked by the lending program
+    #[account(mut, owner = SOLEND_PROGRAM_ID)]
+    pub reserve: UncheckedAccount<'info>,
+
+    /// CHECK: Reserve liquidity supply, checked by the lending program
+    #[account(mut)]
+    pub reserve_liquidity_supply: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub reserve_collateral_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Lending market, checked by the lending program
+    pub lending_market: UncheckedAccount<'info>,
+
+    /// CHECK: Lending market authority PDA, checked by the lending program
+    pub lending_market_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Solend program
+    #[account(address = SOLEND_PROGRAM_ID)]
+    pub lending_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> EnableYield<'info> {
+    /// 托管 PDA 签名把托管资金存入借贷储备
+    fn deposit(&self, amount: u64) -> Result<()> {
+        let escrow = *self.escrow.load()?;
+        lending_cpi(
+            DEPOSIT_RESERVE_LIQUIDITY,
+            amount,
+            [
+                self.escrow_token_account.to_account_info(),
+                self.collateral_account.to_account_info(),
+                self.reserve.to_account_info(),
+                self.reserve_liquidity_supply.to_account_info(),
+                self.reserve_collateral_mint.to_account_info(),
+                self.lending_market.to_account_info(),
+                self.lending_market_authority.to_account_info(),
+                self.escrow.to_account_info(),
+                self.token_program.to_account_info(),
+                self.lending_program.to_account_info(),
+            ],
+            &escrow,
+        )
+    }
+}
+
+/// 赎回收益模式存款的上下文
+#[derive(Accounts)]
+pub struct WithdrawYield<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    /// 买家、提供商或平台
+    pub caller: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"yield", escrow.key().as_ref()],
+        bump = yield_position.bump,
+        has_one = reserve,
+        close = buyer
+    )]
+    pub yield_position: Box<Account<'info, YieldPosition>>,
+
+    /// CHECK: Escrow buyer, receives the closed accounts' rent
+    #[account(mut, address = escrow.load()?.buyer)]
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.load()?.buyer
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = config.treasury,
+        token::mint = escrow_token_account.mint
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::authority = escrow)]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, address = yield_position.collateral)]
+    pub collateral_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Solend reserve, matched against the yield position
+    #[account(mut)]
+    pub reserve: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub reserve_collateral_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Reserve liquidity supply, checked by the lending program
+    #[account(mut)]
+    pub reserve_liquidity_supply: UncheckedAccount<'info>,
+
+    /// CHECK: Lending market, checked by the lending program
+    pub lending_market: UncheckedAccount<'info>,
+
+    /// CHECK: Lending market authority PDA, checked by the lending program
+    pub lending_market_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Solend program
+    #[account(address = SOLEND_PROGRAM_ID)]
+    pub lending_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WithdrawYield<'info> {
+    /// 托管 PDA 签名把抵押代币全部赎回到托管代币账户
+    fn redeem(&self, collateral: u64) -> Result<()> {
+        let escrow = *self.escrow.load()?;
+        lending_cpi(
+            REDEEM_RESERVE_COLLATERAL,
+            collateral,
+            [
+                self.collateral_account.to_account_info(),
+                self.escrow_token_account.to_account_info(),
+                self.reserve.to_account_info(),
+                self.reserve_collateral_mint.to_account_info(),
+                self.reserve_liquidity_supply.to_account_info(),
+                self.lending_market.to_account_info(),
+                self.lending_market_authority.to_account_info(),
+                self.escrow.to_account_info(),
+                self.token_program.to_account_info(),
+                self.lending_program.to_account_info(),
+            ],
+            &escrow,
+        )
+    }
+}
+
+/// 标记已交付的上下文
+#[derive(Accounts)]
+pub struct MarkDelivered<'info> {
+    #[account(mut)]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Provider's bond in the staking program, required above the collateral threshold
+    pub provider_bond: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Delivery oracle PDA, may be uninitialized; checked in `verify_attestation`
+    #[account(seeds = [b"delivery_oracle", escrow.key().as_ref()], bump)]
+    pub delivery_oracle: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar, required when the buyer asked for an oracle attestation
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Key delivery PDA, may be uninitialized; checked in `verify_key_delivery`
+    #[account(seeds = [b"key_delivery", escrow.key().as_ref()], bump)]
+    pub key_delivery: UncheckedAccount<'info>,
+
+    /// CHECK: Delivery commitment PDA, may be uninitialized; checked in `verify_revealed`
+    #[account(seeds = [b"commitment", escrow.key().as_ref()], bump)]
+    pub delivery_commitment: UncheckedAccount<'info>,
+
+    /// 数据块清单，传入 `chunks` 时必填；拒收后重新交付时覆盖
+    #[account(
+        init_if_needed,
+        payer = provider,
+        space = ChunkManifest::LEN,
+        seeds = [b"manifest", escrow.key().as_ref()],
+        bump
+    )]
+    pub chunk_manifest: Option<Box<Account<'info, ChunkManifest>>>,
+
+    /// CHECK: Provider blacklist PDA, may be uninitialized; checked in `check_not_blacklisted`
+    #[account(seeds = [b"blacklist", provider.key().as_ref()], bump)]
+    pub provider_blacklist: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MarkDelivered<'info> {
+    /// 记录数据块清单，`chunks` 和清单账户须同时提供
+    fn record_chunks(&mut self, chunks: Option<ChunkManifestParams>, bump: Option<u8>) -> Result<()> {
+        let escrow = self.escrow.key();
+        match (self.chunk_manifest.as_mut(), chunks) {
+            (Some(manifest), Some(chunks)) => {
+                require!(
+                    chunks.chunk_count > 0 && chunks.chunk_count <= MAX_CHUNKS,
+                    EscrowError::InvalidChunkManifest
+                );
+                manifest.escrow = escrow;
+                manifest.root = chunks.root;
+                manifest.chunk_count = chunks.chunk_count;
+                manifest.disputed_chunks = Vec::new();
+                manifest.bump = bump.unwrap();
+
+                msg!("Chunk manifest: {} chunks", chunks.chunk_count);
+            }
+            (None, None) => {}
+            _ => return err!(EscrowError::InvalidChunkManifest),
+        }
+
+        Ok(())
+    }
+
+    /// 提供商承诺过交付内容时，校验已揭示
+    fn verify_revealed(&self) -> Result<()> {
+        if let Some(commitment) = read_delivery_commitment(&self.delivery_commitment)? {
+            require!(
+                commitment.revealed_at.is_some(),
+                EscrowError::CommitmentNotRevealed
+            );
+        }
+
+        Ok(())
+    }
+
+    /// 买家要求交付解密密钥时，校验提供商已提交加密的密钥
+    fn verify_key_delivery(&self) -> Result<()> {
+        let key_delivery = self.key_delivery.to_account_info();
+        if key_delivery.owner != &crate::ID {
+            return Ok(());
+        }
+        let key_delivery =
+            KeyDelivery::try_deserialize(&mut &key_delivery.try_borrow_data()?[..])?;
+        require!(
+            key_delivery.delivered_at.is_some(),
+            EscrowError::KeyNotDelivered
+        );
+
+        Ok(())
+    }
+
+    /// 买家要求预言机证明时，校验前一条指令是预言机对 `托管地址 || 交付哈希` 的 Ed25519 签名
+    fn verify_attestation(&self, delivery_hash: &[u8; 32]) -> Result<()> {
+        let delivery_oracle = self.delivery_oracle.to_account_info();
+        if delivery_oracle.owner != &crate::ID {
+            return Ok(());
+        }
+        let oracle =
+            DeliveryOracle::try_deserialize(&mut &delivery_oracle.try_borrow_data()?[..])?.oracle;
+
+        let instructions_sysvar = self
+            .instructions_sysvar
+            .as_ref()
+            .ok_or(EscrowError::MissingAttestation)?;
+        let ix = datanexus_sigverify::preceding_instruction(instructions_sysvar)?
+            .ok_or(EscrowError::MissingAttestation)?;
+
+        let mut expected = self.escrow.key().to_bytes().to_vec();
+        expected.extend_from_slice(delivery_hash);
+        datanexus_sigverify::verify_signature(&ix, &oracle, &expected).map_err(|err| match err {
+            SignatureError::Missing => EscrowError::MissingAttestation,
+            SignatureError::Invalid => EscrowError::InvalidAttestation,
+        })?;
+
+        msg!("Delivery attested by oracle: {}", oracle);
+
+        Ok(())
+    }
+}
+
+/// 约定交付截止时间的上下文
+#[derive(Accounts)]
+pub struct SetDeliveryDeadline<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = DeliveryTerms::LEN,
+        seeds = [b"delivery_terms", escrow.key().as_ref()],
+        bump
+    )]
+    pub delivery_terms: Box<Account<'info, DeliveryTerms>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 报价的上下文
+#[derive(Accounts)]
+pub struct QuotePrice<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = EscrowQuote::LEN,
+        seeds = [b"quote", escrow.key().as_ref()],
+        bump
+    )]
+    pub quote: Box<Account<'info, EscrowQuote>>,
+
+    /// CHECK: Unit pricing PDA, must be uninitialized
+    #[account(seeds = [b"units", escrow.key().as_ref()], bump)]
+    pub units: UncheckedAccount<'info>,
+
+    /// CHECK: Download cap PDA, must be uninitialized
+    #[account(seeds = [b"downloads", escrow.key().as_ref()], bump)]
+    pub downloads: UncheckedAccount<'info>,
+
+    /// CHECK: USD pricing PDA, must be uninitialized
+    #[account(seeds = [b"usd_pricing", escrow.key().as_ref()], bump)]
+    pub usd_pricing: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 设置按件计价的上下文
+#[derive(Accounts)]
+pub struct SetUnitPricing<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = EscrowUnits::LEN,
+        seeds = [b"units", escrow.key().as_ref()],
+        bump
+    )]
+    pub units: Box<Account<'info, EscrowUnits>>,
+
+    /// CHECK: Price quote PDA, must be uninitialized
+    #[account(seeds = [b"quote", escrow.key().as_ref()], bump)]
+    pub quote: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 按件交付的上下文
+#[derive(Accounts)]
+pub struct DeliverUnits<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub buyer: Signer<'info>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"units", escrow.key().as_ref()],
+        bump = units.bump
+    )]
+    pub units: Box<Account<'info, EscrowUnits>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(mut, token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(mut, token::mint = escrow_token_account.mint)]
+    pub provider_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// 国库代币账户，国库份额或逾期罚金非零时必填
+    #[account(mut, address = config.treasury)]
+    pub treasury_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 奖励池代币账户，奖励池份额非零时必填
+    #[account(mut, address = config.rewards_pool)]
+    pub rewards_pool_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 保险池，`insurance_bps` 非零时必填
+    #[account(mut, seeds = [b"insurance_vault"], bump)]
+    pub insurance_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// 费用账本，开启 `fee_ledger` 时必填
+    #[account(mut, seeds = [b"fee_ledger"], bump = fee_ledger.bump)]
+    pub fee_ledger: Option<Box<Account<'info, FeeLedger>>>,
+
+    /// 共享费用金库，开启 `fee_ledger` 时必填
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Provider sales history PDA, may be uninitialized; appended in `record_sale`
+    #[account(mut, seeds = [b"sales_history", escrow.load()?.provider.as_ref()], bump)]
+    pub sales_history: UncheckedAccount<'info>,
+
+    /// CHECK: Restriction PDA, may be uninitialized; its data category is recorded in the sale
+    #[account(seeds = [b"restriction", escrow.key().as_ref()], bump)]
+    pub restriction: UncheckedAccount<'info>,
+
+    /// CHECK: Provider blacklist PDA, may be uninitialized; checked in `check_not_blacklisted`
+    #[account(seeds = [b"blacklist", provider.key().as_ref()], bump)]
+    pub provider_blacklist: UncheckedAccount<'info>,
+}
+
+/// 开启销售记录的上下文
+#[derive(Accounts)]
+pub struct InitSalesHistory<'info> {
+    #[account(
+        init,
+        payer = provider,
+        space = SalesHistory::LEN,
+        seeds = [b"sales_history", provider.key().as_ref()],
+        bump
+    )]
+    pub sales_history: Box<Account<'info, SalesHistory>>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 设置下载上限的上下文
+#[derive(Accounts)]
+pub struct SetDownloadCap<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = EscrowDownloads::LEN,
+        seeds = [b"downloads", escrow.key().as_ref()],
+        bump
+    )]
+    pub downloads: Box<Account<'info, EscrowDownloads>>,
+
+    /// CHECK: Price quote PDA, must be uninitialized
+    #[account(seeds = [b"quote", escrow.key().as_ref()], bump)]
+    pub quote: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 消耗一次下载的上下文
+#[derive(Accounts)]
+pub struct ConsumeDownload<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    /// 提供商网关，须与下载记录一致
+    pub gateway: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"downloads", escrow.key().as_ref()],
+        bump = downloads.bump
+    )]
+    pub downloads: Box<Account<'info, EscrowDownloads>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(mut, token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// 提供商代币账户，网关代为调用时须归提供商所有
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.load()?.provider,
+    )]
+    pub provider_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// 国库代币账户，国库份额或逾期罚金非零时必填
+    #[account(mut, address = config.treasury)]
+    pub treasury_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 奖励池代币账户，奖励池份额非零时必填
+    #[account(mut, address = config.rewards_pool)]
+    pub rewards_pool_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 保险池，`insurance_bps` 非零时必填
+    #[account(mut, seeds = [b"insurance_vault"], bump)]
+    pub insurance_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// 费用账本，开启 `fee_ledger` 时必填
+    #[account(mut, seeds = [b"fee_ledger"], bump = fee_ledger.bump)]
+    pub fee_ledger: Option<Box<Account<'info, FeeLedger>>>,
+
+    /// 共享费用金库，开启 `fee_ledger` 时必填
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Provider sales history PDA, may be uninitialized; appended in `record_sale`
+    #[account(mut, seeds = [b"sales_history", escrow.load()?.provider.as_ref()], bump)]
+    pub sales_history: UncheckedAccount<'info>,
+
+    /// CHECK: Restriction PDA, may be uninitialized; its data category is recorded in the sale
+    #[account(seeds = [b"restriction", escrow.key().as_ref()], bump)]
+    pub restriction: UncheckedAccount<'info>,
+
+    /// CHECK: Provider blacklist PDA, may be uninitialized; checked in `check_not_blacklisted`
+    #[account(seeds = [b"blacklist", escrow.load()?.provider.as_ref()], bump)]
+    pub provider_blacklist: UncheckedAccount<'info>,
+}
+
+/// 追加奖励金的上下文
+#[derive(Accounts)]
+pub struct FundBonus<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = EscrowBonus::LEN,
+        seeds = [b"bonus", escrow.key().as_ref()],
+        bump
+    )]
+    pub bonus: Box<Account<'info, EscrowBonus>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"bonus_vault", escrow.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow,
+        token::token_program = token_program,
+    )]
+    pub bonus_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// 托管代币账户，奖励金须与托管使用同一币种
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(mut, token::mint = mint, token::authority = buyer)]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 退还未结算奖励金的上下文
+#[derive(Accounts)]
+pub struct ReclaimBonus<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"bonus", escrow.key().as_ref()],
+        bump = bonus.bump
+    )]
+    pub bonus: Box<Account<'info, EscrowBonus>>,
+
+    #[account(mut, seeds = [b"bonus_vault", escrow.key().as_ref()], bump)]
+    pub bonus_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = bonus_vault.mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(address = bonus_vault.mint)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+}
+
+/// 拒收交付的上下文
+#[derive(Accounts)]
+pub struct RejectDelivery<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = DeliveryRejection::LEN,
+        seeds = [b"rejection", escrow.key().as_ref()],
+        bump
+    )]
+    pub rejection: Box<Account<'info, DeliveryRejection>>,
+
+    /// CHECK: Challenge PDA, may be uninitialized; checked in `reset_delivery_checks`
+    #[account(seeds = [b"challenge", escrow.key().as_ref()], bump)]
+    pub challenge: UncheckedAccount<'info>,
+
+    /// CHECK: Dataset proof PDA, may be uninitialized; reset in `reset_delivery_checks`
+    #[account(mut, seeds = [b"dataset_proof", escrow.key().as_ref()], bump)]
+    pub dataset_proof: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RejectDelivery<'info> {
+    /// 拒收前抽查须已响应；已验证的数据集属性证明绑定旧交付，清除后须重新提交
+    fn reset_delivery_checks(&self) -> Result<()> {
+        let challenge_info = self.challenge.to_account_info();
+        if challenge_info.owner == &crate::ID {
+            let challenge =
+                ChunkChallenge::try_deserialize(&mut &challenge_info.try_borrow_data()?[..])?;
+            require!(
+                challenge.responded_at.is_some(),
+                EscrowError::ChallengeOpen
+            );
+        }
+
+        let proof_info = self.dataset_proof.to_account_info();
+        if proof_info.owner == &crate::ID {
+            let mut proof = DatasetProof::try_deserialize(&mut &proof_info.try_borrow_data()?[..])?;
+            proof.verified_at = None;
+            proof.try_serialize(&mut &mut proof_info.try_borrow_mut_data()?[..])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 重新交付逾期退款的上下文
+#[derive(Accounts)]
+pub struct ExpireRejection<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        seeds = [b"rejection", escrow.key().as_ref()],
+        bump = rejection.bump
+    )]
+    pub rejection: Box<Account<'info, DeliveryRejection>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+}
+
+/// 设置访问有效期的上下文
+#[derive(Accounts)]
+pub struct SetAccessWindow<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(
+        init,
+        payer = provider,
+        space = AccessWindow::LEN,
+        seeds = [b"access_window", escrow.key().as_ref()],
+        bump
+    )]
+    pub access_window: Box<Account<'info, AccessWindow>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 撤销过期访问凭证的上下文
+#[derive(Accounts)]
+pub struct RevokeExpiredAccess<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"access_window", escrow.key().as_ref()],
+        bump = access_window.bump
+    )]
+    pub access_window: Box<Account<'info, AccessWindow>>,
+
+    #[account(
+        address = escrow.load()?.access_mint @ EscrowError::InvalidAccessMint
+    )]
+    pub access_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mut,
+        address = access_window.token_account,
+        token::mint = access_mint,
+        token::token_program = access_token_program,
+    )]
+    pub buyer_access_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// CHECK: PDA freeze authority for the provider's access tokens
+    #[account(seeds = [b"access", escrow.load()?.provider.as_ref()], bump)]
+    pub access_authority: UncheckedAccount<'info>,
+
+    pub access_token_program: Interface<'info, TokenInterface>,
+}
+
+/// 转售程序更新访问凭证账户的上下文
+#[derive(Accounts)]
+pub struct TransferAccess<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"access_window", escrow.key().as_ref()],
+        bump = access_window.bump
+    )]
+    pub access_window: Box<Account<'info, AccessWindow>>,
+
+    #[account(
+        constraint = token_account.mint == escrow.load()?.access_mint
+            @ EscrowError::InvalidAccessMint
+    )]
+    pub token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(seeds = [RESALE_AUTHORITY_SEED], bump, seeds::program = RESALE_PROGRAM_ID)]
+    pub resale_authority: Signer<'info>,
+}
+
+/// 要求数据集属性证明的上下文
+#[derive(Accounts)]
+pub struct RequireDatasetProof<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = DatasetProof::LEN,
+        seeds = [b"dataset_proof", escrow.key().as_ref()],
+        bump
+    )]
+    pub dataset_proof: Box<Account<'info, DatasetProof>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 提交数据集属性证明的上下文
+#[derive(Accounts)]
+pub struct SubmitDatasetProof<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"dataset_proof", escrow.key().as_ref()],
+        bump = dataset_proof.bump
+    )]
+    pub dataset_proof: Box<Account<'info, DatasetProof>>,
+
+    /// CHECK: Verifier program chosen by the buyer
+    #[account(
+        executable,
+        address = dataset_proof.verifier @ EscrowError::InvalidVerifier
+    )]
+    pub verifier: UncheckedAccount<'info>,
+}
+
+/// 抽查数据块的上下文
+#[derive(Accounts)]
+pub struct ChallengeChunks<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [b"manifest", escrow.key().as_ref()],
+        bump = chunk_manifest.bump
+    )]
+    pub chunk_manifest: Box<Account<'info, ChunkManifest>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = ChunkChallenge::LEN,
+        seeds = [b"challenge", escrow.key().as_ref()],
+        bump
+    )]
+    pub challenge: Box<Account<'info, ChunkChallenge>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 响应抽查的上下文
+#[derive(Accounts)]
+pub struct RespondChallenge<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        seeds = [b"manifest", escrow.key().as_ref()],
+        bump = chunk_manifest.bump
+    )]
+    pub chunk_manifest: Box<Account<'info, ChunkManifest>>,
+
+    #[account(
+        mut,
+        seeds = [b"challenge", escrow.key().as_ref()],
+        bump = challenge.bump
+    )]
+    pub challenge: Box<Account<'info, ChunkChallenge>>,
+}
+
+/// 抽查逾期退款的上下文
+#[derive(Accounts)]
+pub struct ExpireChallenge<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        seeds = [b"challenge", escrow.key().as_ref()],
+        bump = challenge.bump
+    )]
+    pub challenge: Box<Account<'info, ChunkChallenge>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+}
+
+/// 确认并释放资金的上下文
+///
+/// `receipt_*` 及其后的程序账户均为可选：
+/// - 提供 `receipt_record` 时写入收据记录，铸造任一种收据都需要它
+/// - 提供 `receipt_mint` 等账户时铸造收据 NFT
+/// - 提供 `receipt_merkle_tree` 等账户时铸造压缩收据（cNFT）
+#[derive(Accounts)]
+pub struct ConfirmAndRelease<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(mut)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(mut)]
+    pub provider_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// 买家代币账户，逾期交付需要退还买家或给小费时必填
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 国库代币账户，国库份额或逾期罚金非零时必填
+    #[account(mut, address = config.treasury)]
+    pub treasury_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 奖励池代币账户，奖励池份额非零时必填
+    #[account(mut, address = config.rewards_pool)]
+    pub rewards_pool_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 保险池，`insurance_bps` 非零时必填
+    #[account(mut, seeds = [b"insurance_vault"], bump)]
+    pub insurance_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// 费用账本，开启 `fee_ledger` 时必填
+    #[account(mut, seeds = [b"fee_ledger"], bump = fee_ledger.bump)]
+    pub fee_ledger: Option<Box<Account<'info, FeeLedger>>>,
+
+    /// 共享费用金库，开启 `fee_ledger` 时必填
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// CHECK: Delivery terms PDA, may be uninitialized; checked in `delivery_terms`
+    #[account(seeds = [b"delivery_terms", escrow.key().as_ref()], bump)]
+    pub delivery_terms: UncheckedAccount<'info>,
+
+    /// CHECK: Coupon discount PDA, may be uninitialized; checked in `escrow_discount`
+    #[account(seeds = [b"discount", escrow.key().as_ref()], bump)]
+    pub discount: UncheckedAccount<'info>,
+
+    /// CHECK: Bonus PDA, may be uninitialized; checked in `settle_bonus`
+    #[account(mut, seeds = [b"bonus", escrow.key().as_ref()], bump)]
+    pub bonus: UncheckedAccount<'info>,
+
+    /// CHECK: Bonus vault PDA, only used when the bonus PDA is initialized
+    #[account(mut, seeds = [b"bonus_vault", escrow.key().as_ref()], bump)]
+    pub bonus_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Valuation PDA, may be uninitialized; checked in `revalue`
+    #[account(mut, seeds = [b"valuation", escrow.key().as_ref()], bump)]
+    pub valuation: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth PriceUpdateV2 account, required when the escrow has a valuation
+    pub price_update: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: USD pricing PDA, may be uninitialized; checked in `usd_release_amount`
+    #[account(mut, seeds = [b"usd_pricing", escrow.key().as_ref()], bump)]
+    pub usd_pricing: UncheckedAccount<'info>,
+
+    /// CHECK: Settlement swap PDA, may be uninitialized; checked in `settlement_swap`
+    #[account(seeds = [b"settlement_swap", escrow.key().as_ref()], bump)]
+    pub settlement_swap: UncheckedAccount<'info>,
+
+    /// 提供商的结算代币账户，约定了兑换结算时必填
+    #[account(mut, token::authority = escrow.load()?.provider)]
+    pub provider_settle_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// CHECK: Jupiter aggregator program, required with a settlement swap
+    #[account(address = JUPITER_PROGRAM_ID)]
+    pub jupiter_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Delivery commitment PDA, may be uninitialized; checked in `verify_commitment`
+    #[account(seeds = [b"commitment", escrow.key().as_ref()], bump)]
+    pub delivery_commitment: UncheckedAccount<'info>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional buyer compliance attestation, validated in the handler
+    pub buyer_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional provider compliance attestation, validated in the handler
+    pub provider_attestation: Option<UncheckedAccount<'info>>,
+
+    /// 收据记录，铸造收据 NFT 或压缩收据时必填（同时须传入 `system_program`）
+    #[account(
+        init,
+        payer = buyer,
+        space = ReceiptRecord::LEN,
+        seeds = [b"receipt_record", escrow.key().as_ref()],
+        bump
+    )]
+    pub receipt_record: Option<Box<Account<'info, ReceiptRecord>>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"receipt", escrow.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = escrow,
+        mint::freeze_authority = escrow,
+    )]
+    pub receipt_mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_receipt_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: Metadata PDA, validated and created by the Token Metadata program
+    #[account(mut)]
+    pub receipt_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Master edition PDA, validated and created by the Token Metadata program
+    #[account(mut)]
+    pub receipt_master_edition: Option<UncheckedAccount<'info>>,
+
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub system_program: Option<Program<'info, System>>,
+    pub rent: Option<Sysvar<'info, Rent>>,
+
+    /// CHECK: Tree config PDA, validated by the Bubblegum program
+    #[account(mut)]
+    pub receipt_tree_config: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Platform receipt tree, must match the config
+    #[account(mut, address = config.receipt_tree)]
+    pub receipt_merkle_tree: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Bubblegum program
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: SPL Noop program
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        address = escrow.load()?.access_mint @ EscrowError::InvalidAccessMint
+    )]
+    pub access_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = access_mint,
+        token::authority = escrow.load()?.buyer,
+        token::token_program = access_token_program,
+    )]
+    pub buyer_access_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// CHECK: PDA mint authority for the provider's access tokens
+    #[account(seeds = [b"access", escrow.load()?.provider.as_ref()], bump)]
+    pub access_authority: Option<UncheckedAccount<'info>>,
+
+    pub access_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Access window PDA, may be uninitialized; checked in `mint_access_token`
+    #[account(mut, seeds = [b"access_window", escrow.key().as_ref()], bump)]
+    pub access_window: UncheckedAccount<'info>,
+
+    /// CHECK: Reputation rating PDA `[b"rating", escrow]`, created by the reputation program
+    /// 传入 `quality_score` 时必填（同时须传入 `provider_reputation`、`reputation_program`
+    /// 和 `system_program`）
+    #[account(mut)]
+    pub rating: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Provider reputation PDA, checked by the reputation program
+    #[account(mut)]
+    pub provider_reputation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Reputation program
+    #[account(address = REPUTATION_PROGRAM_ID)]
+    pub reputation_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Provider sales history PDA, may be uninitialized; appended in `record_sale`
+    #[account(mut, seeds = [b"sales_history", escrow.load()?.provider.as_ref()], bump)]
+    pub sales_history: UncheckedAccount<'info>,
+
+    /// CHECK: Restriction PDA, may be uninitialized; its data category is recorded in the sale
+    #[account(seeds = [b"restriction", escrow.key().as_ref()], bump)]
+    pub restriction: UncheckedAccount<'info>,
+}
+
+impl<'info> ConfirmAndRelease<'info> {
+    /// 买家从自己的代币账户直接给提供商小费（不经托管，不收平台费）
+    fn send_tip(&self, tip: u64, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        if tip == 0 {
+            return Ok(());
+        }
+
+        let buyer_token_account = self
+            .buyer_token_account
+            .as_ref()
+            .ok_or(EscrowError::MissingBuyerTokenAccount)?;
+        transfer_tokens(
+            &self.token_program.to_account_info(),
+            buyer_token_account.to_account_info(),
+            &self.mint,
+            self.provider_token_account.to_account_info(),
+            self.buyer.to_account_info(),
+            hook_accounts,
+            &[],
+            tip,
+        )?;
+
+        msg!("Tip: {}", tip);
+
+        Ok(())
+    }
+
+    /// 结算奖励金：在奖励截止时间前交付付给提供商，否则退还买家
+    fn settle_bonus(
+        &self,
+        memo: Option<&TransferMemo<'info>>,
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let bonus_info = self.bonus.to_account_info();
+        if bonus_info.owner != &crate::ID {
+            return Ok(());
+        }
+
+        let mut bonus = EscrowBonus::try_deserialize(&mut &bonus_info.try_borrow_data()?[..])?;
+        if bonus.amount == 0 {
+            return Ok(());
+        }
+
+        let escrow = *self.escrow.load()?;
+        let earned = escrow.delivered_at().unwrap_or_default() <= bonus.deadline;
+        let (recipient, recipient_account) = if earned {
+            (escrow.provider, self.provider_token_account.to_account_info())
+        } else {
+            let buyer_token_account = self
+                .buyer_token_account
+                .as_ref()
+                .ok_or(EscrowError::MissingBuyerTokenAccount)?;
+            (escrow.buyer, buyer_token_account.to_account_info())
+        };
+
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        if let Some(memo) = memo {
+            memo.log(if earned { "provider" } else { "refund" })?;
+        }
+        transfer_tokens(
+            &self.token_program.to_account_info(),
+            self.bonus_vault.to_account_info(),
+            &self.mint,
+            recipient_account,
+            self.escrow.to_account_info(),
+            hook_accounts,
+            &[&seeds[..]],
+            bonus.amount,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let amount = bonus.amount;
+        bonus.amount = 0;
+        bonus.settled_at = Some(now);
+        bonus.try_serialize(&mut &mut bonus_info.try_borrow_mut_data()?[..])?;
+
+        emit!(BonusSettled {
+            escrow: self.escrow.key(),
+            recipient,
+            amount,
+            earned,
+            timestamp: now,
+        });
+
+        msg!("Bonus {}: {}", if earned { "paid" } else { "returned" }, amount);
+
+        Ok(())
+    }
+
+    /// 读取约定的交付条款，未约定时返回 `None`
+    fn delivery_terms(&self) -> Result<Option<DeliveryTerms>> {
+        let terms_info = self.delivery_terms.to_account_info();
+        if terms_info.owner != &crate::ID {
+            return Ok(None);
+        }
+
+        let terms = DeliveryTerms::try_deserialize(&mut &terms_info.try_borrow_data()?[..])?;
+        Ok(Some(terms))
+    }
+
+    /// 约定的兑换结算（未约定时为 None）
+    fn settlement_swap(&self) -> Result<Option<SettlementSwap>> {
+        let settlement_info = self.settlement_swap.to_account_info();
+        if settlement_info.owner != &crate::ID {
+            return Ok(None);
+        }
+
+        let settlement =
+            SettlementSwap::try_deserialize(&mut &settlement_info.try_borrow_data()?[..])?;
+        Ok(Some(settlement))
+    }
+
+    /// 通过信誉程序 `rate_provider` 记录买家评分（托管须已写入 Completed 状态）
+    ///
+    /// 评分记录在信誉程序的 `[b"rating", escrow]`，每个托管只能评分一次，
+    /// 之后不能再通过 `rate_provider` 重复评分
+    fn rate_provider(&self, quality_score: Option<u8>) -> Result<()> {
+        let score = match quality_score {
+            Some(score) => score,
+            None => return Ok(()),
+        };
+        require!(
+            (1..=MAX_QUALITY_SCORE).contains(&score),
+            EscrowError::InvalidQualityScore
+        );
+        let (Some(rating), Some(provider_reputation), Some(reputation_program), Some(system)) = (
+            self.rating.as_ref(),
+            self.provider_reputation.as_ref(),
+            self.reputation_program.as_ref(),
+            self.system_program.as_ref(),
+        ) else {
+            return err!(EscrowError::InvalidQualityScore);
+        };
+
+        let mut data = RATE_PROVIDER_DISCRIMINATOR.to_vec();
+        data.push(score);
+        invoke(
+            &Instruction {
+                program_id: REPUTATION_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new_readonly(self.escrow.key(), false),
+                    AccountMeta::new(rating.key(), false),
+                    AccountMeta::new(provider_reputation.key(), false),
+                    AccountMeta::new(self.buyer.key(), true),
+                    AccountMeta::new_readonly(system.key(), false),
+                ],
+                data,
+            },
+            &[
+                self.escrow.to_account_info(),
+                rating.to_account_info(),
+                provider_reputation.to_account_info(),
+                self.buyer.to_account_info(),
+                system.to_account_info(),
+                reputation_program.to_account_info(),
+            ],
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let escrow = *self.escrow.load()?;
+        emit!(QualityRated {
+            escrow: self.escrow.key(),
+            provider: escrow.provider,
+            buyer: escrow.buyer,
+            score,
+            timestamp: now,
+        });
+
+        msg!("Quality score: {}/{}", score, MAX_QUALITY_SCORE);
+
+        Ok(())
+    }
+
+    /// 提供商承诺过交付内容时，校验揭示的存储 URI 和 salt 与承诺一致
+    fn verify_commitment(&self) -> Result<()> {
+        if let Some(commitment) = read_delivery_commitment(&self.delivery_commitment)? {
+            let storage = commitment
+                .storage
+                .ok_or(EscrowError::CommitmentNotRevealed)?;
+            let revealed = hashv(&[storage.uri().as_bytes(), &commitment.salt]);
+            require!(
+                revealed.to_bytes() == commitment.commitment,
+                EscrowError::CommitmentMismatch
+            );
+        }
+
+        Ok(())
+    }
+
+    /// 托管记录过美元估值时，按新鲜的 Pyth 价格重新估值并写入释放时价值
+    ///
+    /// 未估值的托管（估值 PDA 不存在）直接跳过
+    fn revalue(&self) -> Result<()> {
+        let valuation_info = self.valuation.to_account_info();
+        if valuation_info.owner != &crate::ID {
+            return Ok(());
+        }
+
+        let mut valuation =
+            EscrowValuation::try_deserialize(&mut &valuation_info.try_borrow_data()?[..])?;
+        let price_update = self
+            .price_update
+            .as_ref()
+            .ok_or(EscrowError::MissingPriceFeed)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let price = read_pyth_price(price_update, &valuation.feed_id, now)?;
+        let release_usd_value = usd_value(self.escrow.load()?.amount, valuation.decimals, &price)?;
+
+        valuation.release_usd_value = Some(release_usd_value);
+        valuation.released_at = Some(now);
+        valuation.try_serialize(&mut &mut valuation_info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "USD value: {} at creation, {} at release",
+            valuation.usd_value,
+            release_usd_value
+        );
+
+        Ok(())
+    }
+
+    /// 写入收据记录（未提供时跳过）
+    fn record_receipt(&mut self, amount: u64, bump: Option<u8>) -> Result<()> {
+        let Some(receipt_record) = self.receipt_record.as_mut() else {
+            return Ok(());
+        };
+
+        let escrow = *self.escrow.load()?;
+        receipt_record.record(self.escrow.key(), &escrow, amount, bump.unwrap());
+
+        msg!("Receipt recorded: {}", receipt_record.key());
+
+        Ok(())
+    }
+
+    /// 给买家铸造收据 NFT（未提供收据账户时跳过）
+    ///
+    /// 铸币地址由托管地址派生，链上可验证收据与托管的对应关系；
+    /// 元数据 URI 指向收据记录，金额、交付哈希和时间戳从中读取
+    fn mint_receipt(&self) -> Result<()> {
+        let Some(receipt_mint) = &self.receipt_mint else {
+            return Ok(());
+        };
+        let (
+            Some(receipt_record),
+            Some(buyer_receipt_account),
+            Some(receipt_metadata),
+            Some(receipt_master_edition),
+            Some(token_metadata_program),
+            Some(system_program),
+            Some(rent),
+        ) = (
+            &self.receipt_record,
+            &self.buyer_receipt_account,
+            &self.receipt_metadata,
+            &self.receipt_master_edition,
+            &self.token_metadata_program,
+            &self.system_program,
+            &self.rent,
+        )
+        else {
+            return err!(EscrowError::MissingReceiptAccounts);
+        };
+
+        let escrow = *self.escrow.load()?;
+        let seeds = &[
+            b"escrow",
+            escrow.buyer.as_ref(),
+            escrow.request_id().as_bytes(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        // 铸造 1 枚收据给买家
+        let cpi_accounts = MintTo {
+            mint: receipt_mint.to_account_info(),
+            to: buyer_receipt_account.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::mint_to(cpi_ctx, 1)?;
+
+        // 创建元数据
+        let cpi_accounts = CreateMetadataAccountsV3 {
+            metadata: receipt_metadata.to_account_info(),
+            mint: receipt_mint.to_account_info(),
+            mint_authority: self.escrow.to_account_info(),
+            payer: self.buyer.to_account_info(),
+            update_authority: self.escrow.to_account_info(),
+            system_program: system_program.to_account_info(),
+            rent: rent.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_metadata_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        let data = DataV2 {
+            name: RECEIPT_NAME.to_string(),
+            symbol: RECEIPT_SYMBOL.to_string(),
+            uri: ReceiptRecord::uri(&receipt_record.key()),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        };
+        metadata::create_metadata_accounts_v3(cpi_ctx, data, false, true, None)?;
+
+        // 创建主版本，锁定供应量为 1
+        let cpi_accounts = CreateMasterEditionV3 {
+            edition: receipt_master_edition.to_account_info(),
+            mint: receipt_mint.to_account_info(),
+            update_authority: self.escrow.to_account_info(),
+            mint_authority: self.escrow.to_account_info(),
+            payer: self.buyer.to_account_info(),
+            metadata: receipt_metadata.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            system_program: system_program.to_account_info(),
+            rent: rent.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_metadata_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        metadata::create_master_edition_v3(cpi_ctx, Some(0))?;
+
+        msg!("Receipt minted: {}", receipt_mint.key());
+
+        Ok(())
+    }
+
+    /// 向平台 Merkle 树铸造压缩收据（未提供树账户时跳过）
+    ///
+    /// 叶子归买家所有，URI 指向收据记录，单次成本仅为一次树更新
+    fn mint_compressed_receipt(&self) -> Result<()> {
+        let Some(receipt_merkle_tree) = &self.receipt_merkle_tree else {
+            return Ok(());
+        };
+        let (
+            Some(receipt_record),
+            Some(receipt_tree_config),
+            Some(bubblegum_program),
+            Some(log_wrapper),
+            Some(compression_program),
+            Some(system_program),
+        ) = (
+            &self.receipt_record,
+            &self.receipt_tree_config,
+            &self.bubblegum_program,
+            &self.log_wrapper,
+            &self.compression_program,
+            &self.system_program,
+        )
+        else {
+            return err!(EscrowError::MissingReceiptAccounts);
+        };
+
+        let config = &self.config;
+        let seeds = &[b"config".as_ref(), &[config.bump]];
+        let signer = &[&seeds[..]];
+
+        let buyer = self.buyer.to_account_info();
+        let config_info = config.to_account_info();
+        let bubblegum_program = bubblegum_program.to_account_info();
+        let receipt_tree_config = receipt_tree_config.to_account_info();
+        let receipt_merkle_tree = receipt_merkle_tree.to_account_info();
+        let log_wrapper = log_wrapper.to_account_info();
+        let compression_program = compression_program.to_account_info();
+        let system_program = system_program.to_account_info();
+
+        MintV1CpiBuilder::new(&bubblegum_program)
+            .tree_config(&receipt_tree_config)
+            .leaf_owner(&buyer)
+            .leaf_delegate(&buyer)
+            .merkle_tree(&receipt_merkle_tree)
+            .payer(&buyer)
+            .tree_creator_or_delegate(&config_info)
+            .log_wrapper(&log_wrapper)
+            .compression_program(&compression_program)
+            .system_program(&system_program)
+            .metadata(MetadataArgs {
+                name: RECEIPT_NAME.to_string(),
+                symbol: RECEIPT_SYMBOL.to_string(),
+                uri: ReceiptRecord::uri(&receipt_record.key()),
+                seller_fee_basis_points: 0,
+                primary_sale_happened: true,
+                is_mutable: false,
+                edition_nonce: None,
+                token_standard: Some(TokenStandard::NonFungible),
+                collection: None,
+                uses: None,
+                token_program_version: TokenProgramVersion::Original,
+                creators: vec![],
+            })
+            .invoke_signed(signer)?;
+
+        msg!("Compressed receipt minted to tree: {}", receipt_merkle_tree.key());
+
+        Ok(())
+    }
+}
+
+/// 退款的上下文
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Dispute record PDA, may be uninitialized; closed in `close_dispute_record`
+    #[account(mut, seeds = [b"dispute", escrow.key().as_ref()], bump)]
+    pub dispute_record: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer wallet, receives the dispute record rent after a raised dispute
+    #[account(mut, address = escrow.load()?.buyer @ EscrowError::BuyerMismatch)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: Provider wallet, receives the dispute record rent after a contested cancel
+    #[account(mut, address = escrow.load()?.provider @ EscrowError::ProviderMismatch)]
+    pub provider: UncheckedAccount<'info>,
+}
+
+/// 阻止释放的上下文
+#[derive(Accounts)]
+pub struct BlockRelease<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump,
+        constraint = escrow.load()?.platform == platform.key() @ EscrowError::OnlyPlatform
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub platform: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"restriction", escrow.key().as_ref()],
+        bump = restriction.bump
+    )]
+    pub restriction: Box<Account<'info, EscrowRestriction>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(mut, token::authority = escrow.load()?.buyer)]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Dispute record PDA, may be uninitialized; closed in `close_dispute_record`
+    #[account(mut, seeds = [b"dispute", escrow.key().as_ref()], bump)]
+    pub dispute_record: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer wallet, receives the dispute record rent after a raised dispute
+    #[account(mut, address = escrow.load()?.buyer @ EscrowError::BuyerMismatch)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: Provider wallet, receives the dispute record rent after a contested cancel
+    #[account(mut, address = escrow.load()?.provider @ EscrowError::ProviderMismatch)]
+    pub provider: UncheckedAccount<'info>,
+}
+
+/// 申请取消的上下文
+#[derive(Accounts)]
+pub struct Cancel<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = CancelRequest::LEN,
+        seeds = [b"cancel_request", escrow.key().as_ref()],
+        bump
+    )]
+    pub cancel_request: Box<Account<'info, CancelRequest>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 提供商反对取消的上下文
+#[derive(Accounts)]
+pub struct ContestCancel<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    /// CHECK: Escrow buyer, receives the cancel request rent
+    #[account(mut, address = escrow.load()?.buyer @ EscrowError::BuyerMismatch)]
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"cancel_request", escrow.key().as_ref()],
+        bump = cancel_request.bump,
+        close = buyer
+    )]
+    pub cancel_request: Box<Account<'info, CancelRequest>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(
+        init,
+        payer = provider,
+        space = DisputeRecord::LEN,
+        seeds = [b"dispute", escrow.key().as_ref()],
+        bump
+    )]
+    pub dispute_record: Box<Account<'info, DisputeRecord>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 执行取消的上下文
+#[derive(Accounts)]
+pub struct ExecuteCancel<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    /// CHECK: Escrow buyer, receives the cancel request rent
+    #[account(mut, address = escrow.load()?.buyer @ EscrowError::BuyerMismatch)]
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"cancel_request", escrow.key().as_ref()],
+        bump = cancel_request.bump,
+        close = buyer
+    )]
+    pub cancel_request: Box<Account<'info, CancelRequest>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+}
+
+/// 回收未注资托管的上下文
+#[derive(Accounts)]
+pub struct ReclaimStale<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump,
+        close = buyer
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    /// CHECK: Escrow buyer, receives the closed accounts' rent
+    #[account(mut, address = escrow.load()?.buyer)]
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    /// 托管记录的铸币（版本 5 之前创建的未充值托管未记录，接受调用者传入的铸币）
+    #[account(
+        constraint = escrow.load()?.mint().is_none_or(|escrow_mint| escrow_mint == mint.key())
+            @ EscrowError::MintMismatch,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    /// CHECK: Escrow's associated token account for `mint`, may not exist yet; closed when it does
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &escrow.key(),
+            &mint.key(),
+            &token_program.key(),
+        ) @ EscrowError::EscrowTokenAccountMismatch
+    )]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    /// 买家代币账户，托管代币账户有余额时必填，余额退到这里
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow.load()?.buyer,
+        token::token_program = token_program
+    )]
+    pub buyer_token_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// 发起争议的上下文
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    /// 数据块清单，按块发起争议时必填
+    #[account(
+        mut,
+        seeds = [b"manifest", escrow.key().as_ref()],
+        bump = chunk_manifest.bump
+    )]
+    pub chunk_manifest: Option<Box<Account<'info, ChunkManifest>>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = DisputeRecord::LEN,
+        seeds = [b"dispute", escrow.key().as_ref()],
+        bump
+    )]
+    pub dispute_record: Box<Account<'info, DisputeRecord>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 移交陪审团的上下文
+#[derive(Accounts)]
+pub struct EscalateDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(seeds = [JURY_AUTHORITY_SEED], bump, seeds::program = JURY_PROGRAM_ID)]
+    pub jury_authority: Signer<'info>,
+}
+
+/// 平台可上诉裁决的上下文
+#[derive(Accounts)]
+pub struct RuleDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = Ruling::LEN,
+        seeds = [b"ruling", escrow.key().as_ref()],
+        bump
+    )]
+    pub ruling: Box<Account<'info, Ruling>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    pub platform: Signer<'info>,
+
+    /// 支付裁决记录租金（平台为多签 PDA 时由执行人支付）
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 上诉的上下文
+#[derive(Accounts)]
+pub struct Appeal<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"ruling", escrow.key().as_ref()],
+        bump = ruling.bump
+    )]
+    pub ruling: Box<Account<'info, Ruling>>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute", escrow.key().as_ref()],
+        bump = dispute_record.bump
+    )]
+    pub dispute_record: Box<Account<'info, DisputeRecord>>,
+
+    /// 败诉方（买家或提供商）
+    pub appellant: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut, token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = appellant,
+    )]
+    pub appellant_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// 解决争议的上下文
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    /// 平台，争议已移交陪审团时为陪审团权限 PDA，执行期满裁决时可为任何人
+    pub platform: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(mut)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow.load()?.provider,
+    )]
+    pub provider_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// 国库代币账户，国库份额或逾期罚金非零时必填
+    #[account(mut, address = config.treasury)]
+    pub treasury_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 奖励池代币账户，奖励池份额非零时必填
+    #[account(mut, address = config.rewards_pool)]
+    pub rewards_pool_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 保险池，`insurance_bps` 非零时必填
+    #[account(mut, seeds = [b"insurance_vault"], bump)]
+    pub insurance_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// 费用账本，开启 `fee_ledger` 时必填
+    #[account(mut, seeds = [b"fee_ledger"], bump = fee_ledger.bump)]
+    pub fee_ledger: Option<Box<Account<'info, FeeLedger>>>,
+
+    /// 共享费用金库，开启 `fee_ledger` 时必填
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 平台可上诉裁决，执行 Ruled 状态的托管时必填
+    #[account(seeds = [b"ruling", escrow.key().as_ref()], bump = ruling.bump)]
+    pub ruling: Option<Box<Account<'info, Ruling>>>,
+
+    /// CHECK: Coupon discount PDA, may be uninitialized; checked in `escrow_discount`
+    #[account(seeds = [b"discount", escrow.key().as_ref()], bump)]
+    pub discount: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth PriceUpdateV2 account, required when the escrow is priced in USD
+    pub price_update: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: USD pricing PDA, may be uninitialized; checked in `usd_release_amount`
+    #[account(mut, seeds = [b"usd_pricing", escrow.key().as_ref()], bump)]
+    pub usd_pricing: UncheckedAccount<'info>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        address = escrow.load()?.access_mint @ EscrowError::InvalidAccessMint
+    )]
+    pub access_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = access_mint,
+        token::authority = escrow.load()?.buyer,
+        token::token_program = access_token_program,
+    )]
+    pub buyer_access_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// CHECK: PDA mint authority for the provider's access tokens
+    #[account(seeds = [b"access", escrow.load()?.provider.as_ref()], bump)]
+    pub access_authority: Option<UncheckedAccount<'info>>,
+
+    pub access_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Access window PDA, may be uninitialized; checked in `mint_access_token`
+    #[account(mut, seeds = [b"access_window", escrow.key().as_ref()], bump)]
+    pub access_window: UncheckedAccount<'info>,
+
+    /// CHECK: Provider sales history PDA, may be uninitialized; appended in `record_sale`
+    #[account(mut, seeds = [b"sales_history", escrow.load()?.provider.as_ref()], bump)]
+    pub sales_history: UncheckedAccount<'info>,
+
+    /// CHECK: Restriction PDA, may be uninitialized; its data category is recorded in the sale
+    #[account(seeds = [b"restriction", escrow.key().as_ref()], bump)]
+    pub restriction: UncheckedAccount<'info>,
+
+    /// CHECK: Dispute record PDA, may be uninitialized; closed in `close_dispute_record`
+    #[account(mut, seeds = [b"dispute", escrow.key().as_ref()], bump)]
+    pub dispute_record: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer wallet, receives the dispute record rent after a raised dispute
+    #[account(mut, address = escrow.load()?.buyer @ EscrowError::BuyerMismatch)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: Provider wallet, receives the dispute record rent after a contested cancel
+    #[account(mut, address = escrow.load()?.provider @ EscrowError::ProviderMismatch)]
+    pub provider: UncheckedAccount<'info>,
+}
+
+/// 按数据块拆分争议的上下文
+#[derive(Accounts)]
+pub struct ResolveDisputeSplit<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    /// 平台，争议已移交陪审团时为陪审团权限 PDA
+    pub platform: Signer<'info>,
+
+    #[account(
+        seeds = [b"manifest", escrow.key().as_ref()],
+        bump = chunk_manifest.bump
+    )]
+    pub chunk_manifest: Box<Account<'info, ChunkManifest>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_stats", escrow.load()?.provider.as_ref()],
+        bump = provider_stats.bump
+    )]
+    pub provider_stats: Box<Account<'info, ProviderStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", escrow.load()?.buyer.as_ref()],
+        bump = buyer_stats.bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(mut, token::authority = escrow)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.load()?.provider,
+    )]
+    pub provider_token_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// 国库代币账户，国库份额或逾期罚金非零时必填
+    #[account(mut, address = config.treasury)]
+    pub treasury_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 奖励池代币账户，奖励池份额非零时必填
+    #[account(mut, address = config.rewards_pool)]
+    pub rewards_pool_token_account:
+        Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// 保险池，`insurance_bps` 非零时必填
+    #[account(mut, seeds = [b"insurance_vault"], bump)]
+    pub insurance_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// 费用账本，开启 `fee_ledger` 时必填
+    #[account(mut, seeds = [b"fee_ledger"], bump = fee_ledger.bump)]
+    pub fee_ledger: Option<Box<Account<'info, FeeLedger>>>,
+
+    /// 共享费用金库，开启 `fee_ledger` 时必填
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// CHECK: Coupon discount PDA, may be uninitialized; checked in `escrow_discount`
+    #[account(seeds = [b"discount", escrow.key().as_ref()], bump)]
+    pub discount: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth PriceUpdateV2 account, required when the escrow is priced in USD
+    pub price_update: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: USD pricing PDA, may be uninitialized; checked in `usd_release_amount`
+    #[account(mut, seeds = [b"usd_pricing", escrow.key().as_ref()], bump)]
+    pub usd_pricing: UncheckedAccount<'info>,
+
+    #[account(address = escrow_token_account.mint @ EscrowError::MintMismatch)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, required when transfer memos are enabled
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Provider sales history PDA, may be uninitialized; appended in `record_sale`
+    #[account(mut, seeds = [b"sales_history", escrow.load()?.provider.as_ref()], bump)]
+    pub sales_history: UncheckedAccount<'info>,
+
+    /// CHECK: Restriction PDA, may be uninitialized; its data category is recorded in the sale
+    #[account(seeds = [b"restriction", escrow.key().as_ref()], bump)]
+    pub restriction: UncheckedAccount<'info>,
+
+    /// CHECK: Dispute record PDA, may be uninitialized; closed in `close_dispute_record`
+    #[account(mut, seeds = [b"dispute", escrow.key().as_ref()], bump)]
+    pub dispute_record: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer wallet, receives the dispute record rent after a raised dispute
+    #[account(mut, address = escrow.load()?.buyer @ EscrowError::BuyerMismatch)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: Provider wallet, receives the dispute record rent after a contested cancel
+    #[account(mut, address = escrow.load()?.provider @ EscrowError::ProviderMismatch)]
+    pub provider: UncheckedAccount<'info>,
+}
+
+/// 追加扩展元数据的上下文
+#[derive(Accounts)]
+#[instruction(data: Vec<u8>)]
+pub struct ExtendMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump,
+        constraint = escrow.load()?.version == ESCROW_VERSION @ EscrowError::MigrationRequired,
+        constraint = escrow.load()?.platform == platform.key() @ EscrowError::OnlyPlatform,
+        realloc = Escrow::LEN + escrow.load()?.metadata_len as usize + data.len(),
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub platform: Signer<'info>,
+
+    /// 支付扩容租金（平台为多签 PDA 时由执行人支付）
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 记录协商消息的上下文
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.load()?.buyer.as_ref(), escrow.load()?.request_id().as_bytes()],
+        bump = escrow.load()?.bump
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(mut)]
+    pub author: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = author,
+        space = MessageLog::LEN,
+        seeds = [b"message_log", escrow.key().as_ref()],
+        bump
+    )]
+    pub message_log: Box<Account<'info, MessageLog>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 迁移托管账户的上下文
+#[derive(Accounts)]
+pub struct MigrateEscrow<'info> {
+    /// CHECK: Escrow account in any past layout; the discriminator is checked after realloc
+    #[account(mut, owner = crate::ID)]
+    pub escrow: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 迁移全局配置的上下文
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: Config account in a past layout; the discriminator is checked in `Config::migrate`
+    #[account(mut, seeds = [b"config"], bump, owner = crate::ID)]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 迁移买家统计的上下文，remaining accounts 为该买家全部未进入终态的托管
+#[derive(Accounts)]
+pub struct MigrateBuyerStats<'info> {
+    /// CHECK: Buyer stats account in a past or current layout; checked in `BuyerStats::migrate`
+    #[account(mut, seeds = [b"buyer_stats", buyer.key().as_ref()], bump, owner = crate::ID)]
+    pub buyer_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer whose stats are migrated, only used to derive the stats PDA
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::OnlyAdmin
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum EscrowError {
+    #[msg("Invalid escrow status for this operation")]
+    InvalidStatus,
+
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("Fee split weights must sum to 10000 bps")]
+    InvalidFeeSplit,
+
+    #[msg("Receipt mint provided without the remaining receipt accounts")]
+    MissingReceiptAccounts,
+
+    #[msg("Access mint does not match the escrow or its provider's access authority")]
+    InvalidAccessMint,
+
+    #[msg("Escrow has an access mint but the access token accounts are missing")]
+    MissingAccessAccounts,
+
+    #[msg("Provider is not registered or its profile is inactive")]
+    ProviderNotRegistered,
+
+    #[msg("Proposal does not belong to this request and provider")]
+    ProposalMismatch,
+
+    #[msg("Escrow account is already at the current version")]
+    AlreadyMigrated,
+
+    #[msg("Escrow account must be migrated to the current version first")]
+    MigrationRequired,
+
+    #[msg("Metadata must not be empty")]
+    InvalidMetadata,
+
+    #[msg("Metadata exceeds the maximum length")]
+    MetadataTooLong,
+
+    #[msg("Insurance bps must not exceed 10000")]
+    InvalidInsuranceBps,
+
+    #[msg("Insurance vault is required while insurance bps is set")]
+    MissingInsuranceVault,
 
-        // 转账 USDC 到托管账户
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.buyer_token_account.to_account_info(),
-            to: ctx.accounts.escrow_token_account.to_account_info(),
-            authority: ctx.accounts.buyer.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        
-        token::transfer(cpi_ctx, amount)?;
+    #[msg("Escrow must be settled before an insurance claim")]
+    InsuranceNotClaimable,
 
-        escrow.status = EscrowStatus::Funded;
-        escrow.funded_at = Some(Clock::get()?.unix_timestamp);
+    #[msg("Provider collateral in the staking program is below the requirement")]
+    InsufficientCollateral,
 
-        msg!("Escrow created: {}", escrow.key());
-        msg!("Amount: {} USDC", amount);
-        msg!("Buyer: {}", escrow.buyer);
-        msg!("Provider: {}", escrow.provider);
+    #[msg("Provider bond account is not the staking program's bond PDA")]
+    InvalidCollateralAccount,
 
-        Ok(())
-    }
+    #[msg("An appeal window is configured: rule the dispute with rule_dispute first")]
+    RulingRequired,
 
-    /// 提供商标记数据已交付
-    pub fn mark_delivered(ctx: Context<MarkDelivered>) -> Result<()> {
-        let escrow = &mut ctx.accounts.escrow;
+    #[msg("Ruling is missing, does not match, or appeals are disabled")]
+    InvalidRuling,
 
-        require!(
-            escrow.status == EscrowStatus::Funded,
-            EscrowError::InvalidStatus
-        );
+    #[msg("Appeal window has closed")]
+    AppealWindowClosed,
 
-        require!(
-            ctx.accounts.provider.key() == escrow.provider,
-            EscrowError::Unauthorized
-        );
+    #[msg("Appeal window is still open")]
+    AppealWindowOpen,
 
-        escrow.status = EscrowStatus::Delivered;
-        escrow.delivered_at = Some(Clock::get()?.unix_timestamp);
+    #[msg("Config change is still in its timelock")]
+    TimelockActive,
 
-        msg!("Data delivered for escrow: {}", escrow.key());
+    #[msg("Price account is not a verified Pyth price update for this feed")]
+    InvalidPriceFeed,
 
-        Ok(())
-    }
+    #[msg("Pyth price is too old")]
+    StalePrice,
 
-    /// 买家确认交付并释放资金
-    /// 
-    /// 自动分配：95% 给提供商，5% 给平台
-    pub fn confirm_and_release(ctx: Context<ConfirmAndRelease>) -> Result<()> {
-        let escrow = &mut ctx.accounts.escrow;
+    #[msg("Escrow has a USD valuation: a Pyth price update is required")]
+    MissingPriceFeed,
 
-        require!(
-            escrow.status == EscrowStatus::Delivered,
-            EscrowError::InvalidStatus
-        );
+    #[msg("Delivery requires an oracle Ed25519 attestation right before this instruction")]
+    MissingAttestation,
 
-        require!(
-            ctx.accounts.buyer.key() == escrow.buyer,
-            EscrowError::Unauthorized
-        );
+    #[msg("Oracle attestation does not match the oracle, escrow or delivery hash")]
+    InvalidAttestation,
 
-        // 计算分配金额
-        let total_amount = escrow.amount;
-        let platform_fee = total_amount
-            .checked_mul(5)
-            .unwrap()
-            .checked_div(100)
-            .unwrap(); // 5%
-        let provider_amount = total_amount.checked_sub(platform_fee).unwrap(); // 95%
+    #[msg("Wormhole VAA is not a bridged payment to this program for this escrow")]
+    InvalidBridgePayment,
 
-        msg!("Releasing funds:");
-        msg!("  Total: {} USDC", total_amount);
-        msg!("  Provider (95%): {} USDC", provider_amount);
-        msg!("  Platform (5%): {} USDC", platform_fee);
+    #[msg("Swap must pay out the escrow token into the buyer's token account")]
+    InvalidSwap,
 
-        // 生成 PDA 签名种子
-        let seeds = &[
-            b"escrow",
-            escrow.buyer.as_ref(),
-            escrow.request_id.as_bytes(),
-            &[escrow.bump],
-        ];
-        let signer = &[&seeds[..]];
+    #[msg("Yield buyer bps must not exceed 10000")]
+    InvalidYieldBps,
 
-        // 转账给提供商（95%）
-        let cpi_accounts_provider = Transfer {
-            from: ctx.accounts.escrow_token_account.to_account_info(),
-            to: ctx.accounts.provider_token_account.to_account_info(),
-            authority: escrow.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx_provider = CpiContext::new_with_signer(
-            cpi_program.clone(),
-            cpi_accounts_provider,
-            signer,
-        );
-        token::transfer(cpi_ctx_provider, provider_amount)?;
+    #[msg("Memo program is required when transfer memos are enabled")]
+    MissingMemoProgram,
 
-        // 转账给平台（5%）
-        let cpi_accounts_platform = Transfer {
-            from: ctx.accounts.escrow_token_account.to_account_info(),
-            to: ctx.accounts.platform_token_account.to_account_info(),
-            authority: escrow.to_account_info(),
-        };
-        let cpi_ctx_platform = CpiContext::new_with_signer(
-            cpi_program,
-            cpi_accounts_platform,
-            signer,
-        );
-        token::transfer(cpi_ctx_platform, platform_fee)?;
+    #[msg("A compliance attestation is required")]
+    ComplianceAttestationRequired,
 
-        escrow.status = EscrowStatus::Completed;
-        escrow.completed_at = Some(Clock::get()?.unix_timestamp);
+    #[msg("Invalid or expired compliance attestation")]
+    InvalidComplianceAttestation,
 
-        msg!("Escrow completed: {}", escrow.key());
+    #[msg("Restricted requests must record an escrow restriction")]
+    RestrictionRequired,
 
-        Ok(())
-    }
+    #[msg("Escrow has no recorded restrictions")]
+    NotRestricted,
 
-    /// 退款给买家（仅平台可调用，用于争议解决）
-    pub fn refund(ctx: Context<Refund>) -> Result<()> {
-        let escrow = &mut ctx.accounts.escrow;
+    #[msg("Block reason too long")]
+    BlockReasonTooLong,
 
-        // 只允许在 Disputed 状态退款
-        require!(
-            escrow.status == EscrowStatus::Disputed,
-            EscrowError::InvalidStatus
-        );
+    #[msg("The encrypted decryption key has not been delivered")]
+    KeyNotDelivered,
 
-        // 只有平台可以发起退款（仲裁后）
-        require!(
-            ctx.accounts.authority.key() == escrow.platform,
-            EscrowError::Unauthorized
-        );
+    #[msg("Encrypted key is empty or too long")]
+    InvalidEncryptedKey,
 
-        let amount = escrow.amount;
+    #[msg("The delivery commitment has not been revealed")]
+    CommitmentNotRevealed,
 
-        // 生成 PDA 签名种子
-        let seeds = &[
-            b"escrow",
-            escrow.buyer.as_ref(),
-            escrow.request_id.as_bytes(),
-            &[escrow.bump],
-        ];
-        let signer = &[&seeds[..]];
+    #[msg("Revealed content does not match the delivery commitment")]
+    CommitmentMismatch,
 
-        // 退款给买家
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.escrow_token_account.to_account_info(),
-            to: ctx.accounts.buyer_token_account.to_account_info(),
-            authority: escrow.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        
-        token::transfer(cpi_ctx, amount)?;
+    #[msg("Storage reference ID does not match the protocol's format")]
+    InvalidStorageRef,
 
-        escrow.status = EscrowStatus::Refunded;
-        escrow.refunded_at = Some(Clock::get()?.unix_timestamp);
+    #[msg("Chunk disputes require a chunk manifest recorded at delivery")]
+    MissingChunkManifest,
 
-        msg!("Escrow refunded: {}", escrow.key());
-        msg!("Amount: {} USDC", amount);
+    #[msg("Chunk manifest must be passed together with a chunk root and a valid chunk count")]
+    InvalidChunkManifest,
 
-        Ok(())
-    }
+    #[msg("Chunk index or Merkle proof does not match the chunk manifest")]
+    InvalidChunkProof,
 
-    /// 取消托管（仅在交付前）
-    pub fn cancel(ctx: Context<Cancel>) -> Result<()> {
-        let escrow = &mut ctx.accounts.escrow;
+    #[msg("Too many disputed chunks")]
+    TooManyDisputedChunks,
 
-        // 只允许在 Funded 状态取消（交付前）
-        require!(
-            escrow.status == EscrowStatus::Funded,
-            EscrowError::InvalidStatus
-        );
+    #[msg("Invalid chunks must be distinct chunks disputed by the buyer")]
+    InvalidChunkRuling,
 
-        // 只有买家可以取消
-        require!(
-            ctx.accounts.buyer.key() == escrow.buyer,
-            EscrowError::Unauthorized
-        );
+    #[msg("Challenge must name 1-8 distinct chunks within the chunk manifest")]
+    InvalidChallenge,
 
-        let amount = escrow.amount;
+    #[msg("Challenge was already answered or its response window has closed")]
+    ChallengeClosed,
 
-        // 生成 PDA 签名种子
-        let seeds = &[
-            b"escrow",
-            escrow.buyer.as_ref(),
-            escrow.request_id.as_bytes(),
-            &[escrow.bump],
-        ];
-        let signer = &[&seeds[..]];
+    #[msg("Challenge response window is still open")]
+    ChallengeOpen,
 
-        // 退款给买家
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.escrow_token_account.to_account_info(),
-            to: ctx.accounts.buyer_token_account.to_account_info(),
-            authority: escrow.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    #[msg("Verifier program does not match the one required by the buyer")]
+    InvalidVerifier,
 
-        token::transfer(cpi_ctx, amount)?;
+    #[msg("Dataset proof has already been verified")]
+    DatasetProofVerified,
 
-        escrow.status = EscrowStatus::Cancelled;
+    #[msg("Shadow Drive storage account is missing or not owned by the Shadow Drive program")]
+    InvalidStorageAccount,
 
-        msg!("Escrow cancelled: {}", escrow.key());
-        msg!("Refunded amount: {} USDC", amount);
+    #[msg("Access window requires an access mint and a positive duration")]
+    InvalidAccessWindow,
 
-        Ok(())
-    }
+    #[msg("Access window has not expired yet")]
+    AccessNotExpired,
 
-    /// 买家发起争议
-    pub fn raise_dispute(ctx: Context<RaiseDispute>) -> Result<()> {
-        let escrow = &mut ctx.accounts.escrow;
+    #[msg("Access token has already been revoked")]
+    AccessRevoked,
 
-        // 只允许在 Delivered 状态发起争议
-        require!(
-            escrow.status == EscrowStatus::Delivered,
-            EscrowError::InvalidStatus
-        );
+    #[msg("Delivery has been rejected the maximum number of times")]
+    TooManyRejections,
 
-        // 只有买家可以发起争议
-        require!(
-            ctx.accounts.buyer.key() == escrow.buyer,
-            EscrowError::Unauthorized
-        );
+    #[msg("Redelivery window is still open")]
+    RedeliveryOpen,
 
-        escrow.status = EscrowStatus::Disputed;
-        escrow.disputed_at = Some(Clock::get()?.unix_timestamp);
+    #[msg("Quality score must be between 1 and 5 and needs the rating account")]
+    InvalidQualityScore,
 
-        msg!("Dispute raised for escrow: {}", escrow.key());
-        msg!("Buyer: {}", escrow.buyer);
+    #[msg("Late refund cap must not exceed 10000 bps")]
+    InvalidLatenessSchedule,
 
-        Ok(())
-    }
+    #[msg("Delivery deadline must be in the future")]
+    InvalidDeliveryDeadline,
 
-    /// 平台解决争议（退款或释放）
-    pub fn resolve_dispute(
-        ctx: Context<ResolveDispute>,
-        refund_to_buyer: bool,
-    ) -> Result<()> {
-        let escrow = &mut ctx.accounts.escrow;
+    #[msg("Buyer token account is required to refund a late delivery")]
+    MissingBuyerTokenAccount,
 
-        // 只允许在 Disputed 状态解决争议
-        require!(
-            escrow.status == EscrowStatus::Disputed,
-            EscrowError::InvalidStatus
-        );
+    #[msg("Bonus amount must be positive and its deadline in the future")]
+    InvalidBonus,
 
-        // 只有平台可以解决争议
-        require!(
-            ctx.accounts.platform.key() == escrow.platform,
-            EscrowError::Unauthorized
-        );
+    #[msg("Bonus can only be reclaimed after the escrow closes without release")]
+    BonusLocked,
 
-        let amount = escrow.amount;
+    #[msg("Message log is full")]
+    MessageLogFull,
 
-        // 生成 PDA 签名种子
-        let seeds = &[
-            b"escrow",
-            escrow.buyer.as_ref(),
-            escrow.request_id.as_bytes(),
-            &[escrow.bump],
-        ];
-        let signer = &[&seeds[..]];
+    #[msg("Cancellation can only be executed after the grace period")]
+    CancelGracePeriodOpen,
 
-        if refund_to_buyer {
-            // 退款给买家
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.buyer_token_account.to_account_info(),
-                authority: escrow.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    #[msg("Cancellation grace period has ended")]
+    CancelGracePeriodEnded,
 
-            token::transfer(cpi_ctx, amount)?;
+    #[msg("Unfunded escrow has not reached the stale TTL")]
+    EscrowNotStale,
 
-            escrow.status = EscrowStatus::Refunded;
-            escrow.refunded_at = Some(Clock::get()?.unix_timestamp);
+    #[msg("Price quote has expired; the escrow must be re-quoted")]
+    QuoteExpired,
 
-            msg!("Dispute resolved: Refunded to buyer");
-            msg!("Amount: {} USDC", amount);
-        } else {
-            // 释放给提供商（95/5）
-            let total_amount = amount;
-            let platform_fee = total_amount.checked_mul(5).unwrap().checked_div(100).unwrap();
-            let provider_amount = total_amount.checked_sub(platform_fee).unwrap();
+    #[msg("Invalid coupon")]
+    InvalidCoupon,
 
-            // 转账给提供商（95%）
-            let cpi_accounts_provider = Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.provider_token_account.to_account_info(),
-                authority: escrow.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx_provider = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts_provider, signer);
+    #[msg("Invalid unit pricing or unit count")]
+    InvalidUnits,
 
-            token::transfer(cpi_ctx_provider, provider_amount)?;
+    #[msg("Invalid download cap or no downloads remaining")]
+    InvalidDownloads,
 
-            // 转账给平台（5%）
-            let cpi_accounts_platform = Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.platform_token_account.to_account_info(),
-                authority: escrow.to_account_info(),
-            };
-            let cpi_ctx_platform = CpiContext::new_with_signer(cpi_program, cpi_accounts_platform, signer);
+    #[msg("Invalid escrow template")]
+    InvalidTemplate,
 
-            token::transfer(cpi_ctx_platform, platform_fee)?;
+    #[msg("Proposal has not been selected by the request owner")]
+    ProposalNotSelected,
 
-            escrow.status = EscrowStatus::Completed;
-            escrow.completed_at = Some(Clock::get()?.unix_timestamp);
+    #[msg("Provider sales history is full")]
+    SalesHistoryFull,
 
-            msg!("Dispute resolved: Released to provider");
-            msg!("Provider amount: {} USDC (95%)", provider_amount);
-            msg!("Platform fee: {} USDC (5%)", platform_fee);
-        }
+    #[msg("Provider badge required for this escrow amount")]
+    BadgeRequired,
 
-        Ok(())
-    }
-}
+    #[msg("Provider badge is invalid or expired")]
+    InvalidBadge,
 
-/// 托管账户数据结构
-#[account]
-pub struct Escrow {
-    pub buyer: Pubkey,           // 买家
-    pub provider: Pubkey,        // 提供商
-    pub platform: Pubkey,        // 平台
-    pub amount: u64,             // 托管金额（USDC，6 位小数）
-    pub request_id: String,      // 需求 ID
-    pub proposal_id: String,     // 提案 ID
-    pub status: EscrowStatus,    // 状态
-    pub created_at: i64,         // 创建时间
-    pub funded_at: Option<i64>,  // 充值时间
-    pub delivered_at: Option<i64>, // 交付时间
-    pub completed_at: Option<i64>, // 完成时间
-    pub refunded_at: Option<i64>,  // 退款时间
-    pub disputed_at: Option<i64>,  // 争议时间
-    pub bump: u8,                // PDA bump
-}
+    #[msg("Account is blacklisted by the platform")]
+    Blacklisted,
 
-/// 托管状态
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
-pub enum EscrowStatus {
-    Created,    // 已创建
-    Funded,     // 已充值
-    Delivered,  // 已交付
-    Disputed,   // 争议中
-    Completed,  // 已完成
-    Refunded,   // 已退款
-    Cancelled,  // 已取消
-}
+    #[msg("Buyer has too many open escrows")]
+    TooManyOpenEscrows,
 
-/// 创建托管的上下文
-#[derive(Accounts)]
-#[instruction(amount: u64, request_id: String, proposal_id: String)]
-pub struct CreateEscrow<'info> {
-    #[account(
-        init,
-        payer = buyer,
-        space = 8 + 32 + 32 + 32 + 8 + 64 + 64 + 1 + 8 + 9 + 9 + 9 + 9 + 9 + 1,
-        seeds = [b"escrow", buyer.key().as_ref(), request_id.as_bytes()],
-        bump
-    )]
-    pub escrow: Account<'info, Escrow>,
+    #[msg("Buyer exceeded the daily escrow creation limit")]
+    CreationRateLimited,
 
-    #[account(mut)]
-    pub buyer: Signer<'info>,
+    #[msg("Fee split does not add up to the escrow amount")]
+    FeeSplitMismatch,
 
-    /// CHECK: Provider address
-    pub provider: AccountInfo<'info>,
+    #[msg("Pyth price deviates too far from the price at USD denomination")]
+    PriceDeviation,
 
-    /// CHECK: Platform address
-    pub platform: AccountInfo<'info>,
+    #[msg("Escrowed tokens do not cover the USD price")]
+    UsdPriceNotCovered,
 
-    #[account(mut)]
-    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[msg("Settlement swap paid out less than the agreed minimum")]
+    SwapSlippageExceeded,
 
-    #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[msg("Request or proposal ID is too long")]
+    IdTooLong,
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
+    #[msg("Fee ledger and fee vault are required while the fee ledger is enabled")]
+    MissingFeeLedger,
 
-/// 标记已交付的上下文
-#[derive(Accounts)]
-pub struct MarkDelivered<'info> {
-    #[account(mut)]
-    pub escrow: Account<'info, Escrow>,
+    #[msg("No platform fees have accrued in the fee ledger")]
+    NoFeesAccrued,
 
-    pub provider: Signer<'info>,
-}
+    #[msg("Treasury or rewards pool token account is required while its fee share is nonzero")]
+    MissingPlatformTokenAccount,
 
-/// 确认并释放资金的上下文
-#[derive(Accounts)]
-pub struct ConfirmAndRelease<'info> {
-    #[account(
-        mut,
-        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.request_id.as_bytes()],
-        bump = escrow.bump
-    )]
-    pub escrow: Account<'info, Escrow>,
+    #[msg("Escrow must be in Created status")]
+    NotCreated,
 
-    pub buyer: Signer<'info>,
+    #[msg("Escrow must be in Funded status")]
+    NotFunded,
 
-    #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[msg("Escrow must be in Delivered status")]
+    NotDelivered,
 
-    #[account(mut)]
-    pub provider_token_account: Account<'info, TokenAccount>,
+    #[msg("Escrow must be in Rejected status")]
+    NotRejected,
 
-    #[account(mut)]
-    pub platform_token_account: Account<'info, TokenAccount>,
+    #[msg("Escrow must be in Disputed status")]
+    NotDisputed,
 
-    pub token_program: Program<'info, Token>,
-}
+    #[msg("Escrow must be in Ruled status")]
+    NotRuled,
 
-/// 退款的上下文
-#[derive(Accounts)]
-pub struct Refund<'info> {
-    #[account(
-        mut,
-        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.request_id.as_bytes()],
-        bump = escrow.bump
-    )]
-    pub escrow: Account<'info, Escrow>,
+    #[msg("Escrow must be in Completed status")]
+    NotCompleted,
 
-    pub authority: Signer<'info>,
+    #[msg("Escrow must be in PendingCancel status")]
+    NotPendingCancel,
 
-    #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[msg("Escrow is not awaiting delivery")]
+    NotAwaitingDelivery,
 
-    #[account(mut)]
-    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[msg("Escrow terms can no longer be changed")]
+    TermsLocked,
 
-    pub token_program: Program<'info, Token>,
-}
+    #[msg("No release is pending for this escrow")]
+    ReleaseNotPending,
 
-/// 取消的上下文
-#[derive(Accounts)]
-pub struct Cancel<'info> {
-    #[account(
-        mut,
-        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.request_id.as_bytes()],
-        bump = escrow.bump
-    )]
-    pub escrow: Account<'info, Escrow>,
+    #[msg("Escrow is already settled")]
+    EscrowSettled,
 
-    pub buyer: Signer<'info>,
+    #[msg("Only the buyer can perform this action")]
+    OnlyBuyer,
 
-    #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[msg("Only the provider can perform this action")]
+    OnlyProvider,
 
-    #[account(mut)]
-    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[msg("Requires both buyer and provider signatures")]
+    OnlyBuyerAndProvider,
 
-    pub token_program: Program<'info, Token>,
-}
+    #[msg("Only the buyer can confirm and release funds")]
+    OnlyBuyerCanConfirm,
 
-/// 发起争议的上下文
-#[derive(Accounts)]
-pub struct RaiseDispute<'info> {
-    #[account(
-        mut,
-        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.request_id.as_bytes()],
-        bump = escrow.bump
-    )]
-    pub escrow: Account<'info, Escrow>,
+    #[msg("Only the arbitrator can refund a disputed escrow")]
+    OnlyArbitratorCanRefund,
 
-    pub buyer: Signer<'info>,
-}
+    #[msg("Only the arbitrator can perform this action")]
+    OnlyArbitrator,
 
-/// 解决争议的上下文
-#[derive(Accounts)]
-pub struct ResolveDispute<'info> {
-    #[account(
-        mut,
-        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.request_id.as_bytes()],
-        bump = escrow.bump
-    )]
-    pub escrow: Account<'info, Escrow>,
+    #[msg("Only the platform authority can perform this action")]
+    OnlyPlatform,
 
-    pub platform: Signer<'info>,
+    #[msg("Only the config admin can perform this action")]
+    OnlyAdmin,
 
-    #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[msg("Only an escrow participant can perform this action")]
+    OnlyParticipant,
 
-    #[account(mut)]
-    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[msg("Only the losing party can appeal")]
+    OnlyLosingParty,
 
-    #[account(mut)]
-    pub provider_token_account: Account<'info, TokenAccount>,
+    #[msg("Only the registered gateway can perform this action")]
+    OnlyGateway,
 
-    #[account(mut)]
-    pub platform_token_account: Account<'info, TokenAccount>,
+    #[msg("Only the template owner can perform this action")]
+    OnlyTemplateOwner,
 
-    pub token_program: Program<'info, Token>,
-}
+    #[msg("Buyer account does not match the escrow")]
+    BuyerMismatch,
 
-/// 错误代码
-#[error_code]
-pub enum EscrowError {
-    #[msg("Invalid escrow status for this operation")]
-    InvalidStatus,
+    #[msg("Mint does not match the escrow token account")]
+    MintMismatch,
 
-    #[msg("Unauthorized: You don't have permission to perform this action")]
-    Unauthorized,
+    #[msg("The response deadline has passed")]
+    DeadlinePassed,
 
-    #[msg("Invalid amount")]
-    InvalidAmount,
+    #[msg("Only the program upgrade authority can initialize the config")]
+    OnlyUpgradeAuthority,
+
+    #[msg("Escrow account size does not match any known layout")]
+    UnknownEscrowLayout,
+
+    #[msg("Yield position must be withdrawn before paying out the escrow")]
+    YieldPositionOpen,
+
+    #[msg("Token account is not the escrow's associated token account")]
+    EscrowTokenAccountMismatch,
+
+    #[msg("Price quotes cannot be combined with unit, download or USD pricing")]
+    QuoteConflictsWithPricing,
+
+    #[msg("Provider account does not match the escrow")]
+    ProviderMismatch,
+
+    #[msg("Platform must be the config admin")]
+    PlatformMismatch,
+
+    #[msg("Quoted price cannot exceed the escrow amount")]
+    QuoteAboveAmount,
+
+    #[msg("Open escrows must be the buyer's distinct, unsettled escrows")]
+    OpenEscrowMismatch,
 }
 
@@ -0,0 +1,767 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use datanexus_escrow::cpi::accounts::{ClaimInsurance, SetAdmin, UpdateConfig, UpdateSettings};
+use datanexus_escrow::program::DatanexusEscrow;
+use datanexus_escrow::{ProtocolSettings, VoteLock};
+
+declare_id!("FpDWoRsHrWduGMkDYimhB3MhoZARbHCxhxK1iQDuGk6p");
+
+/// 治理权限 PDA 种子，移交后作为托管程序 `Config.admin`
+pub const GOVERNANCE_AUTHORITY_SEED: &[u8] = b"governance_authority";
+
+/// DataNexus Governance Program
+///
+/// 以代币持有人投票取代单一管理员密钥修改协议参数：
+/// 1. 托管程序管理员调用 `set_admin` 把 `Config.admin` 移交给本程序的权限 PDA
+/// 2. 持币人存入治理代币获得投票权重，质押达到门槛即可发起提案
+/// 3. 投票期结束后赞成票多于反对票且达到法定票数的提案进入时间锁队列
+/// 4. 时间锁到期后任何人都可以执行，本程序以权限 PDA 通过 CPI 修改托管配置
+#[program]
+pub mod datanexus_governance {
+    use super::*;
+
+    /// 初始化治理参数和代币金库
+    ///
+    /// 仅程序升级权限可调用，防止抢先初始化并以自己控制的治理代币接管移交过来的托管管理员
+    pub fn initialize(ctx: Context<Initialize>, params: GovernanceParams) -> Result<()> {
+        params.validate()?;
+
+        let governance = &mut ctx.accounts.governance;
+        governance.mint = ctx.accounts.mint.key();
+        governance.params = params;
+        governance.proposal_count = 0;
+        governance.bump = ctx.bumps.governance;
+
+        msg!("Governance initialized: {}", governance.key());
+        msg!("Governance mint: {}", governance.mint);
+        msg!(
+            "Authority (hand escrow admin to this): {}",
+            Pubkey::find_program_address(&[GOVERNANCE_AUTHORITY_SEED], &ID).0
+        );
+
+        Ok(())
+    }
+
+    /// 存入治理代币（可多次追加）
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        require!(amount > 0, GovernanceError::InvalidAmount);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.owner_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+        let voter = &mut ctx.accounts.voter;
+        if voter.owner == Pubkey::default() {
+            voter.owner = ctx.accounts.owner.key();
+            voter.bump = ctx.bumps.voter;
+        }
+        voter.deposited = voter.deposited.checked_add(amount).unwrap();
+
+        msg!("Deposited: {}", voter.owner);
+        msg!("Amount: {}, total: {}", amount, voter.deposited);
+
+        Ok(())
+    }
+
+    /// 取回治理代币（参与的投票全部结束后）
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let voter = &mut ctx.accounts.voter;
+
+        require!(
+            amount > 0 && amount <= voter.deposited,
+            GovernanceError::InvalidAmount
+        );
+        require!(
+            voter.lock.is_unlocked(Clock::get()?.unix_timestamp),
+            GovernanceError::DepositLocked
+        );
+
+        voter.deposited -= amount;
+
+        let seeds = &[b"governance".as_ref(), &[ctx.accounts.governance.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.governance.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            amount,
+        )?;
+
+        msg!("Withdrawn: {}", voter.owner);
+        msg!("Amount: {}, remaining: {}", amount, voter.deposited);
+
+        Ok(())
+    }
+
+    /// 发起提案，存入量需达到提案门槛
+    pub fn propose(ctx: Context<Propose>, action: ProposalAction) -> Result<()> {
+        action.validate()?;
+
+        let governance = &mut ctx.accounts.governance;
+        require!(
+            ctx.accounts.voter.deposited >= governance.params.proposal_threshold,
+            GovernanceError::BelowProposalThreshold
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.id = governance.proposal_count;
+        proposal.proposer = ctx.accounts.owner.key();
+        proposal.action = action;
+        proposal.for_votes = 0;
+        proposal.against_votes = 0;
+        proposal.created_at = now;
+        proposal.voting_ends_at = now.checked_add(governance.params.voting_period).unwrap();
+        proposal.eta = 0;
+        proposal.status = ProposalStatus::Voting;
+        proposal.bump = ctx.bumps.proposal;
+
+        governance.proposal_count = governance.proposal_count.checked_add(1).unwrap();
+
+        msg!("Proposal created: {}", proposal.key());
+        msg!("Id: {}, proposer: {}", proposal.id, proposal.proposer);
+        msg!("Voting ends at: {}", proposal.voting_ends_at);
+
+        Ok(())
+    }
+
+    /// 投票，权重为当前存入量
+    pub fn vote(ctx: Context<CastVote>, approve: bool) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let voter = &mut ctx.accounts.voter;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            proposal.status == ProposalStatus::Voting,
+            GovernanceError::InvalidProposalStatus
+        );
+        require!(now < proposal.voting_ends_at, GovernanceError::VotingClosed);
+        require!(voter.deposited > 0, GovernanceError::InvalidAmount);
+
+        let weight = voter.deposited;
+        proposal.record_vote(approve, weight);
+        voter.lock.extend(proposal.voting_ends_at);
+
+        let vote = &mut ctx.accounts.vote;
+        vote.proposal = proposal.key();
+        vote.voter = voter.owner;
+        vote.approve = approve;
+        vote.weight = weight;
+        vote.bump = ctx.bumps.vote;
+
+        msg!("Vote cast on proposal: {}", proposal.key());
+        msg!("Voter: {}, weight: {}, approve: {}", voter.owner, weight, approve);
+
+        Ok(())
+    }
+
+    /// 投票期结束后计票（任何人可调用）
+    ///
+    /// 赞成票多于反对票且达到法定票数时进入时间锁，否则否决
+    pub fn queue(ctx: Context<Queue>) -> Result<()> {
+        let params = &ctx.accounts.governance.params;
+        let proposal = &mut ctx.accounts.proposal;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            proposal.status == ProposalStatus::Voting,
+            GovernanceError::InvalidProposalStatus
+        );
+        require!(now >= proposal.voting_ends_at, GovernanceError::VotingOpen);
+
+        proposal.tally(params, now);
+        if proposal.status == ProposalStatus::Queued {
+            msg!("Proposal queued: {}", proposal.key());
+            msg!("Executable at: {}", proposal.eta);
+        } else {
+            msg!("Proposal defeated: {}", proposal.key());
+        }
+        msg!(
+            "For: {}, against: {}, quorum: {}",
+            proposal.for_votes,
+            proposal.against_votes,
+            params.quorum
+        );
+
+        Ok(())
+    }
+
+    /// 时间锁到期后执行提案（任何人可调用）
+    pub fn execute(ctx: Context<Execute>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+
+        proposal.require_executable(Clock::get()?.unix_timestamp)?;
+
+        proposal.status = ProposalStatus::Executed;
+        let proposal_key = proposal.key();
+        let action = proposal.action.clone();
+
+        let seeds = &[GOVERNANCE_AUTHORITY_SEED, &[ctx.bumps.authority]];
+        let signer = &[&seeds[..]];
+        let cpi_program = ctx.accounts.escrow_program.to_account_info();
+
+        match action {
+            ProposalAction::UpdateConfig {
+                treasury,
+                rewards_pool,
+                treasury_bps,
+                rewards_pool_bps,
+            } => {
+                let (Some(treasury_account), Some(rewards_pool_account)) = (
+                    ctx.accounts.treasury.as_ref(),
+                    ctx.accounts.rewards_pool.as_ref(),
+                ) else {
+                    return err!(GovernanceError::MissingAccounts);
+                };
+                require!(
+                    treasury_account.key() == treasury
+                        && rewards_pool_account.key() == rewards_pool,
+                    GovernanceError::AccountMismatch
+                );
+
+                let (pending_change, payer, system_program) = pending_change_accounts(ctx.accounts)?;
+
+                let cpi_accounts = UpdateConfig {
+                    config: ctx.accounts.escrow_config.to_account_info(),
+                    admin: ctx.accounts.authority.to_account_info(),
+                    treasury: treasury_account.to_account_info(),
+                    rewards_pool: rewards_pool_account.to_account_info(),
+                    pending_change,
+                    payer,
+                    system_program,
+                };
+                datanexus_escrow::cpi::update_config(
+                    CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                    treasury_bps,
+                    rewards_pool_bps,
+                )?;
+            }
+            ProposalAction::UpdateSettings(settings) => {
+                let (pending_change, payer, system_program) = pending_change_accounts(ctx.accounts)?;
+
+                let cpi_accounts = UpdateSettings {
+                    config: ctx.accounts.escrow_config.to_account_info(),
+                    admin: ctx.accounts.authority.to_account_info(),
+                    pending_change,
+                    payer,
+                    system_program,
+                };
+                datanexus_escrow::cpi::update_settings(
+                    CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                    settings,
+                )?;
+            }
+            ProposalAction::SetEscrowAdmin(new_admin) => {
+                let cpi_accounts = SetAdmin {
+                    config: ctx.accounts.escrow_config.to_account_info(),
+                    admin: ctx.accounts.authority.to_account_info(),
+                };
+                datanexus_escrow::cpi::set_admin(
+                    CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                    new_admin,
+                )?;
+            }
+            ProposalAction::ClaimInsurance { escrow, amount } => {
+                let accounts = &ctx.accounts;
+                let (
+                    Some(escrow_account),
+                    Some(claim),
+                    Some(insurance_vault),
+                    Some(buyer_token_account),
+                    Some(payer),
+                    Some(token_program),
+                    Some(system_program),
+                ) = (
+                    accounts.escrow.as_ref(),
+                    accounts.insurance_claim.as_ref(),
+                    accounts.insurance_vault.as_ref(),
+                    accounts.buyer_token_account.as_ref(),
+                    accounts.payer.as_ref(),
+                    accounts.token_program.as_ref(),
+                    accounts.system_program.as_ref(),
+                )
+                else {
+                    return err!(GovernanceError::MissingAccounts);
+                };
+                require!(
+                    escrow_account.key() == escrow,
+                    GovernanceError::AccountMismatch
+                );
+
+                let cpi_accounts = ClaimInsurance {
+                    config: accounts.escrow_config.to_account_info(),
+                    admin: accounts.authority.to_account_info(),
+                    escrow: escrow_account.to_account_info(),
+                    claim: claim.to_account_info(),
+                    insurance_vault: insurance_vault.to_account_info(),
+                    buyer_token_account: buyer_token_account.to_account_info(),
+                    payer: payer.to_account_info(),
+                    token_program: token_program.to_account_info(),
+                    system_program: system_program.to_account_info(),
+                };
+                datanexus_escrow::cpi::claim_insurance(
+                    CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                    amount,
+                )?;
+            }
+            ProposalAction::UpdateGovernance(params) => {
+                ctx.accounts.governance.params = params;
+            }
+        }
+
+        msg!("Proposal executed: {}", proposal_key);
+
+        Ok(())
+    }
+}
+
+/// 排队托管配置变更所需的账户（托管程序在公示期后才应用变更）
+fn pending_change_accounts<'info>(
+    accounts: &Execute<'info>,
+) -> Result<(AccountInfo<'info>, AccountInfo<'info>, AccountInfo<'info>)> {
+    let (Some(pending_change), Some(payer), Some(system_program)) = (
+        accounts.pending_config_change.as_ref(),
+        accounts.payer.as_ref(),
+        accounts.system_program.as_ref(),
+    ) else {
+        return err!(GovernanceError::MissingAccounts);
+    };
+
+    Ok((
+        pending_change.to_account_info(),
+        payer.to_account_info(),
+        system_program.to_account_info(),
+    ))
+}
+
+/// 治理参数（本身也只能通过提案修改）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct GovernanceParams {
+    pub quorum: u64,             // 通过所需的最低赞成票
+    pub proposal_threshold: u64, // 发起提案所需的最低存入量
+    pub voting_period: i64,      // 投票期（秒）
+    pub timelock_delay: i64,     // 通过后到可执行的等待时间（秒）
+}
+
+impl GovernanceParams {
+    fn validate(&self) -> Result<()> {
+        require!(self.quorum > 0, GovernanceError::InvalidParams);
+        require!(
+            self.voting_period > 0 && self.timelock_delay >= 0,
+            GovernanceError::InvalidParams
+        );
+        Ok(())
+    }
+}
+
+/// 提案要执行的操作
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub enum ProposalAction {
+    /// 托管程序 `update_config`：平台费分配目标和基点
+    UpdateConfig {
+        treasury: Pubkey,
+        rewards_pool: Pubkey,
+        treasury_bps: u16,
+        rewards_pool_bps: u16,
+    },
+    /// 托管程序 `update_settings`：协议策略参数
+    UpdateSettings(ProtocolSettings),
+    /// 托管程序 `set_admin`：把管理员移交给新的治理方案
+    SetEscrowAdmin(Pubkey),
+    /// 托管程序 `claim_insurance`：从保险池赔付该托管的买家
+    ClaimInsurance { escrow: Pubkey, amount: u64 },
+    /// 修改本程序的治理参数
+    UpdateGovernance(GovernanceParams),
+}
+
+impl ProposalAction {
+    /// 发起提案时校验：修改治理参数的提案须满足法定票数为正、投票期为正且时间锁不为负
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::UpdateGovernance(params) => params.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 治理配置
+#[account]
+#[derive(InitSpace)]
+pub struct Governance {
+    pub mint: Pubkey,            // 治理代币
+    pub params: GovernanceParams, // 治理参数
+    pub proposal_count: u64,     // 已创建的提案数（下一个提案 ID）
+    pub bump: u8,                // PDA bump
+}
+
+impl Governance {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 持币人存入的治理代币
+#[account]
+#[derive(InitSpace)]
+pub struct Voter {
+    pub owner: Pubkey,           // 持币人
+    pub deposited: u64,          // 存入数量（投票权重）
+    pub lock: VoteLock,          // 存入锁定期（所投提案的最晚投票截止时间）
+    pub bump: u8,                // PDA bump
+}
+
+impl Voter {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 提案
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub id: u64,                 // 提案 ID
+    pub proposer: Pubkey,        // 发起人
+    pub action: ProposalAction,  // 要执行的操作
+    pub for_votes: u64,          // 赞成票
+    pub against_votes: u64,      // 反对票
+    pub created_at: i64,         // 创建时间
+    pub voting_ends_at: i64,     // 投票截止时间
+    pub eta: i64,                // 可执行时间（进入队列后有效）
+    pub status: ProposalStatus,  // 提案状态
+    pub bump: u8,                // PDA bump
+}
+
+impl Proposal {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// 按存入量记入一票
+    pub fn record_vote(&mut self, approve: bool, weight: u64) {
+        if approve {
+            self.for_votes = self.for_votes.checked_add(weight).unwrap();
+        } else {
+            self.against_votes = self.against_votes.checked_add(weight).unwrap();
+        }
+    }
+
+    /// 计票：赞成票多于反对票且达到法定票数时进入时间锁队列，否则否决
+    pub fn tally(&mut self, params: &GovernanceParams, now: i64) {
+        if self.for_votes > self.against_votes && self.for_votes >= params.quorum {
+            self.status = ProposalStatus::Queued;
+            self.eta = now.checked_add(params.timelock_delay).unwrap();
+        } else {
+            self.status = ProposalStatus::Defeated;
+        }
+    }
+
+    /// 已通过且时间锁到期才能执行
+    pub fn require_executable(&self, now: i64) -> Result<()> {
+        require!(
+            self.status == ProposalStatus::Queued,
+            GovernanceError::InvalidProposalStatus
+        );
+        require!(now >= self.eta, GovernanceError::TimelockActive);
+        Ok(())
+    }
+}
+
+/// 一次投票（每个提案每人一票）
+#[account]
+#[derive(InitSpace)]
+pub struct VoteRecord {
+    pub proposal: Pubkey,        // 提案
+    pub voter: Pubkey,           // 投票人
+    pub approve: bool,           // 是否赞成
+    pub weight: u64,             // 投票权重
+    pub bump: u8,                // PDA bump
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 提案状态
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub enum ProposalStatus {
+    Voting,     // 投票中
+    Queued,     // 已通过，等待时间锁
+    Defeated,   // 未通过
+    Executed,   // 已执行
+}
+
+/// 初始化的上下文
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Governance::LEN,
+        seeds = [b"governance"],
+        bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"vault"],
+        bump,
+        token::mint = mint,
+        token::authority = governance,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::DatanexusGovernance>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ GovernanceError::OnlyUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 存入的上下文
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Voter::LEN,
+        seeds = [b"voter", owner.key().as_ref()],
+        bump
+    )]
+    pub voter: Account<'info, Voter>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = governance.mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 取回的上下文
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"voter", owner.key().as_ref()],
+        bump = voter.bump,
+        has_one = owner @ GovernanceError::Unauthorized
+    )]
+    pub voter: Account<'info, Voter>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut, token::mint = governance.mint)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 发起提案的上下文
+#[derive(Accounts)]
+pub struct Propose<'info> {
+    #[account(mut, seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Proposal::LEN,
+        seeds = [b"proposal", governance.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        seeds = [b"voter", owner.key().as_ref()],
+        bump = voter.bump,
+        has_one = owner @ GovernanceError::Unauthorized
+    )]
+    pub voter: Account<'info, Voter>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 投票的上下文
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"voter", owner.key().as_ref()],
+        bump = voter.bump,
+        has_one = owner @ GovernanceError::Unauthorized
+    )]
+    pub voter: Account<'info, Voter>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = VoteRecord::LEN,
+        seeds = [b"vote", proposal.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub vote: Account<'info, VoteRecord>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 计票的上下文
+#[derive(Accounts)]
+pub struct Queue<'info> {
+    #[account(seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+/// 执行提案的上下文，托管相关账户由托管程序校验
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    #[account(mut, seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: PDA that signs escrow CPIs as the escrow config admin
+    #[account(seeds = [GOVERNANCE_AUTHORITY_SEED], bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow config PDA, validated by the escrow program
+    #[account(mut)]
+    pub escrow_config: UncheckedAccount<'info>,
+
+    /// CHECK: New treasury token account for `UpdateConfig`, must match the proposal
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: New rewards pool token account for `UpdateConfig`, must match the proposal
+    pub rewards_pool: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Escrow for `ClaimInsurance`, must match the proposal
+    pub escrow: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Insurance claim PDA for `ClaimInsurance`, created by the escrow program
+    #[account(mut)]
+    pub insurance_claim: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Insurance vault for `ClaimInsurance`, validated by the escrow program
+    #[account(mut)]
+    pub insurance_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Buyer token account for `ClaimInsurance`, validated by the escrow program
+    #[account(mut)]
+    pub buyer_token_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Pending config change PDA for `UpdateConfig` / `UpdateSettings`, created by the escrow program
+    #[account(mut)]
+    pub pending_config_change: Option<UncheckedAccount<'info>>,
+
+    /// 支付赔付记录或待生效配置变更的租金（`ClaimInsurance`、`UpdateConfig`、`UpdateSettings`）
+    #[account(mut)]
+    pub payer: Option<Signer<'info>>,
+
+    /// CHECK: Token program for `ClaimInsurance`, validated by the escrow program
+    pub token_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: System program for `ClaimInsurance`, `UpdateConfig` and `UpdateSettings`, validated by the escrow program
+    pub system_program: Option<UncheckedAccount<'info>>,
+
+    pub escrow_program: Program<'info, DatanexusEscrow>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum GovernanceError {
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("Quorum and voting period must be positive and the timelock non-negative")]
+    InvalidParams,
+
+    #[msg("Deposit is below the proposal threshold")]
+    BelowProposalThreshold,
+
+    #[msg("Deposit is locked until the proposals it voted on close")]
+    DepositLocked,
+
+    #[msg("Proposal is not in the required status")]
+    InvalidProposalStatus,
+
+    #[msg("Voting period has ended")]
+    VotingClosed,
+
+    #[msg("Voting period has not ended yet")]
+    VotingOpen,
+
+    #[msg("Timelock has not expired yet")]
+    TimelockActive,
+
+    #[msg("Accounts required by the proposal action are missing")]
+    MissingAccounts,
+
+    #[msg("Accounts do not match the proposal action")]
+    AccountMismatch,
+
+    #[msg("Only the program upgrade authority can initialize governance")]
+    OnlyUpgradeAuthority,
+}
@@ -0,0 +1,764 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use datanexus_escrow::cpi::accounts::{EscalateDispute, ResolveDispute};
+use datanexus_escrow::program::DatanexusEscrow;
+use datanexus_escrow::{Escrow, EscrowStatus, VoteLock, JURY_AUTHORITY_SEED};
+
+declare_id!("HLtvvKCiYsp83uBD5VBSXyjggSkynqzkGSMCN39ZbRFj");
+
+/// DataNexus Jury Program
+///
+/// 代币持有人陪审团，取代平台成为大额争议的唯一裁决方：
+/// 1. 陪审员质押治理代币，质押量即投票权重
+/// 2. 金额达到门槛的争议由买家或提供商移交陪审团，托管程序随即锁定平台的裁决权
+/// 3. 陪审员在投票期内投票（退款给买家或释放给提供商），投票后质押锁定至投票期结束
+/// 4. 投票期结束后任何人都可以执行裁决，本程序以权限 PDA 通过 CPI 调用托管程序结算
+#[program]
+pub mod datanexus_jury {
+    use super::*;
+
+    /// 初始化陪审团配置和质押金库
+    ///
+    /// 仅程序升级权限可调用，陪审团权限 PDA 能裁决所有移交的托管，不能由抢先初始化的一方控制
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        min_amount: u64,
+        min_stake: u64,
+        voting_period: i64,
+    ) -> Result<()> {
+        require!(voting_period > 0, JuryError::InvalidVotingPeriod);
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.stake_mint = ctx.accounts.stake_mint.key();
+        config.min_amount = min_amount;
+        config.min_stake = min_stake;
+        config.voting_period = voting_period;
+        config.bump = ctx.bumps.config;
+
+        msg!("Jury initialized: {}", config.key());
+        msg!("Stake mint: {}", config.stake_mint);
+        msg!("Min escrow amount: {} USDC", min_amount);
+        msg!("Min stake: {}, voting period: {}s", min_stake, voting_period);
+
+        Ok(())
+    }
+
+    /// 更新移交门槛、最低质押和投票期（仅管理员）
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        min_amount: u64,
+        min_stake: u64,
+        voting_period: i64,
+    ) -> Result<()> {
+        require!(voting_period > 0, JuryError::InvalidVotingPeriod);
+
+        let config = &mut ctx.accounts.config;
+        config.min_amount = min_amount;
+        config.min_stake = min_stake;
+        config.voting_period = voting_period;
+
+        msg!("Jury config updated: {}", config.key());
+        msg!("Min escrow amount: {} USDC", min_amount);
+        msg!("Min stake: {}, voting period: {}s", min_stake, voting_period);
+
+        Ok(())
+    }
+
+    /// 质押治理代币成为陪审员（可多次追加）
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        require!(amount > 0, JuryError::InvalidAmount);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.owner_token_account.to_account_info(),
+            to: ctx.accounts.stake_vault.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+        let juror = &mut ctx.accounts.juror;
+        if juror.owner == Pubkey::default() {
+            juror.owner = ctx.accounts.owner.key();
+            juror.bump = ctx.bumps.juror;
+        }
+        juror.staked = juror.staked.checked_add(amount).unwrap();
+
+        msg!("Juror staked: {}", juror.owner);
+        msg!("Amount: {}, total: {}", amount, juror.staked);
+
+        Ok(())
+    }
+
+    /// 取回质押（参与的投票全部结束后）
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        let juror = &mut ctx.accounts.juror;
+
+        require!(amount > 0 && amount <= juror.staked, JuryError::InvalidAmount);
+        require!(
+            juror.lock.is_unlocked(Clock::get()?.unix_timestamp),
+            JuryError::StakeLocked
+        );
+
+        juror.staked -= amount;
+
+        let seeds = &[b"jury_config".as_ref(), &[ctx.accounts.config.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.stake_vault.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            amount,
+        )?;
+
+        msg!("Juror unstaked: {}", juror.owner);
+        msg!("Amount: {}, remaining: {}", amount, juror.staked);
+
+        Ok(())
+    }
+
+    /// 买家或提供商把争议移交陪审团
+    ///
+    /// 托管必须处于 Disputed 状态且金额不低于门槛，或平台裁决已被上诉（不受门槛限制）；
+    /// 通过 CPI 将托管置为 Escalated，此后平台不能再退款或裁决
+    pub fn open_case(ctx: Context<OpenCase>) -> Result<()> {
+        // 升级争议的 CPI 会写入托管账户，这里只读取副本
+        let escrow = *ctx.accounts.escrow.load()?;
+        let party = ctx.accounts.party.key();
+
+        require!(
+            party == escrow.buyer || party == escrow.provider,
+            JuryError::Unauthorized
+        );
+        require!(
+            escrow.status() == EscrowStatus::Disputed || escrow.status() == EscrowStatus::Appealed,
+            JuryError::EscrowNotDisputed
+        );
+        require!(
+            escrow.status() == EscrowStatus::Appealed
+                || escrow.amount >= ctx.accounts.config.min_amount,
+            JuryError::AmountBelowThreshold
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+
+        let case = &mut ctx.accounts.case;
+        case.escrow = ctx.accounts.escrow.key();
+        case.buyer = escrow.buyer;
+        case.provider = escrow.provider;
+        case.opened_by = party;
+        case.opened_at = now;
+        case.voting_ends_at = now.checked_add(ctx.accounts.config.voting_period).unwrap();
+        case.buyer_votes = 0;
+        case.provider_votes = 0;
+        case.voters = 0;
+        case.status = CaseStatus::Voting;
+        case.refund_to_buyer = false;
+        case.bump = ctx.bumps.case;
+
+        let seeds = &[JURY_AUTHORITY_SEED, &[ctx.bumps.jury_authority]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = EscalateDispute {
+            escrow: ctx.accounts.escrow.to_account_info(),
+            jury_authority: ctx.accounts.jury_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.escrow_program.to_account_info();
+        datanexus_escrow::cpi::escalate_dispute(CpiContext::new_with_signer(
+            cpi_program,
+            cpi_accounts,
+            signer,
+        ))?;
+
+        msg!("Case opened: {}", case.key());
+        msg!("Escrow: {}", case.escrow);
+        msg!("Voting ends at: {}", case.voting_ends_at);
+
+        Ok(())
+    }
+
+    /// 陪审员投票，权重为当前质押量；买卖双方不能为自己的案件投票
+    pub fn vote(ctx: Context<CastVote>, refund_to_buyer: bool) -> Result<()> {
+        let case = &mut ctx.accounts.case;
+        let juror = &mut ctx.accounts.juror;
+        let now = Clock::get()?.unix_timestamp;
+
+        case.require_eligible(juror, ctx.accounts.config.min_stake, now)?;
+
+        let weight = juror.staked;
+        case.record_vote(refund_to_buyer, weight);
+        juror.lock.extend(case.voting_ends_at);
+
+        let vote = &mut ctx.accounts.vote;
+        vote.case = case.key();
+        vote.juror = juror.owner;
+        vote.refund_to_buyer = refund_to_buyer;
+        vote.weight = weight;
+        vote.voted_at = now;
+        vote.bump = ctx.bumps.vote;
+
+        msg!("Vote cast on case: {}", case.key());
+        msg!("Juror: {}, weight: {}", juror.owner, weight);
+        msg!("Refund to buyer: {}", refund_to_buyer);
+
+        Ok(())
+    }
+
+    /// 投票期结束后执行裁决（任何人可调用）
+    ///
+    /// 支持买家的权重不低于支持提供商的权重时退款给买家（包括无人投票），
+    /// 否则释放给提供商；通过 CPI 调用托管程序的 `resolve_dispute`
+    pub fn execute_verdict(ctx: Context<ExecuteVerdict>) -> Result<()> {
+        let case = &mut ctx.accounts.case;
+
+        require!(case.status == CaseStatus::Voting, JuryError::CaseClosed);
+        require!(
+            Clock::get()?.unix_timestamp >= case.voting_ends_at,
+            JuryError::VotingOpen
+        );
+
+        let refund_to_buyer = case.verdict();
+        case.status = CaseStatus::Executed;
+        case.refund_to_buyer = refund_to_buyer;
+
+        let seeds = &[JURY_AUTHORITY_SEED, &[ctx.bumps.jury_authority]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = ResolveDispute {
+            escrow: ctx.accounts.escrow.to_account_info(),
+            platform: ctx.accounts.jury_authority.to_account_info(),
+            provider_stats: ctx.accounts.provider_stats.to_account_info(),
+            buyer_stats: ctx.accounts.buyer_stats.to_account_info(),
+            config: ctx.accounts.escrow_config.to_account_info(),
+            protocol_stats: ctx.accounts.protocol_stats.to_account_info(),
+            escrow_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+            buyer_token_account: ctx.accounts.buyer_token_account.to_account_info(),
+            provider_token_account: ctx.accounts.provider_token_account.to_account_info(),
+            treasury_token_account: Some(ctx.accounts.treasury_token_account.to_account_info()),
+            rewards_pool_token_account: Some(
+                ctx.accounts.rewards_pool_token_account.to_account_info(),
+            ),
+            insurance_vault: ctx
+                .accounts
+                .insurance_vault
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            fee_ledger: ctx
+                .accounts
+                .fee_ledger
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            fee_vault: ctx
+                .accounts
+                .fee_vault
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            ruling: None,
+            discount: ctx.accounts.discount.to_account_info(),
+            price_update: ctx
+                .accounts
+                .price_update
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            usd_pricing: ctx.accounts.usd_pricing.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            memo_program: ctx
+                .accounts
+                .memo_program
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            access_mint: ctx
+                .accounts
+                .access_mint
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            buyer_access_account: ctx
+                .accounts
+                .buyer_access_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            access_authority: ctx
+                .accounts
+                .access_authority
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            access_token_program: ctx
+                .accounts
+                .access_token_program
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            access_window: ctx.accounts.access_window.to_account_info(),
+            sales_history: ctx.accounts.sales_history.to_account_info(),
+            restriction: ctx.accounts.restriction.to_account_info(),
+            dispute_record: ctx.accounts.dispute_record.to_account_info(),
+            buyer: ctx.accounts.buyer.to_account_info(),
+            provider: ctx.accounts.provider.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.escrow_program.to_account_info();
+        datanexus_escrow::cpi::resolve_dispute(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            refund_to_buyer,
+        )?;
+
+        msg!("Verdict executed for case: {}", case.key());
+        msg!(
+            "Buyer votes: {}, provider votes: {}, voters: {}",
+            case.buyer_votes,
+            case.provider_votes,
+            case.voters
+        );
+        msg!("Refund to buyer: {}", refund_to_buyer);
+
+        Ok(())
+    }
+}
+
+/// 陪审团配置
+#[account]
+#[derive(InitSpace)]
+pub struct JuryConfig {
+    pub admin: Pubkey,           // 管理员
+    pub stake_mint: Pubkey,      // 质押代币
+    pub min_amount: u64,         // 可移交陪审团的最低托管金额（6 位小数）
+    pub min_stake: u64,          // 投票所需的最低质押
+    pub voting_period: i64,      // 投票期（秒）
+    pub bump: u8,                // PDA bump
+}
+
+impl JuryConfig {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 陪审员质押
+#[account]
+#[derive(InitSpace)]
+pub struct Juror {
+    pub owner: Pubkey,           // 陪审员钱包
+    pub staked: u64,             // 质押数量（投票权重）
+    pub lock: VoteLock,          // 质押锁定期（所投案件的最晚投票截止时间）
+    pub bump: u8,                // PDA bump
+}
+
+impl Juror {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 陪审团案件，每个托管至多一个
+#[account]
+#[derive(InitSpace)]
+pub struct Case {
+    pub escrow: Pubkey,          // 争议托管
+    pub buyer: Pubkey,           // 买家
+    pub provider: Pubkey,        // 提供商
+    pub opened_by: Pubkey,       // 移交方（买家或提供商）
+    pub opened_at: i64,          // 移交时间
+    pub voting_ends_at: i64,     // 投票截止时间
+    pub buyer_votes: u64,        // 支持退款给买家的权重
+    pub provider_votes: u64,     // 支持释放给提供商的权重
+    pub voters: u32,             // 投票人数
+    pub status: CaseStatus,      // 案件状态
+    pub refund_to_buyer: bool,   // 裁决结果（执行后有效）
+    pub bump: u8,                // PDA bump
+}
+
+impl Case {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// 按质押权重记入一票
+    pub fn record_vote(&mut self, refund_to_buyer: bool, weight: u64) {
+        if refund_to_buyer {
+            self.buyer_votes = self.buyer_votes.checked_add(weight).unwrap();
+        } else {
+            self.provider_votes = self.provider_votes.checked_add(weight).unwrap();
+        }
+        self.voters = self.voters.checked_add(1).unwrap();
+    }
+
+    /// 投票中的案件在截止前接受质押不低于 `min_stake` 的陪审员投票，买卖双方不能投票
+    pub fn require_eligible(&self, juror: &Juror, min_stake: u64, now: i64) -> Result<()> {
+        require!(self.status == CaseStatus::Voting, JuryError::CaseClosed);
+        require!(now < self.voting_ends_at, JuryError::VotingClosed);
+        require!(
+            juror.owner != self.buyer && juror.owner != self.provider,
+            JuryError::ConflictOfInterest
+        );
+        require!(
+            juror.staked > 0 && juror.staked >= min_stake,
+            JuryError::InsufficientStake
+        );
+        Ok(())
+    }
+
+    /// 裁决结果：支持买家的权重不低于支持提供商的权重时退款给买家（平票和无人投票均退款）
+    pub fn verdict(&self) -> bool {
+        self.buyer_votes >= self.provider_votes
+    }
+}
+
+/// 陪审员的一次投票（每个案件每人一票）
+#[account]
+#[derive(InitSpace)]
+pub struct Vote {
+    pub case: Pubkey,            // 案件
+    pub juror: Pubkey,           // 陪审员
+    pub refund_to_buyer: bool,   // 投票选项
+    pub weight: u64,             // 投票权重
+    pub voted_at: i64,           // 投票时间
+    pub bump: u8,                // PDA bump
+}
+
+impl Vote {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 案件状态
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub enum CaseStatus {
+    Voting,     // 投票中
+    Executed,   // 已执行裁决
+}
+
+/// 初始化的上下文
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = JuryConfig::LEN,
+        seeds = [b"jury_config"],
+        bump
+    )]
+    pub config: Account<'info, JuryConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"stake_vault"],
+        bump,
+        token::mint = stake_mint,
+        token::authority = config,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    pub stake_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::DatanexusJury>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ JuryError::OnlyUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 更新配置的上下文
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"jury_config"],
+        bump = config.bump,
+        has_one = admin @ JuryError::Unauthorized
+    )]
+    pub config: Account<'info, JuryConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+/// 质押的上下文
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(seeds = [b"jury_config"], bump = config.bump)]
+    pub config: Account<'info, JuryConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Juror::LEN,
+        seeds = [b"juror", owner.key().as_ref()],
+        bump
+    )]
+    pub juror: Account<'info, Juror>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = config.stake_mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"stake_vault"], bump)]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 取回质押的上下文
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(seeds = [b"jury_config"], bump = config.bump)]
+    pub config: Account<'info, JuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"juror", owner.key().as_ref()],
+        bump = juror.bump,
+        has_one = owner @ JuryError::Unauthorized
+    )]
+    pub juror: Account<'info, Juror>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut, token::mint = config.stake_mint)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"stake_vault"], bump)]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 移交陪审团的上下文
+#[derive(Accounts)]
+pub struct OpenCase<'info> {
+    #[account(seeds = [b"jury_config"], bump = config.bump)]
+    pub config: Account<'info, JuryConfig>,
+
+    #[account(
+        init,
+        payer = party,
+        space = Case::LEN,
+        seeds = [b"case", escrow.key().as_ref()],
+        bump
+    )]
+    pub case: Account<'info, Case>,
+
+    #[account(mut)]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    /// 买家或提供商
+    #[account(mut)]
+    pub party: Signer<'info>,
+
+    /// CHECK: PDA that signs escrow CPIs on behalf of the jury
+    #[account(seeds = [JURY_AUTHORITY_SEED], bump)]
+    pub jury_authority: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, DatanexusEscrow>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 投票的上下文
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(seeds = [b"jury_config"], bump = config.bump)]
+    pub config: Account<'info, JuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"case", case.escrow.as_ref()],
+        bump = case.bump
+    )]
+    pub case: Account<'info, Case>,
+
+    #[account(
+        mut,
+        seeds = [b"juror", owner.key().as_ref()],
+        bump = juror.bump,
+        has_one = owner @ JuryError::Unauthorized
+    )]
+    pub juror: Account<'info, Juror>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Vote::LEN,
+        seeds = [b"vote", case.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub vote: Account<'info, Vote>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 执行裁决的上下文，托管相关账户由托管程序校验
+#[derive(Accounts)]
+pub struct ExecuteVerdict<'info> {
+    #[account(
+        mut,
+        seeds = [b"case", case.escrow.as_ref()],
+        bump = case.bump
+    )]
+    pub case: Account<'info, Case>,
+
+    /// CHECK: Escrow under review, validated by the escrow program
+    #[account(mut, address = case.escrow @ JuryError::Unauthorized)]
+    pub escrow: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs escrow CPIs on behalf of the jury
+    #[account(seeds = [JURY_AUTHORITY_SEED], bump)]
+    pub jury_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Provider stats PDA, validated by the escrow program
+    #[account(mut)]
+    pub provider_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer stats PDA, validated by the escrow program
+    #[account(mut)]
+    pub buyer_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow config PDA, validated by the escrow program
+    pub escrow_config: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol stats PDA, validated by the escrow program
+    #[account(mut)]
+    pub protocol_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow token account, validated by the escrow program
+    #[account(mut)]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer token account, validated by the escrow program
+    #[account(mut)]
+    pub buyer_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Provider token account, validated by the escrow program
+    #[account(mut)]
+    pub provider_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury token account, validated by the escrow program
+    #[account(mut)]
+    pub treasury_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Rewards pool token account, validated by the escrow program
+    #[account(mut)]
+    pub rewards_pool_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Optional insurance vault, validated by the escrow program
+    #[account(mut)]
+    pub insurance_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional fee ledger, validated by the escrow program
+    #[account(mut)]
+    pub fee_ledger: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional shared fee vault, validated by the escrow program
+    #[account(mut)]
+    pub fee_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Coupon discount PDA, validated by the escrow program
+    pub discount: UncheckedAccount<'info>,
+
+    /// CHECK: Optional Pyth price update, required for USD-priced escrows released to the provider
+    pub price_update: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: USD pricing PDA, validated and updated by the escrow program
+    #[account(mut)]
+    pub usd_pricing: UncheckedAccount<'info>,
+
+    /// CHECK: Optional access mint, validated by the escrow program
+    #[account(mut)]
+    pub access_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional buyer access token account, validated by the escrow program
+    #[account(mut)]
+    pub buyer_access_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional access authority PDA, validated by the escrow program
+    pub access_authority: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional access token program, validated by the escrow program
+    pub access_token_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Access window PDA, validated by the escrow program
+    #[account(mut)]
+    pub access_window: UncheckedAccount<'info>,
+
+    /// CHECK: Provider sales history PDA, validated by the escrow program
+    #[account(mut)]
+    pub sales_history: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow restriction PDA, validated by the escrow program
+    pub restriction: UncheckedAccount<'info>,
+
+    /// CHECK: Dispute record PDA, validated and closed by the escrow program
+    #[account(mut)]
+    pub dispute_record: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer wallet, validated by the escrow program
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: Provider wallet, validated by the escrow program
+    #[account(mut)]
+    pub provider: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow token mint, validated by the escrow program
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: Optional memo program, validated by the escrow program
+    pub memo_program: Option<UncheckedAccount<'info>>,
+
+    pub escrow_program: Program<'info, DatanexusEscrow>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum JuryError {
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("Voting period must be positive")]
+    InvalidVotingPeriod,
+
+    #[msg("Escrow is not in the Disputed or Appealed state")]
+    EscrowNotDisputed,
+
+    #[msg("Escrow amount is below the jury threshold")]
+    AmountBelowThreshold,
+
+    #[msg("Juror stake is below the minimum")]
+    InsufficientStake,
+
+    #[msg("Stake is locked until the voting periods it took part in end")]
+    StakeLocked,
+
+    #[msg("Buyer and provider cannot vote on their own case")]
+    ConflictOfInterest,
+
+    #[msg("Voting period has ended")]
+    VotingClosed,
+
+    #[msg("Voting period has not ended yet")]
+    VotingOpen,
+
+    #[msg("Case has already been executed")]
+    CaseClosed,
+
+    #[msg("Only the program upgrade authority can initialize the jury")]
+    OnlyUpgradeAuthority,
+}
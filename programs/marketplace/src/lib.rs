@@ -0,0 +1,343 @@
+use std::fmt::Write;
+
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use datanexus_escrow::cpi::accounts::{CreateEscrow, FundEscrow};
+use datanexus_escrow::program::DatanexusEscrow;
+use datanexus_escrow::Config as EscrowConfig;
+
+declare_id!("G8UmtfBFS75a77GrTYtPC185NWN9ZG3q6QoVnwZDSKmz");
+
+/// DataNexus Marketplace Program
+///
+/// 固定价格数据挂单：
+/// 1. 提供商发布挂单（价格、支付币种、元数据哈希、库存）
+/// 2. 买家调用 `purchase`，在同一交易中通过 CPI 创建并充值托管
+/// 3. 交付、确认和争议沿用托管程序的流程
+#[program]
+pub mod datanexus_marketplace {
+    use super::*;
+
+    /// 发布挂单
+    pub fn create_listing(
+        ctx: Context<CreateListing>,
+        listing_id: u64,
+        price: u64,
+        metadata_hash: [u8; 32],
+        stock: u32,
+    ) -> Result<()> {
+        require!(price > 0, MarketplaceError::InvalidPrice);
+
+        let listing = &mut ctx.accounts.listing;
+        listing.provider = ctx.accounts.provider.key();
+        listing.listing_id = listing_id;
+        listing.mint = ctx.accounts.mint.key();
+        listing.price = price;
+        listing.metadata_hash = metadata_hash;
+        listing.stock = stock;
+        listing.sold = 0;
+        listing.active = true;
+        listing.created_at = Clock::get()?.unix_timestamp;
+        listing.bump = ctx.bumps.listing;
+
+        msg!("Listing created: {}", listing.key());
+        msg!("Provider: {}", listing.provider);
+        msg!("Price: {} USDC, stock: {}", price, stock);
+
+        Ok(())
+    }
+
+    /// 更新挂单（仅提供商）
+    pub fn update_listing(
+        ctx: Context<UpdateListing>,
+        price: u64,
+        metadata_hash: [u8; 32],
+        stock: u32,
+        active: bool,
+    ) -> Result<()> {
+        require!(price > 0, MarketplaceError::InvalidPrice);
+
+        let listing = &mut ctx.accounts.listing;
+        listing.price = price;
+        listing.metadata_hash = metadata_hash;
+        listing.stock = stock;
+        listing.active = active;
+
+        msg!("Listing updated: {}", listing.key());
+        msg!("Price: {} USDC, stock: {}, active: {}", price, stock, active);
+
+        Ok(())
+    }
+
+    /// 购买挂单
+    ///
+    /// 扣减库存，并通过 CPI 调用托管程序创建托管并立即充值；
+    /// request_id 由 `purchase_request_id` 生成，proposal_id 为挂单地址
+    pub fn purchase(ctx: Context<Purchase>) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+
+        require!(listing.active, MarketplaceError::ListingInactive);
+        require!(listing.stock > 0, MarketplaceError::OutOfStock);
+
+        let request_id = purchase_request_id(&listing.key(), listing.sold);
+        let proposal_id = listing.key().to_string();
+        let price = listing.price;
+
+        listing.stock -= 1;
+        listing.sold = listing.sold.checked_add(1).unwrap();
+
+        let cpi_accounts = CreateEscrow {
+            escrow: ctx.accounts.escrow.to_account_info(),
+            buyer: ctx.accounts.buyer.to_account_info(),
+            config: ctx.accounts.escrow_config.to_account_info(),
+            buyer_stats: ctx.accounts.buyer_stats.to_account_info(),
+            provider: ctx.accounts.provider.to_account_info(),
+            provider_stats: ctx.accounts.provider_stats.to_account_info(),
+            provider_escrow_index: ctx.accounts.provider_escrow_index.to_account_info(),
+            protocol_stats: ctx.accounts.protocol_stats.to_account_info(),
+            provider_profile: ctx
+                .accounts
+                .provider_profile
+                .as_ref()
+                .map(|profile| profile.to_account_info()),
+            request: None,
+            proposal: None,
+            restriction: None,
+            platform: ctx.accounts.platform.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            coupon: None,
+            discount: None,
+            coupon_redeemer: None,
+            coupons_program: None,
+            access_mint: None,
+            buyer_attestation: ctx
+                .accounts
+                .buyer_attestation
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            provider_attestation: ctx
+                .accounts
+                .provider_attestation
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            provider_badge: ctx
+                .accounts
+                .provider_badge
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            buyer_blacklist: ctx.accounts.buyer_blacklist.to_account_info(),
+            provider_blacklist: ctx.accounts.provider_blacklist.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.escrow_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program.clone(), cpi_accounts);
+
+        datanexus_escrow::cpi::create_escrow(
+            cpi_ctx,
+            price,
+            request_id.clone(),
+            proposal_id,
+            None,
+        )?;
+
+        let cpi_accounts = FundEscrow {
+            escrow: ctx.accounts.escrow.to_account_info(),
+            buyer: ctx.accounts.buyer.to_account_info(),
+            buyer_stats: ctx.accounts.buyer_stats.to_account_info(),
+            protocol_stats: ctx.accounts.protocol_stats.to_account_info(),
+            quote: ctx.accounts.escrow_quote.to_account_info(),
+            buyer_token_account: ctx.accounts.buyer_token_account.to_account_info(),
+            escrow_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        datanexus_escrow::cpi::fund_escrow(cpi_ctx)?;
+
+        msg!("Listing purchased: {}", listing.key());
+        msg!("Escrow request: {}", request_id);
+        msg!("Remaining stock: {}", listing.stock);
+
+        Ok(())
+    }
+}
+
+/// 购买生成的托管 request_id 前缀
+pub const PURCHASE_REQUEST_PREFIX: &str = "mkt-";
+
+/// 购买生成的托管 request_id：前缀加 `(挂单地址, 销售序号)` 哈希前 12 字节的十六进制
+///
+/// 托管 PDA 以 request_id 为种子，单个种子不能超过 32 字节，因此不能直接拼接挂单地址
+pub fn purchase_request_id(listing: &Pubkey, sold: u32) -> String {
+    let hash = hashv(&[listing.as_ref(), &sold.to_le_bytes()]);
+    let mut request_id = String::from(PURCHASE_REQUEST_PREFIX);
+    for byte in &hash.to_bytes()[..12] {
+        write!(request_id, "{:02x}", byte).unwrap();
+    }
+    request_id
+}
+
+/// 固定价格挂单
+#[account]
+#[derive(InitSpace)]
+pub struct Listing {
+    pub provider: Pubkey,        // 提供商
+    pub listing_id: u64,         // 提供商自定义挂单序号
+    pub mint: Pubkey,            // 支付币种
+    pub price: u64,              // 单价（6 位小数）
+    pub metadata_hash: [u8; 32], // 数据集元数据哈希
+    pub stock: u32,              // 剩余库存
+    pub sold: u32,               // 已售数量
+    pub active: bool,            // 是否上架
+    pub created_at: i64,         // 创建时间
+    pub bump: u8,                // PDA bump
+}
+
+impl Listing {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 发布挂单的上下文
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct CreateListing<'info> {
+    #[account(
+        init,
+        payer = provider,
+        space = Listing::LEN,
+        seeds = [b"listing", provider.key().as_ref(), &listing_id.to_le_bytes()],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    /// CHECK: Payment mint, enforced on the buyer and escrow token accounts at purchase
+    pub mint: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 更新挂单的上下文
+#[derive(Accounts)]
+pub struct UpdateListing<'info> {
+    #[account(
+        mut,
+        seeds = [b"listing", provider.key().as_ref(), &listing.listing_id.to_le_bytes()],
+        bump = listing.bump,
+        has_one = provider @ MarketplaceError::Unauthorized
+    )]
+    pub listing: Account<'info, Listing>,
+
+    pub provider: Signer<'info>,
+}
+
+/// 购买的上下文
+#[derive(Accounts)]
+pub struct Purchase<'info> {
+    #[account(
+        mut,
+        seeds = [b"listing", listing.provider.as_ref(), &listing.listing_id.to_le_bytes()],
+        bump = listing.bump,
+        has_one = provider
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// CHECK: Escrow PDA, initialized by the escrow program
+    #[account(mut)]
+    pub escrow: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = escrow_config.bump,
+        seeds::program = datanexus_escrow::ID
+    )]
+    pub escrow_config: Account<'info, EscrowConfig>,
+
+    /// CHECK: Buyer stats PDA, initialized or updated by the escrow program
+    #[account(mut)]
+    pub buyer_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Listing provider
+    pub provider: UncheckedAccount<'info>,
+
+    /// CHECK: Provider stats PDA, initialized or updated by the escrow program
+    #[account(mut)]
+    pub provider_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Provider escrow index PDA, initialized by the escrow program
+    #[account(mut)]
+    pub provider_escrow_index: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol stats PDA, initialized or updated by the escrow program
+    #[account(mut)]
+    pub protocol_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow price quote PDA, validated by the escrow program
+    pub escrow_quote: UncheckedAccount<'info>,
+
+    /// CHECK: Provider registry profile, validated by the escrow program
+    pub provider_profile: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Platform authority, must be the escrow config admin
+    #[account(address = escrow_config.admin @ MarketplaceError::Unauthorized)]
+    pub platform: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = listing.mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = listing.mint,
+        token::authority = escrow,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = listing.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: Optional buyer compliance attestation, validated by the escrow program
+    pub buyer_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional provider compliance attestation, validated by the escrow program
+    pub provider_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional provider badge, validated by the escrow program
+    pub provider_badge: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Buyer blacklist PDA, validated by the escrow program
+    pub buyer_blacklist: UncheckedAccount<'info>,
+
+    /// CHECK: Provider blacklist PDA, validated by the escrow program
+    pub provider_blacklist: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, DatanexusEscrow>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum MarketplaceError {
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[msg("Invalid price")]
+    InvalidPrice,
+
+    #[msg("Listing is not active")]
+    ListingInactive,
+
+    #[msg("Listing is out of stock")]
+    OutOfStock,
+}
@@ -0,0 +1,411 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use datanexus_sigverify::SignatureError;
+
+declare_id!("CcaaRrViwqBZw9bKkrq2h4Kft2bAS8jN7HjnP9AXfmCe");
+
+/// Ed25519 签名校验原生程序
+pub use datanexus_sigverify::ED25519_PROGRAM_ID;
+
+/// 买家停止计量后，提供商提交剩余用量收据的结算期（秒），期满买家才能取回余额
+pub const SETTLEMENT_PERIOD: i64 = 24 * 60 * 60;
+
+/// DataNexus Metering Program
+///
+/// 按查询计费的 API 类数据产品：
+/// 1. 买家为某个提供商开通计量账户，约定每次查询的单价和收据签名密钥，并预存余额
+/// 2. 买家的 API 客户端用收据签名密钥对累计查询数签名，提供商凭最新收据 `draw` 扣款
+/// 3. 买家可随时停止计量（不再充值），结算期内提供商仍可提交停止前的收据，
+///    期满买家取回余额并关闭账户
+#[program]
+pub mod datanexus_metering {
+    use super::*;
+
+    /// 买家开通计量账户并创建余额金库
+    ///
+    /// `receipt_signer` 为买家签发用量收据的密钥（可以是 API 客户端的热钱包），
+    /// 收据内容为 `计量账户地址 || 开通时间（i64 小端）|| 累计查询数（u64 小端）`；
+    /// 开通时间区分同一买家和提供商先后开通的计量账户，关闭前的收据不能在重新开通后重放
+    pub fn open_meter(
+        ctx: Context<OpenMeter>,
+        price_per_query: u64,
+        receipt_signer: Pubkey,
+    ) -> Result<()> {
+        require!(price_per_query > 0, MeteringError::InvalidAmount);
+
+        let meter = &mut ctx.accounts.meter;
+        meter.buyer = ctx.accounts.buyer.key();
+        meter.provider = ctx.accounts.provider.key();
+        meter.mint = ctx.accounts.mint.key();
+        meter.receipt_signer = receipt_signer;
+        meter.price_per_query = price_per_query;
+        meter.consumed_queries = 0;
+        meter.total_paid = 0;
+        meter.opened_at = Clock::get()?.unix_timestamp;
+        meter.stopped_at = None;
+        meter.bump = ctx.bumps.meter;
+
+        msg!("Meter opened: {}", meter.key());
+        msg!("Provider: {}", meter.provider);
+        msg!("Price per query: {}", price_per_query);
+
+        Ok(())
+    }
+
+    /// 买家向计量余额充值（停止计量后不能再充值）
+    pub fn top_up(ctx: Context<TopUp>, amount: u64) -> Result<()> {
+        require!(amount > 0, MeteringError::InvalidAmount);
+        require!(
+            ctx.accounts.meter.stopped_at.is_none(),
+            MeteringError::MeterStopped
+        );
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.buyer_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+        msg!("Meter topped up: {}", ctx.accounts.meter.key());
+        msg!("Amount: {}", amount);
+
+        Ok(())
+    }
+
+    /// 买家停止计量，之后不能再充值，结算期满后可取回余额
+    pub fn stop_meter(ctx: Context<StopMeter>) -> Result<()> {
+        let meter = &mut ctx.accounts.meter;
+        require!(meter.stopped_at.is_none(), MeteringError::MeterStopped);
+
+        let now = Clock::get()?.unix_timestamp;
+        meter.stopped_at = Some(now);
+
+        emit!(MeterStopped {
+            meter: meter.key(),
+            buyer: meter.buyer,
+            provider: meter.provider,
+            settle_by: now.checked_add(SETTLEMENT_PERIOD).unwrap(),
+            timestamp: now,
+        });
+
+        msg!("Meter stopped: {}", meter.key());
+
+        Ok(())
+    }
+
+    /// 提供商凭买家签名的用量收据扣款
+    ///
+    /// 前一条指令必须是收据签名密钥对 `计量账户地址 || 开通时间 || cumulative_queries`
+    /// 的 Ed25519 签名；按新增查询数 × 单价从余额转给提供商，余额不足时整笔失败
+    pub fn draw(ctx: Context<Draw>, cumulative_queries: u64) -> Result<()> {
+        ctx.accounts.verify_receipt(cumulative_queries)?;
+
+        let meter = &ctx.accounts.meter;
+        require!(
+            cumulative_queries > meter.consumed_queries,
+            MeteringError::StaleReceipt
+        );
+        let queries = cumulative_queries - meter.consumed_queries;
+        let amount = queries.checked_mul(meter.price_per_query).unwrap();
+        require!(
+            ctx.accounts.vault.amount >= amount,
+            MeteringError::InsufficientBalance
+        );
+
+        let seeds = &[
+            b"meter",
+            meter.buyer.as_ref(),
+            meter.provider.as_ref(),
+            &[meter.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.provider_token_account.to_account_info(),
+            authority: meter.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            amount,
+        )?;
+
+        let meter = &mut ctx.accounts.meter;
+        meter.consumed_queries = cumulative_queries;
+        meter.total_paid = meter.total_paid.checked_add(amount).unwrap();
+
+        emit!(UsageDrawn {
+            meter: meter.key(),
+            provider: meter.provider,
+            queries,
+            cumulative_queries,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Usage drawn: {}", meter.key());
+        msg!("Queries: {}, amount: {}", queries, amount);
+
+        Ok(())
+    }
+
+    /// 结算期满后买家取回余额，关闭金库和计量账户（租金退还买家）
+    pub fn close_meter(ctx: Context<CloseMeter>) -> Result<()> {
+        let meter = &ctx.accounts.meter;
+        let stopped_at = meter.stopped_at.ok_or(MeteringError::SettlementOpen)?;
+        require!(
+            Clock::get()?.unix_timestamp >= stopped_at.checked_add(SETTLEMENT_PERIOD).unwrap(),
+            MeteringError::SettlementOpen
+        );
+
+        let seeds = &[
+            b"meter",
+            meter.buyer.as_ref(),
+            meter.provider.as_ref(),
+            &[meter.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        let remaining = ctx.accounts.vault.amount;
+        if remaining > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.buyer_token_account.to_account_info(),
+                authority: meter.to_account_info(),
+            };
+            token::transfer(
+                CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer),
+                remaining,
+            )?;
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.vault.to_account_info(),
+            destination: ctx.accounts.buyer.to_account_info(),
+            authority: meter.to_account_info(),
+        };
+        token::close_account(CpiContext::new_with_signer(
+            cpi_program,
+            cpi_accounts,
+            signer,
+        ))?;
+
+        msg!("Meter closed: {}", meter.key());
+        msg!("Returned to buyer: {}", remaining);
+
+        Ok(())
+    }
+}
+
+/// 买家对某个提供商的计量账户
+#[account]
+#[derive(InitSpace)]
+pub struct Meter {
+    pub buyer: Pubkey,           // 买家
+    pub provider: Pubkey,        // 提供商
+    pub mint: Pubkey,            // 计费代币
+    pub receipt_signer: Pubkey,  // 用量收据签名密钥
+    pub price_per_query: u64,    // 每次查询单价
+    pub consumed_queries: u64,   // 已结算的累计查询数
+    pub total_paid: u64,         // 累计付给提供商的金额
+    pub opened_at: i64,          // 开通时间
+    pub stopped_at: Option<i64>, // 停止计量时间
+    pub bump: u8,                // PDA bump
+}
+
+impl Meter {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 提供商凭收据扣款
+#[event]
+pub struct UsageDrawn {
+    pub meter: Pubkey,
+    pub provider: Pubkey,
+    pub queries: u64,
+    pub cumulative_queries: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// 买家停止计量
+#[event]
+pub struct MeterStopped {
+    pub meter: Pubkey,
+    pub buyer: Pubkey,
+    pub provider: Pubkey,
+    pub settle_by: i64,
+    pub timestamp: i64,
+}
+
+/// 开通计量账户的上下文
+#[derive(Accounts)]
+pub struct OpenMeter<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = Meter::LEN,
+        seeds = [b"meter", buyer.key().as_ref(), provider.key().as_ref()],
+        bump
+    )]
+    pub meter: Account<'info, Meter>,
+
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"meter_vault", meter.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = meter,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Provider wallet, only recorded
+    pub provider: UncheckedAccount<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 充值的上下文
+#[derive(Accounts)]
+pub struct TopUp<'info> {
+    #[account(
+        seeds = [b"meter", meter.buyer.as_ref(), meter.provider.as_ref()],
+        bump = meter.bump,
+        has_one = buyer @ MeteringError::Unauthorized
+    )]
+    pub meter: Account<'info, Meter>,
+
+    #[account(mut, seeds = [b"meter_vault", meter.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub buyer: Signer<'info>,
+
+    #[account(mut, token::mint = meter.mint, token::authority = buyer)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 停止计量的上下文
+#[derive(Accounts)]
+pub struct StopMeter<'info> {
+    #[account(
+        mut,
+        seeds = [b"meter", meter.buyer.as_ref(), meter.provider.as_ref()],
+        bump = meter.bump,
+        has_one = buyer @ MeteringError::Unauthorized
+    )]
+    pub meter: Account<'info, Meter>,
+
+    pub buyer: Signer<'info>,
+}
+
+/// 凭收据扣款的上下文
+#[derive(Accounts)]
+pub struct Draw<'info> {
+    #[account(
+        mut,
+        seeds = [b"meter", meter.buyer.as_ref(), meter.provider.as_ref()],
+        bump = meter.bump,
+        has_one = provider @ MeteringError::Unauthorized
+    )]
+    pub meter: Account<'info, Meter>,
+
+    #[account(mut, seeds = [b"meter_vault", meter.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub provider: Signer<'info>,
+
+    #[account(mut, token::mint = meter.mint)]
+    pub provider_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Instructions sysvar, holds the receipt signature verification
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Draw<'info> {
+    /// 校验前一条指令是收据签名密钥对 `计量账户地址 || 开通时间 || 累计查询数` 的 Ed25519 签名
+    fn verify_receipt(&self, cumulative_queries: u64) -> Result<()> {
+        let ix = datanexus_sigverify::preceding_instruction(&self.instructions_sysvar)?
+            .ok_or(MeteringError::MissingReceipt)?;
+
+        let mut expected = self.meter.key().to_bytes().to_vec();
+        expected.extend_from_slice(&self.meter.opened_at.to_le_bytes());
+        expected.extend_from_slice(&cumulative_queries.to_le_bytes());
+        datanexus_sigverify::verify_signature(&ix, &self.meter.receipt_signer, &expected).map_err(
+            |err| match err {
+                SignatureError::Missing => MeteringError::MissingReceipt,
+                SignatureError::Invalid => MeteringError::InvalidReceipt,
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+/// 关闭计量账户的上下文
+#[derive(Accounts)]
+pub struct CloseMeter<'info> {
+    #[account(
+        mut,
+        seeds = [b"meter", meter.buyer.as_ref(), meter.provider.as_ref()],
+        bump = meter.bump,
+        has_one = buyer @ MeteringError::Unauthorized,
+        close = buyer
+    )]
+    pub meter: Account<'info, Meter>,
+
+    #[account(mut, seeds = [b"meter_vault", meter.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut, token::mint = meter.mint, token::authority = buyer)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum MeteringError {
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("Meter has been stopped")]
+    MeterStopped,
+
+    #[msg("Draw requires a receipt Ed25519 signature right before this instruction")]
+    MissingReceipt,
+
+    #[msg("Usage receipt signature does not match the meter and receipt signer")]
+    InvalidReceipt,
+
+    #[msg("Receipt does not add any queries beyond those already drawn")]
+    StaleReceipt,
+
+    #[msg("Meter balance is insufficient for the receipt")]
+    InsufficientBalance,
+
+    #[msg("Meter must be stopped and the settlement period elapsed")]
+    SettlementOpen,
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+declare_id!("8LJWLgTCWNJu4iscneQFhKzi74pGwmENbhqDbVZPmj12");
+
+/// DataNexus Registry Program
+///
+/// 提供商注册表：
+/// 1. 提供商注册资料 PDA（名称哈希、数据类别、交付端点哈希、收款账户）
+/// 2. 提供商可以更新或停用资料
+/// 3. 托管程序可以要求提供商已注册才能创建托管
+#[program]
+pub mod datanexus_registry {
+    use super::*;
+
+    /// 注册提供商资料
+    pub fn register_provider(
+        ctx: Context<RegisterProvider>,
+        name_hash: [u8; 32],
+        categories: u64,
+        endpoints_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        let profile = &mut ctx.accounts.profile;
+        profile.authority = ctx.accounts.authority.key();
+        profile.name_hash = name_hash;
+        profile.categories = categories;
+        profile.endpoints_hash = endpoints_hash;
+        profile.payout_token_account = ctx.accounts.payout_token_account.key();
+        profile.active = true;
+        profile.registered_at = now;
+        profile.updated_at = now;
+        profile.bump = ctx.bumps.profile;
+
+        msg!("Provider registered: {}", profile.authority);
+        msg!("Profile: {}", profile.key());
+        msg!("Payout account: {}", profile.payout_token_account);
+
+        Ok(())
+    }
+
+    /// 更新提供商资料（仅提供商本人）
+    pub fn update_profile(
+        ctx: Context<UpdateProfile>,
+        name_hash: [u8; 32],
+        categories: u64,
+        endpoints_hash: [u8; 32],
+        active: bool,
+    ) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        profile.name_hash = name_hash;
+        profile.categories = categories;
+        profile.endpoints_hash = endpoints_hash;
+        profile.payout_token_account = ctx.accounts.payout_token_account.key();
+        profile.active = active;
+        profile.updated_at = Clock::get()?.unix_timestamp;
+
+        msg!("Provider profile updated: {}", profile.key());
+        msg!("Active: {}", active);
+
+        Ok(())
+    }
+}
+
+/// 提供商资料
+#[account]
+#[derive(InitSpace)]
+pub struct ProviderProfile {
+    pub authority: Pubkey,            // 提供商钱包
+    pub name_hash: [u8; 32],          // 名称哈希
+    pub categories: u64,              // 数据类别（位标志）
+    pub endpoints_hash: [u8; 32],     // 交付端点哈希
+    pub payout_token_account: Pubkey, // 收款代币账户
+    pub active: bool,                 // 是否启用
+    pub registered_at: i64,           // 注册时间
+    pub updated_at: i64,              // 更新时间
+    pub bump: u8,                     // PDA bump
+}
+
+impl ProviderProfile {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 注册提供商的上下文
+#[derive(Accounts)]
+pub struct RegisterProvider<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ProviderProfile::LEN,
+        seeds = [b"provider", authority.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, ProviderProfile>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(token::authority = authority)]
+    pub payout_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 更新资料的上下文
+#[derive(Accounts)]
+pub struct UpdateProfile<'info> {
+    #[account(
+        mut,
+        seeds = [b"provider", authority.key().as_ref()],
+        bump = profile.bump,
+        has_one = authority @ RegistryError::Unauthorized
+    )]
+    pub profile: Account<'info, ProviderProfile>,
+
+    pub authority: Signer<'info>,
+
+    #[account(token::authority = authority)]
+    pub payout_token_account: Account<'info, TokenAccount>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum RegistryError {
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+}
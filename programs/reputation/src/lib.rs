@@ -0,0 +1,245 @@
+use anchor_lang::prelude::*;
+use datanexus_escrow::{Escrow, EscrowStatus};
+
+declare_id!("GoFyy3FG4xZbSfKwVvbx29Pw6D9MFdXUmBofBo37x7NL");
+
+/// 评分下限
+pub const MIN_SCORE: u8 = 1;
+
+/// 评分上限
+pub const MAX_SCORE: u8 = 5;
+
+/// DataNexus Reputation Program
+///
+/// 链上提供商信誉：
+/// 1. 只有已完成托管的买家可以给提供商评分（直接调用，或在托管 `confirm_and_release` 时经 CPI）
+/// 2. 每个托管只能评分一次
+/// 3. 按提供商累计总分和评分次数，市场可据此无需信任地排序提供商
+/// 4. 买家可以为已评分的托管留下评论哈希，提供商可以回复
+#[program]
+pub mod datanexus_reputation {
+    use super::*;
+
+    /// 买家给提供商评分
+    ///
+    /// 托管必须处于 Completed 状态，且调用者必须是该托管的买家
+    pub fn rate_provider(ctx: Context<RateProvider>, score: u8) -> Result<()> {
+        require!(
+            (MIN_SCORE..=MAX_SCORE).contains(&score),
+            ReputationError::InvalidScore
+        );
+
+        let escrow_key = ctx.accounts.escrow.key();
+        let escrow = ctx.accounts.escrow.load()?;
+        let now = Clock::get()?.unix_timestamp;
+
+        // 记录本次评分（每个托管唯一）
+        let rating = &mut ctx.accounts.rating;
+        rating.escrow = escrow_key;
+        rating.buyer = escrow.buyer;
+        rating.provider = escrow.provider;
+        rating.score = score;
+        rating.rated_at = now;
+        rating.bump = ctx.bumps.rating;
+
+        // 累计提供商信誉
+        let reputation = &mut ctx.accounts.provider_reputation;
+        if reputation.provider == Pubkey::default() {
+            reputation.provider = escrow.provider;
+            reputation.bump = ctx.bumps.provider_reputation;
+        }
+        reputation.total_score = reputation.total_score.checked_add(score as u64).unwrap();
+        reputation.rating_count = reputation.rating_count.checked_add(1).unwrap();
+        reputation.last_rated_at = now;
+
+        msg!("Provider rated: {}", escrow.provider);
+        msg!("Escrow: {}", escrow_key);
+        msg!("Score: {}", score);
+        msg!(
+            "Total: {} over {} ratings",
+            reputation.total_score,
+            reputation.rating_count
+        );
+
+        Ok(())
+    }
+
+    /// 买家为已评分的托管提交评价（每个托管一次）
+    ///
+    /// 评价只附带评论，评分沿用该托管唯一的 `Rating`
+    pub fn submit_review(ctx: Context<SubmitReview>, comment_hash: [u8; 32]) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let escrow = ctx.accounts.escrow.load()?;
+
+        let review = &mut ctx.accounts.review;
+        review.escrow = escrow_key;
+        review.reviewer = escrow.buyer;
+        review.provider = escrow.provider;
+        review.comment_hash = comment_hash;
+        review.reply_hash = None;
+        review.created_at = Clock::get()?.unix_timestamp;
+        review.replied_at = None;
+        review.bump = ctx.bumps.review;
+
+        msg!("Review submitted: {}", review.key());
+        msg!("Escrow: {}", escrow_key);
+        msg!("Rating: {}", ctx.accounts.rating.score);
+
+        Ok(())
+    }
+
+    /// 提供商回复评价（仅一次）
+    pub fn reply_to_review(ctx: Context<ReplyToReview>, reply_hash: [u8; 32]) -> Result<()> {
+        let review = &mut ctx.accounts.review;
+
+        require!(review.reply_hash.is_none(), ReputationError::AlreadyReplied);
+
+        review.reply_hash = Some(reply_hash);
+        review.replied_at = Some(Clock::get()?.unix_timestamp);
+
+        msg!("Provider replied to review: {}", review.key());
+
+        Ok(())
+    }
+}
+
+/// 单次评分记录
+#[account]
+#[derive(InitSpace)]
+pub struct Rating {
+    pub escrow: Pubkey,          // 被评分的托管
+    pub buyer: Pubkey,           // 评分买家
+    pub provider: Pubkey,        // 被评分提供商
+    pub score: u8,               // 评分（1-5）
+    pub rated_at: i64,           // 评分时间
+    pub bump: u8,                // PDA bump
+}
+
+impl Rating {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 提供商累计信誉
+#[account]
+#[derive(InitSpace)]
+pub struct ProviderReputation {
+    pub provider: Pubkey,        // 提供商
+    pub total_score: u64,        // 累计总分
+    pub rating_count: u64,       // 评分次数
+    pub last_rated_at: i64,      // 最近评分时间
+    pub bump: u8,                // PDA bump
+}
+
+impl ProviderReputation {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 托管评价
+#[account]
+#[derive(InitSpace)]
+pub struct Review {
+    pub escrow: Pubkey,              // 被评价的托管
+    pub reviewer: Pubkey,            // 评价人（托管买家）
+    pub provider: Pubkey,            // 被评价提供商
+    pub comment_hash: [u8; 32],      // 评论内容哈希
+    pub reply_hash: Option<[u8; 32]>, // 提供商回复哈希
+    pub created_at: i64,             // 评价时间
+    pub replied_at: Option<i64>,     // 回复时间
+    pub bump: u8,                    // PDA bump
+}
+
+impl Review {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 评分的上下文
+#[derive(Accounts)]
+pub struct RateProvider<'info> {
+    #[account(
+        constraint = escrow.load()?.status() == EscrowStatus::Completed
+            @ ReputationError::EscrowNotCompleted,
+        constraint = escrow.load()?.buyer == buyer.key() @ ReputationError::Unauthorized
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = Rating::LEN,
+        seeds = [b"rating", escrow.key().as_ref()],
+        bump
+    )]
+    pub rating: Account<'info, Rating>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = ProviderReputation::LEN,
+        seeds = [b"reputation", escrow.load()?.provider.as_ref()],
+        bump
+    )]
+    pub provider_reputation: Account<'info, ProviderReputation>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 提交评价的上下文
+#[derive(Accounts)]
+pub struct SubmitReview<'info> {
+    #[account(
+        constraint = escrow.load()?.status() == EscrowStatus::Completed
+            @ ReputationError::EscrowNotCompleted,
+        constraint = escrow.load()?.buyer == reviewer.key() @ ReputationError::Unauthorized
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(seeds = [b"rating", escrow.key().as_ref()], bump = rating.bump)]
+    pub rating: Account<'info, Rating>,
+
+    #[account(
+        init,
+        payer = reviewer,
+        space = Review::LEN,
+        seeds = [b"review", escrow.key().as_ref()],
+        bump
+    )]
+    pub review: Account<'info, Review>,
+
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 回复评价的上下文
+#[derive(Accounts)]
+pub struct ReplyToReview<'info> {
+    #[account(
+        mut,
+        seeds = [b"review", review.escrow.as_ref()],
+        bump = review.bump,
+        has_one = provider @ ReputationError::Unauthorized
+    )]
+    pub review: Account<'info, Review>,
+
+    pub provider: Signer<'info>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum ReputationError {
+    #[msg("Unauthorized: Only the escrow's buyer or provider can perform this action")]
+    Unauthorized,
+
+    #[msg("Escrow is not completed")]
+    EscrowNotCompleted,
+
+    #[msg("Score must be between 1 and 5")]
+    InvalidScore,
+
+    #[msg("Provider has already replied to this review")]
+    AlreadyReplied,
+}
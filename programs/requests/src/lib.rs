@@ -0,0 +1,1264 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+declare_id!("9uH88gRVAZyeJLtUyWHRNvaPduhBBPGCpVVNRqHmxtWk");
+
+/// request_id / proposal_id 最大长度（字节）
+pub const MAX_ID_LEN: usize = 32;
+
+/// 托管程序 ID，竞价授标时 CPI 创建托管
+pub const ESCROW_PROGRAM_ID: Pubkey = pubkey!("gxDTeSCzk9mqiokrmTb1uNbWCjQ1rj2hsj5N65K9698");
+
+/// 托管程序 `create_escrow` 指令的 Anchor 鉴别码
+///
+/// 托管程序依赖本程序的账户类型，本程序不能反过来依赖托管程序，CPI 指令在此手工构造，
+/// 鉴别码和账户顺序由 test-fixtures 中的测试与托管程序比对
+pub const CREATE_ESCROW_DISCRIMINATOR: [u8; 8] = [253, 215, 165, 116, 36, 108, 68, 80];
+
+/// 托管程序 `fund_escrow` 指令的 Anchor 鉴别码，反向竞价授标时 CPI 充值
+pub const FUND_ESCROW_DISCRIMINATOR: [u8; 8] = [155, 18, 218, 141, 182, 213, 69, 201];
+
+/// 竞价截止后买家选定中标者并授标的期限（秒），期满未授标时所有保证金都可取回
+pub const AWARD_PERIOD: i64 = 7 * 24 * 60 * 60;
+
+/// 需求限制标志：数据类别（低 16 位）
+pub const CATEGORY_PERSONAL: u32 = 1 << 0;
+pub const CATEGORY_HEALTH: u32 = 1 << 1;
+pub const CATEGORY_FINANCIAL: u32 = 1 << 2;
+pub const CATEGORY_BIOMETRIC: u32 = 1 << 3;
+pub const CATEGORY_LOCATION: u32 = 1 << 4;
+
+/// 需求限制标志：受限司法辖区（高 16 位）
+pub const JURISDICTION_EU: u32 = 1 << 16;
+pub const JURISDICTION_US: u32 = 1 << 17;
+pub const JURISDICTION_CN: u32 = 1 << 18;
+pub const JURISDICTION_SANCTIONED: u32 = 1 << 19;
+
+/// DataNexus Requests Program
+///
+/// 链上数据需求与提案：
+/// 1. 买家发布需求 PDA（预算、需求规格哈希、截止时间）
+/// 2. 提供商针对需求提交提案 PDA（报价、条款哈希）
+/// 3. 买家选定提案，托管程序创建托管时校验提案属于该需求和该提供商且已被选定
+/// 4. 竞价模式：买家为需求开启竞价，提供商缴纳保证金出价（即提交提案），
+///    截止后买家（或按最低价自动规则）选定中标者，授标时 CPI 托管程序按中标报价创建托管
+/// 5. 反向竞价：买家开启竞价时锁定全部预算，最低报价中标，授标时按中标报价创建并充值托管，
+///    差额退回买家
+#[program]
+pub mod datanexus_requests {
+    use super::*;
+
+    /// 买家发布需求
+    ///
+    /// `restrictions` 为数据类别和受限司法辖区标志（`CATEGORY_*` / `JURISDICTION_*`），
+    /// 托管程序创建托管时记录到托管上
+    pub fn create_request(
+        ctx: Context<CreateRequest>,
+        request_id: String,
+        budget: u64,
+        spec_hash: [u8; 32],
+        deadline: i64,
+        restrictions: u32,
+    ) -> Result<()> {
+        require!(request_id.len() <= MAX_ID_LEN, RequestsError::IdTooLong);
+        require!(budget > 0, RequestsError::InvalidAmount);
+
+        let request = &mut ctx.accounts.request;
+        request.buyer = ctx.accounts.buyer.key();
+        request.request_id = request_id;
+        request.budget = budget;
+        request.spec_hash = spec_hash;
+        request.deadline = deadline;
+        request.status = RequestStatus::Open;
+        request.proposal_count = 0;
+        request.created_at = Clock::get()?.unix_timestamp;
+        request.bump = ctx.bumps.request;
+        request.restrictions = restrictions;
+
+        msg!("Request created: {}", request.key());
+        msg!("Request ID: {}", request.request_id);
+        msg!("Budget: {} USDC", budget);
+        msg!("Restrictions: {:#010x}", restrictions);
+
+        Ok(())
+    }
+
+    /// 买家关闭需求，不再接受新提案
+    pub fn close_request(ctx: Context<CloseRequest>) -> Result<()> {
+        let request = &mut ctx.accounts.request;
+
+        require!(
+            request.status == RequestStatus::Open,
+            RequestsError::InvalidStatus
+        );
+
+        request.status = RequestStatus::Closed;
+
+        msg!("Request closed: {}", request.key());
+
+        Ok(())
+    }
+
+    /// 提供商提交提案
+    pub fn submit_proposal(
+        ctx: Context<SubmitProposal>,
+        proposal_id: String,
+        price: u64,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(proposal_id.len() <= MAX_ID_LEN, RequestsError::IdTooLong);
+        require!(price > 0, RequestsError::InvalidAmount);
+
+        let request = &mut ctx.accounts.request;
+        require!(
+            request.status == RequestStatus::Open,
+            RequestsError::InvalidStatus
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= request.deadline, RequestsError::DeadlinePassed);
+
+        request.proposal_count = request.proposal_count.checked_add(1).unwrap();
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.request = request.key();
+        proposal.provider = ctx.accounts.provider.key();
+        proposal.proposal_id = proposal_id;
+        proposal.price = price;
+        proposal.terms_hash = terms_hash;
+        proposal.status = ProposalStatus::Submitted;
+        proposal.created_at = now;
+        proposal.bump = ctx.bumps.proposal;
+
+        msg!("Proposal submitted: {}", proposal.key());
+        msg!("Request: {}", proposal.request);
+        msg!("Price: {} USDC", price);
+
+        Ok(())
+    }
+
+    /// 提供商撤回提案（缴纳了竞价保证金的出价不能撤回）
+    ///
+    /// 已被选定但尚未创建托管的提案也可以撤回
+    pub fn withdraw_proposal(ctx: Context<WithdrawProposal>) -> Result<()> {
+        require!(
+            ctx.accounts.bid.owner != &crate::ID,
+            RequestsError::BidLocked
+        );
+
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(
+            proposal.status == ProposalStatus::Submitted
+                || proposal.status == ProposalStatus::Selected,
+            RequestsError::InvalidStatus
+        );
+
+        proposal.status = ProposalStatus::Withdrawn;
+
+        msg!("Proposal withdrawn: {}", proposal.key());
+
+        Ok(())
+    }
+
+    /// 买家选定提案，托管程序只接受已选定的提案创建托管
+    ///
+    /// 竞价出价须通过 `select_bid` 选定
+    pub fn select_proposal(ctx: Context<SelectProposal>) -> Result<()> {
+        require!(
+            ctx.accounts.bid.owner != &crate::ID,
+            RequestsError::BidLocked
+        );
+        require!(
+            ctx.accounts.request.status == RequestStatus::Open,
+            RequestsError::InvalidStatus
+        );
+
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            proposal.status == ProposalStatus::Submitted,
+            RequestsError::InvalidStatus
+        );
+
+        proposal.status = ProposalStatus::Selected;
+
+        msg!("Proposal selected: {}", proposal.key());
+        msg!("Provider: {}", proposal.provider);
+
+        Ok(())
+    }
+
+    /// 买家为开放中的需求开启竞价
+    ///
+    /// 竞价期内提供商须缴纳 `bid_bond` 保证金才能出价；`auto_select` 为真时，
+    /// 截止后任何人都可以按最低报价选定中标者，否则只能由买家选定
+    pub fn open_auction(
+        ctx: Context<OpenAuction>,
+        bid_bond: u64,
+        closes_at: i64,
+        auto_select: bool,
+    ) -> Result<()> {
+        let bump = ctx.bumps.auction;
+        ctx.accounts.open(bump, bid_bond, closes_at, auto_select)
+    }
+
+    /// 买家开启反向竞价：把需求预算全额锁入竞价金库，截止后最低报价中标
+    ///
+    /// 反向竞价总是自动选择（任何人都可以选定最低报价），授标须用 `award_reverse_auction`
+    /// 按中标报价为托管充值并退回差额；无人出价或期满未授标时买家用 `withdraw_budget` 取回预算
+    pub fn open_reverse_auction(
+        ctx: Context<OpenReverseAuction>,
+        bid_bond: u64,
+        closes_at: i64,
+    ) -> Result<()> {
+        let bump = ctx.bumps.open.auction;
+        ctx.accounts.open.open(bump, bid_bond, closes_at, true)?;
+
+        let budget = ctx.accounts.open.request.budget;
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.buyer_token_account.to_account_info(),
+            to: ctx.accounts.open.vault.to_account_info(),
+            authority: ctx.accounts.open.buyer.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.open.token_program.to_account_info(), cpi_accounts),
+            budget,
+        )?;
+        ctx.accounts.open.auction.locked_budget = budget;
+
+        msg!("Budget locked: {} USDC", budget);
+
+        Ok(())
+    }
+
+    /// 提供商在竞价期内出价：提交提案并缴纳保证金
+    ///
+    /// 报价不能超过需求预算；出价后不能撤回，保证金在选出中标者后
+    /// （中标者在授标后）通过 `refund_bid` 取回
+    pub fn submit_bid(
+        ctx: Context<SubmitBid>,
+        proposal_id: String,
+        price: u64,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(proposal_id.len() <= MAX_ID_LEN, RequestsError::IdTooLong);
+        require!(price > 0, RequestsError::InvalidAmount);
+
+        let request = &mut ctx.accounts.request;
+        require!(
+            request.status == RequestStatus::Open,
+            RequestsError::InvalidStatus
+        );
+        require!(price <= request.budget, RequestsError::InvalidAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        let auction = &mut ctx.accounts.auction;
+        require!(now < auction.closes_at, RequestsError::AuctionClosed);
+
+        request.proposal_count = request.proposal_count.checked_add(1).unwrap();
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.request = request.key();
+        proposal.provider = ctx.accounts.provider.key();
+        proposal.proposal_id = proposal_id;
+        proposal.price = price;
+        proposal.terms_hash = terms_hash;
+        proposal.status = ProposalStatus::Submitted;
+        proposal.created_at = now;
+        proposal.bump = ctx.bumps.proposal;
+
+        // 同价时先出价者优先
+        if auction.best_proposal.is_none() || price < auction.best_price {
+            auction.best_proposal = Some(proposal.key());
+            auction.best_price = price;
+        }
+
+        let bid = &mut ctx.accounts.bid;
+        bid.auction = auction.key();
+        bid.proposal = proposal.key();
+        bid.provider = proposal.provider;
+        bid.bond = auction.bid_bond;
+        bid.refunded = false;
+        bid.bump = ctx.bumps.bid;
+
+        if bid.bond > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.provider_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.provider.to_account_info(),
+            };
+            token::transfer(
+                CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+                bid.bond,
+            )?;
+        }
+
+        msg!("Bid submitted: {}", proposal.key());
+        msg!("Price: {} USDC, bond: {}", price, bid.bond);
+
+        Ok(())
+    }
+
+    /// 竞价截止后选定中标者，中标提案标记为已选定
+    ///
+    /// 买家可以选定任一有效出价；开启自动选择时任何人都可以选定最低报价
+    pub fn select_bid(ctx: Context<SelectBid>) -> Result<()> {
+        let auction = &ctx.accounts.auction;
+        let proposal = &ctx.accounts.proposal;
+
+        require!(
+            Clock::get()?.unix_timestamp >= auction.closes_at,
+            RequestsError::AuctionOpen
+        );
+        require!(auction.winner.is_none(), RequestsError::InvalidStatus);
+        require!(
+            proposal.status == ProposalStatus::Submitted,
+            RequestsError::InvalidStatus
+        );
+        if ctx.accounts.authority.key() != ctx.accounts.request.buyer {
+            require!(
+                auction.auto_select && auction.best_proposal == Some(proposal.key()),
+                RequestsError::Unauthorized
+            );
+        }
+
+        let auction = &mut ctx.accounts.auction;
+        auction.winner = Some(proposal.key());
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.status = ProposalStatus::Selected;
+
+        msg!("Bid selected: {}", proposal.key());
+        msg!("Provider: {}", proposal.provider);
+
+        Ok(())
+    }
+
+    /// 买家授标：CPI 托管程序按中标报价创建托管（Created 状态，之后由买家充值），并关闭需求
+    ///
+    /// 托管程序所需的账户按 `create_escrow` 的顺序传入，优惠券和访问凭证不适用于竞价托管
+    pub fn award_auction(ctx: Context<AwardAuction>) -> Result<()> {
+        require!(
+            ctx.accounts.auction.locked_budget == 0,
+            RequestsError::BudgetLocked
+        );
+
+        ctx.accounts.award()
+    }
+
+    /// 买家为反向竞价授标：按中标报价创建托管，并用锁定的预算为托管充值，差额退回买家
+    ///
+    /// 锁定的预算先全额退到买家的代币账户，再由买家签名 CPI 托管程序 `fund_escrow`，
+    /// 托管代币账户须在此之前创建
+    pub fn award_reverse_auction(ctx: Context<AwardReverseAuction>) -> Result<()> {
+        let locked_budget = ctx.accounts.award.auction.locked_budget;
+        require!(locked_budget > 0, RequestsError::InvalidAuction);
+
+        ctx.accounts.award.award()?;
+
+        let auction = &ctx.accounts.award.auction;
+        let seeds = &[b"auction", auction.request.as_ref(), &[auction.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: auction.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer,
+            ),
+            locked_budget,
+        )?;
+        ctx.accounts.award.auction.locked_budget = 0;
+
+        ctx.accounts.fund_escrow()?;
+
+        let price = ctx.accounts.award.proposal.price;
+        msg!("Escrow funded: {} USDC", price);
+        msg!("Budget refunded: {} USDC", locked_budget - price);
+
+        Ok(())
+    }
+
+    /// 买家取回反向竞价锁定的预算
+    ///
+    /// 仅在尚未授标且竞价截止后无人出价，或截止后 `AWARD_PERIOD` 内仍未授标时可取回
+    pub fn withdraw_budget(ctx: Context<WithdrawBudget>) -> Result<()> {
+        let auction = &ctx.accounts.auction;
+        let locked_budget = auction.locked_budget;
+        require!(locked_budget > 0, RequestsError::InvalidStatus);
+        require!(auction.escrow.is_none(), RequestsError::InvalidStatus);
+
+        let now = Clock::get()?.unix_timestamp;
+        let withdrawable = (now >= auction.closes_at && auction.best_proposal.is_none())
+            || now >= auction.closes_at.checked_add(AWARD_PERIOD).unwrap();
+        require!(withdrawable, RequestsError::BudgetLocked);
+
+        let seeds = &[b"auction", auction.request.as_ref(), &[auction.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: auction.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer,
+            ),
+            locked_budget,
+        )?;
+
+        let auction = &mut ctx.accounts.auction;
+        auction.locked_budget = 0;
+
+        msg!("Budget withdrawn: {}", auction.key());
+        msg!("Amount: {} USDC", locked_budget);
+
+        Ok(())
+    }
+
+    /// 退还竞价保证金（任何人可调用，退到提供商的代币账户）
+    ///
+    /// 未中标的出价在选出中标者后即可退还，中标出价在授标后退还；
+    /// 截止后 `AWARD_PERIOD` 内仍未授标时所有保证金都可退还
+    pub fn refund_bid(ctx: Context<RefundBid>) -> Result<()> {
+        let auction = &ctx.accounts.auction;
+        let bid = &ctx.accounts.bid;
+        require!(!bid.refunded, RequestsError::InvalidStatus);
+
+        let now = Clock::get()?.unix_timestamp;
+        let refundable = auction.escrow.is_some()
+            || auction.winner.is_some_and(|winner| winner != bid.proposal)
+            || now >= auction.closes_at.checked_add(AWARD_PERIOD).unwrap();
+        require!(refundable, RequestsError::BondLocked);
+
+        let seeds = &[b"auction", auction.request.as_ref(), &[auction.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.provider_token_account.to_account_info(),
+            authority: auction.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer,
+            ),
+            bid.bond,
+        )?;
+
+        let bid = &mut ctx.accounts.bid;
+        bid.refunded = true;
+
+        msg!("Bid bond refunded: {}", bid.proposal);
+        msg!("Amount: {}", bid.bond);
+
+        Ok(())
+    }
+}
+
+/// 数据需求
+#[account]
+#[derive(InitSpace)]
+pub struct Request {
+    pub buyer: Pubkey,           // 买家
+    #[max_len(MAX_ID_LEN)]
+    pub request_id: String,      // 需求 ID（与托管 PDA 种子一致）
+    pub budget: u64,             // 预算（USDC，6 位小数）
+    pub spec_hash: [u8; 32],     // 需求规格哈希
+    pub deadline: i64,           // 提案截止时间
+    pub status: RequestStatus,   // 状态
+    pub proposal_count: u32,     // 提案数量
+    pub created_at: i64,         // 创建时间
+    pub bump: u8,                // PDA bump
+    pub restrictions: u32,       // 数据类别和受限司法辖区标志
+}
+
+impl Request {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 提供商提案
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub request: Pubkey,         // 所属需求
+    pub provider: Pubkey,        // 提供商
+    #[max_len(MAX_ID_LEN)]
+    pub proposal_id: String,     // 提案 ID
+    pub price: u64,              // 报价（USDC，6 位小数）
+    pub terms_hash: [u8; 32],    // 条款哈希
+    pub status: ProposalStatus,  // 状态
+    pub created_at: i64,         // 提交时间
+    pub bump: u8,                // PDA bump
+}
+
+impl Proposal {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 需求的竞价设置与进度，每个需求一条
+#[account]
+#[derive(InitSpace)]
+pub struct Auction {
+    pub request: Pubkey,                // 所属需求
+    pub mint: Pubkey,                   // 保证金币种
+    pub bid_bond: u64,                  // 每个出价须缴纳的保证金
+    pub closes_at: i64,                 // 竞价截止时间
+    pub auto_select: bool,              // 截止后任何人可按最低报价选定中标者
+    pub best_proposal: Option<Pubkey>,  // 当前最低报价的提案
+    pub best_price: u64,                // 当前最低报价
+    pub winner: Option<Pubkey>,         // 中标提案
+    pub escrow: Option<Pubkey>,         // 授标后创建的托管
+    pub bump: u8,                       // PDA bump
+    pub locked_budget: u64,             // 反向竞价锁定的预算（0 表示未锁定）
+}
+
+impl Auction {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 出价保证金，每个提案一条
+#[account]
+#[derive(InitSpace)]
+pub struct BidBond {
+    pub auction: Pubkey,         // 所属竞价
+    pub proposal: Pubkey,        // 出价提案
+    pub provider: Pubkey,        // 提供商
+    pub bond: u64,               // 保证金金额
+    pub refunded: bool,          // 是否已退还
+    pub bump: u8,                // PDA bump
+}
+
+impl BidBond {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 需求状态
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum RequestStatus {
+    Open,       // 接受提案
+    Closed,     // 已关闭
+}
+
+/// 提案状态
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum ProposalStatus {
+    Submitted,  // 已提交
+    Withdrawn,  // 已撤回
+    Selected,   // 买家已选定，可据此创建托管
+}
+
+/// 发布需求的上下文
+#[derive(Accounts)]
+#[instruction(request_id: String)]
+pub struct CreateRequest<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = Request::LEN,
+        seeds = [b"request", buyer.key().as_ref(), request_id.as_bytes()],
+        bump
+    )]
+    pub request: Account<'info, Request>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 关闭需求的上下文
+#[derive(Accounts)]
+pub struct CloseRequest<'info> {
+    #[account(
+        mut,
+        seeds = [b"request", buyer.key().as_ref(), request.request_id.as_bytes()],
+        bump = request.bump,
+        has_one = buyer @ RequestsError::Unauthorized
+    )]
+    pub request: Account<'info, Request>,
+
+    pub buyer: Signer<'info>,
+}
+
+/// 提交提案的上下文
+#[derive(Accounts)]
+pub struct SubmitProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"request", request.buyer.as_ref(), request.request_id.as_bytes()],
+        bump = request.bump
+    )]
+    pub request: Account<'info, Request>,
+
+    #[account(
+        init,
+        payer = provider,
+        space = Proposal::LEN,
+        seeds = [b"proposal", request.key().as_ref(), provider.key().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 撤回提案的上下文
+#[derive(Accounts)]
+pub struct WithdrawProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.request.as_ref(), provider.key().as_ref()],
+        bump = proposal.bump,
+        has_one = provider @ RequestsError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub provider: Signer<'info>,
+
+    /// CHECK: Bid bond PDA of this proposal; bonded bids cannot be withdrawn
+    #[account(seeds = [b"bid", proposal.key().as_ref()], bump)]
+    pub bid: UncheckedAccount<'info>,
+}
+
+/// 选定提案的上下文
+#[derive(Accounts)]
+pub struct SelectProposal<'info> {
+    #[account(
+        seeds = [b"request", buyer.key().as_ref(), request.request_id.as_bytes()],
+        bump = request.bump,
+        has_one = buyer @ RequestsError::Unauthorized
+    )]
+    pub request: Account<'info, Request>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", request.key().as_ref(), proposal.provider.as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Bid bond PDA of this proposal; bonded bids are selected through the auction
+    #[account(seeds = [b"bid", proposal.key().as_ref()], bump)]
+    pub bid: UncheckedAccount<'info>,
+}
+
+/// 开启竞价的上下文
+#[derive(Accounts)]
+pub struct OpenAuction<'info> {
+    #[account(
+        seeds = [b"request", buyer.key().as_ref(), request.request_id.as_bytes()],
+        bump = request.bump,
+        has_one = buyer @ RequestsError::Unauthorized
+    )]
+    pub request: Account<'info, Request>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = Auction::LEN,
+        seeds = [b"auction", request.key().as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"auction_vault", auction.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = auction,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenAuction<'info> {
+    /// 校验需求和截止时间并初始化竞价，`open_auction` 与 `open_reverse_auction` 共用
+    fn open(&mut self, bump: u8, bid_bond: u64, closes_at: i64, auto_select: bool) -> Result<()> {
+        let request = &self.request;
+        require!(
+            request.status == RequestStatus::Open,
+            RequestsError::InvalidStatus
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            closes_at > now && closes_at <= request.deadline,
+            RequestsError::InvalidAuction
+        );
+
+        let auction = &mut self.auction;
+        auction.request = request.key();
+        auction.mint = self.mint.key();
+        auction.bid_bond = bid_bond;
+        auction.closes_at = closes_at;
+        auction.auto_select = auto_select;
+        auction.best_proposal = None;
+        auction.best_price = 0;
+        auction.winner = None;
+        auction.escrow = None;
+        auction.bump = bump;
+        auction.locked_budget = 0;
+
+        msg!("Auction opened: {}", auction.key());
+        msg!("Bid bond: {}, closes at: {}", bid_bond, closes_at);
+
+        Ok(())
+    }
+}
+
+/// 开启反向竞价的上下文，预算与保证金同币种，锁入同一个竞价金库
+#[derive(Accounts)]
+pub struct OpenReverseAuction<'info> {
+    pub open: OpenAuction<'info>,
+
+    #[account(mut, token::mint = open.mint, token::authority = open.buyer)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+}
+
+/// 出价的上下文
+#[derive(Accounts)]
+pub struct SubmitBid<'info> {
+    #[account(
+        mut,
+        seeds = [b"request", request.buyer.as_ref(), request.request_id.as_bytes()],
+        bump = request.bump
+    )]
+    pub request: Account<'info, Request>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", request.key().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        init,
+        payer = provider,
+        space = Proposal::LEN,
+        seeds = [b"proposal", request.key().as_ref(), provider.key().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = provider,
+        space = BidBond::LEN,
+        seeds = [b"bid", proposal.key().as_ref()],
+        bump
+    )]
+    pub bid: Account<'info, BidBond>,
+
+    #[account(mut, seeds = [b"auction_vault", auction.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(mut, token::mint = auction.mint, token::authority = provider)]
+    pub provider_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 选定中标者的上下文
+#[derive(Accounts)]
+pub struct SelectBid<'info> {
+    #[account(
+        seeds = [b"request", request.buyer.as_ref(), request.request_id.as_bytes()],
+        bump = request.bump
+    )]
+    pub request: Account<'info, Request>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", request.key().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", request.key().as_ref(), proposal.provider.as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// 买家，或开启自动选择时的任何人
+    pub authority: Signer<'info>,
+}
+
+/// 授标的上下文，托管程序的账户按 `create_escrow` 的顺序排列
+#[derive(Accounts)]
+pub struct AwardAuction<'info> {
+    #[account(
+        mut,
+        seeds = [b"request", buyer.key().as_ref(), request.request_id.as_bytes()],
+        bump = request.bump,
+        has_one = buyer @ RequestsError::Unauthorized
+    )]
+    pub request: Account<'info, Request>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", request.key().as_ref()],
+        bump = auction.bump,
+        constraint = auction.winner == Some(proposal.key()) @ RequestsError::InvalidStatus
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        seeds = [b"proposal", request.key().as_ref(), proposal.provider.as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: Escrow PDA, created and validated by the escrow program
+    #[account(mut)]
+    pub escrow: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Escrow program config, validated by the escrow program
+    pub escrow_config: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer stats, validated by the escrow program
+    #[account(mut)]
+    pub buyer_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Winning provider
+    #[account(address = proposal.provider)]
+    pub provider: UncheckedAccount<'info>,
+
+    /// CHECK: Provider stats, validated by the escrow program
+    #[account(mut)]
+    pub provider_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Provider escrow index, validated by the escrow program
+    #[account(mut)]
+    pub provider_escrow_index: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol stats, validated by the escrow program
+    #[account(mut)]
+    pub protocol_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Optional provider registry profile, validated by the escrow program
+    pub provider_profile: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional escrow restriction record, required for restricted requests
+    #[account(mut)]
+    pub restriction: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Platform address, must be the escrow config admin (checked by the escrow program)
+    pub platform: UncheckedAccount<'info>,
+
+    /// CHECK: Optional buyer compliance attestation, validated by the escrow program
+    pub buyer_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional provider compliance attestation, validated by the escrow program
+    pub provider_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional provider badge, validated by the escrow program
+    pub provider_badge: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Buyer blacklist PDA, validated by the escrow program
+    pub buyer_blacklist: UncheckedAccount<'info>,
+
+    /// CHECK: Provider blacklist PDA, validated by the escrow program
+    pub provider_blacklist: UncheckedAccount<'info>,
+
+    /// 竞价币种，记录为托管铸币
+    #[account(address = auction.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: DataNexus escrow program
+    #[account(address = ESCROW_PROGRAM_ID)]
+    pub escrow_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AwardAuction<'info> {
+    /// 校验需求、创建托管并关闭需求，`award_auction` 与 `award_reverse_auction` 共用
+    fn award(&mut self) -> Result<()> {
+        require!(
+            self.request.status == RequestStatus::Open,
+            RequestsError::InvalidStatus
+        );
+        require!(self.auction.escrow.is_none(), RequestsError::InvalidStatus);
+
+        self.create_escrow()?;
+
+        self.auction.escrow = Some(self.escrow.key());
+        self.request.status = RequestStatus::Closed;
+
+        msg!("Auction awarded: {}", self.auction.key());
+        msg!("Escrow: {}", self.escrow.key());
+        msg!("Price: {} USDC", self.proposal.price);
+
+        Ok(())
+    }
+
+    /// CPI 托管程序 `create_escrow`，金额为中标报价，同时传入需求和中标提案供托管程序校验
+    fn create_escrow(&self) -> Result<()> {
+        let optional = |account: &Option<UncheckedAccount<'info>>| {
+            account.as_ref().map(|account| account.key())
+        };
+        let accounts = CreateEscrowAccounts {
+            escrow: self.escrow.key(),
+            buyer: self.buyer.key(),
+            config: self.escrow_config.key(),
+            buyer_stats: self.buyer_stats.key(),
+            provider: self.provider.key(),
+            provider_stats: self.provider_stats.key(),
+            provider_escrow_index: self.provider_escrow_index.key(),
+            protocol_stats: self.protocol_stats.key(),
+            provider_profile: optional(&self.provider_profile),
+            request: self.request.key(),
+            proposal: self.proposal.key(),
+            restriction: optional(&self.restriction),
+            platform: self.platform.key(),
+            system_program: self.system_program.key(),
+            buyer_attestation: optional(&self.buyer_attestation),
+            provider_attestation: optional(&self.provider_attestation),
+            provider_badge: optional(&self.provider_badge),
+            buyer_blacklist: self.buyer_blacklist.key(),
+            provider_blacklist: self.provider_blacklist.key(),
+            mint: self.mint.key(),
+        }
+        .to_account_metas();
+        let data = create_escrow_data(
+            self.proposal.price,
+            &self.request.request_id,
+            &self.proposal.proposal_id,
+        )?;
+
+        let mut account_infos = vec![
+            self.escrow.to_account_info(),
+            self.buyer.to_account_info(),
+            self.escrow_config.to_account_info(),
+            self.buyer_stats.to_account_info(),
+            self.provider.to_account_info(),
+            self.provider_stats.to_account_info(),
+            self.provider_escrow_index.to_account_info(),
+            self.protocol_stats.to_account_info(),
+            self.request.to_account_info(),
+            self.proposal.to_account_info(),
+            self.platform.to_account_info(),
+            self.system_program.to_account_info(),
+            self.buyer_blacklist.to_account_info(),
+            self.provider_blacklist.to_account_info(),
+            self.mint.to_account_info(),
+            self.escrow_program.to_account_info(),
+        ];
+        account_infos.extend(
+            [
+                &self.provider_profile,
+                &self.restriction,
+                &self.buyer_attestation,
+                &self.provider_attestation,
+                &self.provider_badge,
+            ]
+            .into_iter()
+            .flatten()
+            .map(|account| account.to_account_info()),
+        );
+
+        invoke(
+            &Instruction {
+                program_id: ESCROW_PROGRAM_ID,
+                accounts,
+                data,
+            },
+            &account_infos,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// 反向竞价授标的上下文，托管程序 `fund_escrow` 的账户跟在授标账户之后
+#[derive(Accounts)]
+pub struct AwardReverseAuction<'info> {
+    pub award: AwardAuction<'info>,
+
+    #[account(mut, seeds = [b"auction_vault", award.auction.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = award.auction.mint, token::authority = award.buyer)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Escrow token account, validated by the escrow program
+    #[account(mut)]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow price quote PDA, validated by the escrow program
+    pub quote: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> AwardReverseAuction<'info> {
+    /// CPI 托管程序 `fund_escrow`，由买家签名从其代币账户转入中标报价
+    fn fund_escrow(&self) -> Result<()> {
+        let award = &self.award;
+        let accounts = FundEscrowAccounts {
+            escrow: award.escrow.key(),
+            buyer: award.buyer.key(),
+            buyer_stats: award.buyer_stats.key(),
+            protocol_stats: award.protocol_stats.key(),
+            quote: self.quote.key(),
+            buyer_token_account: self.buyer_token_account.key(),
+            escrow_token_account: self.escrow_token_account.key(),
+            mint: award.mint.key(),
+            token_program: self.token_program.key(),
+        }
+        .to_account_metas();
+
+        invoke(
+            &Instruction {
+                program_id: ESCROW_PROGRAM_ID,
+                accounts,
+                data: FUND_ESCROW_DISCRIMINATOR.to_vec(),
+            },
+            &[
+                award.escrow.to_account_info(),
+                award.buyer.to_account_info(),
+                award.buyer_stats.to_account_info(),
+                award.protocol_stats.to_account_info(),
+                self.quote.to_account_info(),
+                self.buyer_token_account.to_account_info(),
+                self.escrow_token_account.to_account_info(),
+                award.mint.to_account_info(),
+                self.token_program.to_account_info(),
+                award.escrow_program.to_account_info(),
+            ],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// 托管程序 `create_escrow` 的账户，顺序与其 `CreateEscrow` 上下文一致
+///
+/// 授标不使用优惠券和访问凭证铸币，这些可选账户固定不传
+pub struct CreateEscrowAccounts {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub config: Pubkey,
+    pub buyer_stats: Pubkey,
+    pub provider: Pubkey,
+    pub provider_stats: Pubkey,
+    pub provider_escrow_index: Pubkey,
+    pub protocol_stats: Pubkey,
+    pub provider_profile: Option<Pubkey>,
+    pub request: Pubkey,
+    pub proposal: Pubkey,
+    pub restriction: Option<Pubkey>,
+    pub platform: Pubkey,
+    pub system_program: Pubkey,
+    pub buyer_attestation: Option<Pubkey>,
+    pub provider_attestation: Option<Pubkey>,
+    pub provider_badge: Option<Pubkey>,
+    pub buyer_blacklist: Pubkey,
+    pub provider_blacklist: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl CreateEscrowAccounts {
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        // 未传入的可选账户按 Anchor 约定以被调用程序 ID 占位
+        let optional = |account: Option<Pubkey>, writable: bool| match account {
+            Some(account) if writable => AccountMeta::new(account, false),
+            Some(account) => AccountMeta::new_readonly(account, false),
+            None => AccountMeta::new_readonly(ESCROW_PROGRAM_ID, false),
+        };
+        vec![
+            AccountMeta::new(self.escrow, false),
+            AccountMeta::new(self.buyer, true),
+            AccountMeta::new_readonly(self.config, false),
+            AccountMeta::new(self.buyer_stats, false),
+            AccountMeta::new_readonly(self.provider, false),
+            AccountMeta::new(self.provider_stats, false),
+            AccountMeta::new(self.provider_escrow_index, false),
+            AccountMeta::new(self.protocol_stats, false),
+            optional(self.provider_profile, false),
+            AccountMeta::new_readonly(self.request, false),
+            AccountMeta::new_readonly(self.proposal, false),
+            optional(self.restriction, true),
+            AccountMeta::new_readonly(self.platform, false),
+            AccountMeta::new_readonly(self.system_program, false),
+            // 优惠券、折扣、兑换权限、优惠券程序、访问凭证铸币
+            optional(None, false),
+            optional(None, false),
+            optional(None, false),
+            optional(None, false),
+            optional(None, false),
+            optional(self.buyer_attestation, false),
+            optional(self.provider_attestation, false),
+            optional(self.provider_badge, false),
+            AccountMeta::new_readonly(self.buyer_blacklist, false),
+            AccountMeta::new_readonly(self.provider_blacklist, false),
+            AccountMeta::new_readonly(self.mint, false),
+        ]
+    }
+}
+
+/// 托管程序 `create_escrow` 的指令数据：金额、需求 ID、提案 ID，授标创建的托管不带优惠码
+pub fn create_escrow_data(amount: u64, request_id: &str, proposal_id: &str) -> Result<Vec<u8>> {
+    let mut data = CREATE_ESCROW_DISCRIMINATOR.to_vec();
+    amount.serialize(&mut data)?;
+    request_id.serialize(&mut data)?;
+    proposal_id.serialize(&mut data)?;
+    None::<String>.serialize(&mut data)?;
+    Ok(data)
+}
+
+/// 托管程序 `fund_escrow` 的账户，顺序与其 `FundEscrow` 上下文一致
+pub struct FundEscrowAccounts {
+    pub escrow: Pubkey,
+    pub buyer: Pubkey,
+    pub buyer_stats: Pubkey,
+    pub protocol_stats: Pubkey,
+    pub quote: Pubkey,
+    pub buyer_token_account: Pubkey,
+    pub escrow_token_account: Pubkey,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl FundEscrowAccounts {
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.escrow, false),
+            AccountMeta::new_readonly(self.buyer, true),
+            AccountMeta::new(self.buyer_stats, false),
+            AccountMeta::new(self.protocol_stats, false),
+            AccountMeta::new_readonly(self.quote, false),
+            AccountMeta::new(self.buyer_token_account, false),
+            AccountMeta::new(self.escrow_token_account, false),
+            AccountMeta::new_readonly(self.mint, false),
+            AccountMeta::new_readonly(self.token_program, false),
+        ]
+    }
+}
+
+/// 取回反向竞价预算的上下文
+#[derive(Accounts)]
+pub struct WithdrawBudget<'info> {
+    #[account(
+        seeds = [b"request", buyer.key().as_ref(), request.request_id.as_bytes()],
+        bump = request.bump,
+        has_one = buyer @ RequestsError::Unauthorized
+    )]
+    pub request: Account<'info, Request>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", request.key().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(mut, seeds = [b"auction_vault", auction.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub buyer: Signer<'info>,
+
+    #[account(mut, token::mint = auction.mint, token::authority = buyer)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 退还保证金的上下文
+#[derive(Accounts)]
+pub struct RefundBid<'info> {
+    #[account(
+        seeds = [b"auction", auction.request.as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        mut,
+        seeds = [b"bid", bid.proposal.as_ref()],
+        bump = bid.bump,
+        has_one = auction @ RequestsError::InvalidAuction
+    )]
+    pub bid: Account<'info, BidBond>,
+
+    #[account(mut, seeds = [b"auction_vault", auction.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = auction.mint, token::authority = bid.provider)]
+    pub provider_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum RequestsError {
+    #[msg("Invalid status for this operation")]
+    InvalidStatus,
+
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("ID exceeds the maximum length")]
+    IdTooLong,
+
+    #[msg("Proposal deadline has passed")]
+    DeadlinePassed,
+
+    #[msg("Invalid auction parameters")]
+    InvalidAuction,
+
+    #[msg("Auction bidding window has closed")]
+    AuctionClosed,
+
+    #[msg("Auction bidding window is still open")]
+    AuctionOpen,
+
+    #[msg("Bonded bids cannot be withdrawn")]
+    BidLocked,
+
+    #[msg("Bid bond cannot be refunded yet")]
+    BondLocked,
+
+    #[msg("Auction budget is locked")]
+    BudgetLocked,
+}
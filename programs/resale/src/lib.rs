@@ -0,0 +1,605 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, TokenInterface, TransferChecked};
+use datanexus_escrow::cpi::accounts::TransferAccess;
+use datanexus_escrow::program::DatanexusEscrow;
+use datanexus_escrow::{
+    Config as EscrowConfig, Escrow, EscrowStatus, FeeSplit, BPS_DENOMINATOR, PLATFORM_FEE_BPS,
+    RESALE_AUTHORITY_SEED,
+};
+
+declare_id!("DMoHhiEEFp81ubEYvDmx5H4vmsEpTVYDHrrwcswbzPo");
+
+/// DataNexus Resale Program
+///
+/// 允许买家转售已购买的数据访问凭证：
+/// 1. 原托管的买家挂单并把访问凭证存入挂单 PDA
+/// 2. 新买家付款后获得访问凭证
+/// 3. 成交价分给原提供商（版税）、平台和卖家，平台费与托管程序相同并按其配置分给国库和奖励池，
+///    全部在链上强制执行
+/// 4. 凭证每次换手都通过 CPI 更新托管的访问期记录，到期撤销冻结的始终是当前持有账户
+#[program]
+pub mod datanexus_resale {
+    use super::*;
+
+    /// 初始化转售配置（仅托管程序的平台管理员）
+    pub fn initialize(ctx: Context<Initialize>, royalty_bps: u16) -> Result<()> {
+        require!(
+            royalty_bps as u64 + PLATFORM_FEE_BPS <= BPS_DENOMINATOR,
+            ResaleError::InvalidFeeBps
+        );
+
+        let resale_config = &mut ctx.accounts.resale_config;
+        resale_config.admin = ctx.accounts.admin.key();
+        resale_config.royalty_bps = royalty_bps;
+        resale_config.bump = ctx.bumps.resale_config;
+
+        msg!("Resale config initialized: {}", resale_config.key());
+        msg!("Royalty: {} bps", royalty_bps);
+
+        Ok(())
+    }
+
+    /// 更新版税（仅管理员）
+    pub fn update_config(ctx: Context<UpdateConfig>, royalty_bps: u16) -> Result<()> {
+        require!(
+            royalty_bps as u64 + PLATFORM_FEE_BPS <= BPS_DENOMINATOR,
+            ResaleError::InvalidFeeBps
+        );
+
+        let resale_config = &mut ctx.accounts.resale_config;
+        resale_config.royalty_bps = royalty_bps;
+
+        msg!("Resale config updated: {}", resale_config.key());
+        msg!("Royalty: {} bps", royalty_bps);
+
+        Ok(())
+    }
+
+    /// 挂单转售访问凭证
+    ///
+    /// 原托管的买家把 1 枚访问凭证存入挂单金库，原托管必须已完成且配置了访问凭证
+    pub fn list(ctx: Context<List>, price: u64) -> Result<()> {
+        require!(price > 0, ResaleError::InvalidPrice);
+
+        let listing = &mut ctx.accounts.listing;
+        listing.seller = ctx.accounts.seller.key();
+        listing.escrow = ctx.accounts.escrow.key();
+        listing.provider = ctx.accounts.escrow.load()?.provider;
+        listing.access_mint = ctx.accounts.access_mint.key();
+        listing.price = price;
+        listing.created_at = Clock::get()?.unix_timestamp;
+        listing.bump = ctx.bumps.listing;
+
+        // 访问凭证存入挂单金库
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.seller_access_account.to_account_info(),
+            mint: ctx.accounts.access_mint.to_account_info(),
+            to: ctx.accounts.listing_vault.to_account_info(),
+            authority: ctx.accounts.seller.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.access_token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token_interface::transfer_checked(cpi_ctx, 1, ctx.accounts.access_mint.decimals)?;
+
+        record_access_holder(
+            ctx.accounts.escrow_program.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.accounts.access_window.to_account_info(),
+            ctx.accounts.listing_vault.to_account_info(),
+            ctx.accounts.resale_authority.to_account_info(),
+            ctx.bumps.resale_authority,
+        )?;
+
+        msg!("Listing created: {}", listing.key());
+        msg!("Escrow: {}", listing.escrow);
+        msg!("Price: {} USDC", price);
+
+        Ok(())
+    }
+
+    /// 取消挂单，访问凭证退回卖家
+    pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+
+        let seeds = &[
+            b"listing",
+            listing.escrow.as_ref(),
+            listing.seller.as_ref(),
+            &[listing.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        // 访问凭证退回卖家
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.listing_vault.to_account_info(),
+            mint: ctx.accounts.access_mint.to_account_info(),
+            to: ctx.accounts.seller_access_account.to_account_info(),
+            authority: listing.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.access_token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer);
+        token_interface::transfer_checked(cpi_ctx, 1, ctx.accounts.access_mint.decimals)?;
+
+        record_access_holder(
+            ctx.accounts.escrow_program.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.accounts.access_window.to_account_info(),
+            ctx.accounts.seller_access_account.to_account_info(),
+            ctx.accounts.resale_authority.to_account_info(),
+            ctx.bumps.resale_authority,
+        )?;
+
+        close_vault(
+            cpi_program,
+            ctx.accounts.listing_vault.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            listing.to_account_info(),
+            signer,
+        )?;
+
+        msg!("Listing cancelled: {}", listing.key());
+
+        Ok(())
+    }
+
+    /// 购买转售的访问凭证
+    ///
+    /// 成交价分配：版税给原提供商；平台费按托管程序的 `PLATFORM_FEE_BPS` 计算，
+    /// 并按托管配置的权重分给国库和奖励池；余额给卖家
+    pub fn purchase(ctx: Context<Purchase>) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        let resale_config = &ctx.accounts.resale_config;
+        let escrow_config = &ctx.accounts.escrow_config;
+
+        // 计算分配金额
+        let price = listing.price;
+        let royalty = price
+            .checked_mul(resale_config.royalty_bps as u64)
+            .and_then(|value| value.checked_div(BPS_DENOMINATOR))
+            .ok_or(ResaleError::MathOverflow)?;
+        let platform_fee = FeeSplit::new(price)?.platform_fee;
+        let treasury_fee = platform_fee
+            .checked_mul(escrow_config.treasury_bps as u64)
+            .and_then(|value| value.checked_div(BPS_DENOMINATOR))
+            .ok_or(ResaleError::MathOverflow)?;
+        let rewards_pool_fee = platform_fee
+            .checked_sub(treasury_fee)
+            .ok_or(ResaleError::MathOverflow)?;
+        let seller_amount = price
+            .checked_sub(royalty)
+            .and_then(|value| value.checked_sub(platform_fee))
+            .ok_or(ResaleError::MathOverflow)?;
+
+        msg!("Resale payment:");
+        msg!("  Price: {} USDC", price);
+        msg!("  Seller: {} USDC", seller_amount);
+        msg!("  Provider royalty ({} bps): {} USDC", resale_config.royalty_bps, royalty);
+        msg!("  Platform fee ({} bps): {} USDC", PLATFORM_FEE_BPS, platform_fee);
+        msg!("    Treasury: {} USDC", treasury_fee);
+        msg!("    Rewards pool: {} USDC", rewards_pool_fee);
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let buyer = ctx.accounts.buyer.to_account_info();
+        let buyer_token_account = ctx.accounts.buyer_token_account.to_account_info();
+
+        // 付款给卖家、原提供商、国库和奖励池，金额为零的一笔跳过
+        for (to, amount) in [
+            (ctx.accounts.seller_token_account.to_account_info(), seller_amount),
+            (ctx.accounts.provider_token_account.to_account_info(), royalty),
+            (ctx.accounts.treasury_token_account.to_account_info(), treasury_fee),
+            (ctx.accounts.rewards_pool_token_account.to_account_info(), rewards_pool_fee),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            let cpi_accounts = Transfer {
+                from: buyer_token_account.clone(),
+                to,
+                authority: buyer.clone(),
+            };
+            token::transfer(CpiContext::new(cpi_program.clone(), cpi_accounts), amount)?;
+        }
+
+        let seeds = &[
+            b"listing",
+            listing.escrow.as_ref(),
+            listing.seller.as_ref(),
+            &[listing.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        // 访问凭证转给新买家
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.listing_vault.to_account_info(),
+            mint: ctx.accounts.access_mint.to_account_info(),
+            to: ctx.accounts.buyer_access_account.to_account_info(),
+            authority: listing.to_account_info(),
+        };
+        let access_program = ctx.accounts.access_token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(access_program.clone(), cpi_accounts, signer);
+        token_interface::transfer_checked(cpi_ctx, 1, ctx.accounts.access_mint.decimals)?;
+
+        record_access_holder(
+            ctx.accounts.escrow_program.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.accounts.access_window.to_account_info(),
+            ctx.accounts.buyer_access_account.to_account_info(),
+            ctx.accounts.resale_authority.to_account_info(),
+            ctx.bumps.resale_authority,
+        )?;
+
+        close_vault(
+            access_program,
+            ctx.accounts.listing_vault.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            listing.to_account_info(),
+            signer,
+        )?;
+
+        msg!("Listing sold: {}", listing.key());
+        msg!("New holder: {}", ctx.accounts.buyer.key());
+
+        Ok(())
+    }
+}
+
+/// 原托管设置过访问期时，通过 CPI 让托管程序记录凭证当前的持有账户
+fn record_access_holder<'info>(
+    escrow_program: AccountInfo<'info>,
+    escrow: AccountInfo<'info>,
+    access_window: AccountInfo<'info>,
+    token_account: AccountInfo<'info>,
+    resale_authority: AccountInfo<'info>,
+    resale_authority_bump: u8,
+) -> Result<()> {
+    if access_window.owner != &datanexus_escrow::ID {
+        return Ok(());
+    }
+
+    let seeds = &[RESALE_AUTHORITY_SEED, &[resale_authority_bump]];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = TransferAccess {
+        escrow,
+        access_window,
+        token_account,
+        resale_authority,
+    };
+    datanexus_escrow::cpi::transfer_access(CpiContext::new_with_signer(
+        escrow_program,
+        cpi_accounts,
+        signer,
+    ))
+}
+
+/// 关闭挂单金库，租金退回卖家
+fn close_vault<'info>(
+    token_program: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    listing: AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    let cpi_accounts = token_interface::CloseAccount {
+        account: vault,
+        destination,
+        authority: listing,
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer);
+    token_interface::close_account(cpi_ctx)
+}
+
+/// 转售配置
+#[account]
+#[derive(InitSpace)]
+pub struct ResaleConfig {
+    pub admin: Pubkey,           // 管理员
+    pub royalty_bps: u16,        // 原提供商版税（基点）
+    pub bump: u8,                // PDA bump
+}
+
+impl ResaleConfig {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 转售挂单
+#[account]
+#[derive(InitSpace)]
+pub struct Listing {
+    pub seller: Pubkey,          // 卖家（当前凭证持有人）
+    pub escrow: Pubkey,          // 原始托管
+    pub provider: Pubkey,        // 原提供商（版税接收方）
+    pub access_mint: Pubkey,     // 访问凭证铸币
+    pub price: u64,              // 挂单价格（USDC，6 位小数）
+    pub created_at: i64,         // 挂单时间
+    pub bump: u8,                // PDA bump
+}
+
+impl Listing {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 初始化配置的上下文
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = ResaleConfig::LEN,
+        seeds = [b"resale_config"],
+        bump
+    )]
+    pub resale_config: Account<'info, ResaleConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = escrow_config.bump,
+        seeds::program = datanexus_escrow::ID,
+        has_one = admin @ ResaleError::Unauthorized
+    )]
+    pub escrow_config: Account<'info, EscrowConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 更新配置的上下文
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"resale_config"],
+        bump = resale_config.bump,
+        has_one = admin @ ResaleError::Unauthorized
+    )]
+    pub resale_config: Account<'info, ResaleConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+/// 挂单的上下文
+#[derive(Accounts)]
+pub struct List<'info> {
+    #[account(
+        init,
+        payer = seller,
+        space = Listing::LEN,
+        seeds = [b"listing", escrow.key().as_ref(), seller.key().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        constraint = escrow.load()?.status() == EscrowStatus::Completed
+            @ ResaleError::EscrowNotCompleted,
+        constraint = escrow.load()?.buyer == seller.key() @ ResaleError::NotEscrowBuyer,
+        constraint = escrow.load()?.access_mint() == Some(access_mint.key())
+            @ ResaleError::InvalidAccessMint
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub access_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = access_mint,
+        token::authority = seller,
+        token::token_program = access_token_program,
+    )]
+    pub seller_access_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        init,
+        payer = seller,
+        associated_token::mint = access_mint,
+        associated_token::authority = listing,
+        associated_token::token_program = access_token_program,
+    )]
+    pub listing_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: Escrow access window PDA, may be uninitialized; updated through `transfer_access`
+    #[account(
+        mut,
+        seeds = [b"access_window", escrow.key().as_ref()],
+        bump,
+        seeds::program = datanexus_escrow::ID
+    )]
+    pub access_window: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs escrow CPIs on behalf of the resale program
+    #[account(seeds = [RESALE_AUTHORITY_SEED], bump)]
+    pub resale_authority: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, DatanexusEscrow>,
+
+    pub access_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 取消挂单的上下文
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", listing.escrow.as_ref(), listing.seller.as_ref()],
+        bump = listing.bump,
+        has_one = seller @ ResaleError::Unauthorized,
+        has_one = access_mint
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(address = listing.escrow)]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub access_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = access_mint,
+        associated_token::authority = listing,
+        associated_token::token_program = access_token_program,
+    )]
+    pub listing_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = access_mint,
+        token::authority = seller,
+        token::token_program = access_token_program,
+    )]
+    pub seller_access_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: Escrow access window PDA, may be uninitialized; updated through `transfer_access`
+    #[account(
+        mut,
+        seeds = [b"access_window", escrow.key().as_ref()],
+        bump,
+        seeds::program = datanexus_escrow::ID
+    )]
+    pub access_window: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs escrow CPIs on behalf of the resale program
+    #[account(seeds = [RESALE_AUTHORITY_SEED], bump)]
+    pub resale_authority: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, DatanexusEscrow>,
+
+    pub access_token_program: Interface<'info, TokenInterface>,
+}
+
+/// 购买的上下文
+#[derive(Accounts)]
+pub struct Purchase<'info> {
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", listing.escrow.as_ref(), listing.seller.as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+        has_one = access_mint
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(seeds = [b"resale_config"], bump = resale_config.bump)]
+    pub resale_config: Account<'info, ResaleConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = escrow_config.bump,
+        seeds::program = datanexus_escrow::ID
+    )]
+    pub escrow_config: Account<'info, EscrowConfig>,
+
+    #[account(address = listing.escrow)]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Listing seller, receives the closed listing rent
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = treasury_token_account.mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = treasury_token_account.mint,
+        token::authority = listing.seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = treasury_token_account.mint,
+        token::authority = listing.provider,
+    )]
+    pub provider_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = escrow_config.treasury)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = escrow_config.rewards_pool,
+        token::mint = treasury_token_account.mint,
+    )]
+    pub rewards_pool_token_account: Account<'info, TokenAccount>,
+
+    pub access_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = access_mint,
+        associated_token::authority = listing,
+        associated_token::token_program = access_token_program,
+    )]
+    pub listing_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = access_mint,
+        token::authority = buyer,
+        token::token_program = access_token_program,
+    )]
+    pub buyer_access_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: Escrow access window PDA, may be uninitialized; updated through `transfer_access`
+    #[account(
+        mut,
+        seeds = [b"access_window", escrow.key().as_ref()],
+        bump,
+        seeds::program = datanexus_escrow::ID
+    )]
+    pub access_window: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs escrow CPIs on behalf of the resale program
+    #[account(seeds = [RESALE_AUTHORITY_SEED], bump)]
+    pub resale_authority: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, DatanexusEscrow>,
+
+    pub token_program: Program<'info, Token>,
+    pub access_token_program: Interface<'info, TokenInterface>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum ResaleError {
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[msg("Royalty and platform fee must not exceed 10000 bps")]
+    InvalidFeeBps,
+
+    #[msg("Invalid price")]
+    InvalidPrice,
+
+    #[msg("Original escrow is not completed")]
+    EscrowNotCompleted,
+
+    #[msg("Access mint does not match the original escrow")]
+    InvalidAccessMint,
+
+    #[msg("Only the buyer of the original escrow can list its access token")]
+    NotEscrowBuyer,
+
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+}
@@ -0,0 +1,607 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+use datanexus_escrow::{Escrow, EscrowStatus};
+use datanexus_jury::{Case, CaseStatus};
+
+declare_id!("3cknkeLdBfENtjS4MtNEmJs6JQp6tWRCpyFokan4PZiA");
+
+/// 基点分母
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// DataNexus Staking Program
+///
+/// 提供商质押保证金，让争议裁决具有实际约束力：
+/// 1. 提供商存入保证金，质押量公开在 `Bond` 账户中，市场和索引器据此提升其曝光排序
+/// 2. 取回保证金需先申请解绑并等待解绑期，期间仍可被罚没，避免败诉前抢先撤资
+/// 3. 争议以退款给买家结案（平台或陪审团裁定欺诈）后，托管的平台调用 `slash`，
+///    按配置比例罚没保证金，销毁或转给受损买家；每个托管只能罚没一次
+/// 4. 陪审团裁定退款的案件，任何人可凭陪审团 `Case` 调用 `execute_slash` 把罚没转给买家，
+///    与陪审团的 `execute_verdict` 放在同一笔交易中即可一次完成退款和赔偿
+#[program]
+pub mod datanexus_staking {
+    use super::*;
+
+    /// 初始化质押配置和保证金金库
+    ///
+    /// 仅程序升级权限可调用，防止抢先初始化并自任管理员、选定保证金代币和罚没参数
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        slash_bps: u16,
+        slash_to_buyer: bool,
+        unbonding_period: i64,
+    ) -> Result<()> {
+        require!(
+            slash_bps as u64 <= BPS_DENOMINATOR,
+            StakingError::InvalidSlashBps
+        );
+        require!(unbonding_period >= 0, StakingError::InvalidUnbondingPeriod);
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.mint = ctx.accounts.mint.key();
+        config.slash_bps = slash_bps;
+        config.slash_to_buyer = slash_to_buyer;
+        config.unbonding_period = unbonding_period;
+        config.bump = ctx.bumps.config;
+
+        msg!("Staking initialized: {}", config.key());
+        msg!("Bond mint: {}", config.mint);
+        msg!("Slash: {} bps, to buyer: {}", slash_bps, slash_to_buyer);
+        msg!("Unbonding period: {}s", unbonding_period);
+
+        Ok(())
+    }
+
+    /// 更新罚没比例、去向和解绑期（仅管理员）
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        slash_bps: u16,
+        slash_to_buyer: bool,
+        unbonding_period: i64,
+    ) -> Result<()> {
+        require!(
+            slash_bps as u64 <= BPS_DENOMINATOR,
+            StakingError::InvalidSlashBps
+        );
+        require!(unbonding_period >= 0, StakingError::InvalidUnbondingPeriod);
+
+        let config = &mut ctx.accounts.config;
+        config.slash_bps = slash_bps;
+        config.slash_to_buyer = slash_to_buyer;
+        config.unbonding_period = unbonding_period;
+
+        msg!("Staking config updated: {}", config.key());
+        msg!("Slash: {} bps, to buyer: {}", slash_bps, slash_to_buyer);
+        msg!("Unbonding period: {}s", unbonding_period);
+
+        Ok(())
+    }
+
+    /// 提供商存入保证金（可多次追加）
+    pub fn bond(ctx: Context<BondTokens>, amount: u64) -> Result<()> {
+        require!(amount > 0, StakingError::InvalidAmount);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.provider_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.provider.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+        let bond = &mut ctx.accounts.bond;
+        if bond.provider == Pubkey::default() {
+            bond.provider = ctx.accounts.provider.key();
+            bond.bump = ctx.bumps.bond;
+        }
+        bond.amount = bond.amount.checked_add(amount).unwrap();
+
+        msg!("Bonded: {}", bond.provider);
+        msg!("Amount: {}, total: {}", amount, bond.amount);
+
+        Ok(())
+    }
+
+    /// 申请解绑，解绑期结束后才能取回；再次申请会与未取回的部分合并并重新计时
+    pub fn request_unbond(ctx: Context<RequestUnbond>, amount: u64) -> Result<()> {
+        let bond = &mut ctx.accounts.bond;
+
+        require!(
+            amount > 0 && amount <= bond.amount,
+            StakingError::InvalidAmount
+        );
+
+        bond.amount -= amount;
+        bond.unbonding_amount = bond.unbonding_amount.checked_add(amount).unwrap();
+        bond.unbonding_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(ctx.accounts.config.unbonding_period)
+            .unwrap();
+
+        msg!("Unbond requested: {}", bond.provider);
+        msg!("Amount: {}, unbonding: {}", amount, bond.unbonding_amount);
+        msg!("Withdrawable at: {}", bond.unbonding_at);
+
+        Ok(())
+    }
+
+    /// 取回解绑期已满的保证金
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        let bond = &mut ctx.accounts.bond;
+
+        require!(bond.unbonding_amount > 0, StakingError::InvalidAmount);
+        require!(
+            Clock::get()?.unix_timestamp >= bond.unbonding_at,
+            StakingError::StillUnbonding
+        );
+
+        let amount = bond.unbonding_amount;
+        bond.unbonding_amount = 0;
+
+        let seeds = &[b"staking_config".as_ref(), &[ctx.accounts.config.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.provider_token_account.to_account_info(),
+            authority: ctx.accounts.config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            amount,
+        )?;
+
+        msg!("Withdrawn: {}", bond.provider);
+        msg!("Amount: {}", amount);
+
+        Ok(())
+    }
+
+    /// 按欺诈裁决罚没提供商保证金（托管的平台调用）
+    ///
+    /// 托管须已通过争议退款给买家；罚没额为保证金（含解绑中部分）的 `slash_bps`，
+    /// 先从已质押部分扣除，不足时再扣解绑中部分
+    pub fn slash(ctx: Context<Slash>) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let escrow = ctx.accounts.escrow.load()?;
+        let config = &ctx.accounts.config;
+
+        require_fraud_ruling(&escrow)?;
+
+        let bond = &mut ctx.accounts.bond;
+        let amount = bond.slash(config.slash_bps)?;
+
+        ctx.accounts.slash_record.set_inner(SlashRecord {
+            escrow: escrow_key,
+            provider: bond.provider,
+            buyer: escrow.buyer,
+            amount,
+            to_buyer: config.slash_to_buyer,
+            slashed_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.slash_record,
+        });
+
+        let seeds = &[b"staking_config".as_ref(), &[config.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        if config.slash_to_buyer {
+            let buyer_token_account = ctx
+                .accounts
+                .buyer_token_account
+                .as_ref()
+                .ok_or(StakingError::MissingBuyerAccount)?;
+
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: buyer_token_account.to_account_info(),
+                authority: config.to_account_info(),
+            };
+            token::transfer(
+                CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                amount,
+            )?;
+        } else {
+            let cpi_accounts = Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.vault.to_account_info(),
+                authority: config.to_account_info(),
+            };
+            token::burn(
+                CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                amount,
+            )?;
+        }
+
+        msg!("Provider slashed: {}", bond.provider);
+        msg!("Escrow: {}", escrow_key);
+        msg!("Amount: {}, to buyer: {}", amount, config.slash_to_buyer);
+
+        Ok(())
+    }
+
+    /// 按陪审团裁决罚没提供商保证金并赔付买家（任何人可调用）
+    ///
+    /// 陪审团案件须已执行且裁定退款给买家；罚没额计算与 `slash` 相同，
+    /// 但无论 `slash_to_buyer` 如何配置都转给买家
+    pub fn execute_slash(ctx: Context<ExecuteSlash>) -> Result<()> {
+        let escrow_key = ctx.accounts.escrow.key();
+        let escrow = ctx.accounts.escrow.load()?;
+        let ruling = &ctx.accounts.ruling;
+        let config = &ctx.accounts.config;
+
+        require!(
+            ruling.status == CaseStatus::Executed && ruling.refund_to_buyer,
+            StakingError::NoFraudRuling
+        );
+        require!(
+            escrow.status() == EscrowStatus::Refunded,
+            StakingError::NoFraudRuling
+        );
+
+        let bond = &mut ctx.accounts.bond;
+        let amount = bond.slash(config.slash_bps)?;
+
+        ctx.accounts.slash_record.set_inner(SlashRecord {
+            escrow: escrow_key,
+            provider: bond.provider,
+            buyer: escrow.buyer,
+            amount,
+            to_buyer: true,
+            slashed_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.slash_record,
+        });
+
+        let seeds = &[b"staking_config".as_ref(), &[config.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            amount,
+        )?;
+
+        msg!("Provider slashed by jury ruling: {}", bond.provider);
+        msg!("Escrow: {}, case: {}", escrow_key, ruling.key());
+        msg!("Amount paid to buyer: {}", amount);
+
+        Ok(())
+    }
+}
+
+/// 质押配置
+#[account]
+#[derive(InitSpace)]
+pub struct StakingConfig {
+    pub admin: Pubkey,           // 管理员
+    pub mint: Pubkey,            // 保证金代币
+    pub slash_bps: u16,          // 每次罚没比例（基点）
+    pub slash_to_buyer: bool,    // 罚没转给买家（否则销毁）
+    pub unbonding_period: i64,   // 解绑期（秒）
+    pub bump: u8,                // PDA bump
+}
+
+impl StakingConfig {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 平台通过争议退款给买家即为欺诈裁决；未经争议的退款（如取消或超时退款）不能罚没
+pub fn require_fraud_ruling(escrow: &Escrow) -> Result<()> {
+    require!(
+        escrow.status() == EscrowStatus::Refunded && escrow.disputed_at().is_some(),
+        StakingError::NoFraudRuling
+    );
+    Ok(())
+}
+
+/// 提供商保证金
+#[account]
+#[derive(InitSpace)]
+pub struct Bond {
+    pub provider: Pubkey,        // 提供商
+    pub amount: u64,             // 已质押数量
+    pub unbonding_amount: u64,   // 解绑中数量（仍可被罚没）
+    pub unbonding_at: i64,       // 解绑中部分可取回的时间
+    pub slashed_total: u64,      // 累计被罚没数量
+    pub bump: u8,                // PDA bump
+}
+
+impl Bond {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+impl Bond {
+    /// 按比例罚没（含解绑中部分），先扣已质押部分，返回罚没数量
+    pub fn slash(&mut self, slash_bps: u16) -> Result<u64> {
+        let total = self.amount.checked_add(self.unbonding_amount).unwrap();
+        let amount = (total as u128 * slash_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        require!(amount > 0, StakingError::NothingToSlash);
+
+        let from_bonded = amount.min(self.amount);
+        self.amount -= from_bonded;
+        self.unbonding_amount -= amount - from_bonded;
+        self.slashed_total = self.slashed_total.checked_add(amount).unwrap();
+
+        Ok(amount)
+    }
+}
+
+/// 罚没记录，每个托管一条，防止重复罚没
+#[account]
+#[derive(InitSpace)]
+pub struct SlashRecord {
+    pub escrow: Pubkey,          // 裁决所依据的托管
+    pub provider: Pubkey,        // 被罚没的提供商
+    pub buyer: Pubkey,           // 受损买家
+    pub amount: u64,             // 罚没数量
+    pub to_buyer: bool,          // 是否转给买家（否则已销毁）
+    pub slashed_at: i64,         // 罚没时间
+    pub bump: u8,                // PDA bump
+}
+
+impl SlashRecord {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 初始化的上下文
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = StakingConfig::LEN,
+        seeds = [b"staking_config"],
+        bump
+    )]
+    pub config: Account<'info, StakingConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"bond_vault"],
+        bump,
+        token::mint = mint,
+        token::authority = config,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::DatanexusStaking>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ StakingError::OnlyUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 更新配置的上下文
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_config"],
+        bump = config.bump,
+        has_one = admin @ StakingError::Unauthorized
+    )]
+    pub config: Account<'info, StakingConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+/// 存入保证金的上下文
+#[derive(Accounts)]
+pub struct BondTokens<'info> {
+    #[account(seeds = [b"staking_config"], bump = config.bump)]
+    pub config: Account<'info, StakingConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = provider,
+        space = Bond::LEN,
+        seeds = [b"bond", provider.key().as_ref()],
+        bump
+    )]
+    pub bond: Account<'info, Bond>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = config.mint,
+        token::authority = provider,
+    )]
+    pub provider_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"bond_vault"], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 申请解绑的上下文
+#[derive(Accounts)]
+pub struct RequestUnbond<'info> {
+    #[account(seeds = [b"staking_config"], bump = config.bump)]
+    pub config: Account<'info, StakingConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"bond", provider.key().as_ref()],
+        bump = bond.bump,
+        has_one = provider @ StakingError::Unauthorized
+    )]
+    pub bond: Account<'info, Bond>,
+
+    pub provider: Signer<'info>,
+}
+
+/// 取回保证金的上下文
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(seeds = [b"staking_config"], bump = config.bump)]
+    pub config: Account<'info, StakingConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"bond", provider.key().as_ref()],
+        bump = bond.bump,
+        has_one = provider @ StakingError::Unauthorized
+    )]
+    pub bond: Account<'info, Bond>,
+
+    pub provider: Signer<'info>,
+
+    #[account(mut, token::mint = config.mint)]
+    pub provider_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"bond_vault"], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 罚没的上下文
+#[derive(Accounts)]
+pub struct Slash<'info> {
+    #[account(seeds = [b"staking_config"], bump = config.bump)]
+    pub config: Account<'info, StakingConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"bond", escrow.load()?.provider.as_ref()],
+        bump = bond.bump
+    )]
+    pub bond: Account<'info, Bond>,
+
+    #[account(
+        constraint = escrow.load()?.platform == arbitrator.key() @ StakingError::Unauthorized
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    #[account(
+        init,
+        payer = arbitrator,
+        space = SlashRecord::LEN,
+        seeds = [b"slash", escrow.key().as_ref()],
+        bump
+    )]
+    pub slash_record: Account<'info, SlashRecord>,
+
+    /// 托管的平台（仲裁方）
+    #[account(mut)]
+    pub arbitrator: Signer<'info>,
+
+    #[account(mut, address = config.mint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [b"bond_vault"], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// 买家代币账户，罚没转给买家时必填
+    #[account(
+        mut,
+        token::mint = config.mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 按陪审团裁决罚没的上下文
+#[derive(Accounts)]
+pub struct ExecuteSlash<'info> {
+    #[account(seeds = [b"staking_config"], bump = config.bump)]
+    pub config: Account<'info, StakingConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"bond", escrow.load()?.provider.as_ref()],
+        bump = bond.bump
+    )]
+    pub bond: Account<'info, Bond>,
+
+    pub escrow: AccountLoader<'info, Escrow>,
+
+    /// 陪审团对该托管的案件
+    #[account(
+        seeds = [b"case", escrow.key().as_ref()],
+        bump = ruling.bump,
+        seeds::program = datanexus_jury::ID
+    )]
+    pub ruling: Account<'info, Case>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = SlashRecord::LEN,
+        seeds = [b"slash", escrow.key().as_ref()],
+        bump
+    )]
+    pub slash_record: Account<'info, SlashRecord>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, seeds = [b"bond_vault"], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = config.mint,
+        token::authority = escrow.load()?.buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum StakingError {
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("Slash bps must not exceed 10000")]
+    InvalidSlashBps,
+
+    #[msg("Unbonding period must not be negative")]
+    InvalidUnbondingPeriod,
+
+    #[msg("Unbonding period has not ended yet")]
+    StillUnbonding,
+
+    #[msg("Escrow was not refunded to the buyer through a dispute")]
+    NoFraudRuling,
+
+    #[msg("Bond is empty or the slash amount rounds to zero")]
+    NothingToSlash,
+
+    #[msg("Buyer token account is required when slashing to the buyer")]
+    MissingBuyerAccount,
+
+    #[msg("Only the program upgrade authority can initialize staking")]
+    OnlyUpgradeAuthority,
+}
@@ -0,0 +1,440 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, CreateAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{self, Mint, TokenAccount as InterfaceTokenAccount};
+
+declare_id!("GSoF6emEWwAbyDGJiSEgxVqujfDkKSi4GsrwYpA2sBy5");
+
+/// DataNexus Subscriptions Program
+///
+/// 订阅制数据产品（API 密钥）的链上授权：
+/// 1. 提供商创建订阅计划，程序同时创建计划专属的 Token-2022 不可转让访问凭证铸币，
+///    铸币和冻结权限都属于计划 PDA
+/// 2. 订阅者支付一个周期的费用后获得 1 枚访问凭证，续费顺延到期时间，已冻结的凭证自动解冻
+/// 3. 到期未续费时任何人都可以冻结订阅者的凭证账户；
+///    提供商网关只需检查请求方持有未冻结的凭证即可授权
+#[program]
+pub mod datanexus_subscriptions {
+    use super::*;
+
+    /// 提供商创建订阅计划和访问凭证铸币
+    ///
+    /// `price` 为每个周期的费用（以 `payment_mint` 计），`period` 为周期长度（秒）
+    pub fn create_plan(
+        ctx: Context<CreatePlan>,
+        plan_id: u64,
+        price: u64,
+        period: i64,
+    ) -> Result<()> {
+        require!(price > 0 && period > 0, SubscriptionError::InvalidPlan);
+
+        let plan_key = ctx.accounts.plan.key();
+        let mint_seeds = &[
+            b"plan_mint".as_ref(),
+            plan_key.as_ref(),
+            &[ctx.bumps.access_mint],
+        ];
+        let mint_signer = &[&mint_seeds[..]];
+
+        // 创建带不可转让扩展的 Token-2022 铸币，防止订阅者转让凭证
+        let space = ExtensionType::try_calculate_account_len::<MintState>(&[
+            ExtensionType::NonTransferable,
+        ])?;
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                CreateAccount {
+                    from: ctx.accounts.provider.to_account_info(),
+                    to: ctx.accounts.access_mint.to_account_info(),
+                },
+                mint_signer,
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &ctx.accounts.access_token_program.key(),
+        )?;
+        token_interface::non_transferable_mint_initialize(CpiContext::new(
+            ctx.accounts.access_token_program.to_account_info(),
+            token_interface::NonTransferableMintInitialize {
+                token_program_id: ctx.accounts.access_token_program.to_account_info(),
+                mint: ctx.accounts.access_mint.to_account_info(),
+            },
+        ))?;
+        token_interface::initialize_mint2(
+            CpiContext::new(
+                ctx.accounts.access_token_program.to_account_info(),
+                token_interface::InitializeMint2 {
+                    mint: ctx.accounts.access_mint.to_account_info(),
+                },
+            ),
+            0,
+            &plan_key,
+            Some(&plan_key),
+        )?;
+
+        let plan = &mut ctx.accounts.plan;
+        plan.provider = ctx.accounts.provider.key();
+        plan.plan_id = plan_id;
+        plan.payment_mint = ctx.accounts.payment_mint.key();
+        plan.access_mint = ctx.accounts.access_mint.key();
+        plan.price = price;
+        plan.period = period;
+        plan.active = true;
+        plan.subscribers = 0;
+        plan.bump = ctx.bumps.plan;
+
+        msg!("Plan created: {}", plan_key);
+        msg!("Access mint: {}", plan.access_mint);
+        msg!("Price: {} per {}s", price, period);
+
+        Ok(())
+    }
+
+    /// 提供商调整价格、周期或停止接受新订阅和续费（已付费的订阅到期前不受影响）
+    pub fn update_plan(
+        ctx: Context<UpdatePlan>,
+        price: u64,
+        period: i64,
+        active: bool,
+    ) -> Result<()> {
+        require!(price > 0 && period > 0, SubscriptionError::InvalidPlan);
+
+        let plan = &mut ctx.accounts.plan;
+        plan.price = price;
+        plan.period = period;
+        plan.active = active;
+
+        msg!("Plan updated: {}", plan.key());
+        msg!("Price: {} per {}s, active: {}", price, period, active);
+
+        Ok(())
+    }
+
+    /// 订阅者支付一个周期的费用，获得或解冻访问凭证
+    ///
+    /// 未到期时续费从原到期时间顺延，已到期则从当前时间起算
+    pub fn subscribe(ctx: Context<Subscribe>) -> Result<()> {
+        let plan = &ctx.accounts.plan;
+        require!(plan.active, SubscriptionError::PlanInactive);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.subscriber_token_account.to_account_info(),
+            to: ctx.accounts.provider_token_account.to_account_info(),
+            authority: ctx.accounts.subscriber.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), plan.price)?;
+
+        let plan_id = plan.plan_id.to_le_bytes();
+        let seeds = &[
+            b"plan".as_ref(),
+            plan.provider.as_ref(),
+            plan_id.as_ref(),
+            &[plan.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let access_account = &ctx.accounts.subscriber_access_account;
+
+        if access_account.is_frozen() {
+            token_interface::thaw_account(CpiContext::new_with_signer(
+                ctx.accounts.access_token_program.to_account_info(),
+                token_interface::ThawAccount {
+                    account: access_account.to_account_info(),
+                    mint: ctx.accounts.access_mint.to_account_info(),
+                    authority: plan.to_account_info(),
+                },
+                signer,
+            ))?;
+        }
+        if access_account.amount == 0 {
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.access_token_program.to_account_info(),
+                    token_interface::MintTo {
+                        mint: ctx.accounts.access_mint.to_account_info(),
+                        to: access_account.to_account_info(),
+                        authority: plan.to_account_info(),
+                    },
+                    signer,
+                ),
+                1,
+            )?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let price = plan.price;
+        let period = plan.period;
+        let subscription = &mut ctx.accounts.subscription;
+        if subscription.subscriber == Pubkey::default() {
+            subscription.plan = plan.key();
+            subscription.subscriber = ctx.accounts.subscriber.key();
+            subscription.access_account = access_account.key();
+            subscription.started_at = now;
+            subscription.bump = ctx.bumps.subscription;
+
+            let plan = &mut ctx.accounts.plan;
+            plan.subscribers = plan.subscribers.checked_add(1).unwrap();
+        }
+        subscription.paid_until = subscription
+            .paid_until
+            .max(now)
+            .checked_add(period)
+            .unwrap();
+
+        emit!(Subscribed {
+            plan: subscription.plan,
+            subscriber: subscription.subscriber,
+            amount: price,
+            paid_until: subscription.paid_until,
+            timestamp: now,
+        });
+
+        msg!("Subscribed: {}", subscription.subscriber);
+        msg!("Paid until: {}", subscription.paid_until);
+
+        Ok(())
+    }
+
+    /// 订阅到期未续费时冻结订阅者的访问凭证账户（任何人可调用）
+    pub fn lapse(ctx: Context<Lapse>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &ctx.accounts.subscription;
+
+        require!(
+            now >= subscription.paid_until,
+            SubscriptionError::SubscriptionActive
+        );
+        require!(
+            !ctx.accounts.subscriber_access_account.is_frozen(),
+            SubscriptionError::AlreadyLapsed
+        );
+
+        let plan = &ctx.accounts.plan;
+        let plan_id = plan.plan_id.to_le_bytes();
+        let seeds = &[
+            b"plan".as_ref(),
+            plan.provider.as_ref(),
+            plan_id.as_ref(),
+            &[plan.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        token_interface::freeze_account(CpiContext::new_with_signer(
+            ctx.accounts.access_token_program.to_account_info(),
+            token_interface::FreezeAccount {
+                account: ctx.accounts.subscriber_access_account.to_account_info(),
+                mint: ctx.accounts.access_mint.to_account_info(),
+                authority: plan.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        emit!(SubscriptionLapsed {
+            plan: plan.key(),
+            subscriber: subscription.subscriber,
+            paid_until: subscription.paid_until,
+            timestamp: now,
+        });
+
+        msg!("Subscription lapsed: {}", subscription.subscriber);
+        msg!("Paid until: {}", subscription.paid_until);
+
+        Ok(())
+    }
+}
+
+/// 提供商的订阅计划
+#[account]
+#[derive(InitSpace)]
+pub struct Plan {
+    pub provider: Pubkey,     // 提供商（收款方）
+    pub plan_id: u64,         // 提供商自定义的计划编号
+    pub payment_mint: Pubkey, // 付费代币
+    pub access_mint: Pubkey,  // 访问凭证铸币（Token-2022 不可转让）
+    pub price: u64,           // 每个周期的费用
+    pub period: i64,          // 周期长度（秒）
+    pub active: bool,         // 是否接受新订阅和续费
+    pub subscribers: u64,     // 累计订阅者数
+    pub bump: u8,             // PDA bump
+}
+
+impl Plan {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 订阅者在某个计划下的订阅
+#[account]
+#[derive(InitSpace)]
+pub struct Subscription {
+    pub plan: Pubkey,           // 订阅计划
+    pub subscriber: Pubkey,     // 订阅者
+    pub access_account: Pubkey, // 订阅者的访问凭证账户
+    pub paid_until: i64,        // 已付费到期时间
+    pub started_at: i64,        // 首次订阅时间
+    pub bump: u8,               // PDA bump
+}
+
+impl Subscription {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// 订阅或续费
+#[event]
+pub struct Subscribed {
+    pub plan: Pubkey,
+    pub subscriber: Pubkey,
+    pub amount: u64,
+    pub paid_until: i64,
+    pub timestamp: i64,
+}
+
+/// 订阅到期，访问凭证已冻结
+#[event]
+pub struct SubscriptionLapsed {
+    pub plan: Pubkey,
+    pub subscriber: Pubkey,
+    pub paid_until: i64,
+    pub timestamp: i64,
+}
+
+/// 创建订阅计划的上下文
+#[derive(Accounts)]
+#[instruction(plan_id: u64)]
+pub struct CreatePlan<'info> {
+    #[account(
+        init,
+        payer = provider,
+        space = Plan::LEN,
+        seeds = [b"plan", provider.key().as_ref(), plan_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub plan: Account<'info, Plan>,
+
+    /// CHECK: Access token mint PDA, created and initialized in the handler
+    #[account(mut, seeds = [b"plan_mint", plan.key().as_ref()], bump)]
+    pub access_mint: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    pub payment_mint: Account<'info, anchor_spl::token::Mint>,
+
+    pub access_token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 更新订阅计划的上下文
+#[derive(Accounts)]
+pub struct UpdatePlan<'info> {
+    #[account(
+        mut,
+        seeds = [b"plan", plan.provider.as_ref(), plan.plan_id.to_le_bytes().as_ref()],
+        bump = plan.bump,
+        has_one = provider @ SubscriptionError::Unauthorized
+    )]
+    pub plan: Account<'info, Plan>,
+
+    pub provider: Signer<'info>,
+}
+
+/// 订阅的上下文
+#[derive(Accounts)]
+pub struct Subscribe<'info> {
+    #[account(
+        mut,
+        seeds = [b"plan", plan.provider.as_ref(), plan.plan_id.to_le_bytes().as_ref()],
+        bump = plan.bump
+    )]
+    pub plan: Account<'info, Plan>,
+
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = Subscription::LEN,
+        seeds = [b"subscription", plan.key().as_ref(), subscriber.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = plan.payment_mint,
+        token::authority = subscriber,
+    )]
+    pub subscriber_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = plan.payment_mint,
+        token::authority = plan.provider,
+    )]
+    pub provider_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = plan.access_mint)]
+    pub access_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        associated_token::mint = access_mint,
+        associated_token::authority = subscriber,
+        associated_token::token_program = access_token_program,
+    )]
+    pub subscriber_access_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub access_token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 冻结到期订阅的上下文
+#[derive(Accounts)]
+pub struct Lapse<'info> {
+    #[account(
+        seeds = [b"plan", plan.provider.as_ref(), plan.plan_id.to_le_bytes().as_ref()],
+        bump = plan.bump
+    )]
+    pub plan: Account<'info, Plan>,
+
+    #[account(
+        seeds = [b"subscription", plan.key().as_ref(), subscription.subscriber.as_ref()],
+        bump = subscription.bump,
+        has_one = plan
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(address = plan.access_mint)]
+    pub access_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = subscription.access_account)]
+    pub subscriber_access_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    pub access_token_program: Program<'info, Token2022>,
+}
+
+/// 错误代码
+#[error_code]
+pub enum SubscriptionError {
+    #[msg("Unauthorized: You don't have permission to perform this action")]
+    Unauthorized,
+
+    #[msg("Plan price and period must be positive")]
+    InvalidPlan,
+
+    #[msg("Plan is not accepting subscriptions")]
+    PlanInactive,
+
+    #[msg("Subscription is still paid up")]
+    SubscriptionActive,
+
+    #[msg("Subscription access is already frozen")]
+    AlreadyLapsed,
+}
@@ -0,0 +1,37 @@
+#!/bin/bash
+
+# 检查链上程序的实际栈占用
+# 使用方法: bash scripts/check-sbf-stack.sh [程序目录...]（默认检查 programs/ 下所有程序）
+#
+# context_size 测试只检查上下文结构体的 size_of；函数栈帧超过 4KB 时 cargo build-sbf
+# 只打印 "Stack offset of N exceeded max offset of 4096" 告警，编译照常成功，
+# 运行时才会访问越界。这里逐个构建程序，出现该告警时返回非零；
+# context_size 中的 sbf_stack_frames_fit（#[ignore]）运行本脚本
+
+set -o pipefail
+
+cd "$(dirname "$0")/.."
+
+if [ $# -eq 0 ]; then
+    set -- programs/*/
+fi
+
+LOG=$(mktemp)
+trap 'rm -f "$LOG"' EXIT
+
+for program in "$@"; do
+    echo "==> cargo build-sbf ${program%/}"
+    if ! cargo build-sbf --manifest-path "${program%/}/Cargo.toml" 2>&1 | tee -a "$LOG"; then
+        echo "❌ ${program%/} 构建失败" >&2
+        exit 1
+    fi
+done
+
+if grep -q "Stack offset of" "$LOG"; then
+    echo "" >&2
+    echo "❌ 以下函数超出 SBF 栈帧上限，请把大的局部变量或账户放进 Box：" >&2
+    grep "Stack offset of" "$LOG" | sort -u >&2
+    exit 1
+fi
+
+echo "✅ 未发现栈帧超限告警"
@@ -2,8 +2,19 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { DatanexusEscrow } from "../target/types/datanexus_escrow";
 import { PublicKey, SystemProgram, Keypair } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID, createMint, createAccount, mintTo } from "@solana/spl-token";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  createAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
 import { assert } from "chai";
+import { AnchorEscrowClient, EscrowStatus, RawEscrowAccount } from "../lib/anchor-escrow-client";
+
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
 
 describe("datanexus-escrow", () => {
   // Configure the client to use the local cluster.
@@ -15,7 +26,8 @@ describe("datanexus-escrow", () => {
   let mint: PublicKey;
   let buyerTokenAccount: PublicKey;
   let providerTokenAccount: PublicKey;
-  let platformTokenAccount: PublicKey;
+  let treasuryTokenAccount: PublicKey;
+  let rewardsPoolTokenAccount: PublicKey;
   let escrowTokenAccount: PublicKey;
 
   const buyer = Keypair.generate();
@@ -25,6 +37,48 @@ describe("datanexus-escrow", () => {
   const requestId = "test-request-001";
   const proposalId = "test-proposal-001";
   const amount = new anchor.BN(1_000_000); // 1 USDC (6 decimals)
+  const deliveryHash = Array.from(Buffer.alloc(32, 7));
+
+  const escrowPda = () =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), buyer.publicKey.toBuffer(), Buffer.from(requestId)],
+      program.programId
+    )[0];
+
+  const fetchEscrow = async () =>
+    AnchorEscrowClient.decodeEscrow(
+      (await program.account.escrow.fetch(escrowPda())) as unknown as RawEscrowAccount
+    );
+
+  const configPda = () =>
+    PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId)[0];
+
+  const buyerStatsPda = () =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("buyer_stats"), buyer.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+  const providerStatsPda = () =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("provider_stats"), provider_user.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+  const providerEscrowIndexPda = (sequence: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("provider_escrow"),
+        provider_user.publicKey.toBuffer(),
+        new anchor.BN(sequence).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const [protocolStatsPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("protocol_stats")],
+    program.programId
+  );
 
   before(async () => {
     // Airdrop SOL to test accounts
@@ -59,13 +113,21 @@ describe("datanexus-escrow", () => {
       provider_user.publicKey
     );
 
-    platformTokenAccount = await createAccount(
+    treasuryTokenAccount = await createAccount(
       provider.connection,
       platform,
       mint,
       platform.publicKey
     );
 
+    rewardsPoolTokenAccount = await createAccount(
+      provider.connection,
+      platform,
+      mint,
+      platform.publicKey,
+      Keypair.generate()
+    );
+
     // Mint USDC to buyer
     await mintTo(
       provider.connection,
@@ -83,36 +145,60 @@ describe("datanexus-escrow", () => {
     console.log("  Platform:", platform.publicKey.toBase58());
   });
 
-  it("Creates an escrow", async () => {
-    const [escrowPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("escrow"),
-        buyer.publicKey.toBuffer(),
-        Buffer.from(requestId),
-      ],
-      program.programId
+  it("Initializes the config", async () => {
+    // Only the upgrade authority (the deploying wallet) may initialize the config
+    const [programDataPda] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_PROGRAM_ID
     );
 
-    // Create escrow token account
-    escrowTokenAccount = await createAccount(
-      provider.connection,
-      buyer,
-      mint,
-      escrowPda,
-      undefined
-    );
+    // 80% of the platform fee to the treasury, 20% to the rewards pool
+    await program.methods
+      .initializeConfig(8000, 2000)
+      .accountsPartial({
+        config: configPda(),
+        admin: provider.wallet.publicKey,
+        treasury: treasuryTokenAccount,
+        rewardsPool: rewardsPoolTokenAccount,
+        program: program.programId,
+        programData: programDataPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const configAccount = await program.account.config.fetch(configPda());
+    assert.equal(configAccount.treasuryBps, 8000);
+    assert.equal(configAccount.rewardsPoolBps, 2000);
+  });
 
+  it("Creates an escrow", async () => {
+    // Creation only records the terms; funds move in fundEscrow
     const tx = await program.methods
-      .createEscrow(amount, requestId, proposalId)
-      .accounts({
-        escrow: escrowPda,
+      .createEscrow(amount, requestId, proposalId, null)
+      .accountsPartial({
+        escrow: escrowPda(),
         buyer: buyer.publicKey,
+        config: configPda(),
+        buyerStats: buyerStatsPda(),
         provider: provider_user.publicKey,
-        platform: platform.publicKey,
-        buyerTokenAccount: buyerTokenAccount,
-        escrowTokenAccount: escrowTokenAccount,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        providerStats: providerStatsPda(),
+        providerEscrowIndex: providerEscrowIndexPda(0),
+        protocolStats: protocolStatsPda,
+        providerProfile: null,
+        request: null,
+        proposal: null,
+        restriction: null,
+        platform: provider.wallet.publicKey,
         systemProgram: SystemProgram.programId,
+        coupon: null,
+        discount: null,
+        couponRedeemer: null,
+        couponsProgram: null,
+        accessMint: null,
+        buyerAttestation: null,
+        providerAttestation: null,
+        providerBadge: null,
+        mint,
       })
       .signers([buyer])
       .rpc();
@@ -120,29 +206,82 @@ describe("datanexus-escrow", () => {
     console.log("Escrow created:", tx);
 
     // Verify escrow account
-    const escrowAccount = await program.account.escrow.fetch(escrowPda);
+    const escrowAccount = await fetchEscrow();
     assert.equal(escrowAccount.buyer.toBase58(), buyer.publicKey.toBase58());
     assert.equal(escrowAccount.provider.toBase58(), provider_user.publicKey.toBase58());
     assert.equal(escrowAccount.amount.toString(), amount.toString());
     assert.equal(escrowAccount.requestId, requestId);
     assert.equal(escrowAccount.proposalId, proposalId);
+    assert.equal(escrowAccount.mint?.toBase58(), mint.toBase58());
+
+    const buyerStats = await program.account.buyerStats.fetch(buyerStatsPda());
+    assert.equal(buyerStats.totalEscrows.toNumber(), 1);
+
+    const providerStats = await program.account.providerStats.fetch(providerStatsPda());
+    assert.equal(providerStats.totalEscrows.toNumber(), 1);
+
+    const index = await program.account.providerEscrowIndex.fetch(providerEscrowIndexPda(0));
+    assert.equal(index.escrow.toBase58(), escrowPda().toBase58());
+
+    const protocolStats = await program.account.protocolStats.fetch(protocolStatsPda);
+    assert.equal(protocolStats.totalEscrows.toNumber(), 1);
   });
 
-  it("Marks data as delivered", async () => {
-    const [escrowPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("escrow"),
-        buyer.publicKey.toBuffer(),
-        Buffer.from(requestId),
-      ],
-      program.programId
-    );
+  it("Funds the escrow", async () => {
+    // The escrow PDA is off-curve, so its associated token account needs allowOwnerOffCurve
+    escrowTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        buyer,
+        mint,
+        escrowPda(),
+        true
+      )
+    ).address;
+
+    const tx = await program.methods
+      .fundEscrow()
+      .accountsPartial({
+        escrow: escrowPda(),
+        buyer: buyer.publicKey,
+        buyerStats: buyerStatsPda(),
+        protocolStats: protocolStatsPda,
+        buyerTokenAccount: buyerTokenAccount,
+        escrowTokenAccount: escrowTokenAccount,
+        mint: mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([buyer])
+      .rpc();
 
+    console.log("Escrow funded:", tx);
+
+    const escrowBalance = await provider.connection.getTokenAccountBalance(escrowTokenAccount);
+    assert.equal(escrowBalance.value.amount, amount.toString());
+
+    const escrowAccount = await fetchEscrow();
+    assert.equal(escrowAccount.status, EscrowStatus.Funded);
+
+    const buyerStats = await program.account.buyerStats.fetch(buyerStatsPda());
+    assert.equal(buyerStats.totalVolume.toString(), amount.toString());
+
+    const protocolStats = await program.account.protocolStats.fetch(protocolStatsPda);
+    assert.equal(protocolStats.totalVolume.toString(), amount.toString());
+  });
+
+  it("Marks data as delivered", async () => {
+    // No chunk manifest: the delivery is disputed as a whole
     const tx = await program.methods
-      .markDelivered()
-      .accounts({
-        escrow: escrowPda,
+      .markDelivered(deliveryHash, null)
+      .accountsPartial({
+        escrow: escrowPda(),
         provider: provider_user.publicKey,
+        providerStats: providerStatsPda(),
+        config: configPda(),
+        providerBond: null,
+        instructionsSysvar: null,
+        chunkManifest: null,
+        systemProgram: SystemProgram.programId,
       })
       .signers([provider_user])
       .rpc();
@@ -150,29 +289,62 @@ describe("datanexus-escrow", () => {
     console.log("Data marked as delivered:", tx);
 
     // Verify status
-    const escrowAccount = await program.account.escrow.fetch(escrowPda);
-    assert.equal(escrowAccount.status.delivered !== undefined, true);
+    const escrowAccount = await fetchEscrow();
+    assert.equal(escrowAccount.status, EscrowStatus.Delivered);
+    assert.deepEqual(escrowAccount.deliveryHash, deliveryHash);
+
+    const providerStats = await program.account.providerStats.fetch(providerStatsPda());
+    assert.equal(providerStats.delivered.toNumber(), 1);
   });
 
   it("Confirms and releases funds", async () => {
-    const [escrowPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("escrow"),
-        buyer.publicKey.toBuffer(),
-        Buffer.from(requestId),
-      ],
-      program.programId
-    );
-
+    // No rating, tip or settlement swap; optional receipt and access accounts are omitted
     const tx = await program.methods
-      .confirmAndRelease()
-      .accounts({
-        escrow: escrowPda,
+      .confirmAndRelease(null, null, null)
+      .accountsPartial({
+        escrow: escrowPda(),
         buyer: buyer.publicKey,
+        buyerStats: buyerStatsPda(),
+        providerStats: providerStatsPda(),
+        config: configPda(),
+        protocolStats: protocolStatsPda,
         escrowTokenAccount: escrowTokenAccount,
         providerTokenAccount: providerTokenAccount,
-        platformTokenAccount: platformTokenAccount,
+        buyerTokenAccount: buyerTokenAccount,
+        treasuryTokenAccount: treasuryTokenAccount,
+        rewardsPoolTokenAccount: rewardsPoolTokenAccount,
+        insuranceVault: null,
+        feeLedger: null,
+        feeVault: null,
+        priceUpdate: null,
+        providerSettleTokenAccount: null,
+        jupiterProgram: null,
+        mint: mint,
         tokenProgram: TOKEN_PROGRAM_ID,
+        memoProgram: null,
+        buyerAttestation: null,
+        providerAttestation: null,
+        receiptRecord: null,
+        receiptMint: null,
+        buyerReceiptAccount: null,
+        receiptMetadata: null,
+        receiptMasterEdition: null,
+        tokenMetadataProgram: null,
+        associatedTokenProgram: null,
+        systemProgram: null,
+        rent: null,
+        receiptTreeConfig: null,
+        receiptMerkleTree: null,
+        bubblegumProgram: null,
+        logWrapper: null,
+        compressionProgram: null,
+        accessMint: null,
+        buyerAccessAccount: null,
+        accessAuthority: null,
+        accessTokenProgram: null,
+        rating: null,
+        providerReputation: null,
+        reputationProgram: null,
       })
       .signers([buyer])
       .rpc();
@@ -180,19 +352,28 @@ describe("datanexus-escrow", () => {
     console.log("Funds released:", tx);
 
     // Verify status
-    const escrowAccount = await program.account.escrow.fetch(escrowPda);
-    assert.equal(escrowAccount.status.completed !== undefined, true);
+    const escrowAccount = await fetchEscrow();
+    assert.equal(escrowAccount.status, EscrowStatus.Completed);
 
     // Verify token balances
     const providerBalance = await provider.connection.getTokenAccountBalance(providerTokenAccount);
-    const platformBalance = await provider.connection.getTokenAccountBalance(platformTokenAccount);
+    const treasuryBalance = await provider.connection.getTokenAccountBalance(treasuryTokenAccount);
+    const rewardsPoolBalance = await provider.connection.getTokenAccountBalance(rewardsPoolTokenAccount);
 
     console.log("Provider received:", providerBalance.value.uiAmount, "USDC");
-    console.log("Platform received:", platformBalance.value.uiAmount, "USDC");
+    console.log("Treasury received:", treasuryBalance.value.uiAmount, "USDC");
+    console.log("Rewards pool received:", rewardsPoolBalance.value.uiAmount, "USDC");
 
-    // 95% to provider, 5% to platform
+    // 95% to provider, 5% platform fee split 80/20 between treasury and rewards pool
     assert.equal(providerBalance.value.amount, "950000"); // 0.95 USDC
-    assert.equal(platformBalance.value.amount, "50000");  // 0.05 USDC
+    assert.equal(treasuryBalance.value.amount, "40000");  // 0.04 USDC
+    assert.equal(rewardsPoolBalance.value.amount, "10000"); // 0.01 USDC
+
+    const providerStats = await program.account.providerStats.fetch(providerStatsPda());
+    assert.equal(providerStats.completedSales.toNumber(), 1);
+    assert.equal(providerStats.salesVolume.toString(), "950000");
+
+    const protocolStats = await program.account.protocolStats.fetch(protocolStatsPda);
+    assert.equal(protocolStats.totalFees.toString(), "50000");
   });
 });
-